@@ -0,0 +1,35 @@
+import init, { WasmBrain } from "../../pkg/eywa.js";
+
+const config = {
+    sensors: ["light"],
+    actuators: ["motor"],
+    reflexes: [
+        { sensor: "light", actuator: "motor", synapse_type: "Excitatory", strength: 20 },
+    ],
+    num_plastic: 125,
+    nearby_count: 27,
+};
+
+async function main() {
+    await init();
+
+    const brain = new WasmBrain(JSON.stringify(config));
+    const light = document.getElementById("light");
+
+    function frame() {
+        brain.set_sensor("light", parseFloat(light.value));
+
+        for (let i = 0; i < 10; i++) {
+            brain.step();
+        }
+
+        document.getElementById("cycle").textContent = brain.cycle_count();
+        document.getElementById("motor").textContent = brain.actuator("motor").toFixed(4);
+
+        requestAnimationFrame(frame);
+    }
+
+    requestAnimationFrame(frame);
+}
+
+main();
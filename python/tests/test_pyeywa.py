@@ -0,0 +1,51 @@
+# Smoke test of the python bindings.  From the root of the repo:
+#
+#     pip install maturin pytest
+#     maturin develop --cargo-extra-args="--no-default-features --features python,pyo3/extension-module"
+#     pytest python/tests
+
+import json
+
+import pytest
+
+import pyeywa
+
+CONFIG = {
+    "sensors": ["light"],
+    "actuators": ["motor"],
+    "reflexes": [
+        {"sensor": "light", "actuator": "motor", "synapse_type": "Excitatory", "strength": 20.0},
+    ],
+    "seed": 7,
+}
+
+
+def test_step_drives_actuator():
+    brain = pyeywa.Brain(CONFIG)
+    brain.set_sensor("light", 1.0)
+    brain.step(200)
+
+    assert brain.actuator("motor") > 0.0
+    assert brain.metrics()["cycle"] == 200
+
+
+def test_unknown_names_raise():
+    brain = pyeywa.Brain(CONFIG)
+
+    with pytest.raises(KeyError):
+        brain.set_sensor("sound", 0.5)
+
+    with pytest.raises(KeyError):
+        brain.actuator("wheel")
+
+
+def test_snapshot_round_trip():
+    brain = pyeywa.Brain(CONFIG)
+    brain.step(50)
+    snapshot = brain.snapshot_bytes()
+
+    brain.step(50)
+    brain.restore_bytes(snapshot)
+
+    assert brain.metrics()["cycle"] == 100
+    assert isinstance(json.loads(brain.connectome_json()), list)
@@ -0,0 +1,6 @@
+#!/bin/sh
+# Makes sure the library still compiles for the browser.
+# Needs the target: rustup target add wasm32-unknown-unknown
+set -e
+
+cargo check --lib --target wasm32-unknown-unknown --no-default-features --features wasm
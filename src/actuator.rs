@@ -1,11 +1,110 @@
+use std::borrow::Borrow;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
 /// All actuators controlled by the encephalon
 /// must implement this trait
 pub trait Actuator {
-    /// Set the value of this actuator
-    fn set_control_value(&self, value: f32);
+    /// Set the value of this actuator.  An actuator that fails
+    /// (say its serial port hiccups) returns an error, which the
+    /// encephalon counts against it
+    fn set_control_value(&self, value: f32) -> Result<(), ActuatorError>;
 
     /// Gets the unique name of this actuator
     /// This is used to identify this actuator and
     /// form reflexes upon instantiation of the encephalon
     fn get_name(&self) -> String;
 }
+
+/// The unique name of an actuator, which is what reflexes,
+/// layouts, and the encephalon's by-name lookups refer to
+/// the actuator by
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ActuatorName(String);
+
+impl ActuatorName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for ActuatorName {
+    fn from(name: &str) -> ActuatorName {
+        ActuatorName(name.to_string())
+    }
+}
+
+impl From<String> for ActuatorName {
+    fn from(name: String) -> ActuatorName {
+        ActuatorName(name)
+    }
+}
+
+impl FromStr for ActuatorName {
+    type Err = Infallible;
+
+    fn from_str(name: &str) -> Result<ActuatorName, Infallible> {
+        Ok(ActuatorName::from(name))
+    }
+}
+
+impl Borrow<str> for ActuatorName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ActuatorName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The error returned by an actuator that failed to
+/// take on a control value
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActuatorError {
+    message: String,
+}
+
+impl ActuatorError {
+    pub fn new(message: String) -> ActuatorError {
+        ActuatorError { message }
+    }
+}
+
+impl fmt::Display for ActuatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Actuator failed: {}", self.message)
+    }
+}
+
+impl Error for ActuatorError {}
+
+/// Adapts an infallible closure into an actuator, so
+/// simple actuators don't have to deal with errors
+pub struct FnActuator<F: Fn(f32)> {
+    name: String,
+    f: F,
+}
+
+impl<F: Fn(f32)> FnActuator<F> {
+    pub fn new(name: String, f: F) -> FnActuator<F> {
+        FnActuator { name, f }
+    }
+}
+
+impl<F: Fn(f32)> Actuator for FnActuator<F> {
+    fn set_control_value(&self, value: f32) -> Result<(), ActuatorError> {
+        (self.f)(value);
+        Ok(())
+    }
+
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+}
@@ -0,0 +1,292 @@
+//! Tools for understanding how an encephalon changed over time.
+//!
+//! Diffing a snapshot taken before training against one taken after
+//! shows which synapses training formed, pruned, and reshaped:
+//!
+//! ```
+//! use eywa::analysis::diff_snapshots;
+//! use eywa::Encephalon;
+//!
+//! fn train(encephalon: &Encephalon) {
+//!     let before = encephalon.snapshot();
+//!     encephalon.run_n_cycles(200);
+//!
+//!     println!("{}", diff_snapshots(&before, &encephalon.snapshot()).summary());
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::neuron::synapse::{SynapseRecord, SynapticType};
+use crate::snapshot::EncephalonSnapshot;
+
+/// The smallest change in strength diff_snapshots reports
+pub const DEFAULT_STRENGTH_THRESHOLD: f32 = 0.01;
+
+/// Identifies a synapse across snapshots.  Plastic and static
+/// synapses are kept apart, so a reflex never matches a plastic
+/// synapse that happens to join the same neurons
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SynapseKey {
+    pub source: Vec<i32>,
+    pub target: Vec<i32>,
+    pub synaptic_type: SynapticType,
+    pub plastic: bool,
+}
+
+impl SynapseKey {
+    fn of(record: &SynapseRecord) -> SynapseKey {
+        SynapseKey {
+            source: record.source.clone(),
+            target: record.target.clone(),
+            synaptic_type: record.synaptic_type,
+            plastic: record.plastic,
+        }
+    }
+}
+
+/// A synapse present in both snapshots whose strength changed
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrengthChange {
+    pub key: SynapseKey,
+    pub before: f32,
+    pub after: f32,
+}
+
+/// The change in EMA of the neuron at loc
+#[derive(Clone, Debug, PartialEq)]
+pub struct EmaDelta {
+    pub loc: Vec<i32>,
+    pub before: f32,
+    pub after: f32,
+}
+
+impl EmaDelta {
+    pub fn delta(&self) -> f32 {
+        self.after - self.before
+    }
+}
+
+/// The structural changes between two snapshots of an encephalon
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConnectomeDiff {
+    /// Synapses present in the later snapshot but not the earlier
+    pub formed: Vec<SynapseRecord>,
+
+    /// Synapses present in the earlier snapshot but not the later
+    pub pruned: Vec<SynapseRecord>,
+
+    /// Synapses whose strength changed by more than the threshold
+    pub changed: Vec<StrengthChange>,
+
+    /// The EMA of every neuron found in both snapshots
+    pub ema_deltas: Vec<EmaDelta>,
+}
+
+/// Counts of each kind of change within a ConnectomeDiff
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DiffSummary {
+    pub formed: usize,
+    pub pruned: usize,
+    pub changed: usize,
+    pub neurons: usize,
+}
+
+impl fmt::Display for DiffSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "formed: {}, pruned: {}, changed: {}, neurons: {}",
+            self.formed, self.pruned, self.changed, self.neurons
+        )
+    }
+}
+
+impl ConnectomeDiff {
+    pub fn summary(&self) -> DiffSummary {
+        DiffSummary {
+            formed: self.formed.len(),
+            pruned: self.pruned.len(),
+            changed: self.changed.len(),
+            neurons: self.ema_deltas.len(),
+        }
+    }
+
+    /// Writes every synapse change as csv, with a header row.  Formed
+    /// synapses have no strength before and pruned synapses have no
+    /// strength after.  Locations are written with their coordinates
+    /// separated by spaces
+    pub fn write_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writeln!(
+            writer,
+            "change,source,target,synaptic_type,plastic,before,after"
+        )?;
+
+        for record in &self.formed {
+            let key = SynapseKey::of(record);
+            write_row(&mut writer, "formed", &key, None, Some(record.strength))?;
+        }
+
+        for record in &self.pruned {
+            let key = SynapseKey::of(record);
+            write_row(&mut writer, "pruned", &key, Some(record.strength), None)?;
+        }
+
+        for change in &self.changed {
+            write_row(
+                &mut writer,
+                "changed",
+                &change.key,
+                Some(change.before),
+                Some(change.after),
+            )?;
+        }
+
+        writer.flush()
+    }
+}
+
+fn write_row(
+    writer: &mut impl Write,
+    change: &str,
+    key: &SynapseKey,
+    before: Option<f32>,
+    after: Option<f32>,
+) -> io::Result<()> {
+    let strength = |strength: Option<f32>| strength.map_or(String::new(), |s| s.to_string());
+
+    writeln!(
+        writer,
+        "{},{},{},{:?},{},{},{}",
+        change,
+        loc_field(&key.source),
+        loc_field(&key.target),
+        key.synaptic_type,
+        key.plastic,
+        strength(before),
+        strength(after)
+    )
+}
+
+fn loc_field(loc: &[i32]) -> String {
+    loc.iter()
+        .map(|coord| coord.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Diffs snapshot a against the later snapshot b, reporting
+/// strength changes of more than DEFAULT_STRENGTH_THRESHOLD
+pub fn diff_snapshots(a: &EncephalonSnapshot, b: &EncephalonSnapshot) -> ConnectomeDiff {
+    diff_snapshots_with(a, b, DEFAULT_STRENGTH_THRESHOLD)
+}
+
+/// Diffs snapshot a against the later snapshot b, reporting
+/// strength changes of more than threshold.  Synapses sharing a
+/// key are matched up in the order they were recorded, so if a
+/// key's count changes only the extras are formed or pruned
+pub fn diff_snapshots_with(
+    a: &EncephalonSnapshot,
+    b: &EncephalonSnapshot,
+    threshold: f32,
+) -> ConnectomeDiff {
+    let mut diff = ConnectomeDiff::default();
+    let mut before: HashMap<SynapseKey, Vec<&SynapseRecord>> = HashMap::new();
+
+    for record in &a.synapses {
+        before
+            .entry(SynapseKey::of(record))
+            .or_default()
+            .push(record);
+    }
+
+    // Pop matches off the front, so records pair up in order
+    for records in before.values_mut() {
+        records.reverse();
+    }
+
+    for record in &b.synapses {
+        let key = SynapseKey::of(record);
+
+        match before.get_mut(&key).and_then(Vec::pop) {
+            Some(old) => {
+                if (record.strength - old.strength).abs() > threshold {
+                    diff.changed.push(StrengthChange {
+                        key,
+                        before: old.strength,
+                        after: record.strength,
+                    });
+                }
+            }
+            None => diff.formed.push(record.clone()),
+        }
+    }
+
+    // Whatever wasn't matched is gone from b
+    for record in &a.synapses {
+        let key = SynapseKey::of(record);
+
+        if let Some(records) = before.get_mut(&key) {
+            if let Some(position) = records.iter().position(|old| std::ptr::eq(*old, record)) {
+                records.remove(position);
+                diff.pruned.push(record.clone());
+            }
+        }
+    }
+
+    let emas_before: HashMap<&Vec<i32>, f32> = a
+        .neurons
+        .iter()
+        .map(|neuron| (&neuron.loc, neuron.ema))
+        .collect();
+
+    for neuron in &b.neurons {
+        if let Some(&ema) = emas_before.get(&neuron.loc) {
+            diff.ema_deltas.push(EmaDelta {
+                loc: neuron.loc.clone(),
+                before: ema,
+                after: neuron.ema,
+            });
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecp_geometry::{BoxEcp, EcpGeometry};
+    use crate::encephalon::EncephalonBuilder;
+    use crate::test_util::{actuator, sensor};
+
+    #[test]
+    fn diff_shows_synapses_formed_by_an_active_sensor() {
+        let encephalon = EncephalonBuilder::new()
+            .max_plastic_synapses(26)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 1.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let before = encephalon.snapshot();
+        encephalon.run_n_cycles(200);
+        let after = encephalon.snapshot();
+
+        let diff = diff_snapshots(&before, &after);
+        let light_loc = Vec::from(encephalon.sensor_loc(&"light".into()).unwrap());
+
+        assert!(diff
+            .formed
+            .iter()
+            .any(|synapse| synapse.source == light_loc));
+    }
+}
@@ -0,0 +1,165 @@
+//! Charge conservation auditing, for tracking down impulse
+//! accounting bugs.  With the audit feature on, every synapse
+//! that fires and every charge buffer that's deposited into or
+//! drained reports into a per-cycle ledger, and the encephalon
+//! checks at the end of each cycle that every impulse sent
+//! towards a neuron actually landed in one of its charge slots
+//! (or on its charge floor).  With the feature off, none of
+//! this is compiled at all.
+//!
+//! A synapse that fires into nothing is caught by the check:
+//!
+//! ```should_panic
+//! use eywa::audit;
+//!
+//! audit::record(|ledger| ledger.emitted += 1.0);
+//! audit::check(1);
+//! ```
+
+use std::cell::Cell;
+
+/// The relative tolerance of the balance, as a fraction
+/// of all the charge that moved during the cycle
+const TOLERANCE: f64 = 1e-4;
+
+/// The charge that moved through the encephalon during a cycle.
+/// Totals are kept in f64 so that summing thousands of f32
+/// impulses doesn't drift on its own
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ChargeLedger {
+    /// Impulse sent by every synapse that fired
+    pub emitted: f64,
+
+    /// Charge added (or removed) by the input gains of the
+    /// neurons the impulses were sent to
+    pub gained: f64,
+
+    /// Charge injected into neurons from outside the encephalon
+    pub injected: f64,
+
+    /// Charge actually added to the charge slots of neurons
+    pub deposited: f64,
+
+    /// Charge that would have been deposited, but was clamped
+    /// away by the charge floor of a neuron
+    pub floored: f64,
+
+    /// Inhibitory impulse (hence negative) held apart from the
+    /// charge of neurons under shunting inhibition
+    pub shunted: f64,
+
+    /// Charge taken out of slots by neurons evaluating their charge
+    pub consumed: f64,
+}
+
+impl ChargeLedger {
+    /// The charge that was sent towards neurons but never
+    /// landed in a slot (or, if negative, that landed in a
+    /// slot without having been sent)
+    pub fn imbalance(&self) -> f64 {
+        self.emitted + self.gained + self.injected - self.deposited - self.floored - self.shunted
+    }
+
+    /// True if the imbalance is within float tolerance
+    /// of the charge that moved during the cycle
+    pub fn is_balanced(&self) -> bool {
+        let moved = self.emitted.abs()
+            + self.gained.abs()
+            + self.injected.abs()
+            + self.deposited.abs()
+            + self.floored.abs()
+            + self.shunted.abs();
+
+        self.imbalance().abs() <= TOLERANCE * moved.max(1.)
+    }
+}
+
+thread_local! {
+    static LEDGER: Cell<ChargeLedger> = Cell::new(ChargeLedger::default());
+}
+
+/// Records charge moving into the current cycle's ledger
+pub fn record(f: impl FnOnce(&mut ChargeLedger)) {
+    LEDGER.with(|ledger| {
+        let mut current = ledger.get();
+        f(&mut current);
+        ledger.set(current);
+    });
+}
+
+/// Takes the current ledger, starting a fresh one
+pub fn take() -> ChargeLedger {
+    LEDGER.with(|ledger| ledger.replace(ChargeLedger::default()))
+}
+
+/// Takes the ledger of cycle, panicking if charge
+/// appeared or vanished during the cycle
+pub fn check(cycle: u64) -> ChargeLedger {
+    let ledger = take();
+
+    assert!(
+        ledger.is_balanced(),
+        "Charge wasn't conserved on cycle {}: {} charge went missing ({:?})",
+        cycle,
+        ledger.imbalance(),
+        ledger
+    );
+
+    ledger
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charge_that_lands_where_it_was_sent_is_balanced() {
+        let ledger = ChargeLedger {
+            emitted: 10.,
+            gained: -2.,
+            injected: 5.,
+            deposited: 11.,
+            floored: 1.,
+            shunted: 1.,
+            consumed: 20.,
+        };
+
+        assert_eq!(ledger.imbalance(), 0.);
+        assert!(ledger.is_balanced());
+    }
+
+    #[test]
+    fn imbalances_within_float_tolerance_are_forgiven() {
+        let ledger = ChargeLedger {
+            emitted: 1e6,
+            deposited: 1e6 - 1.,
+            ..ChargeLedger::default()
+        };
+
+        assert!(ledger.is_balanced());
+
+        let ledger = ChargeLedger {
+            emitted: 1e6,
+            deposited: 1e6 - 1e3,
+            ..ChargeLedger::default()
+        };
+
+        assert!(!ledger.is_balanced());
+    }
+
+    #[test]
+    fn taking_the_ledger_starts_a_fresh_one() {
+        record(|ledger| ledger.injected += 3.);
+        record(|ledger| ledger.deposited += 3.);
+
+        assert_eq!(check(1).injected, 3.);
+        assert_eq!(take(), ChargeLedger::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "Charge wasn't conserved on cycle 7")]
+    fn charge_deposited_from_nowhere_fails_the_check() {
+        record(|ledger| ledger.deposited += 1.);
+        check(7);
+    }
+}
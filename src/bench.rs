@@ -0,0 +1,568 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::actuator::{Actuator, ActuatorName, FnActuator};
+use crate::clock::{Clock, SystemClock};
+use crate::ecp_geometry::{BoxEcp, EcpGeometry};
+use crate::encephalon::{ClusterPlan, Encephalon, EncephalonBuilder, Reflex};
+use crate::neuron::synapse::{SynapseSpec, SynapticType};
+use crate::neuron::ActuatorParams;
+use crate::reward::TargetComparator;
+use crate::sensor::Sensor;
+
+/// Names and constant readings of the standard workload's sensors
+const SENSORS: [(&str, f32); 4] = [("1", 0.25), ("2", 0.5), ("3", 0.75), ("4", 1.0)];
+
+/// Names of the standard workload's actuators
+const ACTUATORS: [&str; 3] = ["yote", "yang", "yoder"];
+
+/// Each actuator of a brain along with the last value handed to it
+type Recordings = Vec<(ActuatorName, Rc<Cell<f32>>)>;
+
+/// Preset sizes of the standard workload's brain
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BrainSize {
+    /// 1,000 plastic neurons
+    Small,
+    /// 8,000 plastic neurons
+    Medium,
+    /// 64,000 plastic neurons
+    Large,
+}
+
+impl BrainSize {
+    /// The number of plastic neurons requested of the geometry
+    pub fn num_plastic(&self) -> u32 {
+        match self {
+            BrainSize::Small => 10_u32.pow(3),
+            BrainSize::Medium => 20_u32.pow(3),
+            BrainSize::Large => 40_u32.pow(3),
+        }
+    }
+}
+
+/// The results of running the standard workload
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchReport {
+    pub size: BrainSize,
+    pub cycles: u64,
+    pub seed: u64,
+
+    /// Time spent building the encephalon
+    pub build_time: Duration,
+
+    /// Time spent cycling, which excludes construction
+    pub wall_time: Duration,
+    pub cycles_per_sec: f64,
+
+    /// The most plastic synapses held at the end of any cycle
+    pub peak_plastic_synapses: u32,
+
+    /// The last control value (ie ema frequency)
+    /// handed to each actuator
+    pub actuator_emas: HashMap<ActuatorName, f32>,
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:?} brain, {} cycles (seed {})",
+            self.size, self.cycles, self.seed
+        )?;
+        writeln!(f, "build time: {:.3}s", self.build_time.as_secs_f64())?;
+        writeln!(
+            f,
+            "wall time: {:.3}s ({:.1} cycles/sec)",
+            self.wall_time.as_secs_f64(),
+            self.cycles_per_sec
+        )?;
+        write!(f, "peak plastic synapses: {}", self.peak_plastic_synapses)?;
+
+        for name in &ACTUATORS {
+            if let Some(ema) = self.actuator_emas.get(*name) {
+                write!(f, "\n{} ema: {}", name, ema)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds the canonical brain of the given size (a box geometry
+/// with four constant sensors, three recording actuators and
+/// fixed reflexes), runs it for the given number of cycles, and
+/// reports how it went.  The brain is seeded, so the actuator
+/// emas and peak synapse count of a given size, cycle count and
+/// seed should only change when the behavior of the encephalon
+/// changes.  They're pinned here, so a refactor that shifts them
+/// has to update these numbers on purpose:
+///
+/// ```
+/// use eywa::bench::{standard_workload, BrainSize};
+///
+/// let report = standard_workload(BrainSize::Small, 200, 42);
+///
+/// let golden = [("yote", 0.038_348_67), ("yang", 0.048_840_15), ("yoder", 0.089_783_66)];
+///
+/// assert_eq!(report.actuator_emas.len(), 3);
+///
+/// for (name, ema) in &golden {
+///     let actual = report.actuator_emas[*name];
+///     assert!((actual - ema).abs() < 1e-4, "{} ema {} drifted from {}", name, actual, ema);
+/// }
+///
+/// assert_eq!(report.peak_plastic_synapses, 70_181);
+/// assert!(report.cycles_per_sec > 0.);
+/// ```
+pub fn standard_workload(size: BrainSize, cycles: u64, seed: u64) -> BenchReport {
+    let clock = SystemClock::new();
+    let build_start = clock.now();
+
+    let (encephalon, recordings) = standard_brain(size, seed);
+
+    let build_time = clock.now() - build_start;
+
+    let mut peak_plastic_synapses = 0;
+    let start = clock.now();
+
+    for _ in 0..cycles {
+        let metrics = encephalon.run_cycle();
+        peak_plastic_synapses = peak_plastic_synapses.max(metrics.plastic_synapses);
+    }
+
+    let wall_time = clock.now() - start;
+
+    BenchReport {
+        size,
+        cycles,
+        seed,
+        build_time,
+        wall_time,
+        cycles_per_sec: cycles_per_sec(cycles, wall_time),
+        peak_plastic_synapses,
+        actuator_emas: recordings
+            .into_iter()
+            .map(|(name, recording)| (name, recording.get()))
+            .collect(),
+    }
+}
+
+/// The results of running the eval workload
+#[derive(Clone, Debug, PartialEq)]
+pub struct EvalReport {
+    pub size: BrainSize,
+    pub cycles: u64,
+    pub seed: u64,
+
+    /// Cycles per second with plasticity frozen, but
+    /// each neuron cycled and fired as usual
+    pub frozen_cycles_per_sec: f64,
+
+    /// Cycles per second in eval mode
+    pub eval_cycles_per_sec: f64,
+}
+
+impl EvalReport {
+    /// How many times faster eval mode ran than plain freezing
+    pub fn speedup(&self) -> f64 {
+        self.eval_cycles_per_sec / self.frozen_cycles_per_sec.max(f64::EPSILON)
+    }
+}
+
+impl fmt::Display for EvalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:?} brain, {} cycles (seed {}) after as many of training",
+            self.size, self.cycles, self.seed
+        )?;
+        writeln!(f, "frozen: {:.1} cycles/sec", self.frozen_cycles_per_sec)?;
+        writeln!(f, "eval mode: {:.1} cycles/sec", self.eval_cycles_per_sec)?;
+        write!(f, "speedup: {:.2}x", self.speedup())
+    }
+}
+
+/// Trains two copies of the canonical brain of the given size for
+/// the given number of cycles, then times as many more cycles of
+/// one with plasticity frozen against the other in eval mode
+pub fn eval_workload(size: BrainSize, cycles: u64, seed: u64) -> EvalReport {
+    let clock = SystemClock::new();
+
+    let time = |encephalon: &Encephalon| {
+        let start = clock.now();
+
+        for _ in 0..cycles {
+            encephalon.run_cycle();
+        }
+
+        cycles_per_sec(cycles, clock.now() - start)
+    };
+
+    let (frozen, _) = standard_brain(size, seed);
+    let (eval, _) = standard_brain(size, seed);
+
+    for encephalon in &[&frozen, &eval] {
+        for _ in 0..cycles {
+            encephalon.run_cycle();
+        }
+    }
+
+    frozen.freeze_plasticity(true);
+    eval.set_eval_mode(true);
+
+    EvalReport {
+        size,
+        cycles,
+        seed,
+        frozen_cycles_per_sec: time(&frozen),
+        eval_cycles_per_sec: time(&eval),
+    }
+}
+
+/// The results of building a bare box brain
+#[derive(Clone, Debug, PartialEq)]
+pub struct BuildReport {
+    pub side: u32,
+
+    /// The plastic neurons the brain was built with
+    pub neurons: u32,
+    pub build_time: Duration,
+}
+
+impl fmt::Display for BuildReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}x{}x{} box, {} plastic neurons",
+            self.side, self.side, self.side, self.neurons
+        )?;
+        write!(f, "build time: {:.3}s", self.build_time.as_secs_f64())
+    }
+}
+
+/// Times the construction of a box brain side neurons
+/// across, without any sensors or actuators, which is
+/// where construction spends nearly all of its time
+pub fn build_workload(side: u32) -> BuildReport {
+    let clock = SystemClock::new();
+    let start = clock.now();
+
+    let encephalon = EncephalonBuilder::new()
+        .build(Box::new(BoxEcp::new(side.pow(3), 0, 0, 27)), vec![], vec![])
+        .expect("a bare box is always a valid encephalon");
+
+    BuildReport {
+        side,
+        neurons: encephalon.size_report().actual_plastic,
+        build_time: clock.now() - start,
+    }
+}
+
+fn cycles_per_sec(cycles: u64, wall_time: Duration) -> f64 {
+    cycles as f64 / wall_time.as_secs_f64().max(f64::EPSILON)
+}
+
+/// Builds the canonical brain of the given size, along with
+/// the last value handed to each of its actuators
+fn standard_brain(size: BrainSize, seed: u64) -> (Rc<Encephalon>, Recordings) {
+    let sensors: Vec<Box<dyn Sensor>> = SENSORS
+        .iter()
+        .map(|(name, value)| {
+            Box::new(ConstantSensor {
+                name: name.to_string(),
+                value: *value,
+            }) as Box<dyn Sensor>
+        })
+        .collect();
+
+    let recordings: Recordings = ACTUATORS
+        .iter()
+        .map(|name| (ActuatorName::from(*name), Rc::new(Cell::new(0.0))))
+        .collect();
+
+    let actuators: Vec<Box<dyn Actuator>> = recordings
+        .iter()
+        .map(|(name, recording)| {
+            let recording = Rc::clone(recording);
+
+            Box::new(FnActuator::new(name.to_string(), move |value| {
+                recording.set(value)
+            })) as Box<dyn Actuator>
+        })
+        .collect();
+
+    let reflexes = vec![
+        Reflex::new("1".into(), "yote".into(), SynapticType::Excitatory, 20.),
+        Reflex::new("3".into(), "yang".into(), SynapticType::Excitatory, 20.),
+        Reflex::new("1".into(), "yoder".into(), SynapticType::Excitatory, 20.),
+        Reflex::new("2".into(), "yoder".into(), SynapticType::Excitatory, 20.),
+    ];
+
+    let ecp_geometry: Box<dyn EcpGeometry> = Box::new(BoxEcp::new(
+        size.num_plastic(),
+        SENSORS.len() as u32,
+        ACTUATORS.len() as u32,
+        125,
+    ));
+
+    let encephalon = EncephalonBuilder::new()
+        .seed(seed)
+        .reflexes(reflexes)
+        .build(ecp_geometry, sensors, actuators)
+        .expect("the standard workload is always a valid encephalon");
+
+    (encephalon, recordings)
+}
+
+/// Cycles each pattern of the association task is held before it swaps
+const PATTERN_LENGTH: u64 = 50;
+
+/// How far the mean ema of the rewarded actuator must exceed
+/// the other's over a pattern for the pattern to count as won
+/// (or fall short of it to count as lost)
+const ASSOCIATION_MARGIN: f32 = 0.05;
+
+/// Initial strengths of the association task's synapses onto the
+/// "left" actuator, which fires it, and onto "right", which doesn't
+const ASSOCIATION_STRENGTHS: (f32, f32) = (12., 8.);
+
+/// True if cycle of the association task presents pattern
+/// A (sensor "a" active, "b" silent), rather than pattern B
+fn presents_a(cycle: u64) -> bool {
+    ((cycle - 1) / PATTERN_LENGTH).is_multiple_of(2)
+}
+
+/// The results of running the association task
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AssociationReport {
+    pub cycles: u64,
+    pub seed: u64,
+
+    /// The score of the patterns of a run with plasticity frozen,
+    /// where a pattern the rewarded actuator won by the margin
+    /// scores 1, one it lost by the margin scores 0, and a draw 0.5
+    pub accuracy_untrained: f32,
+
+    /// The same score over the patterns within the first quarter
+    /// of a run that learns
+    pub accuracy_early: f32,
+
+    /// The same score over the patterns within its last quarter
+    pub accuracy_late: f32,
+}
+
+impl fmt::Display for AssociationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "association task, {} cycles (seed {})",
+            self.cycles, self.seed
+        )?;
+        writeln!(f, "untrained accuracy: {:.3}", self.accuracy_untrained)?;
+        writeln!(f, "early accuracy: {:.3}", self.accuracy_early)?;
+        write!(f, "late accuracy: {:.3}", self.accuracy_late)
+    }
+}
+
+/// Runs a minimal learning task: two sensors, "a" and "b", take turns
+/// being active every PATTERN_LENGTH cycles, while comparators reward
+/// actuator "left" being high under pattern A and "right" being high
+/// under pattern B.  Both sensors start out wired to fire "left" and
+/// not "right", so an untrained brain wins pattern A and loses pattern
+/// B, scoring 0.5.  Only reward modulated plasticity can move pattern
+/// B over to "right", as plain plasticity keeps strengthening the
+/// synapse that fires "left".  The plastic volume fires above the
+/// actuators' threshold, so its activity doesn't swamp the actuators.
+///
+/// Runs the task twice, once with plasticity frozen as the untrained
+/// baseline, and once learning, reporting how well the rewarded
+/// actuator did over the frozen run and over the first and last
+/// quarters of the learning one.  A change that breaks learning shows
+/// up as late accuracy no better than the baseline, while one that
+/// speeds it up shows up as early accuracy further above it.  The brain is seeded, so a
+/// report is reproducible:
+///
+/// ```
+/// use eywa::bench::association_task;
+///
+/// let report = association_task(0, 400);
+///
+/// assert_eq!(report.accuracy_untrained, 0.5);
+/// assert!((0.0..=1.0).contains(&report.accuracy_late));
+/// assert_eq!(report, association_task(0, 400));
+/// ```
+pub fn association_task(seed: u64, cycles: u64) -> AssociationReport {
+    let untrained = association_scores(seed, cycles, true);
+    let learned = association_scores(seed, cycles, false);
+
+    let quarter = (learned.len() / 4).max(1).min(learned.len());
+    let accuracy = |scores: &[f32]| match scores.is_empty() {
+        true => 0.0,
+        false => scores.iter().sum::<f32>() / scores.len() as f32,
+    };
+
+    AssociationReport {
+        cycles,
+        seed,
+        accuracy_untrained: accuracy(&untrained),
+        accuracy_early: accuracy(&learned[..quarter]),
+        accuracy_late: accuracy(&learned[learned.len() - quarter..]),
+    }
+}
+
+/// Runs the association task for cycles cycles, with its plasticity
+/// frozen or not, returning the score of each full pattern presented
+fn association_scores(seed: u64, cycles: u64, frozen: bool) -> Vec<f32> {
+    let pattern_a = Rc::new(Cell::new(true));
+
+    let sensors: Vec<Box<dyn Sensor>> = vec![
+        Box::new(PatternSensor {
+            name: "a".to_string(),
+            pattern_a: Rc::clone(&pattern_a),
+            active_on_a: true,
+        }),
+        Box::new(PatternSensor {
+            name: "b".to_string(),
+            pattern_a: Rc::clone(&pattern_a),
+            active_on_a: false,
+        }),
+    ];
+
+    let actuators: Vec<Box<dyn Actuator>> = ["left", "right"]
+        .iter()
+        .map(|name| Box::new(FnActuator::new(name.to_string(), |_| {})) as Box<dyn Actuator>)
+        .collect();
+
+    let builder = || {
+        let target = |on_a: bool| move |cycle| (presents_a(cycle) == on_a) as u8 as f32;
+
+        EncephalonBuilder::new()
+            .seed(seed)
+            .fire_threshold(15.)
+            .actuator_params(ActuatorParams {
+                alpha: 0.2,
+                ..ActuatorParams::default()
+            })
+            .reward_modulation(true)
+            .target_comparator(TargetComparator::new("left".into(), 0.5, target(true)))
+            .target_comparator(TargetComparator::new("right".into(), 0.5, target(false)))
+    };
+
+    let ecp_geometry = BoxEcp::new(125, 2, 2, 27);
+    let names = |names: &[&str]| {
+        names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>()
+    };
+    let plan = builder()
+        .plan(
+            &ecp_geometry,
+            &names(&["a", "b"]),
+            &names(&["left", "right"]),
+        )
+        .expect("the association task is always a valid encephalon");
+
+    let loc = |clusters: &[ClusterPlan], name: &str| {
+        clusters
+            .iter()
+            .find(|cluster| cluster.name.as_deref() == Some(name))
+            .map(|cluster| cluster.locs[0].clone())
+            .expect("the association task plans every sensor and actuator")
+    };
+
+    let (fires, doesnt_fire) = ASSOCIATION_STRENGTHS;
+    let wiring = [
+        ("a", "left", fires),
+        ("a", "right", doesnt_fire),
+        ("b", "left", fires),
+        ("b", "right", doesnt_fire),
+    ];
+
+    let synapses = wiring
+        .iter()
+        .map(|(sensor, actuator, strength)| SynapseSpec {
+            src_loc: loc(&plan.sensors, sensor),
+            dst_loc: loc(&plan.actuators, actuator),
+            synaptic_type: SynapticType::Excitatory,
+            initial_strength: *strength,
+            plastic: true,
+        })
+        .collect();
+
+    let encephalon = builder()
+        .initial_synapses(synapses)
+        .build(Box::new(ecp_geometry), sensors, actuators)
+        .expect("the association task is always a valid encephalon");
+
+    encephalon.freeze_plasticity(frozen);
+
+    let mut scores = Vec::new();
+    let (mut rewarded_total, mut other_total) = (0.0, 0.0);
+
+    for cycle in 1..=cycles {
+        pattern_a.set(presents_a(cycle));
+        encephalon.run_cycle();
+
+        let ema = |name: &str| encephalon.actuator_ema(name).unwrap_or(0.0);
+        let (rewarded, other) = match presents_a(cycle) {
+            true => (ema("left"), ema("right")),
+            false => (ema("right"), ema("left")),
+        };
+
+        rewarded_total += rewarded;
+        other_total += other;
+
+        if cycle % PATTERN_LENGTH == 0 {
+            let margin = ASSOCIATION_MARGIN * PATTERN_LENGTH as f32;
+
+            scores.push(match rewarded_total - other_total {
+                lead if lead > margin => 1.0,
+                lead if lead < -margin => 0.0,
+                _ => 0.5,
+            });
+            rewarded_total = 0.0;
+            other_total = 0.0;
+        }
+    }
+
+    scores
+}
+
+/// A sensor of the association task, which is fully
+/// active during one pattern and silent during the other
+struct PatternSensor {
+    name: String,
+    pattern_a: Rc<Cell<bool>>,
+    active_on_a: bool,
+}
+
+impl Sensor for PatternSensor {
+    fn measure(&mut self) -> f32 {
+        (self.pattern_a.get() == self.active_on_a) as u8 as f32
+    }
+
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// A sensor that always measures the same value
+struct ConstantSensor {
+    name: String,
+    value: f32,
+}
+
+impl Sensor for ConstantSensor {
+    fn measure(&mut self) -> f32 {
+        self.value
+    }
+
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+}
@@ -0,0 +1,71 @@
+use std::env;
+
+use std::process;
+use std::time::Duration;
+
+use eywa::bench::{association_task, build_workload, eval_workload, standard_workload, BrainSize};
+
+/// How long building a 50x50x50 brain may take in a release build
+const BUILD_TARGET: Duration = Duration::from_secs(1);
+
+/// Runs the standard workload, ie
+///
+/// cargo run --release --bin bench -- [small|medium|large] [cycles] [seed]
+///
+/// or the association task, ie
+///
+/// cargo run --release --bin bench -- association [cycles] [seed]
+///
+/// or, to compare eval mode against frozen plasticity
+/// on a trained medium (20x20x20) brain,
+///
+/// cargo run --release --bin bench -- eval [cycles] [seed]
+///
+/// or, to time building a bare 50x50x50 box of 125k neurons,
+/// failing if it takes longer than BUILD_TARGET,
+///
+/// cargo run --release --bin bench -- build
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    let cycles = args
+        .get(2)
+        .map(|cycles| cycles.parse().expect("cycles must be a whole number"))
+        .unwrap_or(1000);
+
+    let seed = args
+        .get(3)
+        .map(|seed| seed.parse().expect("seed must be a whole number"))
+        .unwrap_or(0);
+
+    let size = match args.get(1).map(String::as_str) {
+        None | Some("small") => BrainSize::Small,
+        Some("medium") => BrainSize::Medium,
+        Some("large") => BrainSize::Large,
+        Some("association") => {
+            println!("{}", association_task(seed, cycles));
+            return;
+        }
+        Some("eval") => {
+            println!("{}", eval_workload(BrainSize::Medium, cycles, seed));
+            return;
+        }
+        Some("build") => {
+            let report = build_workload(50);
+            println!("{}", report);
+
+            if report.build_time > BUILD_TARGET {
+                eprintln!(
+                    "took longer than the {:.1}s target",
+                    BUILD_TARGET.as_secs_f64()
+                );
+                process::exit(1);
+            }
+
+            return;
+        }
+        Some(other) => panic!("Unknown brain size: {}", other),
+    };
+
+    println!("{}", standard_workload(size, cycles, seed));
+}
@@ -0,0 +1,126 @@
+//! The brain of the hell-mazer server, apart from the server
+//! itself, so that its plan can be checked against the snapshot
+//! in tests/fixtures/hell_mazer_plan.json
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use eywa::prelude::*;
+
+// Encephalon Parameters
+const FIRE_THRESHOLD: f32 = 10.;
+const EMA_ALPHA: f32 = 2. / 100.;
+const SYNAPTIC_TYPE_THRESHOLD: f32 = 0.1;
+
+const NUM_PLASTIC: u32 = 27;
+
+// A neuron can't hold more plastic synapses than it has distinct
+// neighbors, which is every position of the nearby cube but its own
+const NEARBY_COUNT: u32 = 27;
+const MAX_PLASTIC_SYNAPSES: usize = NEARBY_COUNT as usize - 1;
+
+const SIGMOID_MAX_VAL: f32 = 15.0;
+const WEAKNESS_THRESHOLD: f32 = 1.0;
+const X_INCR: f32 = 0.1;
+
+// Sensors fire every cycle at 1.0 and every 20 cycles at 0.0
+const ENCODER_MIN_PERIOD: u32 = 1;
+const ENCODER_MAX_PERIOD: u32 = 20;
+
+fn encoder(input: f32) -> u32 {
+    sensory_encoders::linear_period_encoder(input, ENCODER_MIN_PERIOD, ENCODER_MAX_PERIOD)
+}
+
+const REFLEX_STRENGTH: f32 = 20.0;
+
+/// The sensors of the server, in the order they're handed to build
+pub const SENSOR_NAMES: [&str; 8] = [
+    "forward",
+    "forward_pain",
+    "left",
+    "left_pain",
+    "right",
+    "right_pain",
+    "back",
+    "back_pain",
+];
+
+/// The actuators of the server, in the order they're handed to build
+pub const ACTUATOR_NAMES: [&str; 4] = [
+    "left_forward",
+    "left_backward",
+    "right_forward",
+    "right_backward",
+];
+
+/// Polarity of the reflex from a side or back pain sensor onto a
+/// wheel actuator.  Each wheel is driven forward if it's on the
+/// side in pain (or back pain), and backward otherwise, so the
+/// robot turns (or drives) away from whatever hurt it
+fn pain_polarity(sensor: &str, actuator: &str) -> Option<SynapticType> {
+    let wheel_forward = match (sensor, actuator.starts_with("left")) {
+        ("left_pain", is_left) => is_left,
+        ("right_pain", is_left) => !is_left,
+        ("back_pain", _) => true,
+        _ => return None,
+    };
+
+    match actuator.ends_with("forward") == wheel_forward {
+        true => Some(SynapticType::Excitatory),
+        false => Some(SynapticType::Inhibitory),
+    }
+}
+
+pub fn ecp_geometry() -> BoxEcp {
+    BoxEcp::new(
+        NUM_PLASTIC,
+        SENSOR_NAMES.len() as u32,
+        ACTUATOR_NAMES.len() as u32,
+        NEARBY_COUNT,
+    )
+}
+
+/// Forward pain reverses both wheels, while pain on any
+/// other side drives each wheel away from that side
+fn reflexes() -> Vec<Reflex> {
+    ReflexTemplate::crossed_pair(
+        "forward_pain".into(),
+        ("left_forward".into(), "left_backward".into()),
+        REFLEX_STRENGTH,
+    )
+    .and(ReflexTemplate::crossed_pair(
+        "forward_pain".into(),
+        ("right_forward".into(), "right_backward".into()),
+        REFLEX_STRENGTH,
+    ))
+    .and(ReflexTemplate::all_pairs(
+        &["left_pain".into(), "right_pain".into(), "back_pain".into()],
+        &[
+            "left_forward".into(),
+            "left_backward".into(),
+            "right_forward".into(),
+            "right_backward".into(),
+        ],
+        pain_polarity,
+        REFLEX_STRENGTH,
+    ))
+    .expand()
+}
+
+/// Everything about the brain but how its build is reported
+pub fn builder() -> EncephalonBuilder {
+    EncephalonBuilder::new()
+        .fire_threshold(FIRE_THRESHOLD)
+        .ema_alpha(EMA_ALPHA)
+        .synaptic_strength_generator(Rc::new(|| {
+            Box::new(RefCell::new(SigmoidStrength::new(
+                SIGMOID_MAX_VAL,
+                WEAKNESS_THRESHOLD,
+                X_INCR,
+            )))
+        }))
+        .synapse_type_threshold(SYNAPTIC_TYPE_THRESHOLD)
+        .max_plastic_synapses(MAX_PLASTIC_SYNAPSES)
+        .sensory_encoder(encoder)
+        .reflexes(reflexes())
+}
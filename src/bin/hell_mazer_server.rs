@@ -1,249 +1,114 @@
 use std::boxed::Box;
-use std::cell::RefCell;
-use std::rc::Rc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
 
 use serde::{Deserialize, Serialize};
-use tokio::sync::{mpsc, mpsc::error::TrySendError, watch};
+use tokio::sync::watch;
 use warp::Filter;
 
-use eywa::{
-    ecp_geometry::{BoxEcp, EcpGeometry},
-    encephalon::{Encephalon, Reflex},
-    neuron::synapse::{synaptic_strength::SigmoidStrength, SynapticType},
-    neuron_interfaces::sensory_encoders,
-    Actuator, Sensor,
-};
+use eywa::prelude::*;
 
-// Encephalon Parameters
-const FIRE_THRESHOLD: f32 = 10.;
-const EMA_ALPHA: f32 = 2. / 100.;
-const SYNAPTIC_TYPE_THRESHOLD: f32 = 0.1;
-const MAX_PLASTIC_SYNAPSES: usize = 64;
+#[path = "hell_mazer/config.rs"]
+mod config;
 
-const SIGMOID_MAX_VAL: f32 = 15.0;
-const WEAKNESS_THRESHOLD: f32 = 1.0;
-const X_INCR: f32 = 0.1;
+use config::{ACTUATOR_NAMES, SENSOR_NAMES};
 
-const ENCODER_Y_INTERCEPT: f32 = 20.0;
+// Log the build of the brain every 10% of each phase
+const BUILD_PROGRESS_GRANULARITY: f32 = 0.1;
 
-fn encoder(input: f32) -> u32 {
-    sensory_encoders::linear_encoder(input, ENCODER_Y_INTERCEPT)
-}
+// The brain stalls after this many cycles without a request,
+// rather than learning from the same frozen sensor values
+const MAX_CYCLES_PER_SENSOR_UPDATE: u64 = 1000;
 
 #[tokio::main]
 async fn main() {
     // Initialize the sensors
-    let (forward_tx, forward_rx) = mpsc::channel::<f32>(10);
-    let forward_name: String = "forward".into();
-
-    let (forward_pain_tx, forward_pain_rx) = mpsc::channel::<f32>(10);
-    let forward_pain_name: String = "forward_pain".into();
-
-    let (left_tx, left_rx) = mpsc::channel::<f32>(10);
-    let left_name: String = "left".into();
-
-    let (left_pain_tx, left_pain_rx) = mpsc::channel::<f32>(10);
-    let left_pain_name: String = "left_pain".into();
-
-    let (right_tx, right_rx) = mpsc::channel::<f32>(10);
-    let right_name: String = "right".into();
-
-    let (right_pain_tx, right_pain_rx) = mpsc::channel::<f32>(10);
-    let right_pain_name: String = "right_pain".into();
-
-    let (back_tx, back_rx) = mpsc::channel::<f32>(10);
-    let back_name: String = "back".into();
-
-    let (back_pain_tx, back_pain_rx) = mpsc::channel::<f32>(10);
-    let back_pain_name: String = "back_pain".into();
+    let [forward_name, forward_pain_name, left_name, left_pain_name, right_name, right_pain_name, back_name, back_pain_name] =
+        SENSOR_NAMES;
+
+    let (forward_sensor, forward_handle) =
+        LatestValueSensor::watched(forward_name.to_string(), 0.0);
+    let (forward_pain_sensor, forward_pain_handle) =
+        LatestValueSensor::watched(forward_pain_name.to_string(), 0.0);
+    let (left_sensor, left_handle) = LatestValueSensor::watched(left_name.to_string(), 0.0);
+    let (left_pain_sensor, left_pain_handle) =
+        LatestValueSensor::watched(left_pain_name.to_string(), 0.0);
+    let (right_sensor, right_handle) = LatestValueSensor::watched(right_name.to_string(), 0.0);
+    let (right_pain_sensor, right_pain_handle) =
+        LatestValueSensor::watched(right_pain_name.to_string(), 0.0);
+    let (back_sensor, back_handle) = LatestValueSensor::watched(back_name.to_string(), 0.0);
+    let (back_pain_sensor, back_pain_handle) =
+        LatestValueSensor::watched(back_pain_name.to_string(), 0.0);
 
     // Initialize the actuators
+    let [left_forward_name, left_backward_name, right_forward_name, right_backward_name] =
+        ACTUATOR_NAMES;
 
     // lf -> Left Forward
     let (lf_tx, lf_rx) = watch::channel::<f32>(0.0);
-    let left_forward_name: String = "left_forward".into();
 
     // lb -> Left Backward
     let (lb_tx, lb_rx) = watch::channel::<f32>(0.0);
-    let left_backward_name: String = "left_backward".into();
 
     // rf -> Right Forward
     let (rf_tx, rf_rx) = watch::channel::<f32>(0.0);
-    let right_forward_name: String = "right_forward".into();
 
     // rb -> Right Backward
     let (rb_tx, rb_rx) = watch::channel::<f32>(0.0);
-    let right_backward_name: String = "right_backward".into();
-
-    // Initialize reflexes
 
     //Make ecp_geometry
-    let ecp_geometry = Box::new(BoxEcp::new(27, 8, 4, 27));
+    let ecp_geometry = Box::new(config::ecp_geometry());
+
+    // Notified on every request, so a stalled brain knows to resume
+    let request_notify = Arc::new(RequestNotify::default());
+    let brain_notify = Arc::clone(&request_notify);
 
-    tokio::spawn(async move {
+    // The brain isn't Send, so it's built and run on a thread of
+    // its own, which blocks while the brain is stalled
+    thread::spawn(move || {
         let sensors = vec![
-            Box::new(HttpReqSensor::new(forward_rx, forward_name.clone())) as Box<dyn Sensor>,
-            Box::new(HttpReqSensor::new(
-                forward_pain_rx,
-                forward_pain_name.clone(),
-            )),
-            Box::new(HttpReqSensor::new(left_rx, left_name.clone())),
-            Box::new(HttpReqSensor::new(left_pain_rx, left_pain_name.clone())),
-            Box::new(HttpReqSensor::new(right_rx, right_name.clone())),
-            Box::new(HttpReqSensor::new(right_pain_rx, right_pain_name.clone())),
-            Box::new(HttpReqSensor::new(back_rx, back_name.clone())),
-            Box::new(HttpReqSensor::new(back_pain_rx, back_pain_name.clone())),
+            Box::new(forward_sensor) as Box<dyn Sensor>,
+            Box::new(forward_pain_sensor),
+            Box::new(left_sensor),
+            Box::new(left_pain_sensor),
+            Box::new(right_sensor),
+            Box::new(right_pain_sensor),
+            Box::new(back_sensor),
+            Box::new(back_pain_sensor),
         ];
 
         let actuators = vec![
-            Box::new(HttpResActuator::new(lf_tx, left_forward_name.clone())) as Box<dyn Actuator>,
-            Box::new(HttpResActuator::new(lb_tx, left_backward_name.clone())),
-            Box::new(HttpResActuator::new(rf_tx, right_forward_name.clone())),
-            Box::new(HttpResActuator::new(rb_tx, right_backward_name.clone())),
+            Box::new(HttpResActuator::new(lf_tx, left_forward_name.into())) as Box<dyn Actuator>,
+            Box::new(HttpResActuator::new(lb_tx, left_backward_name.into())),
+            Box::new(HttpResActuator::new(rf_tx, right_forward_name.into())),
+            Box::new(HttpResActuator::new(rb_tx, right_backward_name.into())),
         ];
 
-        let reflexes = vec![
-            //Forward motion
-            // Reflex::new(
-            //
-            // )
-
-
-            //Forward Pain
-            Reflex::new(
-                forward_pain_name.clone(),
-                left_forward_name.clone(),
-                SynapticType::Inhibitory,
-                20.0,
-            ),
-            Reflex::new(
-                forward_pain_name.clone(),
-                left_backward_name.clone(),
-                SynapticType::Excitatory,
-                20.0,
-            ),
-            Reflex::new(
-                forward_pain_name.clone(),
-                right_forward_name.clone(),
-                SynapticType::Inhibitory,
-                20.0,
-            ),
-            Reflex::new(
-                forward_pain_name.clone(),
-                right_backward_name.clone(),
-                SynapticType::Excitatory,
-                20.0,
-            ),
-            //Left Pain
-            Reflex::new(
-                left_pain_name.clone(),
-                left_forward_name.clone(),
-                SynapticType::Excitatory,
-                20.0,
-            ),
-            Reflex::new(
-                left_pain_name.clone(),
-                left_backward_name.clone(),
-                SynapticType::Inhibitory,
-                20.0,
-            ),
-            Reflex::new(
-                left_pain_name.clone(),
-                right_forward_name.clone(),
-                SynapticType::Inhibitory,
-                20.0,
-            ),
-            Reflex::new(
-                left_pain_name.clone(),
-                right_backward_name.clone(),
-                SynapticType::Excitatory,
-                20.0,
-            ),
-            //Right Pain
-            Reflex::new(
-                right_pain_name.clone(),
-                left_forward_name.clone(),
-                SynapticType::Inhibitory,
-                20.0,
-            ),
-            Reflex::new(
-                right_pain_name.clone(),
-                left_backward_name.clone(),
-                SynapticType::Excitatory,
-                20.0,
-            ),
-            Reflex::new(
-                right_pain_name.clone(),
-                right_forward_name.clone(),
-                SynapticType::Excitatory,
-                20.0,
-            ),
-            Reflex::new(
-                right_pain_name.clone(),
-                right_backward_name.clone(),
-                SynapticType::Inhibitory,
-                20.0,
-            ),
-            //Back Pain
-            Reflex::new(
-                back_pain_name.clone(),
-                left_forward_name.clone(),
-                SynapticType::Excitatory,
-                20.0,
-            ),
-            Reflex::new(
-                back_pain_name.clone(),
-                left_backward_name.clone(),
-                SynapticType::Inhibitory,
-                20.0,
-            ),
-            Reflex::new(
-                back_pain_name.clone(),
-                right_forward_name.clone(),
-                SynapticType::Excitatory,
-                20.0,
-            ),
-            Reflex::new(
-                back_pain_name.clone(),
-                right_backward_name.clone(),
-                SynapticType::Inhibitory,
-                20.0,
-            ),
-        ];
+        let encephalon = config::builder()
+            .progress_granularity(BUILD_PROGRESS_GRANULARITY)
+            .progress(|phase, done, total| println!("Building {:?}: {}/{}", phase, done, total))
+            .build(ecp_geometry, sensors, actuators)
+            .unwrap_or_else(|e| panic!("{}", e));
 
-        let encephalon = Encephalon::new(
-            ecp_geometry,
-            sensors,
-            actuators,
-            FIRE_THRESHOLD,
-            EMA_ALPHA,
-            Rc::new(|| {
-                Box::new(RefCell::new(SigmoidStrength::new(
-                    SIGMOID_MAX_VAL,
-                    WEAKNESS_THRESHOLD,
-                    X_INCR,
-                )))
-            }),
-            SYNAPTIC_TYPE_THRESHOLD,
-            MAX_PLASTIC_SYNAPSES,
-            encoder,
-            reflexes,
-        );
+        encephalon.set_max_cycles_per_sensor_update(MAX_CYCLES_PER_SENSOR_UPDATE);
 
         loop {
-            encephalon.run_cycle();
+            if encephalon.run_cycle().stalled {
+                brain_notify.wait();
+            }
         }
     });
 
     let sensor_sender = SensorSender {
-        forward: forward_tx,
-        forward_pain: forward_pain_tx,
-        left: left_tx,
-        left_pain: left_pain_tx,
-        right: right_tx,
-        right_pain: right_pain_tx,
-        back: back_tx,
-        back_pain: back_pain_tx,
+        forward: forward_handle,
+        forward_pain: forward_pain_handle,
+        left: left_handle,
+        left_pain: left_pain_handle,
+        right: right_handle,
+        right_pain: right_pain_handle,
+        back: back_handle,
+        back_pain: back_pain_handle,
+        request_notify,
     };
 
     let actuator_watcher = ActuatorWatcher {
@@ -258,18 +123,14 @@ async fn main() {
         .and(warp::path("sensactio"))
         .and(warp::body::json())
         .map(move |sensory_inputs: HttpSensorBody| {
-            let mut sender = sensor_sender.clone();
-            let watcher = actuator_watcher.clone();
-
             // println!("Receieved: {:?}", sensory_inputs);
 
-            // Send in latest sensory inputs
-            if let Err(e) = sender.send_all(sensory_inputs) {
-                println!("Send error: {:?}", e);
-            }
+            // Send in latest sensory inputs, which replace any
+            // the brain hasn't gotten around to measuring yet
+            sensor_sender.send_all(sensory_inputs);
 
             // Respond with current actuator values
-            warp::reply::json(&watcher.get_actuator_values())
+            warp::reply::json(&actuator_watcher.get_actuator_values())
         });
 
     warp::serve(sensactio).run(([127, 0, 0, 1], 4200)).await;
@@ -295,29 +156,56 @@ struct HttpActuatorResponse {
     right_backward: f32,
 }
 
+/// Wakes the brain's thread on the next request.  A request that
+/// arrives while the brain is running is remembered, so the brain
+/// doesn't wait on it once it stalls
+#[derive(Default)]
+struct RequestNotify {
+    requested: Mutex<bool>,
+    condvar: Condvar,
+}
+
+impl RequestNotify {
+    fn notify(&self) {
+        *self.requested.lock().unwrap() = true;
+        self.condvar.notify_one();
+    }
+
+    fn wait(&self) {
+        let mut requested = self.requested.lock().unwrap();
+
+        while !*requested {
+            requested = self.condvar.wait(requested).unwrap();
+        }
+
+        *requested = false;
+    }
+}
+
 #[derive(Clone)]
 struct SensorSender {
-    forward: mpsc::Sender<f32>,
-    forward_pain: mpsc::Sender<f32>,
-    left: mpsc::Sender<f32>,
-    left_pain: mpsc::Sender<f32>,
-    right: mpsc::Sender<f32>,
-    right_pain: mpsc::Sender<f32>,
-    back: mpsc::Sender<f32>,
-    back_pain: mpsc::Sender<f32>,
+    forward: LatestValueHandle,
+    forward_pain: LatestValueHandle,
+    left: LatestValueHandle,
+    left_pain: LatestValueHandle,
+    right: LatestValueHandle,
+    right_pain: LatestValueHandle,
+    back: LatestValueHandle,
+    back_pain: LatestValueHandle,
+    request_notify: Arc<RequestNotify>,
 }
 
 impl SensorSender {
-    pub fn send_all(&mut self, input: HttpSensorBody) -> Result<(), TrySendError<f32>> {
-        self.forward.try_send(input.forward)?;
-        self.forward_pain.try_send(input.forward_pain)?;
-        self.left.try_send(input.left)?;
-        self.left_pain.try_send(input.left_pain)?;
-        self.right.try_send(input.right)?;
-        self.right_pain.try_send(input.right_pain)?;
-        self.back_pain.try_send(input.back_pain)?;
-        self.back.try_send(input.back)?;
-        Ok(())
+    pub fn send_all(&self, input: HttpSensorBody) {
+        self.forward.set(input.forward);
+        self.forward_pain.set(input.forward_pain);
+        self.left.set(input.left);
+        self.left_pain.set(input.left_pain);
+        self.right.set(input.right);
+        self.right_pain.set(input.right_pain);
+        self.back_pain.set(input.back_pain);
+        self.back.set(input.back);
+        self.request_notify.notify();
     }
 }
 
@@ -340,58 +228,25 @@ impl ActuatorWatcher {
     }
 }
 
-struct HttpReqSensor {
-    rx: mpsc::Receiver<f32>,
-    name: String,
-    cache: RefCell<Option<f32>>,
-}
-
-impl HttpReqSensor {
-    pub fn new(rx: mpsc::Receiver<f32>, name: String) -> HttpReqSensor {
-        HttpReqSensor {
-            rx,
-            name,
-            cache: RefCell::new(None),
-        }
-    }
-}
-
-impl Sensor for HttpReqSensor {
-    fn measure(&mut self) -> f32 {
-        if let Ok(measurement) = self.rx.try_recv() {
-            *self.cache.borrow_mut() = Some(measurement);
-            measurement
-        } else if let Some(cached_measurement) = *self.cache.borrow() {
-            cached_measurement
-        } else {
-            0.0
-        }
-    }
-
-    fn get_name(&self) -> String {
-        self.name.clone()
-    }
-}
-
 struct HttpResActuator {
     tx: watch::Sender<f32>,
-    name: String,
+    name: ActuatorName,
 }
 
 impl HttpResActuator {
-    pub fn new(tx: watch::Sender<f32>, name: String) -> HttpResActuator {
+    pub fn new(tx: watch::Sender<f32>, name: ActuatorName) -> HttpResActuator {
         HttpResActuator { tx, name }
     }
 }
 
 impl Actuator for HttpResActuator {
-    fn set_control_value(&self, value: f32) {
-        if let Err(e) = self.tx.broadcast(value) {
-            println!("Error sending actuator value: {:?}", e);
-        }
+    fn set_control_value(&self, value: f32) -> Result<(), ActuatorError> {
+        self.tx
+            .broadcast(value)
+            .map_err(|e| ActuatorError::new(format!("Error sending actuator value: {:?}", e)))
     }
 
     fn get_name(&self) -> String {
-        self.name.clone()
+        self.name.to_string()
     }
 }
@@ -1,121 +1,70 @@
 use std::boxed::Box;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
-use eywa::ecp_geometry::{BoxEcp, EcpGeometry};
-use eywa::encephalon::{Encephalon, Reflex};
-use eywa::neuron::synapse::synaptic_strength::SigmoidStrength;
-use eywa::neuron::synapse::SynapticType;
-use eywa::neuron_interfaces::sensory_encoders;
-use eywa::{Actuator, Sensor};
+use eywa::prelude::*;
+
+const CYCLES: u32 = 3000;
+const REPORT_INTERVAL: u32 = 100;
 
 fn encoder(input: f32) -> u32 {
-    sensory_encoders::linear_encoder(input, 1000.)
+    sensory_encoders::linear_period_encoder(input, 1, 1000)
 }
 
 fn main() {
     let sensor_names = ["1", "2", "3", "4"];
-
-    let mut sensors: Vec<Box<dyn Sensor>> = Vec::new();
-
-    for name in &sensor_names {
-        sensors.push(Box::new(ConstantSensor::new(0.5, name.parse().unwrap())));
-    }
-
     let actuator_names = ["yote", "yang", "yoder"];
 
-    let mut actuators: Vec<Box<dyn Actuator>> = Vec::new();
-
-    for name in &actuator_names {
-        actuators.push(Box::new(BasicActuator::new(name.parse().unwrap())));
-    }
+    let actuators: Vec<Box<dyn Actuator>> = actuator_names
+        .iter()
+        .map(|name| Box::new(FnActuator::new(name.to_string(), |_| {})) as Box<dyn Actuator>)
+        .collect();
 
     let reflexes = vec![
-        Reflex::new(
-            "1".parse().unwrap(),
-            "yote".parse().unwrap(),
-            SynapticType::Excitatory,
-            20.,
-        ),
-        Reflex::new(
-            "3".parse().unwrap(),
-            "yang".parse().unwrap(),
-            SynapticType::Excitatory,
-            20.,
-        ),
-        Reflex::new(
-            "1".parse().unwrap(),
-            "yoder".parse().unwrap(),
-            SynapticType::Excitatory,
-            20.,
-        ),
-        Reflex::new(
-            "2".parse().unwrap(),
-            "yoder".parse().unwrap(),
-            SynapticType::Excitatory,
-            20.,
-        ),
+        Reflex::new("1".into(), "yote".into(), SynapticType::Excitatory, 20.),
+        Reflex::new("3".into(), "yang".into(), SynapticType::Excitatory, 20.),
+        Reflex::new("1".into(), "yoder".into(), SynapticType::Excitatory, 20.),
+        Reflex::new("2".into(), "yoder".into(), SynapticType::Excitatory, 20.),
     ];
 
-    let ecp_g = Box::new(BoxEcp::new(10_u32.pow(3), 4, 3, 216));
-
-    let encephalon = Encephalon::new(
-        ecp_g,
-        sensors,
-        actuators,
-        10.,
-        2. / 101.,
-        Rc::new(|| Box::new(RefCell::new(SigmoidStrength::new(9., 1., 0.1)))),
-        0.1,
-        64,
-        encoder,
-        reflexes,
-    );
-
-    encephalon.run_n_cycles(3000);
-}
-
-struct ConstantSensor {
-    value: f32,
-    name: String,
-}
-
-impl ConstantSensor {
-    fn new(value: f32, name: String) -> ConstantSensor {
-        ConstantSensor { value, name }
-    }
-}
-
-impl Sensor for ConstantSensor {
-    fn measure(&mut self) -> f32 {
-        self.value
-    }
-
-    fn get_name(&self) -> String {
-        self.name.clone()
-    }
-}
-
-struct BasicActuator {
-    name: String,
-    value: RefCell<f32>,
-}
-
-impl BasicActuator {
-    fn new(name: String) -> BasicActuator {
-        BasicActuator {
-            name,
-            value: RefCell::new(0.0),
+    let encephalon = EncephalonBuilder::new()
+        .fire_threshold(10.)
+        .ema_alpha(ema_alpha_from_window(100))
+        .synaptic_strength_generator(Rc::new(|| {
+            Box::new(RefCell::new(SigmoidStrength::new(9., 1., 0.1)))
+        }))
+        .synapse_type_threshold(0.1)
+        .max_plastic_synapses(64)
+        .sensory_encoder(encoder)
+        .injected_sensors(sensor_names.iter().copied())
+        .reflexes(reflexes)
+        .build(
+            Box::new(BoxEcp::new(10_u32.pow(3), 4, 3, 216)),
+            Vec::new(),
+            actuators,
+        )
+        .unwrap();
+
+    // Every sensor holds at 0.5, except the 4th, which sweeps
+    // slowly back and forth as if driven by some simulation
+    let mut frame: HashMap<String, f32> = sensor_names
+        .iter()
+        .map(|name| (name.to_string(), 0.5))
+        .collect();
+
+    for cycle in 1..=CYCLES {
+        frame.insert("4".to_string(), 0.5 + 0.5 * (cycle as f32 / 200.).sin());
+        encephalon.set_sensor_values(&frame).unwrap();
+
+        let metrics = encephalon.run_cycle();
+
+        if cycle % REPORT_INTERVAL == 0 {
+            let mut values: Vec<(String, f32)> = encephalon.actuator_values().into_iter().collect();
+            values.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            println!("{}", metrics);
+            println!("  actuators: {:?}", values);
         }
     }
 }
-
-impl Actuator for BasicActuator {
-    fn set_control_value(&self, value: f32) {
-        *self.value.borrow_mut() = value;
-    }
-
-    fn get_name(&self) -> String {
-        self.name.clone()
-    }
-}
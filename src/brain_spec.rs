@@ -0,0 +1,411 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use serde::{Deserialize, Serialize};
+
+use crate::actuator::{Actuator, ActuatorName, FnActuator};
+use crate::ecp_geometry::{BoxEcp, EcpGeometry, GeometrySpec};
+use crate::encephalon::{Encephalon, EncephalonBuilder, Reflex};
+use crate::error::EywaError;
+use crate::metrics::CycleMetrics;
+use crate::neuron::synapse::SynapticType;
+use crate::sensor::adapters::InjectedSensor;
+use crate::sensor::{Sensor, SensorName};
+
+/// A plain data description of a brain, which is how
+/// bindings to other languages (js, python) describe
+/// the brain they want built
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrainSpec {
+    pub sensors: Vec<String>,
+    pub actuators: Vec<String>,
+
+    #[serde(default)]
+    pub reflexes: Vec<ReflexSpec>,
+
+    /// Number of plastic neurons requested of the box geometry
+    #[serde(default = "default_num_plastic")]
+    pub num_plastic: u32,
+
+    /// Number of neurons near enough to each
+    /// plastic neuron that it can synapse onto
+    #[serde(default = "default_nearby_count")]
+    pub nearby_count: u32,
+
+    /// The geometry of the brain, which takes the place of
+    /// num_plastic and nearby_count.  Without one, the brain
+    /// is a box geometry fit to the sensors and actuators
+    #[serde(default)]
+    pub geometry: Option<GeometrySpec>,
+
+    pub seed: Option<u64>,
+}
+
+/// A reflex of a BrainSpec
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReflexSpec {
+    pub sensor: String,
+    pub actuator: String,
+    pub synapse_type: SynapticType,
+    pub strength: f32,
+}
+
+impl From<ReflexSpec> for Reflex {
+    fn from(reflex: ReflexSpec) -> Reflex {
+        Reflex::new(
+            reflex.sensor.into(),
+            reflex.actuator.into(),
+            reflex.synapse_type,
+            reflex.strength,
+        )
+    }
+}
+
+fn default_num_plastic() -> u32 {
+    5_u32.pow(3)
+}
+
+fn default_nearby_count() -> u32 {
+    27
+}
+
+impl BrainSpec {
+    /// Parses a json encoded spec
+    pub fn from_json(json: &str) -> Result<BrainSpec, EywaError> {
+        serde_json::from_str(json).map_err(|e| EywaError::InvalidConfig(e.to_string()))
+    }
+}
+
+/// An encephalon built from a BrainSpec whose sensors and
+/// actuators are plain values.  Sensors measure whatever value
+/// was last set, and actuators record the last control value
+/// they were handed, so a binding never has to call back into
+/// its host language while a cycle is running
+pub struct SpecBrain {
+    encephalon: Rc<Encephalon>,
+    sensor_values: HashMap<SensorName, Rc<Cell<f32>>>,
+    actuator_values: HashMap<ActuatorName, Rc<Cell<f32>>>,
+    last_metrics: CycleMetrics,
+}
+
+impl SpecBrain {
+    /// Builds the brain described by spec
+    pub fn new(spec: BrainSpec) -> Result<SpecBrain, EywaError> {
+        SpecBrain::with_builder(spec, EncephalonBuilder::new())
+    }
+
+    /// Builds the brain described by spec with builder, so that
+    /// parameters a spec doesn't cover can be set beforehand
+    pub fn with_builder(
+        spec: BrainSpec,
+        builder: EncephalonBuilder,
+    ) -> Result<SpecBrain, EywaError> {
+        let actuator_values: HashMap<ActuatorName, Rc<Cell<f32>>> = spec
+            .actuators
+            .iter()
+            .map(|name| (ActuatorName::from(name.as_str()), Rc::new(Cell::new(0.0))))
+            .collect();
+
+        let injected: Vec<InjectedSensor> = spec
+            .sensors
+            .iter()
+            .map(|name| InjectedSensor::new(name.clone()))
+            .collect();
+
+        let sensor_values: HashMap<SensorName, Rc<Cell<f32>>> = injected
+            .iter()
+            .map(|sensor| (SensorName::from(sensor.get_name()), sensor.value()))
+            .collect();
+
+        let sensors: Vec<Box<dyn Sensor>> = injected
+            .into_iter()
+            .map(|sensor| Box::new(sensor) as Box<dyn Sensor>)
+            .collect();
+
+        let actuators: Vec<Box<dyn Actuator>> = spec
+            .actuators
+            .iter()
+            .map(|name| {
+                let value = Rc::clone(&actuator_values[name.as_str()]);
+
+                Box::new(FnActuator::new(name.clone(), move |control_value| {
+                    value.set(control_value)
+                })) as Box<dyn Actuator>
+            })
+            .collect();
+
+        let reflexes = spec.reflexes.into_iter().map(Reflex::from).collect();
+
+        let mut builder = builder.reflexes(reflexes).clamp_synapse_budget(true);
+
+        if let Some(seed) = spec.seed {
+            builder = builder.seed(seed);
+        }
+
+        let ecp_geometry: Box<dyn EcpGeometry> = match spec.geometry {
+            Some(geometry) => geometry.build()?,
+            None => Box::new(BoxEcp::new(
+                spec.num_plastic,
+                spec.sensors.len() as u32,
+                spec.actuators.len() as u32,
+                spec.nearby_count,
+            )),
+        };
+
+        let encephalon = builder.build(ecp_geometry, sensors, actuators)?;
+
+        Ok(SpecBrain {
+            encephalon,
+            sensor_values,
+            actuator_values,
+            last_metrics: CycleMetrics::default(),
+        })
+    }
+
+    /// Sets the value the named sensor measures from the
+    /// next cycle on.  Values are clamped into [0, 1]
+    pub fn set_sensor(&self, name: &str, value: f32) -> Result<(), EywaError> {
+        match self.sensor_values.get(name) {
+            Some(sensor_value) => {
+                sensor_value.set(value.clamp(0., 1.));
+                Ok(())
+            }
+            None => Err(EywaError::UnknownName(name.to_string())),
+        }
+    }
+
+    /// Runs n cycles of the encephalon
+    pub fn step(&mut self, n: u32) {
+        for _ in 0..n {
+            self.last_metrics = self.encephalon.run_cycle();
+        }
+    }
+
+    /// The last control value handed to the named actuator
+    pub fn actuator(&self, name: &str) -> Option<f32> {
+        self.actuator_values.get(name).map(|value| value.get())
+    }
+
+    /// Metrics of the most recent cycle
+    pub fn last_metrics(&self) -> &CycleMetrics {
+        &self.last_metrics
+    }
+
+    /// The encephalon driven by this brain
+    pub fn encephalon(&self) -> &Encephalon {
+        &self.encephalon
+    }
+}
+
+/// Something done to the brain of a SpecBrainHandle on its thread
+type Job = Box<dyn FnOnce(&mut SpecBrain) + Send>;
+
+/// A SpecBrain that can be driven from any thread.  The encephalon
+/// isn't Send, so the brain is built and run on a thread of its own,
+/// and everything done to it is sent over to that thread as a job.
+/// The thread stops once the handle is dropped
+pub struct SpecBrainHandle {
+    jobs: Sender<Job>,
+}
+
+impl SpecBrainHandle {
+    /// Builds the brain described by spec on a thread of its own
+    pub fn spawn(spec: BrainSpec) -> Result<SpecBrainHandle, EywaError> {
+        let (jobs, job_rx) = mpsc::channel::<Job>();
+        let (built_tx, built_rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut brain = match SpecBrain::new(spec) {
+                Ok(brain) => brain,
+                Err(e) => {
+                    let _ = built_tx.send(Err(e));
+                    return;
+                }
+            };
+
+            let _ = built_tx.send(Ok(()));
+
+            for job in job_rx {
+                job(&mut brain);
+            }
+        });
+
+        built_rx
+            .recv()
+            .expect("the brain's thread panicked while building it")?;
+
+        Ok(SpecBrainHandle { jobs })
+    }
+
+    /// Runs f on the brain's thread and waits for its result
+    pub fn with<R: Send + 'static>(
+        &self,
+        f: impl FnOnce(&mut SpecBrain) -> R + Send + 'static,
+    ) -> R {
+        let (result_tx, result_rx) = mpsc::channel();
+
+        self.jobs
+            .send(Box::new(move |brain| {
+                let _ = result_tx.send(f(brain));
+            }))
+            .expect("the brain's thread has stopped");
+
+        result_rx.recv().expect("the brain's thread panicked")
+    }
+}
+
+/// Flattens cycle metrics into key value pairs, which is how
+/// the bindings hand metrics over to their host language
+pub fn metric_entries(metrics: &CycleMetrics) -> Vec<(&'static str, f64)> {
+    let mut entries = vec![
+        ("cycle", metrics.cycle as f64),
+        ("stalled", metrics.stalled as u8 as f64),
+        ("sensors_sampled", metrics.sensors_sampled as f64),
+        ("sensory_count", metrics.sensory_count as f64),
+        ("sensory_fired", metrics.sensory_fired as f64),
+        ("rx_count", metrics.rx_count as f64),
+        ("rx_fired", metrics.rx_fired as f64),
+        ("mean_sensory_ema", metrics.mean_sensory_ema() as f64),
+        ("mean_rx_ema", metrics.mean_rx_ema() as f64),
+        ("charge_consumed", metrics.charge_consumed as f64),
+        ("mean_rx_adaptation", metrics.mean_rx_adaptation() as f64),
+        ("plastic_synapses", metrics.plastic_synapses as f64),
+        ("actuator_failures", metrics.actuator_failures as f64),
+        ("disabled_actuators", metrics.disabled_actuators as f64),
+        ("reward", metrics.reward as f64),
+    ];
+
+    if let Some(percentiles) = metrics.strength_percentiles {
+        entries.push(("strength_p10", percentiles.p10 as f64));
+        entries.push(("strength_p50", percentiles.p50 as f64));
+        entries.push(("strength_p90", percentiles.p90 as f64));
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::StrengthPercentiles;
+
+    const SPEC: &str = r#"{
+        "sensors": ["light"],
+        "actuators": ["motor"],
+        "reflexes": [
+            {"sensor": "light", "actuator": "motor", "synapse_type": "Excitatory", "strength": 20.0}
+        ],
+        "seed": 7
+    }"#;
+
+    #[test]
+    fn parses_a_spec_and_fills_in_defaults() {
+        let spec = BrainSpec::from_json(SPEC).unwrap();
+
+        assert_eq!(
+            spec,
+            BrainSpec {
+                sensors: vec!["light".to_string()],
+                actuators: vec!["motor".to_string()],
+                reflexes: vec![ReflexSpec {
+                    sensor: "light".to_string(),
+                    actuator: "motor".to_string(),
+                    synapse_type: SynapticType::Excitatory,
+                    strength: 20.,
+                }],
+                num_plastic: 125,
+                nearby_count: 27,
+                geometry: None,
+                seed: Some(7),
+            }
+        );
+    }
+
+    #[test]
+    fn malformed_specs_are_invalid_configs() {
+        let malformed = [
+            r#"{"sensors": ["light"], "actuators": ["motor"]"#,
+            r#"{"actuators": ["motor"]}"#,
+            r#"{"sensors": "light", "actuators": ["motor"]}"#,
+            r#"{"sensors": [], "actuators": [], "reflexes": [{"sensor": "light"}]}"#,
+        ];
+
+        for json in &malformed {
+            match BrainSpec::from_json(json) {
+                Err(EywaError::InvalidConfig(_)) => {}
+                other => panic!("{} parsed to {:?}", json, other),
+            }
+        }
+    }
+
+    #[test]
+    fn metric_entries_map_each_field() {
+        let metrics = CycleMetrics {
+            stalled: true,
+            sensory_count: 4,
+            sensory_fired: 1,
+            sensory_ema_total: 2.,
+            rx_count: 10,
+            rx_fired: 3,
+            rx_ema_total: 5.,
+            rx_adaptation_total: 1.,
+            plastic_synapses: 40,
+            reward: -0.5,
+            ..CycleMetrics::new(12)
+        };
+
+        let entries: HashMap<_, _> = metric_entries(&metrics).into_iter().collect();
+
+        assert_eq!(entries["cycle"], 12.);
+        assert_eq!(entries["stalled"], 1.);
+        assert_eq!(entries["sensory_fired"], 1.);
+        assert_eq!(entries["rx_fired"], 3.);
+        assert_eq!(entries["mean_sensory_ema"], 0.5);
+        assert_eq!(entries["mean_rx_ema"], 0.5);
+        assert_eq!(entries["mean_rx_adaptation"], 0.1f32 as f64);
+        assert_eq!(entries["plastic_synapses"], 40.);
+        assert_eq!(entries["reward"], -0.5);
+        assert!(!entries.contains_key("strength_p50"));
+
+        let detailed = CycleMetrics {
+            strength_percentiles: Some(StrengthPercentiles {
+                p10: 0.25,
+                p50: 0.5,
+                p90: 0.75,
+            }),
+            ..metrics
+        };
+
+        let entries: HashMap<_, _> = metric_entries(&detailed).into_iter().collect();
+
+        assert_eq!(entries["strength_p10"], 0.25);
+        assert_eq!(entries["strength_p50"], 0.5);
+        assert_eq!(entries["strength_p90"], 0.75);
+    }
+
+    #[test]
+    fn a_handle_drives_its_brain_from_another_thread() {
+        let handle = SpecBrainHandle::spawn(BrainSpec::from_json(SPEC).unwrap()).unwrap();
+
+        handle.with(|brain| brain.set_sensor("light", 1.)).unwrap();
+        handle.with(|brain| brain.step(200));
+
+        assert_eq!(handle.with(|brain| brain.last_metrics().cycle), 200);
+        assert!(handle.with(|brain| brain.actuator("motor")).unwrap() > 0.);
+        assert_eq!(
+            handle.with(|brain| brain.set_sensor("sound", 0.5)),
+            Err(EywaError::UnknownName("sound".to_string()))
+        );
+    }
+
+    #[test]
+    fn a_handle_reports_a_brain_that_cant_be_built() {
+        let mut spec = BrainSpec::from_json(SPEC).unwrap();
+        spec.reflexes[0].actuator = "wheel".to_string();
+
+        assert!(SpecBrainHandle::spawn(spec).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use std::cell::Cell;
+use std::time::Duration;
+
+use instant::Instant;
+
+/// The source of wall time for an encephalon.  Every read of the
+/// time within eywa goes through a clock, so that pacing and timing
+/// can be tested deterministically with a MockClock
+pub trait Clock {
+    /// The time elapsed since the clock was created
+    fn now(&self) -> Duration;
+
+    /// Waits for duration to pass
+    fn sleep(&self, duration: Duration);
+}
+
+/// The clock of the system, which is the default clock
+/// of every encephalon
+#[derive(Debug)]
+pub struct SystemClock {
+    origin: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+
+    /// Browsers can't block the thread, so this
+    /// returns right away when built for wasm
+    fn sleep(&self, duration: Duration) {
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::sleep(duration);
+
+        #[cfg(target_arch = "wasm32")]
+        let _ = duration;
+    }
+}
+
+/// A clock whose time only moves when it's told to.
+/// Sleeping advances the clock rather than waiting:
+///
+/// ```
+/// use std::time::Duration;
+/// use eywa::clock::{Clock, MockClock};
+///
+/// let clock = MockClock::new();
+///
+/// clock.advance(Duration::from_millis(5));
+/// clock.sleep(Duration::from_millis(10));
+///
+/// assert_eq!(clock.now(), Duration::from_millis(15));
+/// ```
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now: Cell<Duration>,
+}
+
+impl MockClock {
+    pub fn new() -> MockClock {
+        MockClock::default()
+    }
+
+    /// Moves the clock forward by duration
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
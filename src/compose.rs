@@ -0,0 +1,279 @@
+//! Composes encephalons into a hierarchy, in which the actuators
+//! of one brain (say a small planner) drive the sensors of another
+//! (say a larger motor brain), all within the same cycle loop
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::actuator::{Actuator, ActuatorError, ActuatorName};
+use crate::encephalon::Encephalon;
+use crate::error::EywaError;
+use crate::metrics::CycleMetrics;
+use crate::sensor::{Sensor, SensorName};
+
+/// The actuator end of a route, which holds the last control
+/// value it was handed until the stack passes it along
+pub struct RoutedActuator {
+    name: String,
+    pending: Rc<Cell<f32>>,
+}
+
+impl Actuator for RoutedActuator {
+    fn set_control_value(&self, value: f32) -> Result<(), ActuatorError> {
+        self.pending.set(value);
+        Ok(())
+    }
+
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// The sensor end of a route, which measures the
+/// last value the stack passed along to it
+pub struct RoutedSensor {
+    name: String,
+    current: Rc<Cell<f32>>,
+}
+
+impl Sensor for RoutedSensor {
+    fn measure(&mut self) -> f32 {
+        self.current.get()
+    }
+
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// An actuator of one brain routed into a sensor of another
+struct Route {
+    from: usize,
+    actuator_name: ActuatorName,
+    to: usize,
+    sensor_name: SensorName,
+    pending: Rc<Cell<f32>>,
+    current: Rc<Cell<f32>>,
+}
+
+/// A stack of encephalons, with actuators of some routed into
+/// sensors of others.  Brains are numbered in the order they're
+/// pushed, and each cycle of the stack runs every brain once, in
+/// topological order of the routes, passing each brain's routed
+/// actuator values along as soon as it has run.  So a value reaches
+/// the brain downstream of it on the same cycle.
+///
+/// The routes may form a loop, in which case the brains of the loop
+/// run in the order they were pushed.  A route back to a brain that
+/// has already run on a cycle is only measured on the next cycle,
+/// so each loop carries a delay of one cycle.
+///
+/// Here a planner drives the goal sensor of a motor brain:
+///
+/// ```
+/// use eywa::compose::BrainStack;
+/// use eywa::prelude::*;
+///
+/// fn planned(
+///     light: Box<dyn Sensor>,
+///     wheel: Box<dyn Actuator>,
+/// ) -> Result<BrainStack, EywaError> {
+///     let mut stack = BrainStack::new();
+///     let (plan, goal) = stack.route((0, "plan"), (1, "goal"));
+///
+///     let planner = EncephalonBuilder::new().build(
+///         Box::new(BoxEcp::new(125, 1, 1, 27)),
+///         vec![light],
+///         vec![Box::new(plan)],
+///     )?;
+///     let motor = EncephalonBuilder::new().build(
+///         Box::new(BoxEcp::new(125, 1, 1, 27)),
+///         vec![Box::new(goal)],
+///         vec![wheel],
+///     )?;
+///
+///     stack.push(planner)?;
+///     stack.push(motor)?;
+///
+///     Ok(stack)
+/// }
+/// ```
+#[derive(Default)]
+pub struct BrainStack {
+    brains: Vec<Rc<Encephalon>>,
+    routes: Vec<Route>,
+    order: Vec<usize>, //Indices of the brains in the order they're run
+}
+
+impl BrainStack {
+    pub fn new() -> BrainStack {
+        BrainStack::default()
+    }
+
+    /// Routes the actuator of brain from into the sensor of brain to,
+    /// where brains are numbered in the order they're pushed.  Returns
+    /// the actuator and sensor to build those brains with.  Errors at
+    /// push if a brain doesn't have the actuator or sensor named here
+    pub fn route(
+        &mut self,
+        (from, actuator_name): (usize, &str),
+        (to, sensor_name): (usize, &str),
+    ) -> (RoutedActuator, RoutedSensor) {
+        let pending = Rc::new(Cell::new(0.0));
+        let current = Rc::new(Cell::new(0.0));
+
+        self.routes.push(Route {
+            from,
+            actuator_name: ActuatorName::from(actuator_name),
+            to,
+            sensor_name: SensorName::from(sensor_name),
+            pending: Rc::clone(&pending),
+            current: Rc::clone(&current),
+        });
+        self.order = self.topological_order();
+
+        (
+            RoutedActuator {
+                name: actuator_name.to_string(),
+                pending,
+            },
+            RoutedSensor {
+                name: sensor_name.to_string(),
+                current,
+            },
+        )
+    }
+
+    /// Adds a brain to the stack, returning its number.  Errors if
+    /// a route names an actuator or sensor the brain doesn't have
+    pub fn push(&mut self, encephalon: Rc<Encephalon>) -> Result<usize, EywaError> {
+        let index = self.brains.len();
+
+        for route in &self.routes {
+            if route.from == index && encephalon.actuator_loc(&route.actuator_name).is_none() {
+                return Err(EywaError::UnknownName(route.actuator_name.to_string()));
+            } else if route.to == index && encephalon.sensor_loc(&route.sensor_name).is_none() {
+                return Err(EywaError::UnknownName(route.sensor_name.to_string()));
+            }
+        }
+
+        self.brains.push(encephalon);
+        self.order = self.topological_order();
+
+        Ok(index)
+    }
+
+    /// Gets the brain numbered index
+    pub fn brain(&self, index: usize) -> Option<&Encephalon> {
+        self.brains.get(index).map(|brain| brain.as_ref())
+    }
+
+    /// Every brain of the stack, by number
+    pub fn brains(&self) -> &[Rc<Encephalon>] {
+        &self.brains
+    }
+
+    /// Runs one cycle of every brain, passing routed values along,
+    /// and returns the metrics of each brain's cycle by number
+    pub fn run_cycle(&self) -> Vec<CycleMetrics> {
+        let mut metrics = vec![CycleMetrics::default(); self.brains.len()];
+
+        for &index in &self.order {
+            metrics[index] = self.brains[index].run_cycle();
+
+            for route in self.routes.iter().filter(|route| route.from == index) {
+                route.current.set(route.pending.get());
+            }
+        }
+
+        metrics
+    }
+
+    /// Orders the brains so that each runs after every brain routed
+    /// into it, breaking loops (and ties) by the order they were pushed
+    fn topological_order(&self) -> Vec<usize> {
+        let n = self.brains.len();
+        let edges: Vec<(usize, usize)> = self
+            .routes
+            .iter()
+            .filter(|route| route.from < n && route.to < n && route.from != route.to)
+            .map(|route| (route.from, route.to))
+            .collect();
+
+        let mut order = Vec::with_capacity(n);
+        let mut placed = vec![false; n];
+
+        while order.len() < n {
+            let ready = (0..n).find(|&index| {
+                !placed[index] && edges.iter().all(|&(from, to)| to != index || placed[from])
+            });
+
+            // Within a loop no brain is ever ready, so the
+            // first brain left unplaced runs next
+            let next = ready.unwrap_or_else(|| (0..n).find(|&index| !placed[index]).unwrap());
+
+            placed[next] = true;
+            order.push(next);
+        }
+
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecp_geometry::{BoxEcp, EcpGeometry};
+    use crate::encephalon::{EncephalonBuilder, Reflex};
+    use crate::probe::ProbeReading;
+    use crate::test_util::{actuator, reflex, sensor};
+
+    /// The period of the motor brain's goal sensor after 300 cycles
+    /// of a planner with reflexes driving it
+    fn goal_period(reflexes: Vec<Reflex>) -> u32 {
+        let mut stack = BrainStack::new();
+        let (plan, goal) = stack.route((0, "plan"), (1, "goal"));
+
+        let planner = EncephalonBuilder::new()
+            .seed(1)
+            .reflexes(reflexes)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 1.0)],
+                vec![Box::new(plan)],
+            )
+            .unwrap();
+
+        let motor = EncephalonBuilder::new()
+            .seed(2)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![Box::new(goal)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        stack.push(planner).unwrap();
+        stack.push(motor).unwrap();
+
+        let motor = stack.brain(1).unwrap();
+        let goal_loc = motor.sensor_loc(&"goal".into()).unwrap();
+        let probe = motor.add_probe("goal", goal_loc.into()).unwrap();
+
+        for _ in 0..300 {
+            stack.run_cycle();
+        }
+
+        let trace = stack.brain(1).unwrap().probe_trace(probe).unwrap();
+
+        match trace.last().unwrap().reading {
+            ProbeReading::Period(period) => period,
+            ProbeReading::Charge(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn a_planner_reflex_shortens_the_goal_period() {
+        assert!(goal_period(vec![reflex("light", "plan", 20.)]) < goal_period(vec![]));
+    }
+}
@@ -1,5 +1,67 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::EywaError;
+use crate::loc::{Loc, MAX_DIMS};
 use crate::neuron::RxNeuron;
-use rand::Rng;
+use rand::{Rng, RngCore};
+
+/// Summarizes the number of each type of neuron (and the nearby
+/// count) requested of a geometry against the number it actually
+/// holds, since geometries round the requested values to fit
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeReport {
+    pub requested_plastic: u32,
+    pub actual_plastic: u32,
+    pub requested_nearby: u32,
+    pub actual_nearby: u32,
+    pub requested_sensory: u32,
+    pub actual_sensory: u32,
+    pub requested_actuator: u32,
+    pub actual_actuator: u32,
+
+    /// The (requested, actual) number of plastic synapses each
+    /// neuron may hold, if the budget was clamped to fit within
+    /// the neighborhood of the geometry
+    pub synapse_budget_clamp: Option<(usize, usize)>,
+}
+
+impl SizeReport {
+    /// True if the geometry holds exactly what was requested
+    pub fn is_exact(&self) -> bool {
+        self.requested_plastic == self.actual_plastic
+            && self.requested_nearby == self.actual_nearby
+            && self.requested_sensory == self.actual_sensory
+            && self.requested_actuator == self.actual_actuator
+            && self.synapse_budget_clamp.is_none()
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "plastic: {} (requested {}), nearby: {} (requested {}), \
+             sensory: {} (requested {}), actuator: {} (requested {})",
+            self.actual_plastic,
+            self.requested_plastic,
+            self.actual_nearby,
+            self.requested_nearby,
+            self.actual_sensory,
+            self.requested_sensory,
+            self.actual_actuator,
+            self.requested_actuator
+        )?;
+
+        if let Some((requested, actual)) = self.synapse_budget_clamp {
+            write!(f, ", synapse budget: {} (requested {})", actual, requested)?;
+        }
+
+        Ok(())
+    }
+}
 
 /// Here ECP stands for "Encephalon".
 /// Trait objects of this type correspond to
@@ -37,42 +99,331 @@ pub trait EcpGeometry {
     fn get_num_actuator(&self) -> u32;
     fn get_num_sensory(&self) -> u32;
 
-    /// Here "loc" is short for "location," which is represented
-    /// by a vector of integers. These methods return the position
-    /// hash (and neuron type located at the returned location of
-    /// the method for the rx methods) of either the first neuron
-    /// specified by this geometry or the next neuron in the geometry
+    /// The number of coordinates of every location of the geometry
+    fn dims(&self) -> usize;
+
+    /// These methods return the location and position hash (and
+    /// neuron type located at the returned location for the rx
+    /// methods) of either the first neuron specified by this
+    /// geometry or the next neuron in the geometry
     ///
     /// The next_ methods will return None if they are the last
-    /// of this type of neuron within the structure
-    fn first_rx_loc(&self) -> (Vec<i32>, String, RxNeuron);
-    fn next_rx_loc(&self, curr_loc: Vec<i32>) -> Option<(Vec<i32>, String, RxNeuron)>;
-    fn first_sensory_loc(&self) -> (Vec<i32>, String);
-    fn next_sensory_loc(&self, curr_loc: Vec<i32>) -> Option<(Vec<i32>, String)>;
+    /// of this type of neuron within the structure.  A geometry may
+    /// have no sensory positions at all, in which case first_sensory
+    /// is never called (see sensory_locs)
+    fn first_rx(&self) -> (Loc, String, RxNeuron);
+    fn next_rx(&self, curr_loc: Loc) -> Option<(Loc, String, RxNeuron)>;
+    fn first_sensory(&self) -> (Loc, String);
+    fn next_sensory(&self, curr_loc: Loc) -> Option<(Loc, String)>;
+
+    #[deprecated(note = "use first_rx, which returns a Loc")]
+    fn first_rx_loc(&self) -> (Vec<i32>, String, RxNeuron) {
+        let (loc, hash, rx_neuron) = self.first_rx();
+
+        (loc.into(), hash, rx_neuron)
+    }
+
+    #[deprecated(note = "use next_rx, which returns a Loc")]
+    fn next_rx_loc(&self, curr_loc: &[i32]) -> Option<(Vec<i32>, String, RxNeuron)> {
+        let (loc, hash, rx_neuron) = self.next_rx(Loc::checked(curr_loc)?)?;
+
+        Some((loc.into(), hash, rx_neuron))
+    }
+
+    #[deprecated(note = "use first_sensory, which returns a Loc")]
+    fn first_sensory_loc(&self) -> (Vec<i32>, String) {
+        let (loc, hash) = self.first_sensory();
+
+        (loc.into(), hash)
+    }
+
+    #[deprecated(note = "use next_sensory, which returns a Loc")]
+    fn next_sensory_loc(&self, curr_loc: &[i32]) -> Option<(Vec<i32>, String)> {
+        let (loc, hash) = self.next_sensory(Loc::checked(curr_loc)?)?;
+
+        Some((loc.into(), hash))
+    }
+
+    /// Sensory and actuator neurons each sit on a 2D plane of
+    /// the geometry.  These methods map an (x, y) coordinate on
+    /// that plane to the location of the neuron positioned there,
+    /// returning None if there's no sensory (or actuator) position
+    /// at that coordinate
+    fn sensory_plane_at(&self, x: u32, y: u32) -> Option<Loc>;
+    fn actuator_plane_at(&self, x: u32, y: u32) -> Option<Loc>;
+
+    #[deprecated(note = "use sensory_plane_at, which returns a Loc")]
+    fn sensory_plane_loc(&self, x: u32, y: u32) -> Option<Vec<i32>> {
+        self.sensory_plane_at(x, y).map(Vec::from)
+    }
+
+    #[deprecated(note = "use actuator_plane_at, which returns a Loc")]
+    fn actuator_plane_loc(&self, x: u32, y: u32) -> Option<Vec<i32>> {
+        self.actuator_plane_at(x, y).map(Vec::from)
+    }
+
+    /// The (width, height) of the sensory and actuator planes.
+    /// Every (x, y) coordinate with a position lies within these bounds
+    fn sensory_plane_dims(&self) -> (u32, u32);
+    fn actuator_plane_dims(&self) -> (u32, u32);
+
+    /// Moves the actuator neurons to the positions of spread.  Only
+    /// Packed is supported by default, which leaves them where they are
+    fn set_actuator_spread(&mut self, spread: ActuatorSpread) -> Result<(), EywaError> {
+        match spread {
+            ActuatorSpread::Packed => Ok(()),
+            _ => Err(EywaError::ActuatorSpreadUnsupported),
+        }
+    }
+
+    /// The (number of slices, rows, columns) of the geometry's
+    /// rx neurons sliced perpendicular to axis, and the (slice, row,
+    /// column) of an rx location within those slices.  Geometries
+    /// that don't lie on a grid can't be sliced, so by default
+    /// these return None
+    fn slice_shape(&self, _axis: usize) -> Option<(u32, u32, u32)> {
+        None
+    }
+
+    fn slice_index_of(&self, _loc: &[i32], _axis: usize) -> Option<(u32, u32, u32)> {
+        None
+    }
 
     /// Returns the unique hash that corresponds to each location.
     /// This is used by the encephalon to access different neurons
-    fn loc_hash(&self, loc: &Vec<i32>) -> String;
+    fn loc_hash(&self, loc: &[i32]) -> String;
+
+    /// Returns every rx location within radius of loc (not including
+    /// loc itself).  What "within radius" means depends on the geometry
+    fn neighbor_locs(&self, loc: Loc, radius: u32) -> Vec<Loc>;
+
+    #[deprecated(note = "use neighbor_locs, which returns Locs")]
+    fn neighbors(&self, loc: &[i32], radius: u32) -> Vec<Vec<i32>> {
+        match Loc::checked(loc) {
+            Some(loc) => self
+                .neighbor_locs(loc, radius)
+                .into_iter()
+                .map(Vec::from)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
 
     /// Returns a random location with the set of locations that
     /// are considered "nearby" loc.  This is crucial to plasticity
-    /// and synapse formation
-    fn local_random_hash(&self, loc: &Vec<i32>) -> Option<String>;
+    /// and synapse formation.  All randomness is drawn from rng
+    fn local_random_hash(&self, loc: &[i32], rng: &mut dyn RngCore) -> Option<String>;
+
+    /// The radius passed to neighbors that covers every
+    /// location local_random_hash might pick
+    fn nearby_radius(&self) -> u32;
+
+    /// The most distinct locations local_random_hash can pick for
+    /// any one loc, which bounds how many plastic synapses a neuron
+    /// can hold without doubling up on a target
+    fn max_neighbors(&self) -> u32;
+
+    /// Reports the requested against the actual size of the geometry
+    fn describe(&self) -> SizeReport;
+}
+
+impl<'a> dyn EcpGeometry + 'a {
+    /// Iterates over the location, hash, and type of
+    /// every rx neuron of the geometry in traversal order
+    pub fn rx_locs(&self) -> RxLocs<'_> {
+        RxLocs {
+            ecp_geometry: self,
+            next: Some(self.first_rx()),
+        }
+    }
+
+    /// Iterates over the location and hash of every sensory
+    /// neuron of the geometry in traversal order.  A geometry
+    /// made for no sensors has no sensory neurons to iterate
+    pub fn sensory_locs(&self) -> SensoryLocs<'_> {
+        SensoryLocs {
+            ecp_geometry: self,
+            next: match self.get_num_sensory() {
+                0 => None,
+                _ => Some(self.first_sensory()),
+            },
+        }
+    }
+}
+
+/// Iterator over the rx locations of an ecp geometry
+pub struct RxLocs<'a> {
+    ecp_geometry: &'a dyn EcpGeometry,
+    next: Option<(Loc, String, RxNeuron)>,
+}
+
+impl<'a> Iterator for RxLocs<'a> {
+    type Item = (Loc, String, RxNeuron);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        self.next = self.ecp_geometry.next_rx(current.0);
+
+        Some(current)
+    }
+}
+
+/// Iterator over the sensory locations of an ecp geometry
+pub struct SensoryLocs<'a> {
+    ecp_geometry: &'a dyn EcpGeometry,
+    next: Option<(Loc, String)>,
+}
+
+impl<'a> Iterator for SensoryLocs<'a> {
+    type Item = (Loc, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        self.next = self.ecp_geometry.next_sensory(current.0);
+
+        Some(current)
+    }
+}
+
+/// How actuator neurons are spread across the actuator plane of
+/// geometries that support moving them (see BoxEcp).  Packing the
+/// actuators into a corner leaves their neighborhoods overlapping,
+/// which tends to correlate their outputs.
+///
+/// ```
+/// use eywa::prelude::*;
+///
+/// let ecp = BoxEcp::new(125, 1, 4, 27)
+///     .with_actuator_spread(ActuatorSpread::EvenGrid)
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActuatorSpread {
+    /// Actuators fill the first positions of the plane in row order
+    Packed,
+
+    /// Actuators are spread over a grid spanning the plane, with the
+    /// same number in every row but the last, which is spread on its own
+    EvenGrid,
+
+    /// Actuators are placed at these (x, y) coordinates, one per actuator
+    Explicit(Vec<(u32, u32)>),
+}
+
+impl ActuatorSpread {
+    /// The (x, y) coordinates of count actuators spread across a plane
+    /// of the given dimensions, in row order.  Errors if explicit
+    /// positions don't hold exactly count actuators within the plane
+    fn positions(&self, count: u32, width: u32, height: u32) -> Result<Vec<(u32, u32)>, EywaError> {
+        match self {
+            ActuatorSpread::Packed => Ok((0..count).map(|i| (i % width, i / width)).collect()),
+            ActuatorSpread::EvenGrid => {
+                let mut positions = Vec::new();
+
+                if count == 0 {
+                    return Ok(positions);
+                }
+
+                let cols = (count as f32).sqrt().ceil() as u32;
+                let rows = count.div_ceil(cols);
+
+                for row in 0..rows {
+                    let in_row = cols.min(count - row * cols);
+                    let y = spread_coord(row, rows, height);
+
+                    for col in 0..in_row {
+                        positions.push((spread_coord(col, in_row, width), y));
+                    }
+                }
+
+                Ok(positions)
+            }
+            ActuatorSpread::Explicit(positions) => {
+                if positions.len() as u32 != count {
+                    return Err(EywaError::ActuatorCountMismatch {
+                        expected: positions.len() as u32,
+                        found: count,
+                    });
+                }
+
+                let mut placed = HashSet::new();
+
+                for (i, &(x, y)) in positions.iter().enumerate() {
+                    let name = format!("actuator {}", i);
+
+                    if x >= width || y >= height {
+                        return Err(EywaError::PlacementOutOfBounds {
+                            name,
+                            coord: (x, y),
+                        });
+                    } else if !placed.insert((x, y)) {
+                        return Err(EywaError::DuplicatePlacement {
+                            name,
+                            coord: (x, y),
+                        });
+                    }
+                }
+
+                let mut positions = positions.clone();
+                positions.sort_by_key(|&(x, y)| (y, x));
+
+                Ok(positions)
+            }
+        }
+    }
+}
+
+/// The coordinate of the ith of n points spread evenly along
+/// a side of length len, with a single point in the middle
+fn spread_coord(i: u32, n: u32, len: u32) -> u32 {
+    match n {
+        1 => (len - 1) / 2,
+        _ => ((i * (len - 1)) as f32 / (n - 1) as f32).round() as u32,
+    }
+}
+
+/// An axis-aligned region of an ecp geometry, holding every
+/// location whose coordinates each lie between those of min
+/// and max (inclusive)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Region {
+    pub min: Vec<i32>,
+    pub max: Vec<i32>,
+}
+
+impl Region {
+    pub fn new(min: Vec<i32>, max: Vec<i32>) -> Region {
+        Region { min, max }
+    }
+
+    /// True if loc lies within the region
+    pub fn contains(&self, loc: &[i32]) -> bool {
+        loc.len() == self.min.len()
+            && loc.len() == self.max.len()
+            && loc
+                .iter()
+                .zip(self.min.iter().zip(&self.max))
+                .all(|(coord, (min, max))| min <= coord && coord <= max)
+    }
 }
 
 /// This is the 3D box ecp geometry.  Basically a box of plastic neurons,
 /// with actuator neurons embedded into one end of the box, and sensor
 /// neurons floating on the outside of the other side of the box
 ///
-/// Note that the Box simply places actuators and sensors in rows on
-/// each of their sides for ease of implementation, and because it's
-/// not yet clear if spread has any effect on anything
+/// Sensors are placed in rows on their side of the box, as are
+/// actuators unless they're spread out with with_actuator_spread
+#[derive(Debug)]
 pub struct BoxEcp {
+    requested_num_plastic: u32,
+    requested_nearby_count: u32,
     num_plastic: u32,
     num_actuator: u32,
     num_sensory: u32,
     nearby_side_length: u32,
     side_length: u32,
+    exclusions: Vec<Region>, //Regions of the box that hold no neurons
+    actuator_positions: Vec<(u32, u32)>, //(x, y) of each actuator on the final plane
 }
 
 impl EcpGeometry for BoxEcp {
@@ -103,7 +454,7 @@ impl EcpGeometry for BoxEcp {
 
         let mut nearby_length = (nearby_count as f32).powf(1. / 3.).floor() as u32;
 
-        if nearby_length % 2 == 0 {
+        if nearby_length.is_multiple_of(2) {
             nearby_length -= 1;
         }
 
@@ -116,11 +467,17 @@ impl EcpGeometry for BoxEcp {
         };
 
         BoxEcp {
+            requested_num_plastic: desired_num_plastic,
+            requested_nearby_count: nearby_count,
             num_plastic: volume,
             num_actuator,
             num_sensory,
             nearby_side_length: nearby_length,
             side_length,
+            exclusions: Vec::new(),
+            actuator_positions: (0..num_actuator)
+                .map(|i| (i % side_length, i / side_length))
+                .collect(),
         }
     }
 
@@ -136,76 +493,36 @@ impl EcpGeometry for BoxEcp {
         self.num_sensory
     }
 
-    fn first_rx_loc(&self) -> (Vec<i32>, String, RxNeuron) {
-        let loc = vec![0, 0, 0];
-
-        (loc.clone(), self.loc_hash(&loc), RxNeuron::Plastic)
+    fn dims(&self) -> usize {
+        3
     }
 
-    fn next_rx_loc(&self, curr_loc: Vec<i32>) -> Option<(Vec<i32>, String, RxNeuron)> {
-        if let Some(x) = curr_loc.get(0) {
-            if let Some(y) = curr_loc.get(1) {
-                if let Some(z) = curr_loc.get(2) {
-                    let last_position = (self.side_length - 1) as i32;
-
-                    let new_x;
-                    let new_y;
-                    let new_z;
-
-                    // Figure out the new position values for each dimension
-                    if *x == last_position {
-                        if *y == last_position {
-                            if *z == last_position {
-                                // In this case, curr_loc as the last rx location
-                                // within the box, so return None to indicate there
-                                // is no other rx location
-                                return None;
-                            } else {
-                                new_x = 0;
-                                new_y = 0;
-                                new_z = *z + 1;
-                            }
-                        } else {
-                            new_x = 0;
-                            new_y = *y + 1;
-                            new_z = *z;
-                        }
-                    } else {
-                        new_x = *x + 1;
-                        new_y = *y;
-                        new_z = *z;
-                    }
+    fn first_rx(&self) -> (Loc, String, RxNeuron) {
+        let loc = Loc::new(&[0, 0, 0]);
 
-                    let new_loc = vec![new_x, new_y, new_z];
+        // Actuator neurons are never excluded, so there's
+        // always a first location that isn't excluded
+        match self.is_excluded(&loc) {
+            true => self.next_rx(loc).unwrap(),
+            false => (loc, self.loc_hash(&loc), RxNeuron::Plastic),
+        }
+    }
 
-                    // If new_z is at the final position, then we need to start worrying
-                    // about actuator neurons
-                    return if new_z == last_position {
-                        let plane_position = (new_y * (self.side_length as i32)) + new_x + 1;
-                        let is_actuator = plane_position as u32 <= self.num_actuator;
+    fn next_rx(&self, curr_loc: Loc) -> Option<(Loc, String, RxNeuron)> {
+        let (loc, rx_neuron) = self.next_included_loc(&curr_loc)?;
+        let hash = self.loc_hash(&loc);
 
-                        if is_actuator {
-                            Some((new_loc.clone(), self.loc_hash(&new_loc), RxNeuron::Actuator))
-                        } else {
-                            Some((new_loc.clone(), self.loc_hash(&new_loc), RxNeuron::Plastic))
-                        }
-                    } else {
-                        Some((new_loc.clone(), self.loc_hash(&new_loc), RxNeuron::Plastic))
-                    };
-                }
-            }
-        }
-        None
+        Some((loc, hash, rx_neuron))
     }
 
-    fn first_sensory_loc(&self) -> (Vec<i32>, String) {
-        let loc = vec![0, 0, -1];
+    fn first_sensory(&self) -> (Loc, String) {
+        let loc = Loc::new(&[0, 0, -1]);
 
-        (loc.clone(), self.loc_hash(&loc))
+        (loc, self.loc_hash(&loc))
     }
 
-    fn next_sensory_loc(&self, curr_loc: Vec<i32>) -> Option<(Vec<i32>, String)> {
-        if let Some(x) = curr_loc.get(0) {
+    fn next_sensory(&self, curr_loc: Loc) -> Option<(Loc, String)> {
+        if let Some(x) = curr_loc.first() {
             if let Some(y) = curr_loc.get(1) {
                 let last_position = (self.side_length - 1) as i32;
 
@@ -223,20 +540,121 @@ impl EcpGeometry for BoxEcp {
                     new_y = *y;
                 }
 
-                let new_loc = vec![new_x, new_y];
+                let new_loc = Loc::new(&[new_x, new_y, -1]);
 
-                return Some((new_loc.clone(), self.loc_hash(&new_loc)));
+                return Some((new_loc, self.loc_hash(&new_loc)));
             }
         }
         None
     }
 
-    fn loc_hash(&self, loc: &Vec<i32>) -> String {
+    fn sensory_plane_at(&self, x: u32, y: u32) -> Option<Loc> {
+        if x < self.side_length && y < self.side_length {
+            Some(Loc::new(&[x as i32, y as i32, -1]))
+        } else {
+            None
+        }
+    }
+
+    fn actuator_plane_at(&self, x: u32, y: u32) -> Option<Loc> {
+        if self.actuator_positions.contains(&(x, y)) {
+            Some(Loc::new(&[
+                x as i32,
+                y as i32,
+                (self.side_length - 1) as i32,
+            ]))
+        } else {
+            None
+        }
+    }
+
+    fn sensory_plane_dims(&self) -> (u32, u32) {
+        (self.side_length, self.side_length)
+    }
+
+    fn actuator_plane_dims(&self) -> (u32, u32) {
+        (self.side_length, self.side_length)
+    }
+
+    fn set_actuator_spread(&mut self, spread: ActuatorSpread) -> Result<(), EywaError> {
+        let positions = spread.positions(self.num_actuator, self.side_length, self.side_length)?;
+        let z = (self.side_length - 1) as i32;
+
+        for &(x, y) in &positions {
+            let loc = [x as i32, y as i32, z];
+
+            if self.is_excluded(&loc) {
+                return Err(EywaError::ExclusionOverlapsPlane(loc.to_vec()));
+            }
+        }
+
+        self.actuator_positions = positions;
+
+        Ok(())
+    }
+
+    /// Slices of the box are side length by side length, with rows
+    /// and columns running along the remaining axes, last axis first
+    fn slice_shape(&self, axis: usize) -> Option<(u32, u32, u32)> {
+        match axis < 3 {
+            true => Some((self.side_length, self.side_length, self.side_length)),
+            false => None,
+        }
+    }
+
+    fn slice_index_of(&self, loc: &[i32], axis: usize) -> Option<(u32, u32, u32)> {
+        if axis >= 3 || loc.len() != 3 {
+            return None;
+        }
+
+        if loc
+            .iter()
+            .any(|&coord| coord < 0 || coord >= self.side_length as i32)
+        {
+            return None;
+        }
+
+        let others: Vec<u32> = (0..3)
+            .filter(|&other| other != axis)
+            .map(|other| loc[other] as u32)
+            .collect();
+
+        Some((loc[axis] as u32, others[1], others[0]))
+    }
+
+    fn loc_hash(&self, loc: &[i32]) -> String {
         format!("{:?}", loc)
     }
 
-    fn local_random_hash(&self, loc: &Vec<i32>) -> Option<String> {
-        if let Some(x) = loc.get(0) {
+    /// Neighbors are all locations within the cube of side
+    /// length 2 * radius + 1 centered at loc, clipped to the box
+    fn neighbor_locs(&self, loc: Loc, radius: u32) -> Vec<Loc> {
+        let mut neighbors = Vec::new();
+
+        if loc.dims() != 3 {
+            return neighbors;
+        }
+
+        let last_position = (self.side_length - 1) as i32;
+        let radius = radius as i32;
+
+        let range = |center: i32| (center - radius).max(0)..=(center + radius).min(last_position);
+
+        for z in range(loc[2]) {
+            for y in range(loc[1]) {
+                for x in range(loc[0]) {
+                    if loc != [x, y, z][..] && !self.is_excluded(&[x, y, z]) {
+                        neighbors.push(Loc::new(&[x, y, z]));
+                    }
+                }
+            }
+        }
+
+        neighbors
+    }
+
+    fn local_random_hash(&self, loc: &[i32], rng: &mut dyn RngCore) -> Option<String> {
+        if let Some(x) = loc.first() {
             if let Some(y) = loc.get(1) {
                 if let Some(z) = loc.get(2) {
                     let last_position = (self.side_length - 1) as i32;
@@ -252,13 +670,13 @@ impl EcpGeometry for BoxEcp {
                     if bottom_x < 0 {
                         bottom_x = 0;
                     } else if bottom_x + (nearby_side_length_i32 - 1) > last_position {
-                        bottom_x = (last_position - (nearby_side_length_i32 - 1)) as i32
+                        bottom_x = last_position - (nearby_side_length_i32 - 1)
                     }
 
                     if bottom_y < 0 {
                         bottom_y = 0;
                     } else if bottom_y + (nearby_side_length_i32 - 1) > last_position {
-                        bottom_y = (last_position - (nearby_side_length_i32 - 1)) as i32
+                        bottom_y = last_position - (nearby_side_length_i32 - 1)
                     }
 
                     if bottom_z < 0 {
@@ -267,19 +685,18 @@ impl EcpGeometry for BoxEcp {
                         bottom_z = last_position - (nearby_side_length_i32 - 1)
                     }
 
-                    let mut random_gen = rand::thread_rng();
+                    let rand_x = rng.gen_range(bottom_x, bottom_x + nearby_side_length_i32 - 1);
+                    let rand_y = rng.gen_range(bottom_y, bottom_y + nearby_side_length_i32 - 1);
+                    let rand_z = rng.gen_range(bottom_z, bottom_z + nearby_side_length_i32 - 1);
 
-                    let rand_x =
-                        random_gen.gen_range(bottom_x, bottom_x + nearby_side_length_i32 - 1);
-                    let rand_y =
-                        random_gen.gen_range(bottom_y, bottom_y + nearby_side_length_i32 - 1);
-                    let rand_z =
-                        random_gen.gen_range(bottom_z, bottom_z + nearby_side_length_i32 - 1);
-
-                    let new_loc = vec![rand_x, rand_y, rand_z];
+                    let new_loc = [rand_x, rand_y, rand_z];
 
+                    // Excluded locations hold no neurons, so no
+                    // synapse is formed if one is picked
                     return if rand_x == *x && rand_y == *y && rand_z == *z {
-                        self.local_random_hash(loc)
+                        self.local_random_hash(loc, rng)
+                    } else if self.is_excluded(&new_loc) {
+                        None
                     } else {
                         Some(self.loc_hash(&new_loc))
                     };
@@ -288,4 +705,1773 @@ impl EcpGeometry for BoxEcp {
         }
         None
     }
+
+    fn nearby_radius(&self) -> u32 {
+        (self.nearby_side_length - 1) / 2
+    }
+
+    /// Every location of the nearby cube but loc itself
+    fn max_neighbors(&self) -> u32 {
+        self.nearby_side_length.pow(3) - 1
+    }
+
+    fn describe(&self) -> SizeReport {
+        SizeReport {
+            requested_plastic: self.requested_num_plastic,
+            actual_plastic: self.num_plastic,
+            requested_nearby: self.requested_nearby_count,
+            actual_nearby: self.nearby_side_length.pow(3),
+            requested_sensory: self.num_sensory,
+            actual_sensory: self.num_sensory,
+            requested_actuator: self.num_actuator,
+            actual_actuator: self.num_actuator,
+            synapse_budget_clamp: None,
+        }
+    }
+}
+
+impl BoxEcp {
+    /// The number of neurons along each edge of the box.  Sizes
+    /// that aren't cubes are rounded down, as is the nearby count,
+    /// which also has to have an odd cubed root, and describe
+    /// reports both roundings:
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let ecp = BoxEcp::new(1100, 2, 2, 100);
+    ///
+    /// assert_eq!(ecp.side_length(), 10);
+    /// assert!(!ecp.describe().is_exact());
+    /// ```
+    pub fn side_length(&self) -> u32 {
+        self.side_length
+    }
+
+    /// The number of neurons along each edge of the cube
+    /// of nearby neurons centered on a neuron
+    pub fn nearby_side_length(&self) -> u32 {
+        self.nearby_side_length
+    }
+
+    /// The number of plastic neurons passed to new, before
+    /// it was rounded down to a cube
+    pub fn requested_num_plastic(&self) -> u32 {
+        self.requested_num_plastic
+    }
+
+    /// Carves regions out of the box which hold no neurons, so
+    /// information has to flow around them.  Errors if a region
+    /// covers a sensory or actuator position.
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// // A slab across the middle of the box, short of one corner row
+    /// let slab = Region::new(vec![0, 1, 4], vec![8, 8, 4]);
+    /// let ecp = BoxEcp::new(750, 1, 1, 27).with_exclusions(vec![slab]).unwrap();
+    ///
+    /// assert_eq!(ecp.get_num_plastic(), 729 - 72);
+    /// ```
+    pub fn with_exclusions(mut self, exclusions: Vec<Region>) -> Result<BoxEcp, EywaError> {
+        let (width, height) = self.actuator_plane_dims();
+
+        for y in 0..height {
+            for x in 0..width {
+                for loc in self
+                    .sensory_plane_at(x, y)
+                    .into_iter()
+                    .chain(self.actuator_plane_at(x, y))
+                {
+                    if exclusions.iter().any(|region| region.contains(&loc)) {
+                        return Err(EywaError::ExclusionOverlapsPlane(loc.into()));
+                    }
+                }
+            }
+        }
+
+        self.exclusions = exclusions;
+
+        let mut num_plastic = 0;
+        let mut rx_option = Some(self.first_rx().0);
+
+        while let Some(loc) = rx_option {
+            num_plastic += 1;
+            rx_option = self.next_included_loc(&loc).map(|(loc, _)| loc);
+        }
+
+        self.num_plastic = num_plastic;
+
+        Ok(self)
+    }
+
+    /// The regions of the box that hold no neurons
+    pub fn exclusions(&self) -> &[Region] {
+        &self.exclusions
+    }
+
+    /// Spreads the actuators across the final plane of the box, rather
+    /// than packing them into its first row.  Errors if the spread
+    /// doesn't fit the plane or lands on an excluded region
+    pub fn with_actuator_spread(mut self, spread: ActuatorSpread) -> Result<BoxEcp, EywaError> {
+        self.set_actuator_spread(spread)?;
+
+        Ok(self)
+    }
+
+    /// True if loc lies within an excluded region
+    fn is_excluded(&self, loc: &[i32]) -> bool {
+        self.exclusions.iter().any(|region| region.contains(loc))
+    }
+
+    /// The first location after curr_loc that isn't excluded.  Only
+    /// the location that's returned is ever hashed (see next_rx)
+    fn next_included_loc(&self, curr_loc: &[i32]) -> Option<(Loc, RxNeuron)> {
+        let mut next = self.next_box_loc(curr_loc);
+
+        while let Some((loc, _)) = &next {
+            if !self.is_excluded(loc) {
+                break;
+            }
+
+            next = self.next_box_loc(loc);
+        }
+
+        next
+    }
+
+    /// The location after curr_loc within the box, whether
+    /// or not it's excluded
+    fn next_box_loc(&self, curr_loc: &[i32]) -> Option<(Loc, RxNeuron)> {
+        if let Some(x) = curr_loc.first() {
+            if let Some(y) = curr_loc.get(1) {
+                if let Some(z) = curr_loc.get(2) {
+                    let last_position = (self.side_length - 1) as i32;
+
+                    let new_x;
+                    let new_y;
+                    let new_z;
+
+                    // Figure out the new position values for each dimension
+                    if *x == last_position {
+                        if *y == last_position {
+                            if *z == last_position {
+                                // In this case, curr_loc as the last rx location
+                                // within the box, so return None to indicate there
+                                // is no other rx location
+                                return None;
+                            } else {
+                                new_x = 0;
+                                new_y = 0;
+                                new_z = *z + 1;
+                            }
+                        } else {
+                            new_x = 0;
+                            new_y = *y + 1;
+                            new_z = *z;
+                        }
+                    } else {
+                        new_x = *x + 1;
+                        new_y = *y;
+                        new_z = *z;
+                    }
+
+                    let new_loc = Loc::new(&[new_x, new_y, new_z]);
+
+                    // If new_z is at the final position, then we need to start worrying
+                    // about actuator neurons
+                    return if new_z == last_position {
+                        let is_actuator = self
+                            .actuator_positions
+                            .contains(&(new_x as u32, new_y as u32));
+
+                        if is_actuator {
+                            Some((new_loc, RxNeuron::Actuator))
+                        } else {
+                            Some((new_loc, RxNeuron::Plastic))
+                        }
+                    } else {
+                        Some((new_loc, RxNeuron::Plastic))
+                    };
+                }
+            }
+        }
+        None
+    }
+}
+
+/// This is a face-centered-cubic ecp geometry.  Neurons sit at the
+/// integer coordinates (x, y, z) of a box whose coordinates sum to an
+/// even number, so each interior neuron has 12 nearest neighbors
+/// (at offsets like (1, 1, 0)) rather than the 6 of a cubic lattice,
+/// which makes neighborhoods far less partial to the axis directions.
+///
+/// Like the box, sensory neurons float on the layer just outside the
+/// bottom of the box (z = -1) and actuator neurons fill the top layer
+/// of the box in row order.  A plane coordinate (x, y) refers to the
+/// x-th lattice position of row y of the layer
+///
+/// ```
+/// # use eywa::prelude::*;
+/// // 1000 plastic neurons, 4 sensors, 6 actuators, 12 nearby
+/// let ecp = FccEcp::new(1000, 4, 6, 12);
+/// ```
+#[derive(Debug)]
+pub struct FccEcp {
+    requested_num_plastic: u32,
+    requested_nearby_count: u32,
+    num_plastic: u32,
+    num_actuator: u32,
+    num_sensory: u32,
+    side_length: u32,
+    nearby_shells: u32,
+    nearby_offsets: Vec<[i32; 3]>, //Every offset within the nearby shells
+}
+
+impl EcpGeometry for FccEcp {
+    /// Here nearby_count is interpreted in shells of neighbors
+    /// sorted by distance, which hold 12, 6, 24, 12, 24, ... neurons.
+    /// It's rounded down to the largest number of whole shells whose
+    /// total fits within nearby_count, but always includes the first
+    /// shell (the 12 nearest neighbors)
+    fn new(desired_num_plastic: u32, num_sensory: u32, num_actuator: u32, nearby_count: u32) -> Self
+    where
+        Self: Sized,
+    {
+        // Only half of the points of a box lie on the lattice
+        let side_length = ((2 * desired_num_plastic) as f32).powf(1. / 3.).floor() as u32;
+
+        if side_length < 2 {
+            panic!(
+                "The box is too small to hold a face-centered-cubic lattice. \
+            Increase the number of plastic neurons"
+            );
+        }
+
+        let layer_count = FccEcp::layer_count(side_length);
+
+        if num_actuator > layer_count {
+            panic!(
+                "The number of actuators is greater than the number of neurons in \
+            one layer of the box. Either decrease the number of actuators, or increase \
+            the size of the box"
+            );
+        } else if num_sensory > layer_count {
+            panic!(
+                "The number of sensory neurons is greater than the number of neurons in \
+            one layer of the box. Either decrease the number of sensory neurons, or increase \
+            the size of the box"
+            );
+        }
+
+        let mut shells = 1;
+
+        while FccEcp::shell_offsets(shells + 1).len() as u32 <= nearby_count {
+            shells += 1;
+        }
+
+        let nearby_offsets = FccEcp::shell_offsets(shells);
+        let num_plastic = FccEcp::lattice_count(side_length);
+
+        if nearby_offsets.len() as u32 >= num_plastic {
+            panic!(
+                "The number of nearby neurons exceeds the number of neurons in the box. \
+            Either decrease the number of nearby neurons, or increase the size the size of \
+            the box"
+            );
+        }
+
+        FccEcp {
+            requested_num_plastic: desired_num_plastic,
+            requested_nearby_count: nearby_count,
+            num_plastic,
+            num_actuator,
+            num_sensory,
+            side_length,
+            nearby_shells: shells,
+            nearby_offsets,
+        }
+    }
+
+    fn get_num_plastic(&self) -> u32 {
+        self.num_plastic
+    }
+
+    fn get_num_actuator(&self) -> u32 {
+        self.num_actuator
+    }
+
+    fn get_num_sensory(&self) -> u32 {
+        self.num_sensory
+    }
+
+    fn dims(&self) -> usize {
+        3
+    }
+
+    fn first_rx(&self) -> (Loc, String, RxNeuron) {
+        let loc = Loc::new(&[0, 0, 0]);
+
+        (loc, self.loc_hash(&loc), RxNeuron::Plastic)
+    }
+
+    fn next_rx(&self, curr_loc: Loc) -> Option<(Loc, String, RxNeuron)> {
+        if let [x, y, z] = curr_loc[..] {
+            let last_position = (self.side_length - 1) as i32;
+            let new_loc = self
+                .next_layer_loc(x, y, z)
+                .or_else(|| match z < last_position {
+                    true => Some(self.first_layer_loc(z + 1)),
+                    false => None,
+                })?;
+
+            let rx_neuron = match self.is_actuator(&new_loc) {
+                true => RxNeuron::Actuator,
+                false => RxNeuron::Plastic,
+            };
+
+            return Some((new_loc, self.loc_hash(&new_loc), rx_neuron));
+        }
+        None
+    }
+
+    fn first_sensory(&self) -> (Loc, String) {
+        let loc = self.first_layer_loc(-1);
+
+        (loc, self.loc_hash(&loc))
+    }
+
+    fn next_sensory(&self, curr_loc: Loc) -> Option<(Loc, String)> {
+        if let [x, y, -1] = curr_loc[..] {
+            let new_loc = self.next_layer_loc(x, y, -1)?;
+
+            return Some((new_loc, self.loc_hash(&new_loc)));
+        }
+        None
+    }
+
+    fn sensory_plane_at(&self, x: u32, y: u32) -> Option<Loc> {
+        self.layer_loc(x, y, -1)
+    }
+
+    fn actuator_plane_at(&self, x: u32, y: u32) -> Option<Loc> {
+        let loc = self.layer_loc(x, y, (self.side_length - 1) as i32)?;
+
+        match self.is_actuator(&loc) {
+            true => Some(loc),
+            false => None,
+        }
+    }
+
+    fn sensory_plane_dims(&self) -> (u32, u32) {
+        (self.side_length.div_ceil(2), self.side_length)
+    }
+
+    fn actuator_plane_dims(&self) -> (u32, u32) {
+        (self.side_length.div_ceil(2), self.side_length)
+    }
+
+    fn loc_hash(&self, loc: &[i32]) -> String {
+        format!("{:?}", loc)
+    }
+
+    /// Neighbors are all rx locations within radius shells of loc
+    fn neighbor_locs(&self, loc: Loc, radius: u32) -> Vec<Loc> {
+        match loc[..] {
+            [x, y, z] => FccEcp::shell_offsets(radius)
+                .iter()
+                .map(|[dx, dy, dz]| Loc::new(&[x + dx, y + dy, z + dz]))
+                .filter(|neighbor| self.in_box(neighbor))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Picks uniformly among the rx locations within the nearby
+    /// shells of loc, so neurons near the edges of the box simply
+    /// have fewer locations to choose from
+    fn local_random_hash(&self, loc: &[i32], rng: &mut dyn RngCore) -> Option<String> {
+        if let [x, y, z] = loc[..] {
+            let nearby = |[dx, dy, dz]: &[i32; 3]| {
+                let new_loc = [x + dx, y + dy, z + dz];
+
+                match self.in_box(&new_loc) {
+                    true => Some(new_loc),
+                    false => None,
+                }
+            };
+
+            let count = self.nearby_offsets.iter().filter_map(nearby).count();
+
+            if count == 0 {
+                return None;
+            }
+
+            let new_loc = self
+                .nearby_offsets
+                .iter()
+                .filter_map(nearby)
+                .nth(rng.gen_range(0, count))?;
+
+            return Some(self.loc_hash(&new_loc));
+        }
+        None
+    }
+
+    fn nearby_radius(&self) -> u32 {
+        self.nearby_shells
+    }
+
+    fn max_neighbors(&self) -> u32 {
+        self.nearby_offsets.len() as u32
+    }
+
+    fn describe(&self) -> SizeReport {
+        SizeReport {
+            requested_plastic: self.requested_num_plastic,
+            actual_plastic: self.num_plastic,
+            requested_nearby: self.requested_nearby_count,
+            actual_nearby: self.nearby_offsets.len() as u32,
+            requested_sensory: self.num_sensory,
+            actual_sensory: self.num_sensory,
+            requested_actuator: self.num_actuator,
+            actual_actuator: self.num_actuator,
+            synapse_budget_clamp: None,
+        }
+    }
+}
+
+impl FccEcp {
+    /// The number of neurons along each edge of the box, counting
+    /// positions that aren't on the lattice
+    pub fn side_length(&self) -> u32 {
+        self.side_length
+    }
+
+    /// The number of plastic neurons passed to new, before
+    /// it was rounded down to fit the lattice
+    pub fn requested_num_plastic(&self) -> u32 {
+        self.requested_num_plastic
+    }
+
+    /// Every offset to a lattice point within the first
+    /// shells shells of neighbors.  The n-th shell sits at a
+    /// squared distance of 2n
+    fn shell_offsets(shells: u32) -> Vec<[i32; 3]> {
+        let max_squared = 2 * shells as i32;
+        let reach = (max_squared as f32).sqrt().floor() as i32;
+        let mut offsets = Vec::new();
+
+        for dz in -reach..=reach {
+            for dy in -reach..=reach {
+                for dx in -reach..=reach {
+                    let squared = dx * dx + dy * dy + dz * dz;
+
+                    if squared > 0 && squared <= max_squared && (dx + dy + dz) % 2 == 0 {
+                        offsets.push([dx, dy, dz]);
+                    }
+                }
+            }
+        }
+
+        offsets
+    }
+
+    /// The number of lattice points within a box of side_length
+    fn lattice_count(side_length: u32) -> u32 {
+        side_length.pow(3).div_ceil(2)
+    }
+
+    /// The number of lattice points within a single layer of
+    /// the box.  Odd layers hold one fewer point when the side
+    /// length is odd, so this is the count of the smaller layer
+    fn layer_count(side_length: u32) -> u32 {
+        side_length.pow(2) / 2
+    }
+
+    /// The x offset of the first lattice point of row y of layer z
+    fn row_offset(y: i32, z: i32) -> i32 {
+        (y + z).rem_euclid(2)
+    }
+
+    /// The location of the x-th lattice point of row y of layer z
+    fn layer_loc(&self, x: u32, y: u32, z: i32) -> Option<Loc> {
+        let new_x = 2 * x as i32 + FccEcp::row_offset(y as i32, z);
+
+        if new_x < self.side_length as i32 && y < self.side_length {
+            Some(Loc::new(&[new_x, y as i32, z]))
+        } else {
+            None
+        }
+    }
+
+    /// The first lattice point of layer z
+    fn first_layer_loc(&self, z: i32) -> Loc {
+        Loc::new(&[FccEcp::row_offset(0, z), 0, z])
+    }
+
+    /// The lattice point of layer z after (x, y), if there is one
+    fn next_layer_loc(&self, x: i32, y: i32, z: i32) -> Option<Loc> {
+        let side_length = self.side_length as i32;
+
+        if x + 2 < side_length {
+            Some(Loc::new(&[x + 2, y, z]))
+        } else if y + 1 < side_length {
+            Some(Loc::new(&[FccEcp::row_offset(y + 1, z), y + 1, z]))
+        } else {
+            None
+        }
+    }
+
+    /// True if loc is a lattice point within the box
+    fn in_box(&self, loc: &[i32]) -> bool {
+        let side_length = self.side_length as i32;
+
+        loc.iter().all(|coord| (0..side_length).contains(coord)) && loc.iter().sum::<i32>() % 2 == 0
+    }
+
+    /// True if loc is one of the first num_actuator lattice
+    /// points of the top layer of the box
+    fn is_actuator(&self, loc: &[i32]) -> bool {
+        if let [x, y, z] = loc[..] {
+            if z != (self.side_length - 1) as i32 {
+                return false;
+            }
+
+            let rows_before: i32 = (0..y)
+                .map(|row| (self.side_length as i32 + 1 - FccEcp::row_offset(row, z)) / 2)
+                .sum();
+
+            return ((rows_before + x / 2) as u32) < self.num_actuator;
+        }
+        false
+    }
+}
+
+/// A single node (ie neuron position) of an EcpSpec
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NodeSpec {
+    pub id: String,
+    pub loc: Vec<i32>,
+    /// The ids of the plastic or actuator nodes this
+    /// node may form plastic synapses with
+    pub nearby: Vec<String>,
+}
+
+/// A hand-written description of an ecp geometry, listing
+/// every node of each type along with which nodes count as
+/// nearby each node
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EcpSpec {
+    pub plastic: Vec<NodeSpec>,
+    pub actuator: Vec<NodeSpec>,
+    pub sensory: Vec<NodeSpec>,
+}
+
+/// This is an ecp geometry built from an explicit EcpSpec, which
+/// gives full control over where each neuron sits and which neurons
+/// it may connect to.  Neurons are traversed in the order they're
+/// listed in the spec (plastic nodes, then actuator nodes), and
+/// the hash of each location is the id of the node there.
+///
+/// The sensory and actuator planes are single rows holding the
+/// nodes of each type in the order they're listed
+#[derive(Debug)]
+pub struct CustomEcp {
+    rx_nodes: Vec<NodeSpec>, //Plastic nodes followed by actuator nodes
+    sensory_nodes: Vec<NodeSpec>,
+    num_plastic: u32,
+    rx_index: HashMap<Loc, usize>,
+    sensory_index: HashMap<Loc, usize>,
+    nearby: HashMap<String, Vec<String>>,
+    rx_locs: HashMap<String, Loc>,
+    dims: usize,
+}
+
+impl EcpGeometry for CustomEcp {
+    /// A custom geometry can't be made from counts alone, so
+    /// this panics.  Use CustomEcp::from_spec instead
+    fn new(
+        _desired_num_plastic: u32,
+        _num_sensory: u32,
+        _num_actuator: u32,
+        _nearby_count: u32,
+    ) -> Self
+    where
+        Self: Sized,
+    {
+        panic!("A CustomEcp can only be made from an EcpSpec, so use CustomEcp::from_spec")
+    }
+
+    fn get_num_plastic(&self) -> u32 {
+        self.num_plastic
+    }
+
+    fn get_num_actuator(&self) -> u32 {
+        self.rx_nodes.len() as u32 - self.num_plastic
+    }
+
+    fn get_num_sensory(&self) -> u32 {
+        self.sensory_nodes.len() as u32
+    }
+
+    fn dims(&self) -> usize {
+        self.dims
+    }
+
+    fn first_rx(&self) -> (Loc, String, RxNeuron) {
+        self.rx_entry(0)
+    }
+
+    fn next_rx(&self, curr_loc: Loc) -> Option<(Loc, String, RxNeuron)> {
+        let index = self.rx_index.get(&curr_loc)? + 1;
+
+        match index < self.rx_nodes.len() {
+            true => Some(self.rx_entry(index)),
+            false => None,
+        }
+    }
+
+    fn first_sensory(&self) -> (Loc, String) {
+        let node = &self.sensory_nodes[0];
+
+        (Loc::new(&node.loc), node.id.clone())
+    }
+
+    fn next_sensory(&self, curr_loc: Loc) -> Option<(Loc, String)> {
+        let index = self.sensory_index.get(&curr_loc)? + 1;
+
+        self.sensory_nodes
+            .get(index)
+            .map(|node| (Loc::new(&node.loc), node.id.clone()))
+    }
+
+    fn sensory_plane_at(&self, x: u32, y: u32) -> Option<Loc> {
+        match y {
+            0 => self
+                .sensory_nodes
+                .get(x as usize)
+                .map(|node| Loc::new(&node.loc)),
+            _ => None,
+        }
+    }
+
+    fn actuator_plane_at(&self, x: u32, y: u32) -> Option<Loc> {
+        match y {
+            0 => self
+                .rx_nodes
+                .get(self.num_plastic as usize + x as usize)
+                .map(|node| Loc::new(&node.loc)),
+            _ => None,
+        }
+    }
+
+    fn sensory_plane_dims(&self) -> (u32, u32) {
+        (self.get_num_sensory(), 1)
+    }
+
+    fn actuator_plane_dims(&self) -> (u32, u32) {
+        (self.get_num_actuator(), 1)
+    }
+
+    /// Locations that don't belong to a node hash to their debug
+    /// representation, which never collides with a node id lookup
+    fn loc_hash(&self, loc: &[i32]) -> String {
+        let node_loc = Loc::checked(loc);
+        let node_loc = node_loc.as_ref();
+
+        if let Some(index) = node_loc.and_then(|loc| self.rx_index.get(loc)) {
+            self.rx_nodes[*index].id.clone()
+        } else if let Some(index) = node_loc.and_then(|loc| self.sensory_index.get(loc)) {
+            self.sensory_nodes[*index].id.clone()
+        } else {
+            format!("{:?}", loc)
+        }
+    }
+
+    /// Neighbors are all rx locations reachable from loc by
+    /// following at most radius declared nearby sets
+    fn neighbor_locs(&self, loc: Loc, radius: u32) -> Vec<Loc> {
+        let start = self.loc_hash(&loc);
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut frontier = vec![start.as_str()];
+        let mut neighbors = Vec::new();
+
+        visited.insert(start.as_str());
+
+        for _ in 0..radius {
+            let mut next_frontier = Vec::new();
+
+            for id in frontier {
+                for nearby_id in self.nearby.get(id).into_iter().flatten() {
+                    if visited.insert(nearby_id.as_str()) {
+                        neighbors.push(self.rx_locs[nearby_id]);
+                        next_frontier.push(nearby_id.as_str());
+                    }
+                }
+            }
+
+            frontier = next_frontier;
+        }
+
+        neighbors
+    }
+
+    /// Picks uniformly among the nodes declared nearby loc
+    fn local_random_hash(&self, loc: &[i32], rng: &mut dyn RngCore) -> Option<String> {
+        let nearby = self.nearby.get(&self.loc_hash(loc))?;
+
+        match nearby.is_empty() {
+            true => None,
+            false => Some(nearby[rng.gen_range(0, nearby.len())].clone()),
+        }
+    }
+
+    /// Each node's nearby set is a single step away
+    fn nearby_radius(&self) -> u32 {
+        1
+    }
+
+    fn max_neighbors(&self) -> u32 {
+        self.nearby.values().map(Vec::len).max().unwrap_or(0) as u32
+    }
+
+    /// A custom geometry holds exactly what its spec lists.  The
+    /// nearby count reported is the size of the largest nearby set
+    fn describe(&self) -> SizeReport {
+        let nearby_count = self.nearby.values().map(Vec::len).max().unwrap_or(0) as u32;
+
+        SizeReport {
+            requested_plastic: self.get_num_plastic(),
+            actual_plastic: self.get_num_plastic(),
+            requested_nearby: nearby_count,
+            actual_nearby: nearby_count,
+            requested_sensory: self.get_num_sensory(),
+            actual_sensory: self.get_num_sensory(),
+            requested_actuator: self.get_num_actuator(),
+            actual_actuator: self.get_num_actuator(),
+            synapse_budget_clamp: None,
+        }
+    }
+}
+
+impl CustomEcp {
+    /// Builds a geometry from spec.  Errors if two nodes share an
+    /// id or location, if a node's location has too many dimensions
+    /// (or a different number of dimensions than the first node),
+    /// if a node lists a nearby node that isn't a plastic or actuator
+    /// node, or if the spec has no actuator or sensory nodes
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let node = |id: &str, z: i32, nearby: &[&str]| NodeSpec {
+    ///     id: id.to_string(),
+    ///     loc: vec![0, 0, z],
+    ///     nearby: nearby.iter().map(|id| id.to_string()).collect(),
+    /// };
+    ///
+    /// // s -> p <-> a
+    /// let ecp = CustomEcp::from_spec(EcpSpec {
+    ///     sensory: vec![node("s", -1, &["p"])],
+    ///     plastic: vec![node("p", 0, &["a"])],
+    ///     actuator: vec![node("a", 1, &["p"])],
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(ecp.get_num_plastic(), 1);
+    /// ```
+    pub fn from_spec(spec: EcpSpec) -> Result<CustomEcp, EywaError> {
+        if spec.actuator.is_empty() {
+            return Err(EywaError::NoNodesOfKind("actuator".to_string()));
+        } else if spec.sensory.is_empty() {
+            return Err(EywaError::NoNodesOfKind("sensory".to_string()));
+        }
+
+        let num_plastic = spec.plastic.len() as u32;
+        let rx_nodes: Vec<NodeSpec> = spec.plastic.into_iter().chain(spec.actuator).collect();
+        let sensory_nodes = spec.sensory;
+
+        let mut ids = HashSet::new();
+        let mut locs = HashSet::new();
+        let dims = rx_nodes[0].loc.len();
+
+        for node in rx_nodes.iter().chain(&sensory_nodes) {
+            if node.loc.len() > MAX_DIMS {
+                return Err(EywaError::TooManyDimensions(node.loc.clone()));
+            } else if node.loc.len() != dims {
+                return Err(EywaError::DimensionMismatch {
+                    loc: node.loc.clone(),
+                    expected: dims,
+                });
+            } else if !ids.insert(node.id.clone()) {
+                return Err(EywaError::DuplicateNodeId(node.id.clone()));
+            } else if !locs.insert(Loc::new(&node.loc)) {
+                return Err(EywaError::DuplicateNodeLoc(node.loc.clone()));
+            }
+        }
+
+        let rx_locs: HashMap<String, Loc> = rx_nodes
+            .iter()
+            .map(|node| (node.id.clone(), Loc::new(&node.loc)))
+            .collect();
+
+        for node in rx_nodes.iter().chain(&sensory_nodes) {
+            for neighbor in &node.nearby {
+                if !rx_locs.contains_key(neighbor) {
+                    return Err(EywaError::UnknownNeighbor {
+                        node: node.id.clone(),
+                        neighbor: neighbor.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(CustomEcp {
+            rx_index: index_by_loc(&rx_nodes),
+            sensory_index: index_by_loc(&sensory_nodes),
+            nearby: rx_nodes
+                .iter()
+                .chain(&sensory_nodes)
+                .map(|node| (node.id.clone(), node.nearby.clone()))
+                .collect(),
+            rx_nodes,
+            sensory_nodes,
+            num_plastic,
+            rx_locs,
+            dims,
+        })
+    }
+
+    /// The location, hash, and type of the index-th rx node
+    fn rx_entry(&self, index: usize) -> (Loc, String, RxNeuron) {
+        let node = &self.rx_nodes[index];
+        let rx_neuron = match index < self.num_plastic as usize {
+            true => RxNeuron::Plastic,
+            false => RxNeuron::Actuator,
+        };
+
+        (Loc::new(&node.loc), node.id.clone(), rx_neuron)
+    }
+}
+
+/// Maps the location of each node onto its index within nodes
+fn index_by_loc(nodes: &[NodeSpec]) -> HashMap<Loc, usize> {
+    nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (Loc::new(&node.loc), index))
+        .collect()
+}
+
+/// The probabilities with which a neuron of a LayeredEcp forms
+/// synapses with the next layer (forward), its own layer (lateral),
+/// or the previous layer (backward).  They're renormalized over the
+/// directions available to each layer, and radius is the reach of
+/// the lateral neighborhood searched within the chosen layer
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LayerConnectivity {
+    pub forward_p: f32,
+    pub lateral_p: f32,
+    pub backward_p: f32,
+    pub radius: u32,
+}
+
+impl Default for LayerConnectivity {
+    fn default() -> LayerConnectivity {
+        LayerConnectivity {
+            forward_p: 0.6,
+            lateral_p: 0.3,
+            backward_p: 0.1,
+            radius: 1,
+        }
+    }
+}
+
+/// This is a layered ecp geometry, ie a crude cortex.  Layers of
+/// plastic neurons of (possibly) different dimensions are stacked
+/// along z, and synapse formation is biased to run forward from
+/// each layer to the next.
+///
+/// Sensory neurons sit on a plane above layer 0 (z = -1), and
+/// actuator neurons fill a plane below the last layer in row order.
+/// Positions are mapped between layers of different dimensions by
+/// scaling their coordinates
+#[derive(Debug)]
+pub struct LayeredEcp {
+    requested_num_plastic: u32,
+    layer_dims: Vec<(u32, u32)>,
+    num_plastic: u32,
+    num_sensory: u32,
+    num_actuator: u32,
+    connectivity: LayerConnectivity,
+}
+
+impl EcpGeometry for LayeredEcp {
+    /// Stacks side length layers of side length by side length
+    /// neurons, where the side length is the cube root of
+    /// desired_num_plastic rounded down.  The lateral radius is
+    /// taken from nearby_count as if it were a cube of neurons
+    fn new(desired_num_plastic: u32, num_sensory: u32, num_actuator: u32, nearby_count: u32) -> Self
+    where
+        Self: Sized,
+    {
+        let side_length = (desired_num_plastic as f32).powf(1. / 3.).floor() as u32;
+        let nearby_length = (nearby_count as f32).powf(1. / 3.).floor() as u32;
+
+        let mut layered = LayeredEcp::new(
+            vec![(side_length, side_length); side_length as usize],
+            num_sensory,
+            num_actuator,
+            LayerConnectivity {
+                radius: nearby_length.saturating_sub(1) / 2,
+                ..LayerConnectivity::default()
+            },
+        );
+
+        layered.requested_num_plastic = desired_num_plastic;
+        layered
+    }
+
+    fn get_num_plastic(&self) -> u32 {
+        self.num_plastic
+    }
+
+    fn get_num_actuator(&self) -> u32 {
+        self.num_actuator
+    }
+
+    fn get_num_sensory(&self) -> u32 {
+        self.num_sensory
+    }
+
+    fn dims(&self) -> usize {
+        3
+    }
+
+    fn first_rx(&self) -> (Loc, String, RxNeuron) {
+        let loc = Loc::new(&[0, 0, 0]);
+
+        (loc, self.loc_hash(&loc), RxNeuron::Plastic)
+    }
+
+    fn next_rx(&self, curr_loc: Loc) -> Option<(Loc, String, RxNeuron)> {
+        if let [x, y, z] = curr_loc[..] {
+            let (width, height) = self.dims_of(z)?;
+            let actuator_layer = self.layer_dims.len() as i32;
+
+            let new_loc = if x + 1 < width as i32 {
+                Loc::new(&[x + 1, y, z])
+            } else if y + 1 < height as i32 {
+                Loc::new(&[0, y + 1, z])
+            } else if z < actuator_layer {
+                Loc::new(&[0, 0, z + 1])
+            } else {
+                return None;
+            };
+
+            if !self.in_layer(&new_loc) {
+                return None;
+            }
+
+            let rx_neuron = match new_loc[2] == actuator_layer {
+                true => RxNeuron::Actuator,
+                false => RxNeuron::Plastic,
+            };
+
+            return Some((new_loc, self.loc_hash(&new_loc), rx_neuron));
+        }
+        None
+    }
+
+    fn first_sensory(&self) -> (Loc, String) {
+        let loc = Loc::new(&[0, 0, -1]);
+
+        (loc, self.loc_hash(&loc))
+    }
+
+    fn next_sensory(&self, curr_loc: Loc) -> Option<(Loc, String)> {
+        if let [x, y, -1] = curr_loc[..] {
+            let (width, height) = self.layer_dims[0];
+
+            let new_loc = if x + 1 < width as i32 {
+                Loc::new(&[x + 1, y, -1])
+            } else if y + 1 < height as i32 {
+                Loc::new(&[0, y + 1, -1])
+            } else {
+                return None;
+            };
+
+            return Some((new_loc, self.loc_hash(&new_loc)));
+        }
+        None
+    }
+
+    fn sensory_plane_at(&self, x: u32, y: u32) -> Option<Loc> {
+        let loc = Loc::new(&[x as i32, y as i32, -1]);
+
+        match self.in_layer(&loc) {
+            true => Some(loc),
+            false => None,
+        }
+    }
+
+    fn actuator_plane_at(&self, x: u32, y: u32) -> Option<Loc> {
+        let loc = Loc::new(&[x as i32, y as i32, self.layer_dims.len() as i32]);
+
+        match self.in_layer(&loc) {
+            true => Some(loc),
+            false => None,
+        }
+    }
+
+    fn sensory_plane_dims(&self) -> (u32, u32) {
+        self.layer_dims[0]
+    }
+
+    fn actuator_plane_dims(&self) -> (u32, u32) {
+        self.layer_dims[self.layer_dims.len() - 1]
+    }
+
+    fn loc_hash(&self, loc: &[i32]) -> String {
+        format!("{:?}", loc)
+    }
+
+    /// Neighbors are all rx locations of the layers within radius
+    /// of loc's layer, that lie within radius of loc's position
+    /// once it's mapped onto their layer
+    fn neighbor_locs(&self, loc: Loc, radius: u32) -> Vec<Loc> {
+        let mut neighbors = Vec::new();
+
+        if let [x, y, z] = loc[..] {
+            let radius = radius as i32;
+
+            for target_z in (z - radius).max(0)..=(z + radius) {
+                neighbors.extend(
+                    self.layer_neighborhood(x, y, z, target_z, radius)
+                        .filter(|neighbor| *neighbor != loc),
+                );
+            }
+        }
+
+        neighbors
+    }
+
+    /// Samples the forward, lateral, or backward layer according to
+    /// the connectivity, then a position within the lateral radius of
+    /// loc's position mapped onto that layer
+    fn local_random_hash(&self, loc: &[i32], rng: &mut dyn RngCore) -> Option<String> {
+        if let [x, y, z] = loc[..] {
+            let LayerConnectivity {
+                forward_p,
+                lateral_p,
+                backward_p,
+                radius,
+            } = self.connectivity;
+
+            // Sensory neurons can only connect forward, and
+            // actuator neurons never form synapses
+            let actuator_layer = self.layer_dims.len() as i32;
+            let directions = [
+                (z + 1, forward_p),
+                (z, if z >= 0 { lateral_p } else { 0. }),
+                (z - 1, if z >= 1 { backward_p } else { 0. }),
+            ];
+
+            let directions: Vec<(i32, f32)> = directions
+                .iter()
+                .filter(|(target_z, p)| *p > 0. && *target_z <= actuator_layer)
+                .copied()
+                .collect();
+
+            let total: f32 = directions.iter().map(|(_, p)| p).sum();
+
+            if total <= 0. {
+                return None;
+            }
+
+            let mut pick = rng.gen_range(0., total);
+            let mut target_z = directions[directions.len() - 1].0;
+
+            for (direction_z, p) in &directions {
+                if pick < *p {
+                    target_z = *direction_z;
+                    break;
+                }
+                pick -= p;
+            }
+
+            let radius = radius as i32;
+            let nearby = || {
+                self.layer_neighborhood(x, y, z, target_z, radius)
+                    .filter(|neighbor| neighbor[..] != loc[..])
+            };
+
+            let count = nearby().count();
+
+            if count == 0 {
+                return None;
+            }
+
+            let new_loc = nearby().nth(rng.gen_range(0, count))?;
+
+            return Some(self.loc_hash(&new_loc));
+        }
+        None
+    }
+
+    fn nearby_radius(&self) -> u32 {
+        self.connectivity.radius
+    }
+
+    /// A full lateral radius of positions on each layer that can
+    /// be targeted, leaving out loc itself on its own layer
+    fn max_neighbors(&self) -> u32 {
+        let side = 2 * self.connectivity.radius + 1;
+        let LayerConnectivity {
+            forward_p,
+            lateral_p,
+            backward_p,
+            ..
+        } = self.connectivity;
+
+        let layers = [forward_p, lateral_p, backward_p]
+            .iter()
+            .filter(|p| **p > 0.)
+            .count() as u32;
+
+        match lateral_p > 0. {
+            true => layers * side * side - 1,
+            false => layers * side * side,
+        }
+    }
+
+    fn describe(&self) -> SizeReport {
+        let side = 2 * self.connectivity.radius + 1;
+
+        SizeReport {
+            requested_plastic: self.requested_num_plastic,
+            actual_plastic: self.num_plastic,
+            requested_nearby: side * side,
+            actual_nearby: side * side,
+            requested_sensory: self.num_sensory,
+            actual_sensory: self.num_sensory,
+            requested_actuator: self.num_actuator,
+            actual_actuator: self.num_actuator,
+            synapse_budget_clamp: None,
+        }
+    }
+}
+
+impl LayeredEcp {
+    /// Creates a layered geometry with a layer of plastic neurons
+    /// for each of layer_dims, given as (width, height).
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let ecp = LayeredEcp::new(
+    ///     vec![(6, 6), (4, 4), (8, 8), (3, 5)],
+    ///     5,
+    ///     4,
+    ///     LayerConnectivity {
+    ///         forward_p: 0.6,
+    ///         lateral_p: 0.3,
+    ///         backward_p: 0.1,
+    ///         radius: 1,
+    ///     },
+    /// );
+    ///
+    /// assert_eq!(ecp.get_num_plastic(), 36 + 16 + 64 + 15);
+    /// ```
+    pub fn new(
+        layer_dims: Vec<(u32, u32)>,
+        num_sensory: u32,
+        num_actuator: u32,
+        connectivity: LayerConnectivity,
+    ) -> LayeredEcp {
+        if layer_dims.is_empty() || layer_dims.iter().any(|(w, h)| *w == 0 || *h == 0) {
+            panic!("A layered geometry needs at least one layer, and every layer needs neurons");
+        }
+
+        let (first_width, first_height) = layer_dims[0];
+        let (last_width, last_height) = layer_dims[layer_dims.len() - 1];
+
+        if num_actuator > last_width * last_height {
+            panic!(
+                "The number of actuators is greater than the area of the last layer. \
+            Either decrease the number of actuators, or increase the size of the last layer"
+            );
+        } else if num_sensory > first_width * first_height {
+            panic!(
+                "The number of sensory neurons is greater than the area of the first layer. \
+            Either decrease the number of sensory neurons, or increase the size of the \
+            first layer"
+            );
+        }
+
+        let num_plastic = layer_dims.iter().map(|(w, h)| w * h).sum();
+
+        LayeredEcp {
+            requested_num_plastic: num_plastic,
+            layer_dims,
+            num_plastic,
+            num_sensory,
+            num_actuator,
+            connectivity,
+        }
+    }
+
+    /// The (width, height) of each layer of plastic neurons
+    pub fn layer_dims(&self) -> &[(u32, u32)] {
+        &self.layer_dims
+    }
+
+    /// The dimensions of the layer (or plane) at z.  The sensory
+    /// plane shares the dimensions of the first layer, and the
+    /// actuator plane those of the last
+    fn dims_of(&self, z: i32) -> Option<(u32, u32)> {
+        let num_layers = self.layer_dims.len() as i32;
+
+        match z {
+            -1 => Some(self.layer_dims[0]),
+            z if z >= 0 && z < num_layers => Some(self.layer_dims[z as usize]),
+            z if z == num_layers => Some(self.layer_dims[z as usize - 1]),
+            _ => None,
+        }
+    }
+
+    /// True if loc is a position of a layer or plane, where
+    /// only the first num_actuator positions of the actuator
+    /// plane (in row order) hold neurons
+    fn in_layer(&self, loc: &[i32]) -> bool {
+        if let [x, y, z] = loc[..] {
+            if let Some((width, height)) = self.dims_of(z) {
+                let in_bounds = x >= 0 && y >= 0 && x < width as i32 && y < height as i32;
+
+                return match z == self.layer_dims.len() as i32 {
+                    true => in_bounds && ((y * width as i32 + x) as u32) < self.num_actuator,
+                    false => in_bounds,
+                };
+            }
+        }
+        false
+    }
+
+    /// Every rx location of layer target_z within radius of (x, y)
+    /// of layer z, once (x, y) is scaled onto target_z's dimensions
+    fn layer_neighborhood(
+        &self,
+        x: i32,
+        y: i32,
+        z: i32,
+        target_z: i32,
+        radius: i32,
+    ) -> impl Iterator<Item = Loc> + '_ {
+        let (center_x, center_y) = match (self.dims_of(z), self.dims_of(target_z)) {
+            (Some((width, height)), Some((target_width, target_height))) => (
+                x * target_width as i32 / width as i32,
+                y * target_height as i32 / height as i32,
+            ),
+            _ => (x, y),
+        };
+
+        let valid_target = target_z >= 0;
+
+        ((center_y - radius)..=(center_y + radius))
+            .flat_map(move |new_y| {
+                ((center_x - radius)..=(center_x + radius))
+                    .map(move |new_x| Loc::new(&[new_x, new_y, target_z]))
+            })
+            .filter(move |new_loc| valid_target && self.in_layer(new_loc))
+    }
+}
+
+/// A plain data description of an ecp geometry, so that the
+/// geometry of a brain can be kept in a config file alongside
+/// the rest of it.  The kind of geometry is tagged by "kind":
+///
+/// ```
+/// use eywa::ecp_geometry::GeometrySpec;
+///
+/// let spec: GeometrySpec = serde_json::from_str(
+///     r#"{"kind": "box", "num_plastic": 27, "num_sensory": 1, "num_actuator": 1, "nearby_count": 27}"#,
+/// )
+/// .unwrap();
+///
+/// let ecp = spec.build().unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum GeometrySpec {
+    /// A BoxEcp, with regions carved out of it
+    Box {
+        num_plastic: u32,
+        num_sensory: u32,
+        num_actuator: u32,
+        nearby_count: u32,
+        #[serde(default)]
+        exclusions: Vec<Region>,
+    },
+
+    /// An FccEcp
+    Fcc {
+        num_plastic: u32,
+        num_sensory: u32,
+        num_actuator: u32,
+        nearby_count: u32,
+    },
+
+    /// A LayeredEcp
+    Layered {
+        layer_dims: Vec<(u32, u32)>,
+        num_sensory: u32,
+        num_actuator: u32,
+        #[serde(default)]
+        connectivity: LayerConnectivity,
+    },
+
+    /// A CustomEcp
+    Custom(EcpSpec),
+}
+
+impl GeometrySpec {
+    /// Builds the geometry described.  Errors if an exclusion covers
+    /// a sensory or actuator position, or if a custom spec is invalid
+    pub fn build(self) -> Result<Box<dyn EcpGeometry>, EywaError> {
+        Ok(match self {
+            GeometrySpec::Box {
+                num_plastic,
+                num_sensory,
+                num_actuator,
+                nearby_count,
+                exclusions,
+            } => {
+                let ecp = BoxEcp::new(num_plastic, num_sensory, num_actuator, nearby_count);
+
+                match exclusions.is_empty() {
+                    true => Box::new(ecp),
+                    false => Box::new(ecp.with_exclusions(exclusions)?),
+                }
+            }
+            GeometrySpec::Fcc {
+                num_plastic,
+                num_sensory,
+                num_actuator,
+                nearby_count,
+            } => Box::new(FccEcp::new(
+                num_plastic,
+                num_sensory,
+                num_actuator,
+                nearby_count,
+            )),
+            GeometrySpec::Layered {
+                layer_dims,
+                num_sensory,
+                num_actuator,
+                connectivity,
+            } => Box::new(LayeredEcp::new(
+                layer_dims,
+                num_sensory,
+                num_actuator,
+                connectivity,
+            )),
+            GeometrySpec::Custom(spec) => Box::new(CustomEcp::from_spec(spec)?),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn box_sensory_locs_stay_on_the_sensory_plane() {
+        let ecp: Box<dyn EcpGeometry> = Box::new(BoxEcp::new(125, 25, 1, 27));
+        let locs: Vec<Loc> = ecp.sensory_locs().map(|(loc, _)| loc).collect();
+
+        assert_eq!(locs.len(), 25);
+        assert_eq!(locs[0], Loc::new(&[0, 0, -1]));
+        assert_eq!(locs[24], Loc::new(&[4, 4, -1]));
+
+        for (i, loc) in locs.iter().enumerate() {
+            let (x, y) = (i as u32 % 5, i as u32 / 5);
+
+            assert_eq!(loc.dims(), 3);
+            assert_eq!(ecp.sensory_plane_at(x, y), Some(*loc));
+        }
+    }
+
+    #[test]
+    fn box_sizes_round_down_to_cubes() {
+        let ecp = BoxEcp::new(1100, 2, 2, 100);
+        let report = ecp.describe();
+
+        assert_eq!(ecp.side_length(), 10);
+        assert_eq!(ecp.nearby_side_length(), 3);
+        assert_eq!(
+            (report.requested_plastic, report.actual_plastic),
+            (1100, 1000)
+        );
+        assert_eq!((report.requested_nearby, report.actual_nearby), (100, 27));
+        assert!(!report.is_exact());
+
+        // Just short of a cube rounds all the way down to the next one
+        let ecp = BoxEcp::new(999, 2, 2, 27);
+        assert_eq!(ecp.side_length(), 9);
+        assert_eq!(ecp.describe().actual_plastic, 729);
+    }
+
+    #[test]
+    fn box_nearby_cubes_have_odd_sides() {
+        // 64 nearby neurons would be a cube of side 4, which is even
+        let report = BoxEcp::new(1000, 2, 2, 64).describe();
+        assert_eq!(report.actual_nearby, 27);
+    }
+
+    #[test]
+    fn exact_box_sizes_are_reported_as_such() {
+        let ecp = BoxEcp::new(1000, 2, 2, 27);
+
+        assert_eq!(ecp.side_length(), 10);
+        assert!(ecp.describe().is_exact());
+    }
+
+    fn fcc() -> Box<dyn EcpGeometry> {
+        Box::new(FccEcp::new(1000, 4, 6, 12))
+    }
+
+    #[test]
+    fn fcc_neighbors_thin_out_toward_the_boundary() {
+        let ecp = fcc();
+        let neighbors = |loc: &[i32]| ecp.neighbor_locs(Loc::new(loc), 1).len();
+
+        assert_eq!(neighbors(&[5, 5, 6]), 12); // Interior
+        assert_eq!(neighbors(&[5, 5, 0]), 8); // Face
+        assert_eq!(neighbors(&[0, 6, 0]), 5); // Edge
+        assert_eq!(neighbors(&[0, 0, 0]), 3); // Corner
+    }
+
+    #[test]
+    fn fcc_traversal_visits_every_lattice_point_once() {
+        let ecp = fcc();
+        let rx: Vec<(Loc, String, RxNeuron)> = ecp.rx_locs().collect();
+        let distinct: HashSet<Loc> = rx.iter().map(|(loc, _, _)| *loc).collect();
+
+        assert_eq!(ecp.get_num_plastic(), 864);
+        assert_eq!(rx.len(), 864);
+        assert_eq!(distinct.len(), 864);
+        assert!(
+            distinct
+                .iter()
+                .all(|loc| loc.iter().all(|c| (0..12).contains(c))
+                    && loc.iter().sum::<i32>() % 2 == 0)
+        );
+
+        let actuators: Vec<&Loc> = rx
+            .iter()
+            .filter(|(_, _, kind)| matches!(kind, RxNeuron::Actuator))
+            .map(|(loc, _, _)| loc)
+            .collect();
+
+        assert_eq!(actuators.len(), 6);
+        assert!(actuators.iter().all(|loc| loc[2] == 11));
+    }
+
+    #[test]
+    fn fcc_traversal_covers_the_whole_sensory_layer() {
+        let ecp = fcc();
+
+        assert!(ecp.sensory_locs().all(|(loc, _)| loc[2] == -1));
+        assert_eq!(ecp.sensory_locs().count(), 72);
+    }
+
+    #[test]
+    fn fcc_nearby_picks_from_a_corner_stay_among_its_neighbors() {
+        let ecp = fcc();
+        let mut rng = StdRng::seed_from_u64(1);
+        let corner_neighbors: HashSet<String> = ecp
+            .neighbor_locs(Loc::new(&[0, 0, 0]), 1)
+            .iter()
+            .map(|loc| ecp.loc_hash(loc))
+            .collect();
+
+        assert_eq!(corner_neighbors.len(), 3);
+
+        for _ in 0..100 {
+            let hash = ecp.local_random_hash(&[0, 0, 0], &mut rng).unwrap();
+            assert!(corner_neighbors.contains(&hash));
+        }
+    }
+
+    fn node(id: &str, z: i32, nearby: &[&str]) -> NodeSpec {
+        NodeSpec {
+            id: id.to_string(),
+            loc: vec![0, 0, z],
+            nearby: nearby.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+
+    /// s -> p1 <-> p2 <-> p3 <-> p4 <-> a
+    fn chain_spec() -> EcpSpec {
+        EcpSpec {
+            sensory: vec![node("s", -1, &["p1"])],
+            plastic: vec![
+                node("p1", 0, &["p2"]),
+                node("p2", 1, &["p1", "p3"]),
+                node("p3", 2, &["p2", "p4"]),
+                node("p4", 3, &["p3", "a"]),
+            ],
+            actuator: vec![node("a", 4, &["p4"])],
+        }
+    }
+
+    #[test]
+    fn a_chain_spec_only_connects_declared_neighbors() {
+        let declared: HashSet<(i32, i32)> = vec![
+            (-1, 0),
+            (0, 1),
+            (1, 0),
+            (1, 2),
+            (2, 1),
+            (2, 3),
+            (3, 2),
+            (3, 4),
+            (4, 3),
+        ]
+        .into_iter()
+        .collect();
+
+        let spec = chain_spec();
+        let ecp = CustomEcp::from_spec(spec.clone()).unwrap();
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut picked = HashSet::new();
+
+        for node in spec
+            .sensory
+            .iter()
+            .chain(&spec.plastic)
+            .chain(&spec.actuator)
+        {
+            for _ in 0..50 {
+                let hash = ecp.local_random_hash(&node.loc, &mut rng).unwrap();
+                let target = (-1..=4)
+                    .find(|&z| ecp.loc_hash(&[0, 0, z]) == hash)
+                    .unwrap();
+
+                picked.insert((node.loc[2], target));
+            }
+        }
+
+        assert_eq!(picked, declared);
+    }
+
+    #[test]
+    fn invalid_specs_are_rejected() {
+        let mut duplicate_loc = chain_spec();
+        duplicate_loc.plastic[1].loc = vec![0, 0, 0];
+
+        assert!(matches!(
+            CustomEcp::from_spec(duplicate_loc).err(),
+            Some(EywaError::DuplicateNodeLoc(loc)) if loc == vec![0, 0, 0]
+        ));
+
+        let mut dangling = chain_spec();
+        dangling.plastic[0].nearby.push("p9".to_string());
+
+        assert!(matches!(
+            CustomEcp::from_spec(dangling).err(),
+            Some(EywaError::UnknownNeighbor { node, neighbor }) if node == "p1" && neighbor == "p9"
+        ));
+
+        let mut no_actuators = chain_spec();
+        no_actuators.actuator.clear();
+
+        assert!(matches!(
+            CustomEcp::from_spec(no_actuators).err(),
+            Some(EywaError::NoNodesOfKind(kind)) if kind == "actuator"
+        ));
+    }
+
+    #[test]
+    fn a_wall_forces_every_path_through_its_gap() {
+        let wall = vec![
+            Region::new(vec![0, 0, 4], vec![3, 8, 4]),
+            Region::new(vec![5, 0, 4], vec![8, 8, 4]),
+            Region::new(vec![4, 0, 4], vec![4, 3, 4]),
+            Region::new(vec![4, 5, 4], vec![4, 8, 4]),
+        ];
+        let gap = Loc::new(&[4, 4, 4]);
+
+        let ecp = BoxEcp::new(750, 1, 1, 27)
+            .with_exclusions(wall.clone())
+            .unwrap();
+        assert_eq!(ecp.get_num_plastic(), 729 - 80);
+
+        let traversal: &dyn EcpGeometry = &ecp;
+        assert!(traversal
+            .rx_locs()
+            .all(|(loc, _, _)| !wall.iter().any(|region| region.contains(&loc))));
+
+        let start = Loc::new(&[0, 0, 0]);
+        let motor = ecp.actuator_plane_at(0, 0).unwrap();
+
+        // Whether motor can be reached from start through nearby
+        // neurons without passing through avoid
+        let reachable = |avoid: Option<&Loc>| {
+            let mut seen: HashSet<Loc> = vec![start].into_iter().collect();
+            let mut frontier = vec![start];
+
+            while let Some(loc) = frontier.pop() {
+                for target in ecp.neighbor_locs(loc, 1) {
+                    if Some(&target) != avoid && seen.insert(target) {
+                        frontier.push(target);
+                    }
+                }
+            }
+
+            seen.contains(&motor)
+        };
+
+        assert!(reachable(None));
+        assert!(!reachable(Some(&gap)));
+    }
+
+    const LAYER_DIMS: [(u32, u32); 4] = [(6, 6), (4, 4), (8, 8), (3, 5)];
+
+    fn layered() -> Box<dyn EcpGeometry> {
+        Box::new(LayeredEcp::new(
+            LAYER_DIMS.to_vec(),
+            5,
+            4,
+            LayerConnectivity {
+                forward_p: 0.6,
+                lateral_p: 0.3,
+                backward_p: 0.1,
+                radius: 1,
+            },
+        ))
+    }
+
+    #[test]
+    fn layered_traversal_covers_layers_of_every_size() {
+        let ecp = layered();
+        let rx: Vec<(Loc, String, RxNeuron)> = ecp.rx_locs().collect();
+        let distinct: HashSet<Loc> = rx.iter().map(|(loc, _, _)| *loc).collect();
+        let plastic: Vec<&Loc> = rx
+            .iter()
+            .filter(|(_, _, kind)| matches!(kind, RxNeuron::Plastic))
+            .map(|(loc, _, _)| loc)
+            .collect();
+
+        assert_eq!(plastic.len() as u32, ecp.get_num_plastic());
+        assert_eq!(rx.len() as u32, ecp.get_num_plastic() + 4);
+        assert_eq!(distinct.len(), rx.len());
+
+        for loc in plastic {
+            let (width, height) = LAYER_DIMS[loc[2] as usize];
+            assert!(loc[0] < width as i32 && loc[1] < height as i32);
+        }
+
+        assert!(rx
+            .iter()
+            .filter(|(_, _, kind)| matches!(kind, RxNeuron::Actuator))
+            .all(|(loc, _, _)| loc[2] == 4));
+    }
+
+    #[test]
+    fn layered_picks_follow_the_connectivity() {
+        let ecp = layered();
+        let locs: HashMap<String, Loc> = ecp.rx_locs().map(|(loc, hash, _)| (hash, loc)).collect();
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut layer_counts = [0; 5];
+
+        // Sample from the middle of layer 2, whose forward layer is 3 by 5
+        for _ in 0..10000 {
+            let hash = ecp.local_random_hash(&[4, 4, 2], &mut rng).unwrap();
+            let loc = locs[&hash];
+
+            layer_counts[loc[2] as usize] += 1;
+
+            // (4, 4) of 8 by 8 maps onto (1, 2) of 3 by 5
+            if loc[2] == 3 {
+                assert!((loc[0] - 1).abs() <= 1 && (loc[1] - 2).abs() <= 1);
+            }
+        }
+
+        let fraction = |layer: usize| layer_counts[layer] as f32 / 10000.;
+
+        assert_eq!(layer_counts[0] + layer_counts[4], 0);
+        assert!((fraction(3) - 0.6).abs() < 0.03);
+        assert!((fraction(2) - 0.3).abs() < 0.03);
+        assert!((fraction(1) - 0.1).abs() < 0.03);
+    }
+
+    #[test]
+    fn four_evenly_spread_actuators_sit_in_the_corners() {
+        let ecp = BoxEcp::new(125, 1, 4, 27)
+            .with_actuator_spread(ActuatorSpread::EvenGrid)
+            .unwrap();
+
+        let corners = [(0, 0), (4, 0), (0, 4), (4, 4)];
+
+        for y in 0..5 {
+            for x in 0..5 {
+                let is_corner = corners.contains(&(x, y));
+
+                assert_eq!(ecp.actuator_plane_at(x, y).is_some(), is_corner);
+            }
+        }
+    }
+
+    #[test]
+    fn explicit_actuator_positions_must_lie_on_the_face() {
+        let out_of_range = BoxEcp::new(125, 1, 2, 27)
+            .with_actuator_spread(ActuatorSpread::Explicit(vec![(0, 0), (5, 0)]));
+
+        assert!(out_of_range.is_err());
+    }
+
+    #[test]
+    fn geometry_specs_round_trip_through_json() {
+        let specs = vec![
+            GeometrySpec::Box {
+                num_plastic: 125,
+                num_sensory: 2,
+                num_actuator: 1,
+                nearby_count: 27,
+                exclusions: vec![Region::new(vec![2, 2, 2], vec![2, 2, 2])],
+            },
+            GeometrySpec::Fcc {
+                num_plastic: 125,
+                num_sensory: 2,
+                num_actuator: 1,
+                nearby_count: 12,
+            },
+            GeometrySpec::Layered {
+                layer_dims: vec![(4, 4), (2, 2)],
+                num_sensory: 2,
+                num_actuator: 1,
+                connectivity: LayerConnectivity::default(),
+            },
+        ];
+
+        for spec in specs {
+            let json = serde_json::to_string(&spec).unwrap();
+            assert_eq!(serde_json::from_str::<GeometrySpec>(&json).unwrap(), spec);
+
+            let ecp = spec.build().unwrap();
+            assert_eq!(ecp.get_num_sensory(), 2);
+        }
+    }
+
+    #[test]
+    fn a_box_spec_builds_a_box_of_its_size() {
+        let spec: GeometrySpec = serde_json::from_str(
+            r#"{"kind": "box", "num_plastic": 27, "num_sensory": 1, "num_actuator": 1, "nearby_count": 27}"#,
+        )
+        .unwrap();
+
+        assert_eq!(spec.build().unwrap().get_num_plastic(), 27);
+    }
 }
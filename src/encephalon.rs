@@ -1,44 +1,552 @@
 use std::boxed::Box;
-use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cell::{Cell, Ref, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
 use std::rc::Rc;
-use std::time::SystemTime;
+use std::time::Duration;
 
-use crate::actuator::Actuator;
-use crate::ecp_geometry::EcpGeometry;
-use crate::neuron::synapse::synaptic_strength::SynapticStrength;
-use crate::neuron::synapse::SynapticType;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::actuator::{Actuator, ActuatorName};
+#[cfg(feature = "audit")]
+use crate::audit::ChargeLedger;
+use crate::clock::Clock;
+use crate::ecp_geometry::{EcpGeometry, Region, SizeReport};
+use crate::error::EywaError;
+use crate::journal::{EncephalonEvent, EventKind, Journal};
+use crate::loc::Loc;
+use crate::metrics::{
+    histogram, percentile, ActuatorHealth, CycleMetrics, HealthReport, PacingReport, PhaseTiming,
+    PhaseTimings, ProgressUpdate, ReflexContribution, SensorHealth, StrengthPercentiles,
+};
+use crate::neuron::synapse::synaptic_strength::{StrengthInit, SynapticStrength};
+use crate::neuron::synapse::{
+    BudgetPolicy, ImpulseSource, PlasticSynapse, PrunePolicy, RestoredSynapse, StaticSynapse,
+    Synapse, SynapseInfo, SynapseRecord, SynapseSpec, SynapticType, TargetKey, TargetingRules,
+    Transmission,
+};
 use crate::neuron::{
-    ActuatorNeuron, ChargeCycle, Neuronic, NeuronicRx, PlasticNeuron, RxNeuron, SensoryNeuron,
-    TxNeuronic,
+    ActuatorNeuron, ActuatorParams, ChargeCycle, CycleOutcome, EnergyModel, GapJunctionParams,
+    HasDynamics, HasEma, InterneuronParams, NeuronKind, Neuronic, NeuronicRx, PlasticModel,
+    PlasticParams, PlasticParamsPatch, RxNeuron, SensoryNeuron, SensoryParams, TxNeuronic,
+};
+use crate::neuron_interfaces::{
+    ActuatorGroup, ActuatorInterface, ActuatorOutput, ActuatorView, AntagonistPair, SensorSampling,
+    SensorView, SensoryEncoder, SensoryInterface,
 };
-use crate::neuron_interfaces::{ActuatorInterface, SensoryInterface};
-use crate::sensor::Sensor;
+use crate::probe::{Probe, ProbeId, ProbeReading, ProbeSample};
+use crate::reward::TargetComparator;
+use crate::sensor::adapters::{Normalization, NormalizedSensor};
+use crate::sensor::{Sensor, SensorName};
+use crate::snapshot::{
+    EncephalonSnapshot, GroupSnapshot, NeuronDynamics, NeuronSnapshot, PlasticSynapseSnapshot,
+    RngSnapshot,
+};
+
+pub mod builder;
+pub use builder::{BuildPhase, EncephalonBuilder};
 
 /// This is a high level description of a reflex.
-/// A reflex is a static synapse between a sensor
-/// and actuator neuron of a fixed strength
+/// A reflex is either a static synapse between a sensor
+/// and actuator neuron of a fixed strength, or a gate
+/// from one sensor onto another.  Reflexes are plain data, so
+/// lists of them can be kept in config files:
+///
+/// ```
+/// use eywa::prelude::*;
+///
+/// let reflexes = vec![
+///     Reflex::new("light".into(), "motor".into(), SynapticType::Excitatory, 2.),
+///     Reflex::gate("pain".into(), "light".into(), SynapticType::Inhibitory, 4.),
+/// ];
+///
+/// let json = serde_json::to_string(&reflexes).unwrap();
+/// assert_eq!(serde_json::from_str::<Vec<Reflex>>(&json).unwrap(), reflexes);
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Reflex {
-    pub sensor_name: String,
-    pub actuator_name: String,
+    pub sensor_name: SensorName,
+    pub target: ReflexTarget,
     pub synapse_type: SynapticType,
     pub strength: f32,
 }
 
+/// What a reflex's sensor acts upon
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ReflexTarget {
+    /// The sensor synapses onto the actuator's neurons
+    Actuator(ActuatorName),
+
+    /// The sensor gates the other sensor's neurons (see Reflex::gate)
+    Sensor(SensorName),
+}
+
 impl Reflex {
+    /// A reflex from the named sensor onto the named actuator.
+    /// A name that doesn't match any sensor or actuator passed
+    /// to the encephalon fails the build
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let reflex = Reflex::new("light".into(), "motor".into(), SynapticType::Excitatory, 10.);
+    /// ```
     pub fn new(
-        sensor_name: String,
-        actuator_name: String,
+        sensor_name: SensorName,
+        actuator_name: ActuatorName,
+        synapse_type: SynapticType,
+        strength: f32,
+    ) -> Reflex {
+        Reflex {
+            sensor_name,
+            target: ReflexTarget::Actuator(actuator_name),
+            synapse_type,
+            strength,
+        }
+    }
+
+    /// Gates the sensory neurons of gated_name by the firing of
+    /// the sensory neurons of sensor_name, before the gated signal
+    /// ever propagates.  At the start of each cycle, the period
+    /// each gated neuron's sensor encoded is scaled to
+    ///
+    /// - Inhibitory: period * (1 + strength * ema)
+    /// - Excitatory: period / (1 + strength * ema)
+    ///
+    /// where ema is the mean EMA of the gating sensor's neurons as
+    /// of the previous cycle.  Several gates onto the same sensor
+    /// multiply together, scaled periods are rounded to the nearest
+    /// cycle, and a period of 0 (ie never fire) stays 0.  So pain
+    /// can gate pleasure:
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let gate = Reflex::gate(
+    ///     "pain".into(),
+    ///     "pleasure".into(),
+    ///     SynapticType::Inhibitory,
+    ///     4.,
+    /// );
+    /// ```
+    pub fn gate(
+        sensor_name: SensorName,
+        gated_name: SensorName,
         synapse_type: SynapticType,
         strength: f32,
     ) -> Reflex {
         Reflex {
             sensor_name,
-            actuator_name,
+            target: ReflexTarget::Sensor(gated_name),
             synapse_type,
             strength,
         }
     }
+
+    /// The factor the gated sensor's periods are scaled by
+    /// when the gating sensor's neurons fire with this ema
+    fn gate_scale(&self, ema: f32) -> f32 {
+        let drive = 1. + (self.strength * ema).max(0.);
+
+        match self.synapse_type {
+            SynapticType::Inhibitory => drive,
+            SynapticType::Excitatory => 1. / drive,
+        }
+    }
+}
+
+/// Reflexes display as the arrows they're drawn as, with
+/// gates marked as such:
+///
+/// ```
+/// use eywa::prelude::*;
+///
+/// let reflex = Reflex::new(
+///     "forward_pain".into(),
+///     "left_forward".into(),
+///     SynapticType::Inhibitory,
+///     20.,
+/// );
+///
+/// assert_eq!(reflex.to_string(), "forward_pain -|20.0|-> left_forward (inhibitory)");
+///
+/// let gate = Reflex::gate("pain".into(), "light".into(), SynapticType::Inhibitory, 4.);
+/// assert_eq!(gate.to_string(), "pain -|4.0|-> light (inhibitory gate)");
+/// ```
+impl fmt::Display for Reflex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -|{:?}|-> ", self.sensor_name, self.strength)?;
+
+        match &self.target {
+            ReflexTarget::Actuator(name) => write!(f, "{} ({})", name, self.synapse_type),
+            ReflexTarget::Sensor(name) => write!(f, "{} ({} gate)", name, self.synapse_type),
+        }
+    }
+}
+
+/// A pattern of reflexes, for wiring symmetric robots without
+/// writing out (and getting the polarity right on) every reflex
+/// by hand.  A template expands into plain reflexes, in order.
+///
+/// ```
+/// use eywa::prelude::*;
+///
+/// // Pain drives the left wheel backward, and holds it back from going forward
+/// let reflexes = ReflexTemplate::crossed_pair(
+///     "forward_pain".into(),
+///     ("left_forward".into(), "left_backward".into()),
+///     20.,
+/// )
+/// .expand();
+///
+/// assert_eq!(reflexes.len(), 2);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReflexTemplate {
+    reflexes: Vec<Reflex>,
+}
+
+impl ReflexTemplate {
+    /// Inhibits the forward actuator and excites the backward
+    /// actuator of a pair, so that the sensor drives it into reverse
+    pub fn crossed_pair(
+        sensor_name: SensorName,
+        (forward_name, backward_name): (ActuatorName, ActuatorName),
+        strength: f32,
+    ) -> ReflexTemplate {
+        ReflexTemplate {
+            reflexes: vec![
+                Reflex::new(
+                    sensor_name.clone(),
+                    forward_name,
+                    SynapticType::Inhibitory,
+                    strength,
+                ),
+                Reflex::new(
+                    sensor_name,
+                    backward_name,
+                    SynapticType::Excitatory,
+                    strength,
+                ),
+            ],
+        }
+    }
+
+    /// Considers every sensor against every actuator, in sensor
+    /// major order.  polarity_fn is handed the sensor and actuator
+    /// names of each edge, and returns the type of its reflex, or
+    /// None if the sensor shouldn't reflex onto the actuator at all
+    pub fn all_pairs(
+        sensor_names: &[SensorName],
+        actuator_names: &[ActuatorName],
+        polarity_fn: impl Fn(&str, &str) -> Option<SynapticType>,
+        strength: f32,
+    ) -> ReflexTemplate {
+        let mut reflexes = Vec::new();
+
+        for sensor_name in sensor_names {
+            for actuator_name in actuator_names {
+                if let Some(synapse_type) =
+                    polarity_fn(sensor_name.as_str(), actuator_name.as_str())
+                {
+                    reflexes.push(Reflex::new(
+                        sensor_name.clone(),
+                        actuator_name.clone(),
+                        synapse_type,
+                        strength,
+                    ));
+                }
+            }
+        }
+
+        ReflexTemplate { reflexes }
+    }
+
+    /// Chains another template's reflexes after this one's
+    pub fn and(mut self, other: ReflexTemplate) -> ReflexTemplate {
+        self.reflexes.extend(other.reflexes);
+        self
+    }
+
+    /// The reflexes this template expands into
+    pub fn expand(self) -> Vec<Reflex> {
+        self.reflexes
+    }
+}
+
+/// Maps sensor (or actuator) names onto (x, y) coordinates
+/// of the sensory (or actuator) plane of an ecp geometry.
+///
+/// Without a layout, sensors and actuators are assigned to
+/// positions in the order the geometry traverses them, so
+/// sensors that are adjacent on a robot can end up on opposite
+/// corners of the plane.  Any names left out of a layout are
+/// still assigned in traversal order to the remaining positions.
+///
+/// Layouts serialize as a map from name to coordinate:
+///
+/// ```
+/// use eywa::prelude::*;
+///
+/// let layout = SensorLayout::new().place("left", 0, 0).place("right", 4, 0);
+/// let json = serde_json::to_string(&layout).unwrap();
+///
+/// assert_eq!(serde_json::from_str::<SensorLayout>(&json).unwrap(), layout);
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "N: Eq + Hash + Deserialize<'de>"))]
+pub struct PlaneLayout<N: Eq + Hash> {
+    positions: HashMap<N, (u32, u32)>,
+}
+
+impl<N: Eq + Hash> PlaneLayout<N> {
+    pub fn new() -> PlaneLayout<N> {
+        PlaneLayout {
+            positions: HashMap::new(),
+        }
+    }
+
+    /// Places the sensor or actuator called name at (x, y)
+    pub fn place(mut self, name: impl Into<N>, x: u32, y: u32) -> PlaneLayout<N> {
+        self.positions.insert(name.into(), (x, y));
+        self
+    }
+
+    /// Gets the coordinate at which name was placed
+    pub fn get(&self, name: &N) -> Option<(u32, u32)> {
+        self.positions.get(name).copied()
+    }
+
+    /// Iterates over every placed name and its coordinate
+    pub fn iter(&self) -> impl Iterator<Item = (&N, &(u32, u32))> {
+        self.positions.iter()
+    }
+}
+
+impl<N: Eq + Hash> Default for PlaneLayout<N> {
+    fn default() -> PlaneLayout<N> {
+        PlaneLayout::new()
+    }
+}
+
+/// Layout of sensors on the sensory plane
+pub type SensorLayout = PlaneLayout<SensorName>;
+
+/// Layout of actuators on the actuator plane
+pub type ActuatorLayout = PlaneLayout<ActuatorName>;
+
+/// Everything an encephalon builder resolves before it allocates a
+/// single neuron (see EncephalonBuilder::plan).  A plan is plain
+/// data, so it can be checked into a config repo and diffed
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BrainPlan {
+    /// The geometry's neuron counts, with any synapse budget clamp
+    pub size_report: SizeReport,
+    pub plastic_model: PlasticModel,
+    /// The base parameters of plastic neurons, which interneurons
+    /// (and Izhikevich neurons) build upon
+    pub plastic_params: PlasticParams,
+    pub sensory_params: SensoryParams,
+    pub region_overrides: Vec<(Region, PlasticParamsPatch)>,
+    /// The cluster of every sensor and of every reserved position
+    pub sensors: Vec<ClusterPlan>,
+    /// The cluster of every actuator and of every reserved position
+    pub actuators: Vec<ClusterPlan>,
+    /// The builder's reflexes, with every template expanded
+    pub reflexes: Vec<Reflex>,
+    /// Every neuron of the geometry, in traversal order.  Which
+    /// plastic neurons become interneurons is only drawn at build,
+    /// so every one of them is planned as Plastic
+    pub neurons: Vec<NeuronPlan>,
+    /// Anything about the plan that's legal but likely a mistake
+    #[serde(default)]
+    pub warnings: Vec<PlanWarning>,
+}
+
+impl BrainPlan {
+    /// The number of neurons of kind planned
+    pub fn count(&self, kind: NeuronKind) -> usize {
+        self.neurons
+            .iter()
+            .filter(|neuron| neuron.kind == kind)
+            .count()
+    }
+
+    /// Applies each region override covering loc to params, just
+    /// as Encephalon::region_params will once the brain is built
+    pub fn region_params(&self, loc: &[i32], params: PlasticParams) -> PlasticParams {
+        apply_region_overrides(&self.region_overrides, loc, params)
+    }
+}
+
+/// The cluster of positions a sensor or actuator was placed at
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClusterPlan {
+    /// None for reserved positions that aren't held for a name
+    pub name: Option<String>,
+    /// True if the positions are reserved for attaching later
+    pub reserved: bool,
+    pub locs: Vec<Vec<i32>>,
+    /// The parameters of an actuator cluster's neurons
+    pub actuator_params: Option<ActuatorParams>,
+}
+
+/// A neuron of a plan, and the parameters it'll be built with.
+/// Sensory and actuator neurons take theirs from the plan and
+/// their cluster respectively, so only plastic neurons carry any
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NeuronPlan {
+    pub loc: Vec<i32>,
+    pub kind: NeuronKind,
+    /// Plastic parameters, with every region override applied
+    pub params: Option<PlasticParams>,
+}
+
+/// Something about a plan that builds fine but probably
+/// doesn't behave as intended
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PlanWarning {
+    /// A reflex at (or within a rounding error of) the fire
+    /// threshold of its actuator's neurons under Exclusive
+    /// threshold mode, which on its own never fires them
+    ReflexAtThreshold { reflex: Reflex, fire_threshold: f32 },
+}
+
+impl fmt::Display for PlanWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanWarning::ReflexAtThreshold {
+                reflex,
+                fire_threshold,
+            } => write!(
+                f,
+                "reflex {} is at its actuator's exclusive fire threshold of {:?}, \
+                 so it can't fire the actuator on its own",
+                reflex, fire_threshold
+            ),
+        }
+    }
+}
+
+/// How near a reflex's strength can be to its actuator's
+/// fire threshold, relative to the threshold, before the
+/// plan warns of it
+pub const REFLEX_THRESHOLD_TOLERANCE: f32 = 1e-3;
+
+/// Applies each override covering loc to params, in the
+/// order the overrides were added, so the last match wins
+fn apply_region_overrides(
+    region_overrides: &[(Region, PlasticParamsPatch)],
+    loc: &[i32],
+    params: PlasticParams,
+) -> PlasticParams {
+    region_overrides
+        .iter()
+        .filter(|(region, _)| region.contains(loc))
+        .fold(params, |params, (_, patch)| patch.apply(params))
+}
+
+/// Iterator that runs a cycle of an encephalon each time
+/// it's advanced, yielding the metrics of that cycle
+pub struct Cycles<'a> {
+    encephalon: &'a Encephalon,
+}
+
+impl<'a> Cycles<'a> {
+    /// Runs n cycles per item instead of one, yielding
+    /// the metrics of those n cycles absorbed into one
+    pub fn every(self, n: u32) -> Every<'a> {
+        Every {
+            encephalon: self.encephalon,
+            n,
+        }
+    }
+}
+
+impl<'a> Iterator for Cycles<'a> {
+    type Item = CycleMetrics;
+
+    fn next(&mut self) -> Option<CycleMetrics> {
+        Some(self.encephalon.run_cycle())
+    }
+}
+
+/// Iterator that runs n cycles of an encephalon each time it's
+/// advanced, yielding the metrics of those cycles absorbed into one.
+/// With n of 0 no cycles are run, and the iterator ends immediately
+pub struct Every<'a> {
+    encephalon: &'a Encephalon,
+    n: u32,
+}
+
+impl<'a> Iterator for Every<'a> {
+    type Item = CycleMetrics;
+
+    fn next(&mut self) -> Option<CycleMetrics> {
+        if self.n == 0 {
+            return None;
+        }
+
+        let mut metrics = self.encephalon.run_cycle();
+
+        for _ in 1..self.n {
+            metrics.absorb(self.encephalon.run_cycle());
+        }
+
+        Some(metrics)
+    }
+}
+
+/// An electrical coupling between two adjacent plastic neurons
+struct GapJunction {
+    a: Rc<dyn NeuronicRx>,
+    b: Rc<dyn NeuronicRx>,
+    conductance: f32,
+}
+
+/// A cluster of sensory (or actuator) neurons held for a sensor (or
+/// actuator) attached after building, by name if the layout placed it
+struct ReservedSlot<T, N> {
+    name: Option<N>,
+    cluster: Vec<Rc<T>>,
+}
+
+impl<T, N> ReservedSlot<T, N> {
+    fn new(name: Option<N>, cluster: Vec<Rc<T>>) -> ReservedSlot<T, N> {
+        ReservedSlot { name, cluster }
+    }
+}
+
+/// Takes the reserved slot held for name, or else
+/// the first slot that isn't held for any name
+fn take_reserved_slot<T, N: PartialEq>(
+    slots: &mut Vec<ReservedSlot<T, N>>,
+    name: &N,
+) -> Option<ReservedSlot<T, N>> {
+    let index = slots
+        .iter()
+        .position(|slot| slot.name.as_ref() == Some(name))
+        .or_else(|| slots.iter().position(|slot| slot.name.is_none()))?;
+
+    Some(slots.remove(index))
+}
+
+/// The quantity of each neuron an activity slice is drawn from
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldKind {
+    /// The neuron's EMA firing frequency
+    Ema,
+
+    /// 1.0 if the neuron fired on the last cycle, else 0.0
+    FireLastCycle,
+
+    /// The charge the neuron will evaluate on the next cycle
+    Charge,
 }
 
 /// This is the brains of the operation (lol).
@@ -54,18 +562,81 @@ pub struct Encephalon {
     cycle_count: RefCell<u64>,
     ecp_geometry: Box<dyn EcpGeometry>,
     rx_neurons: RefCell<HashMap<String, Rc<dyn NeuronicRx>>>,
+    rx_hashes: RefCell<Vec<String>>, //Hashes of rx neurons in traversal order
+    plastic_hashes: RefCell<Vec<String>>, //Hashes of the plastic rx neurons, in the same order
+    interneuron_hashes: RefCell<HashSet<String>>,
     sensory_neurons: RefCell<HashMap<String, Rc<SensoryNeuron>>>,
-    actuator_interfaces: RefCell<HashMap<String, ActuatorInterface>>,
-    sensory_interfaces: RefCell<HashMap<String, SensoryInterface>>,
+    sensory_hashes: RefCell<Vec<String>>, //Hashes of sensory neurons in traversal order
+    actuator_interfaces: RefCell<HashMap<ActuatorName, ActuatorInterface>>,
+    sensory_interfaces: RefCell<HashMap<SensorName, SensoryInterface>>,
+    sensor_locs: RefCell<HashMap<SensorName, Vec<Loc>>>,
+    actuator_locs: RefCell<HashMap<ActuatorName, Vec<Loc>>>,
     reflexes: Vec<Reflex>,
+    actuator_groups: Vec<ActuatorGroup>,
+    antagonist_pairs: Vec<AntagonistPair>,
+    comparators: Vec<TargetComparator>,
+    reward_modulation: bool,
+    reward: Cell<f32>,
+    learning_rate: Cell<f32>,
+    plasticity_frozen: Cell<bool>,
+    eval_mode: Cell<bool>,
+    probing: Cell<bool>, //Cycles run by influence_probe leave the world alone
+    eval_cache: RefCell<Option<EvalCache>>, //Transmissions of every neuron, while in eval mode
+    connectome_generation: Cell<u64>, //Bumped whenever the synapses change
+    pairing_window: u32, //Cycles after a firing over which its targets are paired with it
+    targeting_rules: TargetingRules,
+    max_cycles_per_sensor_update: Cell<Option<u64>>,
+    cycles_since_sensor_update: Cell<u64>,
+    prune_policy: Box<dyn PrunePolicy>,
+    synapse_budget: Option<(usize, BudgetPolicy)>, //Global cap on plastic synapses
+    plastic_synapse_count: Cell<usize>,
+    energy_model: Option<EnergyModel>,
+    energy: Cell<f32>, //Energy in store, which runs into debt when overspent
+    plastic_params: PlasticParams,
+    plastic_model: PlasticModel,
+    interneuron_params: InterneuronParams,
+    region_overrides: Vec<(Region, PlasticParamsPatch)>,
+    gap_junctions: RefCell<Vec<GapJunction>>,
+    probes: RefCell<Vec<Probe>>,
+    probe_hashes: RefCell<HashMap<String, Vec<usize>>>,
+    probe_capacity: usize,
+    detailed_metrics: bool, //If true, plastic strengths are swept into every cycle's metrics
+    legacy_phase_order: bool, //If true, actuator interfaces run before neurons
+    synapse_budget_clamp: Option<(usize, usize)>, //Requested and actual synapse budget, if clamped
+    plan_warnings: Vec<PlanWarning>,
+    journal: RefCell<Journal>,
+    groups: RefCell<HashMap<String, Vec<Loc>>>, //Named groups of neurons
+    lesioned: RefCell<HashSet<String>>,         //Hashes of rx neurons that can't take charge
+    #[cfg(feature = "audit")]
+    charge_ledger: Cell<ChargeLedger>, //The ledger of the last cycle
+    small_world_p: f32,                         //Probability a new synapse targets any rx neuron
+    strength_init: StrengthInit, //Where new plastic synapses start along their strength curve
+    clock: Rc<dyn Clock>,        //Source of wall time
+    profile_cycles: bool,        //If true, each phase of every cycle is timed
+    phase_timings: RefCell<PhaseTimings>,
+    reserved_sensors: RefCell<Vec<ReservedSlot<SensoryNeuron, SensorName>>>,
+    reserved_actuators: RefCell<Vec<ReservedSlot<ActuatorNeuron, ActuatorName>>>,
+    sensory_encoder: Rc<dyn SensoryEncoder>, //Settings of interfaces attached after building
+    injected_sensors: HashMap<SensorName, Rc<Cell<f32>>>, //Values measured by injected sensors
+    auto_normalize: bool,
+    assert_sensor_range: bool,
+    isolate_user_code: bool,
+    actuator_failure_threshold: u32,
+    rng: RefCell<ChaCha20Rng>, //Source of all randomness, seeded by the builder
+    rng_key: Cell<([u8; 32], u64)>, //Seed and stream the rng started from
 }
 
 impl Encephalon {
     /// Creates a new encephalon.
+    ///
+    /// This panics if the sensors or actuators don't fit the
+    /// ecp_geometry.  Use EncephalonBuilder for more control over
+    /// construction and to handle these errors gracefully
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         ecp_geometry: Box<dyn EcpGeometry>,
-        mut sensors: Vec<Box<dyn Sensor>>,
-        mut actuators: Vec<Box<dyn Actuator>>,
+        sensors: Vec<Box<dyn Sensor>>,
+        actuators: Vec<Box<dyn Actuator>>,
 
         //Parameters for neurons
         fire_threshold: f32,
@@ -80,224 +651,4532 @@ impl Encephalon {
         //List of reflex synapses
         reflexes: Vec<Reflex>,
     ) -> Rc<Encephalon> {
-        if ecp_geometry.get_num_sensory() != sensors.len() as u32 {
-            panic!(
-                "The number of sensors passed to the encephalon doesn't \
-             match the number of sensor neuron positions within the specified ecp_geometry"
-            );
-        } else if ecp_geometry.get_num_actuator() != actuators.len() as u32 {
-            panic!(
-                "The number of actuators passed to the encephalon doesn't \
-             match the number of actuator neuron positions within the specified ecp_geometry"
-            );
-        }
-
-        let new_encephalon = Rc::new(Encephalon {
-            cycle_count: RefCell::new(0),
-            ecp_geometry,
-            rx_neurons: RefCell::new(HashMap::new()),
-            sensory_neurons: RefCell::new(HashMap::new()),
-            actuator_interfaces: RefCell::new(HashMap::new()),
-            sensory_interfaces: RefCell::new(HashMap::new()),
-            reflexes,
-        });
+        let builder = EncephalonBuilder::new()
+            .fire_threshold(fire_threshold)
+            .ema_alpha(ema_alpha)
+            .synaptic_strength_generator(synaptic_strength_generator)
+            .synapse_type_threshold(synapse_type_threshold)
+            .max_plastic_synapses(max_plastic_synapses)
+            .sensory_encoder(sensory_encoder)
+            .reflexes(reflexes);
 
-        // Populate the encephalon's Rx neurons
-        let mut ecp_rx_option = Some(new_encephalon.ecp_geometry.first_rx_loc());
-
-        loop {
-            if let Some((loc, hash, neuron_type)) = &ecp_rx_option {
-                match neuron_type {
-                    RxNeuron::Actuator => {
-                        // println!("Made actuator neuron!");
-                        let new_neuron = Rc::new(ActuatorNeuron::new(
-                            Rc::clone(&new_encephalon),
-                            fire_threshold,
-                            ema_alpha,
-                        ));
-
-                        let new_rx_neuron = Rc::clone(&new_neuron);
-
-                        new_encephalon.rx_neurons.borrow_mut().insert(
-                            hash.clone(),
-                            Rc::clone(&(new_rx_neuron as Rc<dyn NeuronicRx>)),
-                        );
-
-                        let curr_actuator_option = actuators.pop();
-
-                        if let Some(curr_actuator) = curr_actuator_option {
-                            new_encephalon.actuator_interfaces.borrow_mut().insert(
-                                curr_actuator.get_name(),
-                                ActuatorInterface::new(Rc::clone(&new_neuron), curr_actuator),
-                            );
-                        }
-                    }
-                    RxNeuron::Plastic => {
-                        // println!("Made plastic neuron!");
-                        new_encephalon.rx_neurons.borrow_mut().insert(
-                            hash.clone(),
-                            Rc::new(PlasticNeuron::new(
-                                Rc::clone(&new_encephalon),
-                                fire_threshold,
-                                max_plastic_synapses,
-                                Rc::clone(&synaptic_strength_generator),
-                                synapse_type_threshold,
-                                ema_alpha,
-                                loc.clone(),
-                            )),
-                        );
-                    }
-                };
+        match builder.build(ecp_geometry, sensors, actuators) {
+            Ok(encephalon) => encephalon,
+            Err(e) => panic!("{}", e),
+        }
+    }
 
-                ecp_rx_option = new_encephalon.ecp_geometry.next_rx_loc(loc.clone());
-            } else {
-                break;
-            }
+    /// Runs one full cycle of the encephalon, returning
+    /// metrics aggregated from every neuron's outcome.
+    ///
+    /// A cycle samples the sensors, then runs the sensory neurons,
+    /// then the rx neurons, and last hands each actuator what its
+    /// neurons did.  An impulse lands the cycle after it's sent, so
+    /// an actuator driven straight from a sensor (ie by a reflex) is
+    /// updated exactly one cycle after the sensor is sampled.  See
+    /// EncephalonBuilder::legacy_phase_order for the old order
+    pub fn run_cycle(&self) -> CycleMetrics {
+        if !self.probing.get() && self.sensors_stale() {
+            return CycleMetrics {
+                stalled: true,
+                ..CycleMetrics::new(self.get_cycle_count())
+            };
         }
 
-        // Populate the encephalon's sensory_neurons
-        let mut ecp_sensory_option = Some(new_encephalon.ecp_geometry.first_sensory_loc());
+        self.uptick_cycle_count();
 
-        loop {
-            if let Some((loc, hash)) = &ecp_sensory_option {
-                let new_neuron = Rc::new(SensoryNeuron::new(
-                    Rc::clone(&new_encephalon),
-                    max_plastic_synapses,
-                    Rc::clone(&synaptic_strength_generator),
-                    synapse_type_threshold,
-                    ema_alpha,
-                    loc.clone(),
-                ));
+        // Anything moved between cycles (ie injected charge)
+        // balances on its own, so only this cycle is audited
+        #[cfg(feature = "audit")]
+        crate::audit::take();
 
-                new_encephalon
-                    .sensory_neurons
-                    .borrow_mut()
-                    .insert(hash.clone(), Rc::clone(&new_neuron));
+        let cycle = self.get_cycle_count();
+        let mut metrics = CycleMetrics::new(cycle);
+        let mut fresh = false;
+        let (sensor_panics, actuator_panics) = self.user_code_panics();
+        let range_violations = self.sensor_range_violations();
 
-                let curr_sensor_option = sensors.pop();
+        // Cycle sensory interfaces
+        let phase_start = self.profile_start();
 
-                if let Some(curr_sensor) = curr_sensor_option {
-                    new_encephalon.sensory_interfaces.borrow_mut().insert(
-                        curr_sensor.get_name(),
-                        SensoryInterface::new(curr_sensor, sensory_encoder, Rc::clone(&new_neuron)),
-                    );
+        // A probe forces the period of every sensor, so
+        // there's nothing to gain by measuring them
+        if !self.probing.get() {
+            for sensory_interface in self.sensory_interfaces.borrow_mut().values_mut() {
+                if sensory_interface.run_cycle(cycle) {
+                    metrics.sensors_sampled += 1;
                 }
 
-                ecp_sensory_option = new_encephalon.ecp_geometry.next_sensory_loc(loc.clone());
-            } else {
-                break;
+                fresh |= sensory_interface.is_fresh();
+            }
+
+            match fresh {
+                true => self.cycles_since_sensor_update.set(0),
+                false => self
+                    .cycles_since_sensor_update
+                    .set(self.cycles_since_sensor_update.get() + 1),
             }
         }
 
-        new_encephalon.form_reflex_synapses();
+        self.profile_end(phase_start, |timings| &mut timings.sensory_interfaces);
 
-        new_encephalon
-    }
+        if self.legacy_phase_order && !self.probing.get() {
+            self.run_actuator_interfaces(cycle, &mut metrics);
+        }
 
-    /// Runs one full cycle of the encephalon
-    pub fn run_cycle(&self) {
-        self.uptick_cycle_count();
+        self.run_sensor_gates();
 
-        // Cycle sensory interfaces
-        for sensory_interface in self.sensory_interfaces.borrow_mut().values_mut() {
-            sensory_interface.run_cycle();
+        // Cycle sensory neurons, always in traversal order so
+        // that a seeded encephalon draws from its rng in the same
+        // order on every run
+        let phase_start = self.profile_start();
+        let eval_cache = self.current_eval_cache();
+        let sensory_neurons = self.sensory_neurons.borrow();
+
+        for (i, hash) in self.sensory_hashes.borrow().iter().enumerate() {
+            let sensory_neuron = &sensory_neurons[hash];
+            let outcome = match &eval_cache {
+                Some(cache) => run_cycle_eval(&**sensory_neuron, &cache.sensory[i], cycle),
+                None => sensory_neuron.run_cycle(cycle),
+            };
+            metrics.record_sensory(outcome);
+            self.record_probes(
+                hash,
+                outcome,
+                ProbeReading::Period(sensory_neuron.effective_period()),
+            );
         }
 
-        // Cycle actuator interfaces
-        for actuator_interface in self.actuator_interfaces.borrow().values() {
-            actuator_interface.run_cycle();
+        self.profile_end(phase_start, |timings| &mut timings.sensory_neurons);
+
+        // Cycle rx neurons, once gap junctions have evened
+        // out the charge the rx neurons are about to evaluate
+        let phase_start = self.profile_start();
+        self.run_gap_junctions(cycle);
+        self.run_lesions(cycle);
+
+        let rx_neurons = self.rx_neurons.borrow();
+
+        for (i, hash) in self.rx_hashes.borrow().iter().enumerate() {
+            let outcome = match &eval_cache {
+                Some(cache) => run_cycle_eval(&*rx_neurons[hash], &cache.rx[i], cycle),
+                None => rx_neurons[hash].run_cycle(cycle),
+            };
+            metrics.record_rx(outcome);
+            self.record_probes(hash, outcome, ProbeReading::Charge(outcome.charge_consumed));
         }
 
-        // let mut sensor_ema_total: f32 = 0.0;
+        self.profile_end(phase_start, |timings| &mut timings.rx_neurons);
 
-        // Cycle sensory neurons
-        for sensory_neuron in self.sensory_neurons.borrow().values() {
-            // sensor_ema_total += sensory_neuron.run_cycle();
-            sensory_neuron.run_cycle();
+        if !self.legacy_phase_order && !self.probing.get() {
+            self.run_actuator_interfaces(cycle, &mut metrics);
         }
 
-        // let sensor_ema_average = sensor_ema_total / self.sensory_neurons.borrow().len() as f32;
+        let (sensor_panics_now, actuator_panics_now) = self.user_code_panics();
+        metrics.sensor_panics = (sensor_panics_now - sensor_panics) as u32;
+        metrics.actuator_panics = (actuator_panics_now - actuator_panics) as u32;
+        metrics.sensor_range_violations =
+            (self.sensor_range_violations() - range_violations) as u32;
 
-        // let mut rx_ema_total: f32 = 0.;
+        // Every fire of the cycle is paid for at once, so each
+        // neuron saw the same fatigue while the cycle ran
+        if let Some(energy_model) = &self.energy_model {
+            let fires = metrics.sensory_fired + metrics.rx_fired;
+            let energy = energy_model.after_cycle(self.energy.get(), fires, metrics.transmissions);
+
+            self.energy.set(energy);
+            metrics.energy = Some(energy);
+        }
 
-        // Cycle rx neurons
-        for rx_neuron in self.rx_neurons.borrow().values() {
-            // rx_ema_total += rx_neuron.run_cycle();
-            rx_neuron.run_cycle();
+        if self.detailed_metrics {
+            metrics.strength_percentiles = Some(StrengthPercentiles::from_sorted(
+                &self.sorted_plastic_strengths(),
+            ));
         }
 
-        // let rx_ema_average = rx_ema_total / self.rx_neurons.borrow().len() as f32;
+        #[cfg(feature = "audit")]
+        self.charge_ledger.set(crate::audit::check(cycle));
 
-        // println!("Sensor EMA: {}, Rx EMA: {}", sensor_ema_average, rx_ema_average);
+        metrics
     }
 
-    /// Runs a certain number of full cycles
-    pub fn run_n_cycles(&self, n: u32) {
-        let mut start = SystemTime::now();
+    /// Cycles the actuator interfaces, leaving grouped actuators
+    /// to be set once their group's values have competed
+    fn run_actuator_interfaces(&self, cycle: u64, metrics: &mut CycleMetrics) {
+        let phase_start = self.profile_start();
 
-        for i in 0..n {
-            self.run_cycle();
+        for (name, actuator_interface) in self.actuator_interfaces.borrow().iter() {
+            let grouped = self
+                .actuator_groups
+                .iter()
+                .any(|group| group.actuator_names.contains(name));
 
-            if i % 100 == 0 {
-                println!(
-                    "Cycle: {} Elapsed time: {}",
-                    i,
-                    start.elapsed().unwrap().as_secs_f32()
-                );
-                start = SystemTime::now();
+            if !grouped {
+                metrics.record_actuator(&actuator_interface.run_cycle(cycle));
             }
         }
+
+        self.run_actuator_groups(cycle, metrics);
+        self.run_antagonist_pairs(metrics);
+        self.run_comparators(cycle, metrics);
+        self.profile_end(phase_start, |timings| &mut timings.actuator_interfaces);
+
+        metrics.disabled_actuators = self
+            .actuator_interfaces
+            .borrow()
+            .values()
+            .filter(|interface| interface.is_disabled())
+            .count() as u32;
     }
 
-    /// Upticks cycle count by 1
-    fn uptick_cycle_count(&self) {
-        *self.cycle_count.borrow_mut() += 1;
+    /// Drains the charge of every lesioned neuron before it's
+    /// evaluated, so that lesioned neurons never fire
+    fn run_lesions(&self, cycle: u64) {
+        let rx_neurons = self.rx_neurons.borrow();
+
+        for hash in self.lesioned.borrow().iter() {
+            if let Some(neuron) = rx_neurons.get(hash) {
+                let charge = neuron.peek_charge(cycle);
+
+                #[cfg(feature = "audit")]
+                crate::audit::record(|ledger| ledger.injected -= charge as f64);
+
+                neuron.shift_charge(cycle, -charge);
+            }
+        }
     }
 
-    /// Forms static reflex synapses from the list
-    /// of reflexes passed into Encephalon during creation
-    fn form_reflex_synapses(&self) {
+    /// Scales the periods of every gated sensor's neurons by the
+    /// firing of the sensors gating them, as laid out in Reflex::gate
+    fn run_sensor_gates(&self) {
+        let sensory_interfaces = self.sensory_interfaces.borrow();
+        let mut scales: HashMap<&SensorName, f32> = HashMap::new();
+
         for reflex in &self.reflexes {
-            if let Some(sensor) = self.sensory_interfaces.borrow().get(&reflex.sensor_name) {
-                if let Some(actuator) = self.actuator_interfaces.borrow().get(&reflex.actuator_name)
-                {
-                    sensor.sensory_neuron.add_static_synapse(
-                        reflex.strength,
-                        reflex.synapse_type,
-                        Rc::clone(&(Rc::clone(&actuator.actuator_neuron) as Rc<dyn NeuronicRx>)),
-                    );
+            if let ReflexTarget::Sensor(gated_name) = &reflex.target {
+                if let Some(gating) = sensory_interfaces.get(&reflex.sensor_name) {
+                    let neurons = &gating.sensory_neurons;
+                    let ema = neurons.iter().map(|neuron| neuron.ema()).sum::<f32>()
+                        / neurons.len().max(1) as f32;
+
+                    *scales.entry(gated_name).or_insert(1.) *= reflex.gate_scale(ema);
+                }
+            }
+        }
+
+        for (gated_name, scale) in scales {
+            if let Some(gated) = sensory_interfaces.get(gated_name) {
+                for neuron in &gated.sensory_neurons {
+                    neuron.set_period_scale(scale);
                 }
             }
         }
     }
 
-    /// Gets the elapsed cycle count of the encephalon.
-    /// The cycle count dictates when sensor neurons fire,
-    /// and also the ChargeCycle
-    pub fn get_cycle_count(&self) -> u32 {
-        *self.cycle_count.borrow() as u32
+    /// Moves charge across each gap junction from the more
+    /// charged neuron to the less charged neuron.  The charge
+    /// is conserved, unless it runs into a neuron's charge floor
+    fn run_gap_junctions(&self, cycle: u64) {
+        for junction in self.gap_junctions.borrow().iter() {
+            let delta = junction.conductance
+                * (junction.a.peek_charge(cycle) - junction.b.peek_charge(cycle));
+
+            junction.a.shift_charge(cycle, -delta);
+            junction.b.shift_charge(cycle, delta);
+        }
     }
 
-    /// Indicates the parity of the charge cycle, which allows
-    /// neurons to fire throughout a graphical structure without
-    /// conflicting or incorrect behavior
-    pub fn get_charge_cycle(&self) -> ChargeCycle {
-        if *self.cycle_count.borrow() % 2 == 0 {
-            ChargeCycle::Even
-        } else {
-            ChargeCycle::Odd
+    /// Couples each pair of adjacent plastic neurons with
+    /// a gap junction with the given probability
+    fn form_gap_junctions(&self, params: GapJunctionParams) {
+        let plastic_locs: Vec<(Loc, String)> = self
+            .ecp_geometry
+            .rx_locs()
+            .filter(|(_, _, neuron_type)| matches!(neuron_type, RxNeuron::Plastic))
+            .map(|(loc, hash, _)| (loc, hash))
+            .collect();
+
+        let plastic_hashes: HashSet<&String> = plastic_locs.iter().map(|(_, hash)| hash).collect();
+
+        let rx_neurons = self.rx_neurons.borrow();
+        let mut gap_junctions = self.gap_junctions.borrow_mut();
+
+        for (loc, hash) in &plastic_locs {
+            for neighbor in self.ecp_geometry.neighbor_locs(*loc, 1) {
+                let neighbor_hash = self.ecp_geometry.loc_hash(&neighbor);
+
+                // Each pair is only considered from the side with the lesser hash
+                if neighbor_hash <= *hash || !plastic_hashes.contains(&neighbor_hash) {
+                    continue;
+                }
+
+                if self.random_bool(params.probability) {
+                    gap_junctions.push(GapJunction {
+                        a: Rc::clone(&rx_neurons[hash]),
+                        b: Rc::clone(&rx_neurons[&neighbor_hash]),
+                        conductance: params.conductance,
+                    });
+                }
+            }
         }
     }
 
-    /// Finds a random neuron within the vicinity of loc
-    /// which allows neurons to make new random connections
-    pub fn local_random_neuron(&self, loc: &Vec<i32>) -> Option<Rc<dyn NeuronicRx>> {
-        let hash_option = self.ecp_geometry.local_random_hash(loc);
-        if let Some(hash) = hash_option {
-            if let Some(rx_ref) = self.rx_neurons.borrow().get(&hash) {
-                return Some(Rc::clone(rx_ref));
+    /// Sets the control values of each actuator group's
+    /// members by transforming their raw (ema) values
+    /// according to the group's mode
+    fn run_actuator_groups(&self, cycle: u64, metrics: &mut CycleMetrics) {
+        let actuator_interfaces = self.actuator_interfaces.borrow();
+
+        for group in &self.actuator_groups {
+            let members: Vec<&ActuatorInterface> = group
+                .actuator_names
+                .iter()
+                .filter_map(|name| actuator_interfaces.get(name))
+                .collect();
+
+            let values: Vec<f32> = members
+                .iter()
+                .map(|interface| interface.read_ema_frequency())
+                .collect();
+
+            for (interface, value) in members.iter().zip(group.mode.transform(&values)) {
+                metrics.record_actuator(&interface.update(cycle, value));
             }
         }
-        None
+    }
+
+    /// Sets the actuator of each antagonist pair to the pair's value
+    fn run_antagonist_pairs(&self, metrics: &mut CycleMetrics) {
+        let actuator_interfaces = self.actuator_interfaces.borrow();
+
+        for pair in &self.antagonist_pairs {
+            let value = pair_value(pair, &actuator_interfaces);
+            metrics.record_actuator(&pair.update(value, self.isolate_user_code));
+        }
+    }
+
+    /// The value of the antagonist pair called name, ie the value of
+    /// its agonist less that of its antagonist, decoded and clamped
+    /// to [-1, 1].  Driving either member shows up in the sign
+    pub fn actuator_pair_value(&self, name: &str) -> Result<f32, EywaError> {
+        match self.antagonist_pairs.iter().find(|pair| pair.name == name) {
+            Some(pair) => Ok(pair_value(pair, &self.actuator_interfaces.borrow())),
+            None => Err(EywaError::UnknownName(name.to_string())),
+        }
+    }
+
+    /// Compares each comparator's actuator against its target,
+    /// summing their rewards into the global reward signal
+    fn run_comparators(&self, cycle: u64, metrics: &mut CycleMetrics) {
+        if self.comparators.is_empty() {
+            return;
+        }
+
+        let actuator_interfaces = self.actuator_interfaces.borrow();
+        let mut reward = 0.0;
+
+        for comparator in &self.comparators {
+            let ema = actuator_interfaces
+                .get(&comparator.actuator_name)
+                .map_or(0.0, |interface| interface.read_ema_frequency());
+
+            let error = comparator.error(cycle, ema);
+            reward += comparator.reward(error);
+
+            metrics
+                .comparator_errors
+                .push((comparator.actuator_name.clone(), error));
+        }
+
+        metrics.reward = reward;
+        self.reward.set(reward);
+    }
+
+    /// The global reward signal of the most recent cycle
+    pub fn reward(&self) -> f32 {
+        self.reward.get()
+    }
+
+    /// True if plasticity is reward modulated and the encephalon
+    /// is being punished, in which case synapses whose targets
+    /// fired after them weaken, and the rest strengthen
+    pub fn plasticity_reversed(&self) -> bool {
+        self.reward_modulation && self.reward.get() < 0.
+    }
+
+    /// The factor every plastic synapse's strengthening
+    /// and weakening step is multiplied by
+    pub fn learning_rate(&self) -> f32 {
+        self.learning_rate.get()
+    }
+
+    /// Sets the learning rate, which takes effect on the next
+    /// cycle.  This is how learning rate schedules are run, and
+    /// a rate of 0 freezes the strengths of plastic synapses
+    pub fn set_learning_rate(&self, learning_rate: f32) {
+        self.learning_rate.set(learning_rate.max(0.));
+    }
+
+    /// True if neurons neither form nor prune plastic synapses,
+    /// nor strengthen or weaken them
+    pub fn plasticity_frozen(&self) -> bool {
+        self.plasticity_frozen.get()
+    }
+
+    /// Freezes (or thaws) plasticity from the next cycle on.
+    /// Unlike a learning rate of 0, a frozen encephalon doesn't
+    /// form or prune synapses either, so its wiring holds still
+    pub fn freeze_plasticity(&self, frozen: bool) {
+        self.plasticity_frozen.set(frozen);
+    }
+
+    /// True if the encephalon is in eval mode (see set_eval_mode)
+    pub fn eval_mode(&self) -> bool {
+        self.eval_mode.get()
+    }
+
+    /// Puts the encephalon into (or takes it out of) eval mode from
+    /// the next cycle on.  In eval mode plasticity is frozen, and
+    /// rather than cycling each neuron's plasticity and firing its
+    /// synapses one by one, the encephalon fires a cache of every
+    /// synapse's impulse.  This makes evaluating a trained brain
+    /// over long recordings much cheaper, without changing what it
+    /// does: a brain in eval mode acts just as it would frozen, and
+    /// can enter and leave eval mode mid-run
+    pub fn set_eval_mode(&self, eval_mode: bool) {
+        self.eval_mode.set(eval_mode);
+        self.eval_cache.borrow_mut().take();
+    }
+
+    /// The transmissions of every neuron in traversal order, built
+    /// afresh whenever the connectome generation has moved on, or
+    /// None outside of eval mode
+    fn current_eval_cache(&self) -> Option<Ref<'_, EvalCache>> {
+        if !self.eval_mode.get() {
+            return None;
+        }
+
+        let stale = match &*self.eval_cache.borrow() {
+            Some(cache) => cache.generation != self.connectome_generation.get(),
+            None => true,
+        };
+
+        if stale {
+            let sensory_neurons = self.sensory_neurons.borrow();
+            let rx_neurons = self.rx_neurons.borrow();
+
+            *self.eval_cache.borrow_mut() = Some(EvalCache {
+                generation: self.connectome_generation.get(),
+                sensory: self
+                    .sensory_hashes
+                    .borrow()
+                    .iter()
+                    .map(|hash| sensory_neurons[hash].transmissions())
+                    .collect(),
+                rx: self
+                    .rx_hashes
+                    .borrow()
+                    .iter()
+                    .map(|hash| rx_neurons[hash].transmissions())
+                    .collect(),
+            });
+        }
+
+        Ref::filter_map(self.eval_cache.borrow(), Option::as_ref).ok()
+    }
+
+    /// The number of cycles after a neuron fires over which
+    /// its plastic synapses' targets are paired with it
+    pub fn pairing_window(&self) -> u32 {
+        self.pairing_window
+    }
+
+    /// The energy in store, if the encephalon has an energy model
+    pub fn energy(&self) -> Option<f32> {
+        self.energy_model.map(|_| self.energy.get())
+    }
+
+    /// The factor the fire thresholds of plastic neurons are
+    /// scaled by this cycle, which stays 1 without an energy model
+    pub fn fatigue(&self) -> f32 {
+        match &self.energy_model {
+            Some(energy_model) => energy_model.fatigue(self.energy.get()),
+            None => 1.,
+        }
+    }
+
+    /// Stalls the encephalon once n cycles in a row have run
+    /// without any sensor measuring a fresh value, so it doesn't
+    /// race ahead learning from frozen inputs.  Stalled cycles
+    /// don't advance the encephalon, and it resumes as soon as
+    /// a sensor measures a fresh value again
+    pub fn set_max_cycles_per_sensor_update(&self, n: u64) {
+        self.max_cycles_per_sensor_update.set(Some(n));
+    }
+
+    /// Lets the encephalon run however stale its sensors get
+    pub fn clear_max_cycles_per_sensor_update(&self) {
+        self.max_cycles_per_sensor_update.set(None);
+    }
+
+    /// True if the sensors have been stale for as many cycles as
+    /// the encephalon allows, and polling them again doesn't turn
+    /// up a fresh value
+    fn sensors_stale(&self) -> bool {
+        let max_cycles = match self.max_cycles_per_sensor_update.get() {
+            Some(max_cycles) => max_cycles,
+            None => return false,
+        };
+
+        if self.cycles_since_sensor_update.get() < max_cycles {
+            return false;
+        }
+
+        let cycle = self.get_cycle_count();
+        let mut fresh = false;
+
+        for sensory_interface in self.sensory_interfaces.borrow_mut().values_mut() {
+            fresh |= sensory_interface.poll(cycle);
+        }
+
+        if fresh {
+            self.cycles_since_sensor_update.set(0);
+        }
+
+        !fresh
+    }
+
+    /// True if the encephalon keeps a journal of synapse lifecycle
+    /// events, so callers can skip building events nobody records
+    pub fn journaling(&self) -> bool {
+        !self.probing.get() && self.journal.borrow().is_enabled()
+    }
+
+    /// Appends an event to the encephalon's journal
+    pub fn record_event(&self, event: EncephalonEvent) {
+        if !self.probing.get() {
+            self.journal.borrow_mut().record(event);
+        }
+    }
+
+    /// Returns the journaled events of cycle and every
+    /// cycle since, from oldest to newest
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// # let encephalon = EncephalonBuilder::new()
+    /// #     .journal_capacity(1000)
+    /// #     .build(Box::new(BoxEcp::new(125, 0, 0, 27)), vec![], vec![])
+    /// #     .unwrap();
+    /// encephalon.run_n_cycles(100);
+    ///
+    /// for event in encephalon.events_since(50) {
+    ///     println!("{:?} at cycle {}", event.kind, event.cycle);
+    /// }
+    /// ```
+    pub fn events_since(&self, cycle: u64) -> Vec<EncephalonEvent> {
+        self.journal.borrow().since(cycle)
+    }
+
+    /// Writes the journal to a csv file at path
+    pub fn write_journal_csv(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        self.journal.borrow().write_csv(&mut writer)?;
+        writer.flush()
+    }
+
+    /// The number of plastic synapses across the whole encephalon,
+    /// which is kept as synapses form and dissolve.  Under a global
+    /// synapse budget this never exceeds the budget, while competitive
+    /// replacement keeps synapses turning over once it's reached
+    pub fn plastic_synapse_count(&self) -> usize {
+        self.plastic_synapse_count.get()
+    }
+
+    /// Counts a plastic synapse that just formed
+    pub fn plastic_synapse_formed(&self) {
+        self.plastic_synapse_count
+            .set(self.plastic_synapse_count.get() + 1);
+        self.connectome_changed();
+    }
+
+    /// Counts a plastic synapse that just dissolved
+    pub fn plastic_synapse_pruned(&self) {
+        self.plastic_synapse_count
+            .set(self.plastic_synapse_count.get().saturating_sub(1));
+        self.connectome_changed();
+    }
+
+    /// Moves on the connectome generation, so that eval mode
+    /// rebuilds its transmissions before the next cycle
+    fn connectome_changed(&self) {
+        self.connectome_generation
+            .set(self.connectome_generation.get().wrapping_add(1));
+    }
+
+    /// True if a plastic synapse may form under the global synapse
+    /// budget, dissolving another synapse first if the budget policy
+    /// calls for competitive replacement
+    pub fn make_room_for_plastic_synapse(&self, cycle: u64) -> bool {
+        let (budget, policy) = match self.synapse_budget {
+            Some(synapse_budget) => synapse_budget,
+            None => return true,
+        };
+
+        if self.plastic_synapse_count.get() < budget {
+            return true;
+        }
+
+        match policy {
+            BudgetPolicy::Refuse => false,
+            BudgetPolicy::CompetitiveReplacement { sample_size } => {
+                self.evict_sampled_weakest(sample_size, cycle)
+            }
+        }
+    }
+
+    /// Dissolves the weakest plastic synapse among those of
+    /// sample_size randomly chosen sensory and rx neurons
+    fn evict_sampled_weakest(&self, sample_size: usize, cycle: u64) -> bool {
+        let sensory_neurons = self.sensory_neurons.borrow();
+        let sensory_hashes = self.sensory_hashes.borrow();
+        let rx_neurons = self.rx_neurons.borrow();
+        let rx_hashes = self.rx_hashes.borrow();
+
+        let num_neurons = sensory_hashes.len() + rx_hashes.len();
+
+        if num_neurons == 0 {
+            return false;
+        }
+
+        let weakest_at = |index: usize| match sensory_hashes.get(index) {
+            Some(hash) => sensory_neurons[hash].weakest_plastic_strength(),
+            None => rx_neurons[&rx_hashes[index - sensory_hashes.len()]].weakest_plastic_strength(),
+        };
+
+        let mut weakest: Option<(usize, f32)> = None;
+
+        for _ in 0..sample_size {
+            let index = self.rng.borrow_mut().gen_range(0, num_neurons);
+
+            if let Some(strength) = weakest_at(index) {
+                if weakest.is_none_or(|(_, weakest_strength)| strength < weakest_strength) {
+                    weakest = Some((index, strength));
+                }
+            }
+        }
+
+        match weakest {
+            Some((index, _)) => match sensory_hashes.get(index) {
+                Some(hash) => sensory_neurons[hash].evict_weakest_plastic_synapse(cycle),
+                None => rx_neurons[&rx_hashes[index - sensory_hashes.len()]]
+                    .evict_weakest_plastic_synapse(cycle),
+            },
+            None => false,
+        }
+    }
+
+    /// True if the encephalon's prune policy would dissolve the synapse
+    pub fn should_prune(&self, info: &SynapseInfo) -> bool {
+        self.prune_policy.should_prune(info)
+    }
+
+    /// Reports the failure record of every actuator
+    pub fn health_report(&self) -> HealthReport {
+        HealthReport {
+            cycle: self.get_cycle_count(),
+            actuators: self
+                .actuator_interfaces
+                .borrow()
+                .iter()
+                .map(|(name, interface)| {
+                    (
+                        name.clone(),
+                        ActuatorHealth {
+                            consecutive_failures: interface.consecutive_failures(),
+                            total_failures: interface.total_failures(),
+                            panics: interface.panics(),
+                            disabled: interface.is_disabled(),
+                        },
+                    )
+                })
+                .collect(),
+            sensors: self
+                .sensory_interfaces
+                .borrow()
+                .iter()
+                .map(|(name, interface)| {
+                    (
+                        name.clone(),
+                        SensorHealth {
+                            panics: interface.panics(),
+                            range_violations: interface.range_violations(),
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// The total number of times the sensors and the
+    /// actuators have panicked while isolated
+    fn user_code_panics(&self) -> (u64, u64) {
+        let sensor_panics = self
+            .sensory_interfaces
+            .borrow()
+            .values()
+            .map(|interface| interface.panics())
+            .sum();
+
+        let actuator_panics = self
+            .actuator_interfaces
+            .borrow()
+            .values()
+            .map(|interface| interface.panics())
+            .sum();
+
+        (sensor_panics, actuator_panics)
+    }
+
+    /// The total number of out of range measurements of the sensors
+    fn sensor_range_violations(&self) -> u64 {
+        self.sensory_interfaces
+            .borrow()
+            .values()
+            .map(|interface| interface.range_violations())
+            .sum()
+    }
+
+    /// The number of sensors attached to the encephalon
+    pub fn sensor_count(&self) -> usize {
+        self.sensory_interfaces.borrow().len()
+    }
+
+    /// The number of actuators attached to the encephalon
+    pub fn actuator_count(&self) -> usize {
+        self.actuator_interfaces.borrow().len()
+    }
+
+    /// Calls f with the name and a view of each sensor, in name
+    /// order.  The sensors can't be reached from within f, so f
+    /// mustn't call back into the encephalon's sensors.
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// fn print_sensors(encephalon: &Encephalon) {
+    ///     encephalon.for_each_sensor(|name, view| {
+    ///         println!("{}: {:?} every {:?} cycles", name, view.last_measurement, view.period)
+    ///     });
+    /// }
+    /// ```
+    pub fn for_each_sensor(&self, mut f: impl FnMut(&str, SensorView)) {
+        let sensory_interfaces = self.sensory_interfaces.borrow();
+        let mut names: Vec<&SensorName> = sensory_interfaces.keys().collect();
+        names.sort();
+
+        for name in names {
+            f(name.as_str(), sensory_interfaces[name].view());
+        }
+    }
+
+    /// Calls f with the name and a view of each actuator, in name
+    /// order.  The actuators can't be reached from within f, so f
+    /// mustn't call back into the encephalon's actuators
+    pub fn for_each_actuator(&self, mut f: impl FnMut(&str, ActuatorView)) {
+        let actuator_interfaces = self.actuator_interfaces.borrow();
+        let mut names: Vec<&ActuatorName> = actuator_interfaces.keys().collect();
+        names.sort();
+
+        for name in names {
+            f(name.as_str(), actuator_interfaces[name].view());
+        }
+    }
+
+    /// The EMA of the named actuator's neurons, or
+    /// None if the encephalon has no such actuator
+    pub fn actuator_ema(&self, name: &str) -> Option<f32> {
+        self.actuator_interfaces
+            .borrow()
+            .get(name)
+            .map(|interface| interface.view().ema)
+    }
+
+    /// Pushes a frame of values into the encephalon's injected
+    /// sensors (see EncephalonBuilder::injected_sensors), to be
+    /// measured from the next cycle on.  Injected sensors missing
+    /// from values keep their last value, and if any name isn't
+    /// an injected sensor, no value is set at all:
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use eywa::prelude::*;
+    ///
+    /// fn push_frame(encephalon: &Encephalon, forward: f32) -> Result<(), EywaError> {
+    ///     let mut frame = HashMap::new();
+    ///     frame.insert("forward".to_string(), forward);
+    ///
+    ///     encephalon.set_sensor_values(&frame)
+    /// }
+    /// ```
+    pub fn set_sensor_values(&self, values: &HashMap<String, f32>) -> Result<(), EywaError> {
+        if let Some(name) = values
+            .keys()
+            .find(|name| !self.injected_sensors.contains_key(name.as_str()))
+        {
+            return Err(EywaError::UnknownName(name.clone()));
+        }
+
+        for (name, value) in values {
+            self.injected_sensors[name.as_str()].set(*value);
+        }
+
+        Ok(())
+    }
+
+    /// The last control value handed to each actuator, keyed
+    /// by name.  Actuators yet to be handed one read 0
+    pub fn actuator_values(&self) -> HashMap<String, f32> {
+        self.actuator_interfaces
+            .borrow()
+            .iter()
+            .map(|(name, interface)| (name.to_string(), interface.view().last_sent.unwrap_or(0.0)))
+            .collect()
+    }
+
+    /// Forces the sensory neurons of the named sensor to fire
+    /// at period (a period of 0 silences them), whatever the
+    /// sensor measures, until the sensor is forced to None
+    pub fn force_sensor_period(
+        &self,
+        sensor_name: &str,
+        period: Option<u32>,
+    ) -> Result<(), EywaError> {
+        match self.sensory_interfaces.borrow_mut().get_mut(sensor_name) {
+            Some(interface) => {
+                interface.force_period(period);
+                Ok(())
+            }
+            None => Err(EywaError::UnknownName(sensor_name.to_string())),
+        }
+    }
+
+    /// Measures how much the named sensor drives each actuator.
+    /// With plasticity frozen and every other sensor silenced, the
+    /// sensor is forced to fire at amplitude_period for probe_cycles,
+    /// and the mean EMA of each actuator over those cycles is compared
+    /// against a baseline run of as many cycles from the same state
+    /// with every sensor silenced.  The probe's cycles neither
+    /// measure the sensors nor stall on stale ones, and they leave
+    /// the actuators, journal and probes alone.  Afterwards the
+    /// neurons, sensors, cycle count and plasticity are put back as
+    /// they were before the probe
+    pub fn influence_probe(
+        &self,
+        sensor_name: &str,
+        probe_cycles: u64,
+        amplitude_period: u32,
+    ) -> Result<HashMap<String, f32>, EywaError> {
+        if !self.sensory_interfaces.borrow().contains_key(sensor_name) {
+            return Err(EywaError::UnknownName(sensor_name.to_string()));
+        }
+
+        let frozen = self.plasticity_frozen();
+        let before = self.snapshot();
+
+        let forced_periods: Vec<(SensorName, Option<u32>)> = self
+            .sensory_interfaces
+            .borrow()
+            .iter()
+            .map(|(name, interface)| (name.clone(), interface.forced_period()))
+            .collect();
+
+        self.freeze_plasticity(true);
+        self.probing.set(true);
+
+        // Each window starts from the state before the probe, so the
+        // only difference between them is the probed sensor
+        let mean_emas = |probed_period: u32| -> Result<HashMap<String, f32>, EywaError> {
+            self.restore(&before)?;
+
+            for (name, _) in &forced_periods {
+                let period = match name.as_str() == sensor_name {
+                    true => probed_period,
+                    false => 0,
+                };
+
+                self.force_sensor_period(name.as_str(), Some(period))?;
+            }
+
+            let mut totals: HashMap<String, f32> = HashMap::new();
+
+            for _ in 0..probe_cycles {
+                self.run_cycle();
+
+                for (name, interface) in self.actuator_interfaces.borrow().iter() {
+                    *totals.entry(name.to_string()).or_insert(0.0) += interface.view().ema;
+                }
+            }
+
+            Ok(totals
+                .into_iter()
+                .map(|(name, total)| (name, total / probe_cycles.max(1) as f32))
+                .collect())
+        };
+
+        let windows =
+            mean_emas(0).and_then(|baseline| Ok((baseline, mean_emas(amplitude_period)?)));
+
+        for (name, period) in forced_periods {
+            self.force_sensor_period(name.as_str(), period)?;
+        }
+
+        self.probing.set(false);
+        self.restore(&before)?;
+        self.freeze_plasticity(frozen);
+
+        let (baseline, probed) = windows?;
+
+        Ok(self
+            .actuator_interfaces
+            .borrow()
+            .keys()
+            .map(|name| {
+                let influence = probed.get(name.as_str()).copied().unwrap_or(0.0)
+                    - baseline.get(name.as_str()).copied().unwrap_or(0.0);
+
+                (name.to_string(), influence)
+            })
+            .collect())
+    }
+
+    /// Clears the consecutive failures of an actuator, so
+    /// that it's called again if it had been disabled
+    pub fn reset_actuator_failures(&self, actuator_name: &ActuatorName) -> Result<(), EywaError> {
+        match self.actuator_interfaces.borrow().get(actuator_name) {
+            Some(interface) => {
+                interface.reset_failures();
+                Ok(())
+            }
+            None => Err(EywaError::UnknownName(actuator_name.to_string())),
+        }
+    }
+
+    /// Attaches a sensor to a sensory position reserved with
+    /// EncephalonBuilder::reserved_sensory_positions.  The sensor takes
+    /// the position held for its name, if the layout held one, or else
+    /// the next position that isn't held for a name.  Until then, the
+    /// sensory neurons of the position never fire.  Errors with
+    /// DuplicateName if a sensor of the same name is already attached,
+    /// and with NoReservedPosition if there's no position left for it
+    pub fn attach_sensor(&self, sensor: Box<dyn Sensor>) -> Result<(), EywaError> {
+        let name = SensorName::from(sensor.get_name());
+
+        if self.sensory_interfaces.borrow().contains_key(&name) {
+            return Err(EywaError::DuplicateName(name.to_string()));
+        }
+
+        let slot = match take_reserved_slot(&mut self.reserved_sensors.borrow_mut(), &name) {
+            Some(slot) => slot,
+            None => return Err(EywaError::NoReservedPosition(name.to_string())),
+        };
+
+        let sensor: Box<dyn Sensor> = match self.auto_normalize {
+            true => Box::new(NormalizedSensor::new(sensor, Normalization::Clamp)),
+            false => sensor,
+        };
+
+        self.sensor_locs.borrow_mut().insert(
+            name.clone(),
+            slot.cluster.iter().map(|neuron| neuron.loc()).collect(),
+        );
+
+        self.sensory_interfaces.borrow_mut().insert(
+            name,
+            SensoryInterface::new(
+                sensor,
+                Rc::clone(&self.sensory_encoder),
+                slot.cluster,
+                SensorSampling::default(),
+                self.isolate_user_code,
+                self.assert_sensor_range,
+            ),
+        );
+
+        Ok(())
+    }
+
+    /// Attaches an actuator to an actuator position reserved with
+    /// EncephalonBuilder::reserved_actuator_positions, just as
+    /// attach_sensor attaches a sensor
+    pub fn attach_actuator(&self, actuator: Box<dyn Actuator>) -> Result<(), EywaError> {
+        let name = ActuatorName::from(actuator.get_name());
+
+        if self.actuator_interfaces.borrow().contains_key(&name) {
+            return Err(EywaError::DuplicateName(name.to_string()));
+        }
+
+        let slot = match take_reserved_slot(&mut self.reserved_actuators.borrow_mut(), &name) {
+            Some(slot) => slot,
+            None => return Err(EywaError::NoReservedPosition(name.to_string())),
+        };
+
+        self.actuator_locs.borrow_mut().insert(
+            name.clone(),
+            slot.cluster.iter().map(|neuron| neuron.loc()).collect(),
+        );
+
+        self.actuator_interfaces.borrow_mut().insert(
+            name,
+            ActuatorInterface::new(
+                slot.cluster,
+                actuator,
+                self.actuator_failure_threshold,
+                ActuatorOutput::default(),
+                self.isolate_user_code,
+            ),
+        );
+
+        Ok(())
+    }
+
+    /// Runs the encephalon for probe_cycles cycles, measuring how
+    /// much of the actuator's incoming charge comes from reflex
+    /// synapses versus every other synapse onto it.  The actuator
+    /// must be joined to sensor by a reflex.
+    ///
+    /// Right after construction nearly all of it should come from the
+    /// reflex; as the plastic network learns the behavior, the ratio
+    /// of the contribution should fall
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// # struct Pain;
+    /// # impl Sensor for Pain {
+    /// #     fn measure(&mut self) -> f32 { 1.0 }
+    /// #     fn get_name(&self) -> String { "pain".to_string() }
+    /// # }
+    /// # let encephalon = EncephalonBuilder::new()
+    /// #     .reflexes(vec![Reflex::new("pain".into(), "motor".into(), SynapticType::Excitatory, 20.)])
+    /// #     .build(
+    /// #         Box::new(BoxEcp::new(125, 1, 1, 27)),
+    /// #         vec![Box::new(Pain)],
+    /// #         vec![Box::new(FnActuator::new("motor".to_string(), |_| {}))],
+    /// #     )
+    /// #     .unwrap();
+    /// let contribution = encephalon
+    ///     .reflex_contribution(&"pain".into(), &"motor".into(), 10)
+    ///     .unwrap();
+    ///
+    /// println!("{:.0}% from the reflex", 100. * contribution.ratio());
+    /// ```
+    pub fn reflex_contribution(
+        &self,
+        sensor: &SensorName,
+        actuator: &ActuatorName,
+        probe_cycles: u32,
+    ) -> Result<ReflexContribution, EywaError> {
+        if !self.reflexes.iter().any(|reflex| {
+            &reflex.sensor_name == sensor
+                && matches!(&reflex.target, ReflexTarget::Actuator(name) if name == actuator)
+        }) {
+            return Err(EywaError::NoSuchReflex {
+                sensor: sensor.to_string(),
+                actuator: actuator.to_string(),
+            });
+        }
+
+        let actuator_neurons = match self.actuator_interfaces.borrow().get(actuator) {
+            Some(interface) => interface.actuator_neurons.clone(),
+            None => return Err(EywaError::UnknownName(actuator.to_string())),
+        };
+
+        for neuron in &actuator_neurons {
+            neuron.arm_impulse_tally();
+        }
+
+        for _ in 0..probe_cycles {
+            self.run_cycle();
+        }
+
+        let mut contribution = ReflexContribution {
+            cycles: probe_cycles,
+            ..ReflexContribution::default()
+        };
+
+        for neuron in &actuator_neurons {
+            if let Some(tally) = neuron.take_impulse_tally() {
+                contribution.reflex_charge += tally.reflex;
+                contribution.plastic_charge += tally.plastic;
+                contribution.static_charge += tally.other_static;
+            }
+        }
+
+        Ok(contribution)
+    }
+
+    /// Iterates over the cycles of the encephalon, where each
+    /// call to next runs one cycle and yields its metrics.  The
+    /// iterator never ends on its own, so bound it with take:
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// # let encephalon = EncephalonBuilder::new()
+    /// #     .build(Box::new(BoxEcp::new(125, 0, 0, 27)), vec![], vec![])
+    /// #     .unwrap();
+    /// for metrics in encephalon.cycles().take(100) {
+    ///     println!("cycle {}: {} fired", metrics.cycle, metrics.rx_fired);
+    /// }
+    /// ```
+    pub fn cycles(&self) -> Cycles<'_> {
+        Cycles { encephalon: self }
+    }
+
+    /// Runs a certain number of full cycles
+    pub fn run_n_cycles(&self, n: u32) {
+        for _ in 0..n {
+            self.run_cycle();
+        }
+    }
+
+    /// Runs a certain number of full cycles, calling progress
+    /// after every interval cycles.  An interval of 0 never
+    /// calls progress
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// # let encephalon = EncephalonBuilder::new()
+    /// #     .build(Box::new(BoxEcp::new(125, 0, 0, 27)), vec![], vec![])
+    /// #     .unwrap();
+    /// encephalon.run_n_cycles_with(1000, 100, |update| {
+    ///     println!("{} of {} cycles", update.cycle, update.total);
+    /// });
+    /// ```
+    pub fn run_n_cycles_with<F>(&self, n: u32, interval: u32, mut progress: F)
+    where
+        F: FnMut(ProgressUpdate),
+    {
+        let mut start = self.clock.now();
+
+        for i in 1..=n {
+            let metrics = self.run_cycle();
+
+            if interval > 0 && i % interval == 0 {
+                let elapsed = (self.clock.now() - start).as_secs_f32();
+
+                progress(ProgressUpdate {
+                    cycle: i,
+                    total: n,
+                    cycles_per_sec: interval as f32 / elapsed.max(f32::EPSILON),
+                    metrics: &metrics,
+                });
+
+                start = self.clock.now();
+            }
+        }
+    }
+
+    /// Runs n cycles paced to cycles_per_sec in wall time, sleeping
+    /// out whatever is left of each cycle's slot once it's run.  A
+    /// cycle that overruns its slot isn't made up for, so the next
+    /// cycle starts right away.  A rate that isn't positive runs
+    /// the cycles as fast as possible, while a NaN rate, or one so
+    /// slow its slot doesn't fit in a Duration, is an error.
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// # let encephalon = EncephalonBuilder::new()
+    /// #     .build(Box::new(BoxEcp::new(125, 0, 0, 27)), vec![], vec![])
+    /// #     .unwrap();
+    /// let report = encephalon.run_realtime(10, 1000.).unwrap();
+    ///
+    /// println!("{} of 10 cycles overran", report.overruns);
+    /// ```
+    pub fn run_realtime(&self, n: u32, cycles_per_sec: f32) -> Result<PacingReport, EywaError> {
+        let slot = match cycles_per_sec {
+            rate if rate.is_nan() => return Err(EywaError::InvalidCycleRate(rate)),
+            rate if rate > 0. => Duration::try_from_secs_f64(1. / rate as f64)
+                .map_err(|_| EywaError::InvalidCycleRate(rate))?,
+            _ => Duration::from_secs(0),
+        };
+
+        let run_start = self.clock.now();
+        let mut report = PacingReport {
+            cycles: n,
+            ..PacingReport::default()
+        };
+
+        for _ in 0..n {
+            let start = self.clock.now();
+            self.run_cycle();
+            let elapsed = self.clock.now() - start;
+
+            if elapsed < slot {
+                self.clock.sleep(slot - elapsed);
+                report.slept += slot - elapsed;
+            } else if elapsed > slot {
+                report.overruns += 1;
+            }
+        }
+
+        report.elapsed = self.clock.now() - run_start;
+        Ok(report)
+    }
+
+    /// The time a phase of the cycle starts at, if cycles are profiled
+    fn profile_start(&self) -> Option<Duration> {
+        match self.profile_cycles {
+            true => Some(self.clock.now()),
+            false => None,
+        }
+    }
+
+    /// Adds the time since phase_start to the phase's timing
+    fn profile_end(
+        &self,
+        phase_start: Option<Duration>,
+        phase: fn(&mut PhaseTimings) -> &mut PhaseTiming,
+    ) {
+        if let Some(phase_start) = phase_start {
+            let elapsed = self.clock.now().saturating_sub(phase_start);
+            phase(&mut self.phase_timings.borrow_mut()).record(elapsed);
+        }
+    }
+
+    /// Where the time of every cycle since the encephalon was built
+    /// (or last reset) went, phase by phase.  Empty unless the
+    /// encephalon was built with EncephalonBuilder::profile_cycles
+    pub fn profile_report(&self) -> PhaseTimings {
+        *self.phase_timings.borrow()
+    }
+
+    /// Clears the phase timings, so profiling starts over
+    pub fn reset_profile(&self) {
+        *self.phase_timings.borrow_mut() = PhaseTimings::default();
+    }
+
+    /// Upticks cycle count by 1
+    fn uptick_cycle_count(&self) {
+        *self.cycle_count.borrow_mut() += 1;
+    }
+
+    /// Forms static reflex synapses from the list
+    /// of reflexes passed into Encephalon during creation.
+    ///
+    /// Every sensory neuron of the sensor's cluster synapses onto
+    /// every actuator neuron of the actuator's cluster.  The strength
+    /// is split across the sensory neurons.  Their phase is lined up
+    /// with the period they're handed, since a sensory neuron fires on
+    /// the cycles that are multiples of its period, so a cluster fires
+    /// all at once and each actuator neuron receives the reflex's full
+    /// strength (in one cycle) each time the sensor fires.
+    ///
+    /// Errors if a reflex onto an actuator forms no synapses at all,
+    /// which would otherwise leave it silently doing nothing.
+    /// Calls progress with the number of reflexes onto actuators
+    /// formed so far, out of all of them
+    fn form_reflex_synapses(&self, mut progress: impl FnMut(u32, u32)) -> Result<(), EywaError> {
+        let total = self
+            .reflexes
+            .iter()
+            .filter(|reflex| matches!(reflex.target, ReflexTarget::Actuator(_)))
+            .count() as u32;
+        let mut done = 0;
+
+        progress(0, total);
+
+        for reflex in &self.reflexes {
+            let actuator_name = match &reflex.target {
+                ReflexTarget::Actuator(actuator_name) => actuator_name,
+                ReflexTarget::Sensor(_) => continue,
+            };
+
+            let mut formed = 0;
+
+            if let Some(sensor) = self.sensory_interfaces.borrow().get(&reflex.sensor_name) {
+                if let Some(actuator) = self.actuator_interfaces.borrow().get(actuator_name) {
+                    let strength = reflex.strength / sensor.sensory_neurons.len() as f32;
+
+                    for sensory_neuron in &sensor.sensory_neurons {
+                        for actuator_neuron in &actuator.actuator_neurons {
+                            sensory_neuron.add_reflex_synapse(
+                                strength,
+                                reflex.synapse_type,
+                                Rc::clone(&(Rc::clone(actuator_neuron) as Rc<dyn NeuronicRx>)),
+                            );
+                            formed += 1;
+
+                            if self.journaling() {
+                                self.record_event(EncephalonEvent {
+                                    cycle: self.get_cycle_count(),
+                                    kind: EventKind::Reflex,
+                                    src_loc: sensory_neuron.loc().into(),
+                                    dst_loc: actuator_neuron.loc().into(),
+                                    strength_at_event: strength,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+
+            if formed == 0 {
+                return Err(EywaError::ReflexNotFormed {
+                    sensor: reflex.sensor_name.to_string(),
+                    actuator: actuator_name.to_string(),
+                });
+            }
+
+            done += 1;
+            progress(done, total);
+            self.connectome_changed();
+        }
+
+        Ok(())
+    }
+
+    /// Wires every spec into the encephalon, as the builder does with
+    /// its initial synapses.  Every location is checked before any
+    /// synapse is formed, so an error leaves the encephalon unwired.
+    /// Calls progress with the number of synapses wired so far
+    fn import_synapses(
+        &self,
+        specs: &[SynapseSpec],
+        strength_generator: &dyn Fn() -> Box<RefCell<dyn SynapticStrength>>,
+        mut progress: impl FnMut(u32, u32),
+    ) -> Result<(), EywaError> {
+        let rx_neurons = self.rx_neurons.borrow();
+        let sensory_neurons = self.sensory_neurons.borrow();
+        let mut wiring = Vec::with_capacity(specs.len());
+
+        for spec in specs {
+            let src_hash = self.ecp_geometry.loc_hash(&spec.src_loc);
+            let dst_hash = self.ecp_geometry.loc_hash(&spec.dst_loc);
+
+            if !sensory_neurons.contains_key(&src_hash) {
+                match self.neuron_kind_at(&spec.src_loc)? {
+                    None => return Err(EywaError::UnknownLoc(spec.src_loc.clone())),
+                    Some(NeuronKind::Actuator) => {
+                        return Err(EywaError::NotATxNeuron(spec.src_loc.clone()))
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            let target = match rx_neurons.get(&dst_hash) {
+                Some(rx_neuron) => Rc::clone(rx_neuron),
+                None if sensory_neurons.contains_key(&dst_hash) => {
+                    return Err(EywaError::NotAnRxNeuron(spec.dst_loc.clone()))
+                }
+                None => return Err(EywaError::UnknownLoc(spec.dst_loc.clone())),
+            };
+
+            wiring.push((spec, src_hash, target));
+        }
+
+        let cycle = self.get_cycle_count();
+        let total = wiring.len() as u32;
+
+        progress(0, total);
+
+        for (done, (spec, src_hash, target)) in wiring.into_iter().enumerate() {
+            let adopted = match sensory_neurons.get(&src_hash) {
+                Some(sensory_neuron) => adopt_synapse(
+                    sensory_neuron.as_ref(),
+                    spec,
+                    target,
+                    strength_generator,
+                    cycle,
+                ),
+                None => adopt_synapse(
+                    rx_neurons[&src_hash].as_ref(),
+                    spec,
+                    target,
+                    strength_generator,
+                    cycle,
+                ),
+            };
+
+            if !adopted {
+                return Err(EywaError::NotATxNeuron(spec.src_loc.clone()));
+            }
+
+            self.connectome_changed();
+            progress(done as u32 + 1, total);
+        }
+
+        Ok(())
+    }
+
+    /// Returns every reflex the encephalon formed on construction,
+    /// as the sensor and actuator names and the strength of each,
+    /// read back from the reflex synapses themselves.  A reflex whose
+    /// actuator can't be found fails to build in the first place
+    pub fn reflexes_formed(&self) -> Vec<(String, String, f32)> {
+        let sensory_interfaces = self.sensory_interfaces.borrow();
+        let actuator_interfaces = self.actuator_interfaces.borrow();
+        let mut formed: Vec<(String, String, f32)> = Vec::new();
+
+        for reflex in &self.reflexes {
+            let actuator_name = match &reflex.target {
+                ReflexTarget::Actuator(actuator_name) => actuator_name,
+                ReflexTarget::Sensor(_) => continue,
+            };
+
+            let already_listed = formed.iter().any(|(sensor, actuator, _)| {
+                sensor == reflex.sensor_name.as_str() && actuator == actuator_name.as_str()
+            });
+
+            if already_listed {
+                continue;
+            }
+
+            let (sensor, actuator) = match (
+                sensory_interfaces.get(&reflex.sensor_name),
+                actuator_interfaces.get(actuator_name),
+            ) {
+                (Some(sensor), Some(actuator)) => (sensor, actuator),
+                _ => continue,
+            };
+
+            let actuator_locs: Vec<Loc> = actuator
+                .actuator_neurons
+                .iter()
+                .map(|neuron| neuron.loc())
+                .collect();
+
+            // Each actuator neuron receives the full strength of
+            // the reflex, split across the sensor's neurons
+            let total: f32 = sensor
+                .sensory_neurons
+                .iter()
+                .flat_map(|neuron| {
+                    neuron
+                        .get_static_synapses()
+                        .iter()
+                        .filter(|synapse| {
+                            synapse.source() == ImpulseSource::Reflex
+                                && actuator_locs.contains(&synapse.target_key())
+                        })
+                        .map(|synapse| synapse.strength())
+                        .collect::<Vec<f32>>()
+                })
+                .sum();
+
+            if total > 0. {
+                formed.push((
+                    reflex.sensor_name.to_string(),
+                    actuator_name.to_string(),
+                    total / actuator_locs.len() as f32,
+                ));
+            }
+        }
+
+        formed
+    }
+
+    /// The charge that moved through the encephalon during the
+    /// last cycle, which was checked for conservation
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// # let encephalon = EncephalonBuilder::new()
+    /// #     .build(Box::new(BoxEcp::new(125, 0, 0, 27)), vec![], vec![])
+    /// #     .unwrap();
+    /// encephalon.run_cycle();
+    ///
+    /// println!("{:?}", encephalon.charge_ledger());
+    /// ```
+    #[cfg(feature = "audit")]
+    pub fn charge_ledger(&self) -> ChargeLedger {
+        self.charge_ledger.get()
+    }
+
+    /// Gets the elapsed cycle count of the encephalon.
+    /// The cycle count dictates when sensor neurons fire,
+    /// and also the ChargeCycle
+    pub fn get_cycle_count(&self) -> u64 {
+        *self.cycle_count.borrow()
+    }
+
+    /// Indicates the parity of the charge cycle, which allows
+    /// neurons to fire throughout a graphical structure without
+    /// conflicting or incorrect behavior
+    pub fn get_charge_cycle(&self) -> ChargeCycle {
+        ChargeCycle::from_cycle(*self.cycle_count.borrow())
+    }
+
+    /// Reports the requested against the actual size of the
+    /// encephalon's geometry
+    pub fn size_report(&self) -> SizeReport {
+        SizeReport {
+            synapse_budget_clamp: self.synapse_budget_clamp,
+            ..self.ecp_geometry.describe()
+        }
+    }
+
+    /// The warnings of the plan the encephalon was built from
+    /// (see BrainPlan::warnings), for logging once it's built
+    pub fn plan_warnings(&self) -> &[PlanWarning] {
+        &self.plan_warnings
+    }
+
+    /// Gets the location of the (first) sensory neuron
+    /// driven by the sensor called name
+    pub fn sensor_loc(&self, name: &SensorName) -> Option<Loc> {
+        self.sensor_cluster_locs(name)
+            .and_then(|locs| locs.into_iter().next())
+    }
+
+    /// Gets the locations of every sensory neuron in the
+    /// cluster driven by the sensor called name
+    pub fn sensor_cluster_locs(&self, name: &SensorName) -> Option<Vec<Loc>> {
+        self.sensor_locs.borrow().get(name).cloned()
+    }
+
+    /// Gets the location of the (first) actuator neuron
+    /// driving the actuator called name
+    pub fn actuator_loc(&self, name: &ActuatorName) -> Option<Loc> {
+        self.actuator_cluster_locs(name)
+            .and_then(|locs| locs.into_iter().next())
+    }
+
+    /// Gets the locations of every actuator neuron in the
+    /// cluster driving the actuator called name
+    pub fn actuator_cluster_locs(&self, name: &ActuatorName) -> Option<Vec<Loc>> {
+        self.actuator_locs.borrow().get(name).cloned()
+    }
+
+    /// Errors with DimensionMismatch if loc doesn't have the number
+    /// of dimensions of the encephalon's geometry.  The location based
+    /// methods that return a Result check this before looking loc up,
+    /// so that a location of the wrong shape isn't mistaken for an
+    /// empty one
+    pub fn check_dims(&self, loc: &[i32]) -> Result<(), EywaError> {
+        let expected = self.ecp_geometry.dims();
+
+        match loc.len() == expected {
+            true => Ok(()),
+            false => Err(EywaError::DimensionMismatch {
+                loc: loc.to_vec(),
+                expected,
+            }),
+        }
+    }
+
+    /// Gets the kind of neuron located at loc, or None if there's
+    /// no neuron there.  Errors with DimensionMismatch if loc
+    /// doesn't have the dimensions of the geometry
+    ///
+    /// ```
+    /// use eywa::neuron::NeuronKind;
+    /// # use eywa::prelude::*;
+    /// # struct Light;
+    /// # impl Sensor for Light {
+    /// #     fn measure(&mut self) -> f32 { 0.0 }
+    /// #     fn get_name(&self) -> String { "light".to_string() }
+    /// # }
+    /// let encephalon = EncephalonBuilder::new()
+    ///     .build(Box::new(BoxEcp::new(125, 1, 0, 27)), vec![Box::new(Light)], vec![])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(encephalon.neuron_kind_at(&[2, 2, 2]), Ok(Some(NeuronKind::Plastic)));
+    /// assert_eq!(encephalon.neuron_kind_at(&[2, 2, 9]), Ok(None));
+    /// assert!(encephalon.neuron_kind_at(&[2, 2]).is_err());
+    /// ```
+    pub fn neuron_kind_at(&self, loc: &[i32]) -> Result<Option<NeuronKind>, EywaError> {
+        self.check_dims(loc)?;
+        let hash = self.ecp_geometry.loc_hash(loc);
+
+        // Reserved actuator positions are actuator neurons before
+        // they're attached, so the neuron itself is asked its kind
+        let kind = if self.sensory_neurons.borrow().contains_key(&hash) {
+            Some(NeuronKind::Sensory)
+        } else if let Some(neuron) = self.rx_neurons.borrow().get(&hash) {
+            match neuron.kind() {
+                NeuronKind::Plastic if self.interneuron_hashes.borrow().contains(&hash) => {
+                    Some(NeuronKind::Interneuron)
+                }
+                kind => Some(kind),
+            }
+        } else {
+            None
+        };
+
+        Ok(kind)
+    }
+
+    /// Describes the neuron at loc in a line or two for debugging:
+    /// its state, the charge waiting on it next cycle (for rx
+    /// neurons) and a summary of its outgoing synapses
+    pub fn describe_neuron(&self, loc: &[i32]) -> Result<String, EywaError> {
+        self.check_dims(loc)?;
+        let hash = self.ecp_geometry.loc_hash(loc);
+
+        if let Some(rx_neuron) = self.rx_neurons.borrow().get(&hash) {
+            let charge = rx_neuron.peek_charge(self.get_cycle_count() + 1);
+
+            Ok(format!(
+                "{:?}\n  charge next cycle {}, {}",
+                rx_neuron,
+                charge,
+                synapse_summary(&rx_neuron.synapse_records())
+            ))
+        } else if let Some(sensory_neuron) = self.sensory_neurons.borrow().get(&hash) {
+            Ok(format!(
+                "{:?}\n  {}",
+                sensory_neuron,
+                synapse_summary(&sensory_neuron.synapse_records())
+            ))
+        } else {
+            Err(EywaError::UnknownLoc(loc.to_vec()))
+        }
+    }
+
+    /// Returns the parameters the plastic neuron (or interneuron)
+    /// at loc was built with, including any region overrides, or None
+    /// if there's no such neuron there.  Errors with DimensionMismatch
+    /// if loc doesn't have the dimensions of the geometry
+    pub fn params_at(&self, loc: &[i32]) -> Result<Option<PlasticParams>, EywaError> {
+        let params = match self.neuron_kind_at(loc)? {
+            Some(NeuronKind::Plastic) => {
+                let params = match self.plastic_model {
+                    PlasticModel::Ema(params) => params,
+                    PlasticModel::Izhikevich(_) => self.plastic_params,
+                };
+
+                Some(self.region_params(loc, params))
+            }
+            Some(NeuronKind::Interneuron) => Some(
+                self.interneuron_params
+                    .plastic_params(self.region_params(loc, self.plastic_params)),
+            ),
+            _ => None,
+        };
+
+        Ok(params)
+    }
+
+    /// Applies each region override covering loc to params, in
+    /// the order the overrides were added, so the last match wins
+    pub fn region_params(&self, loc: &[i32], params: PlasticParams) -> PlasticParams {
+        apply_region_overrides(&self.region_overrides, loc, params)
+    }
+
+    /// Returns the EMA of every rx and sensory neuron keyed by
+    /// its location, which is handy for heatmaps of activity
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// # struct Constant;
+    /// # impl Sensor for Constant {
+    /// #     fn measure(&mut self) -> f32 { 1.0 }
+    /// #     fn get_name(&self) -> String { "light".to_string() }
+    /// # }
+    /// let encephalon = EncephalonBuilder::new()
+    ///     .build(
+    ///         Box::new(BoxEcp::new(125, 1, 1, 27)),
+    ///         vec![Box::new(Constant)],
+    ///         vec![Box::new(FnActuator::new("motor".to_string(), |_| {}))],
+    ///     )
+    ///     .unwrap();
+    ///
+    /// encephalon.run_n_cycles(10);
+    ///
+    /// for (loc, ema) in encephalon.ema_field() {
+    ///     println!("{:?}: {}", loc, ema);
+    /// }
+    /// ```
+    pub fn ema_field(&self) -> Vec<(Loc, f32)> {
+        let mut field = Vec::new();
+
+        let rx_neurons = self.rx_neurons.borrow();
+
+        for (loc, hash, _) in self.ecp_geometry.rx_locs() {
+            if let Some(neuron) = rx_neurons.get(&hash) {
+                field.push((loc, neuron.ema()));
+            }
+        }
+
+        let sensory_neurons = self.sensory_neurons.borrow();
+
+        for (loc, hash) in self.ecp_geometry.sensory_locs() {
+            if let Some(neuron) = sensory_neurons.get(&hash) {
+                field.push((loc, neuron.ema()));
+            }
+        }
+
+        field
+    }
+
+    /// The field of every rx neuron in slice index of the geometry
+    /// along axis, as rows of columns (see EcpGeometry::slice_shape).
+    /// Positions without a neuron read 0.0.  Errors if the geometry
+    /// has no such slice
+    pub fn activity_slice(
+        &self,
+        axis: usize,
+        index: u32,
+        field: FieldKind,
+    ) -> Result<Vec<Vec<f32>>, EywaError> {
+        let (rows, cols) = match self.ecp_geometry.slice_shape(axis) {
+            Some((slices, rows, cols)) if index < slices => (rows, cols),
+            _ => return Err(EywaError::NoSuchSlice { axis, index }),
+        };
+
+        let mut slice = vec![vec![0.0; cols as usize]; rows as usize];
+        let next_cycle = self.get_cycle_count() + 1;
+        let rx_neurons = self.rx_neurons.borrow();
+
+        for (loc, hash, _) in self.ecp_geometry.rx_locs() {
+            let (row, col) = match self.ecp_geometry.slice_index_of(&loc, axis) {
+                Some((slice_index, row, col)) if slice_index == index => (row, col),
+                _ => continue,
+            };
+
+            if let Some(neuron) = rx_neurons.get(&hash) {
+                slice[row as usize][col as usize] = match field {
+                    FieldKind::Ema => neuron.ema(),
+                    FieldKind::FireLastCycle => match neuron.fired_on_prev_cycle(next_cycle) {
+                        true => 1.0,
+                        false => 0.0,
+                    },
+                    FieldKind::Charge => neuron.peek_charge(next_cycle),
+                };
+            }
+        }
+
+        Ok(slice)
+    }
+
+    /// Writes an activity slice (see activity_slice) to path as a
+    /// plain (ascii) grayscale PGM image, one pixel per neuron.  The
+    /// values are normalized over the slice, so its most active
+    /// neuron is white and its least active black:
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// fn save_bottom_charges(encephalon: &Encephalon) -> std::io::Result<()> {
+    ///     encephalon.write_activity_image("charge.pgm", 2, 0, FieldKind::Charge)
+    /// }
+    /// ```
+    pub fn write_activity_image(
+        &self,
+        path: impl AsRef<Path>,
+        axis: usize,
+        index: u32,
+        field: FieldKind,
+    ) -> io::Result<()> {
+        let slice = self
+            .activity_slice(axis, index, field)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+
+        let values = slice.iter().flatten();
+        let min = values
+            .clone()
+            .fold(f32::INFINITY, |min, &value| min.min(value));
+        let max = values.fold(f32::NEG_INFINITY, |max, &value| max.max(value));
+
+        let mut writer = BufWriter::new(File::create(path)?);
+        let cols = slice.first().map_or(0, |row| row.len());
+
+        writeln!(writer, "P2\n{} {}\n255", cols, slice.len())?;
+
+        for row in &slice {
+            let pixels: Vec<String> = row
+                .iter()
+                .map(|&value| match max > min {
+                    true => ((value - min) / (max - min) * 255.).round() as u8,
+                    false => 0,
+                })
+                .map(|pixel| pixel.to_string())
+                .collect();
+
+            writeln!(writer, "{}", pixels.join(" "))?;
+        }
+
+        writer.flush()
+    }
+
+    /// Describes every synapse of the encephalon, starting
+    /// with those of the sensory neurons and then those of
+    /// the rx neurons, each in traversal order
+    pub fn connectome(&self) -> Vec<SynapseRecord> {
+        let mut records = Vec::new();
+
+        let sensory_neurons = self.sensory_neurons.borrow();
+
+        for hash in self.sensory_hashes.borrow().iter() {
+            records.extend(sensory_neurons[hash].synapse_records());
+        }
+
+        let rx_neurons = self.rx_neurons.borrow();
+
+        for hash in self.rx_hashes.borrow().iter() {
+            records.extend(rx_neurons[hash].synapse_records());
+        }
+
+        records
+    }
+
+    /// The normalized strength of every plastic synapse,
+    /// sorted from weakest to strongest
+    pub fn sorted_plastic_strengths(&self) -> Vec<f32> {
+        let mut strengths = Vec::new();
+
+        for neuron in self.sensory_neurons.borrow().values() {
+            strengths.extend(neuron.plastic_strengths());
+        }
+
+        for neuron in self.rx_neurons.borrow().values() {
+            strengths.extend(neuron.plastic_strengths());
+        }
+
+        strengths.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        strengths
+    }
+
+    /// Counts the plastic synapses falling into each of bins bins of
+    /// equal width spanning the weakest to the strongest normalized
+    /// strength of any synapse.
+    /// Each bin is given by its lower edge and its count
+    pub fn strength_histogram(&self, bins: usize) -> Vec<(f32, u32)> {
+        histogram(&self.sorted_plastic_strengths(), bins)
+    }
+
+    /// The pth percentile (p between 0 and 100) of the normalized
+    /// strengths of every plastic synapse, or 0 if there are none
+    pub fn strength_percentile(&self, p: f32) -> f32 {
+        percentile(&self.sorted_plastic_strengths(), p)
+    }
+
+    /// Captures the cycle count, the EMA and dynamics of every neuron,
+    /// the neuron groups and lesions, the position of the random number
+    /// generator, and the connectome of the encephalon.  Restoring the
+    /// snapshot rebuilds every plastic synapse, while the static
+    /// synapses are only recorded for analysis (see
+    /// analysis::diff_snapshots), as the builder wires those
+    pub fn snapshot(&self) -> EncephalonSnapshot {
+        let neurons = self
+            .ema_field()
+            .into_iter()
+            .map(|(loc, ema)| NeuronSnapshot {
+                loc: loc.into(),
+                ema,
+            })
+            .collect();
+
+        let mut groups: Vec<GroupSnapshot> = self
+            .groups
+            .borrow()
+            .iter()
+            .map(|(name, locs)| GroupSnapshot {
+                name: name.clone(),
+                locs: locs.iter().map(|&loc| loc.into()).collect(),
+            })
+            .collect();
+
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let lesioned = self.lesioned.borrow();
+        let lesioned = self
+            .ecp_geometry
+            .rx_locs()
+            .filter(|(_, hash, _)| lesioned.contains(hash))
+            .map(|(loc, _, _)| loc.into())
+            .collect();
+
+        EncephalonSnapshot {
+            groups,
+            lesioned,
+            synapses: self.connectome(),
+            rng: Some(self.rng_snapshot()),
+            energy: self.energy(),
+            dynamics: self.dynamics(),
+            plastic_synapses: Some(self.plastic_synapse_snapshots()),
+            ..EncephalonSnapshot::new(self.get_cycle_count(), neurons)
+        }
+    }
+
+    /// Every plastic synapse of the sensory neurons and then of
+    /// the rx neurons, each in traversal order
+    fn plastic_synapse_snapshots(&self) -> Vec<PlasticSynapseSnapshot> {
+        let mut synapses = Vec::new();
+
+        let sensory_neurons = self.sensory_neurons.borrow();
+
+        for hash in self.sensory_hashes.borrow().iter() {
+            synapses.extend(sensory_neurons[hash].plastic_synapse_snapshots());
+        }
+
+        let rx_neurons = self.rx_neurons.borrow();
+
+        for hash in self.rx_hashes.borrow().iter() {
+            synapses.extend(rx_neurons[hash].plastic_synapse_snapshots());
+        }
+
+        synapses
+    }
+
+    /// The dynamics of every rx and sensory neuron, in traversal order
+    fn dynamics(&self) -> Vec<NeuronDynamics> {
+        let cycle = self.get_cycle_count();
+        let rx_neurons = self.rx_neurons.borrow();
+        let sensory_neurons = self.sensory_neurons.borrow();
+
+        let rx = self
+            .rx_hashes
+            .borrow()
+            .iter()
+            .map(|hash| rx_neurons[hash].dynamics(cycle))
+            .collect::<Vec<_>>();
+        let sensory = self
+            .sensory_hashes
+            .borrow()
+            .iter()
+            .map(|hash| sensory_neurons[hash].dynamics(cycle))
+            .collect::<Vec<_>>();
+
+        rx.into_iter().chain(sensory).collect()
+    }
+
+    fn rng_snapshot(&self) -> RngSnapshot {
+        let (seed, stream) = self.rng_key.get();
+
+        RngSnapshot {
+            seed,
+            stream,
+            word_pos: self.rng.borrow().get_word_pos(),
+        }
+    }
+
+    /// Restores a snapshot taken of an encephalon with the same
+    /// geometry.  Errors if the snapshot refers to a location
+    /// that has no neuron, in which case nothing is restored.
+    ///
+    /// The random number generator resumes exactly where it was
+    /// when the snapshot was taken, whatever the encephalon was
+    /// seeded with, and so does every neuron, with its charge in
+    /// flight, bursts, adaptation and recent fires
+    pub fn restore(&self, snapshot: &EncephalonSnapshot) -> Result<(), EywaError> {
+        let rx_neurons = self.rx_neurons.borrow();
+        let sensory_neurons = self.sensory_neurons.borrow();
+
+        let mut emas: Vec<(&dyn HasEma, f32)> = Vec::new();
+
+        for neuron in &snapshot.neurons {
+            let hash = self.ecp_geometry.loc_hash(&neuron.loc);
+
+            if let Some(rx_neuron) = rx_neurons.get(&hash) {
+                emas.push((rx_neuron.as_ref(), neuron.ema));
+            } else if let Some(sensory_neuron) = sensory_neurons.get(&hash) {
+                emas.push((sensory_neuron.as_ref(), neuron.ema));
+            } else {
+                return Err(EywaError::UnknownLoc(neuron.loc.clone()));
+            }
+        }
+
+        let mut dynamics: Vec<(&dyn HasDynamics, &NeuronDynamics)> = Vec::new();
+
+        for neuron in &snapshot.dynamics {
+            let hash = self.ecp_geometry.loc_hash(&neuron.loc);
+
+            if let Some(rx_neuron) = rx_neurons.get(&hash) {
+                dynamics.push((rx_neuron.as_ref(), neuron));
+            } else if let Some(sensory_neuron) = sensory_neurons.get(&hash) {
+                dynamics.push((sensory_neuron.as_ref(), neuron));
+            } else {
+                return Err(EywaError::UnknownLoc(neuron.loc.clone()));
+            }
+        }
+
+        let mut groups = HashMap::new();
+
+        for group in &snapshot.groups {
+            groups.insert(group.name.clone(), self.group_locs(&group.locs)?);
+        }
+
+        let mut lesioned = HashSet::new();
+
+        for loc in &snapshot.lesioned {
+            let hash = self.ecp_geometry.loc_hash(loc);
+
+            if !rx_neurons.contains_key(&hash) {
+                return Err(EywaError::UnknownLoc(loc.clone()));
+            }
+
+            lesioned.insert(hash);
+        }
+
+        let plastic_synapses = match &snapshot.plastic_synapses {
+            Some(synapses) => Some(self.restored_wiring(synapses)?),
+            None => None,
+        };
+
+        for (neuron, ema) in emas {
+            neuron.set_ema(ema);
+        }
+
+        for (neuron, neuron_dynamics) in dynamics {
+            neuron.set_dynamics(snapshot.cycle_count, neuron_dynamics);
+        }
+
+        *self.groups.borrow_mut() = groups;
+        *self.lesioned.borrow_mut() = lesioned;
+
+        if let Some(mut wiring) = plastic_synapses {
+            let mut count = 0;
+
+            for (hash, neuron) in sensory_neurons.iter() {
+                let synapses = wiring.remove(hash).unwrap_or_default();
+                count += synapses.len();
+                neuron.restore_plastic_synapses(synapses);
+            }
+
+            for (hash, neuron) in rx_neurons.iter() {
+                let synapses = wiring.remove(hash).unwrap_or_default();
+                count += synapses.len();
+                neuron.restore_plastic_synapses(synapses);
+            }
+
+            self.plastic_synapse_count.set(count);
+            self.connectome_changed();
+        }
+
+        if let Some(rng) = &snapshot.rng {
+            let mut restored = ChaCha20Rng::from_seed(rng.seed);
+            restored.set_stream(rng.stream);
+            restored.set_word_pos(rng.word_pos);
+
+            *self.rng.borrow_mut() = restored;
+            self.rng_key.set((rng.seed, rng.stream));
+        }
+
+        if let (Some(_), Some(energy)) = (self.energy_model, snapshot.energy) {
+            self.energy.set(energy);
+        }
+
+        *self.cycle_count.borrow_mut() = snapshot.cycle_count;
+
+        Ok(())
+    }
+
+    /// Finds the neurons each plastic synapse of a snapshot runs
+    /// between, grouped by the hash of the source neuron.  Errors
+    /// if a synapse runs from a neuron that can't transmit or onto
+    /// one that can't receive
+    fn restored_wiring<'a>(
+        &self,
+        synapses: &'a [PlasticSynapseSnapshot],
+    ) -> Result<HashMap<String, Vec<RestoredSynapse<'a>>>, EywaError> {
+        let rx_neurons = self.rx_neurons.borrow();
+        let sensory_neurons = self.sensory_neurons.borrow();
+        let mut wiring: HashMap<String, Vec<_>> = HashMap::new();
+
+        for synapse in synapses {
+            let src_hash = self.ecp_geometry.loc_hash(&synapse.source);
+            let dst_hash = self.ecp_geometry.loc_hash(&synapse.target);
+
+            if !sensory_neurons.contains_key(&src_hash) {
+                match self.neuron_kind_at(&synapse.source)? {
+                    None => return Err(EywaError::UnknownLoc(synapse.source.clone())),
+                    Some(NeuronKind::Actuator) => {
+                        return Err(EywaError::NotATxNeuron(synapse.source.clone()))
+                    }
+                    Some(_) => {}
+                }
+            }
+
+            let target = match rx_neurons.get(&dst_hash) {
+                Some(rx_neuron) => Rc::clone(rx_neuron),
+                None if sensory_neurons.contains_key(&dst_hash) => {
+                    return Err(EywaError::NotAnRxNeuron(synapse.target.clone()))
+                }
+                None => return Err(EywaError::UnknownLoc(synapse.target.clone())),
+            };
+
+            wiring.entry(src_hash).or_default().push((synapse, target));
+        }
+
+        Ok(wiring)
+    }
+
+    /// Deposits charge directly into the next cycle of the rx
+    /// neuron at loc, as if it received an impulse of that size.
+    /// Negative charge is taken in as an inhibitory impulse.
+    /// This is the encephalon analogue of current injection
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// # struct Light;
+    /// # impl Sensor for Light {
+    /// #     fn measure(&mut self) -> f32 { 0.0 }
+    /// #     fn get_name(&self) -> String { "light".to_string() }
+    /// # }
+    /// let encephalon = EncephalonBuilder::new()
+    ///     .build(Box::new(BoxEcp::new(125, 1, 0, 27)), vec![Box::new(Light)], vec![])
+    ///     .unwrap();
+    ///
+    /// encephalon.inject_charge(&[2, 2, 2], 100.).unwrap();
+    /// encephalon.run_cycle();
+    /// ```
+    pub fn inject_charge(&self, loc: &[i32], charge: f32) -> Result<(), EywaError> {
+        self.check_dims(loc)?;
+        let hash = self.ecp_geometry.loc_hash(loc);
+
+        if let Some(rx_neuron) = self.rx_neurons.borrow().get(&hash) {
+            #[cfg(feature = "audit")]
+            crate::audit::record(|ledger| ledger.injected += charge as f64);
+
+            let synaptic_type = match charge < 0. {
+                true => SynapticType::Inhibitory,
+                false => SynapticType::Excitatory,
+            };
+
+            rx_neuron.intake_synaptic_impulse(self.get_cycle_count(), charge.abs(), synaptic_type);
+            Ok(())
+        } else if self.sensory_neurons.borrow().contains_key(&hash) {
+            Err(EywaError::NotAnRxNeuron(loc.to_vec()))
+        } else {
+            Err(EywaError::UnknownLoc(loc.to_vec()))
+        }
+    }
+
+    /// Injects charge into the rx neuron at center and every rx
+    /// neuron within radius of it, returning the number of neurons
+    /// that were injected
+    pub fn inject_region(
+        &self,
+        center: &[i32],
+        radius: u32,
+        charge: f32,
+    ) -> Result<usize, EywaError> {
+        self.inject_charge(center, charge)?;
+
+        let mut injected = 1;
+
+        if let Some(center) = Loc::checked(center) {
+            for loc in self.ecp_geometry.neighbor_locs(center, radius) {
+                if self.inject_charge(&loc, charge).is_ok() {
+                    injected += 1;
+                }
+            }
+        }
+
+        Ok(injected)
+    }
+
+    /// Defines a named group of neurons, which can then be stimulated,
+    /// lesioned, and recorded from as one.  Every location must hold
+    /// a neuron, and defining a group under a name that's already
+    /// taken replaces the old group
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// # let encephalon = EncephalonBuilder::new()
+    /// #     .build(Box::new(BoxEcp::new(125, 0, 0, 27)), vec![], vec![])
+    /// #     .unwrap();
+    /// encephalon
+    ///     .define_group("core", vec![vec![2, 2, 2], vec![2, 2, 3]])
+    ///     .unwrap();
+    /// encephalon.stimulate_group("core", 100.).unwrap();
+    /// ```
+    pub fn define_group(&self, name: &str, locs: Vec<Vec<i32>>) -> Result<(), EywaError> {
+        if locs.is_empty() {
+            return Err(EywaError::EmptyGroup(name.to_string()));
+        }
+
+        let locs = self.group_locs(&locs)?;
+        self.groups.borrow_mut().insert(name.to_string(), locs);
+
+        Ok(())
+    }
+
+    /// Defines a named group of every rx and sensory neuron within region
+    pub fn define_region_group(&self, name: &str, region: &Region) -> Result<(), EywaError> {
+        let mut locs: Vec<Vec<i32>> = self
+            .ecp_geometry
+            .rx_locs()
+            .filter(|(loc, _, _)| region.contains(loc))
+            .map(|(loc, _, _)| loc.into())
+            .collect();
+
+        locs.extend(
+            self.ecp_geometry
+                .sensory_locs()
+                .filter(|(loc, _)| region.contains(loc))
+                .map(|(loc, _)| loc.into()),
+        );
+
+        self.define_group(name, locs)
+    }
+
+    /// Checks that every location of a group holds a neuron
+    fn group_locs(&self, locs: &[Vec<i32>]) -> Result<Vec<Loc>, EywaError> {
+        let rx_neurons = self.rx_neurons.borrow();
+        let sensory_neurons = self.sensory_neurons.borrow();
+
+        locs.iter()
+            .map(|loc| {
+                self.check_dims(loc)?;
+                let hash = self.ecp_geometry.loc_hash(loc);
+
+                match rx_neurons.contains_key(&hash) || sensory_neurons.contains_key(&hash) {
+                    true => Loc::checked(loc).ok_or_else(|| EywaError::UnknownLoc(loc.clone())),
+                    false => Err(EywaError::UnknownLoc(loc.clone())),
+                }
+            })
+            .collect()
+    }
+
+    /// Gets the locations of the neurons of the group called name
+    pub fn group(&self, name: &str) -> Result<Vec<Loc>, EywaError> {
+        self.groups
+            .borrow()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| EywaError::UnknownGroup(name.to_string()))
+    }
+
+    /// Injects charge into every rx neuron of the group called name,
+    /// as with inject_charge, returning the number of neurons that
+    /// were injected.  Sensory neurons of the group are skipped
+    pub fn stimulate_group(&self, name: &str, charge: f32) -> Result<usize, EywaError> {
+        Ok(self
+            .group(name)?
+            .iter()
+            .filter(|loc| self.inject_charge(loc, charge).is_ok())
+            .count())
+    }
+
+    /// Lesions every rx neuron of the group called name, so that
+    /// they drop all of their incoming charge and never fire.
+    /// Sensory neurons of the group are spared
+    pub fn lesion_group(&self, name: &str) -> Result<(), EywaError> {
+        let locs = self.group(name)?;
+        let rx_neurons = self.rx_neurons.borrow();
+        let mut lesioned = self.lesioned.borrow_mut();
+
+        for loc in locs {
+            let hash = self.ecp_geometry.loc_hash(&loc);
+
+            if rx_neurons.contains_key(&hash) {
+                lesioned.insert(hash);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Lifts the lesions of every neuron of the group called name.
+    /// A healed group (or one healed by restoring a snapshot from
+    /// before its lesion) picks up firing from the cycle it's in,
+    /// without remembering fires from cycles it never saw
+    pub fn heal_group(&self, name: &str) -> Result<(), EywaError> {
+        let locs = self.group(name)?;
+        let mut lesioned = self.lesioned.borrow_mut();
+
+        for loc in locs {
+            lesioned.remove(&self.ecp_geometry.loc_hash(&loc));
+        }
+
+        Ok(())
+    }
+
+    /// The mean EMA of the neurons of the group called name
+    pub fn group_mean_ema(&self, name: &str) -> Result<f32, EywaError> {
+        let locs = self.group(name)?;
+        let rx_neurons = self.rx_neurons.borrow();
+        let sensory_neurons = self.sensory_neurons.borrow();
+
+        let total: f32 = locs
+            .iter()
+            .map(|loc| {
+                let hash = self.ecp_geometry.loc_hash(loc);
+
+                match rx_neurons.get(&hash) {
+                    Some(neuron) => neuron.ema(),
+                    None => sensory_neurons.get(&hash).map_or(0., |neuron| neuron.ema()),
+                }
+            })
+            .sum();
+
+        Ok(total / locs.len().max(1) as f32)
+    }
+
+    /// The number of neurons of the group called
+    /// name that fired on the last cycle
+    pub fn group_fire_count_last_cycle(&self, name: &str) -> Result<u32, EywaError> {
+        let locs = self.group(name)?;
+        let next_cycle = self.get_cycle_count() + 1;
+        let rx_neurons = self.rx_neurons.borrow();
+        let sensory_neurons = self.sensory_neurons.borrow();
+
+        Ok(locs
+            .iter()
+            .filter(|loc| {
+                let hash = self.ecp_geometry.loc_hash(loc);
+
+                match rx_neurons.get(&hash) {
+                    Some(neuron) => neuron.fired_on_prev_cycle(next_cycle),
+                    None => sensory_neurons
+                        .get(&hash)
+                        .is_some_and(|neuron| neuron.fired_on_prev_cycle(next_cycle)),
+                }
+            })
+            .count() as u32)
+    }
+
+    /// Places a probe on the neuron at loc, which records a
+    /// sample of that neuron every cycle from now on
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// # struct Light;
+    /// # impl Sensor for Light {
+    /// #     fn measure(&mut self) -> f32 { 0.0 }
+    /// #     fn get_name(&self) -> String { "light".to_string() }
+    /// # }
+    /// let encephalon = EncephalonBuilder::new()
+    ///     .build(Box::new(BoxEcp::new(125, 1, 0, 27)), vec![Box::new(Light)], vec![])
+    ///     .unwrap();
+    ///
+    /// let probe = encephalon.add_probe("center", vec![2, 2, 2]).unwrap();
+    /// encephalon.run_n_cycles(10);
+    ///
+    /// assert_eq!(encephalon.probe_trace(probe).unwrap().len(), 10);
+    /// ```
+    pub fn add_probe(&self, name: &str, loc: Vec<i32>) -> Result<ProbeId, EywaError> {
+        self.check_dims(&loc)?;
+        let hash = self.ecp_geometry.loc_hash(&loc);
+
+        if !self.rx_neurons.borrow().contains_key(&hash)
+            && !self.sensory_neurons.borrow().contains_key(&hash)
+        {
+            return Err(EywaError::UnknownLoc(loc));
+        }
+
+        let mut probes = self.probes.borrow_mut();
+
+        if probes.iter().any(|probe| probe.name() == name) {
+            return Err(EywaError::DuplicateProbeName(name.to_string()));
+        }
+
+        let id = probes.len();
+        probes.push(Probe::new(name.to_string(), loc, self.probe_capacity));
+
+        self.probe_hashes
+            .borrow_mut()
+            .entry(hash)
+            .or_default()
+            .push(id);
+
+        Ok(ProbeId(id))
+    }
+
+    /// Gets the id of the probe called name
+    pub fn probe_id(&self, name: &str) -> Option<ProbeId> {
+        self.probes
+            .borrow()
+            .iter()
+            .position(|probe| probe.name() == name)
+            .map(ProbeId)
+    }
+
+    /// Returns the samples recorded by a probe, from oldest to newest
+    pub fn probe_trace(&self, id: ProbeId) -> Result<Vec<ProbeSample>, EywaError> {
+        self.probes
+            .borrow()
+            .get(id.0)
+            .map(|probe| probe.trace())
+            .ok_or(EywaError::UnknownProbe(id.0))
+    }
+
+    /// Records a sample into every probe placed on the neuron at hash
+    fn record_probes(&self, hash: &str, outcome: CycleOutcome, reading: ProbeReading) {
+        if self.probing.get() {
+            return;
+        }
+
+        if let Some(ids) = self.probe_hashes.borrow().get(hash) {
+            let mut probes = self.probes.borrow_mut();
+
+            for id in ids {
+                probes[*id].record(ProbeSample {
+                    cycle: self.get_cycle_count(),
+                    reading,
+                    fired: outcome.fired,
+                    ema: outcome.ema,
+                    adaptation: outcome.adaptation,
+                    input_gain: outcome.input_gain,
+                });
+            }
+        }
+    }
+
+    /// Returns true with probability p, drawn from the
+    /// encephalon's seeded random number generator
+    pub fn random_bool(&self, p: f32) -> bool {
+        self.rng.borrow_mut().gen_bool(p.clamp(0., 1.) as f64)
+    }
+
+    /// Starts the strength of a newly formed plastic synapse
+    /// wherever the encephalon's strength init says to
+    pub fn init_strength(&self, strength: &RefCell<dyn SynapticStrength>) {
+        if let Some(x) = self.strength_init.sample(&mut *self.rng.borrow_mut()) {
+            strength.borrow_mut().init_at(x);
+        }
+    }
+
+    /// Finds a random neuron within the vicinity of loc
+    /// which allows neurons to make new random connections.
+    ///
+    /// With probability small_world_p, the neuron is instead
+    /// drawn from every plastic neuron of the encephalon, which
+    /// adds long range shortcuts to otherwise local connectivity.
+    /// Shortcuts never land on an actuator neuron, which is only
+    /// reached from its neighborhood
+    pub fn local_random_neuron(&self, loc: Loc) -> Option<Rc<dyn NeuronicRx>> {
+        debug_assert!(
+            self.check_dims(&loc).is_ok(),
+            "{} doesn't have the {} dimensions of the geometry",
+            loc,
+            self.ecp_geometry.dims()
+        );
+
+        let hash_option = if self.small_world_p > 0. && self.random_bool(self.small_world_p) {
+            self.random_plastic_hash(loc)
+        } else {
+            self.ecp_geometry
+                .local_random_hash(&loc, &mut *self.rng.borrow_mut())
+        };
+
+        if let Some(hash) = hash_option {
+            if let Some(rx_ref) = self.rx_neurons.borrow().get(&hash) {
+                return Some(Rc::clone(rx_ref));
+            }
+        }
+        None
+    }
+
+    /// True if the targeting rules let a neuron of kind source
+    /// form a plastic synapse onto a neuron of kind target
+    pub fn allows_target(&self, source: NeuronKind, target: NeuronKind) -> bool {
+        self.targeting_rules.allows(source, target)
+    }
+
+    /// The locations within radius of loc (or its nearby radius)
+    /// holding neurons the targeting rules don't let a neuron of
+    /// kind source target.  Empty if the rules allow every target
+    pub fn disallowed_targets(
+        &self,
+        source: NeuronKind,
+        loc: Loc,
+        radius: Option<u32>,
+    ) -> Vec<TargetKey> {
+        if self.targeting_rules.allows_all(source) {
+            return Vec::new();
+        }
+
+        let radius = radius.unwrap_or_else(|| self.ecp_geometry.nearby_radius());
+        let rx_neurons = self.rx_neurons.borrow();
+
+        self.ecp_geometry
+            .neighbor_locs(loc, radius)
+            .into_iter()
+            .filter(|neighbor| {
+                rx_neurons
+                    .get(&self.ecp_geometry.loc_hash(neighbor))
+                    .is_some_and(|neuron| !self.allows_target(source, neuron.kind()))
+            })
+            .collect()
+    }
+
+    /// Picks uniformly among the nearby neurons of loc that
+    /// aren't in exclude, returning None once every nearby
+    /// neuron is excluded
+    pub fn local_random_unconnected_neuron(
+        &self,
+        loc: Loc,
+        exclude: &[TargetKey],
+    ) -> Option<Rc<dyn NeuronicRx>> {
+        self.random_neighbor_neuron(loc, self.ecp_geometry.nearby_radius(), exclude)
+    }
+
+    /// Finds a random rx neuron within radius of loc, as
+    /// defined by the geometry's neighbors, that isn't in exclude
+    pub fn random_neighbor_neuron(
+        &self,
+        loc: Loc,
+        radius: u32,
+        exclude: &[TargetKey],
+    ) -> Option<Rc<dyn NeuronicRx>> {
+        let exclude: HashSet<&TargetKey> = exclude.iter().collect();
+
+        let mut neighbors = self.ecp_geometry.neighbor_locs(loc, radius);
+        neighbors.retain(|neighbor| !exclude.contains(neighbor));
+
+        if neighbors.is_empty() {
+            return None;
+        }
+
+        let neighbor = &neighbors[self.rng.borrow_mut().gen_range(0, neighbors.len())];
+
+        self.rx_neurons
+            .borrow()
+            .get(&self.ecp_geometry.loc_hash(neighbor))
+            .map(Rc::clone)
+    }
+
+    /// Picks the hash of any plastic neuron other than the one at loc
+    fn random_plastic_hash(&self, loc: Loc) -> Option<String> {
+        let plastic_hashes = self.plastic_hashes.borrow();
+
+        if plastic_hashes.is_empty() {
+            return None;
+        }
+
+        let hash = &plastic_hashes[self.rng.borrow_mut().gen_range(0, plastic_hashes.len())];
+
+        match *hash == self.ecp_geometry.loc_hash(&loc) {
+            true => None,
+            false => Some(hash.clone()),
+        }
+    }
+}
+
+impl fmt::Debug for Encephalon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encephalon")
+            .field("cycle_count", &self.get_cycle_count())
+            .field("rx_neurons", &self.rx_neurons.borrow().len())
+            .field("sensory_neurons", &self.sensory_neurons.borrow().len())
+            .field("reflexes", &self.reflexes.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Counts records by plasticity, with the mean normalized
+/// strength of the plastic ones
+fn synapse_summary(records: &[SynapseRecord]) -> String {
+    let plastic: Vec<f32> = records
+        .iter()
+        .filter_map(|record| record.normalized_strength.filter(|_| record.plastic))
+        .collect();
+    let statics = records.iter().filter(|record| !record.plastic).count();
+
+    let mean = match plastic.is_empty() {
+        true => 0.,
+        false => plastic.iter().sum::<f32>() / plastic.len() as f32,
+    };
+
+    format!(
+        "{} plastic synapses (mean normalized strength {:.3}), {} static synapses",
+        plastic.len(),
+        mean,
+        statics
+    )
+}
+
+/// The transmissions of every sensory and rx neuron, in
+/// traversal order, which eval mode fires in place of synapses
+struct EvalCache {
+    generation: u64, //The connectome generation the transmissions were taken at
+    sensory: Vec<Vec<Transmission>>,
+    rx: Vec<Vec<Transmission>>,
+}
+
+/// Runs a cycle of neuron in eval mode, firing its
+/// transmissions if it fires
+fn run_cycle_eval<N: Neuronic + ?Sized>(
+    neuron: &N,
+    transmissions: &[Transmission],
+    cycle: u64,
+) -> CycleOutcome {
+    let mut outcome = neuron.run_cycle_eval(cycle);
+
+    if outcome.fired {
+        for transmission in transmissions {
+            transmission.fire(cycle);
+        }
+
+        outcome.transmissions = transmissions.len() as u32;
+    }
+
+    outcome
+}
+
+/// Forms the synapse described by spec from source onto target.
+/// False if the source doesn't transmit
+fn adopt_synapse<N: Neuronic + ?Sized>(
+    source: &N,
+    spec: &SynapseSpec,
+    target: Rc<dyn NeuronicRx>,
+    strength_generator: &dyn Fn() -> Box<RefCell<dyn SynapticStrength>>,
+    cycle: u64,
+) -> bool {
+    match spec.plastic {
+        true => {
+            let strength = strength_generator();
+            strength
+                .borrow_mut()
+                .set_from_strength(spec.initial_strength);
+
+            source.adopt_plastic_synapse(PlasticSynapse::new(
+                strength,
+                spec.synaptic_type,
+                target,
+                cycle,
+            ))
+        }
+        false => source.adopt_static_synapse(StaticSynapse::new(
+            spec.initial_strength,
+            spec.synaptic_type,
+            target,
+        )),
+    }
+}
+
+/// The value of pair, from the current values of its members
+fn pair_value(
+    pair: &AntagonistPair,
+    actuator_interfaces: &HashMap<ActuatorName, ActuatorInterface>,
+) -> f32 {
+    let member_value = |name: &ActuatorName| {
+        actuator_interfaces
+            .get(name)
+            .map_or(0.0, |interface| interface.read_ema_frequency())
+    };
+
+    pair.value(member_value(&pair.agonist), member_value(&pair.antagonist))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actuator::{ActuatorError, FnActuator};
+    use crate::clock::MockClock;
+    use crate::ecp_geometry::BoxEcp;
+    use crate::neuron::synapse::synaptic_strength::EmStrength;
+    use crate::neuron::synapse::{Staleness, SynapseSpec};
+    use crate::neuron_interfaces::sensory_encoders::InverseEncoder;
+    use crate::snapshot::SnapshotFormat;
+    use crate::test_util::{actuator, reflex, sensor, settable_sensor};
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    struct Light;
+
+    impl Sensor for Light {
+        fn measure(&mut self) -> f32 {
+            0.5
+        }
+
+        fn get_name(&self) -> String {
+            "light".to_string()
+        }
+    }
+
+    fn motor() -> Box<dyn Actuator> {
+        Box::new(FnActuator::new("motor".to_string(), |_| {}))
+    }
+
+    fn build(seed: u64) -> Rc<Encephalon> {
+        EncephalonBuilder::new()
+            .seed(seed)
+            .fire_threshold(5.)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![Box::new(Light)],
+                vec![motor()],
+            )
+            .unwrap()
+    }
+
+    /// Which rx neurons fire on each of the next cycles
+    fn raster(encephalon: &Encephalon, cycles: usize) -> Vec<Vec<bool>> {
+        (0..cycles)
+            .map(|_| {
+                encephalon.run_cycle();
+
+                let next_cycle = encephalon.get_cycle_count() + 1;
+                let rx_neurons = encephalon.rx_neurons.borrow();
+
+                encephalon
+                    .rx_hashes
+                    .borrow()
+                    .iter()
+                    .map(|hash| rx_neurons[hash].fired_on_prev_cycle(next_cycle))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn restored_brain_finishes_the_uninterrupted_raster() {
+        // Under a competitive budget every synapse that forms draws
+        // on the rng to pick one to replace, as does every small world
+        // shortcut, so the stream has to resume exactly
+        let build = || {
+            EncephalonBuilder::new()
+                .seed(23)
+                .fire_threshold(5.)
+                .small_world_p(0.5)
+                .global_synapse_budget(2_000)
+                .budget_policy(BudgetPolicy::CompetitiveReplacement { sample_size: 4 })
+                .build(
+                    Box::new(BoxEcp::new(343, 1, 1, 27)),
+                    vec![Box::new(Light)],
+                    vec![motor()],
+                )
+                .unwrap()
+        };
+
+        let uninterrupted = build();
+        let first_half = raster(&uninterrupted, 250);
+        let snapshot = uninterrupted.snapshot();
+        let second_half = raster(&uninterrupted, 250);
+
+        let resumed = build();
+        resumed.restore(&snapshot).unwrap();
+
+        let mut spliced = first_half.clone();
+        spliced.extend(raster(&resumed, 250));
+
+        let mut expected = first_half;
+        expected.extend(second_half);
+
+        assert_eq!(spliced, expected);
+    }
+
+    #[test]
+    fn seeded_rng_keeps_its_stream() {
+        let encephalon = build(23);
+        let mut expected = ChaCha20Rng::seed_from_u64(23);
+        expected.set_word_pos(encephalon.rng.borrow().get_word_pos());
+
+        let drawn: Vec<u32> = (0..8).map(|_| encephalon.rng.borrow_mut().gen()).collect();
+        let expected: Vec<u32> = (0..8).map(|_| expected.gen()).collect();
+
+        assert_eq!(drawn, expected);
+    }
+
+    #[test]
+    fn restore_rebuilds_plastic_synapses() {
+        let original = build(11);
+        original.run_n_cycles(250);
+
+        let snapshot = original.snapshot();
+        let bytes = snapshot.to_bytes(SnapshotFormat::Bincode).unwrap();
+        let snapshot = EncephalonSnapshot::from_bytes(&bytes, SnapshotFormat::Bincode).unwrap();
+
+        let fresh = build(11);
+        assert_ne!(fresh.connectome(), original.connectome());
+
+        fresh.restore(&snapshot).unwrap();
+
+        assert_eq!(fresh.connectome(), original.connectome());
+        assert_eq!(
+            fresh.plastic_synapse_count(),
+            original.plastic_synapse_count()
+        );
+
+        let expected = raster(&original, 250);
+
+        assert!(expected.iter().flatten().any(|&fired| fired));
+        assert_eq!(raster(&fresh, 250), expected);
+        assert_eq!(fresh.connectome(), original.connectome());
+    }
+
+    #[test]
+    fn eval_mode_fires_the_restored_connectome() {
+        let trained = build(11);
+        trained.run_n_cycles(300);
+        let snapshot = trained.snapshot();
+
+        let eval = build(11);
+        let frozen = build(11);
+
+        eval.set_eval_mode(true);
+        frozen.freeze_plasticity(true);
+
+        // The eval cache is built from the untrained synapses...
+        assert_eq!(raster(&eval, 20), raster(&frozen, 20));
+
+        // ...and has to be rebuilt from the restored ones
+        eval.restore(&snapshot).unwrap();
+        frozen.restore(&snapshot).unwrap();
+
+        let expected = raster(&frozen, 100);
+
+        assert!(expected.iter().flatten().any(|&fired| fired));
+        assert_eq!(raster(&eval, 100), expected);
+    }
+
+    #[test]
+    fn restore_rejects_synapses_from_actuators() {
+        let original = build(11);
+        original.run_n_cycles(10);
+
+        let actuator: Vec<i32> = original.actuator_loc(&"motor".into()).unwrap().into();
+        let mut snapshot = original.snapshot();
+        let mut synapse = snapshot.plastic_synapses.as_ref().unwrap()[0].clone();
+
+        synapse.source = actuator.clone();
+        snapshot.plastic_synapses.as_mut().unwrap().push(synapse);
+
+        let connectome = original.connectome();
+
+        assert_eq!(
+            original.restore(&snapshot),
+            Err(EywaError::NotATxNeuron(actuator))
+        );
+        assert_eq!(original.connectome(), connectome);
+    }
+
+    #[test]
+    fn every_actuator_loc_holds_an_actuator_neuron() {
+        let names = ["left", "right", "forward"];
+        let encephalon = EncephalonBuilder::new()
+            .build(
+                Box::new(BoxEcp::new(125, 1, 3, 27)),
+                vec![sensor("light", 0.)],
+                names.iter().map(|name| actuator(name)).collect(),
+            )
+            .unwrap();
+
+        for name in names.iter() {
+            let loc = encephalon.actuator_loc(&ActuatorName::from(*name)).unwrap();
+
+            assert_eq!(
+                encephalon.neuron_kind_at(&loc),
+                Ok(Some(NeuronKind::Actuator))
+            );
+        }
+
+        let light = encephalon.sensor_loc(&SensorName::from("light")).unwrap();
+
+        assert_eq!(
+            encephalon.neuron_kind_at(&light),
+            Ok(Some(NeuronKind::Sensory))
+        );
+        assert_eq!(
+            encephalon.neuron_kind_at(&[2, 2, 2]),
+            Ok(Some(NeuronKind::Plastic))
+        );
+        assert_eq!(encephalon.neuron_kind_at(&[2, 2, 5]), Ok(None));
+    }
+
+    #[test]
+    fn injected_charge_fires_a_neuron_on_the_next_cycle() {
+        let encephalon = EncephalonBuilder::new()
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        // Nothing drives the neurons until one is injected
+        for _ in 0..3 {
+            assert_eq!(encephalon.run_cycle().rx_fired, 0);
+        }
+
+        encephalon.inject_charge(&[2, 2, 2], 100.).unwrap();
+
+        assert_eq!(encephalon.run_cycle().rx_fired, 1);
+
+        // Sensory neurons hold no charge to inject into
+        assert!(matches!(
+            encephalon.inject_charge(&[0, 0, -1], 100.),
+            Err(EywaError::NotAnRxNeuron(loc)) if loc == vec![0, 0, -1]
+        ));
+        assert!(matches!(
+            encephalon.inject_charge(&[9, 9, 9], 100.),
+            Err(EywaError::UnknownLoc(loc)) if loc == vec![9, 9, 9]
+        ));
+    }
+
+    #[test]
+    fn cycle_metrics_count_fires_against_the_threshold() {
+        let encephalon = EncephalonBuilder::new()
+            .max_plastic_synapses(0)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        // Charge injected into one neuron on each cycle, which it
+        // takes in on the next.  Only charge past 10 fires it
+        let charges = [4., 10., 10.5, 0., 25., 9.99];
+
+        for charge in charges.iter() {
+            encephalon.inject_charge(&[2, 2, 2], *charge).unwrap();
+            let metrics = encephalon.run_cycle();
+
+            assert_eq!(metrics.rx_fired, (*charge > 10.) as u32, "{}", charge);
+            assert_eq!(metrics.charge_consumed, *charge);
+        }
+    }
+
+    #[test]
+    fn probe_traces_an_actuator_driven_by_a_period_5_reflex() {
+        // The light measures 0.2, which the inverse encoder
+        // turns into a sensory neuron firing every 5 cycles
+        let encephalon = EncephalonBuilder::new()
+            .sensory_encoder(InverseEncoder { max_period: 100 })
+            .max_plastic_synapses(0)
+            .reflexes(vec![reflex("light", "motor", 12.)])
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.2)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let light = encephalon.sensor_loc(&SensorName::from("light")).unwrap();
+        let motor = encephalon
+            .actuator_loc(&ActuatorName::from("motor"))
+            .unwrap();
+
+        let light_probe = encephalon.add_probe("light", light.into()).unwrap();
+        let motor_probe = encephalon.add_probe("motor", motor.into()).unwrap();
+
+        encephalon.run_n_cycles(50);
+
+        let light_trace = encephalon.probe_trace(light_probe).unwrap();
+        let motor_trace = encephalon.probe_trace(motor_probe).unwrap();
+
+        assert_eq!(motor_trace.len(), 50);
+        assert!(light_trace
+            .iter()
+            .all(|sample| sample.reading == ProbeReading::Period(5)));
+
+        let light_fires: Vec<u64> = light_trace
+            .iter()
+            .filter(|sample| sample.fired)
+            .map(|sample| sample.cycle)
+            .collect();
+
+        assert_eq!(light_fires.len(), 10);
+        assert!(light_fires.windows(2).all(|pair| pair[1] - pair[0] == 5));
+
+        // The motor's charge rises to the reflex's strength on the
+        // cycle after each of the light's fires, firing the motor,
+        // and falls right back to nothing in between
+        for sample in motor_trace.iter() {
+            let driven = light_fires.contains(&(sample.cycle - 1));
+
+            assert_eq!(sample.fired, driven, "cycle {}", sample.cycle);
+
+            match driven {
+                true => assert_eq!(sample.reading, ProbeReading::Charge(12.)),
+                false => assert_eq!(sample.reading, ProbeReading::Charge(0.)),
+            }
+        }
+    }
+
+    /// An actuator that fails its first five calls
+    struct Flaky {
+        calls: Rc<Cell<u32>>,
+    }
+
+    impl Actuator for Flaky {
+        fn set_control_value(&self, _value: f32) -> Result<(), ActuatorError> {
+            self.calls.set(self.calls.get() + 1);
+
+            if self.calls.get() <= 5 {
+                Err(ActuatorError::new("serial port hiccup".to_string()))
+            } else {
+                Ok(())
+            }
+        }
+
+        fn get_name(&self) -> String {
+            "motor".to_string()
+        }
+    }
+
+    fn flaky_brain(failure_threshold: u32, calls: &Rc<Cell<u32>>) -> Rc<Encephalon> {
+        EncephalonBuilder::new()
+            .actuator_failure_threshold(failure_threshold)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.)],
+                vec![Box::new(Flaky {
+                    calls: Rc::clone(calls),
+                })],
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn an_actuator_rides_out_failures_below_the_threshold() {
+        let calls = Rc::new(Cell::new(0));
+        let encephalon = flaky_brain(10, &calls);
+
+        for cycle in 1..=10 {
+            let metrics = encephalon.run_cycle();
+
+            assert_eq!(metrics.actuator_failures, if cycle <= 5 { 1 } else { 0 });
+            assert_eq!(metrics.disabled_actuators, 0);
+        }
+
+        let health = encephalon.health_report().actuators["motor"];
+
+        assert_eq!((health.consecutive_failures, health.total_failures), (0, 5));
+    }
+
+    #[test]
+    fn a_disabled_actuator_recovers_once_reset() {
+        let calls = Rc::new(Cell::new(0));
+        let encephalon = flaky_brain(3, &calls);
+
+        // At the threshold it's disabled, and isn't called until reset
+        encephalon.run_n_cycles(10);
+
+        assert_eq!(calls.get(), 3);
+        assert_eq!(encephalon.run_cycle().disabled_actuators, 1);
+        assert!(encephalon.health_report().actuators["motor"].disabled);
+
+        encephalon
+            .reset_actuator_failures(&ActuatorName::from("motor"))
+            .unwrap();
+        encephalon.run_n_cycles(3);
+
+        let health = encephalon.health_report().actuators["motor"];
+
+        assert_eq!(calls.get(), 6);
+        assert!(!health.disabled);
+        assert_eq!((health.consecutive_failures, health.total_failures), (0, 5));
+    }
+
+    #[test]
+    fn ema_field_is_hotter_around_a_driven_sensor() {
+        let encephalon = EncephalonBuilder::new()
+            .seed(3)
+            .build(
+                Box::new(BoxEcp::new(1000, 2, 1, 27)),
+                vec![sensor("driven", 1.0), sensor("idle", 0.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        encephalon.run_n_cycles(100);
+
+        let driven = encephalon.sensor_loc(&"driven".into()).unwrap();
+        let field = encephalon.ema_field();
+
+        let mean_ema = |within: &dyn Fn(&[i32]) -> bool| {
+            let emas: Vec<f32> = field
+                .iter()
+                .filter(|(loc, _)| within(loc))
+                .map(|(_, ema)| *ema)
+                .collect();
+
+            emas.iter().sum::<f32>() / emas.len() as f32
+        };
+
+        // The rx neurons within a step of the driven sensor,
+        // against the two planes on the far side of the box
+        let near = mean_ema(&|loc| {
+            loc[2] >= 0
+                && loc
+                    .iter()
+                    .zip(driven.iter())
+                    .all(|(a, b)| (a - b).abs() <= 1)
+        });
+        let far = mean_ema(&|loc| loc[2] >= 8);
+
+        assert!(near > far);
+    }
+
+    /// The fraction of 2000 neurons picked for a new synapse out
+    /// of the center of a box of side 9 that lie outside of its
+    /// nearby cube
+    fn far_fraction(small_world_p: f32) -> f32 {
+        let encephalon = EncephalonBuilder::new()
+            .seed(13)
+            .small_world_p(small_world_p)
+            .build(
+                Box::new(BoxEcp::new(750, 1, 1, 27)),
+                vec![sensor("light", 0.)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let center = Loc::new(&[4, 4, 4]);
+        let rx_neurons = encephalon.rx_neurons.borrow();
+        let mut nearby = Vec::new();
+
+        for x in 3..=5 {
+            for y in 3..=5 {
+                for z in 3..=5 {
+                    let hash = encephalon.ecp_geometry.loc_hash(&[x, y, z]);
+                    nearby.push(Rc::as_ptr(&rx_neurons[&hash]) as *const ());
+                }
+            }
+        }
+
+        let far = (0..2000)
+            .filter_map(|_| encephalon.local_random_neuron(center))
+            .filter(|neuron| !nearby.contains(&(Rc::as_ptr(neuron) as *const ())))
+            .count();
+
+        far as f32 / 2000.
+    }
+
+    #[test]
+    fn shortcuts_reach_beyond_the_nearby_cube() {
+        assert_eq!(far_fraction(0.), 0.);
+        assert!((far_fraction(0.2) - 0.2).abs() < 0.05);
+    }
+
+    fn empty_brain() -> Rc<Encephalon> {
+        EncephalonBuilder::new()
+            .build(Box::new(BoxEcp::new(125, 0, 0, 27)), vec![], vec![])
+            .unwrap()
+    }
+
+    #[test]
+    fn progress_is_reported_every_interval() {
+        let encephalon = empty_brain();
+        let mut updates = Vec::new();
+
+        encephalon.run_n_cycles_with(1000, 100, |update| {
+            assert_eq!(update.total, 1000);
+            assert!(update.cycles_per_sec > 0.);
+
+            updates.push(update.cycle);
+        });
+
+        assert_eq!(updates, (1..=10).map(|i| i * 100).collect::<Vec<u32>>());
+        assert_eq!(encephalon.get_cycle_count(), 1000);
+    }
+
+    #[test]
+    fn an_interval_of_zero_never_reports_progress() {
+        let encephalon = empty_brain();
+        let mut calls = 0;
+
+        encephalon.run_n_cycles_with(1000, 0, |_| calls += 1);
+
+        assert_eq!(calls, 0);
+        assert_eq!(encephalon.get_cycle_count(), 1000);
+    }
+
+    #[test]
+    fn only_unconnected_neighbors_are_picked() {
+        let encephalon = EncephalonBuilder::new()
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let center = Loc::new(&[2, 2, 2]);
+        let mut neighbors = Vec::new();
+
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if (dx, dy, dz) != (0, 0, 0) {
+                        neighbors.push(Loc::new(&[2 + dx, 2 + dy, 2 + dz]));
+                    }
+                }
+            }
+        }
+
+        // Connected to all 26 neighbors, there's nothing left to pick
+        assert!(encephalon
+            .local_random_unconnected_neuron(center, &neighbors)
+            .is_none());
+
+        // Connected to 25, only the missing neighbor can be picked
+        let missing = neighbors.remove(7);
+
+        for _ in 0..50 {
+            let neuron = encephalon
+                .local_random_unconnected_neuron(center, &neighbors)
+                .unwrap();
+
+            assert_eq!(neuron.loc(), missing);
+        }
+    }
+
+    /// The growth of a correlated pair's synapse over cycles 10
+    /// to 30, during which the learning rate is learning_rate
+    fn correlated_growth(learning_rate: f32) -> f32 {
+        let source = vec![1, 1, 1];
+        let encephalon = EncephalonBuilder::new()
+            .seed(37)
+            .max_plastic_synapses(1)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let source_synapse = || {
+            encephalon
+                .connectome()
+                .into_iter()
+                .find(|record| record.plastic && record.source == source)
+        };
+
+        // The source forms its one synapse once it fires
+        while source_synapse().is_none() {
+            encephalon.inject_charge(&source, 100.).unwrap();
+            encephalon.run_cycle();
+        }
+
+        let target = source_synapse().unwrap().target;
+
+        // From then on, both neurons fire every cycle
+        let run = |cycles| {
+            for _ in 0..cycles {
+                encephalon.inject_charge(&source, 100.).unwrap();
+                encephalon.inject_charge(&target, 100.).unwrap();
+                encephalon.run_cycle();
+            }
+
+            source_synapse().unwrap().strength
+        };
+
+        let before = run(10);
+        encephalon.set_learning_rate(learning_rate);
+
+        run(20) - before
+    }
+
+    #[test]
+    fn halving_the_learning_rate_slows_a_correlated_pair() {
+        let full = correlated_growth(1.0);
+        let halved = correlated_growth(0.5);
+
+        assert!(halved > 0.);
+        assert!(halved < 0.75 * full);
+    }
+
+    #[test]
+    fn a_learning_rate_of_zero_freezes_strengths() {
+        assert_eq!(correlated_growth(0.0), 0.);
+    }
+
+    /// A bare box whose only plastic synapses run from the center
+    /// to a neighbor, one per strength.  Strengths are EmStrengths
+    /// out of 16 that halve their distance to 16 when strengthened
+    /// and halve when weakened, starting from 8
+    fn brain_with_strengths(strengths: Vec<f32>) -> Rc<Encephalon> {
+        let targets = [
+            vec![2, 2, 3],
+            vec![2, 3, 2],
+            vec![3, 2, 2],
+            vec![2, 2, 1],
+            vec![2, 1, 2],
+        ];
+
+        let specs = strengths
+            .into_iter()
+            .zip(targets.iter())
+            .map(|(initial_strength, target)| SynapseSpec {
+                src_loc: vec![2, 2, 2],
+                dst_loc: target.clone(),
+                synaptic_type: SynapticType::Excitatory,
+                initial_strength,
+                plastic: true,
+            })
+            .collect();
+
+        EncephalonBuilder::new()
+            .synaptic_strength_generator(Rc::new(|| {
+                Box::new(RefCell::new(EmStrength::new(16., 1., 0.5)))
+            }))
+            .initial_synapses(specs)
+            .build(Box::new(BoxEcp::new(125, 0, 0, 27)), vec![], vec![])
+            .unwrap()
+    }
+
+    /// The strength of a fresh synapse after strengthening
+    /// it `strengthen` times and then weakening it `weaken` times
+    fn strength_after(strengthen: u32, weaken: u32) -> f32 {
+        let mut strength = EmStrength::new(16., 1., 0.5);
+
+        for _ in 0..strengthen {
+            strength.strengthen();
+        }
+
+        for _ in 0..weaken {
+            strength.weaken();
+        }
+
+        strength.get_strength()
+    }
+
+    #[test]
+    fn strength_percentiles_match_hand_computed_values() {
+        // 8 -> 12 -> 14, 8 -> 12, 8, 8 -> 4, and 8 -> 4 -> 2, out of 16
+        let encephalon = brain_with_strengths(vec![
+            strength_after(2, 0),
+            strength_after(1, 0),
+            strength_after(0, 0),
+            strength_after(0, 1),
+            strength_after(0, 2),
+        ]);
+
+        assert_eq!(
+            encephalon.sorted_plastic_strengths(),
+            vec![0.125, 0.25, 0.5, 0.75, 0.875]
+        );
+
+        assert_eq!(encephalon.strength_percentile(0.), 0.125);
+        assert_eq!(encephalon.strength_percentile(25.), 0.25);
+        assert_eq!(encephalon.strength_percentile(50.), 0.5);
+        assert_eq!(encephalon.strength_percentile(100.), 0.875);
+
+        // Ranks 0.4 and 3.6 interpolate between neighbors
+        assert!((encephalon.strength_percentile(10.) - 0.175).abs() < 1e-6);
+        assert!((encephalon.strength_percentile(90.) - 0.825).abs() < 1e-6);
+
+        // Percentiles beyond [0, 100] are clamped
+        assert_eq!(encephalon.strength_percentile(-10.), 0.125);
+        assert_eq!(encephalon.strength_percentile(150.), 0.875);
+    }
+
+    #[test]
+    fn strength_histogram_matches_hand_computed_bins() {
+        let encephalon = brain_with_strengths(vec![
+            strength_after(2, 0),
+            strength_after(1, 0),
+            strength_after(0, 0),
+            strength_after(0, 1),
+            strength_after(0, 2),
+        ]);
+
+        // Bins a quarter wide from 0.125, the strongest landing in the last
+        assert_eq!(
+            encephalon.strength_histogram(3),
+            vec![(0.125, 2), (0.375, 1), (0.625, 2)]
+        );
+        assert_eq!(encephalon.strength_histogram(1), vec![(0.125, 5)]);
+        assert_eq!(encephalon.strength_histogram(0), vec![]);
+
+        // Every synapse at the same strength lands in the first bin
+        let uniform = brain_with_strengths(vec![strength_after(1, 0); 3]);
+
+        assert_eq!(uniform.strength_histogram(2), vec![(0.75, 3), (0.75, 0)]);
+    }
+
+    #[test]
+    fn strengths_of_no_synapses_are_empty() {
+        let encephalon = brain_with_strengths(vec![]);
+
+        assert!(encephalon.sorted_plastic_strengths().is_empty());
+        assert_eq!(encephalon.strength_percentile(0.), 0.);
+        assert_eq!(encephalon.strength_percentile(100.), 0.);
+        assert_eq!(encephalon.strength_histogram(4), vec![]);
+    }
+
+    /// The charge each rx neuron evaluates on the
+    /// cycle after 5.0 is injected into the center
+    fn charges_after_injection(builder: EncephalonBuilder) -> Vec<(Vec<i32>, f32)> {
+        let encephalon = builder
+            .max_plastic_synapses(0)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 0, 27)),
+                vec![sensor("light", 0.0)],
+                vec![],
+            )
+            .unwrap();
+
+        let mut probes = Vec::new();
+
+        for x in 0..5 {
+            for y in 0..5 {
+                for z in 0..5 {
+                    let loc = vec![x, y, z];
+                    let probe = encephalon
+                        .add_probe(&format!("rx {:?}", loc), loc.clone())
+                        .unwrap();
+
+                    probes.push((loc, probe));
+                }
+            }
+        }
+
+        encephalon.run_n_cycles(3);
+        encephalon.inject_charge(&[2, 2, 2], 5.).unwrap();
+        encephalon.run_cycle();
+
+        probes
+            .into_iter()
+            .map(|(loc, probe)| {
+                let sample = *encephalon.probe_trace(probe).unwrap().last().unwrap();
+
+                match sample.reading {
+                    ProbeReading::Charge(charge) => (loc, charge),
+                    ProbeReading::Period(_) => unreachable!(),
+                }
+            })
+            .collect()
+    }
+
+    fn charge_at(charges: &[(Vec<i32>, f32)], loc: &[i32]) -> f32 {
+        charges.iter().find(|(at, _)| at[..] == *loc).unwrap().1
+    }
+
+    #[test]
+    fn injected_charge_spreads_across_gap_junctions() {
+        let coupled = charges_after_injection(EncephalonBuilder::new().gap_junction(1.0, 0.25));
+        let total: f32 = coupled.iter().map(|(_, charge)| charge).sum();
+
+        assert!(charge_at(&coupled, &[2, 2, 2]) < 5.);
+        assert!(charge_at(&coupled, &[2, 2, 3]) > 0.);
+        assert!(coupled.iter().all(|(_, charge)| *charge >= 0.));
+        assert!((total - 5.).abs() < 1e-4);
+
+        let uncoupled = charges_after_injection(EncephalonBuilder::new());
+
+        assert_eq!(charge_at(&uncoupled, &[2, 2, 2]), 5.);
+        assert_eq!(charge_at(&uncoupled, &[2, 2, 3]), 0.);
+    }
+
+    #[test]
+    fn silenced_synapses_are_journaled_as_they_go_stale() {
+        let (light, brightness) = settable_sensor("light", 1.0);
+        let encephalon = EncephalonBuilder::new()
+            .seed(43)
+            .journal_capacity(1_000_000)
+            .sensory_encoder(|measurement: f32| (measurement > 0.5) as u32)
+            .prune_policy(Staleness {
+                max_idle_cycles: 50,
+            })
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![light],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let light: Vec<i32> = encephalon.sensor_loc(&"light".into()).unwrap().into();
+
+        // The light fires every cycle, until it's silenced at cycle 200
+        encephalon.run_n_cycles(200);
+        brightness.set(0.0);
+        encephalon.run_n_cycles(200);
+
+        let pruned: Vec<u64> = encephalon
+            .events_since(200)
+            .into_iter()
+            .filter(|event| event.kind == EventKind::Pruning && event.src_loc == light)
+            .map(|event| event.cycle)
+            .collect();
+
+        // No sooner than max_idle_cycles after it last fired
+        assert!(!pruned.is_empty());
+        assert!(pruned.iter().all(|cycle| *cycle >= 245 && *cycle < 400));
+        assert!(*pruned.iter().min().unwrap() <= 260);
+
+        // The csv holds a header row, then a row for every event
+        let path = std::env::temp_dir().join("eywa_silenced_journal.csv");
+        encephalon.write_journal_csv(&path).unwrap();
+
+        let csv = std::fs::read_to_string(&path).unwrap();
+        let rows: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(rows[0], "cycle,kind,src_loc,dst_loc,strength_at_event");
+        assert_eq!(rows.len(), encephalon.events_since(0).len() + 1);
+        assert!(rows[1..].iter().any(|row| row.contains(",pruning,")));
+    }
+
+    fn pain_polarity(sensor: &str, actuator: &str) -> Option<SynapticType> {
+        let wheel_forward = match (sensor, actuator.starts_with("left")) {
+            ("left_pain", is_left) => is_left,
+            ("right_pain", is_left) => !is_left,
+            ("back_pain", _) => true,
+            _ => return None,
+        };
+
+        match actuator.ends_with("forward") == wheel_forward {
+            true => Some(SynapticType::Excitatory),
+            false => Some(SynapticType::Inhibitory),
+        }
+    }
+
+    #[test]
+    fn templates_expand_the_hell_mazer_pain_wiring() {
+        let reflexes = ReflexTemplate::crossed_pair(
+            "forward_pain".into(),
+            ("left_forward".into(), "left_backward".into()),
+            20.,
+        )
+        .and(ReflexTemplate::crossed_pair(
+            "forward_pain".into(),
+            ("right_forward".into(), "right_backward".into()),
+            20.,
+        ))
+        .and(ReflexTemplate::all_pairs(
+            &["left_pain".into(), "right_pain".into(), "back_pain".into()],
+            &[
+                "left_forward".into(),
+                "left_backward".into(),
+                "right_forward".into(),
+                "right_backward".into(),
+            ],
+            pain_polarity,
+            20.,
+        ))
+        .expand();
+
+        let expanded: Vec<String> = reflexes
+            .iter()
+            .map(|reflex| match &reflex.target {
+                ReflexTarget::Actuator(actuator) => format!(
+                    "{} -> {} ({:?})",
+                    reflex.sensor_name, actuator, reflex.synapse_type
+                ),
+                ReflexTarget::Sensor(_) => unreachable!(),
+            })
+            .collect();
+
+        assert!(reflexes.iter().all(|reflex| reflex.strength == 20.));
+
+        assert_eq!(
+            expanded,
+            vec![
+                "forward_pain -> left_forward (Inhibitory)",
+                "forward_pain -> left_backward (Excitatory)",
+                "forward_pain -> right_forward (Inhibitory)",
+                "forward_pain -> right_backward (Excitatory)",
+                "left_pain -> left_forward (Excitatory)",
+                "left_pain -> left_backward (Inhibitory)",
+                "left_pain -> right_forward (Inhibitory)",
+                "left_pain -> right_backward (Excitatory)",
+                "right_pain -> left_forward (Inhibitory)",
+                "right_pain -> left_backward (Excitatory)",
+                "right_pain -> right_forward (Excitatory)",
+                "right_pain -> right_backward (Inhibitory)",
+                "back_pain -> left_forward (Excitatory)",
+                "back_pain -> left_backward (Inhibitory)",
+                "back_pain -> right_forward (Excitatory)",
+                "back_pain -> right_backward (Inhibitory)",
+            ]
+        );
+    }
+
+    #[test]
+    fn edges_the_polarity_turns_down_are_left_out() {
+        let none =
+            ReflexTemplate::all_pairs(&["light".into()], &["motor".into()], |_, _| None, 20.);
+        assert!(none.expand().is_empty());
+    }
+
+    /// A sensor that measures the values it's sent, and
+    /// repeats the last of them until it's sent another
+    struct Channel {
+        sent: Rc<Cell<Option<f32>>>,
+        last: f32,
+        fresh: bool,
+    }
+
+    impl Sensor for Channel {
+        fn measure(&mut self) -> f32 {
+            self.fresh = match self.sent.take() {
+                Some(value) => {
+                    self.last = value;
+                    true
+                }
+                None => false,
+            };
+
+            self.last
+        }
+
+        fn get_name(&self) -> String {
+            "light".to_string()
+        }
+
+        fn is_fresh(&self) -> bool {
+            self.fresh
+        }
+    }
+
+    #[test]
+    fn frozen_inputs_stall_the_encephalon_until_a_fresh_value() {
+        // A channel that's sent a single value, and then freezes
+        let sent = Rc::new(Cell::new(Some(0.5)));
+        let light = Channel {
+            sent: Rc::clone(&sent),
+            last: 0.0,
+            fresh: false,
+        };
+
+        let encephalon = EncephalonBuilder::new()
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![Box::new(light)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        encephalon.set_max_cycles_per_sensor_update(20);
+
+        // The fresh value is measured on cycle 1, then 20 stale cycles run
+        let stalled: Vec<bool> = (0..100).map(|_| encephalon.run_cycle().stalled).collect();
+
+        assert_eq!(encephalon.get_cycle_count(), 21);
+        assert!(stalled[..21].iter().all(|stalled| !stalled));
+        assert!(stalled[21..].iter().all(|stalled| *stalled));
+
+        // A fresh value lets it run another 20 stale cycles
+        sent.set(Some(0.7));
+        encephalon.run_n_cycles(100);
+
+        assert_eq!(encephalon.get_cycle_count(), 41);
+
+        encephalon.clear_max_cycles_per_sensor_update();
+        encephalon.run_n_cycles(100);
+
+        assert_eq!(encephalon.get_cycle_count(), 141);
+    }
+
+    #[test]
+    fn each_cycle_item_runs_one_cycle() {
+        let encephalon = EncephalonBuilder::new()
+            .max_plastic_synapses(26)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.5)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let mean_emas: Vec<f32> = encephalon
+            .cycles()
+            .take(100)
+            .map(|metrics| metrics.mean_rx_ema())
+            .collect();
+
+        assert_eq!(mean_emas.len(), 100);
+        assert_eq!(encephalon.get_cycle_count(), 100);
+
+        // Taking nothing runs nothing
+        assert_eq!(encephalon.cycles().take(0).count(), 0);
+        assert_eq!(encephalon.get_cycle_count(), 100);
+
+        // Each item of every(10) summarizes 10 cycles
+        let summaries: Vec<CycleMetrics> = encephalon.cycles().every(10).take(3).collect();
+
+        assert_eq!(summaries[2].cycle, 130);
+        assert_eq!(encephalon.get_cycle_count(), 130);
+    }
+
+    fn pain_reflexed() -> Rc<Encephalon> {
+        EncephalonBuilder::new()
+            .seed(6)
+            .reflexes(vec![reflex("pain", "motor", 20.)])
+            .build(
+                Box::new(BoxEcp::new(125, 2, 1, 27)),
+                vec![sensor("pain", 1.0), sensor("light", 1.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn a_fresh_actuator_is_driven_by_its_reflex() {
+        let contribution = pain_reflexed()
+            .reflex_contribution(&"pain".into(), &"motor".into(), 10)
+            .unwrap();
+
+        assert!(contribution.reflex_charge > 0.);
+        assert!(contribution.ratio() > 0.9);
+    }
+
+    #[test]
+    fn other_synapses_onto_the_actuator_take_their_share() {
+        let wired = pain_reflexed();
+
+        // Hand wire the light onto the motor, as strongly as the reflex
+        let light = Rc::clone(&wired.sensory_interfaces.borrow()["light"].sensory_neurons[0]);
+        let motor: Rc<dyn NeuronicRx> =
+            wired.actuator_interfaces.borrow()["motor"].actuator_neurons[0].clone();
+        light.add_static_synapse(20., SynapticType::Excitatory, motor);
+
+        let contribution = wired
+            .reflex_contribution(&"pain".into(), &"motor".into(), 10)
+            .unwrap();
+
+        assert!(contribution.static_charge > 0.);
+        assert!(contribution.ratio() < 0.75);
+
+        assert!(matches!(
+            wired.reflex_contribution(&"light".into(), &"motor".into(), 10),
+            Err(EywaError::NoSuchReflex { sensor, actuator })
+                if sensor == "light" && actuator == "motor"
+        ));
+    }
+
+    /// The EMA of the pleasure sensor's neuron after 200 cycles
+    /// of constant pain and pleasure
+    fn pleasure_ema(reflexes: Vec<Reflex>) -> f32 {
+        let encephalon = EncephalonBuilder::new()
+            .max_plastic_synapses(26)
+            .reflexes(reflexes)
+            .build(
+                Box::new(BoxEcp::new(125, 2, 1, 27)),
+                vec![sensor("pain", 1.0), sensor("pleasure", 0.5)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        encephalon.run_n_cycles(200);
+
+        let pleasure = encephalon.sensor_loc(&"pleasure".into()).unwrap();
+        let (_, ema) = encephalon
+            .ema_field()
+            .into_iter()
+            .find(|(loc, _)| *loc == pleasure)
+            .unwrap();
+
+        ema
+    }
+
+    #[test]
+    fn pain_gates_pleasure() {
+        let gate = Reflex::gate(
+            "pain".into(),
+            "pleasure".into(),
+            SynapticType::Inhibitory,
+            4.,
+        );
+
+        assert!(pleasure_ema(vec![gate]) < pleasure_ema(vec![]));
+    }
+
+    #[cfg(feature = "audit")]
+    #[test]
+    fn charge_is_conserved_across_gap_junctions() {
+        let encephalon = EncephalonBuilder::new()
+            .max_plastic_synapses(26)
+            .gap_junction(0.5, 0.2)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.5)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        // Every cycle is checked as it runs, so this would
+        // panic if charge appeared or vanished
+        encephalon.run_n_cycles(200);
+
+        assert!(encephalon.charge_ledger().is_balanced());
+    }
+
+    #[test]
+    fn a_group_drives_its_actuator_until_lesioned() {
+        let encephalon = EncephalonBuilder::new()
+            .max_plastic_synapses(26)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let motor_locs = encephalon.actuator_cluster_locs(&"motor".into()).unwrap();
+        let cluster_size = motor_locs.len() as u32;
+
+        encephalon
+            .define_group("motor", motor_locs.into_iter().map(Vec::from).collect())
+            .unwrap();
+
+        encephalon.stimulate_group("motor", 100.).unwrap();
+        encephalon.run_cycle();
+
+        assert_eq!(
+            encephalon.group_fire_count_last_cycle("motor").unwrap(),
+            cluster_size
+        );
+
+        encephalon.lesion_group("motor").unwrap();
+        encephalon.stimulate_group("motor", 100.).unwrap();
+        encephalon.run_cycle();
+
+        assert_eq!(encephalon.group_fire_count_last_cycle("motor").unwrap(), 0);
+    }
+
+    /// Measuring the light takes 4ms of (mock) time
+    struct SlowLight(Rc<MockClock>);
+
+    impl Sensor for SlowLight {
+        fn measure(&mut self) -> f32 {
+            self.0.advance(Duration::from_millis(4));
+            0.5
+        }
+
+        fn get_name(&self) -> String {
+            "light".to_string()
+        }
+    }
+
+    fn slow_brain(clock: &Rc<MockClock>) -> Rc<Encephalon> {
+        EncephalonBuilder::new()
+            .clock(clock.clone())
+            .max_plastic_synapses(26)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![Box::new(SlowLight(clock.clone()))],
+                vec![actuator("motor")],
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn realtime_runs_sleep_out_the_rest_of_each_slot() {
+        let clock = Rc::new(MockClock::new());
+        let encephalon = slow_brain(&clock);
+
+        // Each 10ms slot runs for 4ms and sleeps for 6ms
+        let report = encephalon.run_realtime(10, 100.).unwrap();
+
+        assert_eq!(report.overruns, 0);
+        assert_eq!(report.slept, Duration::from_millis(60));
+        assert_eq!(clock.now(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn overrun_slots_are_not_made_up_for() {
+        let clock = Rc::new(MockClock::new());
+        let encephalon = slow_brain(&clock);
+
+        // At 500 cycles per second, every 2ms slot is overrun
+        let report = encephalon.run_realtime(10, 500.).unwrap();
+
+        assert_eq!(report.overruns, 10);
+        assert_eq!(report.slept, Duration::from_millis(0));
+        assert_eq!(report.elapsed, Duration::from_millis(40));
+    }
+
+    #[test]
+    fn formed_reflexes_are_read_back_from_their_synapses() {
+        let encephalon = EncephalonBuilder::new()
+            .max_plastic_synapses(26)
+            .reflexes(vec![reflex("light", "motor", 20.)])
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.5)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        assert_eq!(
+            encephalon.reflexes_formed(),
+            vec![("light".to_string(), "motor".to_string(), 20.)]
+        );
+    }
+
+    #[test]
+    fn a_misnamed_reflex_fails_the_build() {
+        let typo = EncephalonBuilder::new()
+            .reflexes(vec![reflex("light", "motr", 10.)])
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.)],
+                vec![actuator("motor")],
+            );
+
+        assert!(matches!(
+            typo.err(),
+            Some(EywaError::ReflexNotFormed { sensor, actuator })
+                if sensor == "light" && actuator == "motr"
+        ));
+    }
+
+    #[test]
+    fn locations_of_the_wrong_shape_are_dimension_mismatches() {
+        let encephalon = EncephalonBuilder::new()
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+        let is_mismatch = |result: Result<_, EywaError>| {
+            matches!(
+                result,
+                Err(EywaError::DimensionMismatch { expected: 3, .. })
+            )
+        };
+
+        assert!(encephalon.check_dims(&[1, 1, 1]).is_ok());
+        assert!(is_mismatch(encephalon.check_dims(&[1, 1])));
+        assert!(is_mismatch(encephalon.inject_charge(&[1, 1], 1.)));
+        assert!(is_mismatch(
+            encephalon.inject_region(&[1, 1], 1, 1.).map(|_| ())
+        ));
+        assert!(is_mismatch(
+            encephalon.define_group("plane", vec![vec![1, 1]])
+        ));
+        assert!(is_mismatch(
+            encephalon.add_probe("probe", vec![1, 1, 1, 1]).map(|_| ())
+        ));
+        assert!(is_mismatch(encephalon.neuron_kind_at(&[2, 2]).map(|_| ())));
+        assert!(is_mismatch(encephalon.params_at(&[2, 2, 2, 0]).map(|_| ())));
+    }
+
+    #[test]
+    fn profile_times_each_phase_exactly_under_a_mock_clock() {
+        let clock = Rc::new(MockClock::new());
+        let motor_clock = Rc::clone(&clock);
+
+        // The light takes 4ms to measure and the motor 5ms to set
+        let motor = FnActuator::new("motor".to_string(), move |_| {
+            motor_clock.advance(Duration::from_millis(5))
+        });
+
+        let encephalon = EncephalonBuilder::new()
+            .clock(clock.clone())
+            .profile_cycles(true)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![Box::new(SlowLight(clock.clone()))],
+                vec![Box::new(motor)],
+            )
+            .unwrap();
+
+        encephalon.run_n_cycles(10);
+        let report = encephalon.profile_report();
+
+        assert_eq!(report.sensory_interfaces.cycles, 10);
+        assert_eq!(report.sensory_interfaces.total, Duration::from_millis(40));
+        assert_eq!(report.sensory_interfaces.max, Duration::from_millis(4));
+        assert_eq!(report.actuator_interfaces.mean(), Duration::from_millis(5));
+        assert_eq!(report.rx_neurons.total, Duration::from_millis(0));
+        assert_eq!(report.total(), Duration::from_millis(90));
+
+        encephalon.reset_profile();
+        assert_eq!(encephalon.profile_report(), PhaseTimings::default());
+    }
+
+    #[test]
+    fn an_attached_sensor_fires_its_reserved_position() {
+        // Two sensory positions, one of them held for later
+        let encephalon = EncephalonBuilder::new()
+            .reserved_sensory_positions(1)
+            .build(
+                Box::new(BoxEcp::new(125, 2, 1, 27)),
+                vec![sensor("light", 0.5)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let fired = |encephalon: &Encephalon| {
+            (0..100)
+                .map(|_| encephalon.run_cycle().sensory_fired)
+                .sum::<u32>()
+        };
+
+        encephalon.run_n_cycles(400);
+        let light_only = fired(&encephalon);
+
+        encephalon.attach_sensor(sensor("sound", 0.5)).unwrap();
+        assert!(encephalon.sensor_loc(&"sound".into()).is_some());
+        assert!(fired(&encephalon) > light_only);
+
+        assert!(matches!(
+            encephalon.attach_sensor(sensor("sound", 0.5)),
+            Err(EywaError::DuplicateName(_))
+        ));
+        assert!(matches!(
+            encephalon.attach_sensor(sensor("smell", 0.5)),
+            Err(EywaError::NoReservedPosition(_))
+        ));
+    }
+
+    #[test]
+    fn a_reserved_actuator_position_holds_an_actuator_neuron() {
+        // Two actuator positions, one of them held for later
+        let encephalon = EncephalonBuilder::new()
+            .reserved_actuator_positions(1)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 2, 27)),
+                vec![sensor("light", 0.5)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let motor = encephalon.actuator_loc(&"motor".into()).unwrap();
+        let ecp_geometry = BoxEcp::new(125, 1, 2, 27);
+        let reserved = (0..2)
+            .filter_map(|x| ecp_geometry.actuator_plane_at(x, 0))
+            .find(|loc| *loc != motor)
+            .unwrap();
+
+        let assert_actuator = |loc: &Loc| {
+            assert_eq!(
+                encephalon.neuron_kind_at(loc),
+                Ok(Some(NeuronKind::Actuator))
+            );
+            assert_eq!(encephalon.params_at(loc), Ok(None));
+        };
+
+        assert_actuator(&reserved);
+
+        encephalon.attach_actuator(actuator("horn")).unwrap();
+        assert_eq!(encephalon.actuator_loc(&"horn".into()), Some(reserved));
+        assert_actuator(&reserved);
+        assert_actuator(&motor);
+    }
+
+    /// The value of an antagonist pair after 200 cycles
+    /// of a reflex driving one of its members
+    fn driven_pair_value(driven: &str) -> f32 {
+        let encephalon = EncephalonBuilder::new()
+            .seed(3)
+            .reflexes(vec![reflex("light", driven, 20.)])
+            .antagonist_pair(AntagonistPair::new(
+                "left".to_string(),
+                "left_forward".into(),
+                "left_backward".into(),
+            ))
+            .build(
+                Box::new(BoxEcp::new(125, 1, 2, 27)),
+                vec![sensor("light", 1.0)],
+                vec![actuator("left_forward"), actuator("left_backward")],
+            )
+            .unwrap();
+
+        encephalon.run_n_cycles(200);
+        encephalon.actuator_pair_value("left").unwrap()
+    }
+
+    #[test]
+    fn driving_a_pair_member_shows_in_the_sign() {
+        assert!(driven_pair_value("left_forward") > 0.);
+        assert!(driven_pair_value("left_backward") < 0.);
+    }
+
+    #[test]
+    fn competitive_replacement_turns_over_a_full_budget() {
+        let encephalon = EncephalonBuilder::new()
+            .global_synapse_budget(100)
+            .budget_policy(BudgetPolicy::CompetitiveReplacement { sample_size: 8 })
+            .journal_capacity(100_000)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 1.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        for _ in 0..1000 {
+            encephalon.run_cycle();
+            assert!(encephalon.plastic_synapse_count() <= 100);
+        }
+
+        let plastic = encephalon
+            .connectome()
+            .iter()
+            .filter(|record| record.plastic)
+            .count();
+        assert_eq!(encephalon.plastic_synapse_count(), plastic);
+
+        let late_formations = encephalon
+            .events_since(900)
+            .iter()
+            .filter(|event| event.kind == EventKind::Formation)
+            .count();
+
+        assert!(late_formations > 0);
+    }
+
+    #[test]
+    fn activity_image_is_brightest_where_charge_was_injected() {
+        let encephalon = EncephalonBuilder::new()
+            .build(
+                Box::new(BoxEcp::new(1100, 1, 1, 27)),
+                vec![sensor("light", 0.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        encephalon.inject_charge(&[9, 9, 0], 50.).unwrap();
+
+        let path = std::env::temp_dir().join("eywa_activity_slice.pgm");
+        encephalon
+            .write_activity_image(&path, 2, 0, FieldKind::Charge)
+            .unwrap();
+
+        let image = std::fs::read_to_string(&path).unwrap();
+        let mut tokens = image.split_whitespace();
+
+        assert_eq!(tokens.next(), Some("P2"));
+
+        let header: Vec<usize> = tokens
+            .by_ref()
+            .take(3)
+            .map(|t| t.parse().unwrap())
+            .collect();
+        assert_eq!(header, vec![10, 10, 255]);
+
+        let pixels: Vec<u32> = tokens.map(|t| t.parse().unwrap()).collect();
+        let brightest = (0..pixels.len()).max_by_key(|&i| pixels[i]).unwrap();
+
+        // Row 9, column 9 is the stimulated corner
+        assert_eq!(brightest, 99);
+    }
+
+    #[test]
+    fn interfaces_are_viewed_in_name_order() {
+        let levels = [("a", 0.0), ("b", 0.25), ("c", 0.5), ("d", 1.0)];
+
+        let encephalon = EncephalonBuilder::new()
+            .sensory_encoder(|measurement: f32| (10. - 9. * measurement) as u32)
+            .build(
+                Box::new(BoxEcp::new(125, 4, 3, 27)),
+                levels
+                    .iter()
+                    .map(|&(name, level)| sensor(name, level))
+                    .collect(),
+                vec![actuator("x"), actuator("y"), actuator("z")],
+            )
+            .unwrap();
+
+        encephalon.run_n_cycles(5);
+
+        assert_eq!(
+            (encephalon.sensor_count(), encephalon.actuator_count()),
+            (4, 3)
+        );
+
+        let mut sensors = Vec::new();
+        encephalon.for_each_sensor(|name, view| {
+            sensors.push((name.to_string(), view.last_measurement, view.period))
+        });
+
+        assert_eq!(
+            sensors,
+            vec![
+                ("a".to_string(), Some(0.0), Some(10)),
+                ("b".to_string(), Some(0.25), Some(7)),
+                ("c".to_string(), Some(0.5), Some(5)),
+                ("d".to_string(), Some(1.0), Some(1)),
+            ]
+        );
+
+        let mut actuators = Vec::new();
+        encephalon.for_each_actuator(|name, view| {
+            assert!(view.last_sent.is_some() && !view.disabled);
+            actuators.push(name.to_string());
+        });
+
+        assert_eq!(actuators, vec!["x", "y", "z"]);
+    }
+
+    #[test]
+    fn a_healed_group_fires_from_the_cycle_its_in() {
+        let encephalon = EncephalonBuilder::new()
+            .max_plastic_synapses(26)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let motor_locs = encephalon.actuator_cluster_locs(&"motor".into()).unwrap();
+        let cluster_size = motor_locs.len() as u32;
+
+        encephalon
+            .define_group("motor", motor_locs.into_iter().map(Vec::from).collect())
+            .unwrap();
+
+        let before = encephalon.snapshot();
+
+        for _ in 0..17 {
+            encephalon.stimulate_group("motor", 100.).unwrap();
+            encephalon.run_cycle();
+        }
+
+        encephalon.lesion_group("motor").unwrap();
+        encephalon.stimulate_group("motor", 100.).unwrap();
+        encephalon.run_cycle();
+
+        assert_eq!(encephalon.group_fire_count_last_cycle("motor").unwrap(), 0);
+
+        encephalon.heal_group("motor").unwrap();
+        encephalon.stimulate_group("motor", 100.).unwrap();
+        encephalon.run_cycle();
+
+        assert_eq!(
+            encephalon.group_fire_count_last_cycle("motor").unwrap(),
+            cluster_size
+        );
+
+        // Rewinding to before the stimulation forgets it
+        encephalon.restore(&before).unwrap();
+
+        assert_eq!(encephalon.group_fire_count_last_cycle("motor").unwrap(), 0);
+
+        encephalon.stimulate_group("motor", 100.).unwrap();
+        encephalon.run_cycle();
+
+        assert_eq!(
+            encephalon.group_fire_count_last_cycle("motor").unwrap(),
+            cluster_size
+        );
+    }
+
+    #[test]
+    fn neurons_describe_their_kind_and_synapses() {
+        let encephalon = EncephalonBuilder::new()
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.5)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let light = encephalon.sensor_loc(&"light".into()).unwrap();
+        let description = encephalon.describe_neuron(&light).unwrap();
+
+        assert!(description.starts_with("SensoryNeuron"));
+        assert!(description.contains("plastic synapses"));
+        assert!(encephalon.describe_neuron(&[100, 100, 100]).is_err());
+    }
+
+    #[test]
+    fn reflexes_near_the_threshold_are_warned_of() {
+        let build = |strength: f32| {
+            EncephalonBuilder::new()
+                .fire_threshold(10.)
+                .reflexes(vec![reflex("pain", "motor", strength)])
+                .build(
+                    Box::new(BoxEcp::new(125, 1, 1, 27)),
+                    vec![sensor("pain", 0.0)],
+                    vec![actuator("motor")],
+                )
+                .unwrap()
+        };
+
+        assert!(matches!(
+            build(10.).plan_warnings(),
+            [PlanWarning::ReflexAtThreshold { .. }]
+        ));
+        assert_eq!(build(10.0001).plan_warnings().len(), 1);
+        assert!(build(12.).plan_warnings().is_empty());
+    }
+
+    #[test]
+    fn injected_frames_drive_their_sensors() {
+        let encephalon = EncephalonBuilder::new()
+            .injected_sensors(vec!["forward", "left"])
+            .reflexes(vec![reflex("forward", "motor", 20.)])
+            .build(
+                Box::new(BoxEcp::new(125, 2, 1, 27)),
+                vec![],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let mut frame = HashMap::new();
+        frame.insert("forward".to_string(), 1.0);
+        encephalon.set_sensor_values(&frame).unwrap();
+
+        encephalon.run_n_cycles(100);
+
+        assert!(encephalon.actuator_values()["motor"] > 0.);
+
+        frame.insert("backward".to_string(), 1.0);
+        assert!(matches!(
+            encephalon.set_sensor_values(&frame),
+            Err(EywaError::UnknownName(name)) if name == "backward"
+        ));
+    }
+
+    #[test]
+    fn eval_mode_acts_as_a_frozen_brain_mid_run() {
+        let build = || {
+            EncephalonBuilder::new()
+                .seed(11)
+                .injected_sensors(vec!["light", "sound"])
+                .reflexes(vec![reflex("light", "motor", 20.)])
+                .build(
+                    Box::new(BoxEcp::new(125, 2, 1, 27)),
+                    vec![],
+                    vec![actuator("motor")],
+                )
+                .unwrap()
+        };
+
+        let frozen = build();
+        let eval = build();
+
+        let step = |cycle: u32| {
+            let mut frame = HashMap::new();
+            frame.insert("light".to_string(), (cycle % 100) as f32 / 100.);
+            frame.insert("sound".to_string(), 0.5);
+
+            for encephalon in &[&frozen, &eval] {
+                encephalon.set_sensor_values(&frame).unwrap();
+                encephalon.run_cycle();
+            }
+
+            assert_eq!(frozen.actuator_values(), eval.actuator_values());
+        };
+
+        (0..300).for_each(step);
+
+        frozen.freeze_plasticity(true);
+        eval.set_eval_mode(true);
+        let wiring = eval.connectome();
+
+        (300..600).for_each(step);
+        assert_eq!(eval.connectome(), wiring);
+
+        frozen.freeze_plasticity(false);
+        eval.set_eval_mode(false);
+
+        (600..900).for_each(step);
+        assert_eq!(frozen.connectome(), eval.connectome());
+    }
+
+    #[test]
+    fn only_the_probed_sensors_reflexes_carry_its_influence() {
+        let encephalon = EncephalonBuilder::new()
+            .reflexes(vec![
+                reflex("left", "left motor", 20.),
+                reflex("right", "right motor", 20.),
+            ])
+            .build(
+                Box::new(BoxEcp::new(125, 2, 2, 27)),
+                vec![sensor("left", 0.5), sensor("right", 0.5)],
+                vec![actuator("left motor"), actuator("right motor")],
+            )
+            .unwrap();
+
+        let influence = encephalon.influence_probe("left", 100, 2).unwrap();
+
+        assert!(influence["left motor"] > 0.);
+        assert!(influence["right motor"].abs() < 1e-6);
+        assert!(!encephalon.plasticity_frozen());
+    }
+
+    #[test]
+    fn unpaceable_rates_are_rejected_without_running() {
+        let clock = Rc::new(MockClock::new());
+        let encephalon = slow_brain(&clock);
+
+        assert!(matches!(
+            encephalon.run_realtime(10, f32::NAN),
+            Err(EywaError::InvalidCycleRate(_))
+        ));
+        assert!(matches!(
+            encephalon.run_realtime(10, 1e-30),
+            Err(EywaError::InvalidCycleRate(_))
+        ));
+        assert_eq!(encephalon.get_cycle_count(), 0);
+        assert_eq!(clock.now(), Duration::from_millis(0));
+    }
+
+    #[test]
+    fn small_world_shortcuts_skip_actuators() {
+        let encephalon = EncephalonBuilder::new()
+            .seed(5)
+            .small_world_p(1.)
+            .build(
+                Box::new(BoxEcp::new(343, 1, 1, 27)),
+                vec![Box::new(Light)],
+                vec![motor()],
+            )
+            .unwrap();
+
+        encephalon.run_n_cycles(300);
+
+        let actuator: Vec<i32> = encephalon.actuator_loc(&"motor".into()).unwrap().into();
+        let nearby = |a: &[i32], b: &[i32]| a.iter().zip(b).all(|(a, b)| (a - b).abs() <= 1);
+
+        let plastic: Vec<SynapseRecord> = encephalon
+            .connectome()
+            .into_iter()
+            .filter(|record| record.plastic)
+            .collect();
+
+        // Most synapses are shortcuts...
+        let shortcuts = plastic
+            .iter()
+            .filter(|record| !nearby(&record.source, &record.target))
+            .count();
+
+        assert!(shortcuts > plastic.len() / 2);
+
+        // ...but the only synapses onto the actuator are
+        // the local ones formed when a shortcut falls through
+        for record in plastic.iter().filter(|record| record.target == actuator) {
+            assert!(
+                nearby(&record.source, &actuator),
+                "{:?} reached the actuator",
+                record.source
+            );
+        }
+    }
+
+    #[test]
+    fn restore_undoes_a_disturbance_to_neuron_dynamics() {
+        let build = || {
+            EncephalonBuilder::new()
+                .seed(3)
+                .max_delay(3)
+                .burst_length(2)
+                .adaptation(2., 0.9)
+                .build(
+                    Box::new(BoxEcp::new(125, 1, 1, 27)),
+                    vec![Box::new(Light)],
+                    vec![motor()],
+                )
+                .unwrap()
+        };
+
+        let original = build();
+        let disturbed = build();
+
+        original.run_n_cycles(100);
+        disturbed.run_n_cycles(100);
+
+        let bytes = original
+            .snapshot()
+            .to_bytes(SnapshotFormat::Bincode)
+            .unwrap();
+
+        disturbed.inject_region(&[2, 2, 2], 2, 50.).unwrap();
+        disturbed
+            .restore(&EncephalonSnapshot::from_bytes(&bytes, SnapshotFormat::Bincode).unwrap())
+            .unwrap();
+
+        assert_eq!(raster(&original, 40), raster(&disturbed, 40));
+    }
+
+    #[test]
+    fn reseeded_brain_resumes_the_snapshot_stream() {
+        let original = build(7);
+        original.run_n_cycles(250);
+
+        let resumed = build(99);
+        resumed.restore(&original.snapshot()).unwrap();
+
+        let loc = original.sensor_loc(&"light".into()).unwrap();
+        let draw = |encephalon: &Encephalon| {
+            (0..250)
+                .map(|_| {
+                    encephalon
+                        .local_random_neuron(loc)
+                        .map(|neuron| neuron.loc())
+                })
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(draw(&original), draw(&resumed));
+    }
+
+    #[test]
+    fn influence_probe_leaves_the_world_alone() {
+        struct Eye {
+            measures: Rc<Cell<u32>>,
+            fresh: Rc<Cell<bool>>,
+        }
+
+        impl Sensor for Eye {
+            fn measure(&mut self) -> f32 {
+                self.measures.set(self.measures.get() + 1);
+                0.5
+            }
+
+            fn get_name(&self) -> String {
+                "light".to_string()
+            }
+
+            fn is_fresh(&self) -> bool {
+                self.fresh.get()
+            }
+        }
+
+        let build = |measures: &Rc<Cell<u32>>, fresh: &Rc<Cell<bool>>, sent: &Rc<Cell<u32>>| {
+            let sent = Rc::clone(sent);
+
+            EncephalonBuilder::new()
+                .seed(3)
+                .journal_capacity(10_000)
+                .reflexes(vec![Reflex::new(
+                    "light".into(),
+                    "motor".into(),
+                    SynapticType::Excitatory,
+                    20.,
+                )])
+                .build(
+                    Box::new(BoxEcp::new(125, 1, 1, 27)),
+                    vec![Box::new(Eye {
+                        measures: Rc::clone(measures),
+                        fresh: Rc::clone(fresh),
+                    })],
+                    vec![Box::new(FnActuator::new("motor".to_string(), move |_| {
+                        sent.set(sent.get() + 1)
+                    }))],
+                )
+                .unwrap()
+        };
+
+        let (measures, fresh, sent) = (
+            Rc::new(Cell::new(0)),
+            Rc::new(Cell::new(true)),
+            Rc::new(Cell::new(0)),
+        );
+        let probed = build(&measures, &fresh, &sent);
+        let unprobed = build(
+            &Rc::new(Cell::new(0)),
+            &Rc::new(Cell::new(true)),
+            &Rc::new(Cell::new(0)),
+        );
+
+        let motor = probed.actuator_loc(&"motor".into()).unwrap();
+        let probe = probed.add_probe("motor", motor.into()).unwrap();
+
+        raster(&probed, 100);
+        raster(&unprobed, 100);
+
+        // A sensor gone stale would stall the probe's cycles
+        fresh.set(false);
+        probed.set_max_cycles_per_sensor_update(1);
+
+        let (cycle, trace, events) = (
+            probed.get_cycle_count(),
+            probed.probe_trace(probe).unwrap(),
+            probed.events_since(0),
+        );
+        let (measured, sent_before) = (measures.get(), sent.get());
+
+        let influence = probed.influence_probe("light", 100, 2).unwrap();
+
+        assert!(influence["motor"] > 0.);
+        assert_eq!(probed.get_cycle_count(), cycle);
+        assert_eq!(probed.probe_trace(probe).unwrap(), trace);
+        assert_eq!(probed.events_since(0), events);
+        assert_eq!(measures.get(), measured);
+        assert_eq!(sent.get(), sent_before);
+        assert!(!probed.plasticity_frozen());
+
+        fresh.set(true);
+        probed.clear_max_cycles_per_sensor_update();
+
+        assert_eq!(probed.connectome(), unprobed.connectome());
+        assert_eq!(raster(&probed, 100), raster(&unprobed, 100));
     }
 }
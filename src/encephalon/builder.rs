@@ -0,0 +1,3786 @@
+use std::boxed::Box;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use super::{
+    ActuatorLayout, BrainPlan, ClusterPlan, Encephalon, NeuronPlan, PlanWarning, Reflex,
+    ReflexTarget, ReflexTemplate, ReservedSlot, SensorLayout, REFLEX_THRESHOLD_TOLERANCE,
+};
+use crate::actuator::{Actuator, ActuatorName};
+use crate::clock::{Clock, SystemClock};
+use crate::ecp_geometry::{ActuatorSpread, EcpGeometry, Region, SizeReport};
+use crate::error::EywaError;
+use crate::journal::Journal;
+use crate::loc::Loc;
+use crate::metrics::PhaseTimings;
+use crate::neuron::synapse::synaptic_strength::{SigmoidStrength, StrengthInit, SynapticStrength};
+use crate::neuron::synapse::{
+    BudgetPolicy, PrunePolicy, StrengthThreshold, SynapseSpec, TargetingRules,
+};
+use crate::neuron::{
+    is_valid_ema_alpha, ActuatorNeuron, ActuatorParams, Adaptation, EmaParams, EnergyModel,
+    GapJunctionParams, InhibitionMode, InterneuronParams, IzhikevichNeuron, NeuronKind, NeuronicRx,
+    PlasticModel, PlasticNeuron, PlasticParams, PlasticParamsPatch, PolarityPolicy, RxNeuron,
+    SensoryNeuron, SensoryParams, SynapticScaling, ThresholdMode, DEFAULT_EMA_FLOOR,
+};
+use crate::neuron_interfaces::{
+    sensory_encoders, ActuatorGroup, ActuatorInterface, ActuatorOutput, AntagonistPair,
+    SensorSampling, SensoryEncoder, SensoryInterface,
+};
+use crate::reward::TargetComparator;
+use crate::sensor::adapters::{InjectedSensor, Normalization, NormalizedSensor};
+use crate::sensor::{split_multi_sensor, MultiSensor, Sensor, SensorName};
+
+const DEFAULT_MAX_PLASTIC_SYNAPSES: usize = 64;
+
+/// Configures and constructs an encephalon.  Every parameter
+/// starts at a sensible default, so only the parameters that
+/// matter for a particular brain need to be set
+pub struct EncephalonBuilder {
+    //Parameters for neurons
+    fire_threshold: f32,
+    threshold_mode: ThresholdMode,
+    inhibition_mode: InhibitionMode,
+    ema_alpha: f32,
+    ema_floor: f32,
+    synaptic_strength_generator: Rc<dyn Fn() -> Box<RefCell<dyn SynapticStrength>>>,
+    strength_init: StrengthInit,
+    prune_policy: Box<dyn PrunePolicy>,
+    global_synapse_budget: Option<usize>,
+    budget_policy: BudgetPolicy,
+    targeting_rules: TargetingRules,
+    synapse_type_threshold: f32,
+    max_plastic_synapses: Option<usize>,
+    clamp_synapse_budget: bool,
+    max_delay: u32,
+    fire_window: u32,
+    pairing_window: u32,
+    burst_length: u32,
+    adaptation: Adaptation,
+    plastic_model: Option<PlasticModel>,
+    normalize_outgoing: bool,
+    target_total_strength: f32,
+    synaptic_scaling: Option<SynapticScaling>,
+    charge_floor: f32,
+    polarity_policy: Option<PolarityPolicy>,
+    gap_junction: Option<GapJunctionParams>,
+    energy_model: Option<EnergyModel>,
+
+    //Fraction of plastic positions filled with inhibitory interneurons
+    interneuron_fraction: f32,
+    interneuron_params: InterneuronParams,
+
+    //Per-kind overrides of the parameters above
+    plastic_params: Option<PlasticParams>,
+    region_overrides: Vec<(Region, PlasticParamsPatch)>,
+    actuator_params: Option<ActuatorParams>,
+    actuator_overrides: HashMap<ActuatorName, ActuatorParams>,
+    sensory_params: Option<SensoryParams>,
+
+    //Parameters for interfaces
+    sensory_encoder: Rc<dyn SensoryEncoder>,
+    sensor_sampling: HashMap<SensorName, SensorSampling>,
+
+    //Sensors that measure several channels at once
+    multi_sensors: Vec<Box<dyn MultiSensor>>,
+
+    //Names of sensors whose values are pushed in by set_sensor_values
+    injected_sensors: Vec<String>,
+
+    //List of reflex synapses, and templates that expand into more of them
+    reflexes: Vec<Reflex>,
+    reflex_templates: Vec<ReflexTemplate>,
+
+    //Sets of actuators whose values compete
+    actuator_groups: Vec<ActuatorGroup>,
+    antagonist_pairs: Vec<AntagonistPair>,
+
+    //Pacing of the control values sent to each actuator
+    actuator_output: HashMap<ActuatorName, ActuatorOutput>,
+
+    //Sources of the global reward signal, and whether it modulates plasticity
+    comparators: Vec<TargetComparator>,
+    reward_modulation: bool,
+
+    //Consecutive failures after which an actuator is no longer called
+    actuator_failure_threshold: u32,
+
+    //Whether panics in sensors and actuators are caught
+    isolate_user_code: bool,
+
+    //Whether sensors are clamped into [0, 1], and whether
+    //measurements outside of it trip a debug assertion
+    auto_normalize: bool,
+    assert_sensor_range: bool,
+
+    //Synapses wired in once the encephalon is built
+    initial_synapses: Vec<SynapseSpec>,
+
+    //Placement of sensors and actuators
+    sensor_layout: SensorLayout,
+    actuator_layout: ActuatorLayout,
+    actuator_spread: Option<ActuatorSpread>,
+
+    //Number of neurons driven by (or driving) each interface
+    sensor_cluster_size: u32,
+    actuator_cluster_size: u32,
+
+    //Positions held for sensors and actuators attached after building
+    reserved_sensory_positions: u32,
+    reserved_actuator_positions: u32,
+
+    //Number of samples each probe holds onto
+    probe_capacity: usize,
+
+    //Sweep every plastic synapse into each cycle's metrics
+    detailed_metrics: bool,
+
+    //Run actuator interfaces before neurons, as before
+    legacy_phase_order: bool,
+
+    //Number of synapse lifecycle events journaled, or 0 for no journal
+    journal_capacity: usize,
+
+    //Probability a new synapse targets any rx neuron
+    small_world_p: f32,
+
+    //Seed of the encephalon's random number generator
+    seed: Option<u64>,
+
+    //Source of wall time for timing and pacing runs
+    clock: Rc<dyn Clock>,
+
+    //Whether each phase of every cycle is timed
+    profile_cycles: bool,
+
+    //Reports of progress through build, as a fraction of each phase
+    progress: Option<ProgressCallback>,
+    progress_granularity: f32,
+}
+
+/// Called with a phase of build, the number of its items
+/// built so far, and the number it will build in all
+type ProgressCallback = Box<dyn FnMut(BuildPhase, u32, u32)>;
+
+/// The phases of building an encephalon, in the order build
+/// starts them.  Plastic and actuator neurons are placed in
+/// the same pass over the geometry, so their progress interleaves
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BuildPhase {
+    PlasticNeurons,
+    ActuatorNeurons,
+    SensoryNeurons,
+    Reflexes,
+    InitialSynapses,
+}
+
+/// Passes progress through build along to the builder's
+/// callback, once per step of granularity through a phase
+struct BuildProgress {
+    callback: Option<ProgressCallback>,
+    granularity: f32,
+}
+
+impl BuildProgress {
+    /// Reports that done of total items of phase are built, if
+    /// done starts or ends the phase or lands on a step
+    fn report(&mut self, phase: BuildPhase, done: u32, total: u32) {
+        if let Some(callback) = &mut self.callback {
+            let step = ((total as f32 * self.granularity).ceil() as u32).max(1);
+
+            if done == 0 || done == total || done.is_multiple_of(step) {
+                callback(phase, done, total);
+            }
+        }
+    }
+}
+
+impl Default for EncephalonBuilder {
+    fn default() -> Self {
+        EncephalonBuilder::new()
+    }
+}
+
+impl EncephalonBuilder {
+    pub fn new() -> EncephalonBuilder {
+        EncephalonBuilder {
+            fire_threshold: 10.,
+            threshold_mode: ThresholdMode::Exclusive,
+            inhibition_mode: InhibitionMode::Subtractive,
+            ema_alpha: 2. / 100.,
+            ema_floor: DEFAULT_EMA_FLOOR,
+            synaptic_strength_generator: Rc::new(|| {
+                Box::new(RefCell::new(SigmoidStrength::new(15., 1., 0.1)))
+            }),
+            strength_init: StrengthInit::Default,
+            prune_policy: Box::new(StrengthThreshold),
+            global_synapse_budget: None,
+            budget_policy: BudgetPolicy::default(),
+            targeting_rules: TargetingRules::default(),
+            synapse_type_threshold: 0.1,
+            max_plastic_synapses: None,
+            clamp_synapse_budget: false,
+            max_delay: 1,
+            fire_window: 2,
+            pairing_window: 1,
+            burst_length: 1,
+            adaptation: Adaptation::default(),
+            plastic_model: None,
+            normalize_outgoing: false,
+            target_total_strength: 30.,
+            synaptic_scaling: None,
+            charge_floor: 0.0,
+            polarity_policy: None,
+            gap_junction: None,
+            energy_model: None,
+            interneuron_fraction: 0.,
+            interneuron_params: InterneuronParams::default(),
+            plastic_params: None,
+            region_overrides: Vec::new(),
+            actuator_params: None,
+            actuator_overrides: HashMap::new(),
+            sensory_params: None,
+            sensory_encoder: Rc::new(sensory_encoders::LinearPeriodEncoder {
+                min_period: 1,
+                max_period: 20,
+            }),
+            sensor_sampling: HashMap::new(),
+            actuator_output: HashMap::new(),
+            multi_sensors: Vec::new(),
+            injected_sensors: Vec::new(),
+            reflexes: Vec::new(),
+            reflex_templates: Vec::new(),
+            actuator_groups: Vec::new(),
+            antagonist_pairs: Vec::new(),
+            comparators: Vec::new(),
+            reward_modulation: false,
+            actuator_failure_threshold: 10,
+            isolate_user_code: true,
+            auto_normalize: true,
+            assert_sensor_range: false,
+            initial_synapses: Vec::new(),
+            sensor_layout: SensorLayout::new(),
+            actuator_layout: ActuatorLayout::new(),
+            actuator_spread: None,
+            sensor_cluster_size: 1,
+            actuator_cluster_size: 1,
+            reserved_sensory_positions: 0,
+            reserved_actuator_positions: 0,
+            probe_capacity: 1000,
+            detailed_metrics: false,
+            legacy_phase_order: false,
+            journal_capacity: 0,
+            small_world_p: 0.,
+            seed: None,
+            clock: Rc::new(SystemClock::new()),
+            profile_cycles: false,
+            progress: None,
+            progress_granularity: 0.01,
+        }
+    }
+
+    /// The fire threshold of every rx neuron in the encephalon,
+    /// unless overridden by plastic_params or actuator_params
+    pub fn fire_threshold(mut self, fire_threshold: f32) -> EncephalonBuilder {
+        self.fire_threshold = fire_threshold;
+        self
+    }
+
+    /// Whether charge exactly at the fire threshold fires plastic
+    /// and actuator neurons, unless overridden by plastic_params
+    /// or actuator_params.  The default is Exclusive, under which
+    /// a reflex exactly at threshold never fires its actuator
+    pub fn threshold_mode(mut self, threshold_mode: ThresholdMode) -> EncephalonBuilder {
+        self.threshold_mode = threshold_mode;
+        self
+    }
+
+    /// How inhibitory impulses act on plastic and actuator neurons,
+    /// unless overridden by plastic_params or actuator_params.  The
+    /// default is Subtractive.  Under Shunting, inhibition divides
+    /// rather than subtracts, so a weak inhibitory reflex can still
+    /// silence a strong excitatory one.  The gain of shunting
+    /// inhibition has to be finite and non-negative, or the brain
+    /// isn't built
+    pub fn inhibition_mode(mut self, inhibition_mode: InhibitionMode) -> EncephalonBuilder {
+        self.inhibition_mode = inhibition_mode;
+        self
+    }
+
+    /// The constant of the exponential moving average used by
+    /// every neuron in the encephalon, unless overridden by
+    /// plastic_params, actuator_params, or sensory_params
+    pub fn ema_alpha(mut self, ema_alpha: f32) -> EncephalonBuilder {
+        self.ema_alpha = ema_alpha;
+        self
+    }
+
+    /// The EMA beneath which the EMA of every neuron in the
+    /// encephalon snaps to exactly 0, unless overridden by
+    /// plastic_params, actuator_params, or sensory_params
+    pub fn ema_floor(mut self, ema_floor: f32) -> EncephalonBuilder {
+        self.ema_floor = ema_floor;
+        self
+    }
+
+    /// Sets the constant of the exponential moving average
+    /// from ema, ie `.ema(EmaParams::from_window(100))`
+    pub fn ema(mut self, ema: EmaParams) -> EncephalonBuilder {
+        self.ema_alpha = ema.alpha;
+        self
+    }
+
+    pub fn synaptic_strength_generator(
+        mut self,
+        synaptic_strength_generator: Rc<dyn Fn() -> Box<RefCell<dyn SynapticStrength>>>,
+    ) -> EncephalonBuilder {
+        self.synaptic_strength_generator = synaptic_strength_generator;
+        self
+    }
+
+    /// Decides when plastic synapses dissolve.  The default
+    /// dissolves synapses once their strength falls beneath
+    /// their weakness threshold
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let builder = EncephalonBuilder::new().prune_policy(Composite(vec![
+    ///     Box::new(StrengthThreshold),
+    ///     Box::new(Staleness { max_idle_cycles: 50 }),
+    /// ]));
+    /// ```
+    pub fn prune_policy(mut self, prune_policy: impl PrunePolicy + 'static) -> EncephalonBuilder {
+        self.prune_policy = Box::new(prune_policy);
+        self
+    }
+
+    /// Caps the number of plastic synapses across the whole encephalon,
+    /// rather than just per neuron, so memory stays bounded on long
+    /// runs.  What happens to synapses beyond the budget is up to the
+    /// budget policy.  Without a budget, only the per neuron caps apply
+    pub fn global_synapse_budget(mut self, budget: usize) -> EncephalonBuilder {
+        self.global_synapse_budget = Some(budget);
+        self
+    }
+
+    /// Decides what becomes of synapses that would form beyond the
+    /// global synapse budget.  The default refuses to form them
+    pub fn budget_policy(mut self, budget_policy: BudgetPolicy) -> EncephalonBuilder {
+        self.budget_policy = budget_policy;
+        self
+    }
+
+    /// Restricts which kinds of neuron plastic synapses may form
+    /// onto.  Keeping sensory neurons off the actuators, say, makes
+    /// every learned pathway run through the plastic pool, even
+    /// when long range synapses could reach the actuators:
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let builder = EncephalonBuilder::new().targeting_rules(TargetingRules {
+    ///     sensory_to_actuator: false,
+    ///     ..TargetingRules::default()
+    /// });
+    /// ```
+    pub fn targeting_rules(mut self, targeting_rules: TargetingRules) -> EncephalonBuilder {
+        self.targeting_rules = targeting_rules;
+        self
+    }
+
+    /// Where along their strength curve newly formed plastic
+    /// synapses start.  The default leaves them wherever the
+    /// synaptic strength generator starts them, so every new
+    /// synapse is identical
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let builder = EncephalonBuilder::new().strength_init(StrengthInit::UniformRandom {
+    ///     min_x: -2.,
+    ///     max_x: 2.,
+    /// });
+    /// ```
+    pub fn strength_init(mut self, strength_init: StrengthInit) -> EncephalonBuilder {
+        self.strength_init = strength_init;
+        self
+    }
+
+    pub fn synapse_type_threshold(mut self, synapse_type_threshold: f32) -> EncephalonBuilder {
+        self.synapse_type_threshold = synapse_type_threshold;
+        self
+    }
+
+    /// Left unset, the budget is 64 synapses, quietly trimmed to
+    /// the geometry's neighborhood.  A budget set here has to fit
+    /// that neighborhood (see clamp_synapse_budget)
+    pub fn max_plastic_synapses(mut self, max_plastic_synapses: usize) -> EncephalonBuilder {
+        self.max_plastic_synapses = Some(max_plastic_synapses);
+        self
+    }
+
+    /// If true, a synapse budget larger than the geometry's
+    /// neighborhood is clamped down to fit (and the clamp noted
+    /// in the size report) rather than failing the build
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let builder = EncephalonBuilder::new()
+    ///     .max_plastic_synapses(64)
+    ///     .clamp_synapse_budget(true);
+    /// ```
+    pub fn clamp_synapse_budget(mut self, clamp_synapse_budget: bool) -> EncephalonBuilder {
+        self.clamp_synapse_budget = clamp_synapse_budget;
+        self
+    }
+
+    /// The longest delay (in cycles) with which an impulse can
+    /// arrive at an rx neuron.  Each rx neuron holds a slot of
+    /// charge for each cycle of delay, and the default of 1 has
+    /// every impulse arrive on the cycle after it was sent
+    pub fn max_delay(mut self, max_delay: u32) -> EncephalonBuilder {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// The number of recent cycles over which each neuron
+    /// remembers whether it fired.  This is the window used by
+    /// recent_fire_count, and it's at least 2
+    pub fn fire_window(mut self, fire_window: u32) -> EncephalonBuilder {
+        self.fire_window = fire_window;
+        self
+    }
+
+    /// The number of cycles after a neuron fires over which the
+    /// targets of its plastic synapses are paired with it.  Each time
+    /// a neuron fires on cycle t, its synapses onto targets that fired
+    /// within (t, t + pairing_window] strengthen, and the rest decay.
+    /// The default of 1 pairs only monosynaptic latencies, so a wider
+    /// window lets a synapse learn from a target reached a few hops on
+    pub fn pairing_window(mut self, pairing_window: u32) -> EncephalonBuilder {
+        self.pairing_window = pairing_window.max(1);
+        self
+    }
+
+    /// The number of consecutive cycles a plastic neuron fires
+    /// each time its charge crosses the fire threshold.  The default
+    /// of 1 fires a single spike per crossing.  Every spike of a
+    /// burst is transmitted, so a single crossing of a neuron
+    /// bursting for 3 cycles charges its targets 3 times
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let builder = EncephalonBuilder::new().burst_length(3);
+    /// ```
+    pub fn burst_length(mut self, burst_length: u32) -> EncephalonBuilder {
+        self.burst_length = burst_length;
+        self
+    }
+
+    /// Spike-frequency adaptation of plastic neurons.  Each
+    /// fire raises a neuron's threshold by `incr`, which decays
+    /// by a factor of `decay` each cycle.  The default `incr`
+    /// of 0 disables adaptation.  Under strong constant input,
+    /// an adapting neuron's intervals between spikes lengthen
+    /// before settling
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let builder = EncephalonBuilder::new().adaptation(0.5, 0.97);
+    /// ```
+    pub fn adaptation(mut self, incr: f32, decay: f32) -> EncephalonBuilder {
+        self.adaptation = Adaptation { incr, decay };
+        self
+    }
+
+    /// Couples each pair of adjacent plastic neurons with a gap
+    /// junction with the given probability.  Each cycle a coupled
+    /// pair exchanges conductance times the difference of their
+    /// charges, which tends to synchronize local populations
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let builder = EncephalonBuilder::new().gap_junction(0.5, 0.2);
+    /// ```
+    pub fn gap_junction(mut self, probability: f32, conductance: f32) -> EncephalonBuilder {
+        self.gap_junction = Some(GapJunctionParams {
+            probability,
+            conductance,
+        });
+        self
+    }
+
+    /// Charges every fire and transmission against a shared energy
+    /// store, which starts full.  Under saturating drive the brain
+    /// settles at the firing its regeneration pays for.  Errors on
+    /// build if the store never regenerates, as it could never recover
+    pub fn energy_model(mut self, energy_model: EnergyModel) -> EncephalonBuilder {
+        self.energy_model = Some(energy_model);
+        self
+    }
+
+    /// If true, after each plasticity update a plastic neuron
+    /// rescales its outgoing plastic synapses so their strengths
+    /// sum to the target total strength
+    pub fn normalize_outgoing(mut self, normalize_outgoing: bool) -> EncephalonBuilder {
+        self.normalize_outgoing = normalize_outgoing;
+        self
+    }
+
+    /// The total strength outgoing plastic synapses are
+    /// normalized to if normalize_outgoing is set
+    pub fn target_total_strength(mut self, target_total_strength: f32) -> EncephalonBuilder {
+        self.target_total_strength = target_total_strength;
+        self
+    }
+
+    /// Has every rx neuron adjust the gain of its incoming
+    /// impulses to keep its EMA near the scaling's target.  By
+    /// default the gain is fixed at 1
+    pub fn synaptic_scaling(mut self, synaptic_scaling: SynapticScaling) -> EncephalonBuilder {
+        self.synaptic_scaling = Some(synaptic_scaling);
+        self
+    }
+
+    /// How neurons decide whether the plastic synapses they form
+    /// are excitatory or inhibitory.  If this isn't set, neurons use
+    /// SourceEmaThreshold with the builder's synapse_type_threshold
+    pub fn polarity_policy(mut self, polarity_policy: PolarityPolicy) -> EncephalonBuilder {
+        self.polarity_policy = Some(polarity_policy);
+        self
+    }
+
+    /// The lowest internal charge an rx neuron can hold, which
+    /// keeps strong inhibition from burying a neuron.  The default
+    /// is 0, and f32::NEG_INFINITY leaves charge unbounded
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let builder = EncephalonBuilder::new().charge_floor(-20.);
+    /// ```
+    pub fn charge_floor(mut self, charge_floor: f32) -> EncephalonBuilder {
+        self.charge_floor = charge_floor;
+        self
+    }
+
+    /// The model used for plastic neurons.  If this isn't set,
+    /// plastic neurons use the Ema model with the parameters
+    /// set on this builder
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let builder = EncephalonBuilder::new()
+    ///     .plastic_model(PlasticModel::Izhikevich(IzhParams::regular_spiking()));
+    /// ```
+    pub fn plastic_model(mut self, plastic_model: PlasticModel) -> EncephalonBuilder {
+        self.plastic_model = Some(plastic_model);
+        self
+    }
+
+    /// The fraction of plastic positions that are filled with
+    /// inhibitory interneurons, chosen at random.  The default
+    /// of 0 creates no interneurons.  Interneurons only ever
+    /// form inhibitory synapses
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let builder = EncephalonBuilder::new().interneuron_fraction(0.2);
+    /// ```
+    pub fn interneuron_fraction(mut self, interneuron_fraction: f32) -> EncephalonBuilder {
+        self.interneuron_fraction = interneuron_fraction;
+        self
+    }
+
+    /// The parameters that set inhibitory interneurons
+    /// apart from other plastic neurons
+    pub fn interneuron_params(
+        mut self,
+        interneuron_params: InterneuronParams,
+    ) -> EncephalonBuilder {
+        self.interneuron_params = interneuron_params;
+        self
+    }
+
+    /// Overrides every parameter of plastic neurons.  Without
+    /// this, plastic neurons take their parameters from the
+    /// individual setters of this builder
+    pub fn plastic_params(mut self, plastic_params: PlasticParams) -> EncephalonBuilder {
+        self.plastic_params = Some(plastic_params);
+        self
+    }
+
+    /// Patches the parameters of the plastic neurons (and
+    /// interneurons) within region.  Where regions overlap,
+    /// the override added last wins
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let builder = EncephalonBuilder::new().region_override(
+    ///     Region::new(vec![0, 0, 2], vec![4, 4, 2]),
+    ///     PlasticParamsPatch {
+    ///         fire_threshold: Some(1000.),
+    ///         ..PlasticParamsPatch::default()
+    ///     },
+    /// );
+    /// ```
+    pub fn region_override(
+        mut self,
+        region: Region,
+        patch: PlasticParamsPatch,
+    ) -> EncephalonBuilder {
+        self.region_overrides.push((region, patch));
+        self
+    }
+
+    /// Overrides every parameter of actuator neurons.  Without
+    /// this, actuator neurons take their parameters from the
+    /// individual setters of this builder
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let builder = EncephalonBuilder::new().actuator_params(ActuatorParams {
+    ///     fire_threshold: 20.,
+    ///     ..ActuatorParams::default()
+    /// });
+    /// ```
+    pub fn actuator_params(mut self, actuator_params: ActuatorParams) -> EncephalonBuilder {
+        self.actuator_params = Some(actuator_params);
+        self
+    }
+
+    /// Overrides every parameter of the actuator neurons driving the
+    /// named actuator, so that snappy and heavily smoothed actuators
+    /// can share an encephalon.  Other actuators keep the parameters
+    /// of actuator_params
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let builder = EncephalonBuilder::new().actuator_override(
+    ///     "gripper".into(),
+    ///     ActuatorParams {
+    ///         alpha: 0.2,
+    ///         ..ActuatorParams::default()
+    ///     },
+    /// );
+    /// ```
+    pub fn actuator_override(
+        mut self,
+        actuator_name: ActuatorName,
+        actuator_params: ActuatorParams,
+    ) -> EncephalonBuilder {
+        self.actuator_overrides
+            .insert(actuator_name, actuator_params);
+        self
+    }
+
+    /// Overrides every parameter of sensory neurons.  Without
+    /// this, sensory neurons take their parameters from the
+    /// individual setters of this builder
+    pub fn sensory_params(mut self, sensory_params: SensoryParams) -> EncephalonBuilder {
+        self.sensory_params = Some(sensory_params);
+        self
+    }
+
+    /// The polarity policy set on this builder
+    fn resolved_polarity_policy(&self) -> PolarityPolicy {
+        self.polarity_policy
+            .unwrap_or(PolarityPolicy::SourceEmaThreshold {
+                threshold: self.synapse_type_threshold,
+            })
+    }
+
+    /// The plastic neuron parameters set on this builder
+    fn resolved_plastic_params(&self) -> PlasticParams {
+        self.plastic_params.unwrap_or(PlasticParams {
+            fire_threshold: self.fire_threshold,
+            threshold_mode: self.threshold_mode,
+            inhibition_mode: self.inhibition_mode,
+            alpha: self.ema_alpha,
+            ema_floor: self.ema_floor,
+            max_plastic_synapses: self
+                .max_plastic_synapses
+                .unwrap_or(DEFAULT_MAX_PLASTIC_SYNAPSES),
+            polarity_policy: self.resolved_polarity_policy(),
+            max_delay: self.max_delay,
+            fire_window: self.fire_window,
+            burst_length: self.burst_length,
+            adaptation: self.adaptation,
+            outgoing_total: match self.normalize_outgoing {
+                true => Some(self.target_total_strength),
+                false => None,
+            },
+            synaptic_scaling: self.synaptic_scaling,
+            charge_floor: self.charge_floor,
+            local_radius: None,
+        })
+    }
+
+    /// The actuator neuron parameters set on this builder
+    fn resolved_actuator_params(&self) -> ActuatorParams {
+        self.actuator_params.unwrap_or(ActuatorParams {
+            fire_threshold: self.fire_threshold,
+            threshold_mode: self.threshold_mode,
+            inhibition_mode: self.inhibition_mode,
+            alpha: self.ema_alpha,
+            ema_floor: self.ema_floor,
+            max_delay: self.max_delay,
+            fire_window: self.fire_window,
+            synaptic_scaling: self.synaptic_scaling,
+            charge_floor: self.charge_floor,
+        })
+    }
+
+    /// The sensory neuron parameters set on this builder
+    fn resolved_sensory_params(&self) -> SensoryParams {
+        self.sensory_params.unwrap_or(SensoryParams {
+            alpha: self.ema_alpha,
+            ema_floor: self.ema_floor,
+            max_plastic_synapses: self
+                .max_plastic_synapses
+                .unwrap_or(DEFAULT_MAX_PLASTIC_SYNAPSES),
+            polarity_policy: self.resolved_polarity_policy(),
+            fire_window: self.fire_window,
+        })
+    }
+
+    /// Encodes each sensor's measurement into the period of its
+    /// sensory neurons.  Either a function from measurement to
+    /// period, or one of the structs of sensory_encoders
+    pub fn sensory_encoder(
+        mut self,
+        sensory_encoder: impl SensoryEncoder + 'static,
+    ) -> EncephalonBuilder {
+        self.sensory_encoder = Rc::new(sensory_encoder);
+        self
+    }
+
+    /// Registers a sensor with several channels.  Each channel
+    /// is attached to the encephalon as a sensor named after the
+    /// channel, in addition to the sensors passed to build.
+    /// However many channels it has, a multi sensor is measured
+    /// exactly once per cycle (see split_multi_sensor)
+    pub fn multi_sensor(mut self, multi_sensor: Box<dyn MultiSensor>) -> EncephalonBuilder {
+        self.multi_sensors.push(multi_sensor);
+        self
+    }
+
+    /// Attaches an InjectedSensor for each name, in addition to
+    /// the sensors passed to build.  Their values are pushed in a
+    /// frame at a time with Encephalon::set_sensor_values
+    pub fn injected_sensors<S: Into<String>>(
+        mut self,
+        names: impl IntoIterator<Item = S>,
+    ) -> EncephalonBuilder {
+        self.injected_sensors
+            .extend(names.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn reflexes(mut self, reflexes: Vec<Reflex>) -> EncephalonBuilder {
+        self.reflexes = reflexes;
+        self
+    }
+
+    /// Adds a reflex template, which is expanded at build
+    /// time into reflexes following those passed to reflexes
+    pub fn reflex_template(mut self, template: ReflexTemplate) -> EncephalonBuilder {
+        self.reflex_templates.push(template);
+        self
+    }
+
+    /// Adds a group of actuators whose values compete according
+    /// to the group's mode, rather than each actuator being set
+    /// to its own ema.  An actuator can belong to at most one group.
+    /// In Argmax mode exactly one member is sent 1.0 each cycle,
+    /// and it's the member whose neurons have the highest ema
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let builder = EncephalonBuilder::new().actuator_group(ActuatorGroup::new(
+    ///     "heading".to_string(),
+    ///     vec!["left".into(), "straight".into(), "right".into()],
+    ///     GroupMode::Argmax,
+    /// ));
+    /// ```
+    pub fn actuator_group(mut self, actuator_group: ActuatorGroup) -> EncephalonBuilder {
+        self.actuator_groups.push(actuator_group);
+        self
+    }
+
+    /// Adds a pair of antagonistic actuators, whose difference is
+    /// read out as a single signed value.  Unlike a group, the pair
+    /// leaves its members' own actuators alone, so an actuator can
+    /// belong to any number of pairs
+    pub fn antagonist_pair(mut self, antagonist_pair: AntagonistPair) -> EncephalonBuilder {
+        self.antagonist_pairs.push(antagonist_pair);
+        self
+    }
+
+    /// Sets how often the named sensor is sampled.  Sensors
+    /// without sampling of their own are measured every cycle.
+    /// In between samples, the sensor's neurons hold their period
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let builder = EncephalonBuilder::new().sensor_sampling(
+    ///     "camera".into(),
+    ///     SensorSampling {
+    ///         sample_divisor: 10,
+    ///         ..SensorSampling::default()
+    ///     },
+    /// );
+    /// ```
+    pub fn sensor_sampling(
+        mut self,
+        sensor_name: SensorName,
+        sampling: SensorSampling,
+    ) -> EncephalonBuilder {
+        self.sensor_sampling.insert(sensor_name, sampling);
+        self
+    }
+
+    /// Sets how the control values sent to the named actuator
+    /// are paced.  Actuators without output settings of their
+    /// own are sent their raw value every cycle
+    pub fn actuator_output(
+        mut self,
+        actuator_name: ActuatorName,
+        output: ActuatorOutput,
+    ) -> EncephalonBuilder {
+        self.actuator_output.insert(actuator_name, output);
+        self
+    }
+
+    /// Adds a comparator whose reward is summed into the
+    /// encephalon's global reward signal each cycle.  Each
+    /// comparator's error is reported in the cycle's metrics
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let builder = EncephalonBuilder::new()
+    ///     .target_comparator(TargetComparator::constant("motor".into(), 0.2, 0.3));
+    /// ```
+    pub fn target_comparator(mut self, comparator: TargetComparator) -> EncephalonBuilder {
+        self.comparators.push(comparator);
+        self
+    }
+
+    /// Whether the global reward signal modulates plasticity.
+    /// While the reward is negative, correlated synapses weaken
+    /// and uncorrelated synapses strengthen, so behavior that's
+    /// punished is unlearned
+    pub fn reward_modulation(mut self, reward_modulation: bool) -> EncephalonBuilder {
+        self.reward_modulation = reward_modulation;
+        self
+    }
+
+    /// The number of times in a row an actuator can fail before
+    /// the encephalon stops calling it.  A disabled actuator is
+    /// called again after Encephalon::reset_actuator_failures
+    pub fn actuator_failure_threshold(
+        mut self,
+        actuator_failure_threshold: u32,
+    ) -> EncephalonBuilder {
+        self.actuator_failure_threshold = actuator_failure_threshold;
+        self
+    }
+
+    /// Whether panics in sensors and actuators are caught, so
+    /// that the rest of the cycle still runs.  A sensor that panics
+    /// is counted and its neurons hold their last period, and an
+    /// actuator that panics fails.  This is on by default, and has
+    /// no effect when panics abort
+    pub fn isolate_user_code(mut self, isolate_user_code: bool) -> EncephalonBuilder {
+        self.isolate_user_code = isolate_user_code;
+        self
+    }
+
+    /// Whether every sensor is wrapped in a NormalizedSensor that
+    /// clamps its measurements into [0, 1] before they're encoded.
+    /// This is on by default.  Measurements that still fall outside
+    /// of [0, 1] are counted in the cycle metrics either way
+    pub fn auto_normalize(mut self, auto_normalize: bool) -> EncephalonBuilder {
+        self.auto_normalize = auto_normalize;
+        self
+    }
+
+    /// Whether a sensor measurement outside of [0, 1] trips a
+    /// debug assertion, rather than just being counted.  Off by
+    /// default, and only checked in debug builds
+    pub fn assert_sensor_range(mut self, assert_sensor_range: bool) -> EncephalonBuilder {
+        self.assert_sensor_range = assert_sensor_range;
+        self
+    }
+
+    /// Synapses to wire into the encephalon once it's built, after
+    /// its reflexes.  This is how a connectome exported from one
+    /// brain seeds another, so long as both share a geometry.
+    /// Building errors if a spec names a location the geometry
+    /// doesn't have, a source that doesn't transmit, or a target
+    /// that doesn't receive:
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// fn seeded_by(trained: &Encephalon) -> EncephalonBuilder {
+    ///     let specs = trained
+    ///         .connectome()
+    ///         .into_iter()
+    ///         .map(SynapseSpec::from)
+    ///         .collect();
+    ///
+    ///     EncephalonBuilder::new().initial_synapses(specs)
+    /// }
+    /// ```
+    pub fn initial_synapses(mut self, initial_synapses: Vec<SynapseSpec>) -> EncephalonBuilder {
+        self.initial_synapses = initial_synapses;
+        self
+    }
+
+    /// Places sensors at specific coordinates of the sensory plane.
+    /// Coordinates must lie on the plane, and no two may coincide
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let layout = SensorLayout::new()
+    ///     .place("left", 0, 0)
+    ///     .place("right", 4, 0);
+    /// let builder = EncephalonBuilder::new().sensor_layout(layout);
+    /// ```
+    pub fn sensor_layout(mut self, sensor_layout: SensorLayout) -> EncephalonBuilder {
+        self.sensor_layout = sensor_layout;
+        self
+    }
+
+    /// Places actuators at specific coordinates of the actuator plane.
+    /// Only the actuator positions of a box lie on its plane (see
+    /// actuator_spread), so packed actuators swap places at most
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let builder = EncephalonBuilder::new().actuator_layout(ActuatorLayout::new().place("right", 0, 0));
+    /// ```
+    pub fn actuator_layout(mut self, actuator_layout: ActuatorLayout) -> EncephalonBuilder {
+        self.actuator_layout = actuator_layout;
+        self
+    }
+
+    /// Spreads the actuator neurons across the actuator plane of the
+    /// geometry.  Building errors if the geometry doesn't support it
+    pub fn actuator_spread(mut self, actuator_spread: ActuatorSpread) -> EncephalonBuilder {
+        self.actuator_spread = Some(actuator_spread);
+        self
+    }
+
+    /// Number of sensory neurons driven by each sensor.  Every
+    /// neuron of the cluster receives the same encoded period, so
+    /// a sensor isn't blinded if one neuron's synapses land poorly.
+    /// The geometry must then have sensors * sensor_cluster_size
+    /// sensory positions, and each cluster is a contiguous strip
+    /// along a row of the plane
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// // Two sensors of 3 neurons each
+    /// let ecp = BoxEcp::new(125, 6, 0, 27);
+    /// let builder = EncephalonBuilder::new().sensor_cluster_size(3);
+    /// ```
+    pub fn sensor_cluster_size(mut self, sensor_cluster_size: u32) -> EncephalonBuilder {
+        self.sensor_cluster_size = sensor_cluster_size;
+        self
+    }
+
+    /// Number of actuator neurons whose EMAs are averaged to
+    /// drive each actuator.  The geometry must then have
+    /// actuators * actuator_cluster_size actuator positions.
+    /// Averaging smooths the output of neurons that fire out
+    /// of phase with each other
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// // One actuator driven by 5 neurons
+    /// let ecp = BoxEcp::new(125, 0, 5, 27);
+    /// let builder = EncephalonBuilder::new().actuator_cluster_size(5);
+    /// ```
+    pub fn actuator_cluster_size(mut self, actuator_cluster_size: u32) -> EncephalonBuilder {
+        self.actuator_cluster_size = actuator_cluster_size;
+        self
+    }
+
+    /// Number of sensors' worth of sensory positions to hold for
+    /// sensors attached after building, with Encephalon::attach_sensor.
+    /// The geometry must then fit the sensors and the reserved
+    /// positions.  A sensor layout may place a name that isn't one of
+    /// the sensors, which holds one of the reserved positions for the
+    /// sensor of that name
+    pub fn reserved_sensory_positions(mut self, reserved: u32) -> EncephalonBuilder {
+        self.reserved_sensory_positions = reserved;
+        self
+    }
+
+    /// Number of actuators' worth of actuator positions to hold for
+    /// actuators attached after building, with Encephalon::attach_actuator.
+    /// Reserved positions can be placed by name, as with sensors
+    pub fn reserved_actuator_positions(mut self, reserved: u32) -> EncephalonBuilder {
+        self.reserved_actuator_positions = reserved;
+        self
+    }
+
+    /// The number of most recent samples each probe keeps
+    pub fn probe_capacity(mut self, probe_capacity: usize) -> EncephalonBuilder {
+        self.probe_capacity = probe_capacity;
+        self
+    }
+
+    /// If true, each cycle's metrics include percentiles of the
+    /// strengths of every plastic synapse.  This sweeps every
+    /// synapse each cycle, so it's off by default
+    pub fn detailed_metrics(mut self, detailed_metrics: bool) -> EncephalonBuilder {
+        self.detailed_metrics = detailed_metrics;
+        self
+    }
+
+    /// If true, each cycle runs its actuator interfaces before its
+    /// neurons, as encephalons used to, so actuators are handed what
+    /// their neurons did the cycle before.  That lags every actuator
+    /// by a cycle: a reflex's actuator is updated two cycles after
+    /// its sensor is sampled, rather than one.
+    ///
+    /// This is only here to ease the change of phase order,
+    /// and will be removed in a release or so
+    pub fn legacy_phase_order(mut self, legacy_phase_order: bool) -> EncephalonBuilder {
+        self.legacy_phase_order = legacy_phase_order;
+        self
+    }
+
+    /// The number of most recent synapse lifecycle events
+    /// (formations, prunings, and reflexes) the encephalon
+    /// journals.  Defaults to 0, which keeps no journal
+    pub fn journal_capacity(mut self, journal_capacity: usize) -> EncephalonBuilder {
+        self.journal_capacity = journal_capacity;
+        self
+    }
+
+    /// The probability that a newly formed synapse targets a plastic
+    /// neuron drawn from the entire encephalon, rather than a neuron
+    /// nearby its source.  These long range shortcuts make the
+    /// connectivity small-world, without wiring any neuron straight
+    /// into a distant actuator.  The default of 0 keeps every
+    /// synapse local.
+    ///
+    /// ```
+    /// # use eywa::prelude::*;
+    /// let builder = EncephalonBuilder::new().small_world_p(0.05);
+    /// ```
+    pub fn small_world_p(mut self, small_world_p: f32) -> EncephalonBuilder {
+        self.small_world_p = small_world_p;
+        self
+    }
+
+    /// Seeds the encephalon's random number generator, which every
+    /// random choice (synapse targets, polarities, etc) is drawn
+    /// from.  Without a seed, the generator is seeded from entropy
+    pub fn seed(mut self, seed: u64) -> EncephalonBuilder {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// The clock the encephalon reads wall time from when timing
+    /// and pacing runs.  Defaults to the system clock, but a
+    /// MockClock makes timing deterministic
+    pub fn clock(mut self, clock: Rc<dyn Clock>) -> EncephalonBuilder {
+        self.clock = clock;
+        self
+    }
+
+    /// Whether the encephalon times each phase of every cycle on
+    /// its clock, as reported by Encephalon::profile_report.  Off
+    /// by default, which leaves the cycle untimed
+    pub fn profile_cycles(mut self, profile_cycles: bool) -> EncephalonBuilder {
+        self.profile_cycles = profile_cycles;
+        self
+    }
+
+    /// Calls progress with each phase of build, the number of its
+    /// items built so far, and the number it will build in all (read
+    /// from the geometry up front), so that the long build of a large
+    /// brain can be shown or logged.  Every phase is reported at its
+    /// start and end, and in steps of progress_granularity between:
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let builder = EncephalonBuilder::new().progress(|phase, done, total| {
+    ///     println!("{:?}: {} of {}", phase, done, total)
+    /// });
+    /// ```
+    pub fn progress(
+        mut self,
+        progress: impl FnMut(BuildPhase, u32, u32) + 'static,
+    ) -> EncephalonBuilder {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// The fraction of a phase of build between reports to the
+    /// progress callback.  Defaults to 0.01, a report every 1%
+    pub fn progress_granularity(mut self, progress_granularity: f32) -> EncephalonBuilder {
+        self.progress_granularity = progress_granularity;
+        self
+    }
+
+    /// Resolves the brain that build would construct upon
+    /// ecp_geometry, with sensors and actuators of these names (and
+    /// the builder's multi sensors), without allocating a neuron.  So
+    /// the configuration of a large brain can be checked before
+    /// waiting on it to build.  Planning errs wherever build would:
+    ///
+    /// ```
+    /// use eywa::prelude::*;
+    ///
+    /// let plan = EncephalonBuilder::new()
+    ///     .plan(
+    ///         &BoxEcp::new(125, 1, 1, 27),
+    ///         &["light".to_string()],
+    ///         &["motor".to_string()],
+    ///     )
+    ///     .unwrap();
+    ///
+    /// println!("{:?}", plan.size_report);
+    /// ```
+    ///
+    /// Build sets any actuator spread upon the geometry it owns, which
+    /// planning can't, so the geometry is planned just as it's given
+    pub fn plan(
+        &self,
+        ecp_geometry: &dyn EcpGeometry,
+        sensor_names: &[String],
+        actuator_names: &[String],
+    ) -> Result<BrainPlan, EywaError> {
+        let sensor_names: Vec<SensorName> = sensor_names
+            .iter()
+            .cloned()
+            .chain(
+                self.multi_sensors
+                    .iter()
+                    .flat_map(|multi_sensor| multi_sensor.channel_names()),
+            )
+            .chain(self.injected_sensors.iter().cloned())
+            .map(SensorName::from)
+            .collect();
+        let actuator_names: Vec<ActuatorName> = actuator_names
+            .iter()
+            .map(|name| ActuatorName::from(name.as_str()))
+            .collect();
+
+        let (mut plan, _, _) = self.resolve(ecp_geometry, &sensor_names, &actuator_names)?;
+
+        let base_params = match plan.plastic_model {
+            PlasticModel::Ema(params) => params,
+            PlasticModel::Izhikevich(_) => plan.plastic_params,
+        };
+
+        let rx_neurons = ecp_geometry
+            .rx_locs()
+            .map(|(loc, _, neuron_type)| match neuron_type {
+                RxNeuron::Actuator => NeuronPlan {
+                    loc: loc.into(),
+                    kind: NeuronKind::Actuator,
+                    params: None,
+                },
+                RxNeuron::Plastic => NeuronPlan {
+                    loc: loc.into(),
+                    kind: NeuronKind::Plastic,
+                    params: Some(plan.region_params(&loc, base_params)),
+                },
+            });
+
+        let sensory_neurons = ecp_geometry.sensory_locs().map(|(loc, _)| NeuronPlan {
+            loc: loc.into(),
+            kind: NeuronKind::Sensory,
+            params: None,
+        });
+
+        plan.neurons = rx_neurons.chain(sensory_neurons).collect();
+
+        Ok(plan)
+    }
+
+    /// Builds the encephalon within ecp_geometry, attaching
+    /// sensors to sensory neurons and actuators to actuator neurons.
+    ///
+    /// A brain can go without sensors, driven only by injected
+    /// charge, or without actuators, for analysis alone.  Neither
+    /// can have reflexes, so those are refused up front.
+    ///
+    /// Construction scales to large brains, which
+    /// `cargo run --release --bin bench -- build` times
+    pub fn build(
+        mut self,
+        mut ecp_geometry: Box<dyn EcpGeometry>,
+        mut sensors: Vec<Box<dyn Sensor>>,
+        actuators: Vec<Box<dyn Actuator>>,
+    ) -> Result<Rc<Encephalon>, EywaError> {
+        for multi_sensor in self.multi_sensors.drain(..) {
+            sensors.extend(split_multi_sensor(multi_sensor));
+        }
+
+        let mut injected_sensors = HashMap::new();
+
+        for name in self.injected_sensors.drain(..) {
+            let sensor = InjectedSensor::new(name.clone());
+
+            injected_sensors.insert(SensorName::from(name), sensor.value());
+            sensors.push(Box::new(sensor));
+        }
+
+        if self.auto_normalize {
+            sensors = sensors
+                .into_iter()
+                .map(|sensor| {
+                    Box::new(NormalizedSensor::new(sensor, Normalization::Clamp)) as Box<dyn Sensor>
+                })
+                .collect();
+        }
+
+        if let Some(actuator_spread) = self.actuator_spread.take() {
+            ecp_geometry.set_actuator_spread(actuator_spread)?;
+        }
+
+        let sensor_names: Vec<SensorName> = sensors
+            .iter()
+            .map(|sensor| SensorName::from(sensor.get_name()))
+            .collect();
+        let actuator_names: Vec<ActuatorName> = actuators
+            .iter()
+            .map(|actuator| ActuatorName::from(actuator.get_name()))
+            .collect();
+
+        let (plan, sensor_items, actuator_items) =
+            self.resolve(ecp_geometry.as_ref(), &sensor_names, &actuator_names)?;
+
+        let actuator_params = self.resolved_actuator_params();
+        let plastic_params = plan.plastic_params;
+        let sensory_params = plan.sensory_params;
+        let plastic_model = plan.plastic_model;
+
+        let cluster_hashes = |clusters: &[ClusterPlan]| -> Vec<Vec<String>> {
+            clusters
+                .iter()
+                .map(|cluster| {
+                    cluster
+                        .locs
+                        .iter()
+                        .map(|loc| ecp_geometry.loc_hash(loc))
+                        .collect()
+                })
+                .collect()
+        };
+
+        let sensor_hashes = cluster_hashes(&plan.sensors);
+        let actuator_hashes = cluster_hashes(&plan.actuators);
+
+        // Size the neuron maps up front, as they can hold
+        // hundreds of thousands of neurons
+        let num_rx = (ecp_geometry.get_num_plastic() + ecp_geometry.get_num_actuator()) as usize;
+        let num_actuator = ecp_geometry.get_num_actuator() as usize;
+        let (sensory_width, sensory_height) = ecp_geometry.sensory_plane_dims();
+        let num_sensory = (sensory_width * sensory_height) as usize;
+
+        let mut progress = BuildProgress {
+            callback: self.progress.take(),
+            granularity: self.progress_granularity,
+        };
+
+        let synapse_budget = self
+            .global_synapse_budget
+            .map(|budget| (budget, self.budget_policy));
+
+        // The rng is keyed by its full seed, which snapshots record so
+        // that a restored brain resumes the same stream.  The seed is
+        // expanded just as ChaCha20Rng::seed_from_u64 expands it, so a
+        // seeded brain draws the same numbers it always has
+        let RngSeed(rng_seed) = match self.seed {
+            Some(seed) => RngSeed::seed_from_u64(seed),
+            None => RngSeed::from_entropy(),
+        };
+
+        // A fresh chacha rng can't report its word position until it
+        // has filled its first block, so fill it up front; the stream
+        // it produces is unchanged
+        let mut rng = ChaCha20Rng::from_seed(rng_seed);
+        rng.set_word_pos(0);
+
+        let new_encephalon = Rc::new(Encephalon {
+            cycle_count: RefCell::new(0),
+            ecp_geometry,
+            rx_neurons: RefCell::new(HashMap::with_capacity(num_rx)),
+            rx_hashes: RefCell::new(Vec::with_capacity(num_rx)),
+            plastic_hashes: RefCell::new(Vec::with_capacity(num_rx)),
+            interneuron_hashes: RefCell::new(HashSet::new()),
+            sensory_neurons: RefCell::new(HashMap::with_capacity(num_sensory)),
+            sensory_hashes: RefCell::new(Vec::with_capacity(num_sensory)),
+            actuator_interfaces: RefCell::new(HashMap::new()),
+            sensory_interfaces: RefCell::new(HashMap::new()),
+            sensor_locs: RefCell::new(HashMap::new()),
+            actuator_locs: RefCell::new(HashMap::new()),
+            reflexes: plan.reflexes,
+            actuator_groups: self.actuator_groups,
+            antagonist_pairs: self.antagonist_pairs,
+            comparators: self.comparators,
+            reward_modulation: self.reward_modulation,
+            reward: Cell::new(0.0),
+            learning_rate: Cell::new(1.0),
+            plasticity_frozen: Cell::new(false),
+            eval_mode: Cell::new(false),
+            probing: Cell::new(false),
+            eval_cache: RefCell::new(None),
+            connectome_generation: Cell::new(0),
+            pairing_window: self.pairing_window,
+            max_cycles_per_sensor_update: Cell::new(None),
+            cycles_since_sensor_update: Cell::new(0),
+            prune_policy: self.prune_policy,
+            synapse_budget,
+            plastic_synapse_count: Cell::new(0),
+            energy_model: self.energy_model,
+            energy: Cell::new(
+                self.energy_model
+                    .map_or(0., |energy_model| energy_model.capacity),
+            ),
+            targeting_rules: self.targeting_rules,
+            plastic_params,
+            plastic_model,
+            interneuron_params: self.interneuron_params,
+            region_overrides: plan.region_overrides,
+            gap_junctions: RefCell::new(Vec::new()),
+            probes: RefCell::new(Vec::new()),
+            probe_hashes: RefCell::new(HashMap::new()),
+            probe_capacity: self.probe_capacity,
+            detailed_metrics: self.detailed_metrics,
+            legacy_phase_order: self.legacy_phase_order,
+            synapse_budget_clamp: plan.size_report.synapse_budget_clamp,
+            plan_warnings: plan.warnings,
+            journal: RefCell::new(Journal::new(self.journal_capacity)),
+            groups: RefCell::new(HashMap::new()),
+            lesioned: RefCell::new(HashSet::new()),
+            #[cfg(feature = "audit")]
+            charge_ledger: Cell::new(Default::default()),
+            small_world_p: self.small_world_p,
+            strength_init: self.strength_init,
+            clock: Rc::clone(&self.clock),
+            profile_cycles: self.profile_cycles,
+            reserved_sensors: RefCell::new(Vec::new()),
+            reserved_actuators: RefCell::new(Vec::new()),
+            sensory_encoder: Rc::clone(&self.sensory_encoder),
+            injected_sensors,
+            auto_normalize: self.auto_normalize,
+            assert_sensor_range: self.assert_sensor_range,
+            isolate_user_code: self.isolate_user_code,
+            actuator_failure_threshold: self.actuator_failure_threshold,
+            phase_timings: RefCell::new(PhaseTimings::default()),
+            rng: RefCell::new(rng),
+            rng_key: Cell::new((rng_seed, 0)),
+        });
+
+        let mut actuator_neurons = HashMap::with_capacity(num_actuator);
+
+        // Actuator neurons take the parameters of the actuator
+        // they drive, which the plan already ties to them
+        let actuator_params_at: HashMap<&String, ActuatorParams> = plan
+            .actuators
+            .iter()
+            .zip(&actuator_hashes)
+            .flat_map(|(cluster, hashes)| {
+                let params = cluster.actuator_params.unwrap_or(actuator_params);
+
+                hashes.iter().map(move |hash| (hash, params))
+            })
+            .collect();
+
+        let dims = new_encephalon.ecp_geometry.dims();
+
+        // Populate the encephalon's Rx neurons
+        {
+            let mut rx_neurons = new_encephalon.rx_neurons.borrow_mut();
+            let mut rx_hashes = new_encephalon.rx_hashes.borrow_mut();
+            let mut plastic_hashes = new_encephalon.plastic_hashes.borrow_mut();
+            let mut interneuron_hashes = new_encephalon.interneuron_hashes.borrow_mut();
+            // Not every geometry counts its actuator neurons as
+            // plastic, so the totals come from the traversal itself
+            let rx_locs: Vec<_> = new_encephalon.ecp_geometry.rx_locs().collect();
+            let num_actuator = rx_locs
+                .iter()
+                .filter(|(_, _, neuron_type)| *neuron_type == RxNeuron::Actuator)
+                .count() as u32;
+            let num_plastic = rx_locs.len() as u32 - num_actuator;
+            let (mut plastic_done, mut actuator_done) = (0, 0);
+
+            progress.report(BuildPhase::PlasticNeurons, 0, num_plastic);
+            progress.report(BuildPhase::ActuatorNeurons, 0, num_actuator);
+
+            for (loc, hash, neuron_type) in rx_locs {
+                assert_traversal_dims(loc, dims);
+
+                match neuron_type {
+                    RxNeuron::Actuator => {
+                        actuator_done += 1;
+                        progress.report(BuildPhase::ActuatorNeurons, actuator_done, num_actuator);
+                    }
+                    RxNeuron::Plastic => {
+                        plastic_done += 1;
+                        progress.report(BuildPhase::PlasticNeurons, plastic_done, num_plastic);
+                    }
+                }
+
+                let new_neuron: Rc<dyn NeuronicRx> = match neuron_type {
+                    RxNeuron::Actuator => {
+                        let params = actuator_params_at
+                            .get(&hash)
+                            .copied()
+                            .unwrap_or(actuator_params);
+                        let new_neuron = Rc::new(ActuatorNeuron::new(params, loc));
+                        new_neuron.remember_fires(self.pairing_window);
+
+                        actuator_neurons.insert(hash.clone(), Rc::clone(&new_neuron));
+                        new_neuron
+                    }
+                    RxNeuron::Plastic
+                        if self.interneuron_fraction > 0.
+                            && new_encephalon.random_bool(self.interneuron_fraction) =>
+                    {
+                        interneuron_hashes.insert(hash.clone());
+
+                        let params = self
+                            .interneuron_params
+                            .plastic_params(new_encephalon.region_params(&loc, plastic_params));
+
+                        Rc::new(PlasticNeuron::new(
+                            Rc::clone(&new_encephalon),
+                            Rc::clone(&self.synaptic_strength_generator),
+                            params,
+                            loc,
+                        ))
+                    }
+                    RxNeuron::Plastic => match plastic_model {
+                        PlasticModel::Ema(params) => Rc::new(PlasticNeuron::new(
+                            Rc::clone(&new_encephalon),
+                            Rc::clone(&self.synaptic_strength_generator),
+                            new_encephalon.region_params(&loc, params),
+                            loc,
+                        )),
+                        PlasticModel::Izhikevich(izh_params) => Rc::new(IzhikevichNeuron::new(
+                            Rc::clone(&new_encephalon),
+                            Rc::clone(&self.synaptic_strength_generator),
+                            new_encephalon.region_params(&loc, plastic_params),
+                            izh_params,
+                            loc,
+                        )),
+                    },
+                };
+
+                if neuron_type == RxNeuron::Plastic {
+                    plastic_hashes.push(hash.clone());
+                }
+
+                rx_hashes.push(hash.clone());
+                rx_neurons.insert(hash, new_neuron);
+            }
+        }
+
+        if let Some(gap_junction) = self.gap_junction {
+            if gap_junction.probability > 0. {
+                new_encephalon.form_gap_junctions(gap_junction);
+            }
+        }
+
+        // Populate the encephalon's sensory_neurons
+        {
+            let mut sensory_neurons = new_encephalon.sensory_neurons.borrow_mut();
+            let mut sensory_hashes = new_encephalon.sensory_hashes.borrow_mut();
+            let num_sensory = num_sensory as u32;
+
+            progress.report(BuildPhase::SensoryNeurons, 0, num_sensory);
+
+            for (done, (loc, hash)) in new_encephalon.ecp_geometry.sensory_locs().enumerate() {
+                assert_traversal_dims(loc, dims);
+                progress.report(BuildPhase::SensoryNeurons, done as u32 + 1, num_sensory);
+
+                let new_neuron = Rc::new(SensoryNeuron::new(
+                    Rc::clone(&new_encephalon),
+                    Rc::clone(&self.synaptic_strength_generator),
+                    sensory_params,
+                    loc,
+                ));
+
+                sensory_hashes.push(hash.clone());
+                sensory_neurons.insert(hash, new_neuron);
+            }
+        }
+
+        // Attach the interfaces to the clusters of neurons the plan
+        // placed them at.  Each item is taken by its index among
+        // the names it was planned with
+        let mut actuators: Vec<Option<Box<dyn Actuator>>> =
+            actuators.into_iter().map(Some).collect();
+
+        for ((planned, item), hashes) in plan
+            .actuators
+            .into_iter()
+            .zip(actuator_items)
+            .zip(actuator_hashes)
+        {
+            let cluster: Vec<Rc<ActuatorNeuron>> = hashes
+                .iter()
+                .filter_map(|hash| actuator_neurons.get(hash).map(Rc::clone))
+                .collect();
+
+            let name = planned.name.map(ActuatorName::from);
+
+            let (name, actuator) = match (name, item.and_then(|item| actuators[item].take())) {
+                (Some(name), Some(actuator)) => (name, actuator),
+                (name, _) => {
+                    new_encephalon
+                        .reserved_actuators
+                        .borrow_mut()
+                        .push(ReservedSlot::new(name, cluster));
+                    continue;
+                }
+            };
+
+            new_encephalon.actuator_locs.borrow_mut().insert(
+                name.clone(),
+                cluster.iter().map(|neuron| neuron.loc()).collect(),
+            );
+
+            let output = self.actuator_output.get(&name).copied().unwrap_or_default();
+
+            new_encephalon.actuator_interfaces.borrow_mut().insert(
+                name,
+                ActuatorInterface::new(
+                    cluster,
+                    actuator,
+                    self.actuator_failure_threshold,
+                    output,
+                    self.isolate_user_code,
+                ),
+            );
+        }
+
+        let mut sensors: Vec<Option<Box<dyn Sensor>>> = sensors.into_iter().map(Some).collect();
+
+        for ((planned, item), hashes) in plan
+            .sensors
+            .into_iter()
+            .zip(sensor_items)
+            .zip(sensor_hashes)
+        {
+            let cluster: Vec<Rc<SensoryNeuron>> = hashes
+                .iter()
+                .filter_map(|hash| {
+                    new_encephalon
+                        .sensory_neurons
+                        .borrow()
+                        .get(hash)
+                        .map(Rc::clone)
+                })
+                .collect();
+
+            let name = planned.name.map(SensorName::from);
+
+            let (name, sensor) = match (name, item.and_then(|item| sensors[item].take())) {
+                (Some(name), Some(sensor)) => (name, sensor),
+                (name, _) => {
+                    new_encephalon
+                        .reserved_sensors
+                        .borrow_mut()
+                        .push(ReservedSlot::new(name, cluster));
+                    continue;
+                }
+            };
+
+            new_encephalon.sensor_locs.borrow_mut().insert(
+                name.clone(),
+                cluster.iter().map(|neuron| neuron.loc()).collect(),
+            );
+
+            let sampling = self.sensor_sampling.get(&name).copied().unwrap_or_default();
+
+            new_encephalon.sensory_interfaces.borrow_mut().insert(
+                name,
+                SensoryInterface::new(
+                    sensor,
+                    Rc::clone(&self.sensory_encoder),
+                    cluster,
+                    sampling,
+                    self.isolate_user_code,
+                    self.assert_sensor_range,
+                ),
+            );
+        }
+
+        new_encephalon.form_reflex_synapses(|done, total| {
+            progress.report(BuildPhase::Reflexes, done, total)
+        })?;
+        new_encephalon.import_synapses(
+            &self.initial_synapses,
+            self.synaptic_strength_generator.as_ref(),
+            |done, total| progress.report(BuildPhase::InitialSynapses, done, total),
+        )?;
+
+        Ok(new_encephalon)
+    }
+
+    /// Resolves everything about a brain that can be settled without
+    /// allocating a neuron: validating the configuration, placing
+    /// every sensor and actuator, clamping the synapse budgets and
+    /// expanding reflex templates.  Both plan and build go through
+    /// here, so a plan is always just what build would have built.
+    /// Alongside the plan, gives the index among sensor_names and
+    /// actuator_names of each planned cluster, or None if reserved.
+    /// The plan's neurons are left for plan to fill in
+    #[allow(clippy::type_complexity)]
+    fn resolve(
+        &self,
+        ecp_geometry: &dyn EcpGeometry,
+        sensor_names: &[SensorName],
+        actuator_names: &[ActuatorName],
+    ) -> Result<(BrainPlan, Vec<Option<usize>>, Vec<Option<usize>>), EywaError> {
+        let mut plastic_params = self.resolved_plastic_params();
+        let actuator_params = self.resolved_actuator_params();
+        let mut sensory_params = self.resolved_sensory_params();
+
+        for alpha in &[
+            plastic_params.alpha,
+            actuator_params.alpha,
+            sensory_params.alpha,
+        ] {
+            if !is_valid_ema_alpha(*alpha) {
+                return Err(EywaError::InvalidEmaAlpha(*alpha));
+            }
+        }
+
+        for (_, patch) in &self.region_overrides {
+            if let Some(alpha) = patch.alpha {
+                if !is_valid_ema_alpha(alpha) {
+                    return Err(EywaError::InvalidEmaAlpha(alpha));
+                }
+            }
+        }
+
+        for params in self.actuator_overrides.values() {
+            if !is_valid_ema_alpha(params.alpha) {
+                return Err(EywaError::InvalidEmaAlpha(params.alpha));
+            }
+        }
+
+        let inhibition_modes = self
+            .actuator_overrides
+            .values()
+            .map(|params| params.inhibition_mode)
+            .chain(
+                self.region_overrides
+                    .iter()
+                    .filter_map(|(_, patch)| patch.inhibition_mode),
+            )
+            .chain(vec![
+                plastic_params.inhibition_mode,
+                actuator_params.inhibition_mode,
+            ]);
+
+        for inhibition_mode in inhibition_modes {
+            if let Some(gain) = inhibition_mode.invalid_gain() {
+                return Err(EywaError::InvalidShuntingGain(gain));
+            }
+        }
+
+        if let Some(energy_model) = self.energy_model {
+            if !energy_model.is_valid() {
+                return Err(EywaError::InvalidEnergyModel(energy_model));
+            }
+        }
+
+        // Each sensor and actuator (or reserved position for one)
+        // takes up a full cluster of positions
+        let needed_sensory = (sensor_names.len() as u32 + self.reserved_sensory_positions)
+            * self.sensor_cluster_size;
+        let needed_actuator = (actuator_names.len() as u32 + self.reserved_actuator_positions)
+            * self.actuator_cluster_size;
+
+        if ecp_geometry.get_num_sensory() != needed_sensory {
+            return Err(EywaError::SensorCountMismatch {
+                expected: ecp_geometry.get_num_sensory(),
+                found: needed_sensory,
+            });
+        } else if ecp_geometry.get_num_actuator() != needed_actuator {
+            return Err(EywaError::ActuatorCountMismatch {
+                expected: ecp_geometry.get_num_actuator(),
+                found: needed_actuator,
+            });
+        }
+
+        // Every actuator group member must be a real actuator
+        // that belongs to no other group
+        let mut grouped = HashSet::new();
+
+        for group in &self.actuator_groups {
+            for name in &group.actuator_names {
+                if !actuator_names.contains(name) {
+                    return Err(EywaError::UnknownGroupMember {
+                        group: group.name.clone(),
+                        name: name.to_string(),
+                    });
+                } else if !grouped.insert(name) {
+                    return Err(EywaError::DuplicateGroupMember {
+                        group: group.name.clone(),
+                        name: name.to_string(),
+                    });
+                }
+            }
+        }
+
+        for pair in &self.antagonist_pairs {
+            for name in &[&pair.agonist, &pair.antagonist] {
+                if !actuator_names.contains(name) {
+                    return Err(EywaError::UnknownGroupMember {
+                        group: pair.name.clone(),
+                        name: name.to_string(),
+                    });
+                }
+            }
+        }
+
+        for comparator in &self.comparators {
+            if !actuator_names.contains(&comparator.actuator_name) {
+                return Err(EywaError::UnknownName(comparator.actuator_name.to_string()));
+            }
+        }
+
+        for actuator_name in self
+            .actuator_output
+            .keys()
+            .chain(self.actuator_overrides.keys())
+        {
+            if !actuator_names.contains(actuator_name) {
+                return Err(EywaError::UnknownName(actuator_name.to_string()));
+            }
+        }
+
+        for sensor_name in self.sensor_sampling.keys() {
+            if !sensor_names.contains(sensor_name) {
+                return Err(EywaError::UnknownName(sensor_name.to_string()));
+            }
+        }
+
+        // Assign every sensor and actuator the locations of the
+        // neurons in its cluster, honoring the layouts
+        let sensor_slots = with_reserved_slots(
+            (0..sensor_names.len()).collect::<Vec<usize>>(),
+            |&index| sensor_names[index].clone(),
+            &self.sensor_layout,
+            self.reserved_sensory_positions,
+        )?;
+
+        let actuator_slots = with_reserved_slots(
+            (0..actuator_names.len()).collect::<Vec<usize>>(),
+            |&index| actuator_names[index].clone(),
+            &self.actuator_layout,
+            self.reserved_actuator_positions,
+        )?;
+
+        let sensor_clusters = assign_clusters(
+            sensor_slots,
+            &self.sensor_layout,
+            self.sensor_cluster_size,
+            ecp_geometry.sensory_plane_dims(),
+            |x, y| ecp_geometry.sensory_plane_at(x, y),
+        )?;
+
+        let actuator_clusters = assign_clusters(
+            actuator_slots,
+            &self.actuator_layout,
+            self.actuator_cluster_size,
+            ecp_geometry.actuator_plane_dims(),
+            |x, y| ecp_geometry.actuator_plane_at(x, y),
+        )?;
+
+        // Synapses beyond the number of distinct neighbors could only
+        // ever double up on a target, so the budget has to fit
+        let max_neighbors = ecp_geometry.max_neighbors() as usize;
+
+        // The default budget just fills whatever neighborhood there is
+        if self.max_plastic_synapses.is_none() {
+            if self.plastic_params.is_none() {
+                plastic_params.max_plastic_synapses =
+                    plastic_params.max_plastic_synapses.min(max_neighbors);
+            }
+
+            if self.sensory_params.is_none() {
+                sensory_params.max_plastic_synapses =
+                    sensory_params.max_plastic_synapses.min(max_neighbors);
+            }
+        }
+
+        let mut plastic_model = self.plastic_model;
+        let mut region_overrides = self.region_overrides.clone();
+        let mut budgets = vec![
+            &mut plastic_params.max_plastic_synapses,
+            &mut sensory_params.max_plastic_synapses,
+        ];
+
+        if let Some(PlasticModel::Ema(params)) = &mut plastic_model {
+            budgets.push(&mut params.max_plastic_synapses);
+        }
+
+        for (_, patch) in &mut region_overrides {
+            if let Some(budget) = &mut patch.max_plastic_synapses {
+                budgets.push(budget);
+            }
+        }
+
+        let requested_budget = budgets.iter().map(|budget| **budget).max().unwrap_or(0);
+        let mut synapse_budget_clamp = None;
+
+        if requested_budget > max_neighbors {
+            if !self.clamp_synapse_budget {
+                return Err(EywaError::SynapseBudgetExceedsNeighborhood {
+                    max_plastic_synapses: requested_budget,
+                    max_neighbors: max_neighbors as u32,
+                });
+            }
+
+            for budget in budgets {
+                *budget = (*budget).min(max_neighbors);
+            }
+
+            synapse_budget_clamp = Some((requested_budget, max_neighbors));
+        }
+
+        let plastic_model = plastic_model.unwrap_or(PlasticModel::Ema(plastic_params));
+
+        let mut reflexes = self.reflexes.clone();
+
+        for template in &self.reflex_templates {
+            reflexes.extend(template.clone().expand());
+        }
+
+        // A brain may go without sensors (driven only by injected
+        // charge) or without actuators, but then can't have reflexes
+        let onto_actuator = |reflex: &Reflex| matches!(reflex.target, ReflexTarget::Actuator(_));
+
+        if needed_sensory == 0 && !reflexes.is_empty() {
+            return Err(EywaError::ReflexesWithoutSensors);
+        } else if needed_actuator == 0 && reflexes.iter().any(onto_actuator) {
+            return Err(EywaError::ReflexesWithoutActuators);
+        }
+
+        let (sensor_items, sensors) = sensor_clusters
+            .into_iter()
+            .map(|(slot, locs)| {
+                let cluster = ClusterPlan {
+                    name: slot.name.map(|name| name.to_string()),
+                    reserved: slot.item.is_none(),
+                    locs: locs.into_iter().map(Vec::from).collect(),
+                    actuator_params: None,
+                };
+
+                (slot.item, cluster)
+            })
+            .unzip();
+
+        // Actuator neurons take the parameters of the actuator they
+        // drive, and reserved positions the parameters of every other
+        let (actuator_items, actuators): (_, Vec<ClusterPlan>) = actuator_clusters
+            .into_iter()
+            .map(|(slot, locs)| {
+                let params = slot
+                    .name
+                    .as_ref()
+                    .and_then(|name| self.actuator_overrides.get(name))
+                    .copied()
+                    .unwrap_or(actuator_params);
+
+                let cluster = ClusterPlan {
+                    name: slot.name.map(|name| name.to_string()),
+                    reserved: slot.item.is_none(),
+                    locs: locs.into_iter().map(Vec::from).collect(),
+                    actuator_params: Some(params),
+                };
+
+                (slot.item, cluster)
+            })
+            .unzip();
+
+        let warnings = reflex_threshold_warnings(&reflexes, &actuators);
+
+        let plan = BrainPlan {
+            size_report: SizeReport {
+                synapse_budget_clamp,
+                ..ecp_geometry.describe()
+            },
+            plastic_model,
+            plastic_params,
+            sensory_params,
+            region_overrides,
+            sensors,
+            actuators,
+            reflexes,
+            neurons: Vec::new(),
+            warnings,
+        };
+
+        Ok((plan, sensor_items, actuator_items))
+    }
+}
+
+/// The full seed a ChaCha20Rng would be seeded with, which
+/// lets the builder keep hold of it for snapshots
+struct RngSeed([u8; 32]);
+
+impl SeedableRng for RngSeed {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: [u8; 32]) -> RngSeed {
+        RngSeed(seed)
+    }
+}
+
+/// Warns of each reflex that can't fire its actuator's neurons on
+/// its own because it sits right at their exclusive fire threshold
+fn reflex_threshold_warnings(reflexes: &[Reflex], actuators: &[ClusterPlan]) -> Vec<PlanWarning> {
+    reflexes
+        .iter()
+        .filter_map(|reflex| {
+            let actuator_name = match &reflex.target {
+                ReflexTarget::Actuator(actuator_name) => actuator_name.to_string(),
+                ReflexTarget::Sensor(_) => return None,
+            };
+
+            let params = actuators
+                .iter()
+                .find(|cluster| cluster.name.as_ref() == Some(&actuator_name))
+                .and_then(|cluster| cluster.actuator_params)?;
+
+            let tolerance = REFLEX_THRESHOLD_TOLERANCE * params.fire_threshold.abs().max(1.);
+            let at_threshold = (reflex.strength - params.fire_threshold).abs() <= tolerance;
+
+            match params.threshold_mode == ThresholdMode::Exclusive && at_threshold {
+                true => Some(PlanWarning::ReflexAtThreshold {
+                    reflex: reflex.clone(),
+                    fire_threshold: params.fire_threshold,
+                }),
+                false => None,
+            }
+        })
+        .collect()
+}
+
+/// A geometry that hands out a location of the wrong dimension
+/// is broken, as no neuron there would ever find a neighbor
+fn assert_traversal_dims(loc: Loc, dims: usize) {
+    assert_eq!(
+        loc.dims(),
+        dims,
+        "The geometry traversed to {}, but its locations have {} dimensions",
+        loc,
+        dims
+    );
+}
+
+/// Assigns each item (sensor or actuator) a cluster of cluster_size
+/// neurons, returning each item with the locations in its cluster.
+///
+/// A cluster is a contiguous strip of positions running along the x
+/// axis of the plane.  Items placed by the layout have their strip
+/// start at their layout coordinate.  The rest are assigned, in the
+/// order they are popped off of items, to the first free strips
+/// found scanning the plane in row order.  A placed strip that runs
+/// off of the plane or onto a claimed position errors with the
+/// coordinate of the position that did
+fn assign_clusters<T, N: Eq + Hash + Display>(
+    items: Vec<Slot<T, N>>,
+    layout: &super::PlaneLayout<N>,
+    cluster_size: u32,
+    plane_dims: (u32, u32),
+    plane_loc: impl Fn(u32, u32) -> Option<Loc>,
+) -> Result<Vec<Cluster<T, N>>, EywaError> {
+    let names: Vec<&N> = items.iter().filter_map(|item| item.name.as_ref()).collect();
+
+    for (name, _) in layout.iter() {
+        if !names.contains(&name) {
+            return Err(EywaError::UnknownName(name.to_string()));
+        }
+    }
+
+    let mut claimed = HashSet::new();
+    let mut assigned = Vec::new();
+    let mut unplaced = Vec::new();
+
+    for item in items {
+        let placement = item.name.as_ref().and_then(|name| layout.get(name));
+
+        if let Some((x, y)) = placement {
+            let name = item.name_or_reserved();
+            let mut cluster = Vec::new();
+
+            for i in 0..cluster_size {
+                let loc = match plane_loc(x + i, y) {
+                    Some(loc) => loc,
+                    None => {
+                        return Err(EywaError::PlacementOutOfBounds {
+                            name: name.to_string(),
+                            coord: (x + i, y),
+                        })
+                    }
+                };
+
+                if !claimed.insert((x + i, y)) {
+                    return Err(EywaError::DuplicatePlacement {
+                        name: name.to_string(),
+                        coord: (x + i, y),
+                    });
+                }
+
+                cluster.push(loc);
+            }
+
+            assigned.push((item, cluster));
+        } else {
+            unplaced.push(item);
+        }
+    }
+
+    let (width, height) = plane_dims;
+    let (mut x, mut y) = (0, 0);
+
+    while let Some(item) = unplaced.pop() {
+        loop {
+            if x + cluster_size > width {
+                x = 0;
+                y += 1;
+            }
+
+            if y >= height {
+                return Err(EywaError::NoRoomForCluster(item.name_or_reserved()));
+            }
+
+            let locs: Vec<Option<Loc>> = (0..cluster_size)
+                .map(|i| match claimed.contains(&(x + i, y)) {
+                    true => None,
+                    false => plane_loc(x + i, y),
+                })
+                .collect();
+
+            if locs.iter().all(Option::is_some) {
+                for i in 0..cluster_size {
+                    claimed.insert((x + i, y));
+                }
+
+                assigned.push((item, locs.into_iter().flatten().collect()));
+                x += cluster_size;
+                break;
+            }
+
+            x += 1;
+        }
+    }
+
+    Ok(assigned)
+}
+
+/// A sensor or actuator to find a cluster of positions for, or
+/// a position reserved for one to be attached after building
+struct Slot<T, N> {
+    name: Option<N>, //None for reserved positions that aren't held for a name
+    item: Option<T>, //None for reserved positions
+}
+
+impl<T, N: Display> Slot<T, N> {
+    fn name_or_reserved(&self) -> String {
+        match &self.name {
+            Some(name) => name.to_string(),
+            None => "(reserved)".to_string(),
+        }
+    }
+}
+
+/// A slot and the positions of the cluster it was assigned
+type Cluster<T, N> = (Slot<T, N>, Vec<Loc>);
+
+/// Makes a slot of every reserved position, and then of every
+/// item.  Names that the layout places but that aren't among
+/// the items hold reserved positions, so errors if there are
+/// more of them than positions reserved.  Unplaced slots are
+/// assigned from the back, so the reserved positions come after
+/// the items' and don't shift the items around
+fn with_reserved_slots<T, N: Eq + Hash + Clone + Display>(
+    items: Vec<T>,
+    get_name: impl Fn(&T) -> N,
+    layout: &super::PlaneLayout<N>,
+    reserved: u32,
+) -> Result<Vec<Slot<T, N>>, EywaError> {
+    let names: Vec<N> = items.iter().map(&get_name).collect();
+
+    let held: Vec<N> = layout
+        .iter()
+        .map(|(name, _)| name)
+        .filter(|name| !names.contains(name))
+        .cloned()
+        .collect();
+
+    if let Some(name) = held.get(reserved as usize) {
+        return Err(EywaError::UnknownName(name.to_string()));
+    }
+
+    let unheld = reserved as usize - held.len();
+    let mut slots: Vec<Slot<T, N>> = (0..unheld)
+        .map(|_| Slot {
+            name: None,
+            item: None,
+        })
+        .collect();
+
+    slots.extend(held.into_iter().map(|name| Slot {
+        name: Some(name),
+        item: None,
+    }));
+    slots.extend(items.into_iter().zip(names).map(|(item, name)| Slot {
+        name: Some(name),
+        item: Some(item),
+    }));
+
+    Ok(slots)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecp_geometry::{
+        BoxEcp, CustomEcp, EcpGeometry, EcpSpec, LayerConnectivity, LayeredEcp, NodeSpec,
+    };
+    use crate::encephalon::ActuatorLayout;
+    use crate::neuron::synapse::{
+        Staleness, StrengthThreshold, SynapseInfo, SynapseRecord, SynapticType,
+    };
+    use crate::neuron::{IzhParams, NeuronKind};
+    use crate::neuron_interfaces::{ActuatorOutputMode, GroupMode};
+    use crate::probe::{ProbeId, ProbeReading};
+    use crate::test_util::{
+        actuator, fired_trace, recording_actuator, reflex, sensor, settable_sensor,
+    };
+    use std::cell::Cell;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn sensor_layout_places_sensors_on_the_sensory_plane() {
+        let build = |layout: SensorLayout| {
+            EncephalonBuilder::new().sensor_layout(layout).build(
+                Box::new(BoxEcp::new(125, 3, 1, 27)),
+                vec![
+                    sensor("left", 0.),
+                    sensor("middle", 0.),
+                    sensor("right", 0.),
+                ],
+                vec![actuator("motor")],
+            )
+        };
+
+        let layout = SensorLayout::new()
+            .place("left", 0, 0)
+            .place("middle", 2, 3)
+            .place("right", 4, 0);
+        let encephalon = build(layout).unwrap();
+
+        for (name, loc) in &[
+            ("left", [0, 0, -1]),
+            ("middle", [2, 3, -1]),
+            ("right", [4, 0, -1]),
+        ] {
+            assert_eq!(
+                encephalon.sensor_loc(&SensorName::from(*name)),
+                Some(Loc::new(loc))
+            );
+        }
+
+        assert!(matches!(
+            build(SensorLayout::new().place("right", 5, 0)).err(),
+            Some(EywaError::PlacementOutOfBounds { name, coord: (5, 0) }) if name == "right"
+        ));
+
+        let duplicate = build(SensorLayout::new().place("left", 1, 1).place("right", 1, 1));
+
+        assert!(matches!(
+            duplicate.err(),
+            Some(EywaError::DuplicatePlacement { coord: (1, 1), .. })
+        ));
+    }
+
+    #[test]
+    fn actuator_layout_swaps_packed_actuators() {
+        let build = |layout: ActuatorLayout| {
+            EncephalonBuilder::new().actuator_layout(layout).build(
+                Box::new(BoxEcp::new(125, 1, 2, 27)),
+                vec![sensor("light", 0.)],
+                vec![actuator("left"), actuator("right")],
+            )
+        };
+
+        let encephalon = build(ActuatorLayout::new().place("right", 0, 0)).unwrap();
+
+        assert_eq!(
+            encephalon.actuator_loc(&ActuatorName::from("right")),
+            Some(Loc::new(&[0, 0, 4]))
+        );
+
+        // Actuators left out of the layout take the first free position
+        assert_eq!(
+            encephalon.actuator_loc(&ActuatorName::from("left")),
+            Some(Loc::new(&[1, 0, 4]))
+        );
+
+        // Actuators fill the plane in row order, so only its
+        // first two positions hold one
+        assert!(matches!(
+            build(ActuatorLayout::new().place("left", 3, 4)).err(),
+            Some(EywaError::PlacementOutOfBounds { coord: (3, 4), .. })
+        ));
+    }
+
+    #[test]
+    fn sensor_clusters_are_contiguous_strips() {
+        let encephalon = EncephalonBuilder::new()
+            .sensor_cluster_size(3)
+            .build(
+                Box::new(BoxEcp::new(125, 6, 1, 27)),
+                vec![sensor("left", 0.5), sensor("right", 0.5)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        for name in &["left", "right"] {
+            let locs = encephalon
+                .sensor_cluster_locs(&SensorName::from(*name))
+                .unwrap();
+
+            assert_eq!(locs.len(), 3);
+
+            for pair in locs.windows(2) {
+                assert_eq!(pair[1][0], pair[0][0] + 1);
+                assert_eq!(pair[1][1..], pair[0][1..]);
+            }
+        }
+
+        let mismatched = EncephalonBuilder::new().sensor_cluster_size(3).build(
+            Box::new(BoxEcp::new(125, 2, 1, 27)),
+            vec![sensor("left", 0.5), sensor("right", 0.5)],
+            vec![actuator("motor")],
+        );
+
+        assert!(matches!(
+            mismatched.err(),
+            Some(EywaError::SensorCountMismatch {
+                expected: 2,
+                found: 6,
+            })
+        ));
+    }
+
+    #[test]
+    fn actuator_clusters_smooth_the_output() {
+        // Every actuator neuron is driven to fire once every 5 cycles,
+        // but the neurons of the cluster fire out of phase
+        let output_variance = |cluster_size: u32| {
+            let (motor, outputs) = recording_actuator("motor");
+            let encephalon = EncephalonBuilder::new()
+                .actuator_cluster_size(cluster_size)
+                .build(
+                    Box::new(BoxEcp::new(125, 1, cluster_size, 27)),
+                    vec![sensor("light", 0.)],
+                    vec![motor],
+                )
+                .unwrap();
+
+            let locs = encephalon
+                .actuator_cluster_locs(&ActuatorName::from("motor"))
+                .unwrap();
+
+            assert_eq!(locs.len(), cluster_size as usize);
+
+            for cycle in 0..300 {
+                for (i, loc) in locs.iter().enumerate() {
+                    if (cycle + i) % 5 == 0 {
+                        encephalon.inject_charge(loc, 100.).unwrap();
+                    }
+                }
+
+                encephalon.run_cycle();
+            }
+
+            // Skip the cycles the EMAs spend warming up
+            let outputs = &outputs.borrow()[200..];
+            let mean = outputs.iter().sum::<f32>() / outputs.len() as f32;
+
+            outputs
+                .iter()
+                .map(|output| (output - mean).powi(2))
+                .sum::<f32>()
+                / outputs.len() as f32
+        };
+
+        let single = output_variance(1);
+
+        assert!(single > 0.);
+        assert!(output_variance(5) < single / 4.);
+    }
+
+    #[test]
+    fn a_sensor_cluster_delivers_a_reflex_in_full() {
+        let encephalon = EncephalonBuilder::new()
+            .sensor_cluster_size(3)
+            .max_plastic_synapses(0)
+            .reflexes(vec![reflex("light", "motor", 20.)])
+            .build(
+                Box::new(BoxEcp::new(125, 3, 1, 27)),
+                vec![sensor("light", 0.5)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let motor = encephalon
+            .actuator_loc(&ActuatorName::from("motor"))
+            .unwrap();
+        let probe = encephalon.add_probe("motor", motor.into()).unwrap();
+
+        encephalon.run_n_cycles(60);
+
+        // The cluster fires all at once, so the motor only
+        // ever takes in the reflex's whole strength
+        let charges: Vec<f32> = encephalon
+            .probe_trace(probe)
+            .unwrap()
+            .iter()
+            .filter_map(|sample| match sample.reading {
+                ProbeReading::Charge(charge) if charge > 0. => Some(charge),
+                _ => None,
+            })
+            .collect();
+
+        assert!(!charges.is_empty());
+        assert!(charges.iter().all(|charge| (charge - 20.).abs() < 1e-4));
+    }
+
+    #[test]
+    fn placed_clusters_report_the_position_that_conflicted() {
+        let build = |layout: SensorLayout| {
+            EncephalonBuilder::new()
+                .sensor_cluster_size(3)
+                .sensor_layout(layout)
+                .build(
+                    Box::new(BoxEcp::new(125, 6, 1, 27)),
+                    vec![sensor("left", 0.5), sensor("right", 0.5)],
+                    vec![actuator("motor")],
+                )
+        };
+
+        // A strip from (3, 0) runs off of the 5 wide plane at (5, 0)
+        assert!(matches!(
+            build(SensorLayout::new().place("left", 3, 0)).err(),
+            Some(EywaError::PlacementOutOfBounds { name, coord: (5, 0) }) if name == "left"
+        ));
+
+        // A strip from (0, 1) runs into the left strip at (2, 1)
+        let overlapping = build(SensorLayout::new().place("left", 2, 1).place("right", 0, 1));
+
+        assert!(matches!(
+            overlapping.err(),
+            Some(EywaError::DuplicatePlacement { name, coord: (2, 1) }) if name == "right"
+        ));
+    }
+
+    #[test]
+    fn one_crossing_fires_a_full_burst() {
+        let fired = |burst_length: u32| {
+            let encephalon = EncephalonBuilder::new()
+                .burst_length(burst_length)
+                .build(
+                    Box::new(BoxEcp::new(125, 1, 1, 27)),
+                    vec![sensor("light", 0.)],
+                    vec![actuator("motor")],
+                )
+                .unwrap();
+
+            let source = encephalon.add_probe("source", vec![2, 2, 2]).unwrap();
+
+            encephalon.run_n_cycles(2);
+            encephalon.inject_charge(&[2, 2, 2], 100.).unwrap();
+            encephalon.run_n_cycles(8);
+
+            fired_trace(&encephalon, source)
+        };
+
+        assert_eq!(
+            fired(3),
+            [false, false, true, true, true, false, false, false, false, false]
+        );
+        assert_eq!(fired(1).iter().filter(|fired| **fired).count(), 1);
+    }
+
+    #[test]
+    fn adaptation_lengthens_intervals_under_constant_drive() {
+        let intervals = |builder: EncephalonBuilder| {
+            let encephalon = builder
+                .build(
+                    Box::new(BoxEcp::new(125, 1, 1, 27)),
+                    vec![sensor("light", 0.)],
+                    vec![actuator("motor")],
+                )
+                .unwrap();
+
+            let probe = encephalon.add_probe("center", vec![2, 2, 2]).unwrap();
+
+            for _ in 0..600 {
+                encephalon.inject_charge(&[2, 2, 2], 15.).unwrap();
+                encephalon.run_cycle();
+            }
+
+            let spikes: Vec<u64> = encephalon
+                .probe_trace(probe)
+                .unwrap()
+                .iter()
+                .filter(|sample| sample.fired)
+                .map(|sample| sample.cycle)
+                .collect();
+
+            spikes
+                .windows(2)
+                .map(|pair| pair[1] - pair[0])
+                .collect::<Vec<u64>>()
+        };
+
+        let adapting = intervals(EncephalonBuilder::new().adaptation(0.5, 0.97));
+        let settled = &adapting[adapting.len() - 20..];
+
+        assert!(adapting[..5].iter().all(|interval| *interval == 1));
+        assert!(settled
+            .iter()
+            .all(|interval| *interval == 3 || *interval == 4));
+
+        // A neuron that doesn't adapt fires at the same rate throughout
+        let constant = intervals(EncephalonBuilder::new());
+
+        assert!(constant.iter().all(|interval| *interval == 1));
+    }
+
+    #[test]
+    fn izhikevich_neurons_fire_on_injected_pulses() {
+        let encephalon = EncephalonBuilder::new()
+            .plastic_model(PlasticModel::Izhikevich(IzhParams::regular_spiking()))
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let center = encephalon.add_probe("center", vec![2, 2, 2]).unwrap();
+
+        // Enough current to fire an Izhikevich neuron outright
+        for cycle in 0..100 {
+            if cycle % 10 == 0 {
+                encephalon.inject_charge(&[2, 2, 2], 200.).unwrap();
+            }
+
+            encephalon.run_cycle();
+        }
+
+        let fire_count = fired_trace(&encephalon, center)
+            .iter()
+            .filter(|fired| **fired)
+            .count();
+
+        assert!(fire_count >= 8);
+    }
+
+    #[test]
+    fn a_multi_sensor_is_measured_once_per_cycle() {
+        struct Camera {
+            measurements: Rc<Cell<u32>>,
+        }
+
+        impl MultiSensor for Camera {
+            fn measure(&mut self) -> Vec<f32> {
+                self.measurements.set(self.measurements.get() + 1);
+                vec![0.2, 0.4, 0.6, 0.8]
+            }
+
+            fn channel_names(&self) -> Vec<String> {
+                (0..4).map(|channel| format!("pixel {}", channel)).collect()
+            }
+        }
+
+        let measurements = Rc::new(Cell::new(0));
+        let encephalon = EncephalonBuilder::new()
+            .multi_sensor(Box::new(Camera {
+                measurements: Rc::clone(&measurements),
+            }))
+            .build(
+                Box::new(BoxEcp::new(125, 4, 1, 27)),
+                vec![],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        encephalon.run_n_cycles(50);
+
+        assert_eq!(measurements.get(), 50);
+        assert!(encephalon
+            .sensor_loc(&SensorName::from("pixel 3"))
+            .is_some());
+    }
+
+    #[test]
+    fn an_argmax_group_sends_one_to_its_highest_ema_member() {
+        let names = ["left", "straight", "right"];
+        let (actuators, sent): (Vec<_>, Vec<_>) =
+            names.iter().map(|name| recording_actuator(name)).unzip();
+
+        let encephalon = EncephalonBuilder::new()
+            .reflexes(vec![reflex("light", "right", 20.)])
+            .actuator_group(ActuatorGroup::new(
+                "heading".to_string(),
+                names.iter().map(|&name| name.into()).collect(),
+                GroupMode::Argmax,
+            ))
+            .build(
+                Box::new(BoxEcp::new(125, 1, 3, 27)),
+                vec![sensor("light", 1.)],
+                actuators,
+            )
+            .unwrap();
+
+        let probes: Vec<ProbeId> = names
+            .iter()
+            .map(|name| {
+                let loc = encephalon.actuator_loc(&ActuatorName::from(*name)).unwrap();
+                encephalon.add_probe(name, loc.into()).unwrap()
+            })
+            .collect();
+
+        encephalon.run_n_cycles(100);
+
+        // Actuators are sent the emas their neurons held after the
+        // cycle before
+        let traces: Vec<_> = probes
+            .iter()
+            .map(|probe| encephalon.probe_trace(*probe).unwrap())
+            .collect();
+
+        for cycle in 1..100 {
+            let sent: Vec<f32> = sent.iter().map(|sent| sent.borrow()[cycle]).collect();
+            let emas: Vec<f32> = traces.iter().map(|trace| trace[cycle - 1].ema).collect();
+
+            let winners: Vec<usize> = (0..3).filter(|&i| sent[i] == 1.0).collect();
+            assert_eq!(winners.len(), 1);
+            assert!(sent.iter().all(|&value| value == 1.0 || value == 0.0));
+            assert!(emas.iter().all(|&ema| ema <= emas[winners[0]]));
+        }
+    }
+
+    #[test]
+    fn a_floored_neuron_fires_after_heavy_inhibition() {
+        let fires_after_inhibition = |charge_floor: f32| {
+            let encephalon = EncephalonBuilder::new()
+                .charge_floor(charge_floor)
+                .build(
+                    Box::new(BoxEcp::new(125, 1, 1, 27)),
+                    vec![sensor("light", 0.)],
+                    vec![actuator("motor")],
+                )
+                .unwrap();
+
+            let motor = encephalon
+                .actuator_loc(&ActuatorName::from("motor"))
+                .unwrap();
+            let probe = encephalon.add_probe("motor", motor.into()).unwrap();
+
+            encephalon.inject_charge(&motor, -1000.).unwrap();
+            encephalon.inject_charge(&motor, 12.).unwrap();
+            encephalon.run_cycle();
+
+            fired_trace(&encephalon, probe)[0]
+        };
+
+        assert!(fires_after_inhibition(0.0));
+        assert!(!fires_after_inhibition(f32::NEG_INFINITY));
+    }
+
+    /// The motor's EMA and how often the plastic neuron at the center
+    /// fired, when both are driven hard for 50 cycles
+    fn drive_past_actuator(actuator_params: ActuatorParams) -> (f32, usize) {
+        let encephalon = EncephalonBuilder::new()
+            .actuator_params(actuator_params)
+            .reflexes(vec![reflex("light", "motor", 20.)])
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 1.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let motor = encephalon
+            .actuator_loc(&ActuatorName::from("motor"))
+            .unwrap();
+        let motor_probe = encephalon.add_probe("motor", motor.into()).unwrap();
+        let probe = encephalon.add_probe("center", vec![2, 2, 2]).unwrap();
+
+        for _ in 0..50 {
+            encephalon.inject_charge(&[2, 2, 2], 100.).unwrap();
+            encephalon.run_cycle();
+        }
+
+        let plastic_fires = fired_trace(&encephalon, probe)
+            .into_iter()
+            .filter(|fired| *fired)
+            .count();
+        let motor_trace = encephalon.probe_trace(motor_probe).unwrap();
+
+        (motor_trace.last().unwrap().ema, plastic_fires)
+    }
+
+    #[test]
+    fn a_huge_actuator_threshold_silences_only_actuators() {
+        let (motor_ema, plastic_fires) = drive_past_actuator(ActuatorParams::default());
+        assert!(motor_ema > 0.);
+        assert_eq!(plastic_fires, 50);
+
+        let (motor_ema, plastic_fires) = drive_past_actuator(ActuatorParams {
+            fire_threshold: 1e9,
+            ..ActuatorParams::default()
+        });
+        assert_eq!(motor_ema, 0.);
+        assert_eq!(plastic_fires, 50);
+    }
+
+    /// The mean number of synapses along the shortest path from each
+    /// sensor to each actuator it can reach, on either side of a box
+    /// of side 9
+    fn mean_path_length(small_world_p: f32) -> f32 {
+        let encephalon = EncephalonBuilder::new()
+            .seed(13)
+            .small_world_p(small_world_p)
+            .build(
+                Box::new(BoxEcp::new(750, 4, 4, 27)),
+                (0..4).map(|i| sensor(&format!("s{}", i), 1.0)).collect(),
+                (0..4).map(|i| actuator(&format!("a{}", i))).collect(),
+            )
+            .unwrap();
+
+        encephalon.run_n_cycles(2000);
+
+        let mut targets: HashMap<Vec<i32>, Vec<Vec<i32>>> = HashMap::new();
+
+        for record in encephalon.connectome() {
+            targets
+                .entry(record.source)
+                .or_default()
+                .push(record.target);
+        }
+
+        let mut lengths = Vec::new();
+
+        for s in 0..4 {
+            let start: Vec<i32> = encephalon
+                .sensor_loc(&format!("s{}", s).into())
+                .unwrap()
+                .into();
+            let mut distances = HashMap::new();
+            let mut queue = VecDeque::new();
+
+            distances.insert(start.clone(), 0);
+            queue.push_back(start);
+
+            while let Some(loc) = queue.pop_front() {
+                let distance = distances[&loc];
+
+                for target in targets.get(&loc).into_iter().flatten() {
+                    if !distances.contains_key(target) {
+                        distances.insert(target.clone(), distance + 1);
+                        queue.push_back(target.clone());
+                    }
+                }
+            }
+
+            for a in 0..4 {
+                let end: Vec<i32> = encephalon
+                    .actuator_loc(&format!("a{}", a).into())
+                    .unwrap()
+                    .into();
+
+                if let Some(distance) = distances.get(&end) {
+                    lengths.push(*distance as f32);
+                }
+            }
+        }
+
+        assert!(!lengths.is_empty());
+        lengths.iter().sum::<f32>() / lengths.len() as f32
+    }
+
+    #[test]
+    fn shortcuts_bring_actuators_closer_to_sensors() {
+        let local = mean_path_length(0.);
+        let small_world = mean_path_length(0.05);
+
+        // Local synapses climb at most one plane at a time, but for
+        // the first, whose nearby cube is pushed inside the box
+        assert!(local >= 7.);
+        assert!(small_world < 0.75 * local);
+    }
+
+    /// Nine lights driving a brain with the
+    /// given fraction of interneurons
+    fn sprinkled_brain(interneuron_fraction: f32) -> Rc<Encephalon> {
+        EncephalonBuilder::new()
+            .seed(19)
+            .fire_threshold(20.)
+            .polarity_policy(PolarityPolicy::AlwaysExcitatory)
+            .interneuron_fraction(interneuron_fraction)
+            .build(
+                Box::new(BoxEcp::new(1000, 9, 1, 27)),
+                (0..9)
+                    .map(|i| sensor(&format!("light {}", i), 1.0))
+                    .collect(),
+                vec![actuator("motor")],
+            )
+            .unwrap()
+    }
+
+    fn kind_count(encephalon: &Encephalon, kind: NeuronKind) -> usize {
+        let field = encephalon.ema_field();
+        let locs = field.iter().map(|(loc, _)| loc);
+
+        locs.filter(|loc| encephalon.neuron_kind_at(loc) == Ok(Some(kind)))
+            .count()
+    }
+
+    #[test]
+    fn interneurons_fill_their_fraction() {
+        let sprinkled = sprinkled_brain(0.2);
+        let interneurons = kind_count(&sprinkled, NeuronKind::Interneuron) as f32;
+        let plastic = kind_count(&sprinkled, NeuronKind::Plastic) as f32;
+
+        assert!((interneurons / (interneurons + plastic) - 0.2).abs() < 0.05);
+    }
+
+    /// The strength of every plastic synapse formed on the first
+    /// cycle, when each neuron forms its first synapse, as a
+    /// fraction of the default sigmoid's max value of 15
+    fn initial_strengths(strength_init: StrengthInit) -> Vec<f32> {
+        let encephalon = EncephalonBuilder::new()
+            .seed(23)
+            .strength_init(strength_init)
+            .build(
+                Box::new(BoxEcp::new(1000, 1, 1, 27)),
+                vec![sensor("silent", 0.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        encephalon.run_cycle();
+
+        let strengths: Vec<f32> = encephalon
+            .connectome()
+            .into_iter()
+            .filter(|record| record.plastic)
+            .map(|record| record.strength / 15.)
+            .collect();
+
+        assert!(strengths.len() > 500);
+        strengths
+    }
+
+    #[test]
+    fn uniformly_started_synapses_span_the_sigmoid_between() {
+        let sigmoid = |x: f32| 1. / (1. + (-x).exp());
+
+        let uniform = initial_strengths(StrengthInit::UniformRandom {
+            min_x: -2.,
+            max_x: 2.,
+        });
+        let lowest = uniform.iter().copied().fold(f32::INFINITY, f32::min);
+        let highest = uniform.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+        assert!(lowest >= sigmoid(-2.) - 1e-4 && lowest < sigmoid(-1.5));
+        assert!(highest <= sigmoid(2.) + 1e-4 && highest > sigmoid(1.5));
+    }
+
+    #[test]
+    fn by_default_new_synapses_start_at_the_midpoint() {
+        let default = initial_strengths(StrengthInit::Default);
+        assert!(default.iter().all(|strength| (strength - 0.5).abs() < 1e-4));
+    }
+
+    #[test]
+    fn comparator_rewards_sum_into_the_global_reward() {
+        let steady = || TargetComparator::constant("motor".into(), 0.2, 0.3);
+        let wobble = || TargetComparator::replay("motor".into(), 0.5, vec![0.0, 1.0]).weight(0.5);
+
+        // The motor is sent its EMA every cycle
+        let (motor, sent) = recording_actuator("motor");
+        let encephalon = EncephalonBuilder::new()
+            .reflexes(vec![reflex("light", "motor", 20.)])
+            .target_comparator(steady())
+            .target_comparator(wobble())
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 1.0)],
+                vec![motor],
+            )
+            .unwrap();
+
+        let (steady, wobble) = (steady(), wobble());
+
+        for _ in 0..100 {
+            let metrics = encephalon.run_cycle();
+            let ema = *sent.borrow().last().unwrap();
+            let errors = [
+                steady.error(metrics.cycle, ema),
+                wobble.error(metrics.cycle, ema),
+            ];
+
+            assert_eq!(
+                metrics.comparator_errors,
+                vec![("motor".into(), errors[0]), ("motor".into(), errors[1])]
+            );
+            assert_eq!(
+                metrics.reward,
+                steady.reward(errors[0]) + wobble.reward(errors[1])
+            );
+            assert!((-1.5..=1.5).contains(&metrics.reward));
+        }
+    }
+
+    /// Records the longest idle stretch among the synapses
+    /// its policy keeps
+    struct LongestKept {
+        policy: Box<dyn PrunePolicy>,
+        longest: Rc<Cell<u64>>,
+    }
+
+    impl PrunePolicy for LongestKept {
+        fn should_prune(&self, info: &SynapseInfo) -> bool {
+            let prune = self.policy.should_prune(info);
+            if !prune {
+                self.longest.set(self.longest.get().max(info.idle_cycles()));
+            }
+
+            prune
+        }
+    }
+
+    /// The longest a synapse is kept idle while a light
+    /// is lit for 200 cycles and then silent for 200
+    fn longest_idle_after_silence(prune_policy: Box<dyn PrunePolicy>) -> u64 {
+        let (light, brightness) = settable_sensor("light", 1.0);
+        let longest = Rc::new(Cell::new(0));
+        let encephalon = EncephalonBuilder::new()
+            .seed(41)
+            .sensory_encoder(|measurement: f32| (measurement > 0.5) as u32)
+            .prune_policy(LongestKept {
+                policy: prune_policy,
+                longest: Rc::clone(&longest),
+            })
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![light],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        encephalon.run_n_cycles(200);
+        brightness.set(0.0);
+        encephalon.run_n_cycles(200);
+
+        longest.get()
+    }
+
+    #[test]
+    fn staleness_dissolves_the_synapses_of_a_silenced_sensor() {
+        // The light's synapses weaken too slowly to fall below
+        // the threshold, so they sit idle through the silence
+        assert!(longest_idle_after_silence(Box::new(StrengthThreshold)) >= 150);
+        assert!(
+            longest_idle_after_silence(Box::new(Staleness {
+                max_idle_cycles: 50
+            })) <= 50
+        );
+    }
+
+    /// Counts its measurements, and measures something new each time
+    struct Counting(Rc<Cell<u32>>);
+
+    impl Sensor for Counting {
+        fn measure(&mut self) -> f32 {
+            self.0.set(self.0.get() + 1);
+            (self.0.get() % 5) as f32 / 4.
+        }
+
+        fn get_name(&self) -> String {
+            "counting".to_string()
+        }
+    }
+
+    #[test]
+    fn sampled_sensors_hold_their_period_between_samples() {
+        let measured = Rc::new(Cell::new(0));
+        let encephalon = EncephalonBuilder::new()
+            .sensor_sampling(
+                "counting".into(),
+                SensorSampling {
+                    sample_divisor: 10,
+                    ..SensorSampling::default()
+                },
+            )
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![Box::new(Counting(Rc::clone(&measured)))],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let sensor_loc = encephalon.sensor_loc(&"counting".into()).unwrap();
+        let probe = encephalon.add_probe("sensor", sensor_loc.into()).unwrap();
+
+        let sampled: u32 = (0..100)
+            .map(|_| encephalon.run_cycle().sensors_sampled)
+            .sum();
+
+        assert_eq!(measured.get(), 10);
+        assert_eq!(sampled, 10);
+
+        // Cycles are counted from 1, so samples are taken on cycles 1, 11, 21...
+        let interfaces = encephalon.sensory_interfaces.borrow();
+        let counting = &interfaces["counting"];
+        assert_eq!(counting.last_sample_cycle(), Some(91));
+
+        let trace = encephalon.probe_trace(probe).unwrap();
+
+        for held in trace.chunks(10) {
+            assert!(held.iter().all(|sample| sample.reading == held[0].reading));
+        }
+        assert_ne!(trace[0].reading, trace[10].reading);
+    }
+
+    fn build_lit(builder: EncephalonBuilder) -> Result<Rc<Encephalon>, EywaError> {
+        builder.build(
+            Box::new(BoxEcp::new(125, 1, 1, 27)),
+            vec![sensor("light", 1.0)],
+            vec![actuator("motor")],
+        )
+    }
+
+    #[test]
+    fn synapse_budgets_have_to_fit_the_neighborhood() {
+        // A nearby cube of 27 holds 26 neighbors
+        assert!(matches!(
+            build_lit(EncephalonBuilder::new().max_plastic_synapses(64)),
+            Err(EywaError::SynapseBudgetExceedsNeighborhood {
+                max_plastic_synapses: 64,
+                max_neighbors: 26,
+            })
+        ));
+
+        let fitting = build_lit(EncephalonBuilder::new().max_plastic_synapses(26)).unwrap();
+        assert_eq!(fitting.size_report().synapse_budget_clamp, None);
+    }
+
+    #[test]
+    fn clamped_synapse_budgets_are_reported_and_kept() {
+        let clamped = build_lit(
+            EncephalonBuilder::new()
+                .max_plastic_synapses(64)
+                .clamp_synapse_budget(true),
+        )
+        .unwrap();
+
+        assert_eq!(clamped.size_report().synapse_budget_clamp, Some((64, 26)));
+        assert!(!clamped.size_report().is_exact());
+
+        clamped.run_n_cycles(100);
+
+        let mut out_degrees = HashMap::new();
+        for record in clamped.connectome().iter().filter(|record| record.plastic) {
+            *out_degrees.entry(record.source.clone()).or_insert(0) += 1;
+        }
+
+        assert_eq!(out_degrees.values().max(), Some(&26));
+    }
+
+    fn threshold_patch(fire_threshold: f32) -> PlasticParamsPatch {
+        PlasticParamsPatch {
+            fire_threshold: Some(fire_threshold),
+            ..PlasticParamsPatch::default()
+        }
+    }
+
+    #[test]
+    fn a_band_of_high_thresholds_falls_silent_under_uniform_drive() {
+        // The middle plane of the 5x5x5 box, short of one corner row
+        let encephalon = EncephalonBuilder::new()
+            .max_plastic_synapses(0)
+            .region_override(
+                Region::new(vec![0, 0, 2], vec![4, 4, 2]),
+                threshold_patch(1000.),
+            )
+            .region_override(
+                Region::new(vec![0, 0, 2], vec![0, 4, 2]),
+                threshold_patch(5.),
+            )
+            .build(
+                Box::new(BoxEcp::new(125, 1, 0, 27)),
+                vec![sensor("light", 0.0)],
+                vec![],
+            )
+            .unwrap();
+
+        let fire_threshold =
+            |loc: &[i32]| encephalon.params_at(loc).unwrap().unwrap().fire_threshold;
+
+        // Where regions overlap, the override added last wins
+        assert_eq!(fire_threshold(&[2, 2, 2]), 1000.);
+        assert_eq!(fire_threshold(&[0, 2, 2]), 5.);
+        assert_eq!(fire_threshold(&[2, 2, 1]), 10.);
+        assert_eq!(encephalon.params_at(&[0, 0, -1]), Ok(None));
+
+        for _ in 0..100 {
+            for x in 0..5 {
+                for y in 0..5 {
+                    for z in 0..5 {
+                        encephalon.inject_charge(&[x, y, z], 15.).unwrap();
+                    }
+                }
+            }
+
+            encephalon.run_cycle();
+        }
+
+        for (loc, ema) in encephalon.ema_field() {
+            let loc = loc.as_slice();
+
+            if loc[2] < 0 {
+                continue;
+            }
+
+            match loc[2] == 2 && loc[0] > 0 {
+                true => assert_eq!(ema, 0., "{:?} should be silent", loc),
+                false => assert!(ema > 0.3, "{:?} should be active", loc),
+            }
+        }
+    }
+
+    #[test]
+    fn region_inhibition_is_checked_like_the_builders_own() {
+        let shunting = PlasticParamsPatch {
+            inhibition_mode: Some(InhibitionMode::Shunting { gain: -1. }),
+            ..PlasticParamsPatch::default()
+        };
+        let refused = EncephalonBuilder::new()
+            .region_override(Region::new(vec![0, 0, 0], vec![4, 4, 4]), shunting)
+            .build(Box::new(BoxEcp::new(125, 0, 0, 27)), vec![], vec![]);
+
+        assert_eq!(refused.err(), Some(EywaError::InvalidShuntingGain(-1.)));
+    }
+
+    #[test]
+    fn alphas_outside_of_zero_to_one_are_refused() {
+        let build = |builder: EncephalonBuilder| {
+            builder
+                .build(
+                    Box::new(BoxEcp::new(125, 1, 1, 27)),
+                    vec![sensor("light", 0.)],
+                    vec![actuator("motor")],
+                )
+                .err()
+        };
+
+        for alpha in &[1.5, -0.1, 0.0] {
+            let invalid = Some(EywaError::InvalidEmaAlpha(*alpha));
+
+            assert_eq!(build(EncephalonBuilder::new().ema_alpha(*alpha)), invalid);
+
+            let patch = PlasticParamsPatch {
+                alpha: Some(*alpha),
+                ..PlasticParamsPatch::default()
+            };
+            let region = Region::new(vec![0, 0, 0], vec![4, 4, 4]);
+
+            assert_eq!(
+                build(EncephalonBuilder::new().region_override(region, patch)),
+                invalid
+            );
+
+            let params = ActuatorParams {
+                alpha: *alpha,
+                ..ActuatorParams::default()
+            };
+
+            assert_eq!(
+                build(EncephalonBuilder::new().actuator_override("motor".into(), params)),
+                invalid
+            );
+        }
+
+        assert_eq!(build(EncephalonBuilder::new().ema_alpha(1.)), None);
+    }
+
+    #[test]
+    fn an_actuator_with_a_larger_alpha_rises_faster() {
+        let (gripper, gripper_values) = recording_actuator("gripper");
+        let (motor, motor_values) = recording_actuator("motor");
+        let alpha = |alpha: f32| ActuatorParams {
+            alpha,
+            ..ActuatorParams::default()
+        };
+
+        let encephalon = EncephalonBuilder::new()
+            .max_plastic_synapses(26)
+            .reflexes(vec![
+                reflex("light", "gripper", 20.),
+                reflex("light", "motor", 20.),
+            ])
+            .actuator_override("gripper".into(), alpha(0.2))
+            .actuator_override("motor".into(), alpha(0.02))
+            .build(
+                Box::new(BoxEcp::new(125, 1, 2, 27)),
+                vec![sensor("light", 1.0)],
+                vec![gripper, motor],
+            )
+            .unwrap();
+
+        // Driven identically
+        encephalon.run_n_cycles(12);
+
+        let rise_time = |values: &[f32]| values.iter().position(|value| *value > 0.5);
+
+        assert!(rise_time(&gripper_values.borrow()).is_some());
+        assert!(rise_time(&motor_values.borrow()).is_none());
+    }
+
+    /// Panics on every 10th measurement
+    struct Flaky {
+        calls: u32,
+    }
+
+    impl Sensor for Flaky {
+        fn measure(&mut self) -> f32 {
+            self.calls += 1;
+
+            if self.calls.is_multiple_of(10) {
+                panic!("sensor glitched");
+            }
+
+            0.5
+        }
+
+        fn get_name(&self) -> String {
+            "flaky".to_string()
+        }
+    }
+
+    #[test]
+    fn sensor_panics_are_counted_without_stopping_cycles() {
+        let encephalon = EncephalonBuilder::new()
+            .isolate_user_code(true)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![Box::new(Flaky { calls: 0 })],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let panics: u32 = (0..100).map(|_| encephalon.run_cycle().sensor_panics).sum();
+
+        assert_eq!(panics, 10);
+        assert_eq!(encephalon.get_cycle_count(), 100);
+        assert_eq!(encephalon.health_report().sensors["flaky"].panics, 10);
+    }
+
+    /// What a sensor measuring 3 is encoded as on the first
+    /// cycle, and the range violations that cycle counted
+    fn encoded_hot_measurement(auto_normalize: bool) -> (f32, u32) {
+        let encoded = Rc::new(Cell::new(0.0));
+        let seen = Rc::clone(&encoded);
+
+        let encephalon = EncephalonBuilder::new()
+            .auto_normalize(auto_normalize)
+            .sensory_encoder(move |measurement: f32| {
+                seen.set(measurement);
+                1
+            })
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("hot", 3.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let violations = encephalon.run_cycle().sensor_range_violations;
+        (encoded.get(), violations)
+    }
+
+    #[test]
+    fn auto_normalize_clamps_before_encoding() {
+        assert_eq!(encoded_hot_measurement(true), (1.0, 0));
+        assert_eq!(encoded_hot_measurement(false), (3.0, 1));
+    }
+
+    fn pulsed_brain(specs: Vec<SynapseSpec>) -> Result<Rc<Encephalon>, EywaError> {
+        EncephalonBuilder::new()
+            .seed(9)
+            .initial_synapses(specs)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![Box::new(Counting(Rc::new(Cell::new(0))))],
+                vec![actuator("motor")],
+            )
+    }
+
+    #[test]
+    fn an_exported_connectome_seeds_another_brain() {
+        let trained = pulsed_brain(Vec::new()).unwrap();
+        trained.run_n_cycles(200);
+
+        let exported = trained.connectome();
+        assert!(!exported.is_empty());
+
+        let specs = exported.iter().cloned().map(SynapseSpec::from).collect();
+        let imported = pulsed_brain(specs).unwrap().connectome();
+
+        for original in &exported {
+            assert!(imported.iter().any(|copy| {
+                copy.source == original.source
+                    && copy.target == original.target
+                    && copy.synaptic_type == original.synaptic_type
+                    && copy.plastic == original.plastic
+                    && (copy.strength - original.strength).abs() < 1e-4
+            }));
+        }
+    }
+
+    #[test]
+    fn initial_synapses_from_unknown_locs_fail_the_build() {
+        let bogus = SynapseSpec {
+            src_loc: vec![100, 100, 100],
+            dst_loc: vec![2, 2, 2],
+            synaptic_type: SynapticType::Excitatory,
+            initial_strength: 1.,
+            plastic: true,
+        };
+
+        assert!(matches!(
+            pulsed_brain(vec![bogus]),
+            Err(EywaError::UnknownLoc(_))
+        ));
+    }
+
+    #[test]
+    fn a_brain_without_sensors_is_driven_by_injected_charge() {
+        let free_running = EncephalonBuilder::new()
+            .seed(7)
+            .build(
+                Box::new(BoxEcp::new(125, 0, 1, 27)),
+                vec![],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        for _ in 0..200 {
+            free_running.inject_region(&[2, 2, 2], 1, 30.).unwrap();
+            free_running.run_cycle();
+        }
+
+        assert!(free_running.plastic_synapse_count() > 0);
+    }
+
+    #[test]
+    fn a_brain_without_actuators_runs_for_analysis() {
+        let analysis_only = EncephalonBuilder::new()
+            .build(Box::new(BoxEcp::new(125, 0, 0, 27)), vec![], vec![])
+            .unwrap();
+
+        analysis_only.run_n_cycles(1000);
+
+        assert_eq!(analysis_only.get_cycle_count(), 1000);
+    }
+
+    #[test]
+    fn reflexes_are_refused_without_sensors() {
+        let refused = EncephalonBuilder::new()
+            .reflexes(vec![reflex("light", "motor", 20.)])
+            .build(Box::new(BoxEcp::new(125, 0, 0, 27)), vec![], vec![]);
+
+        assert_eq!(refused.err(), Some(EywaError::ReflexesWithoutSensors));
+    }
+
+    /// The strength after 200 cycles of a plastic synapse from a sensory
+    /// neuron onto a target its relay chain drives three cycles later
+    fn relayed_pairing_strength(pairing_window: u32) -> f32 {
+        let spec = |src: Vec<i32>, dst: Vec<i32>, strength, plastic| SynapseSpec {
+            src_loc: src,
+            dst_loc: dst,
+            synaptic_type: SynapticType::Excitatory,
+            initial_strength: strength,
+            plastic,
+        };
+
+        let encephalon = EncephalonBuilder::new()
+            .seed(3)
+            .max_plastic_synapses(0)
+            .sensory_encoder(|_| 10)
+            .pairing_window(pairing_window)
+            .initial_synapses(vec![
+                spec(vec![0, 0, -1], vec![2, 0, 0], 2., true),
+                spec(vec![0, 0, -1], vec![0, 0, 0], 50., false),
+                spec(vec![0, 0, 0], vec![1, 0, 0], 50., false),
+                spec(vec![1, 0, 0], vec![2, 0, 0], 50., false),
+            ])
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 1.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        encephalon.run_n_cycles(200);
+
+        encephalon
+            .connectome()
+            .into_iter()
+            .find(|record| record.plastic && record.target == vec![2, 0, 0])
+            .map_or(0.0, |record| record.strength)
+    }
+
+    #[test]
+    fn only_a_wide_enough_window_pairs_a_relayed_target() {
+        assert!(relayed_pairing_strength(3) > 2.);
+        assert!(relayed_pairing_strength(1) < 2.);
+    }
+
+    #[test]
+    fn the_default_pairing_window_is_one() {
+        let connectome = |builder: EncephalonBuilder| {
+            let encephalon = builder
+                .seed(3)
+                .build(
+                    Box::new(BoxEcp::new(125, 1, 1, 27)),
+                    vec![sensor("light", 1.0)],
+                    vec![actuator("motor")],
+                )
+                .unwrap();
+
+            encephalon.run_n_cycles(200);
+            encephalon.connectome()
+        };
+
+        assert_eq!(
+            connectome(EncephalonBuilder::new()),
+            connectome(EncephalonBuilder::new().pairing_window(1))
+        );
+    }
+
+    #[test]
+    fn sensory_synapses_kept_off_actuators_never_reach_them() {
+        let motors: Vec<String> = (0..9).map(|i| format!("motor {}", i)).collect();
+
+        let encephalon = EncephalonBuilder::new()
+            .seed(5)
+            .max_plastic_synapses(26)
+            .small_world_p(0.5)
+            .targeting_rules(TargetingRules {
+                sensory_to_actuator: false,
+                ..TargetingRules::default()
+            })
+            .build(
+                Box::new(BoxEcp::new(27, 1, 9, 27)),
+                vec![sensor("light", 1.0)],
+                motors.iter().map(|name| actuator(name)).collect(),
+            )
+            .unwrap();
+
+        encephalon.run_n_cycles(2000);
+
+        let light_loc = Vec::from(encephalon.sensor_loc(&"light".into()).unwrap());
+        let actuator_locs: Vec<Vec<i32>> = motors
+            .iter()
+            .map(|name| Vec::from(encephalon.actuator_loc(&name.as_str().into()).unwrap()))
+            .collect();
+
+        let from_light: Vec<SynapseRecord> = encephalon
+            .connectome()
+            .into_iter()
+            .filter(|record| record.plastic && record.source == light_loc)
+            .collect();
+
+        assert!(!from_light.is_empty());
+        assert!(from_light
+            .iter()
+            .all(|record| !actuator_locs.contains(&record.target)));
+    }
+
+    #[test]
+    fn a_plan_resolves_the_brain_build_constructs() {
+        let builder = || {
+            EncephalonBuilder::new()
+                .seed(5)
+                .region_override(
+                    Region::new(vec![0, 0, 0], vec![1, 1, 1]),
+                    PlasticParamsPatch {
+                        fire_threshold: Some(20.),
+                        ..Default::default()
+                    },
+                )
+                .reflexes(vec![reflex("light", "motor", 20.)])
+        };
+
+        let plan = builder()
+            .plan(
+                &BoxEcp::new(125, 1, 1, 27),
+                &["light".to_string()],
+                &["motor".to_string()],
+            )
+            .unwrap();
+
+        let encephalon = builder()
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 1.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let report = encephalon.size_report();
+
+        assert_eq!(plan.size_report, report);
+
+        // The size report counts actuator neurons among the plastic ones
+        assert_eq!(
+            plan.count(NeuronKind::Plastic) + plan.count(NeuronKind::Actuator),
+            report.actual_plastic as usize
+        );
+
+        // While sensory neurons fill the whole 5 by 5 sensory plane
+        assert_eq!(plan.count(NeuronKind::Sensory), 25);
+
+        for neuron in &plan.neurons {
+            assert_eq!(
+                encephalon.neuron_kind_at(&neuron.loc),
+                Ok(Some(neuron.kind))
+            );
+            assert_eq!(encephalon.params_at(&neuron.loc), Ok(neuron.params));
+        }
+
+        let light: Vec<Vec<i32>> = encephalon
+            .sensor_cluster_locs(&"light".into())
+            .unwrap()
+            .into_iter()
+            .map(Vec::from)
+            .collect();
+
+        assert_eq!(plan.sensors[0].locs, light);
+    }
+
+    #[test]
+    fn plans_round_trip_through_json() {
+        let plan = EncephalonBuilder::new()
+            .plan(
+                &BoxEcp::new(125, 1, 1, 27),
+                &["light".to_string()],
+                &["motor".to_string()],
+            )
+            .unwrap();
+
+        let json = serde_json::to_string(&plan).unwrap();
+        assert_eq!(serde_json::from_str::<BrainPlan>(&json).unwrap(), plan);
+    }
+
+    const ENERGY_MODEL: EnergyModel = EnergyModel {
+        fire_cost: 1.,
+        synapse_cost: 0.01,
+        regen_per_cycle: 10.,
+        capacity: 200.,
+    };
+
+    /// The mean rx neurons fired per cycle over the
+    /// second half of 600 cycles of saturating drive
+    fn mean_saturated_firing(energy_model: Option<EnergyModel>) -> f32 {
+        let mut builder = EncephalonBuilder::new().seed(3);
+
+        if let Some(energy_model) = energy_model {
+            builder = builder.energy_model(energy_model);
+        }
+
+        let encephalon = builder
+            .build(
+                Box::new(BoxEcp::new(125, 0, 1, 27)),
+                vec![],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let mut fired = 0;
+
+        for cycle in 0..600 {
+            encephalon.inject_region(&[2, 2, 2], 4, 1000.).unwrap();
+            let metrics = encephalon.run_cycle();
+
+            if cycle >= 300 {
+                fired += metrics.rx_fired;
+            }
+        }
+
+        fired as f32 / 300.
+    }
+
+    #[test]
+    fn firing_settles_at_what_regeneration_pays_for() {
+        // Actuator neurons aren't fatigued, so one fires every cycle
+        assert!(mean_saturated_firing(None) > 100.);
+        assert!(mean_saturated_firing(Some(ENERGY_MODEL)) < 1. + 10. * 1.5);
+    }
+
+    #[test]
+    fn an_energy_store_that_never_regenerates_is_refused() {
+        let stagnant = EnergyModel {
+            regen_per_cycle: 0.,
+            ..ENERGY_MODEL
+        };
+        let refused = EncephalonBuilder::new().energy_model(stagnant).build(
+            Box::new(BoxEcp::new(125, 0, 0, 27)),
+            vec![],
+            vec![],
+        );
+
+        assert_eq!(refused.err(), Some(EywaError::InvalidEnergyModel(stagnant)));
+    }
+
+    /// Builds ecp_geometry with one sensor, one actuator per
+    /// actuator neuron, and a reflex, checking each phase counts
+    /// up to its total in the order of totals
+    fn assert_build_reports(ecp_geometry: Box<dyn EcpGeometry>, totals: &[(BuildPhase, u32)]) {
+        let reports = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&reports);
+        let actuators = (0..ecp_geometry.get_num_actuator())
+            .map(|i| actuator(&format!("motor {}", i)))
+            .collect();
+
+        EncephalonBuilder::new()
+            .reflexes(vec![reflex("light", "motor 0", 20.)])
+            .progress(move |phase, done, total| recorded.borrow_mut().push((phase, done, total)))
+            .build(ecp_geometry, vec![sensor("light", 0.5)], actuators)
+            .unwrap();
+
+        for (phase, total) in totals.iter() {
+            let done: Vec<u32> = reports
+                .borrow()
+                .iter()
+                .filter(|(reported, _, reported_total)| {
+                    assert!(reported != phase || reported_total == total);
+                    reported == phase
+                })
+                .map(|(_, done, _)| *done)
+                .collect();
+
+            assert!(done.windows(2).all(|pair| pair[0] < pair[1]));
+            assert_eq!(done.first(), Some(&0));
+            assert_eq!(done.last(), Some(total));
+        }
+    }
+
+    #[test]
+    fn a_large_build_reports_its_way_through_every_neuron() {
+        let ecp_geometry = BoxEcp::new(8000, 1, 1, 27);
+        let (sensory_width, sensory_height) = ecp_geometry.sensory_plane_dims();
+        let totals = [
+            // The box counts its actuator neurons as plastic
+            (
+                BuildPhase::PlasticNeurons,
+                ecp_geometry.get_num_plastic() - ecp_geometry.get_num_actuator(),
+            ),
+            (BuildPhase::ActuatorNeurons, ecp_geometry.get_num_actuator()),
+            (BuildPhase::SensoryNeurons, sensory_width * sensory_height),
+            (BuildPhase::Reflexes, 1),
+            (BuildPhase::InitialSynapses, 0),
+        ];
+
+        assert_build_reports(Box::new(ecp_geometry), &totals);
+    }
+
+    #[test]
+    fn geometries_with_separate_actuator_counts_report_every_neuron() {
+        let node = |id: &str, x: i32, z: i32| NodeSpec {
+            id: id.to_string(),
+            loc: vec![x, 0, z],
+            nearby: vec![],
+        };
+        let custom = CustomEcp::from_spec(EcpSpec {
+            sensory: vec![node("s", 0, -1)],
+            plastic: vec![node("p", 0, 0)],
+            actuator: vec![node("a", 0, 1), node("b", 1, 1)],
+        })
+        .unwrap();
+
+        assert_build_reports(
+            Box::new(custom),
+            &[
+                (BuildPhase::PlasticNeurons, 1),
+                (BuildPhase::ActuatorNeurons, 2),
+                (BuildPhase::SensoryNeurons, 1),
+            ],
+        );
+
+        let layered = LayeredEcp::new(vec![(4, 4), (3, 3)], 1, 2, LayerConnectivity::default());
+
+        assert_build_reports(
+            Box::new(layered),
+            &[
+                (BuildPhase::PlasticNeurons, 16 + 9),
+                (BuildPhase::ActuatorNeurons, 2),
+                (BuildPhase::SensoryNeurons, 16),
+            ],
+        );
+    }
+
+    /// Whether a reflex exactly at the fire threshold
+    /// fires its actuator within the first few cycles
+    fn threshold_reflex_fires(threshold_mode: ThresholdMode) -> bool {
+        let encephalon = EncephalonBuilder::new()
+            .fire_threshold(10.)
+            .threshold_mode(threshold_mode)
+            .reflexes(vec![reflex("light", "motor", 10.)])
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 1.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        // Few enough cycles that no plastic path reaches the actuator
+        encephalon.run_n_cycles(3);
+
+        encephalon.actuator_ema("motor").unwrap() > 0.
+    }
+
+    #[test]
+    fn only_inclusive_thresholds_fire_on_reaching_them() {
+        assert!(!threshold_reflex_fires(ThresholdMode::Exclusive));
+        assert!(threshold_reflex_fires(ThresholdMode::Inclusive));
+    }
+
+    /// Whether a reflex of 20 less an inhibitory one of 5 fires
+    /// its actuator within the first few cycles
+    fn inhibited_reflex_fires(inhibition_mode: InhibitionMode) -> bool {
+        let encephalon = EncephalonBuilder::new()
+            .fire_threshold(10.)
+            .inhibition_mode(inhibition_mode)
+            .reflexes(vec![
+                reflex("go", "motor", 20.),
+                Reflex::new("stop".into(), "motor".into(), SynapticType::Inhibitory, 5.),
+            ])
+            .build(
+                Box::new(BoxEcp::new(125, 2, 1, 27)),
+                vec![sensor("go", 1.0), sensor("stop", 1.0)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        // Few enough cycles that no plastic path reaches the actuator
+        encephalon.run_n_cycles(3);
+
+        encephalon.actuator_ema("motor").unwrap() > 0.
+    }
+
+    #[test]
+    fn shunting_lets_weak_inhibition_silence_strong_excitation() {
+        // 20 - 5 fires, but 20 / (1 + 5) doesn't
+        assert!(inhibited_reflex_fires(InhibitionMode::Subtractive));
+        assert!(!inhibited_reflex_fires(InhibitionMode::Shunting {
+            gain: 1.
+        }));
+    }
+
+    #[test]
+    fn shunting_gains_must_be_finite_and_non_negative() {
+        let build = |gain: f32| {
+            EncephalonBuilder::new()
+                .inhibition_mode(InhibitionMode::Shunting { gain })
+                .build(Box::new(BoxEcp::new(125, 0, 0, 27)), vec![], vec![])
+                .err()
+        };
+
+        assert_eq!(build(0.5), None);
+        assert_eq!(build(-1.), Some(EywaError::InvalidShuntingGain(-1.)));
+        assert!(matches!(
+            build(f32::NAN),
+            Some(EywaError::InvalidShuntingGain(_))
+        ));
+        assert!(matches!(
+            build(f32::INFINITY),
+            Some(EywaError::InvalidShuntingGain(_))
+        ));
+    }
+
+    /// The cycles between a light being sampled and
+    /// its reflex's spiking actuator being sent a fire
+    fn reflex_latency(legacy_phase_order: bool) -> u32 {
+        let (motor, sent) = recording_actuator("motor");
+
+        let encephalon = EncephalonBuilder::new()
+            .legacy_phase_order(legacy_phase_order)
+            .injected_sensors(vec!["light"])
+            .sensory_encoder(|measurement: f32| (measurement > 0.5) as u32)
+            .reflexes(vec![reflex("light", "motor", 20.)])
+            .actuator_output(
+                "motor".into(),
+                ActuatorOutput {
+                    mode: ActuatorOutputMode::Spike,
+                    ..ActuatorOutput::default()
+                },
+            )
+            .build(Box::new(BoxEcp::new(125, 1, 1, 27)), vec![], vec![motor])
+            .unwrap();
+
+        let mut frame = HashMap::new();
+        frame.insert("light".to_string(), 1.0);
+        encephalon.set_sensor_values(&frame).unwrap();
+
+        // The light is sampled on the first cycle
+        let cycles = (1..10)
+            .find(|_| {
+                encephalon.run_cycle();
+                sent.borrow().last() == Some(&1.0)
+            })
+            .unwrap();
+
+        cycles - 1
+    }
+
+    #[test]
+    fn the_legacy_phase_order_lags_actuators_by_a_cycle() {
+        assert_eq!(reflex_latency(false), 1);
+        assert_eq!(reflex_latency(true), 2);
+    }
+}
@@ -0,0 +1,448 @@
+//! Simulated environments for testing encephalons in closed loop,
+//! without an external simulator.
+//!
+//! MazeEnv is a small, deterministic grid maze driven like the
+//! hell-mazer robot: four wall sensors, four pain sensors, and a
+//! forward and backward actuator for each of two wheels:
+//!
+//! ```
+//! use eywa::env::MazeEnv;
+//! use eywa::prelude::*;
+//!
+//! let maze = ["#####", "#S..#", "#####"].join("\n");
+//! let env = MazeEnv::new(&maze, 7).unwrap();
+//!
+//! let encephalon = EncephalonBuilder::new()
+//!     .reflexes(MazeEnv::reflexes(20.))
+//!     .build(
+//!         Box::new(BoxEcp::new(27, 8, 4, 27)),
+//!         env.sensors(),
+//!         env.actuators(),
+//!     )
+//!     .unwrap();
+//!
+//! encephalon.run_n_cycles(100);
+//! println!("{} collisions", env.collisions());
+//! ```
+
+use std::cell::RefCell;
+use std::f32::consts::PI;
+use std::rc::Rc;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+
+use crate::actuator::{Actuator, ActuatorError, ActuatorName};
+use crate::encephalon::{Reflex, ReflexTemplate};
+use crate::error::EywaError;
+use crate::neuron::synapse::SynapticType;
+use crate::sensor::{Sensor, SensorName};
+
+/// The names of the wall sensors of a maze, in the
+/// order of their directions (forward, left, right, back)
+pub const WALL_SENSORS: [&str; 4] = ["forward", "left", "right", "back"];
+
+/// The names of the pain sensors of a maze, in the same order
+pub const PAIN_SENSORS: [&str; 4] = ["forward_pain", "left_pain", "right_pain", "back_pain"];
+
+/// The names of the wheel actuators of a maze
+pub const ACTUATORS: [&str; 4] = [
+    "left_forward",
+    "left_backward",
+    "right_forward",
+    "right_backward",
+];
+
+/// Cells moved per step with both wheels fully forward
+const MAX_SPEED: f32 = 0.1;
+
+/// Radians turned per step with one wheel fully
+/// forward and the other fully backward
+const MAX_TURN: f32 = 0.2;
+
+/// The distance at which a wall sensor reads 0
+const SENSOR_RANGE: f32 = 3.0;
+
+/// How close the center of the agent can get to a wall
+const BODY_RADIUS: f32 = 0.25;
+
+/// A wall within this distance of a side of the
+/// agent touches it, which the side's pain sensor feels
+const TOUCH_DISTANCE: f32 = BODY_RADIUS + MAX_SPEED;
+
+/// The resolution that wall distances are measured at
+const RAY_STEP: f32 = 0.02;
+
+/// Headings of each sensed direction relative to the agent
+const DIRECTIONS: [f32; 4] = [0., PI / 2., -PI / 2., PI];
+
+/// Everything about the maze that its sensors
+/// and actuators share between them
+struct MazeState {
+    walls: Vec<Vec<bool>>,
+    position: (f32, f32),
+    heading: f32,
+    wheels: [f32; 4], //Control values, in the order of ACTUATORS
+    pain: [bool; 4],  //In the order of PAIN_SENSORS
+    cycles_per_step: u32,
+    cycle: u64,
+    steps: u64,
+    collisions: u64,
+    distance_traveled: f32,
+}
+
+impl MazeState {
+    /// Everything outside of the map is a wall
+    fn is_wall(&self, x: f32, y: f32) -> bool {
+        if x < 0. || y < 0. {
+            return true;
+        }
+
+        match self.walls.get(y as usize) {
+            Some(row) => row.get(x as usize).copied().unwrap_or(true),
+            None => true,
+        }
+    }
+
+    /// The distance to the nearest wall along heading,
+    /// up to the range of the wall sensors
+    fn ray(&self, heading: f32) -> f32 {
+        let (x, y) = self.position;
+        let (dx, dy) = (heading.cos(), heading.sin());
+        let mut distance = 0.;
+
+        while distance < SENSOR_RANGE {
+            if self.is_wall(x + dx * distance, y + dy * distance) {
+                return distance;
+            }
+
+            distance += RAY_STEP;
+        }
+
+        SENSOR_RANGE
+    }
+
+    /// Counts a cycle, stepping the agent once every cycles_per_step
+    fn tick(&mut self) {
+        self.cycle += 1;
+
+        if self.cycle.is_multiple_of(self.cycles_per_step as u64) {
+            self.step();
+        }
+    }
+
+    /// Turns and drives the agent by its wheels.  A move that would
+    /// bring the agent within BODY_RADIUS of a wall is a collision,
+    /// which leaves the agent where it was
+    fn step(&mut self) {
+        let [left_forward, left_backward, right_forward, right_backward] = self.wheels;
+        let left = left_forward - left_backward;
+        let right = right_forward - right_backward;
+
+        self.heading = (self.heading + (right - left) / 2. * MAX_TURN).rem_euclid(2. * PI);
+        self.steps += 1;
+
+        let speed = (left + right) / 2. * MAX_SPEED;
+        let reversing = speed < 0.;
+        let direction = match reversing {
+            true => self.heading + PI,
+            false => self.heading,
+        };
+
+        let mut collided = false;
+
+        if speed != 0. {
+            if self.ray(direction) - BODY_RADIUS < speed.abs() {
+                self.collisions += 1;
+                collided = true;
+            } else {
+                self.position.0 += direction.cos() * speed.abs();
+                self.position.1 += direction.sin() * speed.abs();
+                self.distance_traveled += speed.abs();
+            }
+        }
+
+        for (i, relative) in DIRECTIONS.iter().enumerate() {
+            self.pain[i] = self.ray(self.heading + relative) < TOUCH_DISTANCE;
+        }
+
+        if collided {
+            match reversing {
+                true => self.pain[3] = true,
+                false => self.pain[0] = true,
+            }
+        }
+    }
+}
+
+/// A grid maze that an encephalon drives an agent around, stepping
+/// the agent once per cycle (or once every cycles_per_step cycles).
+///
+/// Mazes are given as text, with a # for each wall cell, a . for
+/// each open cell, and an S for the open cell the agent starts in.
+/// Leading whitespace on each line is ignored, as are blank lines.
+/// The agent starts facing a random direction drawn from the seed,
+/// and in a random open cell if the maze has no S.
+///
+/// The maze only knows a cycle has passed when its sensors are
+/// measured, so every one of its sensors should be measured on
+/// every cycle (which they are unless their sampling is divided)
+pub struct MazeEnv {
+    state: Rc<RefCell<MazeState>>,
+}
+
+impl MazeEnv {
+    /// Errors if the maze has no open cells, if its rows are of
+    /// different lengths, or if it holds anything but #, ., or S
+    pub fn new(map: &str, seed: u64) -> Result<MazeEnv, EywaError> {
+        let mut walls: Vec<Vec<bool>> = Vec::new();
+        let mut start = None;
+        let mut open = Vec::new();
+
+        for line in map.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let y = walls.len();
+            let mut row = Vec::new();
+
+            for (x, cell) in line.chars().enumerate() {
+                match cell {
+                    '#' => row.push(true),
+                    '.' | 'S' => {
+                        if cell == 'S' {
+                            start = Some((x, y));
+                        }
+
+                        open.push((x, y));
+                        row.push(false);
+                    }
+                    _ => {
+                        return Err(EywaError::InvalidMaze(format!(
+                            "'{}' at ({}, {}) isn't a wall or an open cell",
+                            cell, x, y
+                        )))
+                    }
+                }
+            }
+
+            if let Some(first) = walls.first() {
+                if first.len() != row.len() {
+                    return Err(EywaError::InvalidMaze(format!(
+                        "row {} is {} cells long, but row 0 is {}",
+                        y,
+                        row.len(),
+                        first.len()
+                    )));
+                }
+            }
+
+            walls.push(row);
+        }
+
+        if open.is_empty() {
+            return Err(EywaError::InvalidMaze(
+                "there are no open cells".to_string(),
+            ));
+        }
+
+        let mut rng = ChaCha20Rng::seed_from_u64(seed);
+        let (x, y) = match start {
+            Some(start) => start,
+            None => open[rng.gen_range(0, open.len())],
+        };
+
+        Ok(MazeEnv {
+            state: Rc::new(RefCell::new(MazeState {
+                walls,
+                position: (x as f32 + 0.5, y as f32 + 0.5),
+                heading: rng.gen_range(0., 2. * PI),
+                wheels: [0.; 4],
+                pain: [false; 4],
+                cycles_per_step: 1,
+                cycle: 0,
+                steps: 0,
+                collisions: 0,
+                distance_traveled: 0.,
+            })),
+        })
+    }
+
+    /// Steps the agent once every cycles_per_step cycles,
+    /// rather than on every cycle
+    pub fn with_cycles_per_step(self, cycles_per_step: u32) -> MazeEnv {
+        self.state.borrow_mut().cycles_per_step = cycles_per_step.max(1);
+        self
+    }
+
+    /// The wall sensors, which read 1 against a wall and 0 once the
+    /// nearest wall is out of range, followed by the pain sensors,
+    /// which read 1 while a wall touches that side of the agent
+    pub fn sensors(&self) -> Vec<Box<dyn Sensor>> {
+        let wall_sensors = WALL_SENSORS.iter().map(|name| (name, MazeReading::Wall));
+        let pain_sensors = PAIN_SENSORS.iter().map(|name| (name, MazeReading::Pain));
+
+        wall_sensors
+            .chain(pain_sensors)
+            .enumerate()
+            .map(|(i, (name, reading))| {
+                Box::new(MazeSensor {
+                    state: Rc::clone(&self.state),
+                    name: SensorName::from(*name),
+                    direction: i % DIRECTIONS.len(),
+                    reading,
+                    last_cycle: 0,
+                }) as Box<dyn Sensor>
+            })
+            .collect()
+    }
+
+    /// The forward and backward actuators of each wheel.  A wheel
+    /// turns at the difference of its forward and backward values
+    pub fn actuators(&self) -> Vec<Box<dyn Actuator>> {
+        ACTUATORS
+            .iter()
+            .enumerate()
+            .map(|(wheel, name)| {
+                Box::new(MazeActuator {
+                    state: Rc::clone(&self.state),
+                    name: ActuatorName::from(*name),
+                    wheel,
+                }) as Box<dyn Actuator>
+            })
+            .collect()
+    }
+
+    /// The reflexes of the hell-mazer robot.  Forward pain reverses
+    /// both wheels, while pain on any other side drives each wheel
+    /// away from that side
+    pub fn reflexes(strength: f32) -> Vec<Reflex> {
+        let sensor = |name: &str| SensorName::from(name);
+        let actuator = |name: &str| ActuatorName::from(name);
+
+        ReflexTemplate::crossed_pair(
+            sensor("forward_pain"),
+            (actuator("left_forward"), actuator("left_backward")),
+            strength,
+        )
+        .and(ReflexTemplate::crossed_pair(
+            sensor("forward_pain"),
+            (actuator("right_forward"), actuator("right_backward")),
+            strength,
+        ))
+        .and(ReflexTemplate::all_pairs(
+            &[
+                sensor("left_pain"),
+                sensor("right_pain"),
+                sensor("back_pain"),
+            ],
+            &ACTUATORS
+                .iter()
+                .map(|name| actuator(name))
+                .collect::<Vec<_>>(),
+            pain_polarity,
+            strength,
+        ))
+        .expand()
+    }
+
+    /// The (x, y) position of the agent, in cells
+    pub fn agent_position(&self) -> (f32, f32) {
+        self.state.borrow().position
+    }
+
+    /// The heading of the agent, in radians counterclockwise from +x
+    pub fn heading(&self) -> f32 {
+        self.state.borrow().heading
+    }
+
+    /// The number of moves that ran the agent into a wall
+    pub fn collisions(&self) -> u64 {
+        self.state.borrow().collisions
+    }
+
+    /// The total distance the agent has moved, in cells
+    pub fn distance_traveled(&self) -> f32 {
+        self.state.borrow().distance_traveled
+    }
+
+    /// The number of times the agent has been stepped
+    pub fn steps(&self) -> u64 {
+        self.state.borrow().steps
+    }
+}
+
+/// Polarity of the reflex from a side or back pain sensor onto a
+/// wheel actuator.  Each wheel is driven forward if it's on the
+/// side in pain (or back pain), and backward otherwise
+fn pain_polarity(sensor: &str, actuator: &str) -> Option<SynapticType> {
+    let wheel_forward = match (sensor, actuator.starts_with("left")) {
+        ("left_pain", is_left) => is_left,
+        ("right_pain", is_left) => !is_left,
+        ("back_pain", _) => true,
+        _ => return None,
+    };
+
+    match actuator.ends_with("forward") == wheel_forward {
+        true => Some(SynapticType::Excitatory),
+        false => Some(SynapticType::Inhibitory),
+    }
+}
+
+#[derive(Copy, Clone)]
+enum MazeReading {
+    Wall,
+    Pain,
+}
+
+struct MazeSensor {
+    state: Rc<RefCell<MazeState>>,
+    name: SensorName,
+    direction: usize,
+    reading: MazeReading,
+    last_cycle: u64,
+}
+
+impl Sensor for MazeSensor {
+    /// The first sensor to be measured in a cycle ticks the maze.
+    /// Since each sensor is measured once per cycle, a sensor that
+    /// has already read the current cycle must be starting the next
+    fn measure(&mut self) -> f32 {
+        let mut state = self.state.borrow_mut();
+
+        if self.last_cycle == state.cycle {
+            state.tick();
+        }
+
+        self.last_cycle = state.cycle;
+
+        match self.reading {
+            MazeReading::Wall => {
+                let distance = state.ray(state.heading + DIRECTIONS[self.direction]);
+                1. - distance / SENSOR_RANGE
+            }
+            MazeReading::Pain => match state.pain[self.direction] {
+                true => 1.,
+                false => 0.,
+            },
+        }
+    }
+
+    fn get_name(&self) -> String {
+        self.name.to_string()
+    }
+}
+
+struct MazeActuator {
+    state: Rc<RefCell<MazeState>>,
+    name: ActuatorName,
+    wheel: usize,
+}
+
+impl Actuator for MazeActuator {
+    fn set_control_value(&self, value: f32) -> Result<(), ActuatorError> {
+        self.state.borrow_mut().wheels[self.wheel] = value.max(0.).min(1.);
+        Ok(())
+    }
+
+    fn get_name(&self) -> String {
+        self.name.to_string()
+    }
+}
@@ -0,0 +1,389 @@
+use std::error::Error;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::loc::MAX_DIMS;
+use crate::neuron::EnergyModel;
+
+/// All of the ways that building or operating
+/// an encephalon can go wrong.  Errors are plain data,
+/// so they can be sent over the wire to a binding:
+///
+/// ```
+/// use eywa::error::EywaError;
+///
+/// let error = EywaError::DimensionMismatch {
+///     loc: vec![1, 2],
+///     expected: 3,
+/// };
+///
+/// let json = serde_json::to_string(&error).unwrap();
+/// assert_eq!(serde_json::from_str::<EywaError>(&json).unwrap(), error);
+/// ```
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum EywaError {
+    /// The number of sensors passed to the encephalon doesn't
+    /// match the number of sensory positions of the geometry
+    SensorCountMismatch {
+        expected: u32,
+        found: u32,
+    },
+
+    /// The number of actuators passed to the encephalon doesn't
+    /// match the number of actuator positions of the geometry
+    ActuatorCountMismatch {
+        expected: u32,
+        found: u32,
+    },
+
+    /// A layout placed a sensor or actuator at a coordinate
+    /// that has no corresponding position on its plane
+    PlacementOutOfBounds {
+        name: String,
+        coord: (u32, u32),
+    },
+
+    /// Two entries of a layout were placed at the same coordinate
+    DuplicatePlacement {
+        name: String,
+        coord: (u32, u32),
+    },
+
+    /// There wasn't a free strip of positions left on the plane
+    /// large enough to hold this sensor or actuator's cluster
+    NoRoomForCluster(String),
+
+    /// Every position reserved for attaching a sensor
+    /// (or actuator) after building has been taken
+    NoReservedPosition(String),
+
+    /// A sensor (or actuator) with this name is already attached
+    DuplicateName(String),
+
+    /// The constant of an exponential moving average
+    /// lies outside of (0, 1]
+    InvalidEmaAlpha(f32),
+
+    /// The gain of shunting inhibition is negative or not finite,
+    /// so inhibition could make a neuron's charge infinite or negative
+    InvalidShuntingGain(f32),
+
+    /// The capacity or regeneration of an energy model isn't
+    /// positive and finite, so its store could never refill
+    InvalidEnergyModel(EnergyModel),
+
+    /// A paced run's rate is NaN, or so slow that the
+    /// slot of a single cycle overflows a Duration
+    InvalidCycleRate(f32),
+
+    /// There's no neuron at this location
+    UnknownLoc(Vec<i32>),
+
+    /// The neuron at this location isn't an rx neuron, so it
+    /// can't take in charge (sensory neurons have no internal charge)
+    NotAnRxNeuron(Vec<i32>),
+
+    /// The neuron at this location doesn't transmit (it's an
+    /// actuator neuron), so it can't be the source of a synapse
+    NotATxNeuron(Vec<i32>),
+
+    /// A probe with this name has already been added
+    DuplicateProbeName(String),
+
+    /// There's no probe with this id
+    UnknownProbe(usize),
+
+    /// A layout refers to a sensor or actuator name that
+    /// wasn't passed to the encephalon
+    UnknownName(String),
+
+    /// An actuator group refers to an actuator name that
+    /// wasn't passed to the encephalon
+    UnknownGroupMember {
+        group: String,
+        name: String,
+    },
+
+    /// An actuator was placed in more than one actuator group
+    /// (or more than once in the same group)
+    DuplicateGroupMember {
+        group: String,
+        name: String,
+    },
+
+    /// An excluded region of a geometry covers this
+    /// sensory or actuator position
+    ExclusionOverlapsPlane(Vec<i32>),
+
+    /// Two nodes of an ecp spec have the same id
+    DuplicateNodeId(String),
+
+    /// Two nodes of an ecp spec are at the same location
+    DuplicateNodeLoc(Vec<i32>),
+
+    /// A location (or a node of an ecp spec) has
+    /// more coordinates than a location can hold
+    TooManyDimensions(Vec<i32>),
+
+    /// A location doesn't have the number of dimensions
+    /// of the geometry (or ecp spec) it was used with
+    DimensionMismatch {
+        loc: Vec<i32>,
+        expected: usize,
+    },
+
+    /// A node of an ecp spec lists a nearby node that isn't
+    /// a plastic or actuator node of the spec
+    UnknownNeighbor {
+        node: String,
+        neighbor: String,
+    },
+
+    /// An ecp spec has no nodes of this kind
+    NoNodesOfKind(String),
+
+    /// The map of a maze environment couldn't be parsed
+    InvalidMaze(String),
+
+    /// A snapshot was written by a newer version of eywa (or
+    /// an older version without a registered migration)
+    SnapshotVersionUnsupported {
+        found: u32,
+        supported: u32,
+    },
+
+    /// A snapshot couldn't be encoded or decoded
+    SnapshotEncoding(String),
+
+    /// A brain spec couldn't be parsed
+    InvalidConfig(String),
+
+    /// Neurons may hold more plastic synapses than there are
+    /// distinct neurons nearby to target
+    SynapseBudgetExceedsNeighborhood {
+        max_plastic_synapses: usize,
+        max_neighbors: u32,
+    },
+
+    /// No reflex joins this sensor to this actuator
+    NoSuchReflex {
+        sensor: String,
+        actuator: String,
+    },
+
+    /// A reflex didn't form any synapses, as its sensor
+    /// or actuator isn't part of the encephalon
+    ReflexNotFormed {
+        sensor: String,
+        actuator: String,
+    },
+
+    /// Reflexes were given to an encephalon with no sensors,
+    /// or reflexes onto actuators to one with no actuators
+    ReflexesWithoutSensors,
+    ReflexesWithoutActuators,
+
+    /// The geometry can't spread its actuators out, so
+    /// only ActuatorSpread::Packed may be used with it
+    ActuatorSpreadUnsupported,
+
+    /// The geometry has no slice at this index along this
+    /// axis, or can't be sliced at all
+    NoSuchSlice {
+        axis: usize,
+        index: u32,
+    },
+
+    /// There's no neuron group with this name
+    UnknownGroup(String),
+
+    /// A neuron group was defined without any neurons
+    EmptyGroup(String),
+}
+
+impl fmt::Display for EywaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EywaError::SensorCountMismatch { expected, found } => write!(
+                f,
+                "The number of sensors passed to the encephalon ({}) doesn't match the \
+                 number of sensor neuron positions within the ecp_geometry ({})",
+                found, expected
+            ),
+            EywaError::ActuatorCountMismatch { expected, found } => write!(
+                f,
+                "The number of actuators passed to the encephalon ({}) doesn't match the \
+                 number of actuator neuron positions within the ecp_geometry ({})",
+                found, expected
+            ),
+            EywaError::PlacementOutOfBounds { name, coord } => write!(
+                f,
+                "\"{}\" was placed at {:?}, which isn't a valid position on its plane",
+                name, coord
+            ),
+            EywaError::DuplicatePlacement { name, coord } => write!(
+                f,
+                "\"{}\" was placed at {:?}, which is already occupied",
+                name, coord
+            ),
+            EywaError::NoRoomForCluster(name) => write!(
+                f,
+                "There's no free strip of positions left for the cluster of \"{}\"",
+                name
+            ),
+            EywaError::NoReservedPosition(name) => write!(
+                f,
+                "There's no reserved position left to attach \"{}\" to",
+                name
+            ),
+            EywaError::DuplicateName(name) => {
+                write!(f, "\"{}\" is already attached to the encephalon", name)
+            }
+            EywaError::InvalidEmaAlpha(alpha) => write!(
+                f,
+                "EMA alpha {} is outside of (0, 1], so the EMA wouldn't converge",
+                alpha
+            ),
+            EywaError::InvalidShuntingGain(gain) => write!(
+                f,
+                "Shunting inhibition gain {} isn't a finite, non-negative number",
+                gain
+            ),
+            EywaError::InvalidEnergyModel(energy_model) => write!(
+                f,
+                "Energy model capacity {} and regeneration {} per cycle \
+                 have to be positive and finite",
+                energy_model.capacity, energy_model.regen_per_cycle
+            ),
+            EywaError::InvalidCycleRate(rate) => write!(
+                f,
+                "Can't pace cycles at {} per second, since a cycle's slot \
+                 has to be a representable duration",
+                rate
+            ),
+            EywaError::UnknownLoc(loc) => write!(f, "There's no neuron at {:?}", loc),
+            EywaError::NotAnRxNeuron(loc) => write!(
+                f,
+                "The neuron at {:?} isn't an rx neuron, so it has no internal charge",
+                loc
+            ),
+            EywaError::NotATxNeuron(loc) => write!(
+                f,
+                "The neuron at {:?} doesn't transmit, so it can't be the source of a synapse",
+                loc
+            ),
+            EywaError::DuplicateProbeName(name) => {
+                write!(f, "A probe named \"{}\" already exists", name)
+            }
+            EywaError::UnknownProbe(id) => write!(f, "There's no probe with id {}", id),
+            EywaError::UnknownName(name) => write!(
+                f,
+                "\"{}\" doesn't match any sensor or actuator passed to the encephalon",
+                name
+            ),
+            EywaError::UnknownGroupMember { group, name } => write!(
+                f,
+                "Actuator group \"{}\" contains \"{}\", which doesn't match any actuator \
+                 passed to the encephalon",
+                group, name
+            ),
+            EywaError::DuplicateGroupMember { group, name } => write!(
+                f,
+                "Actuator group \"{}\" contains \"{}\", which already belongs to a group",
+                group, name
+            ),
+            EywaError::ExclusionOverlapsPlane(loc) => write!(
+                f,
+                "An excluded region covers the sensory or actuator position at {:?}",
+                loc
+            ),
+            EywaError::DuplicateNodeId(id) => {
+                write!(f, "More than one node of the ecp spec has id \"{}\"", id)
+            }
+            EywaError::DuplicateNodeLoc(loc) => {
+                write!(f, "More than one node of the ecp spec is at {:?}", loc)
+            }
+            EywaError::TooManyDimensions(loc) => write!(
+                f,
+                "The location {:?} has {} coordinates, but locations have at most {}",
+                loc,
+                loc.len(),
+                MAX_DIMS
+            ),
+            EywaError::DimensionMismatch { loc, expected } => write!(
+                f,
+                "The location {:?} has {} coordinates, but the geometry's locations have {}",
+                loc,
+                loc.len(),
+                expected
+            ),
+            EywaError::UnknownNeighbor { node, neighbor } => write!(
+                f,
+                "Node \"{}\" lists \"{}\" as nearby, which isn't a plastic or actuator \
+                 node of the ecp spec",
+                node, neighbor
+            ),
+            EywaError::NoNodesOfKind(kind) => write!(f, "The ecp spec has no {} nodes", kind),
+            EywaError::InvalidMaze(reason) => write!(f, "Invalid maze: {}", reason),
+            EywaError::SnapshotVersionUnsupported { found, supported } => write!(
+                f,
+                "The snapshot is version {}, but only versions up to {} are supported",
+                found, supported
+            ),
+            EywaError::SnapshotEncoding(message) => {
+                write!(f, "Failed to encode or decode snapshot: {}", message)
+            }
+            EywaError::InvalidConfig(message) => {
+                write!(f, "Failed to parse brain spec: {}", message)
+            }
+            EywaError::SynapseBudgetExceedsNeighborhood {
+                max_plastic_synapses,
+                max_neighbors,
+            } => write!(
+                f,
+                "Neurons may hold {} plastic synapses, but the geometry only has {} \
+                 neighbors for them to target",
+                max_plastic_synapses, max_neighbors
+            ),
+            EywaError::NoSuchReflex { sensor, actuator } => write!(
+                f,
+                "No reflex joins sensor \"{}\" to actuator \"{}\"",
+                sensor, actuator
+            ),
+            EywaError::ReflexNotFormed { sensor, actuator } => write!(
+                f,
+                "The reflex from sensor \"{}\" to actuator \"{}\" formed no synapses, \
+                 as one of them isn't part of the encephalon",
+                sensor, actuator
+            ),
+            EywaError::ReflexesWithoutSensors => write!(
+                f,
+                "Reflexes were given, but the encephalon has no sensors for them to start from"
+            ),
+            EywaError::ReflexesWithoutActuators => write!(
+                f,
+                "Reflexes onto actuators were given, but the encephalon has no actuators"
+            ),
+            EywaError::ActuatorSpreadUnsupported => write!(
+                f,
+                "The geometry can't spread out its actuators, so they have to stay packed"
+            ),
+            EywaError::NoSuchSlice { axis, index } => {
+                write!(f, "The geometry has no slice {} along axis {}", index, axis)
+            }
+            EywaError::UnknownGroup(name) => {
+                write!(f, "There's no neuron group named \"{}\"", name)
+            }
+            EywaError::EmptyGroup(name) => {
+                write!(
+                    f,
+                    "The neuron group \"{}\" doesn't contain any neurons",
+                    name
+                )
+            }
+        }
+    }
+}
+
+impl Error for EywaError {}
@@ -0,0 +1,360 @@
+//! Sweeps of brains over assignments of builder parameters
+//! and seeds, so comparing hyperparameters doesn't take a new
+//! binary per sweep
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::brain_spec::{BrainSpec, SpecBrain};
+use crate::clock::{Clock, SystemClock};
+use crate::encephalon::{Encephalon, EncephalonBuilder};
+use crate::error::EywaError;
+
+/// The builder parameters a sweep can vary.  Parameters that
+/// are counts are rounded down from the values they're swept over
+pub const SWEEPABLE: &[&str] = &[
+    "fire_threshold",
+    "ema_alpha",
+    "ema_floor",
+    "synapse_type_threshold",
+    "max_plastic_synapses",
+    "max_delay",
+    "fire_window",
+    "pairing_window",
+    "burst_length",
+    "charge_floor",
+    "target_total_strength",
+    "interneuron_fraction",
+    "small_world_p",
+];
+
+/// Sets the builder parameter called name to value
+fn set_param(
+    builder: EncephalonBuilder,
+    name: &str,
+    value: f32,
+) -> Result<EncephalonBuilder, EywaError> {
+    Ok(match name {
+        "fire_threshold" => builder.fire_threshold(value),
+        "ema_alpha" => builder.ema_alpha(value),
+        "ema_floor" => builder.ema_floor(value),
+        "synapse_type_threshold" => builder.synapse_type_threshold(value),
+        "max_plastic_synapses" => builder.max_plastic_synapses(value as usize),
+        "max_delay" => builder.max_delay(value as u32),
+        "fire_window" => builder.fire_window(value as u32),
+        "pairing_window" => builder.pairing_window(value as u32),
+        "burst_length" => builder.burst_length(value as u32),
+        "charge_floor" => builder.charge_floor(value),
+        "target_total_strength" => builder.target_total_strength(value),
+        "interneuron_fraction" => builder.interneuron_fraction(value),
+        "small_world_p" => builder.small_world_p(value),
+        _ => return Err(EywaError::UnknownName(name.to_string())),
+    })
+}
+
+/// The value of each varied parameter of a run, in the order varied
+type Assignment = Vec<(String, f32)>;
+
+/// Measures a brain once a run of a sweep is over
+type Metric = Box<dyn Fn(&Encephalon) -> f32 + Send + Sync>;
+
+/// The outcome of one run of a sweep
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunSummary {
+    /// The value of each varied parameter, in the order varied
+    pub params: Vec<(String, f32)>,
+    pub seed: Option<u64>,
+
+    /// The sweep's metric of the brain after its last cycle,
+    /// or None if the sweep has no metric
+    pub metric: Option<f32>,
+
+    /// Every synapse (plastic and static) the brain ended with
+    pub synapses: usize,
+
+    /// Time spent building and running the brain
+    pub wall_time: Duration,
+}
+
+/// A sweep of a brain over every combination of the values of
+/// its varied parameters and seeds.  Each run builds the brain of
+/// the base spec afresh, holds the sweep's sensors at their values,
+/// and measures the brain with the sweep's metric once it's run:
+///
+/// ```
+/// use eywa::brain_spec::BrainSpec;
+/// use eywa::experiment::Sweep;
+///
+/// let base = BrainSpec::from_json(r#"{
+///     "sensors": ["light"],
+///     "actuators": ["yote"],
+///     "reflexes": [{
+///         "sensor": "light",
+///         "actuator": "yote",
+///         "synapse_type": "Excitatory",
+///         "strength": 20.0
+///     }],
+///     "seed": null
+/// }"#)
+/// .unwrap();
+///
+/// let summaries = Sweep::new(base)
+///     .vary("fire_threshold", vec![5.0, 20.0])
+///     .vary_seed(0..2)
+///     .hold_sensor("light", 1.0)
+///     .metric(|encephalon| encephalon.actuator_ema("yote").unwrap())
+///     .run(100)
+///     .unwrap();
+///
+/// Sweep::write_csv(&summaries, &mut std::io::stdout()).unwrap();
+/// ```
+///
+/// With the parallel feature, the runs are spread across a thread
+/// pool as large as the machine has cores, one brain to a thread at a
+/// time, and the summaries come back in the same order as they would
+/// have run in sequence
+pub struct Sweep {
+    base: BrainSpec,
+    params: Vec<(String, Vec<f32>)>,
+    seeds: Option<Vec<u64>>,
+    held_sensors: Vec<(String, f32)>,
+    metric: Option<Metric>,
+}
+
+impl Sweep {
+    pub fn new(base: BrainSpec) -> Sweep {
+        Sweep {
+            base,
+            params: Vec::new(),
+            seeds: None,
+            held_sensors: Vec::new(),
+            metric: None,
+        }
+    }
+
+    /// Sweeps the builder parameter called name (one of
+    /// SWEEPABLE) over values.  Unknown names fail the run
+    pub fn vary(mut self, name: &str, values: Vec<f32>) -> Sweep {
+        self.params.push((name.to_string(), values));
+        self
+    }
+
+    /// Sweeps the seed over seeds, in place of the base's seed
+    pub fn vary_seed(mut self, seeds: impl IntoIterator<Item = u64>) -> Sweep {
+        self.seeds = Some(seeds.into_iter().collect());
+        self
+    }
+
+    /// Holds the sensor called name at value throughout every run
+    pub fn hold_sensor(mut self, name: &str, value: f32) -> Sweep {
+        self.held_sensors.push((name.to_string(), value));
+        self
+    }
+
+    /// Measures each brain once it has run
+    pub fn metric(mut self, metric: impl Fn(&Encephalon) -> f32 + Send + Sync + 'static) -> Sweep {
+        self.metric = Some(Box::new(metric));
+        self
+    }
+
+    /// Every assignment of the varied parameters, with the
+    /// first parameter varied slowest, paired with every seed
+    fn runs(&self) -> Vec<(Assignment, Option<u64>)> {
+        let mut assignments: Vec<Assignment> = vec![Vec::new()];
+
+        for (name, values) in &self.params {
+            assignments = assignments
+                .into_iter()
+                .flat_map(|assignment| {
+                    values.iter().map(move |&value| {
+                        let mut assignment = assignment.clone();
+                        assignment.push((name.clone(), value));
+                        assignment
+                    })
+                })
+                .collect();
+        }
+
+        let seeds = match &self.seeds {
+            Some(seeds) => seeds.iter().copied().map(Some).collect(),
+            None => vec![self.base.seed],
+        };
+
+        assignments
+            .into_iter()
+            .flat_map(|assignment| seeds.iter().map(move |&seed| (assignment.clone(), seed)))
+            .collect()
+    }
+
+    /// Builds and runs the brain of one assignment for cycles cycles
+    fn run_one(
+        &self,
+        params: Assignment,
+        seed: Option<u64>,
+        cycles: u32,
+    ) -> Result<RunSummary, EywaError> {
+        let clock = SystemClock::new();
+        let start = clock.now();
+
+        let mut builder = EncephalonBuilder::new();
+
+        for (name, value) in &params {
+            builder = set_param(builder, name, *value)?;
+        }
+
+        let spec = BrainSpec {
+            seed,
+            ..self.base.clone()
+        };
+
+        let mut brain = SpecBrain::with_builder(spec, builder)?;
+
+        for (name, value) in &self.held_sensors {
+            brain.set_sensor(name, *value)?;
+        }
+
+        brain.step(cycles);
+
+        let encephalon = brain.encephalon();
+
+        Ok(RunSummary {
+            params,
+            seed,
+            metric: self.metric.as_ref().map(|metric| metric(encephalon)),
+            synapses: encephalon.connectome().len(),
+            wall_time: clock.now() - start,
+        })
+    }
+
+    /// Runs every assignment for cycles cycles, one after another
+    #[cfg(not(feature = "parallel"))]
+    pub fn run(&self, cycles: u32) -> Result<Vec<RunSummary>, EywaError> {
+        self.runs()
+            .into_iter()
+            .map(|(params, seed)| self.run_one(params, seed, cycles))
+            .collect()
+    }
+
+    /// Runs every assignment for cycles cycles across rayon's thread
+    /// pool, so no more runs are in flight than there are cores
+    #[cfg(feature = "parallel")]
+    pub fn run(&self, cycles: u32) -> Result<Vec<RunSummary>, EywaError> {
+        use rayon::prelude::*;
+
+        self.runs()
+            .into_par_iter()
+            .map(|(params, seed)| self.run_one(params, seed, cycles))
+            .collect()
+    }
+
+    /// Writes summaries as csv, with a header row naming the
+    /// parameters of the first summary.  Every summary of a
+    /// sweep varies the same parameters, in the same order
+    pub fn write_csv(summaries: &[RunSummary], writer: &mut impl Write) -> io::Result<()> {
+        let names: Vec<&str> = summaries
+            .first()
+            .map(|summary| {
+                summary
+                    .params
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        for name in &names {
+            write!(writer, "{},", name)?;
+        }
+
+        writeln!(writer, "seed,metric,synapses,wall_time_secs")?;
+
+        for summary in summaries {
+            for (_, value) in &summary.params {
+                write!(writer, "{},", value)?;
+            }
+
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                optional_field(summary.seed),
+                optional_field(summary.metric),
+                summary.synapses,
+                summary.wall_time.as_secs_f64()
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes summaries as csv to a file at path
+    pub fn write_csv_file(summaries: &[RunSummary], path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        Sweep::write_csv(summaries, &mut writer)?;
+        writer.flush()
+    }
+}
+
+fn optional_field<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn light_reflex_sweep() -> Sweep {
+        let base = BrainSpec::from_json(
+            r#"{
+                "sensors": ["light"],
+                "actuators": ["yote"],
+                "reflexes": [{
+                    "sensor": "light",
+                    "actuator": "yote",
+                    "synapse_type": "Excitatory",
+                    "strength": 20.0
+                }],
+                "seed": null
+            }"#,
+        )
+        .unwrap();
+
+        Sweep::new(base)
+            .vary("fire_threshold", vec![5.0, 20.0])
+            .vary_seed(0..2)
+            .hold_sensor("light", 1.0)
+            .metric(|encephalon| encephalon.actuator_ema("yote").unwrap())
+    }
+
+    #[test]
+    fn a_sweep_runs_every_combination_once() {
+        let summaries = light_reflex_sweep().run(100).unwrap();
+
+        assert_eq!(summaries.len(), 4);
+
+        for (i, summary) in summaries.iter().enumerate() {
+            assert!(summary.metric.is_some());
+
+            for other in &summaries[i + 1..] {
+                assert!((&summary.params, summary.seed) != (&other.params, other.seed));
+            }
+        }
+    }
+
+    #[test]
+    fn sweep_csv_has_a_row_per_run() {
+        let summaries = light_reflex_sweep().run(100).unwrap();
+
+        let mut csv = Vec::new();
+        Sweep::write_csv(&summaries, &mut csv).unwrap();
+
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(
+            csv.lines().next(),
+            Some("fire_threshold,seed,metric,synapses,wall_time_secs")
+        );
+        assert_eq!(csv.lines().count(), 5);
+    }
+}
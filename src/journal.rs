@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+/// The kinds of synapse lifecycle events an encephalon journals
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    /// A plastic synapse was formed
+    Formation,
+
+    /// A plastic synapse was dissolved by the prune policy
+    Pruning,
+
+    /// A static reflex synapse was formed
+    Reflex,
+}
+
+impl EventKind {
+    fn label(self) -> &'static str {
+        match self {
+            EventKind::Formation => "formation",
+            EventKind::Pruning => "pruning",
+            EventKind::Reflex => "reflex",
+        }
+    }
+}
+
+/// A single synapse lifecycle event, from the
+/// neuron at src_loc onto the neuron at dst_loc
+#[derive(Clone, Debug, PartialEq)]
+pub struct EncephalonEvent {
+    pub cycle: u64,
+    pub kind: EventKind,
+    pub src_loc: Vec<i32>,
+    pub dst_loc: Vec<i32>,
+    pub strength_at_event: f32,
+}
+
+/// Keeps the most recent `capacity` events of an encephalon.
+/// A journal with no capacity records nothing
+#[derive(Debug)]
+pub struct Journal {
+    capacity: usize,
+    events: VecDeque<EncephalonEvent>,
+}
+
+impl Journal {
+    pub fn new(capacity: usize) -> Journal {
+        Journal {
+            capacity,
+            events: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// True if the journal records events at all
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Records an event, dropping the oldest event
+    /// if the journal is at capacity
+    pub fn record(&mut self, event: EncephalonEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+
+        self.events.push_back(event);
+    }
+
+    /// Returns the events recorded on or after cycle, from oldest to newest
+    pub fn since(&self, cycle: u64) -> Vec<EncephalonEvent> {
+        self.events
+            .iter()
+            .filter(|event| event.cycle >= cycle)
+            .cloned()
+            .collect()
+    }
+
+    /// Writes every recorded event as csv, with a header row.
+    /// Locations are written with their coordinates separated by spaces
+    pub fn write_csv(&self, writer: &mut impl Write) -> io::Result<()> {
+        writeln!(writer, "cycle,kind,src_loc,dst_loc,strength_at_event")?;
+
+        for event in &self.events {
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                event.cycle,
+                event.kind.label(),
+                loc_field(&event.src_loc),
+                loc_field(&event.dst_loc),
+                event.strength_at_event
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+fn loc_field(loc: &[i32]) -> String {
+    loc.iter()
+        .map(|coord| coord.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
@@ -1,9 +1,82 @@
+//! Eywa grows a brain (an encephalon) of spiking neurons
+//! between a set of sensors and a set of actuators.
+//!
+//! Everything needed to set up a brain lives in the prelude:
+//!
+//! ```
+//! use eywa::prelude::*;
+//!
+//! struct Light;
+//!
+//! impl Sensor for Light {
+//!     fn measure(&mut self) -> f32 {
+//!         0.5
+//!     }
+//!
+//!     fn get_name(&self) -> String {
+//!         "light".to_string()
+//!     }
+//! }
+//!
+//! let motor = FnActuator::new("motor".to_string(), |value| println!("{}", value));
+//!
+//! let encephalon = EncephalonBuilder::new()
+//!     .max_plastic_synapses(26)
+//!     .reflexes(vec![Reflex::new(
+//!         "light".into(),
+//!         "motor".into(),
+//!         SynapticType::Excitatory,
+//!         20.,
+//!     )])
+//!     .build(
+//!         Box::new(BoxEcp::new(125, 1, 1, 27)),
+//!         vec![Box::new(Light)],
+//!         vec![Box::new(motor)],
+//!     )
+//!     .unwrap();
+//!
+//! encephalon.run_cycle();
+//! ```
+
+// Measurements and outputs are bounded with max/min chains, which
+// bound NaN to the lower end, where f32::clamp would pass it through
+#![allow(clippy::manual_clamp)]
+
 pub mod actuator;
+pub mod analysis;
+#[cfg(feature = "audit")]
+pub mod audit;
+pub mod bench;
+pub mod brain_spec;
+pub mod clock;
+pub mod compose;
 pub mod ecp_geometry;
 pub mod encephalon;
+#[cfg(feature = "env")]
+pub mod env;
+pub mod error;
+pub mod experiment;
+pub mod journal;
+pub mod loc;
+pub mod metrics;
 pub mod neuron;
 pub mod neuron_interfaces;
+pub mod prelude;
+pub mod probe;
+#[cfg(feature = "python")]
+pub mod pyeywa;
+pub mod reward;
 pub mod sensor;
+pub mod snapshot;
+#[cfg(test)]
+mod test_util;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-pub use actuator::Actuator;
-pub use sensor::Sensor;
+pub use actuator::{Actuator, ActuatorError, ActuatorName};
+pub use encephalon::{Encephalon, EncephalonBuilder, Reflex};
+pub use error::EywaError;
+pub use loc::Loc;
+pub use neuron::synapse::synaptic_strength::{EmStrength, SigmoidStrength};
+pub use sensor::{Sensor, SensorName};
+pub use snapshot::{EncephalonSnapshot, SnapshotFormat};
@@ -0,0 +1,209 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Deref;
+
+use crate::error::EywaError;
+
+/// The most dimensions a location can have
+pub const MAX_DIMS: usize = 4;
+
+/// A location within an ecp geometry.  Locations have up to
+/// MAX_DIMS coordinates, which are held inline so that locations
+/// can be copied and hashed without touching the heap.  Locations
+/// of different dimensions are never equal:
+///
+/// ```
+/// use eywa::loc::Loc;
+///
+/// let loc = Loc::new(&[1, 2, 0]);
+///
+/// assert_eq!(loc.dims(), 3);
+/// assert_eq!(&loc[..2], &[1, 2]);
+/// assert_ne!(loc, Loc::new(&[1, 2]));
+/// assert_eq!(Vec::from(loc), vec![1, 2, 0]);
+/// assert_eq!(loc.to_string(), "(1, 2, 0)");
+/// ```
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Loc {
+    dims: u8,
+    coords: [i32; MAX_DIMS], //Coordinates past dims are always 0
+}
+
+impl Loc {
+    /// Panics if coords has more than MAX_DIMS coordinates
+    pub fn new(coords: &[i32]) -> Loc {
+        assert!(
+            coords.len() <= MAX_DIMS,
+            "Locations have at most {} dimensions, but {} coordinates were given",
+            MAX_DIMS,
+            coords.len()
+        );
+
+        let mut loc = Loc {
+            dims: coords.len() as u8,
+            coords: [0; MAX_DIMS],
+        };
+
+        loc.coords[..coords.len()].copy_from_slice(coords);
+        loc
+    }
+
+    /// The location of coords, or None if it has
+    /// more than MAX_DIMS coordinates
+    pub fn checked(coords: &[i32]) -> Option<Loc> {
+        match coords.len() <= MAX_DIMS {
+            true => Some(Loc::new(coords)),
+            false => None,
+        }
+    }
+
+    /// The number of coordinates of the location
+    pub fn dims(&self) -> usize {
+        self.dims as usize
+    }
+
+    pub fn as_slice(&self) -> &[i32] {
+        &self.coords[..self.dims as usize]
+    }
+}
+
+impl Deref for Loc {
+    type Target = [i32];
+
+    fn deref(&self) -> &[i32] {
+        self.as_slice()
+    }
+}
+
+/// Formats the same as the coordinates as a slice, ie [1, 2, 0]
+impl fmt::Debug for Loc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl fmt::Display for Loc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+
+        for (i, coord) in self.as_slice().iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{}", coord)?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+impl PartialEq<[i32]> for Loc {
+    fn eq(&self, other: &[i32]) -> bool {
+        self.as_slice() == other
+    }
+}
+
+/// Errors with TooManyDimensions if coords has
+/// more than MAX_DIMS coordinates
+impl TryFrom<&[i32]> for Loc {
+    type Error = EywaError;
+
+    fn try_from(coords: &[i32]) -> Result<Loc, EywaError> {
+        Loc::checked(coords).ok_or_else(|| EywaError::TooManyDimensions(coords.to_vec()))
+    }
+}
+
+/// Errors with TooManyDimensions if coords has
+/// more than MAX_DIMS coordinates
+impl TryFrom<Vec<i32>> for Loc {
+    type Error = EywaError;
+
+    fn try_from(coords: Vec<i32>) -> Result<Loc, EywaError> {
+        match Loc::checked(&coords) {
+            Some(loc) => Ok(loc),
+            None => Err(EywaError::TooManyDimensions(coords)),
+        }
+    }
+}
+
+impl From<Loc> for Vec<i32> {
+    fn from(loc: Loc) -> Vec<i32> {
+        loc.as_slice().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn construction_keeps_only_the_given_coordinates() {
+        let loc = Loc::new(&[3, -1]);
+
+        assert_eq!(loc.dims(), 2);
+        assert_eq!(loc.as_slice(), &[3, -1]);
+        assert_eq!(Loc::new(&[]).dims(), 0);
+        assert_eq!(Loc::new(&[1, 2, 3, 4]).as_slice(), &[1, 2, 3, 4]);
+        assert_eq!(Loc::checked(&[1, 2, 3, 4, 5]), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 4 dimensions")]
+    fn new_panics_past_max_dims() {
+        Loc::new(&[0; MAX_DIMS + 1]);
+    }
+
+    #[test]
+    fn conversions_refuse_too_many_dimensions() {
+        assert_eq!(Loc::try_from(vec![1, 2, 3]), Ok(Loc::new(&[1, 2, 3])));
+        assert_eq!(Loc::try_from(&[1, 2][..]), Ok(Loc::new(&[1, 2])));
+        assert_eq!(
+            Loc::try_from(vec![0; 5]),
+            Err(EywaError::TooManyDimensions(vec![0; 5]))
+        );
+        assert_eq!(
+            Loc::try_from(&[0; 5][..]),
+            Err(EywaError::TooManyDimensions(vec![0; 5]))
+        );
+    }
+
+    #[test]
+    fn locations_hash_by_dimensions_and_coordinates() {
+        let locs: HashSet<Loc> = vec![
+            Loc::new(&[1, 2]),
+            Loc::new(&[1, 2, 0]),
+            Loc::new(&[1, 2]),
+            Loc::new(&[2, 1]),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(locs.len(), 3);
+        assert!(locs.contains(&Loc::new(&[1, 2, 0])));
+        assert!(!locs.contains(&Loc::new(&[1, 2, 0, 0])));
+    }
+
+    #[test]
+    fn locations_order_by_dimensions_then_coordinates() {
+        let mut locs = vec![
+            Loc::new(&[0, 0, 1]),
+            Loc::new(&[5, 5]),
+            Loc::new(&[0, 1, 0]),
+            Loc::new(&[-1, 9, 9]),
+        ];
+
+        locs.sort();
+
+        assert_eq!(
+            locs,
+            vec![
+                Loc::new(&[5, 5]),
+                Loc::new(&[-1, 9, 9]),
+                Loc::new(&[0, 0, 1]),
+                Loc::new(&[0, 1, 0]),
+            ]
+        );
+    }
+}
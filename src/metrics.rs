@@ -0,0 +1,575 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+use crate::actuator::ActuatorName;
+use crate::neuron::CycleOutcome;
+use crate::sensor::SensorName;
+
+/// Summary of one full cycle of an encephalon,
+/// aggregated from the outcome of every neuron's cycle
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CycleMetrics {
+    /// The cycle these metrics describe
+    pub cycle: u64,
+
+    /// True if the encephalon stalled instead of running this
+    /// cycle, because its sensors have gone stale for longer than
+    /// the encephalon allows.  A stalled cycle isn't counted
+    pub stalled: bool,
+
+    /// Number of sensors measured this cycle, which is less
+    /// than the number of sensors when sampling is divided down
+    pub sensors_sampled: u32,
+
+    /// Number of sensory neurons cycled and how many of them fired
+    pub sensory_count: u32,
+    pub sensory_fired: u32,
+
+    /// Number of rx (plastic and actuator) neurons
+    /// cycled and how many of them fired
+    pub rx_count: u32,
+    pub rx_fired: u32,
+
+    /// Sum of the EMAs of each class of neuron
+    pub sensory_ema_total: f32,
+    pub rx_ema_total: f32,
+
+    /// Total internal charge consumed by rx neurons
+    pub charge_consumed: f32,
+
+    /// Sum of the adaptation values of the rx neurons
+    pub rx_adaptation_total: f32,
+
+    /// Total number of plastic synapses held by every neuron
+    pub plastic_synapses: u32,
+
+    /// Number of synapses transmitted across by every neuron that fired
+    pub transmissions: u32,
+
+    /// The energy left in store after this cycle, if the
+    /// encephalon has an energy model
+    pub energy: Option<f32>,
+
+    /// Number of actuators that failed to take their control
+    /// value this cycle, and number of actuators that have been
+    /// disabled for failing too many times in a row
+    pub actuator_failures: u32,
+    pub disabled_actuators: u32,
+
+    /// Number of sensors and actuators that panicked this cycle.
+    /// Panics are only caught when the encephalon isolates user
+    /// code, and a panicking actuator also counts as a failure
+    pub sensor_panics: u32,
+    pub actuator_panics: u32,
+
+    /// Number of sensor measurements this cycle that fell
+    /// outside of [0, 1] (after any normalization)
+    pub sensor_range_violations: u32,
+
+    /// The global reward signal, summed across every target
+    /// comparator, and the error each comparator measured
+    /// (in the order the comparators were added)
+    pub reward: f32,
+    pub comparator_errors: Vec<(ActuatorName, f32)>,
+
+    /// Percentiles of the normalized strengths of every plastic synapse,
+    /// which are only swept when detailed metrics are on
+    pub strength_percentiles: Option<StrengthPercentiles>,
+}
+
+/// Cycle metrics display on a single line, for logging:
+///
+/// ```
+/// use eywa::metrics::CycleMetrics;
+///
+/// let metrics = CycleMetrics {
+///     sensory_count: 8,
+///     sensory_fired: 2,
+///     rx_count: 100,
+///     rx_fired: 7,
+///     plastic_synapses: 400,
+///     transmissions: 30,
+///     energy: Some(12.5),
+///     ..CycleMetrics::new(3)
+/// };
+///
+/// assert_eq!(
+///     metrics.to_string(),
+///     "cycle 3: sensory fired 2/8, rx fired 7/100, plastic synapses 400, \
+///      transmissions 30, reward 0, energy 12.5"
+/// );
+/// ```
+impl fmt::Display for CycleMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.stalled {
+            return write!(f, "cycle {}: stalled", self.cycle);
+        }
+
+        write!(
+            f,
+            "cycle {}: sensory fired {}/{}, rx fired {}/{}, plastic synapses {}, \
+             transmissions {}, reward {}",
+            self.cycle,
+            self.sensory_fired,
+            self.sensory_count,
+            self.rx_fired,
+            self.rx_count,
+            self.plastic_synapses,
+            self.transmissions,
+            self.reward
+        )?;
+
+        if let Some(energy) = self.energy {
+            write!(f, ", energy {}", energy)?;
+        }
+
+        if self.actuator_failures > 0 {
+            write!(f, ", actuator failures {}", self.actuator_failures)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The 10th, 50th, and 90th percentiles of normalized plastic synapse strength
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct StrengthPercentiles {
+    pub p10: f32,
+    pub p50: f32,
+    pub p90: f32,
+}
+
+impl StrengthPercentiles {
+    /// Takes the percentiles of strengths, which must be sorted
+    pub fn from_sorted(strengths: &[f32]) -> StrengthPercentiles {
+        StrengthPercentiles {
+            p10: percentile(strengths, 10.),
+            p50: percentile(strengths, 50.),
+            p90: percentile(strengths, 90.),
+        }
+    }
+}
+
+impl CycleMetrics {
+    pub fn new(cycle: u64) -> CycleMetrics {
+        CycleMetrics {
+            cycle,
+            ..CycleMetrics::default()
+        }
+    }
+
+    /// Adds the outcome of a sensory neuron's cycle
+    pub fn record_sensory(&mut self, outcome: CycleOutcome) {
+        self.sensory_count += 1;
+        self.sensory_ema_total += outcome.ema;
+        self.plastic_synapses += outcome.plastic_synapses;
+        self.transmissions += outcome.transmissions;
+
+        if outcome.fired {
+            self.sensory_fired += 1;
+        }
+    }
+
+    /// Adds the outcome of an rx neuron's cycle
+    pub fn record_rx(&mut self, outcome: CycleOutcome) {
+        self.rx_count += 1;
+        self.rx_ema_total += outcome.ema;
+        self.charge_consumed += outcome.charge_consumed;
+        self.rx_adaptation_total += outcome.adaptation;
+        self.plastic_synapses += outcome.plastic_synapses;
+        self.transmissions += outcome.transmissions;
+
+        if outcome.fired {
+            self.rx_fired += 1;
+        }
+    }
+
+    /// Adds the result of setting an actuator's control value
+    pub fn record_actuator<E>(&mut self, result: &Result<(), E>) {
+        if result.is_err() {
+            self.actuator_failures += 1;
+        }
+    }
+
+    /// Folds the metrics of a later cycle into these, so that
+    /// one CycleMetrics can summarize a run of cycles.  Tallies
+    /// (neurons cycled and fired, EMA totals, charge consumed,
+    /// failures, etc) are summed, so the means stay means over
+    /// the whole run, while values that describe the state of
+    /// the encephalon (the cycle, number of synapses, energy,
+    /// disabled actuators, reward, and comparator errors) are taken from
+    /// the later cycle
+    pub fn absorb(&mut self, later: CycleMetrics) {
+        self.cycle = later.cycle;
+        self.stalled = later.stalled;
+        self.sensors_sampled += later.sensors_sampled;
+        self.sensory_count += later.sensory_count;
+        self.sensory_fired += later.sensory_fired;
+        self.rx_count += later.rx_count;
+        self.rx_fired += later.rx_fired;
+        self.sensory_ema_total += later.sensory_ema_total;
+        self.rx_ema_total += later.rx_ema_total;
+        self.charge_consumed += later.charge_consumed;
+        self.rx_adaptation_total += later.rx_adaptation_total;
+        self.plastic_synapses = later.plastic_synapses;
+        self.transmissions += later.transmissions;
+        self.energy = later.energy;
+        self.actuator_failures += later.actuator_failures;
+        self.disabled_actuators = later.disabled_actuators;
+        self.sensor_panics += later.sensor_panics;
+        self.actuator_panics += later.actuator_panics;
+        self.sensor_range_violations += later.sensor_range_violations;
+        self.reward = later.reward;
+        self.comparator_errors = later.comparator_errors;
+        self.strength_percentiles = later.strength_percentiles;
+    }
+
+    /// Average EMA of the sensory neurons
+    pub fn mean_sensory_ema(&self) -> f32 {
+        mean(self.sensory_ema_total, self.sensory_count)
+    }
+
+    /// Average EMA of the rx neurons
+    pub fn mean_rx_ema(&self) -> f32 {
+        mean(self.rx_ema_total, self.rx_count)
+    }
+
+    /// Average adaptation value of the rx neurons
+    pub fn mean_rx_adaptation(&self) -> f32 {
+        mean(self.rx_adaptation_total, self.rx_count)
+    }
+}
+
+/// How closely a run of Encephalon::run_realtime kept to its pace
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PacingReport {
+    /// Number of cycles run
+    pub cycles: u32,
+
+    /// Number of cycles that took longer than their slot
+    pub overruns: u32,
+
+    /// Total time spent sleeping out the rest of slots
+    pub slept: Duration,
+
+    /// Wall time of the whole run
+    pub elapsed: Duration,
+}
+
+/// Time spent in one phase of Encephalon::run_cycle
+/// across every profiled cycle
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PhaseTiming {
+    /// Number of cycles the phase was timed over
+    pub cycles: u64,
+
+    /// Total time spent in the phase
+    pub total: Duration,
+
+    /// Longest time the phase took in a single cycle
+    pub max: Duration,
+}
+
+impl PhaseTiming {
+    /// Adds one cycle's time in the phase
+    pub(crate) fn record(&mut self, elapsed: Duration) {
+        self.cycles += 1;
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+    }
+
+    /// Average time the phase took per cycle
+    pub fn mean(&self) -> Duration {
+        match self.cycles {
+            0 => Duration::from_secs(0),
+            cycles => self.total / cycles as u32,
+        }
+    }
+}
+
+/// Where the time of Encephalon::run_cycle goes, phase by phase,
+/// as profiled since the encephalon was built or last reset
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct PhaseTimings {
+    /// Measuring sensors and encoding their measurements
+    pub sensory_interfaces: PhaseTiming,
+
+    /// Setting actuators, actuator groups, and comparators
+    pub actuator_interfaces: PhaseTiming,
+
+    /// Cycling the sensory neurons
+    pub sensory_neurons: PhaseTiming,
+
+    /// Cycling the rx neurons, gap junctions and lesions included
+    pub rx_neurons: PhaseTiming,
+}
+
+impl PhaseTimings {
+    /// Total time of every phase
+    pub fn total(&self) -> Duration {
+        self.sensory_interfaces.total
+            + self.actuator_interfaces.total
+            + self.sensory_neurons.total
+            + self.rx_neurons.total
+    }
+}
+
+/// Progress of a multi-cycle run, handed to the
+/// progress callback of Encephalon::run_n_cycles_with
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProgressUpdate<'a> {
+    /// Number of cycles completed so far in this run
+    pub cycle: u32,
+
+    /// Total number of cycles in this run
+    pub total: u32,
+
+    /// Cycles per second since the previous update
+    pub cycles_per_sec: f32,
+
+    /// Metrics of the most recent cycle
+    pub metrics: &'a CycleMetrics,
+}
+
+impl<'a> fmt::Display for ProgressUpdate<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cycle: {}/{} ({:.1} cycles/sec, rx fired: {}/{})",
+            self.cycle,
+            self.total,
+            self.cycles_per_sec,
+            self.metrics.rx_fired,
+            self.metrics.rx_count
+        )
+    }
+}
+
+/// The failure record of a single actuator
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ActuatorHealth {
+    pub consecutive_failures: u32,
+    pub total_failures: u64,
+    pub panics: u64,
+    pub disabled: bool,
+}
+
+/// The panic and range violation record of a single sensor
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SensorHealth {
+    pub panics: u64,
+    pub range_violations: u64,
+}
+
+/// Snapshot of the health of an encephalon's interfaces
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HealthReport {
+    /// The cycle this report was taken on
+    pub cycle: u64,
+
+    /// The health of each actuator, keyed by actuator name
+    pub actuators: HashMap<ActuatorName, ActuatorHealth>,
+
+    /// The health of each sensor, keyed by sensor name
+    pub sensors: HashMap<SensorName, SensorHealth>,
+}
+
+impl HealthReport {
+    /// True if no actuator has been disabled
+    pub fn is_healthy(&self) -> bool {
+        self.actuators.values().all(|health| !health.disabled)
+    }
+}
+
+/// Health reports display on a single line, naming only the
+/// interfaces that have had trouble, in order of name:
+///
+/// ```
+/// use eywa::metrics::HealthReport;
+///
+/// assert_eq!(HealthReport::default().to_string(), "cycle 0: healthy");
+/// ```
+impl fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut actuators: Vec<_> = self
+            .actuators
+            .iter()
+            .filter(|(_, health)| health.total_failures > 0 || health.panics > 0)
+            .collect();
+        actuators.sort_by_key(|(name, _)| *name);
+
+        let mut sensors: Vec<_> = self
+            .sensors
+            .iter()
+            .filter(|(_, health)| health.panics > 0 || health.range_violations > 0)
+            .collect();
+        sensors.sort_by_key(|(name, _)| *name);
+
+        if actuators.is_empty() && sensors.is_empty() {
+            return write!(f, "cycle {}: healthy", self.cycle);
+        }
+
+        let troubles: Vec<String> = actuators
+            .into_iter()
+            .map(|(name, health)| {
+                format!(
+                    "{} failed {} times ({} in a row), panicked {} times{}",
+                    name,
+                    health.total_failures,
+                    health.consecutive_failures,
+                    health.panics,
+                    if health.disabled { ", disabled" } else { "" }
+                )
+            })
+            .chain(sensors.into_iter().map(|(name, health)| {
+                format!(
+                    "{} panicked {} times, out of range {} times",
+                    name, health.panics, health.range_violations
+                )
+            }))
+            .collect();
+
+        write!(f, "cycle {}: {}", self.cycle, troubles.join("; "))
+    }
+}
+
+/// How much of an actuator's incoming charge came from its
+/// reflex over a probe window, versus every other afferent
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ReflexContribution {
+    /// The number of cycles the contribution was measured over
+    pub cycles: u32,
+
+    /// Charge delivered by the reflex's static synapses
+    pub reflex_charge: f32,
+
+    /// Charge delivered by plastic synapses
+    pub plastic_charge: f32,
+
+    /// Charge delivered by static synapses that aren't
+    /// part of the reflex
+    pub static_charge: f32,
+}
+
+impl ReflexContribution {
+    /// The fraction of all charge that came from the reflex.
+    /// If the actuator received no charge at all, this is 0
+    pub fn ratio(&self) -> f32 {
+        let total = self.reflex_charge + self.plastic_charge + self.static_charge;
+
+        if total > 0. {
+            self.reflex_charge / total
+        } else {
+            0.
+        }
+    }
+}
+
+/// The pth percentile (p between 0 and 100) of sorted values,
+/// interpolating linearly between the two closest ranks.  The
+/// percentile of no values is 0
+///
+/// ```
+/// use eywa::metrics::percentile;
+///
+/// let values = [1., 2., 3., 4., 5.];
+///
+/// assert_eq!(percentile(&values, 0.), 1.);
+/// assert_eq!(percentile(&values, 50.), 3.);
+/// assert_eq!(percentile(&values, 62.5), 3.5);
+/// assert_eq!(percentile(&values, 100.), 5.);
+/// assert_eq!(percentile(&[], 50.), 0.);
+/// ```
+pub fn percentile(sorted: &[f32], p: f32) -> f32 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+
+    let rank = (p.max(0.).min(100.) / 100.) * (sorted.len() - 1) as f32;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (rank - lower as f32)
+}
+
+/// Splits the range of values into bins of equal width, returning
+/// the lower edge of each bin along with the number of values in
+/// it.  The last bin also holds the largest value
+///
+/// ```
+/// use eywa::metrics::histogram;
+///
+/// let values = [0., 1., 1.5, 2., 4.];
+///
+/// assert_eq!(histogram(&values, 2), vec![(0., 3), (2., 2)]);
+/// assert_eq!(histogram(&[], 2), vec![]);
+/// ```
+pub fn histogram(values: &[f32], bins: usize) -> Vec<(f32, u32)> {
+    if values.is_empty() || bins == 0 {
+        return Vec::new();
+    }
+
+    let min = values.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let width = (max - min) / bins as f32;
+
+    let mut counts = vec![0; bins];
+
+    for value in values {
+        let bin = match width > 0. {
+            true => (((value - min) / width) as usize).min(bins - 1),
+            false => 0,
+        };
+
+        counts[bin] += 1;
+    }
+
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(bin, count)| (min + width * bin as f32, count))
+        .collect()
+}
+
+fn mean(total: f32, count: u32) -> f32 {
+    if count == 0 {
+        0.0
+    } else {
+        total / count as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_interpolate_between_ranks() {
+        let sorted = [0.125, 0.25, 0.5, 0.75, 0.875];
+
+        assert_eq!(percentile(&sorted, 0.), 0.125);
+        assert_eq!(percentile(&sorted, 100.), 0.875);
+        assert_eq!(percentile(&sorted, 75.), 0.75);
+        assert!((percentile(&sorted, 10.) - 0.175).abs() < 1e-6);
+        assert!((percentile(&sorted, 90.) - 0.825).abs() < 1e-6);
+
+        assert_eq!(
+            StrengthPercentiles::from_sorted(&sorted).p50,
+            percentile(&sorted, 50.)
+        );
+    }
+
+    #[test]
+    fn percentiles_of_one_or_no_values() {
+        for p in &[0., 37.5, 100.] {
+            assert_eq!(percentile(&[0.4], *p), 0.4);
+            assert_eq!(percentile(&[], *p), 0.);
+        }
+
+        assert_eq!(
+            StrengthPercentiles::from_sorted(&[]),
+            StrengthPercentiles::default()
+        );
+    }
+}
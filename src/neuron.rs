@@ -1,566 +1,3296 @@
 use super::encephalon::Encephalon;
-use std::cell::{Ref, RefCell};
+use crate::journal::{EncephalonEvent, EventKind};
+use crate::loc::Loc;
+use std::cell::{Cell, Ref, RefCell};
+use std::cmp::Ordering;
+use std::fmt;
 use std::rc::Rc;
 
+use serde::{Deserialize, Serialize};
+
 pub mod synapse;
 use crate::neuron::synapse::synaptic_strength::SynapticStrength;
 use crate::neuron::synapse::SynapticType;
-use synapse::{PlasticSynapse, StaticSynapse, Synapse};
+use crate::snapshot::{NeuronDynamics, PlasticSynapseSnapshot};
+use synapse::{
+    normalize_strengths, ImpulseSource, PlasticSynapse, RestoredSynapse, StaticSynapse, Synapse,
+    SynapseRecord, TargetKey, Transmission,
+};
 
 /// All neurons implement the Neuronic trait
 pub trait Neuronic {
-    fn run_cycle(&self) -> f32;
+    /// Runs the given cycle of the neuron, reporting what happened.
+    /// The cycle is handed down by the encephalon rather than read
+    /// back from it, so every neuron keys its charge and firing
+    /// history by the same absolute cycle.  Only the cycles relative
+    /// to one another matter, not their parity
+    fn run_cycle(&self, cycle: u64) -> CycleOutcome;
+
+    /// Runs the given cycle as the encephalon does in eval mode,
+    /// without any plasticity and without firing any synapses.
+    /// The encephalon fires the neuron's transmissions for it
+    fn run_cycle_eval(&self, cycle: u64) -> CycleOutcome {
+        self.run_cycle(cycle)
+    }
+
+    /// Each of the neuron's outgoing synapses as a transmission,
+    /// in the order the neuron fires them
+    fn transmissions(&self) -> Vec<Transmission> {
+        Vec::new()
+    }
+
+    /// Describes each of the neuron's outgoing synapses.
+    /// Neurons that don't transmit have none
+    fn synapse_records(&self) -> Vec<SynapseRecord> {
+        Vec::new()
+    }
+
+    /// The normalized strength of each of the neuron's plastic
+    /// synapses, so that strengths of every kind are comparable
+    fn plastic_strengths(&self) -> Vec<f32> {
+        Vec::new()
+    }
+
+    /// Takes on an outgoing synapse built elsewhere, as when a
+    /// connectome is imported.  Returns false (dropping the synapse)
+    /// if the neuron doesn't transmit
+    fn adopt_plastic_synapse(&self, _synapse: PlasticSynapse) -> bool {
+        false
+    }
+
+    fn adopt_static_synapse(&self, _synapse: StaticSynapse) -> bool {
+        false
+    }
+
+    /// The normalized strength of the neuron's weakest plastic
+    /// synapse, or None if it has none to spare
+    fn weakest_plastic_strength(&self) -> Option<f32> {
+        None
+    }
+
+    /// Dissolves the neuron's weakest plastic synapse, to make room
+    /// under the encephalon's synapse budget.  False if it had none
+    fn evict_weakest_plastic_synapse(&self, _cycle: u64) -> bool {
+        false
+    }
+
+    /// Records each of the neuron's plastic synapses for a snapshot,
+    /// in the order the neuron fires them
+    fn plastic_synapse_snapshots(&self) -> Vec<PlasticSynapseSnapshot> {
+        Vec::new()
+    }
+
+    /// Replaces the neuron's plastic synapses with ones rebuilt from
+    /// a snapshot onto the given targets.  Returns false (dropping
+    /// them) if the neuron doesn't transmit
+    fn restore_plastic_synapses(&self, _synapses: Vec<RestoredSynapse>) -> bool {
+        false
+    }
 }
 
-/// Neurons that transmit (hence Tx) impulses to
-/// to other neurons implement the TxNeuronic trait
-pub trait TxNeuronic {
-    /// Fire all neuron synapses
-    fn fire_synapses(&self) {
-        for p_synapse in self.get_plastic_synapses().iter() {
-            p_synapse.fire();
+/// The outcome of a single neuron's cycle.  A neuron fires when
+/// the charge it consumed crosses its threshold
+///
+/// ```
+/// use eywa::neuron::{ActuatorNeuron, Neuronic, RxNeuronic};
+/// use eywa::prelude::*;
+///
+/// let neuron = ActuatorNeuron::new(ActuatorParams::default(), Loc::new(&[0, 0, 0]));
+/// neuron.intake_synaptic_impulse(0, 12., SynapticType::Excitatory);
+///
+/// let outcome = neuron.run_cycle(1);
+///
+/// assert!(outcome.fired);
+/// assert_eq!(outcome.charge_consumed, 12.);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CycleOutcome {
+    /// True if the neuron fired this cycle
+    pub fired: bool,
+    /// The neuron's EMA after this cycle
+    pub ema: f32,
+    /// The internal charge the neuron compared against its
+    /// threshold before resetting it (always 0 for sensory neurons)
+    pub charge_consumed: f32,
+    /// The neuron's adaptation value after this cycle
+    /// (always 0 for neurons that don't adapt)
+    pub adaptation: f32,
+    /// The gain applied to the neuron's incoming impulses
+    /// after this cycle (always 1 for sensory neurons)
+    pub input_gain: f32,
+    /// The number of plastic synapses the neuron holds
+    /// after this cycle (always 0 for actuator neurons)
+    pub plastic_synapses: u32,
+    /// The number of synapses the neuron transmitted
+    /// across this cycle (always 0 unless it fired)
+    pub transmissions: u32,
+}
+
+/// Converts a window of roughly n_cycles into the alpha of
+/// an EMA averaging over that window, ie 2 / (n_cycles + 1).
+/// This is usually easier to reason about than alpha itself
+///
+/// ```
+/// use eywa::neuron::ema_alpha_from_window;
+///
+/// // The 2. / 101. used by the main binary averages over 100 cycles
+/// assert_eq!(ema_alpha_from_window(100), 2. / 101.);
+/// ```
+pub fn ema_alpha_from_window(n_cycles: u32) -> f32 {
+    2. / (n_cycles as f32 + 1.)
+}
+
+/// True if alpha lies within (0, 1].  Any other alpha makes
+/// an EMA diverge or oscillate rather than average
+pub fn is_valid_ema_alpha(alpha: f32) -> bool {
+    alpha > 0. && alpha <= 1.
+}
+
+/// The EMA beneath which a neuron's EMA snaps to exactly 0
+pub const DEFAULT_EMA_FLOOR: f32 = 1e-6;
+
+/// Decays the EMA of a neuron that didn't fire.  Rather than
+/// creeping towards 0 forever (and ending up in denormal floats,
+/// which are slow to multiply), an EMA that falls beneath floor
+/// snaps to exactly 0, where it stays until the neuron fires
+pub fn decayed_ema(ema: f32, alpha: f32, floor: f32) -> f32 {
+    let ema = ema * (1.0 - alpha);
+
+    match ema < floor.max(f32::MIN_POSITIVE) {
+        true => 0.0,
+        false => ema,
+    }
+}
+
+/// Parameters of the exponential moving average every
+/// neuron keeps of its firing, ie T(n+1) = αI + (1 - α)T(n)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EmaParams {
+    pub alpha: f32,
+}
+
+impl EmaParams {
+    /// An EMA that averages over roughly n_cycles
+    ///
+    /// ```
+    /// use eywa::neuron::EmaParams;
+    ///
+    /// assert_eq!(EmaParams::from_window(100).alpha, 2. / 101.);
+    /// ```
+    pub fn from_window(n_cycles: u32) -> EmaParams {
+        EmaParams {
+            alpha: ema_alpha_from_window(n_cycles),
         }
+    }
+}
 
-        for s_synapse in self.get_static_synapses().iter() {
-            s_synapse.fire();
+/// Parameters of spike-frequency adaptation.  Each fire raises
+/// a neuron's adaptation value by `incr`, each cycle the value is
+/// multiplied by `decay`, and the neuron's effective threshold is
+/// its fire threshold plus the adaptation value.  An `incr` of 0
+/// disables adaptation
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Adaptation {
+    pub incr: f32,
+    pub decay: f32,
+}
+
+impl Default for Adaptation {
+    fn default() -> Adaptation {
+        Adaptation {
+            incr: 0.0,
+            decay: 0.9,
         }
     }
+}
 
-    /// Add a static synapse with "target" synapse
-    /// Typically called at the inception of the encephalon
-    fn add_static_synapse(
-        &self,
-        strength: f32,
-        synaptic_type: SynapticType,
-        target_neuron: Rc<dyn NeuronicRx>,
-    );
+/// Parameters of gap junctions, which electrically couple
+/// adjacent plastic neurons.  Each pair of adjacent plastic
+/// neurons is coupled with probability `probability`, and each
+/// cycle a coupled pair exchanges `conductance` times the
+/// difference of their charges, so charge flows from the more
+/// charged neuron to the less charged one
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GapJunctionParams {
+    pub probability: f32,
+    pub conductance: f32,
+}
 
-    fn get_plastic_synapses(&self) -> Ref<Vec<PlasticSynapse>>;
-    fn get_static_synapses(&self) -> Ref<Vec<StaticSynapse>>;
+/// A metabolic budget shared by every neuron of an encephalon.
+/// Each fire costs fire_cost and each synapse a fire transmits
+/// across costs synapse_cost, drawn from an energy store that
+/// regenerates by regen_per_cycle up to capacity.  As the store
+/// drains the fire thresholds of plastic neurons are scaled up by
+/// the fatigue (capacity / energy), until an exhausted brain can't
+/// fire at all.
+///
+/// A cycle's fires are paid for all at once, so the store can run
+/// into debt, which isn't floored: a brain that overspends goes
+/// quiet until regen_per_cycle has paid the deficit back.  So a
+/// saturated brain can only keep up the firing regen_per_cycle pays
+/// for.  Both capacity and regen_per_cycle have to be positive and
+/// finite, or the brain is refused at build
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EnergyModel {
+    pub fire_cost: f32,
+    pub synapse_cost: f32,
+    pub regen_per_cycle: f32,
+    pub capacity: f32,
 }
 
-/// Neurons that receive (hence Rx) impulses from
-/// other neurons implement the RxNeuronic trait
-pub trait RxNeuronic {
+impl EnergyModel {
+    /// The factor fire thresholds are scaled by with energy in
+    /// store, which is 1 at capacity and unbounded once exhausted
+    pub fn fatigue(&self, energy: f32) -> f32 {
+        match energy > 0. {
+            true => (self.capacity / energy).max(1.),
+            false => f32::INFINITY,
+        }
+    }
 
-    fn intake_synaptic_impulse(&self, impulse: f32);
+    /// True if capacity and regen_per_cycle are both positive and
+    /// finite, without which an exhausted brain would never recover
+    pub fn is_valid(&self) -> bool {
+        [self.capacity, self.regen_per_cycle]
+            .iter()
+            .all(|value| value.is_finite() && *value > 0.)
+    }
 
-    /// Returns true if the neuron fired on the
-    /// last cycle
-    fn fired_on_prev_cycle(&self) -> bool;
+    /// The energy in store after a cycle that began with energy
+    /// and saw fires fires transmitted across transmissions synapses.
+    /// This is negative when the cycle spent more than was in store
+    pub fn after_cycle(&self, energy: f32, fires: u32, transmissions: u32) -> f32 {
+        let spent = self.fire_cost * fires as f32 + self.synapse_cost * transmissions as f32;
+
+        (energy - spent + self.regen_per_cycle).min(self.capacity)
+    }
 }
 
-/// Enum of the different RxNeurons
-pub enum RxNeuron {
-    Actuator,
-    Plastic,
+/// Parameters of synaptic scaling, a homeostatic mechanism
+/// which keeps an rx neuron's EMA near target_ema by scaling
+/// all of the impulses it receives.  Each cycle the neuron's
+/// input gain is multiplied by (1 + rate) if its EMA is beneath
+/// the target and by (1 - rate) if it's above, and then clamped
+/// to [min_gain, max_gain]
+///
+/// ```
+/// use eywa::prelude::*;
+///
+/// let params = ActuatorParams {
+///     synaptic_scaling: Some(SynapticScaling {
+///         target_ema: 0.05,
+///         ..SynapticScaling::default()
+///     }),
+///     ..ActuatorParams::default()
+/// };
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SynapticScaling {
+    pub target_ema: f32,
+    pub rate: f32,
+    pub min_gain: f32,
+    pub max_gain: f32,
 }
 
-/// Trait used for to reference the fact that a neuron
-/// implements both RxNeuronic and Neuronic
-pub trait NeuronicRx: RxNeuronic + Neuronic {}
+impl SynapticScaling {
+    /// Returns the input gain that follows gain
+    /// for a neuron whose EMA is ema
+    pub fn adjust(&self, gain: f32, ema: f32) -> f32 {
+        let gain = if ema < self.target_ema {
+            gain * (1. + self.rate)
+        } else if ema > self.target_ema {
+            gain * (1. - self.rate)
+        } else {
+            gain
+        };
 
-/// Here Fx stands for "flex" (don't confuse this with
-/// Rx or Tx, it has nothing to do with transmission, I
-/// just like the lexical symmetry).  Any neuron that displays
-/// some level of plasticity implements FxNeuronic.
-///
-/// Here plasticity refers to neurons whose synapses strengthen,
-/// weaken, dissolve, or appear over time
-pub trait FxNeuronic {
-    /// Strengthens or decays plastic synapses and dissolves
-    /// synapses whose strength has fallen beneath it's
-    /// weakness threshold
-    fn prune_synapses(&self);
+        gain.clamp(self.min_gain, self.max_gain)
+    }
+}
 
-    /// Creates new synapse with another (rx) neuron
-    /// within this neurons vicinity
-    fn form_plastic_synapse(&self);
+impl Default for SynapticScaling {
+    fn default() -> SynapticScaling {
+        SynapticScaling {
+            target_ema: 0.05,
+            rate: 0.001,
+            min_gain: 0.1,
+            max_gain: 10.,
+        }
+    }
+}
 
-    /// True if neuron fired 2 cycles ago
-    fn fired_on_prev_prev(&self) -> bool;
+/// Whether charge exactly at a neuron's fire threshold fires it.
+/// Round static strengths sum to exact thresholds surprisingly
+/// often, so a reflex as strong as its actuator's threshold only
+/// drives it under Inclusive
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThresholdMode {
+    /// Charge must exceed the threshold to fire
+    #[default]
+    Exclusive,
+
+    /// Charge at or above the threshold fires
+    Inclusive,
+}
+
+impl ThresholdMode {
+    /// True if charge fires a neuron with this threshold
+    pub fn crosses(self, charge: f32, threshold: f32) -> bool {
+        match self {
+            ThresholdMode::Exclusive => charge > threshold,
+            ThresholdMode::Inclusive => charge >= threshold,
+        }
+    }
 }
 
-/// This represents the internal charge of
-/// an RxNeuron.  There are two slots to
-/// prevent conflicts that happen inherently
-/// in the graphical structure of the encephalon
-/// (I.e. neurons fires before it receives all
-/// proper impulses, or neuron doesn't fire even
-/// though it would have received enough impulse
-/// later in this cycle)
-pub struct InternalCharge(f32, f32);
+/// How inhibitory impulses act on the charge of a neuron
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum InhibitionMode {
+    /// Inhibitory impulses are subtracted from the charge,
+    /// down to the neuron's charge floor
+    #[default]
+    Subtractive,
+
+    /// Inhibitory impulses are accumulated apart from the charge,
+    /// and scale it down when the neuron evaluates it, so that the
+    /// neuron takes excitatory / (1 + gain * inhibition).  Inhibition
+    /// then never pushes a neuron's charge beneath 0, however
+    /// strong it is.  The gain should be non-negative
+    Shunting { gain: f32 },
+}
 
-impl InternalCharge {
-    fn new() -> InternalCharge {
-        InternalCharge(0.0, 0.0)
+impl InhibitionMode {
+    /// The gain of shunting inhibition, if this mode is shunting
+    /// and its gain isn't a finite, non-negative number
+    pub fn invalid_gain(self) -> Option<f32> {
+        match self {
+            InhibitionMode::Shunting { gain } if !(gain.is_finite() && gain >= 0.) => Some(gain),
+            _ => None,
+        }
     }
+}
+
+/// How a neuron decides whether the plastic synapses
+/// it forms are excitatory or inhibitory
+///
+/// ```
+/// use eywa::prelude::*;
+///
+/// let builder = EncephalonBuilder::new().polarity_policy(PolarityPolicy::PerNeuron {
+///     inhibitory_fraction: 0.25,
+/// });
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PolarityPolicy {
+    /// Synapses are excitatory when the source neuron's EMA is
+    /// beneath threshold and inhibitory otherwise, so highly active
+    /// neurons spread inhibition and quiet neurons spread excitation.
+    /// A neuron whose EMA has decayed all the way to 0 is silent, so
+    /// its synapses are always excitatory, whatever the threshold
+    SourceEmaThreshold { threshold: f32 },
+    /// Each synapse is inhibitory with probability inhibitory_fraction
+    FixedRatio { inhibitory_fraction: f32 },
+    /// Each neuron is inhibitory with probability inhibitory_fraction
+    /// when it's created, and every synapse it forms shares its
+    /// polarity (ala Dale's law)
+    PerNeuron { inhibitory_fraction: f32 },
+    /// Every synapse is excitatory
+    AlwaysExcitatory,
+    /// Every synapse is inhibitory
+    AlwaysInhibitory,
+}
 
-    fn get_charge(&self, cycle: ChargeCycle) -> f32 {
-        match cycle {
-            ChargeCycle::Even => self.0,
-            ChargeCycle::Odd => self.1,
+impl PolarityPolicy {
+    /// Picks the polarity of a newly created neuron.  This
+    /// is only random under PerNeuron, and it's ignored by
+    /// every other policy
+    pub fn neuron_polarity(&self, encephalon: &Encephalon) -> SynapticType {
+        match self {
+            PolarityPolicy::PerNeuron {
+                inhibitory_fraction,
+            } if encephalon.random_bool(*inhibitory_fraction) => SynapticType::Inhibitory,
+            _ => SynapticType::Excitatory,
         }
     }
 
-    fn reset_charge(&mut self, cycle: ChargeCycle) {
-        match cycle {
-            ChargeCycle::Even => self.0 = 0.0,
-            ChargeCycle::Odd => self.1 = 0.0,
+    /// Picks the type of a synapse formed by a neuron
+    /// whose EMA is ema and whose polarity is neuron_polarity.
+    /// Silent neurons form excitatory synapses under
+    /// SourceEmaThreshold, even with a threshold of 0
+    pub fn synapse_type(
+        &self,
+        ema: f32,
+        neuron_polarity: SynapticType,
+        encephalon: &Encephalon,
+    ) -> SynapticType {
+        match self {
+            PolarityPolicy::SourceEmaThreshold { threshold } => match ema == 0. || ema < *threshold
+            {
+                true => SynapticType::Excitatory,
+                false => SynapticType::Inhibitory,
+            },
+            PolarityPolicy::FixedRatio {
+                inhibitory_fraction,
+            } => match encephalon.random_bool(*inhibitory_fraction) {
+                true => SynapticType::Inhibitory,
+                false => SynapticType::Excitatory,
+            },
+            PolarityPolicy::PerNeuron { .. } => neuron_polarity,
+            PolarityPolicy::AlwaysExcitatory => SynapticType::Excitatory,
+            PolarityPolicy::AlwaysInhibitory => SynapticType::Inhibitory,
         }
     }
+}
+
+/// Parameters shared by the plastic neurons of an encephalon
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlasticParams {
+    pub fire_threshold: f32,
+    #[serde(default)]
+    pub threshold_mode: ThresholdMode,
+    #[serde(default)]
+    pub inhibition_mode: InhibitionMode,
+    pub alpha: f32,     //The constant of the exponential moving average
+    pub ema_floor: f32, //EMAs beneath this snap to 0
+    pub max_plastic_synapses: usize,
+    pub polarity_policy: PolarityPolicy,
+    pub max_delay: u32,
+    pub fire_window: u32,
+    pub burst_length: u32,
+    pub adaptation: Adaptation,
+    /// If set, the total strength that outgoing plastic
+    /// synapses are normalized to after each plasticity update
+    pub outgoing_total: Option<f32>,
+    /// If set, the synaptic scaling that adjusts the gain
+    /// of incoming impulses.  Otherwise the gain is fixed at 1
+    pub synaptic_scaling: Option<SynapticScaling>,
+    /// The lowest internal charge a neuron can hold
+    pub charge_floor: f32,
+    /// If set, synapses are only formed with rx neurons within
+    /// this radius (as defined by the geometry's neighbors) rather
+    /// than the geometry's nearby neurons
+    pub local_radius: Option<u32>,
+}
 
-    fn incr_next_charge(&mut self, cycle: ChargeCycle, incr_charge: f32) {
-        let next_cycle = cycle.next_cycle();
-        let new_charge = self.get_charge(next_cycle) + incr_charge;
-        match next_cycle {
-            ChargeCycle::Even => self.0 = new_charge,
-            ChargeCycle::Odd => self.1 = new_charge,
+impl Default for PlasticParams {
+    fn default() -> PlasticParams {
+        PlasticParams {
+            fire_threshold: 10.,
+            threshold_mode: ThresholdMode::Exclusive,
+            inhibition_mode: InhibitionMode::Subtractive,
+            alpha: 2. / 100.,
+            ema_floor: DEFAULT_EMA_FLOOR,
+            max_plastic_synapses: 64,
+            polarity_policy: PolarityPolicy::SourceEmaThreshold { threshold: 0.1 },
+            max_delay: 1,
+            fire_window: 2,
+            burst_length: 1,
+            adaptation: Adaptation::default(),
+            outgoing_total: None,
+            synaptic_scaling: None,
+            charge_floor: 0.0,
+            local_radius: None,
         }
     }
 }
 
-/// Represents one of two different types of
-/// Internal Charge Cycles that occur within
-/// a neuron.  Again, this is used to prevent
-/// encephalon graphical conflicts
-#[derive(Copy, Clone, PartialEq)]
-pub enum ChargeCycle {
-    Even,
-    Odd,
+/// Overrides some of the parameters of plastic neurons, leaving
+/// the parameters it doesn't set as they were.  Used to give a
+/// region of the encephalon parameters of its own
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlasticParamsPatch {
+    pub fire_threshold: Option<f32>,
+    pub threshold_mode: Option<ThresholdMode>,
+    pub inhibition_mode: Option<InhibitionMode>,
+    pub alpha: Option<f32>,
+    pub ema_floor: Option<f32>,
+    pub max_plastic_synapses: Option<usize>,
+    pub polarity_policy: Option<PolarityPolicy>,
+    pub max_delay: Option<u32>,
+    pub fire_window: Option<u32>,
+    pub burst_length: Option<u32>,
+    pub adaptation: Option<Adaptation>,
+    pub outgoing_total: Option<f32>,
+    pub synaptic_scaling: Option<SynapticScaling>,
+    pub charge_floor: Option<f32>,
+    pub local_radius: Option<u32>,
 }
 
-impl ChargeCycle {
-    /// Gets the next cycle type
-    fn next_cycle(&self) -> ChargeCycle {
-        match self {
-            ChargeCycle::Even => ChargeCycle::Odd,
-            ChargeCycle::Odd => ChargeCycle::Even,
+impl PlasticParamsPatch {
+    /// Applies the patch on top of params
+    pub fn apply(&self, params: PlasticParams) -> PlasticParams {
+        PlasticParams {
+            fire_threshold: self.fire_threshold.unwrap_or(params.fire_threshold),
+            threshold_mode: self.threshold_mode.unwrap_or(params.threshold_mode),
+            inhibition_mode: self.inhibition_mode.unwrap_or(params.inhibition_mode),
+            alpha: self.alpha.unwrap_or(params.alpha),
+            ema_floor: self.ema_floor.unwrap_or(params.ema_floor),
+            max_plastic_synapses: self
+                .max_plastic_synapses
+                .unwrap_or(params.max_plastic_synapses),
+            polarity_policy: self.polarity_policy.unwrap_or(params.polarity_policy),
+            max_delay: self.max_delay.unwrap_or(params.max_delay),
+            fire_window: self.fire_window.unwrap_or(params.fire_window),
+            burst_length: self.burst_length.unwrap_or(params.burst_length),
+            adaptation: self.adaptation.unwrap_or(params.adaptation),
+            outgoing_total: self.outgoing_total.or(params.outgoing_total),
+            synaptic_scaling: self.synaptic_scaling.or(params.synaptic_scaling),
+            charge_floor: self.charge_floor.unwrap_or(params.charge_floor),
+            local_radius: self.local_radius.or(params.local_radius),
         }
     }
 }
 
-/// Tracks if neurons fired at particular cycles
-struct FireTracker {
-    values: (bool, bool),
-    last_recorded_current_cycle: ChargeCycle,
-    prev_prev: bool,
+/// Parameters of inhibitory interneurons, which are plastic
+/// neurons whose synapses are always inhibitory and purely local.
+/// A sprinkling of fast interneurons keeps activity from running
+/// away.  Every parameter not set here is shared with the
+/// encephalon's other plastic neurons
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InterneuronParams {
+    pub fire_threshold: f32,
+    pub max_plastic_synapses: usize,
+    pub local_radius: u32,
 }
 
-impl FireTracker {
-    fn new() -> FireTracker {
-        FireTracker {
-            values: (false, false),
-            last_recorded_current_cycle: ChargeCycle::Even,
-            prev_prev: false,
+impl InterneuronParams {
+    /// Applies these parameters on top of the parameters
+    /// of the encephalon's other plastic neurons
+    pub fn plastic_params(&self, params: PlasticParams) -> PlasticParams {
+        PlasticParams {
+            fire_threshold: self.fire_threshold,
+            max_plastic_synapses: self.max_plastic_synapses,
+            polarity_policy: PolarityPolicy::AlwaysInhibitory,
+            local_radius: Some(self.local_radius),
+            ..params
         }
     }
+}
 
-    /// Returns true if the neuron fired on the previous cycle
-    fn fired_on_prev_cycle(&self, cycle: ChargeCycle) -> bool {
-        match cycle.next_cycle() {
-            ChargeCycle::Even => self.values.0,
-            ChargeCycle::Odd => self.values.1,
+impl Default for InterneuronParams {
+    fn default() -> InterneuronParams {
+        InterneuronParams {
+            fire_threshold: 5.,
+            max_plastic_synapses: 16,
+            local_radius: 1,
         }
     }
+}
 
-    /// Returns true if the neuron fired two cycles ago
-    fn fired_on_prev_prev(&self, cycle: ChargeCycle) -> bool {
-        if self.last_recorded_current_cycle == cycle {
-            self.prev_prev
-        } else {
-            match cycle {
-                ChargeCycle::Even => self.values.0,
-                ChargeCycle::Odd => self.values.1,
-            }
+/// Parameters of the actuator neurons of an encephalon.
+/// Actuator neurons sit at the end of converging pathways,
+/// so they often want a higher threshold and a slower EMA
+/// than plastic neurons
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ActuatorParams {
+    pub fire_threshold: f32,
+    #[serde(default)]
+    pub threshold_mode: ThresholdMode,
+    #[serde(default)]
+    pub inhibition_mode: InhibitionMode,
+    pub alpha: f32,     //The constant of the exponential moving average
+    pub ema_floor: f32, //EMAs beneath this snap to 0
+    pub max_delay: u32,
+    pub fire_window: u32,
+    /// If set, the synaptic scaling that adjusts the gain
+    /// of incoming impulses.  Otherwise the gain is fixed at 1
+    pub synaptic_scaling: Option<SynapticScaling>,
+    /// The lowest internal charge a neuron can hold
+    pub charge_floor: f32,
+}
+
+impl Default for ActuatorParams {
+    fn default() -> ActuatorParams {
+        ActuatorParams {
+            fire_threshold: 10.,
+            threshold_mode: ThresholdMode::Exclusive,
+            inhibition_mode: InhibitionMode::Subtractive,
+            alpha: 2. / 100.,
+            ema_floor: DEFAULT_EMA_FLOOR,
+            max_delay: 1,
+            fire_window: 2,
+            synaptic_scaling: None,
+            charge_floor: 0.0,
         }
     }
+}
 
-    /// Sets the tracker for the current cycle
-    fn set_tracker(&mut self, cycle: ChargeCycle, fired: bool) {
-        self.last_recorded_current_cycle = cycle;
-        self.prev_prev = match cycle {
-            ChargeCycle::Even => self.values.0,
-            ChargeCycle::Odd => self.values.1,
-        };
+/// Parameters of the sensory neurons of an encephalon.  The
+/// alpha of sensory neurons feeds the polarity policy, so it can
+/// be tuned separately from the alpha of rx neurons
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SensoryParams {
+    pub alpha: f32,     //The constant of the exponential moving average
+    pub ema_floor: f32, //EMAs beneath this snap to 0
+    pub max_plastic_synapses: usize,
+    pub polarity_policy: PolarityPolicy,
+    pub fire_window: u32,
+}
 
-        match cycle {
-            ChargeCycle::Even => self.values.0 = fired,
-            ChargeCycle::Odd => self.values.1 = fired,
+impl Default for SensoryParams {
+    fn default() -> SensoryParams {
+        SensoryParams {
+            alpha: 2. / 100.,
+            ema_floor: DEFAULT_EMA_FLOOR,
+            max_plastic_synapses: 64,
+            polarity_policy: PolarityPolicy::SourceEmaThreshold { threshold: 0.1 },
+            fire_window: 2,
         }
     }
 }
 
-/// A neuron that sends encoded sensory information into
-/// an encephalon
-pub struct SensoryNeuron {
-    encephalon: Rc<Encephalon>,
-    period: RefCell<u32>, //This is the period at which the neuron fires
-    max_plastic_synapses: usize,
-    plastic_synapses: RefCell<Vec<PlasticSynapse>>,
-    static_synapses: RefCell<Vec<StaticSynapse>>,
-    fire_tracker: RefCell<FireTracker>,
-    synaptic_strength_generator: Rc<dyn Fn() -> Box<RefCell<dyn SynapticStrength>>>,
-    synapse_type_threshold: f32,
-    ema: RefCell<f32>, //Exponential moving average, ie T(n+1) = αI + (1 - α)T(n)
-    alpha: f32,        //The constant of the exponential moving average
-    loc: Vec<i32>,
+/// Parameters of the Izhikevich model, ie
+///
+/// v' = 0.04v^2 + 5v + 140 - u + I
+/// u' = a(bv - u)
+///
+/// where the neuron fires when v crosses 30, after which v is
+/// reset to c and u is incremented by d.  The input current I is
+/// the charge the neuron received this cycle scaled by gain
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IzhParams {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub gain: f32,
 }
 
-impl SensoryNeuron {
-    pub fn new(
-        encephalon: Rc<Encephalon>,
-        max_plastic_synapses: usize,
-        synaptic_strength_generator: Rc<dyn Fn() -> Box<RefCell<dyn SynapticStrength>>>,
-        synapse_type_threshold: f32,
-        alpha: f32, //The constant of the exponential moving average
-        loc: Vec<i32>,
-    ) -> SensoryNeuron {
-        SensoryNeuron {
-            encephalon,
-            period: RefCell::new(0),
-            max_plastic_synapses,
-            plastic_synapses: RefCell::new(Vec::new()),
-            static_synapses: RefCell::new(Vec::new()),
-            fire_tracker: RefCell::new(FireTracker::new()),
-            synaptic_strength_generator,
-            synapse_type_threshold,
-            ema: RefCell::new(0.0),
-            alpha,
-            loc,
+impl IzhParams {
+    /// Parameters of a regular spiking cortical neuron
+    pub fn regular_spiking() -> IzhParams {
+        IzhParams {
+            a: 0.02,
+            b: 0.2,
+            c: -65.,
+            d: 8.,
+            gain: 1.,
         }
     }
 
-    /// Sets the period of this neuron, which
-    /// indicates on which cycle values this neuron
-    /// should fire
-    pub fn set_period(&self, period: u32) {
-        *self.period.borrow_mut() = period;
+    /// Parameters of a chattering neuron, which fires
+    /// repetitive bursts of closely spaced spikes
+    pub fn chattering() -> IzhParams {
+        IzhParams {
+            a: 0.02,
+            b: 0.2,
+            c: -50.,
+            d: 2.,
+            gain: 1.,
+        }
     }
 }
 
-impl Neuronic for SensoryNeuron {
-    fn run_cycle(&self) -> f32 {
-        self.prune_synapses();
-        self.form_plastic_synapse();
-
-        let mut fire_tracker = self.fire_tracker.borrow_mut();
-        let current_cycle = self.encephalon.get_charge_cycle();
+impl Default for IzhParams {
+    fn default() -> IzhParams {
+        IzhParams::regular_spiking()
+    }
+}
 
-        let mut ema = self.ema.borrow_mut();
+/// The model used for the plastic neurons of an encephalon
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PlasticModel {
+    /// Integrate and fire neurons that compare each cycle's
+    /// charge against a threshold (ie PlasticNeuron)
+    Ema(PlasticParams),
+    /// Izhikevich neurons (ie IzhikevichNeuron)
+    Izhikevich(IzhParams),
+}
 
-        let period = self.period.borrow();
+/// Neurons that transmit (hence Tx) impulses to
+/// to other neurons implement the TxNeuronic trait
+pub trait TxNeuronic {
+    /// Fire all neuron synapses during the given cycle,
+    /// returning the number of synapses fired
+    fn fire_synapses(&self, cycle: u64) -> u32 {
+        let plastic_synapses = self.get_plastic_synapses();
+        let static_synapses = self.get_static_synapses();
+
+        for p_synapse in plastic_synapses.iter() {
+            p_synapse.fire(cycle);
+        }
 
-        if *period != 0 && self.encephalon.get_cycle_count() % *period == 0 {
-            self.fire_synapses();
-            *ema = self.alpha + ((1.0 - self.alpha) * (*ema));
-            fire_tracker.set_tracker(current_cycle, true);
-        } else {
-            *ema = (1.0 - self.alpha) * (*ema);
-            fire_tracker.set_tracker(current_cycle, false);
+        for s_synapse in static_synapses.iter() {
+            s_synapse.fire(cycle);
         }
 
-        ema.clone()
+        (plastic_synapses.len() + static_synapses.len()) as u32
     }
-}
 
-impl TxNeuronic for SensoryNeuron {
+    /// Every synapse as a transmission, in the order fire_synapses fires them
+    fn frozen_transmissions(&self) -> Vec<Transmission> {
+        let plastic_synapses = self.get_plastic_synapses();
+        let static_synapses = self.get_static_synapses();
+
+        plastic_synapses
+            .iter()
+            .map(PlasticSynapse::transmission)
+            .chain(static_synapses.iter().map(StaticSynapse::transmission))
+            .collect()
+    }
+
+    /// Add a static synapse with "target" synapse
+    /// Typically called at the inception of the encephalon
     fn add_static_synapse(
         &self,
         strength: f32,
         synaptic_type: SynapticType,
         target_neuron: Rc<dyn NeuronicRx>,
     ) {
-        self.static_synapses.borrow_mut().push(StaticSynapse::new(
+        self.push_static_synapse(StaticSynapse::new(strength, synaptic_type, target_neuron));
+    }
+
+    /// Add a static synapse formed from a reflex
+    fn add_reflex_synapse(
+        &self,
+        strength: f32,
+        synaptic_type: SynapticType,
+        target_neuron: Rc<dyn NeuronicRx>,
+    ) {
+        self.push_static_synapse(StaticSynapse::reflex(
             strength,
             synaptic_type,
             target_neuron,
         ));
     }
 
-    fn get_plastic_synapses(&self) -> Ref<Vec<PlasticSynapse>> {
-        self.plastic_synapses.borrow()
+    fn push_static_synapse(&self, synapse: StaticSynapse);
+
+    fn get_plastic_synapses(&self) -> Ref<'_, Vec<PlasticSynapse>>;
+    fn get_static_synapses(&self) -> Ref<'_, Vec<StaticSynapse>>;
+
+    /// Records every plastic synapse of this neuron, which sits at source
+    fn plastic_snapshots_from(&self, source: &[i32]) -> Vec<PlasticSynapseSnapshot> {
+        self.get_plastic_synapses()
+            .iter()
+            .map(|synapse| synapse.snapshot(source))
+            .collect()
     }
 
-    fn get_static_synapses(&self) -> Ref<Vec<StaticSynapse>> {
-        self.static_synapses.borrow()
+    /// Describes every synapse of this neuron, which sits at source
+    fn records_from(&self, source: &[i32]) -> Vec<SynapseRecord> {
+        let plastic = self.get_plastic_synapses();
+        let plastic = plastic.iter().map(|synapse| SynapseRecord {
+            source: source.to_vec(),
+            target: synapse.target_key().to_vec(),
+            strength: synapse.strength(),
+            normalized_strength: Some(synapse.normalized_strength()),
+            synaptic_type: synapse.synaptic_type(),
+            plastic: true,
+        });
+
+        let statics = self.get_static_synapses();
+        let statics = statics.iter().map(|synapse| SynapseRecord {
+            source: source.to_vec(),
+            target: synapse.target_key().to_vec(),
+            strength: synapse.strength(),
+            normalized_strength: None,
+            synaptic_type: synapse.synaptic_type(),
+            plastic: false,
+        });
+
+        plastic.chain(statics).collect()
     }
 }
 
-impl FxNeuronic for SensoryNeuron {
-    fn prune_synapses(&self) {
-        let synapses_fired = self.fired_on_prev_prev();
-        let mut synapses = self.plastic_synapses.borrow_mut();
-
-        synapses.retain(|synapse| {
-            if synapses_fired {
-                if synapse.target.fired_on_prev_cycle() {
-                    synapse.strengthen();
-                } else {
-                    synapse.decay();
-                }
-            }
-            synapse.connected()
-        })
+/// Neurons that receive (hence Rx) impulses from
+/// other neurons implement the RxNeuronic trait
+pub trait RxNeuronic {
+    /// Takes in an impulse of the given (non-negative) size from
+    /// a synapse of synaptic_type sent during cycle, which
+    /// arrives on the cycle after
+    fn intake_synaptic_impulse(&self, cycle: u64, impulse: f32, synaptic_type: SynapticType) {
+        self.intake_delayed_impulse(cycle, impulse, synaptic_type, 1);
     }
 
-    fn form_plastic_synapse(&self) {
-        let mut plastic_synapses = self.plastic_synapses.borrow_mut();
-        if plastic_synapses.len() < self.max_plastic_synapses {
-            let new_target_neuron = self.encephalon.local_random_neuron(&self.loc);
+    /// Takes in an impulse of the given (non-negative) size from
+    /// a synapse of synaptic_type sent during cycle, which arrives
+    /// delay cycles later.  Delays beyond the neuron's max
+    /// delay are clamped
+    fn intake_delayed_impulse(
+        &self,
+        cycle: u64,
+        impulse: f32,
+        synaptic_type: SynapticType,
+        delay: u32,
+    );
 
-            let synapse_type = match *self.ema.borrow() < self.synapse_type_threshold {
-                true => SynapticType::Excitatory,
-                false => SynapticType::Inhibitory,
-            };
+    /// Returns true if the neuron fired on the
+    /// cycle before cycle
+    fn fired_on_prev_cycle(&self, cycle: u64) -> bool;
+
+    /// Returns true if the neuron fired on any of the last_n
+    /// cycles before cycle, as far back as its history goes
+    fn fired_within(&self, cycle: u64, last_n: u32) -> bool;
+
+    /// The kind of the neuron.  Interneurons are plastic neurons
+    /// the encephalon keeps track of, so they report Plastic
+    fn kind(&self) -> NeuronKind;
+
+    /// Notes an impulse sent by a synapse of class source.  Only
+    /// neurons that attribute their charge (ie armed actuator
+    /// neurons) keep track, so by default this does nothing
+    fn tally_impulse(&self, _source: ImpulseSource, _impulse: f32) {}
+
+    /// Returns the charge the neuron will evaluate on cycle
+    fn peek_charge(&self, cycle: u64) -> f32;
+
+    /// Moves delta charge straight into the charge the neuron
+    /// will evaluate on cycle, without applying its input gain
+    fn shift_charge(&self, cycle: u64, delta: f32);
+
+    /// Returns the number of times the neuron fired
+    /// within its fire tracking window
+    ///
+    /// ```
+    /// use eywa::neuron::{ActuatorNeuron, Neuronic, RxNeuronic};
+    /// use eywa::prelude::*;
+    ///
+    /// let neuron = ActuatorNeuron::new(ActuatorParams::default(), Loc::new(&[0, 0, 0]));
+    /// neuron.shift_charge(0, 20.);
+    /// neuron.run_cycle(0);
+    ///
+    /// assert_eq!(neuron.recent_fire_count(), 1);
+    /// ```
+    fn recent_fire_count(&self) -> u32;
+
+    /// Returns the location of the neuron
+    fn loc(&self) -> Loc;
+}
 
-            if let Some(neuron_ref) = new_target_neuron {
-                let new_synapse = PlasticSynapse::new(
-                    (self.synaptic_strength_generator)(),
-                    synapse_type,
-                    neuron_ref,
-                );
+/// Enum of the different RxNeurons
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RxNeuron {
+    Actuator,
+    Plastic,
+}
 
-                plastic_synapses.push(new_synapse);
+/// Enum of every kind of neuron within an encephalon
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NeuronKind {
+    Sensory,
+    Plastic,
+    Interneuron,
+    Actuator,
+}
+
+/// Neurons that keep an exponential moving average of
+/// their firing implement the HasEma trait
+pub trait HasEma {
+    /// Returns the neuron's EMA firing frequency
+    fn ema(&self) -> f32;
+
+    /// Overwrites the neuron's EMA firing frequency,
+    /// which is used when restoring from a snapshot
+    fn set_ema(&self, ema: f32);
+}
+
+/// Neurons whose state carries over from one cycle to the next
+/// (pending charge, recent fires, bursts and so on) implement the
+/// HasDynamics trait, so that state can be snapshotted and restored
+pub trait HasDynamics {
+    /// Records the neuron's state between cycle and the next
+    fn dynamics(&self, cycle: u64) -> NeuronDynamics;
+
+    /// Overwrites the neuron's state with dynamics, which were
+    /// recorded between cycle and the next (though not necessarily
+    /// by this neuron), which is used when restoring from a snapshot
+    fn set_dynamics(&self, cycle: u64, dynamics: &NeuronDynamics);
+}
+
+/// Trait used for to reference the fact that a neuron
+/// implements RxNeuronic, Neuronic, HasEma, HasDynamics and Debug
+pub trait NeuronicRx: RxNeuronic + Neuronic + HasEma + HasDynamics + fmt::Debug {}
+
+/// Here Fx stands for "flex" (don't confuse this with
+/// Rx or Tx, it has nothing to do with transmission, I
+/// just like the lexical symmetry).  Any neuron that displays
+/// some level of plasticity implements FxNeuronic.
+///
+/// Here plasticity refers to neurons whose synapses strengthen,
+/// weaken, dissolve, or appear over time
+pub trait FxNeuronic {
+    /// Strengthens or decays plastic synapses and dissolves
+    /// synapses whose strength has fallen beneath it's
+    /// weakness threshold
+    fn prune_synapses(&self, cycle: u64);
+
+    /// Creates new synapse with another (rx) neuron
+    /// within this neurons vicinity during cycle
+    fn form_plastic_synapse(&self, cycle: u64);
+
+    /// True if neuron fired 2 cycles before cycle
+    fn fired_on_prev_prev(&self, cycle: u64) -> bool;
+
+    /// True if neuron fired n cycles before cycle
+    fn fired_cycles_before(&self, cycle: u64, n: u32) -> bool;
+}
+
+/// Picks the target of a new plastic synapse for the neuron at
+/// loc, skipping neurons its synapses already target and neurons
+/// the targeting rules don't allow a source of kind source to
+/// target.  Neurons with a local radius pick uniformly within it,
+/// while others try the geometry's own pick first, and only fall
+/// back to a uniform pick among the unconnected nearby neurons
+/// if that's taken (or not allowed)
+fn unconnected_target(
+    encephalon: &Encephalon,
+    source: NeuronKind,
+    loc: Loc,
+    synapses: &[PlasticSynapse],
+    local_radius: Option<u32>,
+) -> Option<Rc<dyn NeuronicRx>> {
+    if local_radius.is_none() {
+        if let Some(neuron) = encephalon.local_random_neuron(loc) {
+            if encephalon.allows_target(source, neuron.kind())
+                && synapses
+                    .iter()
+                    .all(|synapse| !Rc::ptr_eq(&synapse.target, &neuron))
+            {
+                return Some(neuron);
             }
         }
     }
 
-    fn fired_on_prev_prev(&self) -> bool {
-        self.fire_tracker
-            .borrow()
-            .fired_on_prev_prev(self.encephalon.get_charge_cycle())
+    let mut excluded: Vec<TargetKey> = synapses.iter().map(PlasticSynapse::target_key).collect();
+    excluded.extend(encephalon.disallowed_targets(source, loc, local_radius));
+
+    match local_radius {
+        Some(radius) => encephalon.random_neighbor_neuron(loc, radius, &excluded),
+        None => encephalon.local_random_unconnected_neuron(loc, &excluded),
     }
 }
 
-/// A neuron that receives impulses but only
-/// sends its average frequency (calculated via EMA)
-/// to an ActuatorInterface
-pub struct ActuatorNeuron {
-    encephalon: Rc<Encephalon>,
-    fire_tracker: RefCell<FireTracker>,
-    internal_charge: RefCell<InternalCharge>,
-    fire_threshold: f32,
-    ema: RefCell<f32>, //Exponential moving average, ie T(n+1) = αI + (1 - α)T(n)
-    alpha: f32,        //The constant of the exponential moving average
+/// Journals an event of a plastic synapse from the neuron
+/// at src_loc, if the encephalon keeps a journal
+fn journal_synapse(
+    encephalon: &Encephalon,
+    kind: EventKind,
+    cycle: u64,
+    src_loc: &[i32],
+    synapse: &PlasticSynapse,
+) {
+    if encephalon.journaling() {
+        encephalon.record_event(EncephalonEvent {
+            cycle,
+            kind,
+            src_loc: src_loc.to_vec(),
+            dst_loc: synapse.target_key().to_vec(),
+            strength_at_event: synapse.strength(),
+        });
+    }
 }
 
-impl ActuatorNeuron {
-    pub fn new(
-        encephalon: Rc<Encephalon>,
-        fire_threshold: f32,
-        alpha: f32, //The constant of the exponential moving average
-    ) -> ActuatorNeuron {
-        ActuatorNeuron {
-            encephalon,
-            fire_tracker: RefCell::new(FireTracker::new()),
-            internal_charge: RefCell::new(InternalCharge::new()),
-            fire_threshold,
-            ema: RefCell::new(0.0),
-            alpha,
+/// The normalized strength of the weakest of synapses.  Synapses
+/// that are already borrowed belong to the neuron that's forming
+/// a synapse, which never has to make room for itself
+fn weakest_strength(synapses: &RefCell<Vec<PlasticSynapse>>) -> Option<f32> {
+    synapses
+        .try_borrow()
+        .ok()?
+        .iter()
+        .map(PlasticSynapse::normalized_strength)
+        .min_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+}
+
+/// Rebuilds the plastic synapses recorded in a snapshot onto
+/// their targets, with strengths from strength_generator
+fn restored_synapses(
+    strength_generator: &dyn Fn() -> Box<RefCell<dyn SynapticStrength>>,
+    synapses: Vec<RestoredSynapse>,
+) -> Vec<PlasticSynapse> {
+    synapses
+        .into_iter()
+        .map(|(snapshot, target)| PlasticSynapse::restored(snapshot, strength_generator(), target))
+        .collect()
+}
+
+/// Dissolves the weakest of the synapses of the neuron at src_loc,
+/// returning false if there were none (or they were borrowed)
+fn evict_weakest(
+    encephalon: &Encephalon,
+    synapses: &RefCell<Vec<PlasticSynapse>>,
+    src_loc: &[i32],
+    cycle: u64,
+) -> bool {
+    let mut synapses = match synapses.try_borrow_mut() {
+        Ok(synapses) => synapses,
+        Err(_) => return false,
+    };
+
+    let weakest = synapses
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| {
+            a.normalized_strength()
+                .partial_cmp(&b.normalized_strength())
+                .unwrap_or(Ordering::Equal)
+        })
+        .map(|(index, _)| index);
+
+    match weakest {
+        Some(index) => {
+            let synapse = synapses.remove(index);
+            journal_synapse(encephalon, EventKind::Pruning, cycle, src_loc, &synapse);
+            encephalon.plastic_synapse_pruned();
+            true
         }
+        None => false,
     }
+}
 
-    /// Reads this actuator neuron's EMA firing frequency
-    pub fn read_ema_frequency(&self) -> f32 {
-        self.ema.borrow().clone()
+/// This represents the internal charge of an RxNeuron.
+/// It's a small ring buffer with a slot for each of the
+/// next few cycles, indexed by absolute cycle modulo the
+/// buffer length.  Impulses are deposited into the slot of
+/// the cycle on which they should arrive, which prevents
+/// conflicts that happen inherently in the graphical structure
+/// of the encephalon (I.e. neurons fires before it receives all
+/// proper impulses, or neuron doesn't fire even though it would
+/// have received enough impulse later in this cycle) and allows
+/// impulses to be delayed by up to max_delay cycles
+///
+/// Under shunting inhibition, inhibitory impulses are held in
+/// slots of their own, and only scale down the charge of their
+/// cycle as it's taken
+#[derive(Debug)]
+pub struct ChargeBuffer {
+    slots: Vec<f32>,
+    inhibition: Vec<f32>, //Shunting inhibition of each slot
+    max_delay: u32,
+    charge_floor: f32,
+    inhibition_mode: InhibitionMode,
+}
+
+impl ChargeBuffer {
+    /// Creates a buffer of max_delay + 2 slots.  A max_delay of 1
+    /// gives every impulse arriving on the next cycle, which is
+    /// identical to the original even/odd charge slots.
+    ///
+    /// The charge of each slot is never allowed beneath
+    /// charge_floor, so a burst of inhibition can't bury a
+    /// neuron so deep that it takes dozens of excitatory
+    /// impulses to recover.  A charge_floor of f32::NEG_INFINITY
+    /// leaves the charge unbounded
+    pub fn new(max_delay: u32, charge_floor: f32, inhibition_mode: InhibitionMode) -> ChargeBuffer {
+        let max_delay = max_delay.max(1);
+
+        ChargeBuffer {
+            slots: vec![0.0; max_delay as usize + 2],
+            inhibition: vec![0.0; max_delay as usize + 2],
+            max_delay,
+            charge_floor,
+            inhibition_mode,
+        }
+    }
+
+    /// The longest delay (in cycles) this buffer can hold an impulse
+    pub fn max_delay(&self) -> u32 {
+        self.max_delay
+    }
+
+    fn slot_index(&self, cycle: u64) -> usize {
+        (cycle % self.slots.len() as u64) as usize
+    }
+
+    /// Adds impulse to the charge that will be
+    /// taken on the given cycle, clamped to the charge floor
+    pub fn incr_charge_at(&mut self, cycle: u64, impulse: f32) {
+        let index = self.slot_index(cycle);
+
+        #[cfg(feature = "audit")]
+        let before = self.slots[index];
+
+        let unclamped = self.slots[index] + impulse;
+        self.slots[index] = unclamped.max(self.charge_floor);
+
+        #[cfg(feature = "audit")]
+        {
+            let deposited = self.slots[index] as f64 - before as f64;
+            let floored = unclamped as f64 - self.slots[index] as f64;
+
+            crate::audit::record(|ledger| {
+                ledger.deposited += deposited;
+                ledger.floored += floored;
+            });
+        }
+    }
+
+    /// Takes in an impulse of the given (non-negative) size from
+    /// a synapse of synaptic_type, to be taken on the given cycle.
+    /// Inhibitory impulses are subtracted from the charge unless
+    /// the buffer's inhibition is shunting
+    pub fn intake_at(&mut self, cycle: u64, impulse: f32, synaptic_type: SynapticType) {
+        match (synaptic_type, self.inhibition_mode) {
+            (SynapticType::Inhibitory, InhibitionMode::Shunting { .. }) => {
+                let index = self.slot_index(cycle);
+                self.inhibition[index] += impulse;
+
+                #[cfg(feature = "audit")]
+                crate::audit::record(|ledger| ledger.shunted -= impulse as f64);
+            }
+            _ => self.incr_charge_at(cycle, synaptic_type.signed(impulse)),
+        }
+    }
+
+    /// Returns the charge accumulated for the given
+    /// cycle without consuming it.  Under shunting
+    /// inhibition, this is the charge before inhibition
+    pub fn peek_charge(&self, cycle: u64) -> f32 {
+        self.slots[self.slot_index(cycle)]
+    }
+
+    /// The charge waiting to be taken on each cycle after
+    /// cycle, for as many cycles as the buffer can hold
+    pub fn pending_after(&self, cycle: u64) -> Vec<f32> {
+        (1..self.slots.len() as u64)
+            .map(|n| self.peek_charge(cycle + n))
+            .collect()
+    }
+
+    /// The shunting inhibition waiting to be taken on each cycle
+    /// after cycle, for as many cycles as the buffer can hold
+    pub fn pending_inhibition_after(&self, cycle: u64) -> Vec<f32> {
+        (1..self.inhibition.len() as u64)
+            .map(|n| self.inhibition[self.slot_index(cycle + n)])
+            .collect()
+    }
+
+    /// Replaces the contents of the buffer with pending, the
+    /// charge waiting on each cycle after cycle (see pending_after)
+    pub fn restore_pending(&mut self, cycle: u64, pending: &[f32]) {
+        self.slots.iter_mut().for_each(|slot| *slot = 0.0);
+
+        for (n, charge) in pending.iter().take(self.slots.len() - 1).enumerate() {
+            let index = self.slot_index(cycle + n as u64 + 1);
+            self.slots[index] = *charge;
+        }
+    }
+
+    /// Replaces the shunting inhibition of the buffer with pending,
+    /// the inhibition waiting on each cycle after cycle
+    /// (see pending_inhibition_after)
+    pub fn restore_pending_inhibition(&mut self, cycle: u64, pending: &[f32]) {
+        self.inhibition.iter_mut().for_each(|slot| *slot = 0.0);
+
+        for (n, inhibition) in pending.iter().take(self.inhibition.len() - 1).enumerate() {
+            let index = self.slot_index(cycle + n as u64 + 1);
+            self.inhibition[index] = *inhibition;
+        }
+    }
+
+    /// Returns the charge accumulated for the given cycle,
+    /// zeroing its slot so it can be reused by a later cycle.
+    /// Under shunting inhibition, the charge is first scaled
+    /// down by the inhibition accumulated for the cycle
+    ///
+    /// ```
+    /// use eywa::neuron::{ChargeBuffer, InhibitionMode};
+    ///
+    /// let mut buffer = ChargeBuffer::new(3, f32::NEG_INFINITY, InhibitionMode::Subtractive);
+    /// buffer.incr_charge_at(12, 2.);
+    ///
+    /// assert_eq!(buffer.take_charge(12), 2.);
+    /// assert_eq!(buffer.take_charge(12), 0.);
+    /// ```
+    pub fn take_charge(&mut self, cycle: u64) -> f32 {
+        let index = self.slot_index(cycle);
+        let excitation = self.slots[index];
+        let inhibition = self.inhibition[index];
+        self.slots[index] = 0.0;
+        self.inhibition[index] = 0.0;
+
+        let charge = match self.inhibition_mode {
+            InhibitionMode::Subtractive => excitation,
+            InhibitionMode::Shunting { gain } => excitation.max(0.0) / (1.0 + gain * inhibition),
+        };
+
+        #[cfg(feature = "audit")]
+        crate::audit::record(|ledger| ledger.consumed += charge as f64);
+
+        charge
     }
 }
 
-impl Neuronic for ActuatorNeuron {
-    fn run_cycle(&self) -> f32 {
-        let current_cycle = self.encephalon.get_charge_cycle();
-        let mut internal_charge = self.internal_charge.borrow_mut();
-        let mut ema = self.ema.borrow_mut();
-        let mut fire_tracker = self.fire_tracker.borrow_mut();
+/// Represents the parity of a cycle.  Charge is now held in
+/// a ChargeBuffer indexed by absolute cycle, but the parity is
+/// still used to track when neurons fired
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ChargeCycle {
+    Even,
+    Odd,
+}
 
-        if internal_charge.get_charge(current_cycle) > self.fire_threshold {
-            *ema = self.alpha + ((1.0 - self.alpha) * (*ema));
-            fire_tracker.set_tracker(current_cycle, true);
+impl ChargeCycle {
+    /// Gets the parity of an absolute cycle
+    pub fn from_cycle(cycle: u64) -> ChargeCycle {
+        if cycle.is_multiple_of(2) {
+            ChargeCycle::Even
         } else {
-            *ema = (1.0 - self.alpha) * (*ema);
-            fire_tracker.set_tracker(current_cycle, false);
+            ChargeCycle::Odd
         }
+    }
+}
+
+/// Tracks if neurons fired at particular cycles.  This
+/// is a ring buffer of the last `window` recorded cycles
+/// (plus one, so the full window is still available while
+/// the current cycle is being recorded), indexed by absolute
+/// cycle modulo the buffer length.
+///
+/// Cycles aren't always recorded back to back (a snapshot may
+/// be restored over a running brain, moving its cycle count
+/// anywhere), so every cycle skipped between two records counts
+/// as a cycle the neuron didn't fire, and a tracker that records
+/// a cycle behind its last record has been rewound and remembers
+/// nothing before it.  Earlier cycles can still be looked back on
+/// after later ones are recorded, as long as the buffer holds them
+struct FireTracker {
+    history: Vec<bool>,
+    window: u32,
+    last_recorded: Option<u64>,
+}
 
-        internal_charge.reset_charge(current_cycle);
+impl FireTracker {
+    /// Creates a tracker remembering the last window cycles.
+    /// The window is at least 2 so that prev/prev-prev queries work
+    fn new(window: u32) -> FireTracker {
+        let window = window.max(2);
 
-        ema.clone()
+        FireTracker {
+            history: vec![false; window as usize + 1],
+            window,
+            last_recorded: None,
+        }
     }
-}
 
-impl RxNeuronic for ActuatorNeuron {
-    fn intake_synaptic_impulse(&self, impulse: f32) {
-        self.internal_charge
-            .borrow_mut()
-            .incr_next_charge(self.encephalon.get_charge_cycle(), impulse);
+    /// Creates a tracker remembering the last window cycles for
+    /// its fire count, but able to look back history cycles
+    fn remembering(window: u32, history: u32) -> FireTracker {
+        let mut fire_tracker = FireTracker::new(window);
+        fire_tracker.remember(history);
+
+        fire_tracker
     }
 
-    fn fired_on_prev_cycle(&self) -> bool {
-        self.fire_tracker
-            .borrow()
-            .fired_on_prev_cycle(self.encephalon.get_charge_cycle())
+    /// Extends the history to at least the last cycles cycles.  This
+    /// clears the history, so it's only for trackers yet to record
+    fn remember(&mut self, cycles: u32) {
+        if cycles as usize >= self.history.len() {
+            self.history = vec![false; cycles as usize + 1];
+            self.last_recorded = None;
+        }
     }
-}
 
-impl NeuronicRx for ActuatorNeuron {}
+    fn slot_index(&self, cycle: u64) -> usize {
+        (cycle % self.history.len() as u64) as usize
+    }
 
-/// This is your standard neuron present in the
-/// encephalon.  Basically everything about this
-/// neuron isn't fixed.  It's incoming or outgoing
-/// synapses are subject to change based on its
-/// environment
-pub struct PlasticNeuron {
+    /// Returns whether the neuron fired n cycles before
+    /// current_cycle, or None if that's outside the history
+    fn fired_n_cycles_ago(&self, current_cycle: u64, n: u32) -> Option<bool> {
+        if n == 0 || n as usize >= self.history.len() {
+            return None;
+        }
+
+        let target_cycle = current_cycle.checked_sub(n as u64)?;
+
+        match self.last_recorded {
+            Some(last_recorded)
+                if target_cycle <= last_recorded
+                    && last_recorded - target_cycle < self.history.len() as u64 =>
+            {
+                Some(self.history[self.slot_index(target_cycle)])
+            }
+            _ => Some(false),
+        }
+    }
+
+    /// Returns the number of times the neuron fired
+    /// within the last window recorded cycles
+    fn fire_count_in_window(&self) -> u32 {
+        match self.last_recorded {
+            Some(last_recorded) => (0..self.window as u64)
+                .take_while(|n| *n <= last_recorded)
+                .filter(|n| self.history[self.slot_index(last_recorded - n)])
+                .count() as u32,
+            None => 0,
+        }
+    }
+
+    /// Returns true if the neuron fired on the previous cycle
+    fn fired_on_prev_cycle(&self, current_cycle: u64) -> bool {
+        self.fired_n_cycles_ago(current_cycle, 1).unwrap_or(false)
+    }
+
+    /// Returns true if the neuron fired two cycles ago
+    fn fired_on_prev_prev(&self, current_cycle: u64) -> bool {
+        self.fired_n_cycles_ago(current_cycle, 2).unwrap_or(false)
+    }
+
+    /// Returns true if the neuron fired on any of
+    /// the last_n cycles before current_cycle
+    fn fired_within(&self, current_cycle: u64, last_n: u32) -> bool {
+        (1..=last_n).any(|n| self.fired_n_cycles_ago(current_cycle, n).unwrap_or(false))
+    }
+
+    /// Whether the neuron fired on cycle and on each cycle before
+    /// it, as far back as the history goes, latest first
+    fn recent_fires(&self, cycle: u64) -> Vec<bool> {
+        (1..self.history.len() as u32)
+            .map(|n| self.fired_n_cycles_ago(cycle + 1, n).unwrap_or(false))
+            .collect()
+    }
+
+    /// Replaces the history with fires, as recorded by
+    /// recent_fires, as if cycle were the last cycle recorded
+    fn restore_recent_fires(&mut self, cycle: u64, fires: &[bool]) {
+        self.history.iter_mut().for_each(|slot| *slot = false);
+
+        for (n, fired) in fires.iter().take(self.history.len() - 1).enumerate() {
+            if let Some(fire_cycle) = cycle.checked_sub(n as u64) {
+                let index = self.slot_index(fire_cycle);
+                self.history[index] = *fired;
+            }
+        }
+
+        self.last_recorded = Some(cycle);
+    }
+
+    /// Sets the tracker for the current cycle, clearing the
+    /// slots of any cycles skipped since the last record
+    fn set_tracker(&mut self, current_cycle: u64, fired: bool) {
+        match self.last_recorded {
+            Some(last_recorded) if last_recorded < current_cycle => {
+                let skipped = (current_cycle - last_recorded - 1).min(self.history.len() as u64);
+
+                for cycle in (current_cycle - skipped)..current_cycle {
+                    let index = self.slot_index(cycle);
+                    self.history[index] = false;
+                }
+            }
+            Some(last_recorded) if last_recorded > current_cycle => {
+                self.history.iter_mut().for_each(|slot| *slot = false)
+            }
+            _ => {}
+        }
+
+        let index = self.slot_index(current_cycle);
+        self.history[index] = fired;
+        self.last_recorded = Some(current_cycle);
+    }
+}
+
+/// A neuron that sends encoded sensory information into
+/// an encephalon
+pub struct SensoryNeuron {
     encephalon: Rc<Encephalon>,
-    internal_charge: RefCell<InternalCharge>,
-    fire_threshold: f32,
-    fire_tracker: RefCell<FireTracker>,
+    period: RefCell<u32>,       //This is the period at which the neuron fires
+    period_scale: RefCell<f32>, //Scales the period, as set by sensor gates
     max_plastic_synapses: usize,
     plastic_synapses: RefCell<Vec<PlasticSynapse>>,
     static_synapses: RefCell<Vec<StaticSynapse>>,
+    fire_tracker: RefCell<FireTracker>,
     synaptic_strength_generator: Rc<dyn Fn() -> Box<RefCell<dyn SynapticStrength>>>,
-    synapse_type_threshold: f32,
-    ema: RefCell<f32>, //Exponential moving average, ie T(n+1) = αI + (1 - α)T(n)
-    alpha: f32,        //The constant of the exponential moving average
-    loc: Vec<i32>,
+    polarity_policy: PolarityPolicy,
+    polarity: SynapticType, //The polarity of this neuron under PerNeuron
+    ema: RefCell<f32>,      //Exponential moving average, ie T(n+1) = αI + (1 - α)T(n)
+    alpha: f32,             //The constant of the exponential moving average
+    ema_floor: f32,
+    loc: Loc,
 }
 
-impl PlasticNeuron {
+impl SensoryNeuron {
     pub fn new(
         encephalon: Rc<Encephalon>,
-        fire_threshold: f32,
-        max_plastic_synapses: usize,
         synaptic_strength_generator: Rc<dyn Fn() -> Box<RefCell<dyn SynapticStrength>>>,
-        synapse_type_threshold: f32,
-        alpha: f32, //The constant of the exponential moving average
-        loc: Vec<i32>,
-    ) -> PlasticNeuron {
-        PlasticNeuron {
+        params: SensoryParams,
+        loc: Loc,
+    ) -> SensoryNeuron {
+        let polarity = params.polarity_policy.neuron_polarity(&encephalon);
+        let fire_history = encephalon.pairing_window() + 1;
+
+        SensoryNeuron {
             encephalon,
-            fire_threshold,
-            internal_charge: RefCell::new(InternalCharge::new()),
-            fire_tracker: RefCell::new(FireTracker::new()),
-            max_plastic_synapses,
+            period: RefCell::new(0),
+            period_scale: RefCell::new(1.0),
+            max_plastic_synapses: params.max_plastic_synapses,
             plastic_synapses: RefCell::new(Vec::new()),
             static_synapses: RefCell::new(Vec::new()),
+            fire_tracker: RefCell::new(FireTracker::remembering(params.fire_window, fire_history)),
             synaptic_strength_generator,
-            synapse_type_threshold,
+            polarity_policy: params.polarity_policy,
+            polarity,
             ema: RefCell::new(0.0),
-            alpha,
+            alpha: params.alpha,
+            ema_floor: params.ema_floor,
             loc,
         }
     }
-}
 
-impl Neuronic for PlasticNeuron {
-    fn run_cycle(&self) -> f32 {
-        self.prune_synapses();
-        self.form_plastic_synapse();
+    /// Gets the location of this neuron within the ecp geometry
+    pub fn loc(&self) -> Loc {
+        self.loc
+    }
 
-        let current_cycle = self.encephalon.get_charge_cycle();
-        let mut internal_charge = self.internal_charge.borrow_mut();
+    /// Returns the number of times the neuron fired
+    /// within its fire tracking window
+    pub fn recent_fire_count(&self) -> u32 {
+        self.fire_tracker.borrow().fire_count_in_window()
+    }
+
+    /// Returns true if the neuron fired on the
+    /// cycle before cycle
+    pub fn fired_on_prev_cycle(&self, cycle: u64) -> bool {
+        self.fire_tracker.borrow().fired_on_prev_cycle(cycle)
+    }
+
+    /// Gets the period at which this neuron is firing
+    pub fn period(&self) -> u32 {
+        *self.period.borrow()
+    }
+
+    /// Sets the period of this neuron, which
+    /// indicates on which cycle values this neuron
+    /// should fire
+    pub fn set_period(&self, period: u32) {
+        *self.period.borrow_mut() = period;
+    }
+
+    /// Gets the period at which this neuron actually fires,
+    /// once its period has been scaled by any sensor gates
+    pub fn effective_period(&self) -> u32 {
+        match *self.period.borrow() {
+            0 => 0,
+            period => ((period as f32 * *self.period_scale.borrow()).round() as u32).max(1),
+        }
+    }
+
+    /// Scales the period at which this neuron fires, without
+    /// touching the period its sensor encoded
+    pub fn set_period_scale(&self, scale: f32) {
+        *self.period_scale.borrow_mut() = scale;
+    }
+}
+
+impl SensoryNeuron {
+    /// Runs the given cycle, short of any plasticity.  Transmit
+    /// is false in eval mode, where the encephalon fires the
+    /// neuron's transmissions in place of its synapses
+    fn step(&self, cycle: u64, transmit: bool) -> CycleOutcome {
         let mut fire_tracker = self.fire_tracker.borrow_mut();
 
         let mut ema = self.ema.borrow_mut();
 
-        if internal_charge.get_charge(current_cycle) > self.fire_threshold {
-            self.fire_synapses();
+        let period = self.effective_period();
+
+        let fired = period != 0 && cycle.is_multiple_of(period as u64);
+        let mut transmissions = 0;
+
+        if fired {
+            if transmit {
+                transmissions = self.fire_synapses(cycle);
+            }
+
             *ema = self.alpha + ((1.0 - self.alpha) * (*ema));
-            fire_tracker.set_tracker(current_cycle, true);
+            fire_tracker.set_tracker(cycle, true);
         } else {
-            *ema = (1.0 - self.alpha) * (*ema);
-            fire_tracker.set_tracker(current_cycle, false);
+            *ema = decayed_ema(*ema, self.alpha, self.ema_floor);
+            fire_tracker.set_tracker(cycle, false);
+        }
+
+        CycleOutcome {
+            fired,
+            ema: *ema,
+            charge_consumed: 0.0,
+            adaptation: 0.0,
+            input_gain: 1.0,
+            plastic_synapses: self.plastic_synapses.borrow().len() as u32,
+            transmissions,
+        }
+    }
+}
+
+impl Neuronic for SensoryNeuron {
+    fn run_cycle(&self, cycle: u64) -> CycleOutcome {
+        if !self.encephalon.plasticity_frozen() {
+            self.prune_synapses(cycle);
+            self.form_plastic_synapse(cycle);
         }
 
-        // println!("This is current ema: {}, and fire_count: {}", *ema, fire_count);
+        self.step(cycle, true)
+    }
+
+    fn run_cycle_eval(&self, cycle: u64) -> CycleOutcome {
+        self.step(cycle, false)
+    }
+
+    fn transmissions(&self) -> Vec<Transmission> {
+        self.frozen_transmissions()
+    }
+
+    fn synapse_records(&self) -> Vec<SynapseRecord> {
+        self.records_from(&self.loc)
+    }
+
+    fn plastic_strengths(&self) -> Vec<f32> {
+        self.get_plastic_synapses()
+            .iter()
+            .map(|synapse| synapse.normalized_strength())
+            .collect()
+    }
+
+    fn adopt_plastic_synapse(&self, synapse: PlasticSynapse) -> bool {
+        self.plastic_synapses.borrow_mut().push(synapse);
+        self.encephalon.plastic_synapse_formed();
+        true
+    }
+
+    fn weakest_plastic_strength(&self) -> Option<f32> {
+        weakest_strength(&self.plastic_synapses)
+    }
+
+    fn evict_weakest_plastic_synapse(&self, cycle: u64) -> bool {
+        evict_weakest(&self.encephalon, &self.plastic_synapses, &self.loc, cycle)
+    }
+
+    fn plastic_synapse_snapshots(&self) -> Vec<PlasticSynapseSnapshot> {
+        self.plastic_snapshots_from(&self.loc)
+    }
 
-        internal_charge.reset_charge(current_cycle);
+    fn restore_plastic_synapses(&self, synapses: Vec<RestoredSynapse>) -> bool {
+        *self.plastic_synapses.borrow_mut() =
+            restored_synapses(self.synaptic_strength_generator.as_ref(), synapses);
+        true
+    }
 
-        ema.clone()
+    fn adopt_static_synapse(&self, synapse: StaticSynapse) -> bool {
+        self.push_static_synapse(synapse);
+        true
     }
 }
 
-impl RxNeuronic for PlasticNeuron {
-    fn intake_synaptic_impulse(&self, impulse: f32) {
-        self.internal_charge
-            .borrow_mut()
-            .incr_next_charge(self.encephalon.get_charge_cycle(), impulse);
+impl fmt::Debug for SensoryNeuron {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SensoryNeuron")
+            .field("loc", &self.loc)
+            .field("period", &self.effective_period())
+            .field("ema", &*self.ema.borrow())
+            .field("plastic_synapses", &self.plastic_synapses.borrow().len())
+            .field("static_synapses", &self.static_synapses.borrow().len())
+            .finish_non_exhaustive()
     }
+}
 
-    fn fired_on_prev_cycle(&self) -> bool {
-        self.fire_tracker
-            .borrow()
-            .fired_on_prev_cycle(self.encephalon.get_charge_cycle())
+impl HasEma for SensoryNeuron {
+    fn ema(&self) -> f32 {
+        *self.ema.borrow()
+    }
+
+    fn set_ema(&self, ema: f32) {
+        *self.ema.borrow_mut() = ema;
     }
 }
 
-impl NeuronicRx for PlasticNeuron {}
+impl HasDynamics for SensoryNeuron {
+    fn dynamics(&self, cycle: u64) -> NeuronDynamics {
+        NeuronDynamics {
+            recent_fires: self.fire_tracker.borrow().recent_fires(cycle),
+            ..NeuronDynamics::new(self.loc.into())
+        }
+    }
 
-impl TxNeuronic for PlasticNeuron {
-    fn add_static_synapse(
-        &self,
-        strength: f32,
-        synaptic_type: SynapticType,
-        target_neuron: Rc<dyn NeuronicRx>,
-    ) {
-        self.static_synapses.borrow_mut().push(StaticSynapse::new(
-            strength,
-            synaptic_type,
-            target_neuron,
-        ));
+    fn set_dynamics(&self, cycle: u64, dynamics: &NeuronDynamics) {
+        self.fire_tracker
+            .borrow_mut()
+            .restore_recent_fires(cycle, &dynamics.recent_fires);
+    }
+}
+
+impl TxNeuronic for SensoryNeuron {
+    fn push_static_synapse(&self, synapse: StaticSynapse) {
+        self.static_synapses.borrow_mut().push(synapse);
     }
 
-    fn get_plastic_synapses(&self) -> Ref<Vec<PlasticSynapse>> {
+    fn get_plastic_synapses(&self) -> Ref<'_, Vec<PlasticSynapse>> {
         self.plastic_synapses.borrow()
     }
 
-    fn get_static_synapses(&self) -> Ref<Vec<StaticSynapse>> {
+    fn get_static_synapses(&self) -> Ref<'_, Vec<StaticSynapse>> {
         self.static_synapses.borrow()
     }
 }
 
-impl FxNeuronic for PlasticNeuron {
-    fn prune_synapses(&self) {
-        let synapses_fired = self.fired_on_prev_prev();
+impl FxNeuronic for SensoryNeuron {
+    fn prune_synapses(&self, cycle: u64) {
+        let window = self.encephalon.pairing_window();
+        let synapses_fired = self.fired_cycles_before(cycle, window + 1);
+        let reversed = self.encephalon.plasticity_reversed();
+        let learning_rate = self.encephalon.learning_rate();
         let mut synapses = self.plastic_synapses.borrow_mut();
 
         synapses.retain(|synapse| {
             if synapses_fired {
-                if synapse.target.fired_on_prev_cycle() {
-                    synapse.strengthen();
+                if synapse.target.fired_within(cycle, window) != reversed {
+                    synapse.strengthen(learning_rate);
                 } else {
-                    synapse.decay();
+                    synapse.decay(learning_rate);
                 }
             }
-            synapse.connected()
+            let prune = self.encephalon.should_prune(&synapse.info(cycle));
+
+            if prune {
+                self.encephalon.plastic_synapse_pruned();
+                journal_synapse(
+                    &self.encephalon,
+                    EventKind::Pruning,
+                    cycle,
+                    &self.loc,
+                    synapse,
+                );
+            }
+            !prune
         })
     }
 
-    fn form_plastic_synapse(&self) {
+    fn form_plastic_synapse(&self, cycle: u64) {
         let mut plastic_synapses = self.plastic_synapses.borrow_mut();
-
         if plastic_synapses.len() < self.max_plastic_synapses {
-            let new_target_neuron = self.encephalon.local_random_neuron(&self.loc);
-
-            let synapse_type = match *self.ema.borrow() < self.synapse_type_threshold {
-                true => SynapticType::Excitatory,
-                false => SynapticType::Inhibitory,
-            };
+            let new_target_neuron = unconnected_target(
+                &self.encephalon,
+                NeuronKind::Sensory,
+                self.loc,
+                &plastic_synapses,
+                None,
+            );
+
+            let synapse_type = self.polarity_policy.synapse_type(
+                *self.ema.borrow(),
+                self.polarity,
+                &self.encephalon,
+            );
+
+            // Under a synapse budget, the synapse only forms if there's room
+            let new_target_neuron =
+                new_target_neuron.filter(|_| self.encephalon.make_room_for_plastic_synapse(cycle));
 
             if let Some(neuron_ref) = new_target_neuron {
-                let new_synapse = PlasticSynapse::new(
-                    (self.synaptic_strength_generator)(),
-                    synapse_type,
-                    neuron_ref,
+                let strength = (self.synaptic_strength_generator)();
+                self.encephalon.init_strength(&strength);
+
+                let new_synapse = PlasticSynapse::new(strength, synapse_type, neuron_ref, cycle);
+                journal_synapse(
+                    &self.encephalon,
+                    EventKind::Formation,
+                    cycle,
+                    &self.loc,
+                    &new_synapse,
                 );
 
                 plastic_synapses.push(new_synapse);
+                self.encephalon.plastic_synapse_formed();
             }
         }
     }
 
-    fn fired_on_prev_prev(&self) -> bool {
+    fn fired_on_prev_prev(&self, cycle: u64) -> bool {
+        self.fire_tracker.borrow().fired_on_prev_prev(cycle)
+    }
+
+    fn fired_cycles_before(&self, cycle: u64, n: u32) -> bool {
         self.fire_tracker
             .borrow()
-            .fired_on_prev_prev(self.encephalon.get_charge_cycle())
+            .fired_n_cycles_ago(cycle, n)
+            .unwrap_or(false)
+    }
+}
+
+/// A neuron that receives impulses but only
+/// sends its average frequency (calculated via EMA)
+/// to an ActuatorInterface
+pub struct ActuatorNeuron {
+    fire_tracker: RefCell<FireTracker>,
+    internal_charge: RefCell<ChargeBuffer>,
+    fire_threshold: f32,
+    threshold_mode: ThresholdMode,
+    ema: RefCell<f32>, //Exponential moving average, ie T(n+1) = αI + (1 - α)T(n)
+    alpha: f32,        //The constant of the exponential moving average
+    ema_floor: f32,
+    loc: Loc,
+    input_gain: RefCell<f32>, //Scales every incoming impulse
+    synaptic_scaling: Option<SynapticScaling>,
+    impulse_tally: RefCell<Option<ImpulseTally>>, //Charge by source class, while armed
+    last_cycle_fired: Cell<bool>,
+    last_cycle_charge: Cell<f32>, //Charge taken on the last cycle, before any reset
+}
+
+/// The magnitude of the impulses an actuator neuron received
+/// from each class of synapse while its tally was armed
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ImpulseTally {
+    pub reflex: f32,
+    pub plastic: f32,
+    pub other_static: f32,
+}
+
+impl ActuatorNeuron {
+    pub fn new(params: ActuatorParams, loc: Loc) -> ActuatorNeuron {
+        ActuatorNeuron {
+            fire_tracker: RefCell::new(FireTracker::new(params.fire_window)),
+            internal_charge: RefCell::new(ChargeBuffer::new(
+                params.max_delay,
+                params.charge_floor,
+                params.inhibition_mode,
+            )),
+            fire_threshold: params.fire_threshold,
+            threshold_mode: params.threshold_mode,
+            ema: RefCell::new(0.0),
+            alpha: params.alpha,
+            ema_floor: params.ema_floor,
+            loc,
+            input_gain: RefCell::new(1.0),
+            synaptic_scaling: params.synaptic_scaling,
+            impulse_tally: RefCell::new(None),
+            last_cycle_fired: Cell::new(false),
+            last_cycle_charge: Cell::new(0.0),
+        }
+    }
+
+    /// The gain applied to every impulse this neuron receives
+    pub fn input_gain(&self) -> f32 {
+        *self.input_gain.borrow()
+    }
+
+    /// Starts tallying the impulses this neuron receives
+    /// by the class of synapse that sent them
+    pub fn arm_impulse_tally(&self) {
+        *self.impulse_tally.borrow_mut() = Some(ImpulseTally::default());
+    }
+
+    /// Stops tallying impulses, returning the tally
+    /// since the neuron was armed
+    pub fn take_impulse_tally(&self) -> Option<ImpulseTally> {
+        self.impulse_tally.borrow_mut().take()
+    }
+
+    /// Gets the location of this neuron within the ecp geometry
+    pub fn loc(&self) -> Loc {
+        self.loc
+    }
+
+    /// Lets fired_within look back at least cycles cycles,
+    /// for neurons pairing over a wider window
+    pub(crate) fn remember_fires(&self, cycles: u32) {
+        self.fire_tracker.borrow_mut().remember(cycles);
+    }
+
+    /// Reads this actuator neuron's EMA firing frequency
+    pub fn read_ema_frequency(&self) -> f32 {
+        *self.ema.borrow()
+    }
+
+    /// True if this neuron fired the last time it was cycled
+    pub fn last_cycle_fired(&self) -> bool {
+        self.last_cycle_fired.get()
+    }
+
+    /// The internal charge this neuron took the last time
+    /// it was cycled, normalized by its fire threshold
+    pub fn last_cycle_charge(&self) -> f32 {
+        self.last_cycle_charge.get() / self.fire_threshold
+    }
+}
+
+impl Neuronic for ActuatorNeuron {
+    fn run_cycle(&self, cycle: u64) -> CycleOutcome {
+        let charge = self.internal_charge.borrow_mut().take_charge(cycle);
+        let mut ema = self.ema.borrow_mut();
+        let mut fire_tracker = self.fire_tracker.borrow_mut();
+
+        let fired = self.threshold_mode.crosses(charge, self.fire_threshold);
+        self.last_cycle_fired.set(fired);
+        self.last_cycle_charge.set(charge);
+
+        if fired {
+            *ema = self.alpha + ((1.0 - self.alpha) * (*ema));
+            fire_tracker.set_tracker(cycle, true);
+        } else {
+            *ema = decayed_ema(*ema, self.alpha, self.ema_floor);
+            fire_tracker.set_tracker(cycle, false);
+        }
+
+        let mut input_gain = self.input_gain.borrow_mut();
+        if let Some(scaling) = &self.synaptic_scaling {
+            *input_gain = scaling.adjust(*input_gain, *ema);
+        }
+
+        CycleOutcome {
+            fired,
+            ema: *ema,
+            charge_consumed: charge,
+            adaptation: 0.0,
+            input_gain: *input_gain,
+            plastic_synapses: 0,
+            transmissions: 0,
+        }
+    }
+}
+
+impl RxNeuronic for ActuatorNeuron {
+    fn intake_delayed_impulse(
+        &self,
+        cycle: u64,
+        impulse: f32,
+        synaptic_type: SynapticType,
+        delay: u32,
+    ) {
+        let mut internal_charge = self.internal_charge.borrow_mut();
+        let delay = delay.clamp(1, internal_charge.max_delay());
+
+        let gained = impulse * *self.input_gain.borrow();
+
+        #[cfg(feature = "audit")]
+        crate::audit::record(|ledger| {
+            ledger.gained += synaptic_type.signed(gained - impulse) as f64
+        });
+
+        internal_charge.intake_at(cycle + delay as u64, gained, synaptic_type);
+    }
+
+    fn tally_impulse(&self, source: ImpulseSource, impulse: f32) {
+        if let Some(tally) = self.impulse_tally.borrow_mut().as_mut() {
+            let impulse = impulse.abs();
+
+            match source {
+                ImpulseSource::Reflex => tally.reflex += impulse,
+                ImpulseSource::Plastic => tally.plastic += impulse,
+                ImpulseSource::Static => tally.other_static += impulse,
+            }
+        }
+    }
+
+    fn fired_on_prev_cycle(&self, cycle: u64) -> bool {
+        self.fire_tracker.borrow().fired_on_prev_cycle(cycle)
+    }
+
+    fn fired_within(&self, cycle: u64, last_n: u32) -> bool {
+        self.fire_tracker.borrow().fired_within(cycle, last_n)
+    }
+
+    fn kind(&self) -> NeuronKind {
+        NeuronKind::Actuator
+    }
+
+    fn peek_charge(&self, cycle: u64) -> f32 {
+        self.internal_charge.borrow().peek_charge(cycle)
+    }
+
+    fn shift_charge(&self, cycle: u64, delta: f32) {
+        self.internal_charge
+            .borrow_mut()
+            .incr_charge_at(cycle, delta);
+    }
+
+    fn recent_fire_count(&self) -> u32 {
+        self.fire_tracker.borrow().fire_count_in_window()
+    }
+
+    fn loc(&self) -> Loc {
+        self.loc
+    }
+}
+
+impl fmt::Debug for ActuatorNeuron {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ActuatorNeuron")
+            .field("loc", &self.loc)
+            .field("fire_threshold", &self.fire_threshold)
+            .field("ema", &*self.ema.borrow())
+            .field("input_gain", &*self.input_gain.borrow())
+            .field("last_cycle_fired", &self.last_cycle_fired.get())
+            .finish_non_exhaustive()
+    }
+}
+
+impl HasEma for ActuatorNeuron {
+    fn ema(&self) -> f32 {
+        *self.ema.borrow()
+    }
+
+    fn set_ema(&self, ema: f32) {
+        *self.ema.borrow_mut() = ema;
+    }
+}
+
+impl HasDynamics for ActuatorNeuron {
+    fn dynamics(&self, cycle: u64) -> NeuronDynamics {
+        NeuronDynamics {
+            pending_charge: self.internal_charge.borrow().pending_after(cycle),
+            pending_inhibition: self
+                .internal_charge
+                .borrow()
+                .pending_inhibition_after(cycle),
+            recent_fires: self.fire_tracker.borrow().recent_fires(cycle),
+            input_gain: *self.input_gain.borrow(),
+            ..NeuronDynamics::new(self.loc.into())
+        }
+    }
+
+    fn set_dynamics(&self, cycle: u64, dynamics: &NeuronDynamics) {
+        let mut internal_charge = self.internal_charge.borrow_mut();
+        internal_charge.restore_pending(cycle, &dynamics.pending_charge);
+        internal_charge.restore_pending_inhibition(cycle, &dynamics.pending_inhibition);
+        self.fire_tracker
+            .borrow_mut()
+            .restore_recent_fires(cycle, &dynamics.recent_fires);
+        *self.input_gain.borrow_mut() = dynamics.input_gain;
+    }
+}
+
+impl NeuronicRx for ActuatorNeuron {}
+
+/// The state and plasticity shared by every kind of plastic neuron,
+/// which differ only in how their charge makes them fire.  Holds the
+/// neuron's charge, firing history, ema, input gain, and synapses,
+/// and forms, strengthens, and prunes its plastic synapses
+struct PlasticCore {
+    encephalon: Rc<Encephalon>,
+    internal_charge: RefCell<ChargeBuffer>,
+    fire_tracker: RefCell<FireTracker>,
+    max_plastic_synapses: usize,
+    plastic_synapses: RefCell<Vec<PlasticSynapse>>,
+    static_synapses: RefCell<Vec<StaticSynapse>>,
+    synaptic_strength_generator: Rc<dyn Fn() -> Box<RefCell<dyn SynapticStrength>>>,
+    polarity_policy: PolarityPolicy,
+    polarity: SynapticType, //The polarity of this neuron under PerNeuron
+    ema: RefCell<f32>,      //Exponential moving average, ie T(n+1) = αI + (1 - α)T(n)
+    alpha: f32,             //The constant of the exponential moving average
+    ema_floor: f32,
+    loc: Loc,
+    outgoing_total: Option<f32>, //Total strength outgoing plastic synapses are normalized to
+    local_radius: Option<u32>,   //Radius synapses are formed within, if not the nearby neurons
+    input_gain: RefCell<f32>,    //Scales every incoming impulse
+    synaptic_scaling: Option<SynapticScaling>,
+}
+
+impl PlasticCore {
+    fn new(
+        encephalon: Rc<Encephalon>,
+        synaptic_strength_generator: Rc<dyn Fn() -> Box<RefCell<dyn SynapticStrength>>>,
+        params: &PlasticParams,
+        loc: Loc,
+    ) -> PlasticCore {
+        let polarity = params.polarity_policy.neuron_polarity(&encephalon);
+        let fire_history = encephalon.pairing_window() + 1;
+
+        PlasticCore {
+            encephalon,
+            internal_charge: RefCell::new(ChargeBuffer::new(
+                params.max_delay,
+                params.charge_floor,
+                params.inhibition_mode,
+            )),
+            fire_tracker: RefCell::new(FireTracker::remembering(params.fire_window, fire_history)),
+            max_plastic_synapses: params.max_plastic_synapses,
+            plastic_synapses: RefCell::new(Vec::new()),
+            static_synapses: RefCell::new(Vec::new()),
+            synaptic_strength_generator,
+            polarity_policy: params.polarity_policy,
+            polarity,
+            ema: RefCell::new(0.0),
+            alpha: params.alpha,
+            ema_floor: params.ema_floor,
+            loc,
+            outgoing_total: params.outgoing_total,
+            local_radius: params.local_radius,
+            input_gain: RefCell::new(1.0),
+            synaptic_scaling: params.synaptic_scaling,
+        }
+    }
+
+    /// Takes the charge that arrives on the given cycle
+    fn take_charge(&self, cycle: u64) -> f32 {
+        self.internal_charge.borrow_mut().take_charge(cycle)
+    }
+
+    /// Records whether the neuron fired on the given cycle, updating
+    /// its ema and then its input gain under synaptic scaling.  Returns
+    /// the CycleOutcome of the cycle, short of the adaptation value
+    fn record_cycle(
+        &self,
+        cycle: u64,
+        fired: bool,
+        charge: f32,
+        transmissions: u32,
+    ) -> CycleOutcome {
+        let mut ema = self.ema.borrow_mut();
+
+        if fired {
+            *ema = self.alpha + ((1.0 - self.alpha) * (*ema));
+        } else {
+            *ema = decayed_ema(*ema, self.alpha, self.ema_floor);
+        }
+
+        self.fire_tracker.borrow_mut().set_tracker(cycle, fired);
+
+        let mut input_gain = self.input_gain.borrow_mut();
+        if let Some(scaling) = &self.synaptic_scaling {
+            *input_gain = scaling.adjust(*input_gain, *ema);
+        }
+
+        CycleOutcome {
+            fired,
+            ema: *ema,
+            charge_consumed: charge,
+            adaptation: 0.0,
+            input_gain: *input_gain,
+            plastic_synapses: self.plastic_synapses.borrow().len() as u32,
+            transmissions,
+        }
+    }
+
+    fn plastic_strengths(&self) -> Vec<f32> {
+        self.plastic_synapses
+            .borrow()
+            .iter()
+            .map(|synapse| synapse.normalized_strength())
+            .collect()
+    }
+
+    fn adopt_plastic_synapse(&self, synapse: PlasticSynapse) -> bool {
+        self.plastic_synapses.borrow_mut().push(synapse);
+        self.encephalon.plastic_synapse_formed();
+        true
+    }
+
+    fn weakest_plastic_strength(&self) -> Option<f32> {
+        weakest_strength(&self.plastic_synapses)
+    }
+
+    fn evict_weakest_plastic_synapse(&self, cycle: u64) -> bool {
+        evict_weakest(&self.encephalon, &self.plastic_synapses, &self.loc, cycle)
+    }
+
+    fn restore_plastic_synapses(&self, synapses: Vec<RestoredSynapse>) {
+        *self.plastic_synapses.borrow_mut() =
+            restored_synapses(self.synaptic_strength_generator.as_ref(), synapses);
+    }
+
+    fn intake_delayed_impulse(
+        &self,
+        cycle: u64,
+        impulse: f32,
+        synaptic_type: SynapticType,
+        delay: u32,
+    ) {
+        let mut internal_charge = self.internal_charge.borrow_mut();
+        let delay = delay.clamp(1, internal_charge.max_delay());
+
+        let gained = impulse * *self.input_gain.borrow();
+
+        #[cfg(feature = "audit")]
+        crate::audit::record(|ledger| {
+            ledger.gained += synaptic_type.signed(gained - impulse) as f64
+        });
+
+        internal_charge.intake_at(cycle + delay as u64, gained, synaptic_type);
+    }
+
+    fn peek_charge(&self, cycle: u64) -> f32 {
+        self.internal_charge.borrow().peek_charge(cycle)
+    }
+
+    fn shift_charge(&self, cycle: u64, delta: f32) {
+        self.internal_charge
+            .borrow_mut()
+            .incr_charge_at(cycle, delta);
+    }
+
+    /// The dynamics every plastic neuron has, which each kind
+    /// of plastic neuron fills out with its own
+    fn dynamics(&self, cycle: u64) -> NeuronDynamics {
+        NeuronDynamics {
+            pending_charge: self.internal_charge.borrow().pending_after(cycle),
+            pending_inhibition: self
+                .internal_charge
+                .borrow()
+                .pending_inhibition_after(cycle),
+            recent_fires: self.fire_tracker.borrow().recent_fires(cycle),
+            input_gain: *self.input_gain.borrow(),
+            ..NeuronDynamics::new(self.loc.into())
+        }
+    }
+
+    fn set_dynamics(&self, cycle: u64, dynamics: &NeuronDynamics) {
+        let mut internal_charge = self.internal_charge.borrow_mut();
+        internal_charge.restore_pending(cycle, &dynamics.pending_charge);
+        internal_charge.restore_pending_inhibition(cycle, &dynamics.pending_inhibition);
+        self.fire_tracker
+            .borrow_mut()
+            .restore_recent_fires(cycle, &dynamics.recent_fires);
+        *self.input_gain.borrow_mut() = dynamics.input_gain;
+    }
+
+    fn prune_synapses(&self, cycle: u64) {
+        let window = self.encephalon.pairing_window();
+        let synapses_fired = self.fired_cycles_before(cycle, window + 1);
+        let reversed = self.encephalon.plasticity_reversed();
+        let learning_rate = self.encephalon.learning_rate();
+        let mut synapses = self.plastic_synapses.borrow_mut();
+
+        synapses.retain(|synapse| {
+            if synapses_fired {
+                if synapse.target.fired_within(cycle, window) != reversed {
+                    synapse.strengthen(learning_rate);
+                } else {
+                    synapse.decay(learning_rate);
+                }
+            }
+            let prune = self.encephalon.should_prune(&synapse.info(cycle));
+
+            if prune {
+                self.encephalon.plastic_synapse_pruned();
+                journal_synapse(
+                    &self.encephalon,
+                    EventKind::Pruning,
+                    cycle,
+                    &self.loc,
+                    synapse,
+                );
+            }
+            !prune
+        });
+
+        if let Some(target_total) = self.outgoing_total {
+            normalize_strengths(&synapses, target_total);
+        }
+    }
+
+    fn form_plastic_synapse(&self, cycle: u64) {
+        let mut plastic_synapses = self.plastic_synapses.borrow_mut();
+
+        if plastic_synapses.len() < self.max_plastic_synapses {
+            let new_target_neuron = unconnected_target(
+                &self.encephalon,
+                NeuronKind::Plastic,
+                self.loc,
+                &plastic_synapses,
+                self.local_radius,
+            );
+
+            let synapse_type = self.polarity_policy.synapse_type(
+                *self.ema.borrow(),
+                self.polarity,
+                &self.encephalon,
+            );
+
+            // Under a synapse budget, the synapse only forms if there's room
+            let new_target_neuron =
+                new_target_neuron.filter(|_| self.encephalon.make_room_for_plastic_synapse(cycle));
+
+            if let Some(neuron_ref) = new_target_neuron {
+                let strength = (self.synaptic_strength_generator)();
+                self.encephalon.init_strength(&strength);
+
+                let new_synapse = PlasticSynapse::new(strength, synapse_type, neuron_ref, cycle);
+                journal_synapse(
+                    &self.encephalon,
+                    EventKind::Formation,
+                    cycle,
+                    &self.loc,
+                    &new_synapse,
+                );
+
+                plastic_synapses.push(new_synapse);
+                self.encephalon.plastic_synapse_formed();
+            }
+        }
+    }
+
+    fn fired_cycles_before(&self, cycle: u64, n: u32) -> bool {
+        self.fire_tracker
+            .borrow()
+            .fired_n_cycles_ago(cycle, n)
+            .unwrap_or(false)
+    }
+}
+
+/// This is your standard neuron present in the
+/// encephalon.  Basically everything about this
+/// neuron isn't fixed.  It's incoming or outgoing
+/// synapses are subject to change based on its
+/// environment
+pub struct PlasticNeuron {
+    core: PlasticCore,
+    fire_threshold: f32,
+    threshold_mode: ThresholdMode,
+    burst_length: u32, //Number of consecutive cycles fired per threshold crossing
+    burst_remaining: RefCell<u32>, //Cycles left to fire in the current burst
+    adaptation: Adaptation,
+    adaptation_value: RefCell<f32>, //Raises the effective fire threshold after fires
+}
+
+impl PlasticNeuron {
+    pub fn new(
+        encephalon: Rc<Encephalon>,
+        synaptic_strength_generator: Rc<dyn Fn() -> Box<RefCell<dyn SynapticStrength>>>,
+        params: PlasticParams,
+        loc: Loc,
+    ) -> PlasticNeuron {
+        PlasticNeuron {
+            core: PlasticCore::new(encephalon, synaptic_strength_generator, &params, loc),
+            fire_threshold: params.fire_threshold,
+            threshold_mode: params.threshold_mode,
+            burst_length: params.burst_length.max(1),
+            burst_remaining: RefCell::new(0),
+            adaptation: params.adaptation,
+            adaptation_value: RefCell::new(0.0),
+        }
+    }
+
+    /// The gain applied to every impulse this neuron receives
+    pub fn input_gain(&self) -> f32 {
+        *self.core.input_gain.borrow()
+    }
+
+    /// The current adaptation value, which is added to
+    /// the fire threshold
+    pub fn adaptation(&self) -> f32 {
+        *self.adaptation_value.borrow()
+    }
+}
+
+impl PlasticNeuron {
+    /// Runs the given cycle, short of any plasticity.  Transmit
+    /// is false in eval mode, where the encephalon fires the
+    /// neuron's transmissions in place of its synapses
+    fn step(&self, cycle: u64, transmit: bool) -> CycleOutcome {
+        let charge = self.core.take_charge(cycle);
+
+        let mut burst_remaining = self.burst_remaining.borrow_mut();
+        let mut adaptation_value = self.adaptation_value.borrow_mut();
+
+        // A neuron in the middle of a burst fires regardless of its
+        // charge, otherwise crossing the threshold starts a new burst
+        let fired = if *burst_remaining > 0 {
+            *burst_remaining -= 1;
+            true
+        } else if self.threshold_mode.crosses(
+            charge,
+            (self.fire_threshold + *adaptation_value) * self.core.encephalon.fatigue(),
+        ) {
+            *burst_remaining = self.burst_length - 1;
+            true
+        } else {
+            false
+        };
+
+        let transmissions = match fired && transmit {
+            true => self.fire_synapses(cycle),
+            false => 0,
+        };
+
+        *adaptation_value *= self.adaptation.decay;
+        if fired {
+            *adaptation_value += self.adaptation.incr;
+        }
+
+        CycleOutcome {
+            adaptation: *adaptation_value,
+            ..self.core.record_cycle(cycle, fired, charge, transmissions)
+        }
+    }
+}
+
+impl Neuronic for PlasticNeuron {
+    fn run_cycle(&self, cycle: u64) -> CycleOutcome {
+        if !self.core.encephalon.plasticity_frozen() {
+            self.prune_synapses(cycle);
+            self.form_plastic_synapse(cycle);
+        }
+
+        self.step(cycle, true)
+    }
+
+    fn run_cycle_eval(&self, cycle: u64) -> CycleOutcome {
+        self.step(cycle, false)
+    }
+
+    fn transmissions(&self) -> Vec<Transmission> {
+        self.frozen_transmissions()
+    }
+
+    fn synapse_records(&self) -> Vec<SynapseRecord> {
+        self.records_from(&self.core.loc)
+    }
+
+    fn plastic_strengths(&self) -> Vec<f32> {
+        self.core.plastic_strengths()
+    }
+
+    fn adopt_plastic_synapse(&self, synapse: PlasticSynapse) -> bool {
+        self.core.adopt_plastic_synapse(synapse)
+    }
+
+    fn weakest_plastic_strength(&self) -> Option<f32> {
+        self.core.weakest_plastic_strength()
+    }
+
+    fn evict_weakest_plastic_synapse(&self, cycle: u64) -> bool {
+        self.core.evict_weakest_plastic_synapse(cycle)
+    }
+
+    fn plastic_synapse_snapshots(&self) -> Vec<PlasticSynapseSnapshot> {
+        self.plastic_snapshots_from(&self.core.loc)
+    }
+
+    fn restore_plastic_synapses(&self, synapses: Vec<RestoredSynapse>) -> bool {
+        self.core.restore_plastic_synapses(synapses);
+        true
+    }
+
+    fn adopt_static_synapse(&self, synapse: StaticSynapse) -> bool {
+        self.push_static_synapse(synapse);
+        true
+    }
+}
+
+impl RxNeuronic for PlasticNeuron {
+    fn intake_delayed_impulse(
+        &self,
+        cycle: u64,
+        impulse: f32,
+        synaptic_type: SynapticType,
+        delay: u32,
+    ) {
+        self.core
+            .intake_delayed_impulse(cycle, impulse, synaptic_type, delay);
+    }
+
+    fn fired_on_prev_cycle(&self, cycle: u64) -> bool {
+        self.core.fire_tracker.borrow().fired_on_prev_cycle(cycle)
+    }
+
+    fn fired_within(&self, cycle: u64, last_n: u32) -> bool {
+        self.core.fire_tracker.borrow().fired_within(cycle, last_n)
+    }
+
+    fn kind(&self) -> NeuronKind {
+        NeuronKind::Plastic
+    }
+
+    fn peek_charge(&self, cycle: u64) -> f32 {
+        self.core.peek_charge(cycle)
+    }
+
+    fn shift_charge(&self, cycle: u64, delta: f32) {
+        self.core.shift_charge(cycle, delta);
+    }
+
+    fn recent_fire_count(&self) -> u32 {
+        self.core.fire_tracker.borrow().fire_count_in_window()
+    }
+
+    fn loc(&self) -> Loc {
+        self.core.loc
+    }
+}
+
+impl fmt::Debug for PlasticNeuron {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PlasticNeuron")
+            .field("loc", &self.core.loc)
+            .field("fire_threshold", &self.fire_threshold)
+            .field("ema", &*self.core.ema.borrow())
+            .field("adaptation", &*self.adaptation_value.borrow())
+            .field("input_gain", &*self.core.input_gain.borrow())
+            .field(
+                "plastic_synapses",
+                &self.core.plastic_synapses.borrow().len(),
+            )
+            .field("static_synapses", &self.core.static_synapses.borrow().len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl HasEma for PlasticNeuron {
+    fn ema(&self) -> f32 {
+        *self.core.ema.borrow()
+    }
+
+    fn set_ema(&self, ema: f32) {
+        *self.core.ema.borrow_mut() = ema;
+    }
+}
+
+impl HasDynamics for PlasticNeuron {
+    fn dynamics(&self, cycle: u64) -> NeuronDynamics {
+        NeuronDynamics {
+            burst_remaining: *self.burst_remaining.borrow(),
+            adaptation: *self.adaptation_value.borrow(),
+            ..self.core.dynamics(cycle)
+        }
+    }
+
+    fn set_dynamics(&self, cycle: u64, dynamics: &NeuronDynamics) {
+        self.core.set_dynamics(cycle, dynamics);
+        *self.burst_remaining.borrow_mut() = dynamics.burst_remaining;
+        *self.adaptation_value.borrow_mut() = dynamics.adaptation;
+    }
+}
+
+impl NeuronicRx for PlasticNeuron {}
+
+impl TxNeuronic for PlasticNeuron {
+    fn push_static_synapse(&self, synapse: StaticSynapse) {
+        self.core.static_synapses.borrow_mut().push(synapse);
+    }
+
+    fn get_plastic_synapses(&self) -> Ref<'_, Vec<PlasticSynapse>> {
+        self.core.plastic_synapses.borrow()
+    }
+
+    fn get_static_synapses(&self) -> Ref<'_, Vec<StaticSynapse>> {
+        self.core.static_synapses.borrow()
+    }
+}
+
+impl FxNeuronic for PlasticNeuron {
+    fn prune_synapses(&self, cycle: u64) {
+        self.core.prune_synapses(cycle);
+    }
+
+    fn form_plastic_synapse(&self, cycle: u64) {
+        self.core.form_plastic_synapse(cycle);
+    }
+
+    fn fired_on_prev_prev(&self, cycle: u64) -> bool {
+        self.core.fire_tracker.borrow().fired_on_prev_prev(cycle)
+    }
+
+    fn fired_cycles_before(&self, cycle: u64, n: u32) -> bool {
+        self.core.fired_cycles_before(cycle, n)
+    }
+}
+
+/// A plastic neuron whose dynamics follow the two variable
+/// Izhikevich model rather than a simple threshold, which gives
+/// it richer behavior like rebound bursts and chattering.  Its
+/// synapses are formed, strengthened, and pruned just like those
+/// of a PlasticNeuron, so it can take a PlasticNeuron's place
+pub struct IzhikevichNeuron {
+    core: PlasticCore,
+    izh_params: IzhParams,
+    v: RefCell<f32>, //Membrane potential
+    u: RefCell<f32>, //Membrane recovery
+}
+
+impl IzhikevichNeuron {
+    /// The membrane potential at which the neuron fires
+    pub const PEAK: f32 = 30.;
+
+    /// The fire threshold, burst length, and adaptation of
+    /// params are ignored, as the Izhikevich model handles
+    /// all of those on its own
+    pub fn new(
+        encephalon: Rc<Encephalon>,
+        synaptic_strength_generator: Rc<dyn Fn() -> Box<RefCell<dyn SynapticStrength>>>,
+        params: PlasticParams,
+        izh_params: IzhParams,
+        loc: Loc,
+    ) -> IzhikevichNeuron {
+        IzhikevichNeuron {
+            core: PlasticCore::new(encephalon, synaptic_strength_generator, &params, loc),
+            izh_params,
+            v: RefCell::new(izh_params.c),
+            u: RefCell::new(izh_params.b * izh_params.c),
+        }
+    }
+
+    /// The gain applied to every impulse this neuron receives
+    pub fn input_gain(&self) -> f32 {
+        *self.core.input_gain.borrow()
+    }
+
+    /// Gets the location of this neuron within the ecp geometry
+    pub fn loc(&self) -> Loc {
+        self.core.loc
+    }
+
+    /// The current membrane potential, v
+    pub fn membrane_potential(&self) -> f32 {
+        *self.v.borrow()
+    }
+
+    /// The current membrane recovery, u
+    pub fn recovery(&self) -> f32 {
+        *self.u.borrow()
+    }
+}
+
+/// Advances the Izhikevich model by one cycle (1 ms) with
+/// input current i, returning the new v and u and whether
+/// the neuron fired.  v is integrated in two half steps
+/// for numerical stability
+///
+/// ```
+/// use eywa::neuron::{izhikevich_step, IzhParams};
+///
+/// let rs = IzhParams::regular_spiking();
+/// let (v, u, fired) = izhikevich_step(&rs, rs.c, rs.b * rs.c, 10.);
+/// ```
+pub fn izhikevich_step(params: &IzhParams, v: f32, u: f32, i: f32) -> (f32, f32, bool) {
+    let mut v = v;
+
+    for _ in 0..2 {
+        v += 0.5 * (0.04 * v * v + 5. * v + 140. - u + i);
+    }
+
+    let u = u + params.a * (params.b * v - u);
+
+    if v >= IzhikevichNeuron::PEAK {
+        (params.c, u + params.d, true)
+    } else {
+        (v, u, false)
+    }
+}
+
+impl IzhikevichNeuron {
+    /// Runs the given cycle, short of any plasticity.  Transmit
+    /// is false in eval mode, where the encephalon fires the
+    /// neuron's transmissions in place of its synapses
+    fn step(&self, cycle: u64, transmit: bool) -> CycleOutcome {
+        let charge = self.core.take_charge(cycle);
+
+        let mut v = self.v.borrow_mut();
+        let mut u = self.u.borrow_mut();
+
+        // Having no threshold, a fatigued neuron instead
+        // takes in proportionally less current
+        let current = charge * self.izh_params.gain / self.core.encephalon.fatigue();
+        let (next_v, next_u, fired) = izhikevich_step(&self.izh_params, *v, *u, current);
+
+        *v = next_v;
+        *u = next_u;
+
+        let transmissions = match fired && transmit {
+            true => self.fire_synapses(cycle),
+            false => 0,
+        };
+
+        self.core.record_cycle(cycle, fired, charge, transmissions)
+    }
+}
+
+impl Neuronic for IzhikevichNeuron {
+    fn run_cycle(&self, cycle: u64) -> CycleOutcome {
+        if !self.core.encephalon.plasticity_frozen() {
+            self.prune_synapses(cycle);
+            self.form_plastic_synapse(cycle);
+        }
+
+        self.step(cycle, true)
+    }
+
+    fn run_cycle_eval(&self, cycle: u64) -> CycleOutcome {
+        self.step(cycle, false)
+    }
+
+    fn transmissions(&self) -> Vec<Transmission> {
+        self.frozen_transmissions()
+    }
+
+    fn synapse_records(&self) -> Vec<SynapseRecord> {
+        self.records_from(&self.core.loc)
+    }
+
+    fn plastic_strengths(&self) -> Vec<f32> {
+        self.core.plastic_strengths()
+    }
+
+    fn adopt_plastic_synapse(&self, synapse: PlasticSynapse) -> bool {
+        self.core.adopt_plastic_synapse(synapse)
+    }
+
+    fn weakest_plastic_strength(&self) -> Option<f32> {
+        self.core.weakest_plastic_strength()
+    }
+
+    fn evict_weakest_plastic_synapse(&self, cycle: u64) -> bool {
+        self.core.evict_weakest_plastic_synapse(cycle)
+    }
+
+    fn plastic_synapse_snapshots(&self) -> Vec<PlasticSynapseSnapshot> {
+        self.plastic_snapshots_from(&self.core.loc)
+    }
+
+    fn restore_plastic_synapses(&self, synapses: Vec<RestoredSynapse>) -> bool {
+        self.core.restore_plastic_synapses(synapses);
+        true
+    }
+
+    fn adopt_static_synapse(&self, synapse: StaticSynapse) -> bool {
+        self.push_static_synapse(synapse);
+        true
+    }
+}
+
+impl RxNeuronic for IzhikevichNeuron {
+    fn intake_delayed_impulse(
+        &self,
+        cycle: u64,
+        impulse: f32,
+        synaptic_type: SynapticType,
+        delay: u32,
+    ) {
+        self.core
+            .intake_delayed_impulse(cycle, impulse, synaptic_type, delay);
+    }
+
+    fn fired_on_prev_cycle(&self, cycle: u64) -> bool {
+        self.core.fire_tracker.borrow().fired_on_prev_cycle(cycle)
+    }
+
+    fn fired_within(&self, cycle: u64, last_n: u32) -> bool {
+        self.core.fire_tracker.borrow().fired_within(cycle, last_n)
+    }
+
+    fn kind(&self) -> NeuronKind {
+        NeuronKind::Plastic
+    }
+
+    fn peek_charge(&self, cycle: u64) -> f32 {
+        self.core.peek_charge(cycle)
+    }
+
+    fn shift_charge(&self, cycle: u64, delta: f32) {
+        self.core.shift_charge(cycle, delta);
+    }
+
+    fn recent_fire_count(&self) -> u32 {
+        self.core.fire_tracker.borrow().fire_count_in_window()
+    }
+
+    fn loc(&self) -> Loc {
+        self.core.loc
+    }
+}
+
+impl fmt::Debug for IzhikevichNeuron {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("IzhikevichNeuron")
+            .field("loc", &self.core.loc)
+            .field("v", &*self.v.borrow())
+            .field("u", &*self.u.borrow())
+            .field("ema", &*self.core.ema.borrow())
+            .field("input_gain", &*self.core.input_gain.borrow())
+            .field(
+                "plastic_synapses",
+                &self.core.plastic_synapses.borrow().len(),
+            )
+            .field("static_synapses", &self.core.static_synapses.borrow().len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl HasEma for IzhikevichNeuron {
+    fn ema(&self) -> f32 {
+        *self.core.ema.borrow()
+    }
+
+    fn set_ema(&self, ema: f32) {
+        *self.core.ema.borrow_mut() = ema;
+    }
+}
+
+impl HasDynamics for IzhikevichNeuron {
+    fn dynamics(&self, cycle: u64) -> NeuronDynamics {
+        NeuronDynamics {
+            membrane: Some((*self.v.borrow(), *self.u.borrow())),
+            ..self.core.dynamics(cycle)
+        }
+    }
+
+    fn set_dynamics(&self, cycle: u64, dynamics: &NeuronDynamics) {
+        self.core.set_dynamics(cycle, dynamics);
+
+        if let Some((v, u)) = dynamics.membrane {
+            *self.v.borrow_mut() = v;
+            *self.u.borrow_mut() = u;
+        }
+    }
+}
+
+impl NeuronicRx for IzhikevichNeuron {}
+
+impl TxNeuronic for IzhikevichNeuron {
+    fn push_static_synapse(&self, synapse: StaticSynapse) {
+        self.core.static_synapses.borrow_mut().push(synapse);
+    }
+
+    fn get_plastic_synapses(&self) -> Ref<'_, Vec<PlasticSynapse>> {
+        self.core.plastic_synapses.borrow()
+    }
+
+    fn get_static_synapses(&self) -> Ref<'_, Vec<StaticSynapse>> {
+        self.core.static_synapses.borrow()
+    }
+}
+
+impl FxNeuronic for IzhikevichNeuron {
+    fn prune_synapses(&self, cycle: u64) {
+        self.core.prune_synapses(cycle);
+    }
+
+    fn form_plastic_synapse(&self, cycle: u64) {
+        self.core.form_plastic_synapse(cycle);
+    }
+
+    fn fired_on_prev_prev(&self, cycle: u64) -> bool {
+        self.core.fire_tracker.borrow().fired_on_prev_prev(cycle)
+    }
+
+    fn fired_cycles_before(&self, cycle: u64, n: u32) -> bool {
+        self.core.fired_cycles_before(cycle, n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actuator::ActuatorName;
+    use crate::ecp_geometry::{BoxEcp, EcpGeometry};
+    use crate::encephalon::EncephalonBuilder;
+    use crate::test_util::{actuator, sensor};
+
+    fn subtractive_buffer(max_delay: u32) -> ChargeBuffer {
+        ChargeBuffer::new(max_delay, f32::NEG_INFINITY, InhibitionMode::Subtractive)
+    }
+
+    #[test]
+    fn charge_buffer_holds_max_delay_plus_two_slots() {
+        assert_eq!(subtractive_buffer(1).slots.len(), 3);
+        assert_eq!(subtractive_buffer(3).slots.len(), 5);
+
+        // Delays of less than 1 aren't possible
+        assert_eq!(subtractive_buffer(0).max_delay(), 1);
+        assert_eq!(subtractive_buffer(0).slots.len(), 3);
+    }
+
+    #[test]
+    fn shunting_inhibition_scales_rather_than_subtracts() {
+        let mut subtractive = subtractive_buffer(1);
+        let mut shunted =
+            ChargeBuffer::new(1, f32::NEG_INFINITY, InhibitionMode::Shunting { gain: 0.5 });
+
+        // Excitation and inhibition of each cycle
+        let pattern = [(12., 0.), (12., 4.), (12., 12.), (4., 30.), (0., 8.)];
+
+        for (cycle, (excitation, inhibition)) in pattern.iter().enumerate() {
+            let cycle = cycle as u64;
+
+            for buffer in [&mut subtractive, &mut shunted].iter_mut() {
+                buffer.intake_at(cycle, *excitation, SynapticType::Excitatory);
+                buffer.intake_at(cycle, *inhibition, SynapticType::Inhibitory);
+            }
+
+            let expected = excitation / (1. + 0.5 * inhibition);
+
+            assert_eq!(subtractive.take_charge(cycle), excitation - inhibition);
+            assert!((shunted.take_charge(cycle) - expected).abs() < 1e-6);
+            assert!(expected >= 0.);
+        }
+    }
+
+    #[test]
+    fn delayed_deposits_arrive_on_their_cycle() {
+        let mut buffer = subtractive_buffer(3);
+
+        // Deposits delayed by 1, 2 and 3 cycles from cycle 10
+        buffer.incr_charge_at(11, 1.);
+        buffer.incr_charge_at(12, 2.);
+        buffer.incr_charge_at(13, 3.);
+        buffer.incr_charge_at(13, 0.5);
+
+        assert_eq!(buffer.peek_charge(12), 2.);
+        assert_eq!(buffer.pending_after(10), vec![1., 2., 3.5, 0.]);
+
+        let taken: Vec<f32> = (11..=14).map(|cycle| buffer.take_charge(cycle)).collect();
+
+        assert_eq!(taken, vec![1., 2., 3.5, 0.]);
+    }
+
+    #[test]
+    fn charge_buffer_slot_is_zeroed_once_taken() {
+        // A max_delay of 3 takes 5 slots, so cycles 11 and 16 share one
+        let mut buffer = subtractive_buffer(3);
+        buffer.incr_charge_at(11, 1.);
+
+        assert_eq!(buffer.take_charge(11), 1.);
+        assert_eq!(buffer.take_charge(11), 0.);
+        assert_eq!(buffer.peek_charge(16), 0.);
+
+        // So the next cycle to wrap around to it starts from nothing
+        buffer.incr_charge_at(16, 4.);
+
+        assert_eq!(buffer.take_charge(16), 4.);
+        assert!(buffer.slots.iter().all(|slot| *slot == 0.));
+    }
+
+    #[test]
+    fn charge_buffer_wraps_around_many_times() {
+        let mut buffer = subtractive_buffer(4);
+
+        // Every cycle deposits charge delayed by 1 through 4 cycles,
+        // so each cycle from 4 on collects one deposit of each delay
+        for cycle in 0..1_000u64 {
+            if cycle >= 4 {
+                let expected: f32 = (1..=4)
+                    .map(|delay| (cycle - delay) as f32 * delay as f32)
+                    .sum();
+
+                assert_eq!(buffer.take_charge(cycle), expected);
+            } else {
+                buffer.take_charge(cycle);
+            }
+
+            for delay in 1..=4 {
+                buffer.incr_charge_at(cycle + delay, cycle as f32 * delay as f32);
+            }
+        }
+    }
+
+    /// A tracker of window that recorded cycles 0 through
+    /// cycles - 1, firing on those for which fires is true
+    fn tracked(window: u32, cycles: u64, fires: fn(u64) -> bool) -> FireTracker {
+        let mut fire_tracker = FireTracker::new(window);
+
+        for cycle in 0..cycles {
+            fire_tracker.set_tracker(cycle, fires(cycle));
+        }
+
+        fire_tracker
+    }
+
+    #[test]
+    fn fire_window_of_two_only_remembers_two_cycles() {
+        assert_eq!(tracked(2, 10, |_| true).fire_count_in_window(), 2);
+        assert_eq!(tracked(2, 11, |cycle| cycle < 10).fire_count_in_window(), 1);
+        assert_eq!(tracked(2, 12, |cycle| cycle < 10).fire_count_in_window(), 0);
+
+        // Windows of less than 2 still answer prev and prev-prev queries
+        let fire_tracker = tracked(0, 10, |cycle| cycle == 8);
+
+        assert!(fire_tracker.fired_on_prev_prev(10));
+        assert!(!fire_tracker.fired_on_prev_cycle(10));
+    }
+
+    #[test]
+    fn fire_window_of_64_wraps_around() {
+        // 200 cycles wrap around the history three times over
+        let even = tracked(64, 200, |cycle| cycle % 2 == 0);
+
+        assert_eq!(even.fire_count_in_window(), 32);
+        assert!(!even.fired_on_prev_cycle(200));
+        assert!(even.fired_on_prev_cycle(199));
+        assert!(even.fired_on_prev_prev(200));
+        assert!(even.fired_within(200, 2));
+
+        // Cycles 136 through 199 hold 21 multiples of 3
+        assert_eq!(
+            tracked(64, 200, |cycle| cycle % 3 == 0).fire_count_in_window(),
+            21
+        );
+    }
+
+    #[test]
+    fn fire_tracking_alternates_across_even_and_odd_cycles() {
+        for start in [0, 1].iter() {
+            let mut fire_tracker = FireTracker::new(4);
+
+            for cycle in *start..*start + 100 {
+                let odd = cycle % 2 == 1;
+                fire_tracker.set_tracker(cycle, odd);
+
+                assert_eq!(fire_tracker.fired_on_prev_cycle(cycle + 1), odd);
+                assert_eq!(
+                    fire_tracker.fired_on_prev_prev(cycle + 1),
+                    !odd && cycle > *start
+                );
+            }
+
+            assert_eq!(fire_tracker.fire_count_in_window(), 2);
+        }
+    }
+
+    /// A tracker of window 4 that fired on cycles 0 through 9,
+    /// missed the next gap cycles, and fired on the one after
+    fn fired_after_gap(gap: u64) -> FireTracker {
+        let mut fire_tracker = tracked(4, 10, |_| true);
+        fire_tracker.set_tracker(10 + gap, true);
+
+        fire_tracker
+    }
+
+    #[test]
+    fn a_missed_cycle_reads_as_silent() {
+        let fire_tracker = tracked(4, 10, |_| true);
+
+        // Before anything is recorded for the missed cycle 10
+        assert!(!fire_tracker.fired_on_prev_cycle(11));
+        assert!(fire_tracker.fired_on_prev_prev(11));
+
+        let fire_tracker = fired_after_gap(1);
+
+        assert!(fire_tracker.fired_on_prev_cycle(12));
+        assert!(!fire_tracker.fired_on_prev_prev(12));
+        assert_eq!(fire_tracker.fire_count_in_window(), 3);
+    }
+
+    #[test]
+    fn two_missed_cycles_read_as_silent() {
+        let fire_tracker = fired_after_gap(2);
+
+        assert!(fire_tracker.fired_on_prev_cycle(13));
+        assert!(!fire_tracker.fired_on_prev_prev(13));
+        assert!(!fire_tracker.fired_within(12, 2));
+        assert_eq!(fire_tracker.fire_count_in_window(), 2);
+    }
+
+    #[test]
+    fn a_gap_longer_than_the_history_forgets_it() {
+        let fire_tracker = fired_after_gap(17);
+
+        assert!(fire_tracker.fired_on_prev_cycle(28));
+        assert!(!fire_tracker.fired_on_prev_prev(28));
+        assert_eq!(fire_tracker.fire_count_in_window(), 1);
+    }
+
+    /// The cycles on which a neuron fires under constant current
+    /// i over 1000 cycles, and its v at the end
+    fn izhikevich_spikes(params: &IzhParams, i: f32) -> (Vec<u32>, f32) {
+        let (mut v, mut u) = (params.c, params.b * params.c);
+        let mut spikes = Vec::new();
+
+        for cycle in 0..1000 {
+            let (next_v, next_u, fired) = izhikevich_step(params, v, u, i);
+            v = next_v;
+            u = next_u;
+
+            if fired {
+                spikes.push(cycle);
+            }
+        }
+
+        (spikes, v)
+    }
+
+    fn intervals(spikes: &[u32]) -> Vec<u32> {
+        spikes.windows(2).map(|pair| pair[1] - pair[0]).collect()
+    }
+
+    #[test]
+    fn izhikevich_step_follows_the_equations() {
+        // One step from rest, worked by hand from the equations
+        let (v, u, fired) = izhikevich_step(&IzhParams::regular_spiking(), -65., -13., 0.);
+
+        assert!((v - -67.805).abs() < 1e-3);
+        assert!((u - -13.01122).abs() < 1e-4);
+        assert!(!fired);
+    }
+
+    #[test]
+    fn regular_spiking_neurons_rest_and_fire_tonically() {
+        let rs = IzhParams::regular_spiking();
+
+        // Without current, a regular spiking neuron settles at about -70
+        let (silent, v) = izhikevich_spikes(&rs, 0.);
+
+        assert!(silent.is_empty());
+        assert!((v - -70.).abs() < 0.5);
+
+        // Under constant current it fires tonically, its first
+        // interval shorter than those after it adapts
+        let tonic = intervals(&izhikevich_spikes(&rs, 10.).0);
+
+        assert!(tonic.len() >= 10);
+        assert!(tonic[1..].iter().all(|interval| *interval > tonic[0]));
+    }
+
+    #[test]
+    fn chattering_neurons_fire_bursts() {
+        let chattering = intervals(&izhikevich_spikes(&IzhParams::chattering(), 10.).0);
+
+        assert!(chattering.iter().any(|interval| *interval <= 5));
+        assert!(chattering.iter().any(|interval| *interval >= 40));
+    }
+
+    #[test]
+    fn synaptic_scaling_turns_down_a_bombarded_neuron() {
+        let scaling = SynapticScaling {
+            target_ema: 0.05,
+            rate: 0.01,
+            ..SynapticScaling::default()
+        };
+        // Without plastic synapses, the motor only
+        // ever sees the charge injected into it
+        let encephalon = EncephalonBuilder::new()
+            .synaptic_scaling(scaling)
+            .max_plastic_synapses(0)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+
+        let motor = encephalon
+            .actuator_loc(&ActuatorName::from("motor"))
+            .unwrap();
+        let probe = encephalon.add_probe("motor", motor.into()).unwrap();
+
+        for _ in 0..3000 {
+            encephalon.inject_charge(&motor, 50.).unwrap();
+            encephalon.run_cycle();
+        }
+
+        // It fires just often enough to hold its EMA around the
+        // target over the last 500 cycles the probe holds
+        let trace = encephalon.probe_trace(probe).unwrap();
+        let settled = &trace[trace.len() - 500..];
+        let mean_ema = settled.iter().map(|sample| sample.ema).sum::<f32>() / 500.;
+
+        assert!(trace.last().unwrap().input_gain < 0.5);
+        assert!((mean_ema - 0.05).abs() < 0.02);
+    }
+
+    fn seeded_brain() -> Rc<Encephalon> {
+        EncephalonBuilder::new()
+            .seed(7)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("silent", 0.)],
+                vec![actuator("motor")],
+            )
+            .unwrap()
+    }
+
+    fn inhibitory_fraction(types: &[SynapticType]) -> f32 {
+        let inhibitory = types
+            .iter()
+            .filter(|t| matches!(t, SynapticType::Inhibitory));
+
+        inhibitory.count() as f32 / types.len() as f32
+    }
+
+    #[test]
+    fn quiet_neurons_spread_excitation() {
+        let encephalon = seeded_brain();
+        let by_ema = PolarityPolicy::SourceEmaThreshold { threshold: 0.1 };
+
+        assert!(matches!(
+            by_ema.synapse_type(0.0, SynapticType::Excitatory, &encephalon),
+            SynapticType::Excitatory
+        ));
+        assert!(matches!(
+            by_ema.synapse_type(0.5, SynapticType::Excitatory, &encephalon),
+            SynapticType::Inhibitory
+        ));
+        assert!(matches!(
+            PolarityPolicy::AlwaysExcitatory.synapse_type(
+                0.5,
+                SynapticType::Inhibitory,
+                &encephalon
+            ),
+            SynapticType::Excitatory
+        ));
+    }
+
+    #[test]
+    fn a_fixed_ratio_of_synapses_are_inhibitory() {
+        let encephalon = seeded_brain();
+        let ratio = PolarityPolicy::FixedRatio {
+            inhibitory_fraction: 0.25,
+        };
+        let types: Vec<SynapticType> = (0..2000)
+            .map(|_| ratio.synapse_type(0.0, SynapticType::Excitatory, &encephalon))
+            .collect();
+
+        assert!((inhibitory_fraction(&types) - 0.25).abs() < 0.05);
+    }
+
+    #[test]
+    fn per_neuron_synapses_share_the_polarity_of_their_source() {
+        let encephalon = seeded_brain();
+        let per_neuron = PolarityPolicy::PerNeuron {
+            inhibitory_fraction: 0.25,
+        };
+        let polarities: Vec<SynapticType> = (0..2000)
+            .map(|_| per_neuron.neuron_polarity(&encephalon))
+            .collect();
+
+        for &polarity in polarities.iter() {
+            let synapse_type = per_neuron.synapse_type(0.5, polarity, &encephalon);
+
+            assert_eq!(
+                matches!(synapse_type, SynapticType::Inhibitory),
+                matches!(polarity, SynapticType::Inhibitory)
+            );
+        }
+
+        // And a fixed ratio of neurons are inhibitory
+        assert!((inhibitory_fraction(&polarities) - 0.25).abs() < 0.05);
+    }
+
+    #[test]
+    fn interneurons_only_inhibit_their_neighbors() {
+        let params = InterneuronParams::default().plastic_params(PlasticParams {
+            polarity_policy: PolarityPolicy::AlwaysExcitatory,
+            local_radius: None,
+            ..PlasticParams::default()
+        });
+
+        assert_eq!(params.polarity_policy, PolarityPolicy::AlwaysInhibitory);
+        assert_eq!(params.local_radius, Some(1));
+    }
+
+    #[test]
+    fn neurons_count_their_fires_within_the_window() {
+        let params = ActuatorParams {
+            fire_window: 64,
+            ..ActuatorParams::default()
+        };
+        let neuron = ActuatorNeuron::new(params, Loc::new(&[0, 0, 0]));
+
+        for cycle in 0..200 {
+            if cycle % 2 == 0 {
+                neuron.shift_charge(cycle, 20.);
+            }
+
+            assert_eq!(neuron.run_cycle(cycle).fired, cycle % 2 == 0);
+        }
+
+        assert_eq!(neuron.recent_fire_count(), 32);
+    }
+
+    #[test]
+    fn fired_follows_the_threshold_for_a_known_charge_sequence() {
+        // The impulses sent to the neuron on each cycle, which
+        // it takes in on the next.  Negative impulses inhibit
+        let impulses: [&[f32]; 6] = [&[4.], &[6., 5.], &[10.], &[3., 8., -2.], &[12., -1.], &[]];
+
+        for threshold_mode in [ThresholdMode::Exclusive, ThresholdMode::Inclusive].iter() {
+            let params = ActuatorParams {
+                fire_threshold: 10.,
+                threshold_mode: *threshold_mode,
+                ..ActuatorParams::default()
+            };
+            let neuron = ActuatorNeuron::new(params, Loc::new(&[0, 0, 0]));
+
+            for (cycle, sent) in impulses.iter().enumerate() {
+                let cycle = cycle as u64;
+
+                for impulse in sent.iter() {
+                    let synaptic_type = match *impulse < 0. {
+                        true => SynapticType::Inhibitory,
+                        false => SynapticType::Excitatory,
+                    };
+                    neuron.intake_synaptic_impulse(cycle, impulse.abs(), synaptic_type);
+                }
+
+                let charge: f32 = sent.iter().sum();
+                let outcome = neuron.run_cycle(cycle + 1);
+
+                assert_eq!(outcome.charge_consumed, charge);
+                assert_eq!(outcome.fired, threshold_mode.crosses(charge, 10.));
+            }
+        }
+    }
+
+    /// Whether an actuator neuron fires on each of the six cycles
+    /// from start, given a delayed impulse sent on the first
+    fn fires_from(start: u64) -> Vec<bool> {
+        let params = ActuatorParams {
+            max_delay: 3,
+            ..ActuatorParams::default()
+        };
+        let neuron = ActuatorNeuron::new(params, Loc::new(&[0, 0, 0]));
+
+        neuron.intake_delayed_impulse(start, 20., SynapticType::Excitatory, 3);
+
+        let fired: Vec<bool> = (start..start + 6)
+            .map(|cycle| neuron.run_cycle(cycle).fired)
+            .collect();
+
+        assert!(neuron.fired_on_prev_cycle(start + 4));
+        assert!(!neuron.fired_on_prev_cycle(start + 6));
+        fired
+    }
+
+    #[test]
+    fn only_relative_cycles_matter_not_their_parity() {
+        let expected = vec![false, false, false, true, false, false];
+
+        assert_eq!(fires_from(0), expected);
+        assert_eq!(fires_from(1_000_001), expected);
+    }
+
+    #[test]
+    fn a_seeded_brain_runs_the_same_way_every_time() {
+        let run = || {
+            let encephalon = EncephalonBuilder::new()
+                .seed(29)
+                .max_delay(2)
+                .build(
+                    Box::new(BoxEcp::new(125, 1, 1, 27)),
+                    vec![sensor("light", 0.7)],
+                    vec![actuator("motor")],
+                )
+                .unwrap();
+
+            (0..301).map(|_| encephalon.run_cycle()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn decaying_ema_snaps_to_zero_beneath_the_floor() {
+        let mut ema = 1.0;
+        let mut silent_cycles = 0;
+
+        while ema > 0. {
+            ema = decayed_ema(ema, 0.02, DEFAULT_EMA_FLOOR);
+            silent_cycles += 1;
+        }
+
+        // 0.98^684 is the first power beneath 1e-6
+        assert_eq!(silent_cycles, 684);
+        assert_eq!(decayed_ema(ema, 0.02, DEFAULT_EMA_FLOOR), 0.);
+    }
+
+    #[test]
+    fn denormal_emas_snap_to_zero_without_a_floor() {
+        assert_eq!(decayed_ema(f32::MIN_POSITIVE, 0.02, 0.), 0.);
+    }
+
+    #[test]
+    fn silent_neurons_form_excitatory_synapses() {
+        let encephalon = EncephalonBuilder::new()
+            .max_plastic_synapses(26)
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.5)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+        let policy = PolarityPolicy::SourceEmaThreshold { threshold: 0. };
+
+        for _ in 0..100 {
+            assert_eq!(
+                policy.synapse_type(0., SynapticType::Excitatory, &encephalon),
+                SynapticType::Excitatory
+            );
+        }
     }
 }
@@ -1,36 +1,133 @@
 use std::boxed::Box;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::fmt;
 use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
 use synaptic_strength::SynapticStrength;
 
-use crate::neuron::NeuronicRx;
+use crate::loc::Loc;
+use crate::neuron::{NeuronKind, NeuronicRx};
+use crate::snapshot::PlasticSynapseSnapshot;
 
 /// All synapses have the capability to fire
 pub trait Synapse {
-    /// Fires the synapse. Pretty basic
-    fn fire(&self);
+    /// Fires the synapse during the given cycle. Pretty basic
+    fn fire(&self, cycle: u64);
 }
 
 /// A synapse can strengthen and weaken in different
 /// ways, and the synaptic_strength module provides a toolbox
 /// of different methods or curves used for synaptic strength
 pub mod synaptic_strength {
+    use rand::Rng;
+
     pub trait SynapticStrength {
         /// Simply return the strength of the synapse
         fn get_strength(&self) -> f32;
+        /// The strength the synapse approaches as it's strengthened,
+        /// which puts strengths of different kinds on the same scale
+        fn max_strength(&self) -> f32;
+        /// The strength of the synapse as a fraction of its max
+        /// strength, which lies within [0, 1] for every kind of
+        /// strength no matter how it's strengthened or weakened
+        ///
+        /// ```
+        /// use eywa::prelude::*;
+        ///
+        /// let strength = EmStrength::new(30., 1., 0.2);
+        /// assert!((0. ..=1.).contains(&strength.normalized()));
+        /// ```
+        fn normalized(&self) -> f32 {
+            self.get_strength() / self.max_strength()
+        }
         /// Strengthen the synapse by one increment
         fn strengthen(&mut self);
         /// Weaken the synapse by one increment
         fn weaken(&mut self);
+        /// Strengthen the synapse by rate increments, where
+        /// a rate of 1 is the same as strengthen
+        fn strengthen_by(&mut self, _rate: f32) {
+            self.strengthen();
+        }
+        /// Weaken the synapse by rate increments, where
+        /// a rate of 1 is the same as weaken
+        fn weaken_by(&mut self, _rate: f32) {
+            self.weaken();
+        }
         /// Returns whether the synaptic strength is
         /// above the weakness threshold
         fn above_weakness_threshold(&self) -> bool;
+        /// Multiplies the strength of the synapse by factor
+        fn scale(&mut self, factor: f32);
+        /// Moves the synapse to position x along its strength
+        /// curve, which is how a StrengthInit spreads out the
+        /// strengths of new synapses.  Strengths without a
+        /// notion of position ignore this
+        fn init_at(&mut self, _x: f32) {}
+        /// Moves the synapse to wherever along its strength curve
+        /// it has the given strength, which is how imported synapses
+        /// start at their recorded strength.  By default this scales
+        /// the current strength, so a strength of 0 stays put
+        fn set_from_strength(&mut self, strength: f32) {
+            let current = self.get_strength();
+
+            if current > 0. {
+                self.scale(strength / current);
+            }
+        }
+        /// Where the synapse lies along its strength curve, which is
+        /// all a snapshot needs to put it back exactly.  Strengths
+        /// without a notion of position report their strength
+        fn position(&self) -> f32 {
+            self.get_strength()
+        }
+        /// Puts the synapse back at a position given by position
+        fn set_position(&mut self, position: f32) {
+            self.set_from_strength(position);
+        }
+    }
+
+    /// Where along its strength curve a newly formed
+    /// plastic synapse starts
+    #[derive(Copy, Clone, Debug, Default, PartialEq)]
+    pub enum StrengthInit {
+        /// Wherever the synaptic strength generator starts it
+        #[default]
+        Default,
+        /// At an x drawn uniformly from [min_x, max_x)
+        UniformRandom { min_x: f32, max_x: f32 },
+        /// At an x drawn from a normal distribution
+        Gaussian { mean: f32, std: f32 },
+    }
+
+    impl StrengthInit {
+        /// Draws the x a new synapse starts at, or None
+        /// if the synapse should be left where it is
+        pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<f32> {
+            match self {
+                StrengthInit::Default => None,
+                StrengthInit::UniformRandom { min_x, max_x } => match min_x < max_x {
+                    true => Some(rng.gen_range(*min_x, *max_x)),
+                    false => Some(*min_x),
+                },
+                StrengthInit::Gaussian { mean, std } => {
+                    // Box-Muller transform of two uniform samples
+                    let u1: f32 = 1. - rng.gen::<f32>();
+                    let u2: f32 = rng.gen();
+                    let z = (-2. * u1.ln()).sqrt() * (2. * std::f32::consts::PI * u2).cos();
+
+                    Some(mean + std * z)
+                }
+            }
+        }
     }
 
     /// This synaptic strength follows a sigmoid curve,
     /// so strengthen moves the x_value to the right by
     /// a fixed margin, and weaken moves the x_value to the
     /// left by that same margin
+    #[derive(Debug)]
     pub struct SigmoidStrength {
         x_value: f32,
         x_incr: f32,
@@ -67,7 +164,11 @@ pub mod synaptic_strength {
 
     impl SynapticStrength for SigmoidStrength {
         fn get_strength(&self) -> f32 {
-            self.max_value / (1.0 + (-1. * self.x_value).exp())
+            self.max_value / (1.0 + (-self.x_value).exp())
+        }
+
+        fn max_strength(&self) -> f32 {
+            self.max_value
         }
 
         fn strengthen(&mut self) {
@@ -78,9 +179,40 @@ pub mod synaptic_strength {
             self.x_value -= self.x_incr;
         }
 
+        fn strengthen_by(&mut self, rate: f32) {
+            self.x_value += self.x_incr * rate;
+        }
+
+        fn weaken_by(&mut self, rate: f32) {
+            self.x_value -= self.x_incr * rate;
+        }
+
         fn above_weakness_threshold(&self) -> bool {
             self.get_strength() > self.weakness_threshold
         }
+
+        /// Solves for the x_value whose strength is the scaled
+        /// strength.  Strengths at or beyond max_value can't be
+        /// reached, so they're capped just beneath it
+        fn scale(&mut self, factor: f32) {
+            let strength = (self.get_strength() * factor)
+                .max(f32::MIN_POSITIVE)
+                .min(self.max_value * (1. - f32::EPSILON));
+
+            self.x_value = -(self.max_value / strength - 1.).ln();
+        }
+
+        fn init_at(&mut self, x: f32) {
+            self.x_value = x;
+        }
+
+        fn position(&self) -> f32 {
+            self.x_value
+        }
+
+        fn set_position(&mut self, position: f32) {
+            self.x_value = position;
+        }
     }
 
     /// This type of strength strengthens or weakens
@@ -91,6 +223,7 @@ pub mod synaptic_strength {
     /// I'm calling it Em (Exponential moving) strength because
     /// it's somewhat similar in implementation to an Exponential
     /// Moving Average
+    #[derive(Debug)]
     pub struct EmStrength {
         strength: f32,
         max_value: f32,
@@ -131,6 +264,10 @@ pub mod synaptic_strength {
             self.strength
         }
 
+        fn max_strength(&self) -> f32 {
+            self.max_value
+        }
+
         fn strengthen(&mut self) {
             self.strength += self.alpha * (self.max_value - self.strength);
         }
@@ -139,9 +276,35 @@ pub mod synaptic_strength {
             self.strength -= self.alpha * self.strength;
         }
 
+        /// The step is capped at the full distance to max_value,
+        /// so a large rate can't overshoot it
+        fn strengthen_by(&mut self, rate: f32) {
+            self.strength += (self.alpha * rate).min(1.) * (self.max_value - self.strength);
+        }
+
+        /// The step is capped at the full strength, so
+        /// a large rate can't push the strength below 0
+        fn weaken_by(&mut self, rate: f32) {
+            self.strength -= (self.alpha * rate).min(1.) * self.strength;
+        }
+
         fn above_weakness_threshold(&self) -> bool {
             self.strength > self.weakness_threshold
         }
+
+        fn scale(&mut self, factor: f32) {
+            self.strength = (self.strength * factor).min(self.max_value);
+        }
+
+        /// Here x is the fraction of max_value the strength
+        /// starts at, so new synapses start at an x of 0.5
+        fn init_at(&mut self, x: f32) {
+            self.strength = self.max_value * x.clamp(0., 1.);
+        }
+
+        fn set_from_strength(&mut self, strength: f32) {
+            self.strength = strength.clamp(0., self.max_value);
+        }
     }
 }
 
@@ -149,34 +312,52 @@ pub mod synaptic_strength {
 /// neuron's internal charge, inhibitory synapses
 /// decrease their target neuron's internal charge
 /// to prevent the neuron from firing
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SynapticType {
     Excitatory,
     Inhibitory,
 }
 
 impl SynapticType {
-    /// Returns the integer modifier which is multiplied
-    /// by the synapse strength to produce the impulse passed
-    /// to the target neuron during synapse firing
-    fn get_synapse_modifier(&self) -> i8 {
+    /// Gives impulse the sign of this type, ie the change in
+    /// charge an impulse of that size makes when subtracted
+    pub fn signed(self, impulse: f32) -> f32 {
         match self {
-            Self::Excitatory => 1,
-            Self::Inhibitory => -1,
+            Self::Excitatory => impulse,
+            Self::Inhibitory => -impulse,
         }
     }
 }
 
+impl fmt::Display for SynapticType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Excitatory => write!(f, "excitatory"),
+            Self::Inhibitory => write!(f, "inhibitory"),
+        }
+    }
+}
+
+/// Identifies the target of a synapse by the target's location
+pub type TargetKey = Loc;
+
+/// A plastic synapse recorded in a snapshot, along
+/// with the neuron it's restored onto
+pub type RestoredSynapse<'a> = (&'a PlasticSynapseSnapshot, Rc<dyn NeuronicRx>);
+
 /// This is a synapse that changes in strength
 /// over time depending on the extent to which
 /// it's firing it correlated with it's targets
-/// firing. Can be excitatory or inhibitory.  If
-/// this synapse strength passes beneath its
-/// weakness threshold, it dissolves
+/// firing. Can be excitatory or inhibitory.  The
+/// synapse dissolves once the encephalon's prune policy
+/// says so, which by default is when its strength passes
+/// beneath its weakness threshold
 pub struct PlasticSynapse {
     strength: Box<RefCell<dyn SynapticStrength>>,
     synaptic_type: SynapticType,
     pub target: Rc<dyn NeuronicRx>,
+    formed: u64,                       //The cycle the synapse was formed on
+    last_fired: Rc<Cell<Option<u64>>>, //The last cycle the synapse fired on
 }
 
 impl PlasticSynapse {
@@ -184,26 +365,77 @@ impl PlasticSynapse {
         strength: Box<RefCell<dyn SynapticStrength>>,
         synaptic_type: SynapticType,
         target: Rc<dyn NeuronicRx>,
+        formed: u64,
     ) -> PlasticSynapse {
         PlasticSynapse {
             strength,
             synaptic_type,
             target,
+            formed,
+            last_fired: Rc::new(Cell::new(None)),
+        }
+    }
+
+    /// Rebuilds a synapse recorded in a snapshot onto target, with
+    /// a strength of the same kind the synapse was recorded with
+    pub fn restored(
+        snapshot: &PlasticSynapseSnapshot,
+        strength: Box<RefCell<dyn SynapticStrength>>,
+        target: Rc<dyn NeuronicRx>,
+    ) -> PlasticSynapse {
+        strength.borrow_mut().set_position(snapshot.position);
+
+        PlasticSynapse {
+            strength,
+            synaptic_type: snapshot.synaptic_type,
+            target,
+            formed: snapshot.formed,
+            last_fired: Rc::new(Cell::new(snapshot.last_fired)),
+        }
+    }
+
+    /// Records the synapse, which sits at source, for a snapshot
+    pub fn snapshot(&self, source: &[i32]) -> PlasticSynapseSnapshot {
+        PlasticSynapseSnapshot {
+            source: source.to_vec(),
+            target: self.target_key().to_vec(),
+            synaptic_type: self.synaptic_type,
+            position: self.strength.borrow().position(),
+            formed: self.formed,
+            last_fired: self.last_fired.get(),
+        }
+    }
+
+    /// Describes the state of the synapse during cycle,
+    /// which is what prune policies decide on
+    pub fn info(&self, cycle: u64) -> SynapseInfo {
+        let strength = self.strength.borrow();
+
+        SynapseInfo {
+            cycle,
+            strength: strength.get_strength(),
+            normalized_strength: strength.normalized(),
+            above_weakness_threshold: strength.above_weakness_threshold(),
+            synaptic_type: self.synaptic_type,
+            formed: self.formed,
+            last_fired: self.last_fired.get(),
         }
     }
 
     /// Strengthens the connection of the synapse, which
     /// means it both lasts longer, and imparts a greater
-    /// impulse on its target whilst firing
-    pub fn strengthen(&self) {
-        self.strength.borrow_mut().strengthen();
+    /// impulse on its target whilst firing.  The step is
+    /// scaled by the encephalon's learning rate
+    pub fn strengthen(&self, learning_rate: f32) {
+        self.strength.borrow_mut().strengthen_by(learning_rate);
     }
 
     /// Weakens the connection of the synapse, which means
     /// it decreases its lifetime and imparts a smaller
-    /// impulse on its target whilst firing
-    pub fn decay(&self) {
-        self.strength.borrow_mut().weaken();
+    /// impulse on its target whilst firing.  The step is
+    /// scaled by the encephalon's learning rate
+    pub fn decay(&self, learning_rate: f32) {
+        self.strength.borrow_mut().weaken_by(learning_rate);
     }
 
     /// Returns whether the synapse is still connected,
@@ -212,17 +444,100 @@ impl PlasticSynapse {
     pub fn connected(&self) -> bool {
         self.strength.borrow().above_weakness_threshold()
     }
+
+    /// The key of the synapse's target neuron
+    pub fn target_key(&self) -> TargetKey {
+        self.target.loc()
+    }
+
+    /// Gets the current strength of the synapse
+    pub fn strength(&self) -> f32 {
+        self.strength.borrow().get_strength()
+    }
+
+    /// Gets the current strength of the synapse as
+    /// a fraction of the strongest it could become
+    pub fn normalized_strength(&self) -> f32 {
+        self.strength.borrow().normalized()
+    }
+
+    /// Multiplies the strength of the synapse by factor
+    pub fn scale(&self, factor: f32) {
+        self.strength.borrow_mut().scale(factor);
+    }
+
+    pub fn synaptic_type(&self) -> SynapticType {
+        self.synaptic_type
+    }
+
+    /// The synapse frozen at its current strength
+    pub fn transmission(&self) -> Transmission {
+        Transmission {
+            target: Rc::clone(&self.target),
+            impulse: self.strength(),
+            synaptic_type: self.synaptic_type,
+            source: ImpulseSource::Plastic,
+            last_fired: Some(Rc::clone(&self.last_fired)),
+        }
+    }
+}
+
+/// Rescales a group of plastic synapses multiplicatively
+/// so that their strengths sum to target_total.  This makes
+/// the synapses compete, as one synapse can only strengthen
+/// at the expense of the others
+pub fn normalize_strengths(synapses: &[PlasticSynapse], target_total: f32) {
+    let total: f32 = synapses.iter().map(PlasticSynapse::strength).sum();
+
+    if total > 0. {
+        let factor = target_total / total;
+
+        for synapse in synapses {
+            synapse.scale(factor);
+        }
+    }
+}
+
+impl fmt::Debug for PlasticSynapse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PlasticSynapse")
+            .field("strength", &self.strength())
+            .field("normalized_strength", &self.normalized_strength())
+            .field("synaptic_type", &self.synaptic_type)
+            .field("target", &self.target_key())
+            .field("formed", &self.formed)
+            .field("last_fired", &self.last_fired.get())
+            .finish()
+    }
 }
 
 impl Synapse for PlasticSynapse {
-    fn fire(&self) {
-        let impulse = self.strength.borrow().get_strength()
-            * (self.synaptic_type.get_synapse_modifier() as f32);
+    fn fire(&self, cycle: u64) {
+        self.last_fired.set(Some(cycle));
+
+        let impulse = self.strength.borrow().get_strength();
+        let signed = self.synaptic_type.signed(impulse);
+
+        #[cfg(feature = "audit")]
+        crate::audit::record(|ledger| ledger.emitted += signed as f64);
 
-        self.target.intake_synaptic_impulse(impulse);
+        self.target
+            .intake_synaptic_impulse(cycle, impulse, self.synaptic_type);
+        self.target.tally_impulse(ImpulseSource::Plastic, signed);
     }
 }
 
+/// The class of synapse an impulse was sent by
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ImpulseSource {
+    /// A static synapse formed from a reflex
+    Reflex,
+    /// A plastic synapse
+    Plastic,
+    /// Any other static synapse
+    Static,
+}
+
 /// This is a synapse that remains fixed
 /// throughout time.  It has a constant
 /// strength and a constant target
@@ -230,6 +545,7 @@ pub struct StaticSynapse {
     strength: f32,
     synaptic_type: SynapticType,
     target: Rc<dyn NeuronicRx>,
+    source: ImpulseSource,
 }
 
 impl StaticSynapse {
@@ -242,13 +558,405 @@ impl StaticSynapse {
             strength,
             synaptic_type,
             target,
+            source: ImpulseSource::Static,
         }
     }
+
+    /// A static synapse formed from a reflex
+    pub fn reflex(
+        strength: f32,
+        synaptic_type: SynapticType,
+        target: Rc<dyn NeuronicRx>,
+    ) -> StaticSynapse {
+        StaticSynapse {
+            source: ImpulseSource::Reflex,
+            ..StaticSynapse::new(strength, synaptic_type, target)
+        }
+    }
+
+    /// Whether the synapse was formed from a reflex
+    pub fn source(&self) -> ImpulseSource {
+        self.source
+    }
+
+    /// The key of the synapse's target neuron
+    pub fn target_key(&self) -> TargetKey {
+        self.target.loc()
+    }
+
+    pub fn strength(&self) -> f32 {
+        self.strength
+    }
+
+    pub fn synaptic_type(&self) -> SynapticType {
+        self.synaptic_type
+    }
+
+    /// The synapse as a transmission, which never changes
+    pub fn transmission(&self) -> Transmission {
+        Transmission {
+            target: Rc::clone(&self.target),
+            impulse: self.strength,
+            synaptic_type: self.synaptic_type,
+            source: self.source,
+            last_fired: None,
+        }
+    }
+}
+
+impl fmt::Debug for StaticSynapse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaticSynapse")
+            .field("strength", &self.strength)
+            .field("synaptic_type", &self.synaptic_type)
+            .field("target", &self.target_key())
+            .field("source", &self.source)
+            .finish()
+    }
 }
 
 impl Synapse for StaticSynapse {
-    fn fire(&self) {
-        let impulse = self.strength * (self.synaptic_type.get_synapse_modifier() as f32);
-        self.target.intake_synaptic_impulse(impulse);
+    fn fire(&self, cycle: u64) {
+        let signed = self.synaptic_type.signed(self.strength);
+
+        #[cfg(feature = "audit")]
+        crate::audit::record(|ledger| ledger.emitted += signed as f64);
+
+        self.target
+            .intake_synaptic_impulse(cycle, self.strength, self.synaptic_type);
+        self.target.tally_impulse(self.source, signed);
+    }
+}
+
+/// A synapse reduced to the impulse it sends its target, as
+/// the encephalon caches its synapses in eval mode.  Firing a
+/// transmission has just the effect of firing its synapse, so
+/// long as the synapse's strength hasn't changed since
+pub struct Transmission {
+    target: Rc<dyn NeuronicRx>,
+    impulse: f32,
+    synaptic_type: SynapticType,
+    source: ImpulseSource,
+    last_fired: Option<Rc<Cell<Option<u64>>>>, //Plastic synapses note when they last fired
+}
+
+impl Synapse for Transmission {
+    fn fire(&self, cycle: u64) {
+        if let Some(last_fired) = &self.last_fired {
+            last_fired.set(Some(cycle));
+        }
+
+        let signed = self.synaptic_type.signed(self.impulse);
+
+        #[cfg(feature = "audit")]
+        crate::audit::record(|ledger| ledger.emitted += signed as f64);
+
+        self.target
+            .intake_synaptic_impulse(cycle, self.impulse, self.synaptic_type);
+        self.target.tally_impulse(self.source, signed);
+    }
+}
+
+/// A description of a single synapse, used to
+/// export the connectome of an encephalon
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SynapseRecord {
+    pub source: Vec<i32>,
+    pub target: Vec<i32>,
+    pub strength: f32,
+    pub normalized_strength: Option<f32>, //Static synapses have no max strength to normalize by
+    pub synaptic_type: SynapticType,
+    pub plastic: bool,
+}
+
+/// A synapse to wire into an encephalon as it's built, from the
+/// neuron at src_loc onto the neuron at dst_loc.  Plastic synapses
+/// start at initial_strength and learn from there, while static
+/// synapses keep it.  Every record of an exported connectome is a
+/// spec, so one brain's wiring can be imported into another
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SynapseSpec {
+    pub src_loc: Vec<i32>,
+    pub dst_loc: Vec<i32>,
+    pub synaptic_type: SynapticType,
+    pub initial_strength: f32,
+    pub plastic: bool,
+}
+
+impl From<SynapseRecord> for SynapseSpec {
+    fn from(record: SynapseRecord) -> SynapseSpec {
+        SynapseSpec {
+            src_loc: record.source,
+            dst_loc: record.target,
+            synaptic_type: record.synaptic_type,
+            initial_strength: record.strength,
+            plastic: record.plastic,
+        }
+    }
+}
+
+/// The state of a plastic synapse during a cycle
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SynapseInfo {
+    pub cycle: u64,
+    pub strength: f32,
+    pub normalized_strength: f32,
+    pub above_weakness_threshold: bool,
+    pub synaptic_type: SynapticType,
+    pub formed: u64,
+    pub last_fired: Option<u64>,
+}
+
+impl SynapseInfo {
+    /// Number of cycles since the synapse last fired,
+    /// or since it formed if it has never fired
+    pub fn idle_cycles(&self) -> u64 {
+        self.cycle
+            .saturating_sub(self.last_fired.unwrap_or(self.formed))
+    }
+}
+
+/// Decides when a plastic synapse dissolves
+///
+/// ```
+/// use eywa::prelude::*;
+///
+/// /// Dissolves inhibitory synapses that haven't fired in a while
+/// struct QuietInhibition;
+///
+/// impl PrunePolicy for QuietInhibition {
+///     fn should_prune(&self, info: &SynapseInfo) -> bool {
+///         info.synaptic_type == SynapticType::Inhibitory && info.idle_cycles() > 100
+///     }
+/// }
+/// ```
+pub trait PrunePolicy {
+    /// True if the synapse should dissolve
+    fn should_prune(&self, info: &SynapseInfo) -> bool;
+}
+
+/// Dissolves synapses whose strength has fallen
+/// beneath their weakness threshold
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct StrengthThreshold;
+
+impl PrunePolicy for StrengthThreshold {
+    fn should_prune(&self, info: &SynapseInfo) -> bool {
+        !info.above_weakness_threshold
+    }
+}
+
+/// Dissolves synapses that haven't fired
+/// in more than max_idle_cycles cycles
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Staleness {
+    pub max_idle_cycles: u64,
+}
+
+impl PrunePolicy for Staleness {
+    fn should_prune(&self, info: &SynapseInfo) -> bool {
+        info.idle_cycles() > self.max_idle_cycles
+    }
+}
+
+/// What becomes of a plastic synapse that would form while the
+/// encephalon holds as many plastic synapses as its global budget
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BudgetPolicy {
+    /// The synapse doesn't form
+    #[default]
+    Refuse,
+
+    /// The weakest plastic synapse of sample_size randomly chosen
+    /// neurons dissolves to make room for the new synapse.  This is
+    /// only approximately the globally weakest synapse, but it costs
+    /// a handful of lookups rather than a sweep of every synapse
+    CompetitiveReplacement { sample_size: usize },
+}
+
+/// Which kinds of neuron may form plastic synapses onto which.
+/// Interneurons count as plastic neurons on either side, and by
+/// default every kind of source may target every kind of rx neuron.
+/// Synapses wired in by hand (ie initial synapses) aren't checked
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetingRules {
+    pub sensory_to_plastic: bool,
+    pub sensory_to_actuator: bool,
+    pub plastic_to_plastic: bool,
+    pub plastic_to_actuator: bool,
+}
+
+impl TargetingRules {
+    /// True if a neuron of kind source may form
+    /// a plastic synapse onto a neuron of kind target
+    pub fn allows(&self, source: NeuronKind, target: NeuronKind) -> bool {
+        use NeuronKind::*;
+
+        match (source, target) {
+            (Sensory, Plastic) | (Sensory, Interneuron) => self.sensory_to_plastic,
+            (Sensory, Actuator) => self.sensory_to_actuator,
+            (Plastic, Plastic) | (Plastic, Interneuron) => self.plastic_to_plastic,
+            (Interneuron, Plastic) | (Interneuron, Interneuron) => self.plastic_to_plastic,
+            (Plastic, Actuator) | (Interneuron, Actuator) => self.plastic_to_actuator,
+            (_, Sensory) | (Actuator, _) => false,
+        }
+    }
+
+    /// True if sources of kind source may target every kind of rx neuron
+    pub fn allows_all(&self, source: NeuronKind) -> bool {
+        self.allows(source, NeuronKind::Plastic) && self.allows(source, NeuronKind::Actuator)
+    }
+}
+
+impl Default for TargetingRules {
+    fn default() -> TargetingRules {
+        TargetingRules {
+            sensory_to_plastic: true,
+            sensory_to_actuator: true,
+            plastic_to_plastic: true,
+            plastic_to_actuator: true,
+        }
+    }
+}
+
+/// Dissolves synapses that any of its policies would dissolve
+pub struct Composite(pub Vec<Box<dyn PrunePolicy>>);
+
+impl PrunePolicy for Composite {
+    fn should_prune(&self, info: &SynapseInfo) -> bool {
+        self.0.iter().any(|policy| policy.should_prune(info))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecp_geometry::{BoxEcp, EcpGeometry};
+    use crate::encephalon::EncephalonBuilder;
+    use crate::test_util::{actuator, sensor};
+    use synaptic_strength::{EmStrength, SigmoidStrength};
+
+    type StrengthGenerator = fn() -> Box<RefCell<dyn SynapticStrength>>;
+
+    fn synapse_onto_center(strength: Box<RefCell<dyn SynapticStrength>>) -> PlasticSynapse {
+        let encephalon = EncephalonBuilder::new()
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 0.)],
+                vec![actuator("motor")],
+            )
+            .unwrap();
+        let target = encephalon
+            .local_random_neuron(Loc::new(&[2, 2, 2]))
+            .unwrap();
+
+        PlasticSynapse::new(strength, SynapticType::Excitatory, target, 0)
+    }
+
+    #[test]
+    fn normalization_makes_synapses_compete() {
+        let strengths: Vec<StrengthGenerator> = vec![
+            || Box::new(RefCell::new(EmStrength::new(10., 0.1, 0.2))),
+            || Box::new(RefCell::new(SigmoidStrength::new(10., 0.1, 0.5))),
+        ];
+
+        for strength in strengths {
+            let synapses = vec![
+                synapse_onto_center(strength()),
+                synapse_onto_center(strength()),
+            ];
+            let total = |synapses: &[PlasticSynapse]| {
+                synapses.iter().map(PlasticSynapse::strength).sum::<f32>()
+            };
+
+            normalize_strengths(&synapses, 8.);
+
+            assert!((total(&synapses) - 8.).abs() < 1e-3);
+
+            // Strengthening only the first synapse weakens the second
+            for _ in 0..20 {
+                let weaker_before = synapses[1].strength();
+
+                synapses[0].strengthen(1.);
+                normalize_strengths(&synapses, 8.);
+
+                assert!(synapses[1].strength() < weaker_before);
+                assert!((total(&synapses) - 8.).abs() < 1e-3);
+            }
+
+            assert!(synapses[0].strength() > 3. * synapses[1].strength());
+        }
+    }
+
+    fn info(strength: f32, last_fired: Option<u64>) -> SynapseInfo {
+        SynapseInfo {
+            cycle: 100,
+            strength,
+            normalized_strength: strength / 15.,
+            above_weakness_threshold: strength > 1.,
+            synaptic_type: SynapticType::Excitatory,
+            formed: 20,
+            last_fired,
+        }
+    }
+
+    #[test]
+    fn strength_threshold_prunes_only_weak_synapses() {
+        assert!(StrengthThreshold.should_prune(&info(0.5, Some(99))));
+        assert!(!StrengthThreshold.should_prune(&info(7.5, Some(40))));
+    }
+
+    #[test]
+    fn staleness_prunes_synapses_idle_too_long() {
+        let staleness = Staleness {
+            max_idle_cycles: 50,
+        };
+        let stale = info(7.5, Some(40));
+        let never_fired = info(7.5, None);
+
+        assert_eq!(stale.idle_cycles(), 60);
+        assert_eq!(never_fired.idle_cycles(), 80);
+        assert!(staleness.should_prune(&stale));
+        assert!(staleness.should_prune(&never_fired));
+        assert!(!staleness.should_prune(&info(0.5, Some(99))));
+        assert!(!staleness.should_prune(&info(7.5, Some(50))));
+    }
+
+    #[test]
+    fn composites_prune_if_any_policy_does() {
+        let weak = info(0.5, Some(99));
+        let composite = Composite(vec![
+            Box::new(StrengthThreshold),
+            Box::new(Staleness {
+                max_idle_cycles: 50,
+            }),
+        ]);
+
+        assert!(composite.should_prune(&weak));
+        assert!(composite.should_prune(&info(7.5, Some(40))));
+        assert!(!composite.should_prune(&info(7.5, Some(95))));
+        assert!(!Composite(vec![]).should_prune(&weak));
+    }
+
+    #[test]
+    fn normalized_strengths_stay_within_unit_range() {
+        let mut strengths: Vec<Box<dyn SynapticStrength>> = vec![
+            Box::new(SigmoidStrength::new(9., 1., 0.5)),
+            Box::new(EmStrength::new(30., 1., 0.2)),
+        ];
+
+        for strength in strengths.iter_mut() {
+            for i in 0..200 {
+                match (i / 20) % 3 {
+                    0 => strength.strengthen(),
+                    1 => strength.weaken_by(2.5),
+                    _ => strength.strengthen_by(4.),
+                }
+
+                let normalized = strength.normalized();
+
+                assert!((0. ..=1.).contains(&normalized));
+            }
+        }
     }
 }
@@ -1,91 +1,1133 @@
-use super::actuator::Actuator;
+use super::actuator::{Actuator, ActuatorError, ActuatorName};
 use super::neuron::SensoryNeuron;
 use crate::neuron::ActuatorNeuron;
 use crate::sensor::Sensor;
+use std::any::Any;
 use std::boxed::Box;
+use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
 use std::rc::Rc;
 
 /// This is an interface between an analog
-/// sensor and its corresponding sensory
+/// sensor and its corresponding cluster of sensory
+/// neurons (typically just one neuron)
 pub struct SensoryInterface {
     sensor: Box<dyn Sensor>,
-    pub sensory_neuron: Rc<SensoryNeuron>,
-    encoder: fn(f32) -> u32,
+    pub sensory_neurons: Vec<Rc<SensoryNeuron>>,
+    encoder: Rc<dyn SensoryEncoder>,
+    sampling: SensorSampling,
+    last_measurement: Option<f32>,
+    last_period: Option<u32>,
+    forced_period: Option<u32>, //Overrides the encoded period, see force_period
+    last_sample_cycle: Option<u64>,
+    fresh: bool,
+    isolate_user_code: bool,
+    panics: u64,
+    assert_range: bool,
+    range_violations: u64,
+}
+
+/// How often a sensory interface samples its sensor.  A sensor
+/// is measured once every sample_divisor cycles, and its sensory
+/// neurons hold the last encoded period in between.  With
+/// on_change_only, a measurement within change_epsilon of the
+/// last one that was encoded isn't encoded again
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SensorSampling {
+    pub sample_divisor: u32,
+    pub on_change_only: bool,
+    pub change_epsilon: f32,
+}
+
+impl Default for SensorSampling {
+    fn default() -> Self {
+        SensorSampling {
+            sample_divisor: 1,
+            on_change_only: false,
+            change_epsilon: 0.0,
+        }
+    }
 }
 
 impl SensoryInterface {
     pub fn new(
         sensor: Box<dyn Sensor>,
-        encoder: fn(f32) -> u32,
-        sensory_neuron: Rc<SensoryNeuron>,
+        encoder: Rc<dyn SensoryEncoder>,
+        sensory_neurons: Vec<Rc<SensoryNeuron>>,
+        sampling: SensorSampling,
+        isolate_user_code: bool,
+        assert_range: bool,
     ) -> SensoryInterface {
         SensoryInterface {
             sensor,
             encoder,
-            sensory_neuron,
+            sensory_neurons,
+            sampling,
+            last_measurement: None,
+            last_period: None,
+            forced_period: None,
+            last_sample_cycle: None,
+            fresh: false,
+            isolate_user_code,
+            panics: 0,
+            assert_range,
+            range_violations: 0,
+        }
+    }
+
+    /// Runs one encephalonaic cycle. If a sample is due,
+    /// takes measurement from its sensor, encodes that
+    /// measurement into a neuronic period, and sends that
+    /// period to each of its sensory_neurons.  Returns
+    /// true if the sensor was measured this cycle
+    pub fn run_cycle(&mut self, cycle: u64) -> bool {
+        let divisor = self.sampling.sample_divisor.max(1) as u64;
+        self.fresh = false;
+
+        if let Some(last_sample_cycle) = self.last_sample_cycle {
+            if cycle.saturating_sub(last_sample_cycle) < divisor {
+                return false;
+            }
+        }
+
+        self.sample(cycle);
+        true
+    }
+
+    /// Samples the sensor on cycle whether or not a sample is
+    /// due, returning true if the sensor measured a fresh value
+    pub fn poll(&mut self, cycle: u64) -> bool {
+        self.sample(cycle);
+        self.fresh
+    }
+
+    /// A sensor that panics while isolated counts as sampled, but
+    /// not fresh, and its sensory neurons hold their last period
+    fn sample(&mut self, cycle: u64) {
+        let sensor = &mut self.sensor;
+        let measurement = isolate(self.isolate_user_code, || sensor.measure());
+        self.last_sample_cycle = Some(cycle);
+
+        let measurement = match measurement {
+            Ok(measurement) => measurement,
+            Err(_) => {
+                self.panics += 1;
+                self.fresh = false;
+                return;
+            }
+        };
+
+        self.fresh = self.sensor.is_fresh();
+
+        if !(0.0..=1.0).contains(&measurement) {
+            self.range_violations += 1;
+
+            debug_assert!(
+                !self.assert_range,
+                "Sensor {} measured {}, which is outside of [0, 1]",
+                self.sensor.get_name(),
+                measurement
+            );
+        }
+
+        if self.sampling.on_change_only {
+            if let Some(last_measurement) = self.last_measurement {
+                if (measurement - last_measurement).abs() <= self.sampling.change_epsilon {
+                    return;
+                }
+            }
+        }
+
+        self.last_measurement = Some(measurement);
+        let period = self.encoder.encode(measurement);
+        self.last_period = Some(period);
+
+        for sensory_neuron in &self.sensory_neurons {
+            sensory_neuron.set_period(self.forced_period.unwrap_or(period));
+        }
+    }
+
+    /// Forces the sensory neurons of the interface to fire at
+    /// period (a period of 0 silences them), whatever the sensor
+    /// measures.  The sensor is still sampled as usual, and
+    /// forcing None hands the neurons back its last encoded period
+    pub fn force_period(&mut self, period: Option<u32>) {
+        self.forced_period = period;
+        let period = period.or(self.last_period).unwrap_or(0);
+
+        for sensory_neuron in &self.sensory_neurons {
+            sensory_neuron.set_period(period);
+        }
+    }
+
+    /// The period the interface's sensory neurons are forced to, if any
+    pub fn forced_period(&self) -> Option<u32> {
+        self.forced_period
+    }
+
+    /// True if the sensor measured a fresh value this cycle
+    pub fn is_fresh(&self) -> bool {
+        self.fresh
+    }
+
+    /// The last cycle on which the sensor was measured
+    pub fn last_sample_cycle(&self) -> Option<u64> {
+        self.last_sample_cycle
+    }
+
+    /// The number of times the sensor has panicked while isolated
+    pub fn panics(&self) -> u64 {
+        self.panics
+    }
+
+    /// The number of measurements the sensor has handed
+    /// to the interface that were outside of [0, 1]
+    pub fn range_violations(&self) -> u64 {
+        self.range_violations
+    }
+
+    /// A read-only view of the interface as it stands
+    pub fn view(&self) -> SensorView {
+        SensorView {
+            period: self.last_period,
+            last_measurement: self.last_measurement,
+            last_sample_cycle: self.last_sample_cycle,
+            fresh: self.fresh,
         }
     }
+}
+
+/// A read-only view of a sensory interface, as handed out by
+/// Encephalon::for_each_sensor.  The period and measurement are
+/// those last encoded, so both are None until the first sample
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SensorView {
+    pub period: Option<u32>,
+    pub last_measurement: Option<f32>,
+    pub last_sample_cycle: Option<u64>,
+    pub fresh: bool,
+}
+
+/// Runs user code (a sensor or an actuator), catching any panic when
+/// isolated so that it can't unwind through a half finished cycle.
+/// A caught panic is returned as its message
+fn isolate<T>(isolated: bool, f: impl FnOnce() -> T) -> Result<T, String> {
+    if !isolated {
+        return Ok(f());
+    }
+
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| panic_message(&*payload))
+}
 
-    /// Runs one encephalonaic cycle. Takes measurement
-    /// from its sensor, encodes that measurement into
-    /// a neuronic period, and sends that period to its
-    /// sensory_neuron
-    pub fn run_cycle(&mut self) {
-        self.sensory_neuron
-            .set_period((self.encoder)(self.sensor.measure()));
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "unknown panic".to_string(),
+        },
     }
 }
 
+/// Encodes a sensor's measurement into the period
+/// at which its sensory neurons fire.  Any function
+/// (or closure) from measurement to period is an encoder
+pub trait SensoryEncoder {
+    fn encode(&self, measurement: f32) -> u32;
+}
+
+impl<F: Fn(f32) -> u32> SensoryEncoder for F {
+    fn encode(&self, measurement: f32) -> u32 {
+        self(measurement)
+    }
+}
+
+/// Functions that encode measurements into periods, along
+/// with SensoryEncoder structs that hold onto their parameters.
+///
+/// Here's how each encoder spreads a sweep of measurements
+/// over periods between 1 and 100 (log with a base of 10,
+/// sigmoid with a midpoint of 0.5 and a steepness of 10):
+///
+/// | measurement | linear | inverse | log | sigmoid |
+/// |-------------|--------|---------|-----|---------|
+/// | 0.0         | 100    | 100     | 100 | 100     |
+/// | 0.25        | 75     | 4       | 49  | 93      |
+/// | 0.5         | 51     | 2       | 27  | ~51     |
+/// | 0.75        | 26     | 1       | 12  | 8       |
+/// | 1.0         | 1      | 1       | 1   | 1       |
+///
+/// ```
+/// use eywa::neuron_interfaces::sensory_encoders::*;
+///
+/// for measurement in &[0.0, 0.25, 0.5, 0.75, 1.0] {
+///     println!(
+///         "{} | {} | {} | {} | {}",
+///         measurement,
+///         linear_period_encoder(*measurement, 1, 100),
+///         inverse_encoder(*measurement, 100),
+///         log_encoder(*measurement, 1, 100, 10.),
+///         sigmoid_encoder(*measurement, 0.5, 10., 1, 100),
+///     );
+/// }
+/// ```
 pub mod sensory_encoders {
+    use super::SensoryEncoder;
+    use crate::error::EywaError;
+
     /// This returns the period of a single pulsed time series
     /// that would result in "input" as the peak value of an
     /// exponential moving average (ema) over that interval
     ///
-    /// Here alpha is the constant of the ema
+    /// Here alpha is the constant of the ema, which must lie within
+    /// (0, 1].  There's no meaningful period for any other alpha, so
+    /// this panics on one, in release builds too.  EmaEncoder::new
+    /// checks its alpha up front instead
     pub fn ema_encoder(measurement: f32, alpha: f32) -> u32 {
+        assert!(
+            crate::neuron::is_valid_ema_alpha(alpha),
+            "ema_encoder alpha {} is outside of (0, 1]",
+            alpha
+        );
+
         (((1. - (alpha / measurement)).ln() / (1. - alpha).ln()) + 1.).round() as u32
     }
 
-    /// This uses a linear function to decode sensory information.
-    /// The linear function has a y intercept greater than 1, and
-    /// contains the point (1, 1)
+    /// Maps a measurement linearly onto a period, so that a
+    /// measurement of 1.0 fires every min_period cycles and a
+    /// measurement of 0.0 fires every max_period cycles.
+    /// Measurements outside of [0, 1] are clamped, and periods
+    /// are never less than 1, as a period of 0 never fires
+    ///
+    /// ```
+    /// use eywa::neuron_interfaces::sensory_encoders::linear_period_encoder;
+    ///
+    /// assert_eq!(linear_period_encoder(1.0, 2, 20), 2);
+    /// assert_eq!(linear_period_encoder(0.0, 2, 20), 20);
+    /// assert_eq!(linear_period_encoder(0.5, 2, 20), 11);
+    /// assert_eq!(linear_period_encoder(-3.0, 2, 20), 20);
+    /// ```
+    pub fn linear_period_encoder(measurement: f32, min_period: u32, max_period: u32) -> u32 {
+        period_at(clamp_measurement(measurement), min_period, max_period)
+    }
+
+    /// Maps a measurement linearly onto a period between y_int
+    /// (for a measurement of 0.0) and 1 (for a measurement of 1.0),
+    /// ie linear_period_encoder(measurement, 1, y_int) without
+    /// clamping the measurement
+    #[deprecated(note = "use linear_period_encoder, whose parameters are the periods themselves")]
     pub fn linear_encoder(measurement: f32, y_int: f32) -> u32 {
         (((1. - y_int) * measurement) + y_int).round() as u32
     }
 
-    /// This uses an inverse function (1/x) to decode sensory information
-    /// This makes most sense for decoding
-    pub fn inverse_encoder(measurement: f32) -> u32 {
-        (1. / measurement).round() as u32
+    /// This uses an inverse function (1/x) to decode sensory information,
+    /// clamping the period into [1, max_period].  Measurements of zero
+    /// or less (or NaN) can't be inverted, so they get max_period too
+    ///
+    /// ```
+    /// use eywa::neuron_interfaces::sensory_encoders::inverse_encoder;
+    ///
+    /// assert_eq!(inverse_encoder(0.25, 100), 4);
+    /// assert_eq!(inverse_encoder(0.0, 100), 100);
+    /// ```
+    pub fn inverse_encoder(measurement: f32, max_period: u32) -> u32 {
+        let max_period = max_period.max(1);
+
+        if measurement.is_nan() || measurement <= 0. {
+            return max_period;
+        }
+
+        let period = 1. / measurement;
+
+        match period < max_period as f32 {
+            true => (period.round() as u32).max(1),
+            false => max_period,
+        }
+    }
+
+    /// Maps a measurement onto a period along a log curve, so that
+    /// small measurements get most of the range of periods, like
+    /// the way light and sound are perceived.  A measurement of 1.0
+    /// fires every min_period cycles and 0.0 every max_period cycles,
+    /// and the larger the base, the stronger the compression.  Bases
+    /// of 1 or less have no curve, so they're linear
+    ///
+    /// ```
+    /// use eywa::neuron_interfaces::sensory_encoders::log_encoder;
+    ///
+    /// assert_eq!(log_encoder(1.0, 1, 100, 10.), 1);
+    /// assert_eq!(log_encoder(0.0, 1, 100, 10.), 100);
+    /// assert_eq!(log_encoder(0.25, 1, 100, 10.), 49);
+    /// ```
+    pub fn log_encoder(measurement: f32, min_period: u32, max_period: u32, base: f32) -> u32 {
+        let measurement = clamp_measurement(measurement);
+
+        let position = match base > 1. {
+            true => (1. + (base - 1.) * measurement).log(base),
+            false => measurement,
+        };
+
+        period_at(position, min_period, max_period)
+    }
+
+    /// Maps a measurement onto a period along an S-shaped curve
+    /// centered on midpoint, so that measurements near the midpoint
+    /// get most of the range of periods.  The curve is stretched so
+    /// that a measurement of 1.0 fires every min_period cycles and
+    /// 0.0 every max_period cycles.  A steepness of 0 has no curve,
+    /// so it's linear
+    ///
+    /// ```
+    /// use eywa::neuron_interfaces::sensory_encoders::sigmoid_encoder;
+    ///
+    /// assert_eq!(sigmoid_encoder(1.0, 0.5, 10., 1, 100), 1);
+    /// assert_eq!(sigmoid_encoder(0.0, 0.5, 10., 1, 100), 100);
+    /// assert_eq!(sigmoid_encoder(0.25, 0.5, 10., 1, 100), 93);
+    /// ```
+    pub fn sigmoid_encoder(
+        measurement: f32,
+        midpoint: f32,
+        steepness: f32,
+        min_period: u32,
+        max_period: u32,
+    ) -> u32 {
+        let measurement = clamp_measurement(measurement);
+        let sigmoid = |x: f32| 1. / (1. + (-steepness * (x - midpoint)).exp());
+
+        let (low, high) = (sigmoid(0.), sigmoid(1.));
+
+        let position = match (high - low).abs() > f32::EPSILON {
+            true => (sigmoid(measurement) - low) / (high - low),
+            false => measurement,
+        };
+
+        period_at(position, min_period, max_period)
+    }
+
+    /// Clamps a measurement into [0, 1], treating NaN as 0
+    fn clamp_measurement(measurement: f32) -> f32 {
+        measurement.max(0.).min(1.)
+    }
+
+    /// The period a fraction position of the way from max_period to
+    /// min_period, which is never less than 1.  Inverted bounds are
+    /// swapped, so the shorter period is always that of position 1
+    fn period_at(position: f32, min_period: u32, max_period: u32) -> u32 {
+        let (min_period, max_period) = (min_period.min(max_period), min_period.max(max_period));
+        let span = max_period as f32 - min_period as f32;
+
+        ((max_period as f32 - (span * position)).round() as u32).max(1)
+    }
+
+    /// SensoryEncoder of ema_encoder
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct EmaEncoder {
+        alpha: f32,
+    }
+
+    impl EmaEncoder {
+        /// Encodes with the ema constant alpha,
+        /// which must lie within (0, 1]
+        ///
+        /// ```
+        /// use eywa::error::EywaError;
+        /// use eywa::neuron_interfaces::sensory_encoders::EmaEncoder;
+        ///
+        /// assert!(EmaEncoder::new(0.5).is_ok());
+        /// assert_eq!(EmaEncoder::new(1.5), Err(EywaError::InvalidEmaAlpha(1.5)));
+        /// ```
+        pub fn new(alpha: f32) -> Result<EmaEncoder, EywaError> {
+            match crate::neuron::is_valid_ema_alpha(alpha) {
+                true => Ok(EmaEncoder { alpha }),
+                false => Err(EywaError::InvalidEmaAlpha(alpha)),
+            }
+        }
+
+        pub fn alpha(&self) -> f32 {
+            self.alpha
+        }
+    }
+
+    impl SensoryEncoder for EmaEncoder {
+        fn encode(&self, measurement: f32) -> u32 {
+            ema_encoder(measurement, self.alpha)
+        }
+    }
+
+    /// SensoryEncoder of linear_period_encoder
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct LinearPeriodEncoder {
+        pub min_period: u32,
+        pub max_period: u32,
+    }
+
+    impl SensoryEncoder for LinearPeriodEncoder {
+        fn encode(&self, measurement: f32) -> u32 {
+            linear_period_encoder(measurement, self.min_period, self.max_period)
+        }
+    }
+
+    /// SensoryEncoder of inverse_encoder
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub struct InverseEncoder {
+        pub max_period: u32,
+    }
+
+    impl SensoryEncoder for InverseEncoder {
+        fn encode(&self, measurement: f32) -> u32 {
+            inverse_encoder(measurement, self.max_period)
+        }
+    }
+
+    /// SensoryEncoder of log_encoder
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct LogEncoder {
+        pub min_period: u32,
+        pub max_period: u32,
+        pub base: f32,
+    }
+
+    impl SensoryEncoder for LogEncoder {
+        fn encode(&self, measurement: f32) -> u32 {
+            log_encoder(measurement, self.min_period, self.max_period, self.base)
+        }
+    }
+
+    /// SensoryEncoder of sigmoid_encoder
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub struct SigmoidEncoder {
+        pub midpoint: f32,
+        pub steepness: f32,
+        pub min_period: u32,
+        pub max_period: u32,
+    }
+
+    impl SensoryEncoder for SigmoidEncoder {
+        fn encode(&self, measurement: f32) -> u32 {
+            sigmoid_encoder(
+                measurement,
+                self.midpoint,
+                self.steepness,
+                self.min_period,
+                self.max_period,
+            )
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Each encoder over periods between 1 and 100
+        fn encoders() -> Vec<Box<dyn Fn(f32) -> u32>> {
+            vec![
+                Box::new(|m| linear_period_encoder(m, 1, 100)),
+                Box::new(|m| inverse_encoder(m, 100)),
+                Box::new(|m| log_encoder(m, 1, 100, 10.)),
+                Box::new(|m| sigmoid_encoder(m, 0.5, 10., 1, 100)),
+            ]
+        }
+
+        #[test]
+        fn every_encoder_sweeps_monotonically_between_its_periods() {
+            for encoder in &encoders() {
+                let sweep: Vec<u32> = (0..=20).map(|i| encoder(i as f32 / 20.)).collect();
+
+                assert_eq!(sweep[0], 100);
+                assert_eq!(sweep[20], 1);
+                assert!(sweep.windows(2).all(|pair| pair[0] >= pair[1]));
+            }
+        }
+
+        #[test]
+        fn linear_periods_run_from_max_period_down_to_min_period() {
+            for (min_period, max_period) in [(1, 100), (2, 20), (0, 10), (7, 7)] {
+                let sweep: Vec<u32> = (0..=20)
+                    .map(|i| linear_period_encoder(i as f32 / 20., min_period, max_period))
+                    .collect();
+
+                assert_eq!(sweep[0], max_period);
+                assert_eq!(sweep[20], min_period.max(1));
+                assert!(sweep.windows(2).all(|pair| pair[0] >= pair[1]));
+            }
+        }
+
+        #[test]
+        fn a_min_period_of_zero_still_fires_the_most_active_measurement() {
+            assert_eq!(linear_period_encoder(1.0, 0, 100), 1);
+            assert_eq!(linear_period_encoder(1.0, 0, 0), 1);
+            assert_eq!(linear_period_encoder(0.0, 0, 0), 1);
+        }
+
+        #[test]
+        fn inverted_linear_bounds_are_swapped() {
+            assert_eq!(linear_period_encoder(1.0, 20, 2), 2);
+            assert_eq!(linear_period_encoder(0.0, 20, 2), 20);
+            assert_eq!(linear_period_encoder(0.5, 20, 2), 11);
+        }
+
+        #[test]
+        #[should_panic(expected = "outside of (0, 1]")]
+        fn ema_encoder_refuses_a_negative_alpha() {
+            ema_encoder(0.5, -0.1);
+        }
+
+        #[test]
+        fn ema_encoders_refuse_alphas_outside_of_zero_to_one() {
+            for alpha in &[1.5, -0.1, 0.0, f32::NAN] {
+                assert!(EmaEncoder::new(*alpha).is_err());
+            }
+
+            let encoder = EmaEncoder::new(0.02).unwrap();
+
+            assert_eq!(encoder.encode(0.5), ema_encoder(0.5, 0.02));
+        }
+
+        #[test]
+        fn log_and_sigmoid_clamp_their_measurements() {
+            assert_eq!(log_encoder(-1.0, 1, 100, 10.), 100);
+            assert_eq!(log_encoder(2.0, 1, 100, 10.), 1);
+            assert_eq!(log_encoder(f32::NAN, 1, 100, 10.), 100);
+            assert_eq!(sigmoid_encoder(-1.0, 0.5, 10., 1, 100), 100);
+            assert_eq!(sigmoid_encoder(2.0, 0.5, 10., 1, 100), 1);
+            assert_eq!(sigmoid_encoder(f32::NAN, 0.5, 10., 1, 100), 100);
+        }
+
+        #[test]
+        fn log_and_sigmoid_periods_are_at_least_one() {
+            assert_eq!(log_encoder(1.0, 0, 100, 10.), 1);
+            assert_eq!(sigmoid_encoder(1.0, 0.5, 10., 0, 100), 1);
+        }
+
+        #[test]
+        fn flat_curves_are_linear() {
+            for i in 0..=20 {
+                let m = i as f32 / 20.;
+                let linear = linear_period_encoder(m, 1, 100);
+
+                assert_eq!(log_encoder(m, 1, 100, 1.), linear);
+                assert_eq!(sigmoid_encoder(m, 0.5, 0., 1, 100), linear);
+            }
+        }
+
+        #[test]
+        fn inverse_periods_are_the_reciprocal() {
+            assert_eq!(inverse_encoder(0.25, 100), 4);
+            assert_eq!(inverse_encoder(0.5, 100), 2);
+            assert_eq!(inverse_encoder(1.0, 100), 1);
+        }
+
+        #[test]
+        fn uninvertible_measurements_get_the_max_period() {
+            assert_eq!(inverse_encoder(0.0, 100), 100);
+            assert_eq!(inverse_encoder(-0.0, 100), 100);
+            assert_eq!(inverse_encoder(-0.5, 100), 100);
+            assert_eq!(inverse_encoder(f32::NEG_INFINITY, 100), 100);
+            assert_eq!(inverse_encoder(f32::NAN, 100), 100);
+        }
+
+        #[test]
+        fn measurements_above_one_fire_every_cycle() {
+            assert_eq!(inverse_encoder(4.0, 100), 1);
+            assert_eq!(inverse_encoder(f32::MAX, 100), 1);
+            assert_eq!(inverse_encoder(f32::INFINITY, 100), 1);
+        }
+
+        #[test]
+        fn tiny_measurements_are_clamped_to_the_max_period() {
+            assert_eq!(inverse_encoder(1e-6, 100), 100);
+            assert_eq!(inverse_encoder(f32::MIN_POSITIVE, 100), 100);
+            assert_eq!(inverse_encoder(f32::MIN_POSITIVE / 2., 100), 100);
+            assert_eq!(inverse_encoder(f32::MIN_POSITIVE / 2., u32::MAX), u32::MAX);
+        }
+
+        #[test]
+        fn a_max_period_of_zero_still_fires() {
+            assert_eq!(inverse_encoder(0.0, 0), 1);
+            assert_eq!(inverse_encoder(0.25, 0), 1);
+        }
+
+        #[test]
+        fn inverse_periods_never_rise_with_the_measurement() {
+            let measurements = (0..=10_000).map(|i| i as f32 / 1_000.);
+            let periods: Vec<u32> = measurements.map(|m| inverse_encoder(m, 100)).collect();
+
+            assert!(periods.windows(2).all(|pair| pair[0] >= pair[1]));
+            assert!(periods.iter().all(|period| (1..=100).contains(period)));
+        }
     }
 }
 
-/// This is the interface between an actuator neuron
-/// and the actual actuator, which takes in an analog
-/// value between min and max.  This interface essentially
-/// provides the mechanism to translate between the neuron's
-/// EMA and the actuator
+/// This is the interface between a cluster of actuator
+/// neurons (typically just one neuron) and the actual
+/// actuator, which takes in an analog value between min
+/// and max.  This interface essentially provides the
+/// mechanism to translate between the neurons' EMA and
+/// the actuator.
+///
+/// The interface keeps track of its actuator's consecutive
+/// failures, and once they reach failure_threshold it stops
+/// calling the actuator until reset_failures is called
 pub struct ActuatorInterface {
-    pub actuator_neuron: Rc<ActuatorNeuron>,
+    pub actuator_neurons: Vec<Rc<ActuatorNeuron>>,
     actuator: Box<dyn Actuator>,
+    failure_threshold: u32,
+    consecutive_failures: Cell<u32>,
+    total_failures: Cell<u64>,
+    panics: Cell<u64>,
+    isolate_user_code: bool,
+    output: ActuatorOutput,
+    last_sent: Cell<Option<f32>>,
+    last_update_cycle: Cell<Option<u64>>,
+}
+
+/// How an actuator interface paces the control values it sends.
+/// The actuator is updated once every update_divisor cycles, each
+/// update moves the control value at most max_slew_per_update away
+/// from the last value sent, and updates that would move it by
+/// less than deadband are skipped.  The mode picks what about the
+/// actuator neurons the control value is read from
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ActuatorOutput {
+    pub update_divisor: u32,
+    pub max_slew_per_update: f32,
+    pub deadband: f32,
+    pub mode: ActuatorOutputMode,
+}
+
+impl Default for ActuatorOutput {
+    fn default() -> Self {
+        ActuatorOutput {
+            update_divisor: 1,
+            max_slew_per_update: f32::INFINITY,
+            deadband: 0.0,
+            mode: ActuatorOutputMode::default(),
+        }
+    }
+}
+
+/// What an actuator interface reads from its actuator neurons
+/// as the control value of its actuator.  In Spike mode an
+/// event-driven actuator sees exactly one 1.0 per firing
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum ActuatorOutputMode {
+    /// The mean EMA firing frequency of the actuator neurons
+    #[default]
+    EmaFrequency,
+
+    /// 1.0 if any actuator neuron fired on its last cycle, else 0.0
+    Spike,
+
+    /// The mean internal charge the actuator neurons took on
+    /// their last cycle, normalized by their fire threshold
+    Charge,
 }
 
 impl ActuatorInterface {
     pub fn new(
-        actuator_neuron: Rc<ActuatorNeuron>,
+        actuator_neurons: Vec<Rc<ActuatorNeuron>>,
         actuator: Box<dyn Actuator>,
+        failure_threshold: u32,
+        output: ActuatorOutput,
+        isolate_user_code: bool,
     ) -> ActuatorInterface {
         ActuatorInterface {
-            actuator_neuron,
+            actuator_neurons,
             actuator,
+            failure_threshold,
+            consecutive_failures: Cell::new(0),
+            total_failures: Cell::new(0),
+            panics: Cell::new(0),
+            isolate_user_code,
+            output,
+            last_sent: Cell::new(None),
+            last_update_cycle: Cell::new(None),
         }
     }
 
-    /// Runs one encephalonaic cycle. Measures its actuator
-    /// neuron's (ema) frequency, and sets its actuator's
-    /// control value to that frequency
-    pub fn run_cycle(&self) {
-        self.actuator
-            .set_control_value(self.actuator_neuron.read_ema_frequency());
+    /// Returns the average (ema) frequency of the
+    /// interface's actuator neurons
+    pub fn read_ema_frequency(&self) -> f32 {
+        if self.actuator_neurons.is_empty() {
+            return 0.0;
+        }
+
+        let total: f32 = self
+            .actuator_neurons
+            .iter()
+            .map(|neuron| neuron.read_ema_frequency())
+            .sum();
+
+        total / self.actuator_neurons.len() as f32
+    }
+
+    /// Reads the interface's actuator neurons as its output mode
+    /// calls for
+    pub fn read_output(&self) -> f32 {
+        let neurons = &self.actuator_neurons;
+
+        match self.output.mode {
+            ActuatorOutputMode::EmaFrequency => self.read_ema_frequency(),
+            ActuatorOutputMode::Spike => match neurons.iter().any(|n| n.last_cycle_fired()) {
+                true => 1.0,
+                false => 0.0,
+            },
+            ActuatorOutputMode::Charge => {
+                let total: f32 = neurons.iter().map(|n| n.last_cycle_charge()).sum();
+                total / neurons.len().max(1) as f32
+            }
+        }
+    }
+
+    /// Runs one encephalonaic cycle. Reads its actuator neurons
+    /// (by default their average (ema) frequency), and moves its
+    /// actuator's control value towards that reading
+    pub fn run_cycle(&self, cycle: u64) -> Result<(), ActuatorError> {
+        self.update(cycle, self.read_output())
+    }
+
+    /// Moves its actuator's control value towards value, paced by
+    /// the interface's output settings.  Cycles on which no update
+    /// is due, or on which the update falls within the deadband,
+    /// leave the actuator alone
+    pub fn update(&self, cycle: u64, value: f32) -> Result<(), ActuatorError> {
+        let divisor = self.output.update_divisor.max(1) as u64;
+
+        if let Some(last_update_cycle) = self.last_update_cycle.get() {
+            if cycle.saturating_sub(last_update_cycle) < divisor {
+                return Ok(());
+            }
+        }
+
+        self.last_update_cycle.set(Some(cycle));
+
+        let value = match self.last_sent.get() {
+            Some(last_sent) => {
+                let change = value - last_sent;
+
+                if change.abs() < self.output.deadband {
+                    return Ok(());
+                }
+
+                let max_slew = self.output.max_slew_per_update;
+                last_sent + change.max(-max_slew).min(max_slew)
+            }
+            None => value,
+        };
+
+        self.set_control_value(value)
+    }
+
+    /// Sets its actuator's control value directly.  If the
+    /// interface is disabled the actuator isn't called at all.
+    /// An actuator that panics while isolated fails like any other
+    pub fn set_control_value(&self, value: f32) -> Result<(), ActuatorError> {
+        if self.is_disabled() {
+            return Ok(());
+        }
+
+        self.last_sent.set(Some(value));
+
+        let actuator = &self.actuator;
+        let result = isolate(self.isolate_user_code, || actuator.set_control_value(value))
+            .unwrap_or_else(|message| {
+                self.panics.set(self.panics.get() + 1);
+                Err(ActuatorError::new(format!("panicked: {}", message)))
+            });
+
+        match result {
+            Ok(_) => self.consecutive_failures.set(0),
+            Err(_) => {
+                self.consecutive_failures
+                    .set(self.consecutive_failures.get() + 1);
+                self.total_failures.set(self.total_failures.get() + 1);
+            }
+        }
+
+        result
+    }
+
+    /// True once the actuator has failed failure_threshold times
+    /// in a row, after which it's no longer called
+    pub fn is_disabled(&self) -> bool {
+        self.consecutive_failures.get() >= self.failure_threshold
+    }
+
+    /// The last control value handed to the actuator
+    pub fn last_sent(&self) -> Option<f32> {
+        self.last_sent.get()
+    }
+
+    /// The number of times in a row the actuator has failed
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures.get()
+    }
+
+    /// The number of times the actuator has failed in total
+    pub fn total_failures(&self) -> u64 {
+        self.total_failures.get()
+    }
+
+    /// The number of those failures that were panics
+    pub fn panics(&self) -> u64 {
+        self.panics.get()
+    }
+
+    /// Clears the actuator's consecutive failures, so a
+    /// disabled interface starts calling its actuator again
+    pub fn reset_failures(&self) {
+        self.consecutive_failures.set(0);
+    }
+
+    /// A read-only view of the interface as it stands
+    pub fn view(&self) -> ActuatorView {
+        ActuatorView {
+            ema: self.read_ema_frequency(),
+            last_sent: self.last_sent(),
+            disabled: self.is_disabled(),
+        }
+    }
+}
+
+/// A read-only view of an actuator interface, as handed out
+/// by Encephalon::for_each_actuator.  The last value sent is
+/// None until the actuator has been sent one
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ActuatorView {
+    pub ema: f32,
+    pub last_sent: Option<f32>,
+    pub disabled: bool,
+}
+
+/// How the values of an actuator group compete with each other
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GroupMode {
+    /// Each member gets exp(value / temperature), normalized so
+    /// that the group's control values sum to 1
+    Softmax { temperature: f32 },
+    /// The member with the highest value gets 1, the rest get 0
+    Argmax,
+    /// Each member gets its share of the group's total value
+    Normalize,
+}
+
+impl GroupMode {
+    /// Transforms the raw values of a group's members
+    /// into the control values sent to their actuators
+    pub fn transform(&self, values: &[f32]) -> Vec<f32> {
+        match self {
+            GroupMode::Softmax { temperature } => {
+                let max = values.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let exps: Vec<f32> = values
+                    .iter()
+                    .map(|value| ((value - max) / temperature).exp())
+                    .collect();
+                let total: f32 = exps.iter().sum();
+
+                exps.iter().map(|exp| exp / total).collect()
+            }
+            GroupMode::Argmax => {
+                let mut winner = 0;
+
+                for (i, value) in values.iter().enumerate() {
+                    if *value > values[winner] {
+                        winner = i;
+                    }
+                }
+
+                (0..values.len())
+                    .map(|i| if i == winner { 1.0 } else { 0.0 })
+                    .collect()
+            }
+            GroupMode::Normalize => {
+                let total: f32 = values.iter().sum();
+
+                values
+                    .iter()
+                    .map(|value| if total > 0. { value / total } else { 0.0 })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// A named set of actuators whose values are interpreted as
+/// a competition, as in a discrete action space, rather than
+/// as independent analog values
+#[derive(Clone, Debug)]
+pub struct ActuatorGroup {
+    pub name: String,
+    pub actuator_names: Vec<ActuatorName>,
+    pub mode: GroupMode,
+}
+
+impl ActuatorGroup {
+    pub fn new(name: String, actuator_names: Vec<ActuatorName>, mode: GroupMode) -> ActuatorGroup {
+        ActuatorGroup {
+            name,
+            actuator_names,
+            mode,
+        }
+    }
+}
+
+/// A pair of antagonistic actuators (say left_forward and
+/// left_backward), read out together as the value of the agonist
+/// less the value of the antagonist.  The difference is passed
+/// through the pair's decoder, if it has one, and clamped to
+/// [-1, 1].  The members still drive their own actuators, and the
+/// pair's value can also drive an actuator of its own
+pub struct AntagonistPair {
+    pub name: String,
+    pub agonist: ActuatorName,
+    pub antagonist: ActuatorName,
+    decoder: Option<Box<dyn Fn(f32) -> f32>>,
+    actuator: Option<Box<dyn Actuator>>,
+}
+
+impl AntagonistPair {
+    pub fn new(name: String, agonist: ActuatorName, antagonist: ActuatorName) -> AntagonistPair {
+        AntagonistPair {
+            name,
+            agonist,
+            antagonist,
+            decoder: None,
+            actuator: None,
+        }
+    }
+
+    /// Maps the difference of the members' values
+    /// onto the pair's value, before it's clamped
+    pub fn decoder(mut self, decoder: impl Fn(f32) -> f32 + 'static) -> AntagonistPair {
+        self.decoder = Some(Box::new(decoder));
+        self
+    }
+
+    /// An actuator whose control value is set to
+    /// the pair's value every cycle
+    pub fn actuator(mut self, actuator: Box<dyn Actuator>) -> AntagonistPair {
+        self.actuator = Some(actuator);
+        self
+    }
+
+    /// The value of the pair, given the values of its members
+    pub fn value(&self, agonist: f32, antagonist: f32) -> f32 {
+        let difference = agonist - antagonist;
+        let value = match &self.decoder {
+            Some(decoder) => decoder(difference),
+            None => difference,
+        };
+
+        value.max(-1.).min(1.)
+    }
+
+    /// Sets the pair's actuator, if it has one, to value
+    pub fn update(&self, value: f32, isolate_user_code: bool) -> Result<(), ActuatorError> {
+        match &self.actuator {
+            Some(actuator) => isolate(isolate_user_code, || actuator.set_control_value(value))
+                .unwrap_or_else(|message| {
+                    Err(ActuatorError::new(format!("panicked: {}", message)))
+                }),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecp_geometry::{BoxEcp, EcpGeometry};
+    use crate::encephalon::EncephalonBuilder;
+    use crate::test_util::{recording_actuator, reflex, sensor};
+
+    /// The control values an actuator is sent as
+    /// its value steps from 0 to 1 on cycle 1
+    fn sent(output: ActuatorOutput) -> Vec<f32> {
+        let (actuator, sent) = recording_actuator("motor");
+        let interface = ActuatorInterface::new(vec![], actuator, 10, output, true);
+
+        for cycle in 0..30 {
+            let value = if cycle == 0 { 0.0 } else { 1.0 };
+            interface.update(cycle, value).unwrap();
+        }
+
+        let sent = sent.borrow().clone();
+        sent
+    }
+
+    #[test]
+    fn a_step_is_sent_as_a_ramp_under_a_limited_slew() {
+        let slewed = sent(ActuatorOutput {
+            max_slew_per_update: 0.05,
+            ..ActuatorOutput::default()
+        });
+
+        assert_eq!(slewed.len(), 30);
+        for (update, value) in slewed.iter().enumerate().take(21) {
+            assert!((value - 0.05 * update as f32).abs() < 1e-4);
+        }
+        assert!(slewed[21..].iter().all(|value| (value - 1.0).abs() < 1e-4));
+
+        let unlimited = sent(ActuatorOutput::default());
+        assert_eq!(unlimited[..3], [0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn only_due_updates_past_the_deadband_are_sent() {
+        // Every 10th cycle is sent, and so is only the step past the deadband
+        let divided = sent(ActuatorOutput {
+            update_divisor: 10,
+            ..ActuatorOutput::default()
+        });
+        assert_eq!(divided, vec![0.0, 1.0, 1.0]);
+
+        let deadbanded = sent(ActuatorOutput {
+            deadband: 0.1,
+            ..ActuatorOutput::default()
+        });
+        assert_eq!(deadbanded, vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn spike_mode_sends_one_per_firing() {
+        let (motor, history) = recording_actuator("motor");
+
+        let encephalon = EncephalonBuilder::new()
+            .reflexes(vec![reflex("light", "motor", 20.)])
+            .actuator_output(
+                "motor".into(),
+                ActuatorOutput {
+                    mode: ActuatorOutputMode::Spike,
+                    ..ActuatorOutput::default()
+                },
+            )
+            .build(
+                Box::new(BoxEcp::new(125, 1, 1, 27)),
+                vec![sensor("light", 1.0)],
+                vec![motor],
+            )
+            .unwrap();
+
+        let motor_locs = encephalon.actuator_cluster_locs(&"motor".into()).unwrap();
+        encephalon
+            .define_group("motor", motor_locs.into_iter().map(Vec::from).collect())
+            .unwrap();
+
+        // The interface sends what its neurons did on the same cycle
+        let mut firing_cycles = 0;
+
+        for _ in 0..500 {
+            encephalon.run_cycle();
+            firing_cycles +=
+                (encephalon.group_fire_count_last_cycle("motor").unwrap() > 0) as usize;
+        }
+
+        let history = history.borrow();
+        assert!(history.iter().all(|&value| value == 0.0 || value == 1.0));
+        assert!(firing_cycles > 0);
+        assert_eq!(
+            history.iter().filter(|&&value| value == 1.0).count(),
+            firing_cycles
+        );
     }
 }
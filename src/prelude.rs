@@ -0,0 +1,47 @@
+//! The user facing surface of eywa, so that setting up
+//! a brain only takes a single `use eywa::prelude::*;`
+
+pub use crate::actuator::{Actuator, ActuatorError, ActuatorName, FnActuator};
+pub use crate::clock::{Clock, MockClock, SystemClock};
+pub use crate::compose::BrainStack;
+pub use crate::ecp_geometry::{
+    ActuatorSpread, BoxEcp, CustomEcp, EcpGeometry, EcpSpec, FccEcp, GeometrySpec,
+    LayerConnectivity, LayeredEcp, NodeSpec, Region, SizeReport,
+};
+pub use crate::encephalon::{
+    ActuatorLayout, BrainPlan, BuildPhase, ClusterPlan, Encephalon, EncephalonBuilder, FieldKind,
+    NeuronPlan, PlanWarning, PlaneLayout, Reflex, ReflexTarget, ReflexTemplate, SensorLayout,
+};
+pub use crate::error::EywaError;
+pub use crate::experiment::{RunSummary, Sweep};
+pub use crate::journal::{EncephalonEvent, EventKind};
+pub use crate::loc::Loc;
+pub use crate::metrics::{
+    CycleMetrics, HealthReport, PacingReport, PhaseTiming, PhaseTimings, ProgressUpdate,
+    ReflexContribution, StrengthPercentiles,
+};
+pub use crate::neuron::synapse::synaptic_strength::{
+    EmStrength, SigmoidStrength, StrengthInit, SynapticStrength,
+};
+pub use crate::neuron::synapse::{
+    BudgetPolicy, Composite, PrunePolicy, Staleness, StrengthThreshold, SynapseInfo, SynapseRecord,
+    SynapseSpec, SynapticType, TargetingRules,
+};
+pub use crate::neuron::{
+    ema_alpha_from_window, ActuatorParams, Adaptation, EmaParams, EnergyModel, GapJunctionParams,
+    HasEma, InhibitionMode, InterneuronParams, IzhParams, PlasticModel, PlasticParams,
+    PlasticParamsPatch, PolarityPolicy, SensoryParams, SynapticScaling, ThresholdMode,
+};
+pub use crate::neuron_interfaces::{
+    sensory_encoders, ActuatorGroup, ActuatorOutput, ActuatorOutputMode, ActuatorView,
+    AntagonistPair, GroupMode, SensorSampling, SensorView, SensoryEncoder,
+};
+pub use crate::probe::{ProbeId, ProbeReading, ProbeSample};
+pub use crate::reward::TargetComparator;
+pub use crate::sensor::adapters::{
+    InjectedSensor, LatestValueHandle, LatestValueSensor, Normalization, NormalizedSensor,
+};
+pub use crate::sensor::{split_multi_sensor, MultiSensor, Sensor, SensorName};
+pub use crate::snapshot::{
+    EncephalonSnapshot, GroupSnapshot, NeuronDynamics, NeuronSnapshot, RngSnapshot, SnapshotFormat,
+};
@@ -0,0 +1,74 @@
+use std::collections::VecDeque;
+
+/// Identifies a probe registered with an encephalon
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ProbeId(pub usize);
+
+/// What a probe reads from its neuron each cycle.  Rx
+/// neurons are read by the charge they compared against their
+/// threshold before resetting it, while sensory neurons (which
+/// have no internal charge) are read by their period
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ProbeReading {
+    Charge(f32),
+    Period(u32),
+}
+
+/// A single cycle's recording from a probe
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ProbeSample {
+    pub cycle: u64,
+    pub reading: ProbeReading,
+    pub fired: bool,
+    pub ema: f32,
+    pub adaptation: f32,
+    pub input_gain: f32,
+}
+
+/// A recording electrode placed at a neuron, which keeps
+/// the most recent `capacity` samples of that neuron
+#[derive(Debug)]
+pub struct Probe {
+    name: String,
+    loc: Vec<i32>,
+    capacity: usize,
+    samples: VecDeque<ProbeSample>,
+}
+
+impl Probe {
+    pub fn new(name: String, loc: Vec<i32>, capacity: usize) -> Probe {
+        Probe {
+            name,
+            loc,
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn loc(&self) -> &Vec<i32> {
+        &self.loc
+    }
+
+    /// Records a sample, dropping the oldest sample
+    /// if the probe is at capacity
+    pub fn record(&mut self, sample: ProbeSample) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(sample);
+    }
+
+    /// Returns the recorded samples from oldest to newest
+    pub fn trace(&self) -> Vec<ProbeSample> {
+        self.samples.iter().copied().collect()
+    }
+}
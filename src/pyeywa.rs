@@ -0,0 +1,132 @@
+//! Python bindings, built with maturin (see pyproject.toml):
+//!
+//! ```python
+//! import pyeywa
+//!
+//! brain = pyeywa.Brain({
+//!     "sensors": ["light"],
+//!     "actuators": ["motor"],
+//!     "reflexes": [{"sensor": "light", "actuator": "motor",
+//!                   "synapse_type": "Excitatory", "strength": 20.0}],
+//! })
+//!
+//! brain.set_sensor("light", 0.5)
+//! brain.step(100)
+//! print(brain.actuator("motor"), brain.metrics())
+//! ```
+//!
+//! The encephalon isn't thread safe, so a Brain runs it
+//! on a thread of its own through a SpecBrainHandle, which
+//! lets the Brain be used from any python thread
+
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use crate::brain_spec::{metric_entries, BrainSpec, SpecBrainHandle};
+use crate::error::EywaError;
+use crate::snapshot::{EncephalonSnapshot, SnapshotFormat};
+
+/// A SpecBrain driven from python
+#[pyclass]
+pub struct Brain {
+    brain: SpecBrainHandle,
+}
+
+#[pymethods]
+impl Brain {
+    /// Builds a brain from a dict laid out like a BrainSpec
+    #[new]
+    fn new(py: Python, config: &PyDict) -> PyResult<Brain> {
+        let json: String = py.import("json")?.call1("dumps", (config,))?.extract()?;
+
+        BrainSpec::from_json(&json)
+            .and_then(SpecBrainHandle::spawn)
+            .map(|brain| Brain { brain })
+            .map_err(to_py)
+    }
+
+    /// Sets the value the named sensor measures from the
+    /// next step on.  Values are clamped into [0, 1]
+    fn set_sensor(&self, name: &str, value: f32) -> PyResult<()> {
+        let name = name.to_string();
+
+        self.brain
+            .with(move |brain| brain.set_sensor(&name, value))
+            .map_err(|e| PyKeyError::new_err(e.to_string()))
+    }
+
+    /// Runs n cycles of the encephalon, letting
+    /// other python threads run in the meantime
+    #[args(n = "1")]
+    fn step(&self, py: Python, n: u32) {
+        py.allow_threads(|| self.brain.with(move |brain| brain.step(n)));
+    }
+
+    /// The last control value handed to the named actuator
+    fn actuator(&self, name: &str) -> PyResult<f32> {
+        let owned_name = name.to_string();
+
+        self.brain
+            .with(move |brain| brain.actuator(&owned_name))
+            .ok_or_else(|| {
+                PyKeyError::new_err(EywaError::UnknownName(name.to_string()).to_string())
+            })
+    }
+
+    /// Snapshot of the encephalon, encoded with bincode
+    fn snapshot_bytes<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
+        let bytes = self
+            .brain
+            .with(|brain| {
+                brain
+                    .encephalon()
+                    .snapshot()
+                    .to_bytes(SnapshotFormat::Bincode)
+            })
+            .map_err(to_py)?;
+
+        Ok(PyBytes::new(py, &bytes))
+    }
+
+    /// Restores a snapshot made by snapshot_bytes
+    fn restore_bytes(&self, bytes: &[u8]) -> PyResult<()> {
+        let snapshot =
+            EncephalonSnapshot::from_bytes(bytes, SnapshotFormat::Bincode).map_err(to_py)?;
+
+        self.brain
+            .with(move |brain| brain.encephalon().restore(&snapshot))
+            .map_err(to_py)
+    }
+
+    /// Metrics of the most recent cycle
+    fn metrics<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        let dict = PyDict::new(py);
+        let entries = self
+            .brain
+            .with(|brain| metric_entries(brain.last_metrics()));
+
+        for (key, value) in entries {
+            dict.set_item(key, value)?;
+        }
+
+        Ok(dict)
+    }
+
+    /// Every synapse of the encephalon as a json list
+    fn connectome_json(&self) -> PyResult<String> {
+        self.brain
+            .with(|brain| serde_json::to_string(&brain.encephalon().connectome()))
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+}
+
+fn to_py(e: EywaError) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+#[pymodule]
+fn pyeywa(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<Brain>()?;
+    Ok(())
+}
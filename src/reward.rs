@@ -0,0 +1,84 @@
+use std::fmt;
+
+use crate::actuator::ActuatorName;
+
+/// Rewards the encephalon for driving an actuator along a
+/// target trajectory.  Each cycle the comparator measures the
+/// error between the actuator's EMA and its target, and turns
+/// that error into a contribution to the global reward signal:
+/// full reward at no error, no reward at an error of tolerance,
+/// and full punishment at twice the tolerance or beyond
+pub struct TargetComparator {
+    pub actuator_name: ActuatorName,
+    target: Box<dyn Fn(u64) -> f32>,
+    tolerance: f32,
+    weight: f32,
+}
+
+impl fmt::Debug for TargetComparator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TargetComparator")
+            .field("actuator_name", &self.actuator_name)
+            .field("tolerance", &self.tolerance)
+            .field("weight", &self.weight)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TargetComparator {
+    /// Compares the actuator against the target provided for
+    /// each cycle, ie target(cycle) is the EMA it should have
+    pub fn new(
+        actuator_name: ActuatorName,
+        tolerance: f32,
+        target: impl Fn(u64) -> f32 + 'static,
+    ) -> TargetComparator {
+        TargetComparator {
+            actuator_name,
+            target: Box::new(target),
+            tolerance,
+            weight: 1.0,
+        }
+    }
+
+    /// Compares the actuator against a constant target
+    pub fn constant(actuator_name: ActuatorName, tolerance: f32, target: f32) -> TargetComparator {
+        TargetComparator::new(actuator_name, tolerance, move |_| target)
+    }
+
+    /// Compares the actuator against a recorded trajectory,
+    /// which loops once every value has been replayed.  An
+    /// empty trajectory targets 0
+    pub fn replay(
+        actuator_name: ActuatorName,
+        tolerance: f32,
+        values: Vec<f32>,
+    ) -> TargetComparator {
+        TargetComparator::new(actuator_name, tolerance, move |cycle| {
+            match values.is_empty() {
+                true => 0.0,
+                false => values[(cycle % values.len() as u64) as usize],
+            }
+        })
+    }
+
+    /// Scales this comparator's contribution to the reward,
+    /// which is summed across every comparator
+    pub fn weight(mut self, weight: f32) -> TargetComparator {
+        self.weight = weight;
+        self
+    }
+
+    /// The error between the actuator's EMA and the target of cycle
+    pub fn error(&self, cycle: u64, ema: f32) -> f32 {
+        (ema - (self.target)(cycle)).abs()
+    }
+
+    /// The weighted reward contributed by an error
+    pub fn reward(&self, error: f32) -> f32 {
+        (1. - error / self.tolerance.max(f32::EPSILON))
+            .max(-1.)
+            .min(1.)
+            * self.weight
+    }
+}
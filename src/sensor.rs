@@ -1,13 +1,591 @@
+use std::borrow::Borrow;
+use std::cell::RefCell;
+use std::convert::Infallible;
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
 /// A sensor is any entity that can take
 /// a measurement.  The sensor is responsible
 /// for scaling this measurement so that is
-/// an analog value between 0 and 1
+/// an analog value between 0 and 1.  Sensors that
+/// can't promise this can be wrapped in an
+/// adapters::NormalizedSensor, which the encephalon
+/// builder does for every sensor by default
 pub trait Sensor {
-    /// Returns a value between 0.0 and 1.0
+    /// Returns a value between 0.0 and 1.0.  Encoders are
+    /// only defined over that range, and the encephalon counts
+    /// any measurement outside of it as a range violation
     fn measure(&mut self) -> f32;
 
     /// Gets the unique name of this sensor
     /// This is used to identify this sensor and
     /// form reflexes upon instantiation of the encephalon
     fn get_name(&self) -> String;
+
+    /// True if the last measurement was a fresh value, rather
+    /// than a repeat of a stale one.  Sensors fed from outside
+    /// (over a channel, say) override this so that the encephalon
+    /// can tell when it's running ahead of its inputs
+    fn is_fresh(&self) -> bool {
+        true
+    }
+}
+
+/// The unique name of a sensor, which is what reflexes,
+/// layouts, and the encephalon's by-name lookups refer to
+/// the sensor by.  Names convert from both &str and String,
+/// and compare, hash, and display as the underlying string:
+///
+/// ```
+/// use eywa::prelude::*;
+/// use std::collections::HashMap;
+///
+/// let light = SensorName::from("light");
+///
+/// assert_eq!(light, SensorName::from("light".to_string()));
+/// assert_eq!(light, "light".parse().unwrap());
+/// assert_eq!(light.to_string(), "light");
+///
+/// let mut periods = HashMap::new();
+/// periods.insert(light, 4);
+/// assert_eq!(periods.get("light"), Some(&4));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct SensorName(String);
+
+impl SensorName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for SensorName {
+    fn from(name: &str) -> SensorName {
+        SensorName(name.to_string())
+    }
+}
+
+impl From<String> for SensorName {
+    fn from(name: String) -> SensorName {
+        SensorName(name)
+    }
+}
+
+impl FromStr for SensorName {
+    type Err = Infallible;
+
+    fn from_str(name: &str) -> Result<SensorName, Infallible> {
+        Ok(SensorName::from(name))
+    }
+}
+
+impl Borrow<str> for SensorName {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for SensorName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A sensor that takes several measurements at once, like
+/// a camera or lidar.  Each channel of the measurement drives
+/// its own sensory neurons, just like a regular sensor
+pub trait MultiSensor {
+    /// Returns a value between 0.0 and 1.0 for each channel,
+    /// in the same order as channel_names
+    fn measure(&mut self) -> Vec<f32>;
+
+    /// Gets the unique name of each channel of this sensor
+    fn channel_names(&self) -> Vec<String>;
+}
+
+/// The measurement shared between the channels of a MultiSensor
+struct SharedMeasurement {
+    multi_sensor: Box<dyn MultiSensor>,
+    sample: Vec<f32>,
+    sample_count: u64,
+}
+
+/// A view of a single channel of a MultiSensor
+struct ChannelSensor {
+    shared: Rc<RefCell<SharedMeasurement>>,
+    channel: usize,
+    name: String,
+    last_sample: u64,
+}
+
+impl Sensor for ChannelSensor {
+    /// The first channel to be measured in a cycle takes a new sample
+    /// from the MultiSensor, and every other channel reads from that
+    /// same sample.  Since each channel is measured once per cycle,
+    /// a channel that has already read the current sample must be
+    /// starting the next cycle
+    fn measure(&mut self) -> f32 {
+        let mut shared = self.shared.borrow_mut();
+
+        if self.last_sample == shared.sample_count {
+            shared.sample = shared.multi_sensor.measure();
+            shared.sample_count += 1;
+        }
+
+        self.last_sample = shared.sample_count;
+        shared.sample.get(self.channel).copied().unwrap_or(0.0)
+    }
+
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// Splits a MultiSensor into a Sensor for each of its channels.
+/// The channels share one measurement of the MultiSensor per cycle
+///
+/// ```
+/// use eywa::prelude::*;
+///
+/// struct Ramp(f32);
+///
+/// impl MultiSensor for Ramp {
+///     fn measure(&mut self) -> Vec<f32> {
+///         self.0 += 0.1;
+///         vec![self.0, self.0 / 2.]
+///     }
+///
+///     fn channel_names(&self) -> Vec<String> {
+///         vec!["full".to_string(), "half".to_string()]
+///     }
+/// }
+///
+/// let channels = split_multi_sensor(Box::new(Ramp(0.)));
+///
+/// assert_eq!(channels[1].get_name(), "half");
+/// ```
+pub fn split_multi_sensor(multi_sensor: Box<dyn MultiSensor>) -> Vec<Box<dyn Sensor>> {
+    let names = multi_sensor.channel_names();
+
+    let shared = Rc::new(RefCell::new(SharedMeasurement {
+        multi_sensor,
+        sample: Vec::new(),
+        sample_count: 0,
+    }));
+
+    names
+        .into_iter()
+        .enumerate()
+        .map(|(channel, name)| {
+            Box::new(ChannelSensor {
+                shared: Rc::clone(&shared),
+                channel,
+                name,
+                last_sample: 0,
+            }) as Box<dyn Sensor>
+        })
+        .collect()
+}
+
+/// Sensors that wrap other sensors
+pub mod adapters {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    #[cfg(feature = "async")]
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex, PoisonError};
+
+    #[cfg(feature = "async")]
+    use tokio::sync::watch;
+
+    use super::Sensor;
+
+    /// How a NormalizedSensor brings measurements into [0, 1]
+    #[derive(Copy, Clone, Debug, PartialEq)]
+    pub enum Normalization {
+        /// Clamps measurements to [0, 1], so NaN becomes 0
+        Clamp,
+
+        /// Maps the running bounds of every measurement seen so far
+        /// onto [0, 1].  The bounds start at observed_min and
+        /// observed_max and widen to take in each new measurement
+        Rescale {
+            observed_min: f32,
+            observed_max: f32,
+        },
+
+        /// Replaces measurements outside of [0, 1] with fallback
+        Reject { fallback: f32 },
+    }
+
+    /// Wraps a sensor, normalizing each of its measurements into
+    /// [0, 1] and counting the measurements that weren't:
+    ///
+    /// ```
+    /// use eywa::sensor::adapters::{Normalization, NormalizedSensor};
+    /// use eywa::sensor::Sensor;
+    ///
+    /// fn clamped(sensor: Box<dyn Sensor>) -> NormalizedSensor {
+    ///     NormalizedSensor::new(sensor, Normalization::Clamp)
+    /// }
+    /// ```
+    pub struct NormalizedSensor {
+        sensor: Box<dyn Sensor>,
+        normalization: Normalization,
+        violations: u64,
+    }
+
+    impl NormalizedSensor {
+        pub fn new(sensor: Box<dyn Sensor>, normalization: Normalization) -> NormalizedSensor {
+            NormalizedSensor {
+                sensor,
+                normalization,
+                violations: 0,
+            }
+        }
+
+        /// The number of raw measurements that fell outside of [0, 1]
+        pub fn violations(&self) -> u64 {
+            self.violations
+        }
+
+        /// The normalization, including the running
+        /// bounds of a Rescale as they stand
+        pub fn normalization(&self) -> Normalization {
+            self.normalization
+        }
+    }
+
+    impl Sensor for NormalizedSensor {
+        fn measure(&mut self) -> f32 {
+            let raw = self.sensor.measure();
+
+            if !(0.0..=1.0).contains(&raw) {
+                self.violations += 1;
+            }
+
+            match &mut self.normalization {
+                Normalization::Clamp => raw.max(0.).min(1.),
+                Normalization::Rescale {
+                    observed_min,
+                    observed_max,
+                } => {
+                    if raw.is_nan() {
+                        return 0.0;
+                    }
+
+                    *observed_min = observed_min.min(raw);
+                    *observed_max = observed_max.max(raw);
+
+                    match *observed_max > *observed_min {
+                        true => (raw - *observed_min) / (*observed_max - *observed_min),
+                        false => 0.0,
+                    }
+                }
+                Normalization::Reject { fallback } => match (0.0..=1.0).contains(&raw) {
+                    true => raw,
+                    false => *fallback,
+                },
+            }
+        }
+
+        fn get_name(&self) -> String {
+            self.sensor.get_name()
+        }
+
+        fn is_fresh(&self) -> bool {
+            self.sensor.is_fresh()
+        }
+    }
+
+    /// The latest value set upon a LatestValueSensor, numbered
+    /// so the sensor can tell whether it has read it before
+    #[derive(Copy, Clone, Debug, Default, PartialEq)]
+    struct Reading {
+        value: Option<f32>,
+        version: u64,
+    }
+
+    /// Where a LatestValueSensor reads the latest value from
+    enum Source {
+        Shared(Arc<Mutex<Reading>>),
+        #[cfg(feature = "async")]
+        Watched(watch::Receiver<Reading>),
+    }
+
+    /// Where a LatestValueHandle sets the latest value
+    #[derive(Clone)]
+    enum Sink {
+        Shared(Arc<Mutex<Reading>>),
+        #[cfg(feature = "async")]
+        Watched(Arc<watch::Sender<Reading>>, Arc<AtomicU64>),
+    }
+
+    /// A sensor fed from outside the cycle loop (by a server, say)
+    /// through its LatestValueHandle.  Only the latest value set is
+    /// kept, so a producer running ahead of the brain never waits or
+    /// fails, and a brain running ahead of its producer measures the
+    /// same value again, which it reports as stale.  Until the first
+    /// value is set, the sensor measures its default:
+    ///
+    /// ```
+    /// use eywa::sensor::adapters::LatestValueSensor;
+    /// use eywa::sensor::Sensor;
+    ///
+    /// let (mut sensor, handle) = LatestValueSensor::new("light".to_string(), 0.5);
+    /// assert_eq!(sensor.measure(), 0.5);
+    ///
+    /// handle.set(0.8);
+    /// assert_eq!(sensor.measure(), 0.8);
+    /// ```
+    pub struct LatestValueSensor {
+        source: Source,
+        name: String,
+        default: f32,
+        last_version: u64,
+        fresh: bool,
+    }
+
+    impl LatestValueSensor {
+        /// A sensor whose handle can be set from any thread
+        pub fn new(name: String, default: f32) -> (LatestValueSensor, LatestValueHandle) {
+            let shared = Arc::new(Mutex::new(Reading::default()));
+
+            (
+                LatestValueSensor::reading(Source::Shared(Arc::clone(&shared)), name, default),
+                LatestValueHandle {
+                    sink: Sink::Shared(shared),
+                },
+            )
+        }
+
+        /// A sensor whose handle sets it through a tokio watch
+        /// channel, for producers that live in async tasks
+        #[cfg(feature = "async")]
+        pub fn watched(name: String, default: f32) -> (LatestValueSensor, LatestValueHandle) {
+            let (tx, rx) = watch::channel(Reading::default());
+
+            (
+                LatestValueSensor::reading(Source::Watched(rx), name, default),
+                LatestValueHandle {
+                    sink: Sink::Watched(Arc::new(tx), Arc::new(AtomicU64::new(0))),
+                },
+            )
+        }
+
+        fn reading(source: Source, name: String, default: f32) -> LatestValueSensor {
+            LatestValueSensor {
+                source,
+                name,
+                default,
+                last_version: 0,
+                fresh: false,
+            }
+        }
+    }
+
+    impl Sensor for LatestValueSensor {
+        fn measure(&mut self) -> f32 {
+            let reading = match &self.source {
+                Source::Shared(shared) => *shared.lock().unwrap_or_else(PoisonError::into_inner),
+                #[cfg(feature = "async")]
+                Source::Watched(rx) => *rx.borrow(),
+            };
+
+            self.fresh = reading.version > self.last_version;
+            self.last_version = self.last_version.max(reading.version);
+
+            reading.value.unwrap_or(self.default)
+        }
+
+        fn get_name(&self) -> String {
+            self.name.clone()
+        }
+
+        fn is_fresh(&self) -> bool {
+            self.fresh
+        }
+    }
+
+    /// The producer's end of a LatestValueSensor, which
+    /// can be cloned and handed to as many producers as needed
+    #[derive(Clone)]
+    pub struct LatestValueHandle {
+        sink: Sink,
+    }
+
+    impl LatestValueHandle {
+        /// Replaces the sensor's latest value.  Never waits on the
+        /// sensor and never fails, even once the sensor is dropped
+        pub fn set(&self, value: f32) {
+            match &self.sink {
+                Sink::Shared(shared) => {
+                    let mut reading = shared.lock().unwrap_or_else(PoisonError::into_inner);
+
+                    *reading = Reading {
+                        value: Some(value),
+                        version: reading.version + 1,
+                    };
+                }
+                #[cfg(feature = "async")]
+                Sink::Watched(tx, versions) => {
+                    let version = versions.fetch_add(1, Ordering::SeqCst) + 1;
+
+                    // Only errs once the sensor is gone, when
+                    // there's no one left to read the value anyway
+                    let _ = tx.broadcast(Reading {
+                        value: Some(value),
+                        version,
+                    });
+                }
+            }
+        }
+    }
+
+    /// A sensor that measures whatever value was last pushed into
+    /// it, for brains stepped from a simulation loop.  The builder
+    /// makes these from names (see EncephalonBuilder::injected_sensors)
+    /// and Encephalon::set_sensor_values pushes a frame of values
+    /// into them, so no Sensor need be written for each channel
+    pub struct InjectedSensor {
+        name: String,
+        value: Rc<Cell<f32>>,
+    }
+
+    impl InjectedSensor {
+        /// A sensor measuring 0 until a value is pushed into it
+        pub fn new(name: String) -> InjectedSensor {
+            InjectedSensor {
+                name,
+                value: Rc::new(Cell::new(0.0)),
+            }
+        }
+
+        /// The cell the sensor measures, through which values are pushed
+        pub fn value(&self) -> Rc<Cell<f32>> {
+            Rc::clone(&self.value)
+        }
+    }
+
+    impl Sensor for InjectedSensor {
+        fn measure(&mut self) -> f32 {
+            self.value.get()
+        }
+
+        fn get_name(&self) -> String {
+            self.name.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::adapters::{LatestValueSensor, Normalization, NormalizedSensor};
+    use super::*;
+    use std::thread;
+
+    struct Ramp(f32);
+
+    impl MultiSensor for Ramp {
+        fn measure(&mut self) -> Vec<f32> {
+            self.0 += 0.1;
+            vec![self.0, self.0 / 2.]
+        }
+
+        fn channel_names(&self) -> Vec<String> {
+            vec!["full".to_string(), "half".to_string()]
+        }
+    }
+
+    #[test]
+    fn split_channels_share_one_measurement() {
+        let mut channels = split_multi_sensor(Box::new(Ramp(0.)));
+
+        for _ in 0..3 {
+            let full = channels[0].measure();
+            let half = channels[1].measure();
+
+            assert_eq!(half, full / 2.);
+        }
+
+        assert!((channels[0].measure() - 0.4).abs() < 1e-6);
+    }
+
+    struct Replay(Vec<f32>);
+
+    impl Sensor for Replay {
+        fn measure(&mut self) -> f32 {
+            self.0.remove(0)
+        }
+
+        fn get_name(&self) -> String {
+            "replay".to_string()
+        }
+    }
+
+    /// Measures -0.5, 0.5 and 3.0 through normalization
+    fn normalized(normalization: Normalization) -> Vec<f32> {
+        let replay = Replay(vec![-0.5, 0.5, 3.0]);
+        let mut sensor = NormalizedSensor::new(Box::new(replay), normalization);
+        let measurements = (0..3).map(|_| sensor.measure()).collect();
+
+        assert_eq!(sensor.violations(), 2);
+        measurements
+    }
+
+    #[test]
+    fn clamping_pins_measurements_to_the_unit_range() {
+        assert_eq!(normalized(Normalization::Clamp), vec![0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn rescaling_stretches_the_observed_range() {
+        let rescale = Normalization::Rescale {
+            observed_min: 0.0,
+            observed_max: 1.0,
+        };
+
+        assert_eq!(normalized(rescale), vec![0.0, 0.6666667, 1.0]);
+    }
+
+    #[test]
+    fn rejection_falls_back_on_measurements_out_of_range() {
+        assert_eq!(
+            normalized(Normalization::Reject { fallback: 0.25 }),
+            vec![0.25, 0.5, 0.25]
+        );
+    }
+
+    #[test]
+    fn latest_values_never_go_back_across_threads() {
+        let (mut sensor, handle) = LatestValueSensor::new("light".to_string(), 0.5);
+
+        assert_eq!(sensor.measure(), 0.5);
+        assert!(!sensor.is_fresh());
+
+        let producer = thread::spawn(move || {
+            for value in 1..=10_000 {
+                handle.set(value as f32);
+            }
+        });
+
+        // Once set, the default never comes back, and
+        // later values are never followed by earlier ones
+        let mut last = 0.0;
+
+        while last < 10_000. {
+            let value = sensor.measure();
+
+            assert!(value >= last);
+            last = value;
+        }
+
+        producer.join().unwrap();
+
+        assert_eq!(sensor.measure(), 10_000.);
+        assert!(!sensor.is_fresh());
+    }
 }
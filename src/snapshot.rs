@@ -0,0 +1,612 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::EywaError;
+use crate::neuron::synapse::{SynapseRecord, SynapticType};
+
+/// The version of the snapshot format written by this
+/// version of eywa.  Bump this whenever a field is added
+/// to (or removed from) EncephalonSnapshot, and register a
+/// migration for the outgoing version in MIGRATIONS
+pub const CURRENT_VERSION: u32 = 8;
+
+/// Decodes a snapshot written at an older version and
+/// upgrades it to the current version
+pub type Migration = fn(&[u8], SnapshotFormat) -> Result<EncephalonSnapshot, EywaError>;
+
+/// Migrations keyed by the version they upgrade from.  Each
+/// migration should decode the bytes with a frozen copy of
+/// that version's snapshot struct and fill in defaults for
+/// any fields added since
+const MIGRATIONS: &[(u32, Migration)] = &[
+    (1, migrate_v1),
+    (2, migrate_v2),
+    (3, migrate_v3),
+    (4, migrate_v4),
+    (5, migrate_v5),
+    (6, migrate_v6),
+    (7, migrate_v7),
+];
+
+/// How a snapshot is encoded into bytes.  JSON is easy to
+/// read and diff while debugging, bincode is far more compact
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SnapshotFormat {
+    Json,
+    Bincode,
+}
+
+/// The state of a single neuron within a snapshot
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NeuronSnapshot {
+    pub loc: Vec<i32>,
+    pub ema: f32,
+}
+
+/// The state a neuron carries from one cycle to the next, beyond
+/// its EMA.  Neurons without some part of this state (ie sensory
+/// neurons, which hold no charge) leave it at its default
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NeuronDynamics {
+    pub loc: Vec<i32>,
+
+    /// The charge in flight to the neuron, waiting
+    /// on each of the cycles after the snapshot
+    pub pending_charge: Vec<f32>,
+
+    /// The shunting inhibition in flight to the neuron,
+    /// waiting on each of the cycles after the snapshot
+    pub pending_inhibition: Vec<f32>,
+
+    /// Whether the neuron fired on the last cycle before
+    /// the snapshot and each cycle before it, latest first
+    pub recent_fires: Vec<bool>,
+
+    pub burst_remaining: u32, //Cycles left to fire in the current burst
+    pub adaptation: f32,      //The neuron's raise of its fire threshold
+    pub input_gain: f32,      //As set by synaptic scaling
+    pub membrane: Option<(f32, f32)>, //Potential and recovery of Izhikevich neurons
+}
+
+impl NeuronDynamics {
+    /// The dynamics of a neuron at loc that has yet to run
+    pub fn new(loc: Vec<i32>) -> NeuronDynamics {
+        NeuronDynamics {
+            loc,
+            pending_charge: Vec::new(),
+            pending_inhibition: Vec::new(),
+            recent_fires: Vec::new(),
+            burst_remaining: 0,
+            adaptation: 0.0,
+            input_gain: 1.0,
+            membrane: None,
+        }
+    }
+}
+
+/// A plastic synapse within a snapshot, with all it
+/// needs to go on learning from where it left off
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PlasticSynapseSnapshot {
+    pub source: Vec<i32>,
+    pub target: Vec<i32>,
+    pub synaptic_type: SynapticType,
+    pub position: f32,           //Where the synapse lies along its strength curve
+    pub formed: u64,             //The cycle the synapse was formed on
+    pub last_fired: Option<u64>, //The last cycle the synapse fired on
+}
+
+/// A named group of neurons within a snapshot
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GroupSnapshot {
+    pub name: String,
+    pub locs: Vec<Vec<i32>>,
+}
+
+/// The position of an encephalon's random number generator
+/// within its stream, from which the generator can be resumed
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RngSnapshot {
+    pub seed: [u8; 32],
+    pub stream: u64,
+    pub word_pos: u128,
+}
+
+/// A persistable record of an encephalon's state, which can be
+/// restored into an encephalon built with the same geometry.
+///
+/// The version is always the first field, so that it can be read
+/// before the rest of the snapshot is decoded
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EncephalonSnapshot {
+    pub version: u32,
+    pub cycle_count: u64,
+    pub neurons: Vec<NeuronSnapshot>,
+    pub groups: Vec<GroupSnapshot>,
+    pub lesioned: Vec<Vec<i32>>,      //Locations of lesioned neurons
+    pub synapses: Vec<SynapseRecord>, //Recorded for analysis (see plastic_synapses)
+    pub rng: Option<RngSnapshot>,     //None leaves the generator as it is on restore
+    pub energy: Option<f32>,          //None for encephalons without an energy model
+
+    /// The dynamics of every neuron.  Empty leaves the
+    /// dynamics of the neurons as they are on restore
+    pub dynamics: Vec<NeuronDynamics>,
+
+    /// Every plastic synapse, in the order its neuron fires them.
+    /// None leaves the plastic synapses as they are on restore
+    pub plastic_synapses: Option<Vec<PlasticSynapseSnapshot>>,
+}
+
+/// Just enough of a snapshot to find its version
+#[derive(Deserialize)]
+struct VersionHeader {
+    version: u32,
+}
+
+/// The snapshot as of version 1, before neuron groups and lesions
+#[derive(Deserialize)]
+struct SnapshotV1 {
+    #[allow(dead_code)]
+    version: u32,
+    cycle_count: u64,
+    neurons: Vec<NeuronSnapshot>,
+}
+
+fn migrate_v1(bytes: &[u8], format: SnapshotFormat) -> Result<EncephalonSnapshot, EywaError> {
+    let snapshot: SnapshotV1 = decode(bytes, format)?;
+
+    Ok(EncephalonSnapshot::new(
+        snapshot.cycle_count,
+        snapshot.neurons,
+    ))
+}
+
+/// The snapshot as of version 2, before synapses were recorded
+#[derive(Deserialize)]
+struct SnapshotV2 {
+    #[allow(dead_code)]
+    version: u32,
+    cycle_count: u64,
+    neurons: Vec<NeuronSnapshot>,
+    groups: Vec<GroupSnapshot>,
+    lesioned: Vec<Vec<i32>>,
+}
+
+fn migrate_v2(bytes: &[u8], format: SnapshotFormat) -> Result<EncephalonSnapshot, EywaError> {
+    let snapshot: SnapshotV2 = decode(bytes, format)?;
+
+    Ok(EncephalonSnapshot {
+        groups: snapshot.groups,
+        lesioned: snapshot.lesioned,
+        ..EncephalonSnapshot::new(snapshot.cycle_count, snapshot.neurons)
+    })
+}
+
+/// The snapshot as of version 3, before the rng was recorded
+#[derive(Deserialize)]
+struct SnapshotV3 {
+    #[allow(dead_code)]
+    version: u32,
+    cycle_count: u64,
+    neurons: Vec<NeuronSnapshot>,
+    groups: Vec<GroupSnapshot>,
+    lesioned: Vec<Vec<i32>>,
+    synapses: Vec<SynapseRecord>,
+}
+
+fn migrate_v3(bytes: &[u8], format: SnapshotFormat) -> Result<EncephalonSnapshot, EywaError> {
+    let snapshot: SnapshotV3 = decode(bytes, format)?;
+
+    Ok(EncephalonSnapshot {
+        groups: snapshot.groups,
+        lesioned: snapshot.lesioned,
+        synapses: snapshot.synapses,
+        ..EncephalonSnapshot::new(snapshot.cycle_count, snapshot.neurons)
+    })
+}
+
+/// The snapshot as of version 4, before energy was recorded
+#[derive(Deserialize)]
+struct SnapshotV4 {
+    #[allow(dead_code)]
+    version: u32,
+    cycle_count: u64,
+    neurons: Vec<NeuronSnapshot>,
+    groups: Vec<GroupSnapshot>,
+    lesioned: Vec<Vec<i32>>,
+    synapses: Vec<SynapseRecord>,
+    rng: Option<RngSnapshot>,
+}
+
+fn migrate_v4(bytes: &[u8], format: SnapshotFormat) -> Result<EncephalonSnapshot, EywaError> {
+    let snapshot: SnapshotV4 = decode(bytes, format)?;
+
+    Ok(EncephalonSnapshot {
+        groups: snapshot.groups,
+        lesioned: snapshot.lesioned,
+        synapses: snapshot.synapses,
+        rng: snapshot.rng,
+        ..EncephalonSnapshot::new(snapshot.cycle_count, snapshot.neurons)
+    })
+}
+
+/// The snapshot as of version 5, before neuron dynamics were recorded
+#[derive(Deserialize)]
+struct SnapshotV5 {
+    #[allow(dead_code)]
+    version: u32,
+    cycle_count: u64,
+    neurons: Vec<NeuronSnapshot>,
+    groups: Vec<GroupSnapshot>,
+    lesioned: Vec<Vec<i32>>,
+    synapses: Vec<SynapseRecord>,
+    rng: Option<RngSnapshot>,
+    energy: Option<f32>,
+}
+
+fn migrate_v5(bytes: &[u8], format: SnapshotFormat) -> Result<EncephalonSnapshot, EywaError> {
+    let snapshot: SnapshotV5 = decode(bytes, format)?;
+
+    Ok(EncephalonSnapshot {
+        groups: snapshot.groups,
+        lesioned: snapshot.lesioned,
+        synapses: snapshot.synapses,
+        rng: snapshot.rng,
+        energy: snapshot.energy,
+        ..EncephalonSnapshot::new(snapshot.cycle_count, snapshot.neurons)
+    })
+}
+
+/// A neuron's dynamics as of version 6, before shunting inhibition
+#[derive(Deserialize)]
+struct NeuronDynamicsV6 {
+    loc: Vec<i32>,
+    pending_charge: Vec<f32>,
+    recent_fires: Vec<bool>,
+    burst_remaining: u32,
+    adaptation: f32,
+    input_gain: f32,
+    membrane: Option<(f32, f32)>,
+}
+
+/// The snapshot as of version 6, before pending shunting
+/// inhibition was recorded with each neuron's dynamics
+#[derive(Deserialize)]
+struct SnapshotV6 {
+    #[allow(dead_code)]
+    version: u32,
+    cycle_count: u64,
+    neurons: Vec<NeuronSnapshot>,
+    groups: Vec<GroupSnapshot>,
+    lesioned: Vec<Vec<i32>>,
+    synapses: Vec<SynapseRecord>,
+    rng: Option<RngSnapshot>,
+    energy: Option<f32>,
+    dynamics: Vec<NeuronDynamicsV6>,
+}
+
+fn migrate_v6(bytes: &[u8], format: SnapshotFormat) -> Result<EncephalonSnapshot, EywaError> {
+    let snapshot: SnapshotV6 = decode(bytes, format)?;
+
+    let dynamics = snapshot
+        .dynamics
+        .into_iter()
+        .map(|dynamics| NeuronDynamics {
+            pending_charge: dynamics.pending_charge,
+            recent_fires: dynamics.recent_fires,
+            burst_remaining: dynamics.burst_remaining,
+            adaptation: dynamics.adaptation,
+            input_gain: dynamics.input_gain,
+            membrane: dynamics.membrane,
+            ..NeuronDynamics::new(dynamics.loc)
+        })
+        .collect();
+
+    Ok(EncephalonSnapshot {
+        groups: snapshot.groups,
+        lesioned: snapshot.lesioned,
+        synapses: snapshot.synapses,
+        rng: snapshot.rng,
+        energy: snapshot.energy,
+        dynamics,
+        ..EncephalonSnapshot::new(snapshot.cycle_count, snapshot.neurons)
+    })
+}
+
+/// The snapshot as of version 7, before the plastic
+/// synapses were recorded in full to be restored
+#[derive(Deserialize)]
+struct SnapshotV7 {
+    #[allow(dead_code)]
+    version: u32,
+    cycle_count: u64,
+    neurons: Vec<NeuronSnapshot>,
+    groups: Vec<GroupSnapshot>,
+    lesioned: Vec<Vec<i32>>,
+    synapses: Vec<SynapseRecord>,
+    rng: Option<RngSnapshot>,
+    energy: Option<f32>,
+    dynamics: Vec<NeuronDynamics>,
+}
+
+fn migrate_v7(bytes: &[u8], format: SnapshotFormat) -> Result<EncephalonSnapshot, EywaError> {
+    let snapshot: SnapshotV7 = decode(bytes, format)?;
+
+    Ok(EncephalonSnapshot {
+        groups: snapshot.groups,
+        lesioned: snapshot.lesioned,
+        synapses: snapshot.synapses,
+        rng: snapshot.rng,
+        energy: snapshot.energy,
+        dynamics: snapshot.dynamics,
+        ..EncephalonSnapshot::new(snapshot.cycle_count, snapshot.neurons)
+    })
+}
+
+impl EncephalonSnapshot {
+    /// Makes a snapshot at the current version, without any
+    /// neuron groups, lesions, synapses, rng position, energy
+    /// or neuron dynamics
+    pub fn new(cycle_count: u64, neurons: Vec<NeuronSnapshot>) -> EncephalonSnapshot {
+        EncephalonSnapshot {
+            version: CURRENT_VERSION,
+            cycle_count,
+            neurons,
+            groups: Vec::new(),
+            lesioned: Vec::new(),
+            synapses: Vec::new(),
+            rng: None,
+            energy: None,
+            dynamics: Vec::new(),
+            plastic_synapses: None,
+        }
+    }
+
+    /// Encodes the snapshot in the given format
+    pub fn to_bytes(&self, format: SnapshotFormat) -> Result<Vec<u8>, EywaError> {
+        match format {
+            SnapshotFormat::Json => serde_json::to_vec_pretty(self)
+                .map_err(|e| EywaError::SnapshotEncoding(e.to_string())),
+            SnapshotFormat::Bincode => {
+                bincode::serialize(self).map_err(|e| EywaError::SnapshotEncoding(e.to_string()))
+            }
+        }
+    }
+
+    /// Decodes a snapshot encoded in the given format.  Snapshots
+    /// from older versions are migrated up to the current version,
+    /// while snapshots from newer versions are rejected
+    ///
+    /// ```
+    /// # use eywa::snapshot::*;
+    /// let snapshot = EncephalonSnapshot::new(42, Vec::new());
+    /// let bytes = snapshot.to_bytes(SnapshotFormat::Bincode).unwrap();
+    ///
+    /// assert_eq!(
+    ///     EncephalonSnapshot::from_bytes(&bytes, SnapshotFormat::Bincode).unwrap(),
+    ///     snapshot
+    /// );
+    /// ```
+    pub fn from_bytes(
+        bytes: &[u8],
+        format: SnapshotFormat,
+    ) -> Result<EncephalonSnapshot, EywaError> {
+        let header: VersionHeader = decode(bytes, format)?;
+
+        if header.version == CURRENT_VERSION {
+            return decode(bytes, format);
+        }
+
+        match MIGRATIONS
+            .iter()
+            .find(|(version, _)| *version == header.version)
+        {
+            Some((_, migration)) => migration(bytes, format),
+            None => Err(EywaError::SnapshotVersionUnsupported {
+                found: header.version,
+                supported: CURRENT_VERSION,
+            }),
+        }
+    }
+}
+
+/// Decodes bytes in the given format into any deserializable type,
+/// which is what migrations use to read older snapshot versions
+pub fn decode<'a, T: Deserialize<'a>>(
+    bytes: &'a [u8],
+    format: SnapshotFormat,
+) -> Result<T, EywaError> {
+    match format {
+        SnapshotFormat::Json => {
+            serde_json::from_slice(bytes).map_err(|e| EywaError::SnapshotEncoding(e.to_string()))
+        }
+        SnapshotFormat::Bincode => {
+            bincode::deserialize(bytes).map_err(|e| EywaError::SnapshotEncoding(e.to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMATS: [SnapshotFormat; 2] = [SnapshotFormat::Json, SnapshotFormat::Bincode];
+
+    /// A snapshot with every field set to something other than its default
+    fn full_snapshot() -> EncephalonSnapshot {
+        let loc = vec![1, 2, 3];
+
+        EncephalonSnapshot {
+            groups: vec![GroupSnapshot {
+                name: "eyes".to_string(),
+                locs: vec![loc.clone()],
+            }],
+            lesioned: vec![vec![0, 0, 0]],
+            synapses: vec![SynapseRecord {
+                source: vec![0, 0, 0],
+                target: loc.clone(),
+                strength: 2.0,
+                normalized_strength: Some(0.25),
+                synaptic_type: SynapticType::Inhibitory,
+                plastic: true,
+            }],
+            rng: Some(RngSnapshot {
+                seed: [7; 32],
+                stream: 1,
+                word_pos: 99,
+            }),
+            energy: Some(3.5),
+            dynamics: vec![NeuronDynamics {
+                pending_charge: vec![1.0, 0.5],
+                pending_inhibition: vec![0.25],
+                recent_fires: vec![true, false],
+                burst_remaining: 2,
+                adaptation: 0.5,
+                input_gain: 1.5,
+                membrane: Some((-65.0, -13.0)),
+                ..NeuronDynamics::new(loc.clone())
+            }],
+            plastic_synapses: Some(vec![PlasticSynapseSnapshot {
+                source: vec![0, 0, 0],
+                target: loc.clone(),
+                synaptic_type: SynapticType::Excitatory,
+                position: 0.5,
+                formed: 3,
+                last_fired: Some(40),
+            }]),
+            ..EncephalonSnapshot::new(42, vec![NeuronSnapshot { loc, ema: 0.5 }])
+        }
+    }
+
+    /// The full snapshot as JSON written at an older version, which
+    /// is just the fields that version had, and the snapshot that it
+    /// should migrate to
+    fn written_at(version: u32) -> (Vec<u8>, EncephalonSnapshot) {
+        let mut expected = full_snapshot();
+        // Through bytes, since a json value can't hold the rng's u128
+        let bytes = serde_json::to_vec(&expected).unwrap();
+        let mut json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        let fields = json.as_object_mut().unwrap();
+
+        fields.insert("version".to_string(), version.into());
+
+        if version < 8 {
+            fields.remove("plastic_synapses");
+            expected.plastic_synapses = None;
+        }
+
+        if version < 7 {
+            for dynamics in fields["dynamics"].as_array_mut().unwrap() {
+                dynamics
+                    .as_object_mut()
+                    .unwrap()
+                    .remove("pending_inhibition");
+            }
+
+            for dynamics in expected.dynamics.iter_mut() {
+                dynamics.pending_inhibition = Vec::new();
+            }
+        }
+
+        if version < 6 {
+            fields.remove("dynamics");
+            expected.dynamics = Vec::new();
+        }
+
+        if version < 5 {
+            fields.remove("energy");
+            expected.energy = None;
+        }
+
+        if version < 4 {
+            fields.remove("rng");
+            expected.rng = None;
+        }
+
+        if version < 3 {
+            fields.remove("synapses");
+            expected.synapses = Vec::new();
+        }
+
+        if version < 2 {
+            fields.remove("groups");
+            fields.remove("lesioned");
+            expected.groups = Vec::new();
+            expected.lesioned = Vec::new();
+        }
+
+        (serde_json::to_vec(&json).unwrap(), expected)
+    }
+
+    #[test]
+    fn current_snapshots_round_trip() {
+        let snapshot = full_snapshot();
+
+        for format in FORMATS.iter() {
+            let bytes = snapshot.to_bytes(*format).unwrap();
+
+            assert_eq!(
+                EncephalonSnapshot::from_bytes(&bytes, *format).unwrap(),
+                snapshot
+            );
+        }
+    }
+
+    #[test]
+    fn every_older_version_migrates_to_the_current_one() {
+        assert_eq!(MIGRATIONS.len() as u32, CURRENT_VERSION - 1);
+
+        for version in 1..CURRENT_VERSION {
+            let (bytes, expected) = written_at(version);
+
+            assert_eq!(
+                EncephalonSnapshot::from_bytes(&bytes, SnapshotFormat::Json).unwrap(),
+                expected,
+                "migrating from version {}",
+                version
+            );
+        }
+    }
+
+    #[test]
+    fn checked_in_v1_fixtures_migrate() {
+        let fixtures: [(&[u8], SnapshotFormat); 2] = [
+            (
+                include_bytes!("../tests/fixtures/snapshot_v1.json"),
+                SnapshotFormat::Json,
+            ),
+            (
+                include_bytes!("../tests/fixtures/snapshot_v1.bin"),
+                SnapshotFormat::Bincode,
+            ),
+        ];
+
+        for (bytes, format) in fixtures.iter() {
+            let snapshot = EncephalonSnapshot::from_bytes(bytes, *format).unwrap();
+
+            assert_eq!(snapshot.version, CURRENT_VERSION);
+            assert_eq!(snapshot.cycle_count, 42);
+            assert_eq!(snapshot.neurons.len(), 3);
+            assert_eq!(snapshot.neurons[1].loc, vec![1, 2, 3]);
+            assert_eq!(snapshot.neurons[1].ema, 0.5);
+            assert!(snapshot.groups.is_empty());
+            assert!(snapshot.dynamics.is_empty());
+            assert_eq!(snapshot.plastic_synapses, None);
+        }
+    }
+
+    #[test]
+    fn future_versions_are_rejected() {
+        let mut future = EncephalonSnapshot::new(0, Vec::new());
+        future.version = CURRENT_VERSION + 1;
+
+        for format in FORMATS.iter() {
+            let bytes = future.to_bytes(*format).unwrap();
+
+            assert!(matches!(
+                EncephalonSnapshot::from_bytes(&bytes, *format),
+                Err(EywaError::SnapshotVersionUnsupported { found, supported })
+                    if found == CURRENT_VERSION + 1 && supported == CURRENT_VERSION
+            ));
+        }
+    }
+}
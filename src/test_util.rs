@@ -0,0 +1,91 @@
+//! Sensors, actuators and brains shared by the unit tests
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use crate::actuator::{Actuator, FnActuator};
+use crate::encephalon::{Encephalon, Reflex};
+use crate::neuron::synapse::SynapticType;
+use crate::probe::ProbeId;
+use crate::sensor::Sensor;
+
+/// A sensor that always measures the same value
+pub struct ConstantSensor {
+    pub name: String,
+    pub value: f32,
+}
+
+impl Sensor for ConstantSensor {
+    fn measure(&mut self) -> f32 {
+        self.value
+    }
+
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+}
+
+/// A sensor named name that always measures value
+pub fn sensor(name: &str, value: f32) -> Box<dyn Sensor> {
+    Box::new(ConstantSensor {
+        name: name.to_string(),
+        value,
+    })
+}
+
+/// A sensor named name that measures whatever the
+/// returned cell is set to, starting at value
+pub fn settable_sensor(name: &str, value: f32) -> (Box<dyn Sensor>, Rc<Cell<f32>>) {
+    struct SettableSensor(String, Rc<Cell<f32>>);
+
+    impl Sensor for SettableSensor {
+        fn measure(&mut self) -> f32 {
+            self.1.get()
+        }
+
+        fn get_name(&self) -> String {
+            self.0.clone()
+        }
+    }
+
+    let value = Rc::new(Cell::new(value));
+    let sensor = SettableSensor(name.to_string(), Rc::clone(&value));
+
+    (Box::new(sensor), value)
+}
+
+/// An actuator named name that ignores what it's sent
+pub fn actuator(name: &str) -> Box<dyn Actuator> {
+    Box::new(FnActuator::new(name.to_string(), |_| {}))
+}
+
+/// Whether the neuron probe samples fired on each cycle it traced
+pub fn fired_trace(encephalon: &Encephalon, probe: ProbeId) -> Vec<bool> {
+    encephalon
+        .probe_trace(probe)
+        .unwrap()
+        .iter()
+        .map(|sample| sample.fired)
+        .collect()
+}
+
+/// An excitatory reflex of the given strength
+pub fn reflex(sensor: &str, actuator: &str, strength: f32) -> Reflex {
+    Reflex::new(
+        sensor.into(),
+        actuator.into(),
+        SynapticType::Excitatory,
+        strength,
+    )
+}
+
+/// An actuator named name that records every value it's sent
+pub fn recording_actuator(name: &str) -> (Box<dyn Actuator>, Rc<RefCell<Vec<f32>>>) {
+    let sent = Rc::new(RefCell::new(Vec::new()));
+    let recorded = Rc::clone(&sent);
+    let actuator = FnActuator::new(name.to_string(), move |value| {
+        recorded.borrow_mut().push(value)
+    });
+
+    (Box::new(actuator), sent)
+}
@@ -0,0 +1,71 @@
+//! A wasm-bindgen wrapper around an encephalon, so that small
+//! brains can be run client-side.  Sensors are set from js
+//! and actuators are read back after each step:
+//!
+//! ```js
+//! const brain = new WasmBrain(JSON.stringify({
+//!     sensors: ["light"],
+//!     actuators: ["motor"],
+//!     reflexes: [{ sensor: "light", actuator: "motor", synapse_type: "Excitatory", strength: 20 }],
+//! }));
+//!
+//! brain.set_sensor("light", 0.5);
+//! brain.step();
+//! console.log(brain.actuator("motor"));
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+use crate::brain_spec::{BrainSpec, SpecBrain};
+use crate::error::EywaError;
+
+/// A SpecBrain whose sensors and actuators are set and read from js
+#[wasm_bindgen]
+pub struct WasmBrain {
+    brain: SpecBrain,
+}
+
+impl WasmBrain {
+    /// The brain wrapped by this WasmBrain
+    pub fn brain(&self) -> &SpecBrain {
+        &self.brain
+    }
+}
+
+#[wasm_bindgen]
+impl WasmBrain {
+    /// Builds a brain from a json encoded BrainSpec
+    #[wasm_bindgen(constructor)]
+    pub fn new(config_json: &str) -> Result<WasmBrain, JsValue> {
+        BrainSpec::from_json(config_json)
+            .and_then(SpecBrain::new)
+            .map(|brain| WasmBrain { brain })
+            .map_err(to_js)
+    }
+
+    /// Sets the value the named sensor measures from the
+    /// next step on.  Values are clamped into [0, 1]
+    pub fn set_sensor(&self, name: &str, value: f32) -> Result<(), JsValue> {
+        self.brain.set_sensor(name, value).map_err(to_js)
+    }
+
+    /// Runs a single cycle of the encephalon
+    pub fn step(&mut self) {
+        self.brain.step(1);
+    }
+
+    /// The last control value handed to the named
+    /// actuator, or NaN if there's no such actuator
+    pub fn actuator(&self, name: &str) -> f32 {
+        self.brain.actuator(name).unwrap_or(f32::NAN)
+    }
+
+    /// Number of cycles run so far
+    pub fn cycle_count(&self) -> f64 {
+        self.brain.encephalon().get_cycle_count() as f64
+    }
+}
+
+fn to_js(e: EywaError) -> JsValue {
+    JsValue::from_str(&e.to_string())
+}
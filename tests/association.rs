@@ -0,0 +1,20 @@
+use eywa::bench::association_task;
+
+/// Running the task twice for each of the seeds takes several seconds
+/// in a debug build, so this is run with `cargo test --release -- --ignored`
+#[test]
+#[ignore]
+fn association_learns() {
+    for seed in 0..4 {
+        let report = association_task(seed, 1_000);
+
+        // Frozen, the brain wins pattern A and loses pattern B
+        assert_eq!(report.accuracy_untrained, 0.5, "{}", report);
+        assert!(report.accuracy_late >= 0.9, "{}", report);
+        assert!(
+            report.accuracy_late - report.accuracy_untrained >= 0.4,
+            "{}",
+            report
+        );
+    }
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "env")]
+
+use eywa::env::MazeEnv;
+use eywa::prelude::*;
+
+/// A seeded brain with the hell-mazer reflexes learns
+/// to bump into the walls of the maze less often
+#[test]
+fn maze_collisions_fall_with_training() {
+    let maze = [
+        "#########",
+        "#S......#",
+        "#.#####.#",
+        "#.......#",
+        "#########",
+    ]
+    .join("\n");
+
+    let env = MazeEnv::new(&maze, 7).unwrap();
+
+    let encephalon = EncephalonBuilder::new()
+        .seed(7)
+        .max_plastic_synapses(26)
+        .sensory_encoder(|m| sensory_encoders::linear_period_encoder(m, 1, 20))
+        .reflexes(MazeEnv::reflexes(20.))
+        .build(
+            Box::new(BoxEcp::new(27, 8, 4, 27)),
+            env.sensors(),
+            env.actuators(),
+        )
+        .unwrap();
+
+    encephalon.run_n_cycles(1000);
+    let early = env.collisions();
+
+    encephalon.run_n_cycles(4000);
+    let before_late = env.collisions();
+
+    encephalon.run_n_cycles(1000);
+    let late = env.collisions() - before_late;
+
+    assert!(
+        late < early,
+        "{} late collisions against {} early",
+        late,
+        early
+    );
+}
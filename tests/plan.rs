@@ -0,0 +1,40 @@
+use eywa::prelude::*;
+
+use std::fs;
+
+#[path = "../src/bin/hell_mazer/config.rs"]
+mod config;
+
+const FIXTURE: &str = "tests/fixtures/hell_mazer_plan.json";
+
+/// Any change to how the hell-mazer server's brain is planned, be it
+/// to the server's config or to the builder's defaults, shows up as a
+/// diff of the fixture.  When the change is intended, rewrite the
+/// fixture with EYWA_BLESS_PLAN=1 and check in the diff
+#[test]
+fn hell_mazer_plan_matches_fixture() {
+    let sensor_names: Vec<String> = config::SENSOR_NAMES
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+    let actuator_names: Vec<String> = config::ACTUATOR_NAMES
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+
+    let plan = config::builder()
+        .plan(&config::ecp_geometry(), &sensor_names, &actuator_names)
+        .unwrap();
+
+    if std::env::var_os("EYWA_BLESS_PLAN").is_some() {
+        let json = serde_json::to_string_pretty(&plan).unwrap();
+        fs::write(FIXTURE, json + "\n").unwrap();
+    }
+
+    let fixture: BrainPlan = serde_json::from_str(&fs::read_to_string(FIXTURE).unwrap()).unwrap();
+
+    assert_eq!(
+        plan, fixture,
+        "rerun with EYWA_BLESS_PLAN=1 if the change is intended"
+    );
+}
@@ -0,0 +1,65 @@
+use eywa::prelude::*;
+
+struct Light;
+
+impl Sensor for Light {
+    fn measure(&mut self) -> f32 {
+        1.0
+    }
+
+    fn get_name(&self) -> String {
+        "light".to_string()
+    }
+}
+
+/// The mean distance of the motor's ema from 0.3 over the last
+/// quarter of a long run, with or without reward modulation.
+///
+/// The light fires its sensory neuron every cycle, and under the
+/// default polarity policy that neuron's synapses are inhibitory,
+/// so with the default threshold of 10 the box never lights up past
+/// the sensory plane and the motor is silent either way.  With a
+/// threshold of 5 the unrewarded box saturates, motor and all, so
+/// there's something for the reward to pull back toward the target
+fn late_error(reward_modulation: bool) -> f32 {
+    let cycles = 5_000;
+    let encephalon = EncephalonBuilder::new()
+        .seed(31)
+        .fire_threshold(5.)
+        .reward_modulation(reward_modulation)
+        .target_comparator(TargetComparator::constant("motor".into(), 0.2, 0.3))
+        .build(
+            Box::new(BoxEcp::new(343, 1, 1, 27)),
+            vec![Box::new(Light)],
+            vec![Box::new(FnActuator::new("motor".to_string(), |_| {}))],
+        )
+        .unwrap();
+
+    let mut total = 0.0;
+
+    for cycle in 0..cycles {
+        let metrics = encephalon.run_cycle();
+
+        if cycle >= cycles * 3 / 4 {
+            total += metrics.comparator_errors[0].1;
+        }
+    }
+
+    total / (cycles / 4) as f32
+}
+
+/// Learning takes far longer than the rest of the tests, so this
+/// is run with `cargo test --release -- --ignored`
+#[test]
+#[ignore]
+fn comparator_reward_pulls_actuator_toward_target() {
+    let rewarded = late_error(true);
+    let control = late_error(false);
+
+    assert!(
+        rewarded < control,
+        "rewarded error {} against control error {}",
+        rewarded,
+        control
+    );
+}